@@ -0,0 +1,111 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use cylinder::Signer;
+use grid_sdk::client::batch::{BatchClient, DeadLetterAction, DeadLetterBatch};
+use protobuf::Message;
+use sawtooth_sdk::messages::batch::BatchList;
+
+use crate::error::CliError;
+use crate::transaction::resign_batch_list;
+
+pub fn display_dead_letter_batches(batches: &[DeadLetterBatch]) {
+    // Minimum length of the batch ID column
+    let mut id_width: usize = "BATCH ID".len();
+    // Minimum length of the data change ID column
+    let mut dcid_width: usize = "DATA CHANGE ID".len();
+    batches.iter().for_each(|batch| {
+        if batch.id.len() > id_width {
+            id_width = batch.id.len();
+        }
+        if let Some(dcid) = &batch.data_change_id {
+            if dcid.len() > dcid_width {
+                dcid_width = dcid.len();
+            }
+        }
+    });
+    println!(
+        "{:<width_id$} {:<width_dcid$} REASON",
+        "BATCH ID",
+        "DATA CHANGE ID",
+        width_id = id_width,
+        width_dcid = dcid_width,
+    );
+    batches.iter().for_each(|batch| {
+        println!(
+            "{:<width_id$} {:<width_dcid$} {}",
+            batch.id,
+            batch.data_change_id.as_deref().unwrap_or("-"),
+            batch.reason,
+            width_id = id_width,
+            width_dcid = dcid_width,
+        )
+    });
+}
+
+pub fn display_dead_letter_action(action: &DeadLetterAction) {
+    match &action.replacement_batch_id {
+        Some(replacement) => println!(
+            "{} {} by {}, replaced by batch {}",
+            action.batch_id, action.action, action.actor, replacement
+        ),
+        None => println!("{} {} by {}", action.batch_id, action.action, action.actor),
+    }
+}
+
+pub fn do_list_dead_letter_batches(
+    client: Box<dyn BatchClient>,
+    service_id: Option<&str>,
+) -> Result<(), CliError> {
+    let batches = client.list_dead_letter_batches(service_id)?;
+    display_dead_letter_batches(&batches);
+    Ok(())
+}
+
+/// Replays a dead-lettered batch
+///
+/// If a signer is given, the batch is re-signed with fresh nonces before it is sent back to
+/// griddle, which replaces the original batch with the re-signed copy.
+pub fn do_replay_dead_letter_batch(
+    client: Box<dyn BatchClient>,
+    id: &str,
+    service_id: Option<&str>,
+    signer: Option<Box<dyn Signer>>,
+) -> Result<(), CliError> {
+    let action = match signer {
+        Some(signer) => {
+            let batch = client
+                .list_dead_letter_batches(service_id)?
+                .into_iter()
+                .find(|batch| batch.id == id || batch.data_change_id.as_deref() == Some(id))
+                .ok_or_else(|| CliError::UserError(format!("Batch {} is not dead-lettered", id)))?;
+            let batch_list = BatchList::parse_from_bytes(&batch.serialized_batch)?;
+            let replacement = resign_batch_list(&batch_list, &*signer)?;
+            client.replay_dead_letter_batch(&batch.id, service_id, Some(&replacement))?
+        }
+        None => client.replay_dead_letter_batch(id, service_id, None)?,
+    };
+    display_dead_letter_action(&action);
+    Ok(())
+}
+
+pub fn do_discard_dead_letter_batch(
+    client: Box<dyn BatchClient>,
+    id: &str,
+    service_id: Option<&str>,
+) -> Result<(), CliError> {
+    let action = client.discard_dead_letter_batch(id, service_id)?;
+    display_dead_letter_action(&action);
+    Ok(())
+}
@@ -15,10 +15,17 @@
 #[cfg(feature = "sqlite")]
 mod sqlite;
 
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+use diesel::connection::Connection as _;
 #[cfg(feature = "postgres")]
-use diesel::{connection::Connection as _, pg::PgConnection};
+use diesel::pg::PgConnection;
+#[cfg(feature = "sqlite")]
+use diesel::sqlite::SqliteConnection;
 use std::str::FromStr;
 
+use grid_sdk::commits::store::IndexedNamespace;
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+use grid_sdk::commits::{store::DieselConnectionCommitStore, CommitStore};
 #[cfg(feature = "postgres")]
 use grid_sdk::migrations::run_postgres_migrations;
 
@@ -52,6 +59,64 @@ pub fn run_migrations(database_url: &str) -> Result<(), CliError> {
     Ok(())
 }
 
+/// Rewinds the given namespaces to a commit height
+///
+/// The namespaces are rebuilt from the commit events that the Grid daemon requests again once it
+/// is restarted.
+// Allow unused_variables here because the arguments are not used if no database is configured
+#[allow(unused_variables)]
+pub fn reindex(
+    database_url: &str,
+    commit_num: i64,
+    namespaces: &[IndexedNamespace],
+) -> Result<(), CliError> {
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
+    match ConnectionUri::from_str(database_url).map_err(CliError::ActionError)? {
+        #[cfg(feature = "postgres")]
+        ConnectionUri::Postgres(database_url) => {
+            let connection = PgConnection::establish(&database_url).map_err(|err| {
+                CliError::ActionError(format!(
+                    "Failed to establish database connection to '{}': {}",
+                    database_url, err
+                ))
+            })?;
+
+            DieselConnectionCommitStore::new(&connection)
+                .reindex(commit_num, namespaces)
+                .map_err(|err| {
+                    CliError::ActionError(format!("Unable to reindex database: {}", err))
+                })?;
+        }
+        #[cfg(feature = "sqlite")]
+        ConnectionUri::Sqlite(connection_string) => {
+            let connection = SqliteConnection::establish(&connection_string).map_err(|err| {
+                CliError::ActionError(format!(
+                    "Failed to establish database connection to '{}': {}",
+                    connection_string, err
+                ))
+            })?;
+
+            DieselConnectionCommitStore::new(&connection)
+                .reindex(commit_num, namespaces)
+                .map_err(|err| {
+                    CliError::ActionError(format!("Unable to reindex database: {}", err))
+                })?;
+        }
+    }
+
+    info!(
+        "Rewound {} to commit {}; restart the Grid daemon to rebuild them",
+        namespaces
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        commit_num
+    );
+
+    Ok(())
+}
+
 /// The possible connection types and identifiers passed to the migrate command
 pub enum ConnectionUri {
     #[cfg(feature = "postgres")]
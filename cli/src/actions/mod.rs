@@ -34,6 +34,8 @@ use super::error::CliError;
 
 #[cfg(feature = "pike")]
 pub mod agent;
+#[cfg(feature = "batch")]
+pub mod batch;
 #[cfg(feature = "database")]
 pub mod database;
 pub mod keygen;
@@ -45,6 +47,8 @@ pub mod organization;
 pub mod product;
 #[cfg(any(feature = "purchase-order"))]
 pub mod purchase_order;
+#[cfg(feature = "track-and-trace")]
+pub mod record;
 #[cfg(feature = "pike")]
 pub mod role;
 #[cfg(feature = "schema")]
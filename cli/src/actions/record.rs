@@ -0,0 +1,829 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cylinder::Signer;
+use grid_sdk::client::schema::{DataType, PropertyDefinition, SchemaClient};
+use grid_sdk::client::track_and_trace::TrackAndTraceClient;
+use grid_sdk::location::addressing::GRID_LOCATION_NAMESPACE;
+use grid_sdk::pike::addressing::GRID_PIKE_NAMESPACE;
+use grid_sdk::product::addressing::GRID_PRODUCT_NAMESPACE;
+use grid_sdk::protocol::schema::state::{
+    DataType as StateDataType, LatLongBuilder, PropertyValue, PropertyValueBuilder,
+};
+use grid_sdk::protocol::track_and_trace::payload::{
+    Action, CreateRecordAction, CreateRecordActionBuilder, CreateRecordActionsBuilder,
+    FinalizeRecordAction, FinalizeRecordActionsBuilder, TrackAndTracePayloadBuilder,
+};
+use grid_sdk::protos::IntoProto;
+use grid_sdk::schema::addressing::GRID_SCHEMA_NAMESPACE;
+use grid_sdk::track_and_trace::addressing::TRACK_AND_TRACE_NAMESPACE;
+
+use crate::error::CliError;
+use crate::transaction::track_and_trace_batch_builder;
+
+const RECORD_ID_COLUMN: &str = "record_id";
+const PRODUCT_ID_COLUMN: &str = "product_id";
+const LOCATION_ID_COLUMN: &str = "location_id";
+
+/// The outcome of submitting a single record in a bulk operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RecordOutcome {
+    record_id: String,
+    status: String,
+    message: Option<String>,
+}
+
+/**
+ * Create many records, skipping records which already exist
+ *
+ * client - Client used to check for existing records and submit the batches
+ * signer - Signer for the agent
+ * wait - Time in seconds to wait for each group of batches to be committed
+ * batch_size - Number of records to submit at a time
+ * actions - The actions creating the records
+ * service_id - The service ID to submit the records to, if running on Splinter
+ */
+pub fn do_create_records(
+    client: Box<dyn TrackAndTraceClient>,
+    signer: Box<dyn Signer>,
+    wait: u64,
+    batch_size: usize,
+    actions: Vec<CreateRecordAction>,
+    service_id: Option<&str>,
+) -> Result<(), CliError> {
+    let mut outcomes = Vec::new();
+    let mut to_submit = Vec::new();
+    for action in actions {
+        if client
+            .get_record(action.record_id().to_string(), service_id)?
+            .is_some()
+        {
+            outcomes.push(RecordOutcome {
+                record_id: action.record_id().to_string(),
+                status: "Skipped".to_string(),
+                message: Some("Record already exists".to_string()),
+            });
+        } else {
+            to_submit.push((action.record_id().to_string(), Action::CreateRecord(action)));
+        }
+    }
+
+    outcomes.extend(submit_payloads(
+        &*client, signer, wait, batch_size, to_submit, service_id,
+    )?);
+
+    display_record_outcomes(&outcomes);
+    check_record_outcomes(&outcomes)
+}
+
+/**
+ * Finalize many records, skipping records which do not exist or are already final
+ *
+ * client - Client used to check the records and submit the batches
+ * signer - Signer for the agent
+ * wait - Time in seconds to wait for each group of batches to be committed
+ * batch_size - Number of records to submit at a time
+ * actions - The actions finalizing the records
+ * service_id - The service ID to submit the records to, if running on Splinter
+ */
+pub fn do_finalize_records(
+    client: Box<dyn TrackAndTraceClient>,
+    signer: Box<dyn Signer>,
+    wait: u64,
+    batch_size: usize,
+    actions: Vec<FinalizeRecordAction>,
+    service_id: Option<&str>,
+) -> Result<(), CliError> {
+    let mut outcomes = Vec::new();
+    let mut to_submit = Vec::new();
+    for action in actions {
+        let record = client.get_record(action.record_id().to_string(), service_id)?;
+        match record.map(|record| record.r#final) {
+            None => outcomes.push(RecordOutcome {
+                record_id: action.record_id().to_string(),
+                status: "Skipped".to_string(),
+                message: Some("Record does not exist".to_string()),
+            }),
+            Some(true) => outcomes.push(RecordOutcome {
+                record_id: action.record_id().to_string(),
+                status: "Skipped".to_string(),
+                message: Some("Record is already final".to_string()),
+            }),
+            Some(false) => to_submit.push((
+                action.record_id().to_string(),
+                Action::FinalizeRecord(action),
+            )),
+        }
+    }
+
+    outcomes.extend(submit_payloads(
+        &*client, signer, wait, batch_size, to_submit, service_id,
+    )?);
+
+    display_record_outcomes(&outcomes);
+    check_record_outcomes(&outcomes)
+}
+
+/// Submits each action in its own batch, `batch_size` batches at a time, and returns the
+/// outcome for each record
+fn submit_payloads(
+    client: &dyn TrackAndTraceClient,
+    signer: Box<dyn Signer>,
+    wait: u64,
+    batch_size: usize,
+    actions: Vec<(String, Action)>,
+    service_id: Option<&str>,
+) -> Result<Vec<RecordOutcome>, CliError> {
+    if batch_size == 0 {
+        return Err(CliError::UserError(
+            "Batch size must be greater than 0".to_string(),
+        ));
+    }
+
+    let mut outcomes = Vec::new();
+
+    for chunk in actions.chunks(batch_size) {
+        let mut builder = track_and_trace_batch_builder(signer.clone());
+
+        for (_, action) in chunk {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .map_err(|err| CliError::PayloadError(format!("{}", err)))?;
+
+            let payload = TrackAndTracePayloadBuilder::new()
+                .with_action(action.clone())
+                .with_timestamp(timestamp)
+                .build()
+                .map_err(|err| CliError::PayloadError(format!("{}", err)))?;
+
+            builder.add_transaction(
+                &payload.into_proto()?,
+                &[
+                    TRACK_AND_TRACE_NAMESPACE.to_string(),
+                    GRID_PIKE_NAMESPACE.to_string(),
+                    GRID_SCHEMA_NAMESPACE.to_string(),
+                    GRID_PRODUCT_NAMESPACE.to_string(),
+                    GRID_LOCATION_NAMESPACE.to_string(),
+                ],
+                &[TRACK_AND_TRACE_NAMESPACE.to_string()],
+            )?;
+        }
+
+        let batch_list = builder.create_batch_list();
+
+        // Each batch holds a single record's transaction, so the batch IDs identify the records
+        let batch_ids = batch_list
+            .get_batches()
+            .iter()
+            .zip(chunk.iter())
+            .map(|(batch, (record_id, _))| {
+                (batch.get_header_signature().to_string(), record_id.clone())
+            })
+            .collect::<HashMap<_, _>>();
+
+        info!("Submitting {} records...", chunk.len());
+        match client.submit_batches(wait, &batch_list, service_id) {
+            Ok(batch_statuses) => {
+                let mut reported = HashSet::new();
+                for batch_status in batch_statuses {
+                    if let Some(record_id) = batch_ids.get(&batch_status.id) {
+                        reported.insert(record_id.clone());
+                        outcomes.push(RecordOutcome {
+                            record_id: record_id.clone(),
+                            message: batch_status.invalid_transactions.first().cloned(),
+                            status: batch_status.status,
+                        });
+                    }
+                }
+                for (record_id, _) in chunk {
+                    if !reported.contains(record_id) {
+                        outcomes.push(RecordOutcome {
+                            record_id: record_id.clone(),
+                            status: "Unknown".to_string(),
+                            message: Some("No batch status was returned".to_string()),
+                        });
+                    }
+                }
+            }
+            Err(err) => {
+                for (record_id, _) in chunk {
+                    outcomes.push(RecordOutcome {
+                        record_id: record_id.clone(),
+                        status: "Failed".to_string(),
+                        message: Some(err.to_string()),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(outcomes)
+}
+
+fn display_record_outcomes(outcomes: &[RecordOutcome]) {
+    let id_length = outcomes
+        .iter()
+        .map(|outcome| outcome.record_id.len())
+        .chain(std::iter::once("RECORD ID".len()))
+        .max()
+        .unwrap_or_default();
+    // The longest status is "Committed"
+    const STATUS_LENGTH: usize = "COMMITTED".len();
+    println!(
+        "{:<length_id$} {:<length_status$} MESSAGE",
+        "RECORD ID",
+        "STATUS",
+        length_id = id_length,
+        length_status = STATUS_LENGTH,
+    );
+    outcomes.iter().for_each(|outcome| {
+        println!(
+            "{:<length_id$} {:<length_status$} {}",
+            outcome.record_id,
+            outcome.status,
+            outcome.message.as_deref().unwrap_or(""),
+            length_id = id_length,
+            length_status = STATUS_LENGTH,
+        )
+    });
+}
+
+/// Returns an error if any record was not accepted. Skipped records are not errors, so that a
+/// partially applied file may be submitted again.
+fn check_record_outcomes(outcomes: &[RecordOutcome]) -> Result<(), CliError> {
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| outcome.status == "Invalid" || outcome.status == "Failed")
+        .count();
+
+    if failed > 0 {
+        Err(CliError::ActionError(format!(
+            "{} of {} records were not accepted",
+            failed,
+            outcomes.len()
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/**
+ * Build the actions creating records from a CSV file. The file must have a header row with a
+ * `record_id` column, and may have `product_id` and `location_id` columns. Every other column is
+ * a property of the schema.
+ *
+ * path - Path to the CSV file
+ * schema_name - Name of the schema of the records
+ * client - Client used to fetch the schema's property definitions
+ * service_id - The service ID to fetch the schema from, if running on Splinter
+ */
+pub fn create_record_payloads_from_file(
+    path: &str,
+    schema_name: &str,
+    client: Box<dyn SchemaClient>,
+    service_id: Option<&str>,
+) -> Result<Vec<CreateRecordAction>, CliError> {
+    let rows = read_csv_file(path)?;
+    let schema = client.get_schema(schema_name.to_string(), service_id)?;
+    create_record_payloads(rows, schema_name, schema.properties)
+}
+
+/**
+ * Build the actions finalizing records from a CSV file with a `record_id` column. Other columns
+ * are ignored, so the file used to create the records may be reused.
+ *
+ * path - Path to the CSV file
+ */
+pub fn finalize_record_payloads_from_file(
+    path: &str,
+) -> Result<Vec<FinalizeRecordAction>, CliError> {
+    let rows = read_csv_file(path)?;
+    finalize_record_payloads(rows)
+}
+
+fn create_record_payloads(
+    rows: Vec<HashMap<String, String>>,
+    schema_name: &str,
+    definitions: Vec<PropertyDefinition>,
+) -> Result<Vec<CreateRecordAction>, CliError> {
+    let mut builder = CreateRecordActionsBuilder::new().with_schema(schema_name.to_string());
+
+    for (line, mut row) in rows.into_iter().enumerate() {
+        let record_id = row
+            .remove(RECORD_ID_COLUMN)
+            .filter(|record_id| !record_id.is_empty())
+            .ok_or_else(|| {
+                CliError::PayloadError(format!("Row {} is missing a record_id", line + 1))
+            })?;
+        let product_id = row.remove(PRODUCT_ID_COLUMN).unwrap_or_default();
+        let location_id = row.remove(LOCATION_ID_COLUMN).unwrap_or_default();
+
+        if let Some(column) = row
+            .keys()
+            .find(|column| !definitions.iter().any(|def| &def.name == *column))
+        {
+            return Err(CliError::PayloadError(format!(
+                "Column {} is not a property of schema {}",
+                column, schema_name
+            )));
+        }
+
+        let properties = csv_to_property_values(&row, &definitions)
+            .map_err(|err| CliError::PayloadError(format!("Record {}: {}", record_id, err)))?;
+
+        builder = builder.with_record(
+            CreateRecordActionBuilder::new()
+                .with_record_id(record_id)
+                .with_properties(properties)
+                .with_product_id(product_id)
+                .with_location_id(location_id),
+        );
+    }
+
+    builder
+        .build()
+        .map_err(|err| CliError::PayloadError(format!("{}", err)))
+}
+
+fn finalize_record_payloads(
+    rows: Vec<HashMap<String, String>>,
+) -> Result<Vec<FinalizeRecordAction>, CliError> {
+    let mut builder = FinalizeRecordActionsBuilder::new();
+
+    for (line, mut row) in rows.into_iter().enumerate() {
+        let record_id = row
+            .remove(RECORD_ID_COLUMN)
+            .filter(|record_id| !record_id.is_empty())
+            .ok_or_else(|| {
+                CliError::PayloadError(format!("Row {} is missing a record_id", line + 1))
+            })?;
+        builder = builder.with_record_id(record_id);
+    }
+
+    builder
+        .build()
+        .map_err(|err| CliError::PayloadError(format!("{}", err)))
+}
+
+fn csv_to_property_values(
+    row: &HashMap<String, String>,
+    definitions: &[PropertyDefinition],
+) -> Result<Vec<PropertyValue>, CliError> {
+    let mut property_values = Vec::new();
+
+    for def in definitions {
+        let value = match row.get(&def.name) {
+            Some(value) if !value.is_empty() => value,
+            _ if !def.required => continue,
+            _ => {
+                return Err(CliError::PayloadError(format!(
+                    "Field {} not found",
+                    def.name
+                )))
+            }
+        };
+
+        let builder = PropertyValueBuilder::new().with_name(def.name.clone());
+
+        let builder = match def.data_type {
+            DataType::Boolean => builder
+                .with_data_type(StateDataType::Boolean)
+                .with_boolean_value(value.parse().map_err(|err| {
+                    CliError::PayloadError(format!("{} is not a boolean: {}", def.name, err))
+                })?),
+            DataType::Number => builder
+                .with_data_type(StateDataType::Number)
+                .with_number_value(value.parse().map_err(|err| {
+                    CliError::PayloadError(format!("{} is not a number: {}", def.name, err))
+                })?),
+            DataType::String => builder
+                .with_data_type(StateDataType::String)
+                .with_string_value(value.to_string()),
+            DataType::Enum => {
+                let enum_value = match def.enum_options.iter().position(|opt| opt == value) {
+                    Some(index) => index as u32,
+                    None => value.parse().map_err(|_| {
+                        CliError::PayloadError(format!(
+                            "{} is not an option of {}",
+                            value, def.name
+                        ))
+                    })?,
+                };
+                builder
+                    .with_data_type(StateDataType::Enum)
+                    .with_enum_value(enum_value)
+            }
+            DataType::LatLong => {
+                let lat_long = value
+                    .split(',')
+                    .map(|x| {
+                        x.trim()
+                            .parse::<i64>()
+                            .map_err(|err| CliError::PayloadError(format!("{}", err)))
+                    })
+                    .collect::<Result<Vec<i64>, CliError>>()?;
+
+                if lat_long.len() != 2 {
+                    return Err(CliError::PayloadError(format!(
+                        "{:?} is not a valid latitude longitude",
+                        lat_long
+                    )));
+                }
+
+                let lat_long = LatLongBuilder::new()
+                    .with_lat_long(lat_long[0], lat_long[1])
+                    .build()
+                    .map_err(|err| CliError::PayloadError(format!("{}", err)))?;
+
+                builder
+                    .with_data_type(StateDataType::LatLong)
+                    .with_lat_long_value(lat_long)
+            }
+            DataType::Bytes | DataType::Struct => {
+                return Err(CliError::PayloadError(format!(
+                    "{} is a {:?} property, which cannot be set from a CSV file",
+                    def.name, def.data_type
+                )))
+            }
+        };
+
+        property_values.push(
+            builder
+                .build()
+                .map_err(|err| CliError::PayloadError(format!("{}", err)))?,
+        );
+    }
+
+    Ok(property_values)
+}
+
+/// Reads a CSV file into one map of column name to value per row
+fn read_csv_file(path: &str) -> Result<Vec<HashMap<String, String>>, CliError> {
+    let file = File::open(path)?;
+    let mut lines = BufReader::new(file).lines();
+
+    let header = match lines.next() {
+        Some(line) => parse_csv_line(&line?)?,
+        None => return Err(CliError::UserError(format!("{} is empty", path))),
+    };
+
+    let mut rows = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(&line)?;
+        if fields.len() != header.len() {
+            return Err(CliError::UserError(format!(
+                "Line {} of {} has {} fields, expected {}",
+                index + 2,
+                path,
+                fields.len(),
+                header.len()
+            )));
+        }
+
+        rows.push(header.iter().cloned().zip(fields).collect());
+    }
+
+    Ok(rows)
+}
+
+/// Splits a line of a CSV file into its fields. Fields may be quoted to contain commas, and
+/// quotes are escaped within a quoted field by doubling them.
+fn parse_csv_line(line: &str) -> Result<Vec<String>, CliError> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(CliError::UserError(format!(
+            "Unterminated quoted field: {}",
+            line
+        )));
+    }
+
+    fields.push(field);
+    Ok(fields.into_iter().map(|f| f.trim().to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use cylinder::{secp256k1::Secp256k1Context, Context};
+    use grid_sdk::client::track_and_trace::Record;
+    use grid_sdk::client::{BatchStatus, Client};
+    use grid_sdk::error::ClientError;
+    use sawtooth_sdk::messages::batch::BatchList;
+
+    /// The number of records in a page of the REST API's record list
+    const PAGE_SIZE: usize = 10;
+
+    /// A client holding the given records, which keeps the IDs of the batches submitted to it
+    struct MockTrackAndTraceClient {
+        records: Vec<(String, bool)>,
+        submitted: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl MockTrackAndTraceClient {
+        fn record(&self, record_id: &str, r#final: bool) -> Record {
+            Record {
+                record_id: record_id.to_string(),
+                schema: "serialized_item".to_string(),
+                owner: "owner".to_string(),
+                custodian: "custodian".to_string(),
+                r#final,
+                product_id: None,
+                location_id: None,
+                service_id: None,
+            }
+        }
+    }
+
+    impl Client for MockTrackAndTraceClient {
+        fn post_batches(
+            &self,
+            _wait: u64,
+            _batch_list: &BatchList,
+            _service_id: Option<&str>,
+        ) -> Result<(), ClientError> {
+            Ok(())
+        }
+    }
+
+    impl TrackAndTraceClient for MockTrackAndTraceClient {
+        fn get_record(
+            &self,
+            id: String,
+            _service_id: Option<&str>,
+        ) -> Result<Option<Record>, ClientError> {
+            Ok(self
+                .records
+                .iter()
+                .find(|(record_id, _)| record_id == &id)
+                .map(|(record_id, r#final)| self.record(record_id, *r#final)))
+        }
+
+        /// Returns the first page of records only
+        fn list_records(&self, _service_id: Option<&str>) -> Result<Vec<Record>, ClientError> {
+            Ok(self
+                .records
+                .iter()
+                .take(PAGE_SIZE)
+                .map(|(record_id, r#final)| self.record(record_id, *r#final))
+                .collect())
+        }
+
+        fn submit_batches(
+            &self,
+            _wait: u64,
+            batch_list: &BatchList,
+            _service_id: Option<&str>,
+        ) -> Result<Vec<BatchStatus>, ClientError> {
+            Ok(batch_list
+                .get_batches()
+                .iter()
+                .map(|batch| {
+                    let id = batch.get_header_signature().to_string();
+                    self.submitted.borrow_mut().push(id.clone());
+                    BatchStatus {
+                        id,
+                        status: "Committed".to_string(),
+                        invalid_transactions: vec![],
+                    }
+                })
+                .collect())
+        }
+    }
+
+    /// Returns a client holding more than one page of records, the last of which is final
+    fn mock_client(submitted: Rc<RefCell<Vec<String>>>) -> Box<dyn TrackAndTraceClient> {
+        let count = PAGE_SIZE * 2 + 5;
+        Box::new(MockTrackAndTraceClient {
+            records: (0..count)
+                .map(|i| (format!("unit-{}", i), i == count - 1))
+                .collect(),
+            submitted,
+        })
+    }
+
+    fn signer() -> Box<dyn Signer> {
+        let context = Secp256k1Context::new();
+        context.new_signer(context.new_random_private_key())
+    }
+
+    fn definition(name: &str, data_type: DataType, required: bool) -> PropertyDefinition {
+        PropertyDefinition {
+            name: name.to_string(),
+            schema_name: "serialized_item".to_string(),
+            data_type,
+            required,
+            description: "".to_string(),
+            number_exponent: 0,
+            enum_options: vec!["new".to_string(), "used".to_string()],
+            struct_properties: vec![],
+        }
+    }
+
+    fn row(values: &[(&str, &str)]) -> HashMap<String, String> {
+        values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    /// Validate CSV lines are split into fields, handling quoted fields
+    fn test_parse_csv_line() {
+        assert_eq!(
+            parse_csv_line("unit-1,\"10,20\",\"say \"\"hi\"\"\"").unwrap(),
+            vec!["unit-1", "10,20", "say \"hi\""]
+        );
+        assert_eq!(parse_csv_line("a,,c").unwrap(), vec!["a", "", "c"]);
+        assert!(parse_csv_line("a,\"b").is_err());
+    }
+
+    #[test]
+    /// Validate rows are converted into "create record" actions with typed property values
+    fn test_create_record_payloads() {
+        let definitions = vec![
+            definition("weight", DataType::Number, true),
+            definition("condition", DataType::Enum, false),
+            definition("position", DataType::LatLong, false),
+        ];
+
+        let actions = create_record_payloads(
+            vec![
+                row(&[
+                    ("record_id", "unit-1"),
+                    ("product_id", "00012345600012"),
+                    ("weight", "42"),
+                    ("condition", "used"),
+                    ("position", "10,20"),
+                ]),
+                row(&[
+                    ("record_id", "unit-2"),
+                    ("product_id", ""),
+                    ("weight", "7"),
+                    ("condition", ""),
+                    ("position", ""),
+                ]),
+            ],
+            "serialized_item",
+            definitions,
+        )
+        .unwrap();
+
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].record_id(), "unit-1");
+        assert_eq!(actions[0].schema(), "serialized_item");
+        assert_eq!(actions[0].product_id(), "00012345600012");
+        assert_eq!(actions[0].properties().len(), 3);
+        assert_eq!(*actions[0].properties()[1].enum_value(), 1);
+        assert_eq!(actions[1].record_id(), "unit-2");
+        assert_eq!(actions[1].product_id(), "");
+        assert_eq!(actions[1].properties().len(), 1);
+    }
+
+    #[test]
+    /// Validate building actions fails on duplicate record IDs, missing required properties and
+    /// unknown columns
+    fn test_create_record_payloads_invalid() {
+        let definitions = || vec![definition("weight", DataType::Number, true)];
+
+        assert!(create_record_payloads(
+            vec![
+                row(&[("record_id", "unit-1"), ("weight", "1")]),
+                row(&[("record_id", "unit-1"), ("weight", "2")]),
+            ],
+            "serialized_item",
+            definitions(),
+        )
+        .is_err());
+
+        assert!(create_record_payloads(
+            vec![row(&[("record_id", "unit-1"), ("weight", "")])],
+            "serialized_item",
+            definitions(),
+        )
+        .is_err());
+
+        assert!(create_record_payloads(
+            vec![row(&[
+                ("record_id", "unit-1"),
+                ("weight", "1"),
+                ("color", "red")
+            ])],
+            "serialized_item",
+            definitions(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    /// Validate rows are converted into "finalize record" actions, ignoring other columns
+    fn test_finalize_record_payloads() {
+        let actions = finalize_record_payloads(vec![
+            row(&[("record_id", "unit-1"), ("weight", "1")]),
+            row(&[("record_id", "unit-2"), ("weight", "2")]),
+        ])
+        .unwrap();
+
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].record_id(), "unit-1");
+        assert_eq!(actions[1].record_id(), "unit-2");
+    }
+
+    #[test]
+    /// Validate records which already exist are skipped when creating records, including those
+    /// past the first page of the record list
+    fn test_create_records_skips_existing() {
+        let submitted = Rc::new(RefCell::new(Vec::new()));
+        let actions = (0..PAGE_SIZE * 2 + 6)
+            .map(|i| {
+                CreateRecordActionBuilder::new()
+                    .with_record_id(format!("unit-{}", i))
+                    .with_schema("serialized_item".to_string())
+                    .with_properties(vec![])
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+
+        do_create_records(
+            mock_client(submitted.clone()),
+            signer(),
+            0,
+            10,
+            actions,
+            None,
+        )
+        .unwrap();
+
+        // Only the one record which does not exist is submitted
+        assert_eq!(submitted.borrow().len(), 1);
+    }
+
+    #[test]
+    /// Validate records which do not exist or are already final are skipped when finalizing
+    /// records, and that records past the first page of the record list are finalized
+    fn test_finalize_records_skips_missing_and_final() {
+        let submitted = Rc::new(RefCell::new(Vec::new()));
+        let actions = finalize_record_payloads(vec![
+            row(&[("record_id", "unit-15")]),
+            row(&[("record_id", "unit-24")]),
+            row(&[("record_id", "unit-30")]),
+        ])
+        .unwrap();
+
+        do_finalize_records(
+            mock_client(submitted.clone()),
+            signer(),
+            0,
+            10,
+            actions,
+            None,
+        )
+        .unwrap();
+
+        // unit-24 is already final and unit-30 does not exist
+        assert_eq!(submitted.borrow().len(), 1);
+    }
+}
@@ -86,3 +86,12 @@ fn load_private_key(key_name: Option<String>) -> Result<PrivateKey, CliError> {
 pub fn load_signer(key_name: Option<String>) -> Result<Box<dyn Signer>, CliError> {
     Ok(Secp256k1Context::new().new_signer(load_private_key(key_name)?))
 }
+
+/// Creates the value of an `Authorization` header identifying the given signer
+#[cfg(feature = "batch")]
+pub fn create_authorization(signer: &dyn Signer) -> Result<String, CliError> {
+    let jwt = cylinder::jwt::JsonWebTokenBuilder::new()
+        .build(signer)
+        .map_err(|err| CliError::ActionError(format!("Unable to create authorization: {}", err)))?;
+    Ok(format!("Bearer Cylinder:{}", jwt))
+}
@@ -15,6 +15,8 @@
 //! Contains functions which assist with the creation of Batches and
 //! Transactions
 
+#[cfg(feature = "batch")]
+use std::collections::HashMap;
 use std::time::Instant;
 
 use crypto::digest::Digest;
@@ -58,6 +60,11 @@ const GRID_PURCHASE_ORDER_FAMILY_NAME: &str = "grid_purchase_order";
 #[cfg(feature = "purchase-order")]
 const GRID_PURCHASE_ORDER_FAMILY_VERSION: &str = "2";
 
+#[cfg(feature = "track-and-trace")]
+const GRID_TRACK_AND_TRACE_FAMILY_NAME: &str = "grid_track_and_trace";
+#[cfg(feature = "track-and-trace")]
+const GRID_TRACK_AND_TRACE_FAMILY_VERSION: &str = "2";
+
 const SABRE_FAMILY_NAME: &str = "sabre";
 const SABRE_FAMILY_VERSION: &str = "0.5";
 const SABRE_NAMESPACE_REGISTRY_PREFIX: &str = "00ec00";
@@ -101,6 +108,15 @@ pub fn location_batch_builder(signer: Box<dyn Signer>) -> BatchBuilder {
     )
 }
 
+#[cfg(feature = "track-and-trace")]
+pub fn track_and_trace_batch_builder(signer: Box<dyn Signer>) -> BatchBuilder {
+    BatchBuilder::new(
+        GRID_TRACK_AND_TRACE_FAMILY_NAME,
+        GRID_TRACK_AND_TRACE_FAMILY_VERSION,
+        signer,
+    )
+}
+
 #[derive(Clone)]
 pub struct BatchBuilder {
     family_name: String,
@@ -243,6 +259,93 @@ impl BatchBuilder {
     }
 }
 
+/// Re-signs the batches in a `BatchList` with the given signer
+///
+/// Every transaction keeps its payload, inputs, outputs and family, but receives a fresh nonce
+/// and is signed and batched by the given signer, so the resulting batches and transactions have
+/// new IDs. Dependencies on transactions in the list are changed to the new IDs of those
+/// transactions, while dependencies on other transactions are kept.
+///
+/// # Arguments
+///
+/// * `batch_list` - the batches to re-sign
+/// * `signer` - the signer for the new transactions and batches
+#[cfg(feature = "batch")]
+pub fn resign_batch_list(
+    batch_list: &BatchList,
+    signer: &dyn Signer,
+) -> Result<BatchList, CliError> {
+    let public_key = signer
+        .public_key()
+        .map_err(|err| CliError::ActionError(err.to_string()))?;
+
+    // The new ID of each re-signed transaction, by its original ID
+    let mut resigned_ids = HashMap::new();
+
+    let mut batches = Vec::new();
+    for original_batch in batch_list.get_batches() {
+        let mut txns = Vec::new();
+        for original_txn in original_batch.get_transactions() {
+            let mut txn_header = TransactionHeader::parse_from_bytes(original_txn.get_header())?;
+
+            let dependencies = txn_header
+                .get_dependencies()
+                .iter()
+                .map(|id| resigned_ids.get(id).cloned().unwrap_or_else(|| id.clone()))
+                .collect::<Vec<String>>();
+            txn_header.set_dependencies(protobuf::RepeatedField::from_vec(dependencies));
+
+            let mut nonce = create_nonce();
+            while nonce == txn_header.get_nonce() {
+                nonce = create_nonce();
+            }
+            txn_header.set_nonce(nonce);
+            txn_header.set_signer_public_key(public_key.as_hex());
+            txn_header.set_batcher_public_key(public_key.as_hex());
+
+            let txn_header_bytes = txn_header.write_to_bytes()?;
+            let header_signature = signer
+                .sign(&txn_header_bytes)
+                .map_err(|err| CliError::ActionError(err.to_string()))?;
+
+            resigned_ids.insert(
+                original_txn.get_header_signature().to_string(),
+                header_signature.as_hex(),
+            );
+
+            let mut txn = Transaction::new();
+            txn.set_header(txn_header_bytes);
+            txn.set_header_signature(header_signature.as_hex());
+            txn.set_payload(original_txn.get_payload().to_vec());
+            txns.push(txn);
+        }
+
+        let mut batch_header = BatchHeader::new();
+        batch_header.set_transaction_ids(protobuf::RepeatedField::from_vec(
+            txns.iter()
+                .map(|txn| txn.header_signature.clone())
+                .collect(),
+        ));
+        batch_header.set_signer_public_key(public_key.as_hex());
+
+        let batch_header_bytes = batch_header.write_to_bytes()?;
+        let batch_header_signature = signer
+            .sign(&batch_header_bytes)
+            .map_err(|err| CliError::ActionError(err.to_string()))?;
+
+        let mut batch = Batch::new();
+        batch.set_header(batch_header_bytes);
+        batch.set_header_signature(batch_header_signature.as_hex());
+        batch.set_transactions(protobuf::RepeatedField::from_vec(txns));
+        batches.push(batch);
+    }
+
+    let mut resigned = BatchList::new();
+    resigned.set_batches(protobuf::RepeatedField::from_vec(batches));
+
+    Ok(resigned)
+}
+
 /// Creates a nonce appropriate for a TransactionHeader
 fn create_nonce() -> String {
     let elapsed = Instant::now().elapsed();
@@ -318,3 +421,65 @@ fn compute_namespace_registry_address(namespace: &str) -> Result<String, CliErro
 
     Ok(String::from(SABRE_NAMESPACE_REGISTRY_PREFIX) + &bytes_to_hex_str(hash)[..64])
 }
+
+#[cfg(all(test, feature = "batch"))]
+mod tests {
+    use super::*;
+
+    use cylinder::{secp256k1::Secp256k1Context, Context};
+
+    /// Returns a transaction signed by the given signer, depending on the given transactions
+    fn transaction(signer: &dyn Signer, nonce: &str, dependencies: &[&str]) -> Transaction {
+        let mut txn_header = TransactionHeader::new();
+        txn_header.set_family_name("test_family".to_string());
+        txn_header.set_family_version("0.1".to_string());
+        txn_header.set_nonce(nonce.to_string());
+        txn_header.set_dependencies(protobuf::RepeatedField::from_vec(
+            dependencies.iter().map(|id| id.to_string()).collect(),
+        ));
+        let txn_header_bytes = txn_header.write_to_bytes().unwrap();
+
+        let mut txn = Transaction::new();
+        txn.set_header_signature(signer.sign(&txn_header_bytes).unwrap().as_hex());
+        txn.set_header(txn_header_bytes);
+        txn
+    }
+
+    fn dependencies(txn: &Transaction) -> Vec<String> {
+        TransactionHeader::parse_from_bytes(txn.get_header())
+            .unwrap()
+            .get_dependencies()
+            .to_vec()
+    }
+
+    #[test]
+    /// Validate that dependencies on re-signed transactions are changed to their new IDs, while
+    /// dependencies on other transactions are kept
+    fn test_resign_batch_list_remaps_dependencies() {
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+
+        let first = transaction(&*signer, "1", &["committed"]);
+        let second = transaction(&*signer, "2", &[first.get_header_signature()]);
+
+        let mut first_batch = Batch::new();
+        first_batch.set_transactions(protobuf::RepeatedField::from_vec(vec![first]));
+        let mut second_batch = Batch::new();
+        second_batch.set_transactions(protobuf::RepeatedField::from_vec(vec![second]));
+        let mut batch_list = BatchList::new();
+        batch_list.set_batches(protobuf::RepeatedField::from_vec(vec![
+            first_batch,
+            second_batch,
+        ]));
+
+        let resigned = resign_batch_list(&batch_list, &*signer).unwrap();
+        let first = &resigned.get_batches()[0].get_transactions()[0];
+        let second = &resigned.get_batches()[1].get_transactions()[0];
+
+        assert_eq!(dependencies(first), vec!["committed".to_string()]);
+        assert_eq!(
+            dependencies(second),
+            vec![first.get_header_signature().to_string()]
+        );
+    }
+}
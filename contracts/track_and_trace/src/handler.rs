@@ -36,14 +36,14 @@ use grid_sdk::{
         schema::state::{PropertyDefinition, PropertyValue},
         track_and_trace::{
             payload::{
-                Action, AnswerProposalAction, CreateProposalAction, CreateRecordAction,
-                FinalizeRecordAction, Response, RevokeReporterAction, TrackAndTracePayload,
-                UpdatePropertiesAction,
+                Action, AnswerProposalAction, CancelStaleProposalsAction, CreateProposalAction,
+                CreateRecordAction, FinalizeRecordAction, Response, RevokeReporterAction,
+                TrackAndTracePayload, UpdatePropertiesAction, UpdateRecordLocationAction,
             },
             state::{
-                AssociatedAgentBuilder, PropertyBuilder, PropertyPageBuilder, ProposalBuilder,
-                ProposalListBuilder, RecordBuilder, ReportedValueBuilder, ReporterBuilder, Role,
-                Status,
+                AssociatedAgentBuilder, PropertyBuilder, PropertyPageBuilder, Proposal,
+                ProposalBuilder, ProposalListBuilder, RecordBuilder, ReportedValueBuilder,
+                ReporterBuilder, Role, Status,
             },
         },
     },
@@ -53,7 +53,7 @@ use grid_sdk::{
 };
 
 use crate::payload::validate_payload;
-use crate::state::TrackAndTraceState;
+use crate::state::{TrackAndTraceState, BLOCK_INFO_NAMESPACE};
 
 const PROPERTY_PAGE_MAX_LENGTH: usize = 256;
 
@@ -73,6 +73,7 @@ impl TrackAndTraceTransactionHandler {
                 TRACK_AND_TRACE_NAMESPACE.to_string(),
                 GRID_PIKE_NAMESPACE.to_string(),
                 GRID_NAMESPACE.to_string(),
+                BLOCK_INFO_NAMESPACE.to_string(),
             ],
         }
     }
@@ -112,6 +113,22 @@ impl TrackAndTraceTransactionHandler {
             }
         };
 
+        let product_id = payload.product_id();
+        if !product_id.is_empty() && state.get_product(product_id)?.is_none() {
+            return Err(ApplyError::InvalidTransaction(format!(
+                "Product does not exist: {}",
+                product_id
+            )));
+        }
+
+        let location_id = payload.location_id();
+        if !location_id.is_empty() && state.get_location(location_id)?.is_none() {
+            return Err(ApplyError::InvalidTransaction(format!(
+                "Location does not exist: {}",
+                location_id
+            )));
+        }
+
         let mut type_schemata: HashMap<&str, PropertyDefinition> = HashMap::new();
         let mut required_properties: HashMap<&str, PropertyDefinition> = HashMap::new();
         let mut provided_properties: HashMap<&str, PropertyValue> = HashMap::new();
@@ -166,6 +183,8 @@ impl TrackAndTraceTransactionHandler {
             .with_field_final(false)
             .with_owners(vec![owner.clone()])
             .with_custodians(vec![owner])
+            .with_product_id(product_id.to_string())
+            .with_location_id(location_id.to_string())
             .build()
             .map_err(|err| map_builder_error_to_apply_error(err, "Record"))?;
 
@@ -428,6 +447,30 @@ impl TrackAndTraceTransactionHandler {
         let role = payload.role();
         let properties = payload.properties();
         let terms = payload.terms();
+        let expires_at = payload.expires_at();
+
+        let existing_proposals = state
+            .get_proposal_list(record_id, receiving_agent)?
+            .map(|proposals| proposals.proposals().to_vec())
+            .unwrap_or_default();
+
+        // The block time is only needed, and BlockInfo only required, if a proposal expires
+        let block_time = if *expires_at != 0
+            || existing_proposals
+                .iter()
+                .any(|proposal| *proposal.expires_at() != 0)
+        {
+            get_block_time(state)?
+        } else {
+            0
+        };
+
+        if *expires_at != 0 && *expires_at <= block_time {
+            return Err(ApplyError::InvalidTransaction(format!(
+                "Proposal expiry {} must be later than the latest block time {}",
+                expires_at, block_time
+            )));
+        }
 
         match state.get_agent(signer)? {
             Some(agent) => agent,
@@ -449,10 +492,12 @@ impl TrackAndTraceTransactionHandler {
             }
         };
 
-        let mut proposals = match state.get_proposal_list(record_id, receiving_agent)? {
-            Some(proposals) => proposals.proposals().to_vec(),
-            None => vec![],
-        };
+        // Open proposals that have expired do not block a new proposal; they are canceled as
+        // part of this transaction.
+        let mut proposals = existing_proposals
+            .iter()
+            .map(|proposal| cancel_if_expired(proposal, block_time))
+            .collect::<Result<Vec<_>, ApplyError>>()?;
 
         let open_proposals = proposals
             .iter()
@@ -550,6 +595,7 @@ impl TrackAndTraceTransactionHandler {
             .with_properties(properties.to_vec())
             .with_status(Status::Open)
             .with_terms(terms.to_string())
+            .with_expires_at(*expires_at)
             .build()
             .map_err(|err| map_builder_error_to_apply_error(err, "Proposal"))?;
 
@@ -614,6 +660,25 @@ impl TrackAndTraceTransactionHandler {
                 ))
             })?;
 
+        if timestamp < *current_proposal.timestamp() {
+            return Err(ApplyError::InvalidTransaction(format!(
+                "Answer timestamp {} is earlier than the proposal timestamp {}",
+                timestamp,
+                current_proposal.timestamp()
+            )));
+        }
+
+        // Expiry is checked against the block time, which the receiving agent cannot set
+        if *current_proposal.expires_at() != 0
+            && response != &Response::Cancel
+            && current_proposal.is_expired(get_block_time(state)?)
+        {
+            return Err(ApplyError::InvalidTransaction(format!(
+                "Proposal for record {} for {} has expired",
+                record_id, receiving_agent
+            )));
+        }
+
         let mut updated_proposal_builder = current_proposal.clone().into_builder();
 
         match response {
@@ -980,12 +1045,169 @@ impl TrackAndTraceTransactionHandler {
 
         Ok(())
     }
+
+    fn _update_record_location(
+        &self,
+        payload: &UpdateRecordLocationAction,
+        state: &mut TrackAndTraceState,
+        signer: &str,
+    ) -> Result<(), ApplyError> {
+        let record_id = payload.record_id();
+        let location_id = payload.location_id();
+
+        let record = match state.get_record(record_id)? {
+            Some(record) => record,
+            None => {
+                return Err(ApplyError::InvalidTransaction(format!(
+                    "Record does not exist: {}",
+                    record_id
+                )));
+            }
+        };
+
+        if *record.field_final() {
+            return Err(ApplyError::InvalidTransaction(format!(
+                "Record is final: {}",
+                record_id
+            )));
+        }
+
+        // The custodian has possession of the record, so only they can say where it is
+        let is_custodian = record
+            .custodians()
+            .last()
+            .map(|custodian| custodian.agent_id() == signer)
+            .unwrap_or(false);
+
+        if !is_custodian {
+            return Err(ApplyError::InvalidTransaction(String::from(
+                "Only the custodian can update the location of a record",
+            )));
+        }
+
+        if !location_id.is_empty() && state.get_location(location_id)?.is_none() {
+            return Err(ApplyError::InvalidTransaction(format!(
+                "Location does not exist: {}",
+                location_id
+            )));
+        }
+
+        let updated_record = record
+            .into_builder()
+            .with_location_id(location_id.to_string())
+            .build()
+            .map_err(|err| map_builder_error_to_apply_error(err, "Record"))?;
+
+        state.set_record(record_id, updated_record)?;
+
+        Ok(())
+    }
+
+    fn _cancel_stale_proposals(
+        &self,
+        payload: &CancelStaleProposalsAction,
+        state: &mut TrackAndTraceState,
+        signer: &str,
+    ) -> Result<(), ApplyError> {
+        let record_id = payload.record_id();
+
+        let proposal_record = match state.get_record(record_id)? {
+            Some(record) => record,
+            None => {
+                return Err(ApplyError::InvalidTransaction(format!(
+                    "Record does not exist: {}",
+                    record_id
+                )));
+            }
+        };
+
+        let is_owner = proposal_record
+            .owners()
+            .last()
+            .map(|owner| owner.agent_id() == signer)
+            .unwrap_or(false);
+        let is_custodian = proposal_record
+            .custodians()
+            .last()
+            .map(|custodian| custodian.agent_id() == signer)
+            .unwrap_or(false);
+
+        if !is_owner && !is_custodian {
+            return Err(ApplyError::InvalidTransaction(String::from(
+                "Only the owner or custodian can cancel stale proposals",
+            )));
+        }
+
+        let block_time = get_block_time(state)?;
+
+        for receiving_agent in payload.receiving_agents() {
+            let proposal_list = match state.get_proposal_list(record_id, receiving_agent)? {
+                Some(proposal_list) => proposal_list,
+                None => continue,
+            };
+
+            if !proposal_list
+                .proposals()
+                .iter()
+                .any(|proposal| is_stale(proposal, block_time))
+            {
+                continue;
+            }
+
+            let proposals = proposal_list
+                .proposals()
+                .iter()
+                .map(|proposal| cancel_if_expired(proposal, block_time))
+                .collect::<Result<Vec<_>, ApplyError>>()?;
+
+            let updated_proposal_list = ProposalListBuilder::new()
+                .with_proposals(proposals)
+                .build()
+                .map_err(|err| map_builder_error_to_apply_error(err, "ProposalList"))?;
+
+            state.set_proposal_list(record_id, receiving_agent, updated_proposal_list)?;
+        }
+
+        Ok(())
+    }
 }
 
 fn map_builder_error_to_apply_error(err: BuilderError, protocol_name: &str) -> ApplyError {
     ApplyError::InvalidTransaction(format!("Failed to build {}. {}", protocol_name, err))
 }
 
+/// Returns the time that proposal expiry is checked against: the timestamp of the latest block,
+/// as recorded by the BlockInfo transaction processor. Unlike the timestamp of the payload, it
+/// cannot be set by the signer, so a proposal cannot be accepted late or canceled early.
+fn get_block_time(state: &TrackAndTraceState) -> Result<u64, ApplyError> {
+    state.get_block_time()?.ok_or_else(|| {
+        ApplyError::InvalidTransaction(String::from(
+            "Proposal expiry requires the BlockInfo transaction processor, but no block info \
+             was found in state",
+        ))
+    })
+}
+
+/// Returns true if the proposal is still open but its expiry time has been reached by the given
+/// block time
+fn is_stale(proposal: &Proposal, block_time: u64) -> bool {
+    proposal.status() == &Status::Open && proposal.is_expired(block_time)
+}
+
+/// Returns a copy of the proposal, with its status set to Canceled if it is stale
+fn cancel_if_expired(proposal: &Proposal, block_time: u64) -> Result<Proposal, ApplyError> {
+    if is_stale(proposal, block_time) {
+        proposal
+            .clone()
+            .into_builder()
+            .with_status(Status::Canceled)
+            .build()
+            .map_err(|err| map_builder_error_to_apply_error(err, "Proposal"))
+    } else {
+        Ok(proposal.clone())
+    }
+}
+
 impl TransactionHandler for TrackAndTraceTransactionHandler {
     fn family_name(&self) -> String {
         self.family_name.clone()
@@ -1038,6 +1260,12 @@ impl TransactionHandler for TrackAndTraceTransactionHandler {
             Action::RevokeReporter(action_payload) => {
                 self._revoke_reporter(action_payload, &mut state, signer)?
             }
+            Action::CancelStaleProposals(action_payload) => {
+                self._cancel_stale_proposals(action_payload, &mut state, signer)?
+            }
+            Action::UpdateRecordLocation(action_payload) => {
+                self._update_record_location(action_payload, &mut state, signer)?
+            }
         }
         Ok(())
     }
@@ -1073,8 +1301,10 @@ mod tests {
     use std::collections::HashMap;
 
     use grid_sdk::{
+        location::addressing::compute_gs1_location_address,
         pike::addressing::compute_agent_address,
         protocol::{
+            location::state::{LocationBuilder, LocationListBuilder, LocationNamespace},
             pike::state::{AgentBuilder, AgentListBuilder},
             schema::state::{
                 DataType, PropertyDefinitionBuilder, PropertyValueBuilder, SchemaBuilder,
@@ -1082,10 +1312,11 @@ mod tests {
             },
             track_and_trace::{
                 payload::{
-                    AnswerProposalActionBuilder, CreateProposalActionBuilder,
-                    CreateRecordActionBuilder, FinalizeRecordActionBuilder,
-                    RevokeReporterActionBuilder, UpdatePropertiesAction,
-                    UpdatePropertiesActionBuilder,
+                    AnswerProposalActionBuilder, CancelStaleProposalsActionBuilder,
+                    CreateProposalActionBuilder, CreateRecordActionBuilder,
+                    FinalizeRecordActionBuilder, RevokeReporterActionBuilder,
+                    UpdatePropertiesAction, UpdatePropertiesActionBuilder,
+                    UpdateRecordLocationActionBuilder,
                 },
                 state::{
                     Property, PropertyListBuilder, PropertyPage, PropertyPageListBuilder, Proposal,
@@ -1093,21 +1324,29 @@ mod tests {
                 },
             },
         },
-        protos::IntoBytes,
+        protos::{
+            block_info::{BlockInfo, BlockInfoConfig},
+            IntoBytes,
+        },
         schema::addressing::compute_schema_address,
         track_and_trace::addressing::{
             make_property_address, make_proposal_address, make_record_address,
         },
     };
 
+    use protobuf::Message;
     use sawtooth_sdk::processor::handler::{ContextError, TransactionContext};
 
+    use crate::state::{make_block_info_address, make_block_info_config_address};
+
     const TIMESTAMP: u64 = 1;
     const RECORD_ID: &str = "test_record_action";
     const PUBLIC_KEY: &str = "agent_public_key";
     const OPTIONAL_PROPERTY_NAME: &str = "test_optional";
     const REQUIRED_PROPERTY_NAME: &str = "test_required";
     const SCHEMA_NAME: &str = "test_schema";
+    const GTIN: &str = "00012345600012";
+    const GLN: &str = "0123456789012";
 
     #[derive(Default, Debug)]
     /// A MockTransactionContext that can be used to test TrackAndTraceState
@@ -1197,6 +1436,25 @@ mod tests {
             self.set_state_entry(schema_address, schema_bytes).unwrap();
         }
 
+        fn add_location(&self, location_id: &str) {
+            let location = LocationBuilder::new()
+                .with_location_id(location_id.to_string())
+                .with_namespace(LocationNamespace::Gs1)
+                .with_owner("test_org".to_string())
+                .with_properties(vec![])
+                .build()
+                .unwrap();
+
+            let location_list = LocationListBuilder::new()
+                .with_locations(vec![location])
+                .build()
+                .unwrap();
+            let location_bytes = location_list.into_bytes().unwrap();
+            let location_address = compute_gs1_location_address(location_id);
+            self.set_state_entry(location_address, location_bytes)
+                .unwrap();
+        }
+
         fn add_record(&self) {
             let record_list = RecordListBuilder::new()
                 .with_records(vec![make_record()])
@@ -1280,6 +1538,48 @@ mod tests {
                 .unwrap();
         }
 
+        /// Records a block with the given timestamp as the latest in the BlockInfo state
+        fn set_block_time(&self, timestamp: u64) {
+            let mut config = BlockInfoConfig::new();
+            config.set_latest_block(7);
+            let mut block_info = BlockInfo::new();
+            block_info.set_block_num(7);
+            block_info.set_timestamp(timestamp);
+
+            self.set_state_entry(
+                make_block_info_config_address(),
+                config.write_to_bytes().unwrap(),
+            )
+            .unwrap();
+            self.set_state_entry(
+                make_block_info_address(7),
+                block_info.write_to_bytes().unwrap(),
+            )
+            .unwrap();
+        }
+
+        fn add_expiring_proposal(
+            &self,
+            issuing_agent: &str,
+            receiving_agent_key: &str,
+            role: Role,
+            expires_at: u64,
+        ) {
+            let proposal = make_proposal(issuing_agent, receiving_agent_key, role, Status::Open)
+                .into_builder()
+                .with_expires_at(expires_at)
+                .build()
+                .expect("Failed to build proposal");
+            let proposal_list = ProposalListBuilder::new()
+                .with_proposals(vec![proposal])
+                .build()
+                .unwrap();
+            let proposal_list_bytes = proposal_list.into_bytes().unwrap();
+            let proposal_list_address = make_proposal_address(RECORD_ID, receiving_agent_key);
+            self.set_state_entry(proposal_list_address, proposal_list_bytes)
+                .unwrap();
+        }
+
         fn add_property_with_reporter(
             &self,
             property_name: &str,
@@ -1428,6 +1728,151 @@ mod tests {
         }
     }
 
+    #[test]
+    /// Test that if the CreateRecordAction links an existing location, an OK is returned and the
+    /// new Record references the location
+    fn test_create_record_with_location() {
+        let mut transaction_context = MockTransactionContext::default();
+        transaction_context.add_schema();
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_location(GLN);
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+        let create_record_action = CreateRecordActionBuilder::new()
+            .with_record_id(RECORD_ID.to_string())
+            .with_schema(SCHEMA_NAME.to_string())
+            .with_properties(vec![required_property_value()])
+            .with_location_id(GLN.to_string())
+            .build()
+            .expect("Failed to build CreateRecordAction");
+
+        assert!(transaction_handler
+            ._create_record(&create_record_action, &mut state, PUBLIC_KEY, TIMESTAMP)
+            .is_ok());
+
+        let record = state
+            .get_record(RECORD_ID)
+            .expect("Failed to fetch record")
+            .expect("No record found");
+
+        assert_eq!(record.location_id(), GLN);
+        assert_eq!(record.product_id(), "");
+    }
+
+    #[test]
+    /// Test that if the CreateRecordAction is invalid if the linked product does not exist.
+    fn test_create_record_product_does_not_exist() {
+        let mut transaction_context = MockTransactionContext::default();
+        transaction_context.add_schema();
+        transaction_context.add_agent(PUBLIC_KEY);
+
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        let create_record_action = CreateRecordActionBuilder::new()
+            .with_record_id(RECORD_ID.to_string())
+            .with_schema(SCHEMA_NAME.to_string())
+            .with_properties(vec![required_property_value()])
+            .with_product_id(GTIN.to_string())
+            .build()
+            .expect("Failed to build CreateRecordAction");
+
+        match transaction_handler._create_record(
+            &create_record_action,
+            &mut state,
+            PUBLIC_KEY,
+            TIMESTAMP,
+        ) {
+            Ok(()) => panic!("Product does not exist, InvalidTransaction should be returned"),
+            Err(ApplyError::InvalidTransaction(err)) => {
+                assert!(err.contains(&format!("Product does not exist: {}", GTIN)));
+            }
+            Err(err) => panic!("Should have gotten invalid error but got {}", err),
+        }
+    }
+
+    #[test]
+    /// Test that if the UpdateRecordLocationAction is valid an OK is returned and the Record
+    /// references the new location
+    fn test_update_record_location() {
+        let mut transaction_context = MockTransactionContext::default();
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_record();
+        transaction_context.add_location(GLN);
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        assert!(transaction_handler
+            ._update_record_location(&update_record_location_action(GLN), &mut state, PUBLIC_KEY)
+            .is_ok());
+
+        let record = state
+            .get_record(RECORD_ID)
+            .expect("Failed to fetch record")
+            .expect("No record found");
+        assert_eq!(record.location_id(), GLN);
+
+        assert!(transaction_handler
+            ._update_record_location(&update_record_location_action(""), &mut state, PUBLIC_KEY)
+            .is_ok());
+
+        let record = state
+            .get_record(RECORD_ID)
+            .expect("Failed to fetch record")
+            .expect("No record found");
+        assert_eq!(record.location_id(), "");
+    }
+
+    #[test]
+    /// Test that the UpdateRecordLocationAction is invalid if the signer is not the custodian
+    fn test_update_record_location_not_custodian() {
+        let mut transaction_context = MockTransactionContext::default();
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_record();
+        transaction_context.add_location(GLN);
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        match transaction_handler._update_record_location(
+            &update_record_location_action(GLN),
+            &mut state,
+            "not_the_custodian",
+        ) {
+            Ok(()) => panic!("Signer is not the custodian, InvalidTransaction should be returned"),
+            Err(ApplyError::InvalidTransaction(err)) => {
+                assert!(err.contains("Only the custodian can update the location"));
+            }
+            Err(err) => panic!("Should have gotten invalid error but got {}", err),
+        }
+    }
+
+    #[test]
+    /// Test that the UpdateRecordLocationAction is invalid if the location does not exist
+    fn test_update_record_location_does_not_exist() {
+        let mut transaction_context = MockTransactionContext::default();
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_record();
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        match transaction_handler._update_record_location(
+            &update_record_location_action(GLN),
+            &mut state,
+            PUBLIC_KEY,
+        ) {
+            Ok(()) => panic!("Location does not exist, InvalidTransaction should be returned"),
+            Err(ApplyError::InvalidTransaction(err)) => {
+                assert!(err.contains(&format!("Location does not exist: {}", GLN)));
+            }
+            Err(err) => panic!("Should have gotten invalid error but got {}", err),
+        }
+    }
+
     #[test]
     /// Test that if the CreateRecordAction is invalid if the a record with the same id
     /// already exists.
@@ -2057,6 +2502,121 @@ mod tests {
         }
     }
 
+    #[test]
+    /// Test that the CreateProposalAction fails when the expiry time is not later than the
+    /// latest block time, whatever the transaction timestamp
+    fn test_create_proposal_expiry_in_past() {
+        let mut transaction_context = MockTransactionContext::default();
+        let receiving_agent_key = "receiving_agent_key";
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_agent(receiving_agent_key);
+        transaction_context.add_record();
+        transaction_context.set_block_time(TIMESTAMP + 10);
+
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        let payload = CreateProposalActionBuilder::new()
+            .with_record_id(RECORD_ID.to_string())
+            .with_properties(vec![])
+            .with_receiving_agent(receiving_agent_key.to_string())
+            .with_role(Role::Owner)
+            .with_terms("".to_string())
+            .with_expires_at(TIMESTAMP + 10)
+            .build()
+            .expect("Failed to build CreateProposalAction");
+
+        match transaction_handler._create_proposal(&payload, &mut state, PUBLIC_KEY, TIMESTAMP) {
+            Ok(()) => {
+                panic!("Proposal expiry is in the past, InvalidTransaction should be returned")
+            }
+            Err(ApplyError::InvalidTransaction(err)) => {
+                assert!(err.contains("must be later than the latest block time"));
+            }
+            Err(err) => panic!("Should have gotten invalid error but got {}", err),
+        }
+    }
+
+    #[test]
+    /// Test that an expiring proposal cannot be created without BlockInfo in state, as there is
+    /// no block time to check its expiry against
+    fn test_create_proposal_expiry_without_block_info() {
+        let mut transaction_context = MockTransactionContext::default();
+        let receiving_agent_key = "receiving_agent_key";
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_agent(receiving_agent_key);
+        transaction_context.add_record();
+
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        let payload = CreateProposalActionBuilder::new()
+            .with_record_id(RECORD_ID.to_string())
+            .with_properties(vec![])
+            .with_receiving_agent(receiving_agent_key.to_string())
+            .with_role(Role::Owner)
+            .with_terms("".to_string())
+            .with_expires_at(TIMESTAMP + 10)
+            .build()
+            .expect("Failed to build CreateProposalAction");
+
+        match transaction_handler._create_proposal(&payload, &mut state, PUBLIC_KEY, TIMESTAMP) {
+            Ok(()) => panic!("There is no BlockInfo, InvalidTransaction should be returned"),
+            Err(ApplyError::InvalidTransaction(err)) => {
+                assert!(err.contains("requires the BlockInfo transaction processor"));
+            }
+            Err(err) => panic!("Should have gotten invalid error but got {}", err),
+        }
+    }
+
+    #[test]
+    /// Test that an open proposal which has expired does not block a new proposal, and that the
+    /// expired proposal is canceled
+    fn test_create_proposal_replaces_expired_proposal() {
+        let mut transaction_context = MockTransactionContext::default();
+        let receiving_agent_key = "receiving_agent_key";
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_agent(receiving_agent_key);
+        transaction_context.add_record();
+        transaction_context.set_block_time(TIMESTAMP);
+        transaction_context.add_expiring_proposal(
+            PUBLIC_KEY,
+            receiving_agent_key,
+            Role::Owner,
+            TIMESTAMP,
+        );
+
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        assert!(transaction_handler
+            ._create_proposal(
+                &create_proposal_action(Role::Owner, receiving_agent_key),
+                &mut state,
+                PUBLIC_KEY,
+                TIMESTAMP,
+            )
+            .is_ok());
+
+        let proposal_list = state
+            .get_proposal_list(RECORD_ID, receiving_agent_key)
+            .expect("Failed to get ProposalList from state")
+            .expect("ProposalList not found");
+
+        assert_eq!(proposal_list.proposals().len(), 2);
+        assert!(proposal_list
+            .proposals()
+            .iter()
+            .any(|p| p.status() == &Status::Canceled && *p.expires_at() == TIMESTAMP));
+        assert!(proposal_list
+            .proposals()
+            .iter()
+            .any(|p| p.status() == &Status::Open && *p.expires_at() == 0));
+    }
+
     #[test]
     /// Test that if the CreateProposalAction fails when the record does not exist
     fn test_create_proposal_record_does_not_exist() {
@@ -2239,6 +2799,79 @@ mod tests {
         );
     }
 
+    #[test]
+    /// Test that an expired proposal cannot be accepted, even by an answer dated before the
+    /// expiry, as expiry is checked against the latest block time
+    fn test_answer_proposal_accept_expired() {
+        let mut transaction_context = MockTransactionContext::default();
+        let receiving_agent_key = "receiving_agent_key";
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_agent(receiving_agent_key);
+        transaction_context.add_record();
+        transaction_context.set_block_time(TIMESTAMP + 20);
+        transaction_context.add_expiring_proposal(
+            PUBLIC_KEY,
+            receiving_agent_key,
+            Role::Owner,
+            TIMESTAMP + 10,
+        );
+
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        let payload = answer_proposal_action(Role::Owner, receiving_agent_key, Response::Accept);
+
+        match transaction_handler._answer_proposal(
+            &payload,
+            &mut state,
+            receiving_agent_key,
+            TIMESTAMP,
+        ) {
+            Ok(()) => panic!("Proposal has expired, InvalidTransaction should be returned"),
+            Err(ApplyError::InvalidTransaction(err)) => {
+                assert!(err.contains("has expired"));
+            }
+            Err(err) => panic!("Should have gotten invalid error but got {}", err),
+        }
+    }
+
+    #[test]
+    /// Test that a proposal cannot be answered with a timestamp earlier than the proposal's, so
+    /// that an answer cannot be backdated to before the proposal was made
+    fn test_answer_proposal_before_proposal_timestamp() {
+        let mut transaction_context = MockTransactionContext::default();
+        let receiving_agent_key = "receiving_agent_key";
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_agent(receiving_agent_key);
+        transaction_context.add_record();
+        transaction_context.add_expiring_proposal(
+            PUBLIC_KEY,
+            receiving_agent_key,
+            Role::Owner,
+            TIMESTAMP + 10,
+        );
+
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        let payload = answer_proposal_action(Role::Owner, receiving_agent_key, Response::Accept);
+
+        match transaction_handler._answer_proposal(
+            &payload,
+            &mut state,
+            receiving_agent_key,
+            TIMESTAMP - 1,
+        ) {
+            Ok(()) => panic!("Answer is backdated, InvalidTransaction should be returned"),
+            Err(ApplyError::InvalidTransaction(err)) => {
+                assert!(err.contains("earlier than the proposal timestamp"));
+            }
+            Err(err) => panic!("Should have gotten invalid error but got {}", err),
+        }
+    }
+
     #[test]
     /// Test that if the AnswerProposalAction, with Response set to Reject, is valid an OK is returned
     /// and the proposal is updated to have status Rejected
@@ -3160,6 +3793,116 @@ mod tests {
         }
     }
 
+    #[test]
+    /// Test that if the CancelStaleProposalsAction is valid an OK is returned and open proposals
+    /// that have expired are updated to have status Canceled
+    fn test_cancel_stale_proposals_valid() {
+        let mut transaction_context = MockTransactionContext::default();
+        let receiving_agent_key = "receiving_agent_key";
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_agent(receiving_agent_key);
+        transaction_context.add_record();
+        transaction_context.set_block_time(TIMESTAMP);
+        transaction_context.add_expiring_proposal(
+            PUBLIC_KEY,
+            receiving_agent_key,
+            Role::Owner,
+            TIMESTAMP,
+        );
+
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        assert!(transaction_handler
+            ._cancel_stale_proposals(
+                &cancel_stale_proposals_action(vec![receiving_agent_key.to_string()]),
+                &mut state,
+                PUBLIC_KEY,
+            )
+            .is_ok());
+
+        let proposal_list = state
+            .get_proposal_list(RECORD_ID, receiving_agent_key)
+            .expect("Failed to get ProposalList from state")
+            .expect("ProposalList not found");
+
+        assert_eq!(proposal_list.proposals().len(), 1);
+        assert_eq!(proposal_list.proposals()[0].status(), &Status::Canceled);
+    }
+
+    #[test]
+    /// Test that the CancelStaleProposalsAction leaves open a proposal whose expiry has not been
+    /// reached by the latest block time
+    fn test_cancel_stale_proposals_not_expired() {
+        let mut transaction_context = MockTransactionContext::default();
+        let receiving_agent_key = "receiving_agent_key";
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_agent(receiving_agent_key);
+        transaction_context.add_record();
+        transaction_context.set_block_time(TIMESTAMP);
+        transaction_context.add_expiring_proposal(
+            PUBLIC_KEY,
+            receiving_agent_key,
+            Role::Owner,
+            TIMESTAMP + 10,
+        );
+
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        assert!(transaction_handler
+            ._cancel_stale_proposals(
+                &cancel_stale_proposals_action(vec![receiving_agent_key.to_string()]),
+                &mut state,
+                PUBLIC_KEY,
+            )
+            .is_ok());
+
+        let proposal_list = state
+            .get_proposal_list(RECORD_ID, receiving_agent_key)
+            .expect("Failed to get ProposalList from state")
+            .expect("ProposalList not found");
+
+        assert_eq!(proposal_list.proposals()[0].status(), &Status::Open);
+    }
+
+    #[test]
+    /// Test that the CancelStaleProposalsAction fails if the signer is neither the owner nor
+    /// the custodian of the record
+    fn test_cancel_stale_proposals_signer_not_owner_or_custodian() {
+        let mut transaction_context = MockTransactionContext::default();
+        let receiving_agent_key = "receiving_agent_key";
+        transaction_context.add_agent(PUBLIC_KEY);
+        transaction_context.add_agent(receiving_agent_key);
+        transaction_context.add_record();
+        transaction_context.add_expiring_proposal(
+            PUBLIC_KEY,
+            receiving_agent_key,
+            Role::Owner,
+            TIMESTAMP,
+        );
+
+        let mut state = TrackAndTraceState::new(&mut transaction_context);
+
+        let transaction_handler = TrackAndTraceTransactionHandler::new();
+
+        match transaction_handler._cancel_stale_proposals(
+            &cancel_stale_proposals_action(vec![receiving_agent_key.to_string()]),
+            &mut state,
+            receiving_agent_key,
+        ) {
+            Ok(()) => {
+                panic!("Signer is not owner or custodian, InvalidTransaction should be returned")
+            }
+            Err(ApplyError::InvalidTransaction(err)) => {
+                assert!(err.contains("Only the owner or custodian can cancel stale proposals"));
+            }
+            Err(err) => panic!("Should have gotten invalid error but got {}", err),
+        }
+    }
+
     fn optional_property_value() -> PropertyValue {
         PropertyValueBuilder::new()
             .with_name(OPTIONAL_PROPERTY_NAME.to_string())
@@ -3262,6 +4005,22 @@ mod tests {
             .expect("Failed to build RevokeReporterAction")
     }
 
+    fn update_record_location_action(location_id: &str) -> UpdateRecordLocationAction {
+        UpdateRecordLocationActionBuilder::new()
+            .with_record_id(RECORD_ID.to_string())
+            .with_location_id(location_id.to_string())
+            .build()
+            .expect("Failed to build UpdateRecordLocationAction")
+    }
+
+    fn cancel_stale_proposals_action(receiving_agents: Vec<String>) -> CancelStaleProposalsAction {
+        CancelStaleProposalsActionBuilder::new()
+            .with_record_id(RECORD_ID.to_string())
+            .with_receiving_agents(receiving_agents)
+            .build()
+            .expect("Failed to build CancelStaleProposalsAction")
+    }
+
     fn optional_property_definition() -> PropertyDefinition {
         PropertyDefinitionBuilder::new()
             .with_name(OPTIONAL_PROPERTY_NAME.to_string())
@@ -21,13 +21,20 @@ cfg_if! {
 }
 
 use grid_sdk::protocol::track_and_trace::payload::{
-    Action, CreateRecordAction, TrackAndTracePayload,
+    Action, CancelStaleProposalsAction, CreateRecordAction, TrackAndTracePayload,
+    UpdateRecordLocationAction,
 };
 
 pub fn validate_payload(payload: &TrackAndTracePayload) -> Result<(), ApplyError> {
     validate_timestamp(*payload.timestamp())?;
     match payload.action() {
         Action::CreateRecord(action_payload) => validate_record_create_action(action_payload),
+        Action::CancelStaleProposals(action_payload) => {
+            validate_cancel_stale_proposals_action(action_payload)
+        }
+        Action::UpdateRecordLocation(action_payload) => {
+            validate_update_record_location_action(action_payload)
+        }
         _ => Ok(()),
     }
 }
@@ -49,6 +56,34 @@ fn validate_record_create_action(
     Ok(())
 }
 
+fn validate_cancel_stale_proposals_action(
+    cancel_stale_proposals_action: &CancelStaleProposalsAction,
+) -> Result<(), ApplyError> {
+    if cancel_stale_proposals_action.record_id() == "" {
+        return Err(ApplyError::InvalidTransaction(String::from(
+            "Record id cannot be empty string",
+        )));
+    }
+
+    if cancel_stale_proposals_action.receiving_agents().is_empty() {
+        return Err(ApplyError::InvalidTransaction(String::from(
+            "At least one receiving agent must be provided",
+        )));
+    }
+    Ok(())
+}
+
+fn validate_update_record_location_action(
+    update_record_location_action: &UpdateRecordLocationAction,
+) -> Result<(), ApplyError> {
+    if update_record_location_action.record_id() == "" {
+        return Err(ApplyError::InvalidTransaction(String::from(
+            "Record id cannot be empty string",
+        )));
+    }
+    Ok(())
+}
+
 fn validate_timestamp(timestamp: u64) -> Result<(), ApplyError> {
     match timestamp {
         0 => Err(ApplyError::InvalidTransaction(String::from(
@@ -63,9 +98,11 @@ mod tests {
     use super::*;
 
     use grid_sdk::protos::track_and_trace_payload::{
+        CancelStaleProposalsAction as CancelStaleProposalsActionProto,
         CreateRecordAction as CreateRecordActionProto,
         TrackAndTracePayload as TrackAndTracePayloadProto,
         TrackAndTracePayload_Action as ActionProto,
+        UpdateRecordLocationAction as UpdateRecordLocationActionProto,
     };
     use grid_sdk::protos::IntoNative;
 
@@ -169,4 +206,44 @@ mod tests {
             "Payload should be valid"
         );
     }
+
+    #[test]
+    /// Test that an error is returned if the payload with CancelStaleProposalsAction does not
+    /// list any receiving agents. This test needs to use the proto directly to be able to
+    /// mimic the scenarios possible from creating a CancelStaleProposalsAction from bytes.
+    fn test_validate_payload_cancel_stale_proposals_agents_missing() {
+        let mut payload_proto = TrackAndTracePayloadProto::new();
+
+        payload_proto.set_action(ActionProto::CANCEL_STALE_PROPOSALS);
+        payload_proto.set_timestamp(2);
+        let mut action = CancelStaleProposalsActionProto::new();
+        action.set_record_id("my_record".to_string());
+        payload_proto.set_cancel_stale_proposals(action.clone());
+        let payload = payload_proto.clone().into_native().unwrap();
+        match validate_payload(&payload) {
+            Ok(_) => panic!("Payload missing receiving agents, should return error"),
+            Err(err) => assert!(err
+                .to_string()
+                .contains("At least one receiving agent must be provided")),
+        }
+    }
+
+    #[test]
+    /// Test that an error is returned if the payload with UpdateRecordLocationAction is missing
+    /// the record_id. This test needs to use the proto directly to be able to mimic the scenarios
+    /// possible from creating an UpdateRecordLocationAction from bytes.
+    fn test_validate_payload_update_record_location_record_id_missing() {
+        let mut payload_proto = TrackAndTracePayloadProto::new();
+
+        payload_proto.set_action(ActionProto::UPDATE_RECORD_LOCATION);
+        payload_proto.set_timestamp(2);
+        let mut action = UpdateRecordLocationActionProto::new();
+        action.set_location_id("0123456789012".to_string());
+        payload_proto.set_update_record_location(action);
+        let payload = payload_proto.into_native().unwrap();
+        match validate_payload(&payload) {
+            Ok(_) => panic!("Payload missing record_id, should return error"),
+            Err(err) => assert!(err.to_string().contains("Record id cannot be empty string")),
+        }
+    }
 }
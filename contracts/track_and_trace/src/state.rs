@@ -23,19 +23,40 @@ cfg_if! {
 }
 
 use grid_sdk::{
+    location::addressing::compute_gs1_location_address,
     pike::addressing::compute_agent_address,
+    product::addressing::compute_gs1_product_address,
     protocol::{
+        location::state::{Location, LocationList},
         pike::state::{Agent, AgentList},
+        product::state::{Product, ProductList},
         schema::state::{Schema, SchemaList},
         track_and_trace::state::{
             Property, PropertyList, PropertyListBuilder, PropertyPage, PropertyPageList,
             PropertyPageListBuilder, ProposalList, Record, RecordList, RecordListBuilder,
         },
     },
-    protos::{FromBytes, IntoBytes},
+    protos::{
+        block_info::{BlockInfo, BlockInfoConfig},
+        FromBytes, IntoBytes,
+    },
     schema::addressing::compute_schema_address,
     track_and_trace::addressing::*,
 };
+use protobuf::Message;
+
+/// The namespace of the Sawtooth BlockInfo transaction processor
+pub const BLOCK_INFO_NAMESPACE: &str = "00b10c";
+
+/// Returns the address of the BlockInfo config, which records the latest block
+pub fn make_block_info_config_address() -> String {
+    format!("{}01{}", BLOCK_INFO_NAMESPACE, "0".repeat(62))
+}
+
+/// Returns the address of the BlockInfo of the block with the given number
+pub fn make_block_info_address(block_num: u64) -> String {
+    format!("{}00{:062x}", BLOCK_INFO_NAMESPACE, block_num)
+}
 
 pub struct TrackAndTraceState<'a> {
     context: &'a mut dyn TransactionContext,
@@ -179,6 +200,60 @@ impl<'a> TrackAndTraceState<'a> {
         }
     }
 
+    /// Gets a Grid Product by its GTIN. Handles retrieving the correct product from a
+    /// ProductList.
+    pub fn get_product(&self, product_id: &str) -> Result<Option<Product>, ApplyError> {
+        let address = compute_gs1_product_address(product_id);
+        let d = self.context.get_state_entry(&address)?;
+        match d {
+            Some(packed) => {
+                let products = match ProductList::from_bytes(packed.as_slice()) {
+                    Ok(products) => products,
+                    Err(err) => {
+                        return Err(ApplyError::InternalError(format!(
+                            "Cannot deserialize product list: {:?}",
+                            err,
+                        )));
+                    }
+                };
+
+                Ok(products
+                    .products()
+                    .iter()
+                    .find(|product| product.product_id() == product_id)
+                    .cloned())
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Gets a Grid Location by its GLN. Handles retrieving the correct location from a
+    /// LocationList.
+    pub fn get_location(&self, location_id: &str) -> Result<Option<Location>, ApplyError> {
+        let address = compute_gs1_location_address(location_id);
+        let d = self.context.get_state_entry(&address)?;
+        match d {
+            Some(packed) => {
+                let locations = match LocationList::from_bytes(packed.as_slice()) {
+                    Ok(locations) => locations,
+                    Err(err) => {
+                        return Err(ApplyError::InternalError(format!(
+                            "Cannot deserialize location list: {:?}",
+                            err,
+                        )));
+                    }
+                };
+
+                Ok(locations
+                    .locations()
+                    .iter()
+                    .find(|location| location.location_id() == location_id)
+                    .cloned())
+            }
+            None => Ok(None),
+        }
+    }
+
     pub fn get_property(
         &self,
         record_id: &str,
@@ -404,6 +479,34 @@ impl<'a> TrackAndTraceState<'a> {
             .map_err(|err| ApplyError::InternalError(format!("{}", err)))?;
         Ok(())
     }
+
+    /// Gets the timestamp of the latest block recorded by the BlockInfo transaction processor.
+    /// Unlike the timestamp of a payload, it is set by the validator, not the signer. Returns
+    /// `None` if there is no BlockInfo in state.
+    pub fn get_block_time(&self) -> Result<Option<u64>, ApplyError> {
+        let config = match self.get_block_info_entry(&make_block_info_config_address())? {
+            Some(packed) => BlockInfoConfig::parse_from_bytes(&packed).map_err(|err| {
+                ApplyError::InternalError(format!("Cannot deserialize block info config: {}", err))
+            })?,
+            None => return Ok(None),
+        };
+
+        match self.get_block_info_entry(&make_block_info_address(config.get_latest_block()))? {
+            Some(packed) => BlockInfo::parse_from_bytes(&packed)
+                .map(|block_info| Some(block_info.get_timestamp()))
+                .map_err(|err| {
+                    ApplyError::InternalError(format!("Cannot deserialize block info: {}", err))
+                }),
+            None => Ok(None),
+        }
+    }
+
+    fn get_block_info_entry(&self, address: &str) -> Result<Option<Vec<u8>>, ApplyError> {
+        Ok(self
+            .context
+            .get_state_entry(address)?
+            .filter(|packed| !packed.is_empty()))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,126 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::ArgMatches;
+
+use crate::config::{GridConfigSource, PartialGridConfig, PartialGridConfigBuilder};
+use crate::error::ConfigurationError;
+
+pub struct ClapPartialGridConfigBuilder<'a> {
+    matches: &'a ArgMatches<'a>,
+}
+
+impl<'a> ClapPartialGridConfigBuilder<'a> {
+    pub fn new(matches: &'a ArgMatches<'a>) -> Self {
+        ClapPartialGridConfigBuilder { matches }
+    }
+
+    fn value_of(&self, name: &str) -> Option<String> {
+        self.matches.value_of(name).map(String::from)
+    }
+
+    fn values_of(&self, name: &str) -> Option<Vec<String>> {
+        self.matches
+            .values_of(name)
+            .map(|values| values.map(String::from).collect())
+    }
+}
+
+impl PartialGridConfigBuilder for ClapPartialGridConfigBuilder<'_> {
+    fn build(self) -> Result<PartialGridConfig, ConfigurationError> {
+        let mut partial_config = PartialGridConfig::new(GridConfigSource::CommandLine);
+
+        partial_config = partial_config
+            .with_endpoints(self.values_of("connect"))
+            .with_rest_api_endpoint(self.value_of("bind"))
+            .with_database_url(self.value_of("database_url"))
+            .with_reindex_from_commit(self.value_of("reindex_from_commit"))
+            .with_reindex_namespaces(self.values_of("reindex_namespace"));
+
+        #[cfg(feature = "splinter-support")]
+        {
+            partial_config = partial_config
+                .with_admin_key_dir(self.value_of("admin_key_dir"))
+                .with_service_data_policy(self.value_of("disbanded_circuit_data"));
+        }
+
+        #[cfg(feature = "integration")]
+        {
+            partial_config = partial_config.with_key_file_name(self.value_of("key"));
+        }
+
+        #[cfg(feature = "track-and-trace")]
+        {
+            partial_config = partial_config.with_alert_rules_file(self.value_of("alert_rules"));
+        }
+
+        #[cfg(feature = "event")]
+        {
+            partial_config =
+                partial_config.with_event_failure_policy(self.value_of("event_failure_policy"));
+        }
+
+        #[cfg(feature = "event-sinks")]
+        {
+            partial_config = partial_config.with_event_sinks(self.values_of("event_sink"));
+        }
+
+        Ok(partial_config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the values given as arguments are set, and that those which were not given are
+    /// left unset for the other sources to provide.
+    #[test]
+    fn test_setup_command_line_config() {
+        let matches = clap::App::new("testapp")
+            .arg(clap::Arg::with_name("connect").short("C").takes_value(true))
+            .arg(clap::Arg::with_name("bind").short("b").takes_value(true))
+            .arg(
+                clap::Arg::with_name("database_url")
+                    .long("database-url")
+                    .takes_value(true),
+            )
+            .get_matches_from(vec![
+                "testapp",
+                "-C",
+                "splinter:http://splinterd:8085",
+                "-b",
+                "rest_api:8080",
+            ]);
+
+        let generated_clap_config = ClapPartialGridConfigBuilder::new(&matches)
+            .build()
+            .expect("Unable to build command line config");
+
+        assert_eq!(
+            generated_clap_config.source(),
+            GridConfigSource::CommandLine
+        );
+        assert_eq!(
+            generated_clap_config.endpoints(),
+            Some(vec!["splinter:http://splinterd:8085".to_string()])
+        );
+        assert_eq!(
+            generated_clap_config.rest_api_endpoint(),
+            Some("rest_api:8080".to_string())
+        );
+        assert_eq!(generated_clap_config.database_url(), None);
+        assert_eq!(generated_clap_config.reindex_namespaces(), None);
+    }
+}
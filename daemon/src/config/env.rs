@@ -0,0 +1,214 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::env;
+
+use crate::config::{GridConfigSource, PartialGridConfig, PartialGridConfigBuilder};
+use crate::error::ConfigurationError;
+
+const CONNECT_ENV: &str = "GRIDD_CONNECT";
+const BIND_ENV: &str = "GRIDD_BIND";
+const DATABASE_URL_ENV: &str = "GRIDD_DATABASE_URL";
+#[cfg(feature = "splinter-support")]
+const ADMIN_KEY_DIR_ENV: &str = "GRIDD_ADMIN_KEY_DIR";
+#[cfg(feature = "integration")]
+const KEY_ENV: &str = "GRIDD_KEY";
+#[cfg(feature = "track-and-trace")]
+const ALERT_RULES_ENV: &str = "GRIDD_ALERT_RULES";
+#[cfg(feature = "event")]
+const EVENT_FAILURE_POLICY_ENV: &str = "GRIDD_EVENT_FAILURE_POLICY";
+#[cfg(feature = "event-sinks")]
+const EVENT_SINKS_ENV: &str = "GRIDD_EVENT_SINKS";
+#[cfg(feature = "splinter-support")]
+const DISBANDED_CIRCUIT_DATA_ENV: &str = "GRIDD_DISBANDED_CIRCUIT_DATA";
+
+pub trait EnvStore {
+    /// Returns an environment variable for a given key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A string slice of the name of the environment variable
+    fn get(&self, key: &str) -> Option<String>;
+}
+
+pub struct GridOsEnvStore;
+
+impl EnvStore for GridOsEnvStore {
+    fn get(&self, key: &str) -> Option<String> {
+        env::var(key).ok()
+    }
+}
+
+pub struct EnvPartialGridConfigBuilder<S: EnvStore> {
+    store: S,
+}
+
+impl EnvPartialGridConfigBuilder<GridOsEnvStore> {
+    pub fn new() -> Self {
+        EnvPartialGridConfigBuilder {
+            store: GridOsEnvStore {},
+        }
+    }
+}
+
+impl Default for EnvPartialGridConfigBuilder<GridOsEnvStore> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: EnvStore> EnvPartialGridConfigBuilder<S> {
+    /// Returns an `EnvPartialGridConfigBuilder` that will fetch data from the given store.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - An instance of `EnvStore`
+    ///
+    #[cfg(test)]
+    pub fn from_store(store: S) -> Self {
+        EnvPartialGridConfigBuilder { store }
+    }
+
+    /// Returns the comma-separated values of a list, such as the endpoints to connect to
+    fn get_list(&self, key: &str) -> Option<Vec<String>> {
+        self.store.get(key).map(|list| {
+            list.split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(String::from)
+                .collect()
+        })
+    }
+}
+
+impl<S: EnvStore> PartialGridConfigBuilder for EnvPartialGridConfigBuilder<S> {
+    fn build(self) -> Result<PartialGridConfig, ConfigurationError> {
+        let mut partial_config = PartialGridConfig::new(GridConfigSource::Environment);
+
+        partial_config = partial_config
+            .with_endpoints(self.get_list(CONNECT_ENV))
+            .with_rest_api_endpoint(self.store.get(BIND_ENV))
+            .with_database_url(self.store.get(DATABASE_URL_ENV));
+
+        #[cfg(feature = "splinter-support")]
+        {
+            partial_config = partial_config
+                .with_admin_key_dir(self.store.get(ADMIN_KEY_DIR_ENV))
+                .with_service_data_policy(self.store.get(DISBANDED_CIRCUIT_DATA_ENV));
+        }
+
+        #[cfg(feature = "integration")]
+        {
+            partial_config = partial_config.with_key_file_name(self.store.get(KEY_ENV));
+        }
+
+        #[cfg(feature = "track-and-trace")]
+        {
+            partial_config = partial_config.with_alert_rules_file(self.store.get(ALERT_RULES_ENV));
+        }
+
+        #[cfg(feature = "event")]
+        {
+            partial_config =
+                partial_config.with_event_failure_policy(self.store.get(EVENT_FAILURE_POLICY_ENV));
+        }
+
+        #[cfg(feature = "event-sinks")]
+        {
+            partial_config = partial_config.with_event_sinks(self.get_list(EVENT_SINKS_ENV));
+        }
+
+        Ok(partial_config)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Implementation of `EnvStore` that supports arbitrary hashmaps
+    pub(crate) struct GridHashmapEnvStore {
+        internal: HashMap<String, String>,
+    }
+
+    impl GridHashmapEnvStore {
+        /// Returns a `GridHashmapEnvStore` that will fetch an environment variable using the
+        /// given hashmap.
+        ///
+        /// # Arguments
+        ///
+        /// * `internal` - The internal map to obtain values from
+        ///
+        pub fn new(internal: HashMap<String, String>) -> GridHashmapEnvStore {
+            GridHashmapEnvStore { internal }
+        }
+    }
+
+    impl EnvStore for GridHashmapEnvStore {
+        fn get(&self, key: &str) -> Option<String> {
+            self.internal.get(key).map(ToOwned::to_owned)
+        }
+    }
+
+    /// Verify that a `PartialGridConfig` built from unset environment variables has no values set.
+    #[test]
+    fn test_empty_env_config() {
+        let store = GridHashmapEnvStore::new(HashMap::new());
+        let empty_generated_config = EnvPartialGridConfigBuilder::from_store(store)
+            .build()
+            .expect("Unable to build `EnvPartialGridConfigBuilder`");
+
+        assert_eq!(
+            empty_generated_config.source(),
+            GridConfigSource::Environment
+        );
+        assert_eq!(empty_generated_config.endpoints(), None);
+        assert_eq!(empty_generated_config.rest_api_endpoint(), None);
+        assert_eq!(empty_generated_config.database_url(), None);
+    }
+
+    /// Verify that the values of the environment variables are set, with the endpoints split on
+    /// commas.
+    #[test]
+    fn test_setup_env_config() {
+        let mut store_internals: HashMap<String, String> = HashMap::new();
+        store_internals.insert(
+            CONNECT_ENV.to_string(),
+            "tcp://validator:4004, splinter:http://alpha:8085".to_string(),
+        );
+        store_internals.insert(
+            DATABASE_URL_ENV.to_string(),
+            "postgres://grid:secret@db/grid".to_string(),
+        );
+        let store = GridHashmapEnvStore::new(store_internals);
+        let generated_config = EnvPartialGridConfigBuilder::from_store(store)
+            .build()
+            .expect("Unable to build `EnvPartialGridConfigBuilder`");
+
+        assert_eq!(generated_config.source(), GridConfigSource::Environment);
+        assert_eq!(
+            generated_config.endpoints(),
+            Some(vec![
+                "tcp://validator:4004".to_string(),
+                "splinter:http://alpha:8085".to_string()
+            ])
+        );
+        assert_eq!(
+            generated_config.database_url(),
+            Some("postgres://grid:secret@db/grid".to_string())
+        );
+        assert_eq!(generated_config.rest_api_endpoint(), None);
+    }
+}
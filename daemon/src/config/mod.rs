@@ -0,0 +1,205 @@
+/*
+ * Copyright 2019 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! Configuration to provide the necessary values to start up the Grid daemon.
+//!
+//! These values may be sourced from command line arguments, environment variables, a TOML
+//! configuration file or pre-defined defaults. This module allows for configuration values from
+//! each of these sources to be combined into a final `GridConfig` object.
+
+mod builder;
+mod clap;
+mod default;
+#[cfg(feature = "config-env")]
+mod env;
+mod partial;
+#[cfg(feature = "config-toml")]
+mod toml;
+
+use grid_sdk::commits::store::IndexedNamespace;
+
+#[cfg(feature = "event")]
+use crate::event::checkpoint::FailurePolicy;
+#[cfg(feature = "event-sinks")]
+use crate::event::sink::EventSinkConfig;
+#[cfg(feature = "splinter-support")]
+use crate::splinter::lifecycle::ServiceDataPolicy;
+
+pub use self::clap::ClapPartialGridConfigBuilder;
+#[cfg(feature = "config-toml")]
+pub use self::toml::TomlPartialGridConfigBuilder;
+pub use builder::{GridConfigBuilder, PartialGridConfigBuilder};
+pub use default::DefaultPartialGridConfigBuilder;
+#[cfg(feature = "config-env")]
+pub use env::EnvPartialGridConfigBuilder;
+pub use partial::{GridConfigSource, PartialGridConfig};
+
+#[derive(Debug)]
+pub struct GridConfig {
+    endpoint: (String, GridConfigSource),
+    #[cfg(feature = "multi-source")]
+    endpoints: Vec<String>,
+    rest_api_endpoint: (String, GridConfigSource),
+    database_url: (String, GridConfigSource),
+    #[cfg(feature = "splinter-support")]
+    admin_key_dir: (String, GridConfigSource),
+    #[cfg(feature = "integration")]
+    key_file_name: (String, GridConfigSource),
+    #[cfg(feature = "track-and-trace")]
+    alert_rules_file: Option<(String, GridConfigSource)>,
+    reindex_from_commit: Option<i64>,
+    reindex_namespaces: Vec<IndexedNamespace>,
+    #[cfg(feature = "event")]
+    event_failure_policy: (FailurePolicy, GridConfigSource),
+    #[cfg(feature = "event-sinks")]
+    event_sinks: (Vec<EventSinkConfig>, GridConfigSource),
+    #[cfg(feature = "splinter-support")]
+    service_data_policy: (ServiceDataPolicy, GridConfigSource),
+}
+
+impl GridConfig {
+    /// The primary connection endpoint; the REST API submits batches to its network
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint.0
+    }
+
+    /// Every connection endpoint that commits are received from, starting with the primary one
+    #[cfg(feature = "multi-source")]
+    pub fn endpoints(&self) -> &[String] {
+        &self.endpoints
+    }
+
+    // dead_code allowed because this isn't used when splinter and sawtooth
+    // features are both off, as is the case with --no-default-features
+    #[allow(dead_code)]
+    pub fn rest_api_endpoint(&self) -> &str {
+        &self.rest_api_endpoint.0
+    }
+
+    // dead_code allowed because this isn't used when splinter and sawtooth
+    // features are both off, as is the case with --no-default-features
+    #[allow(dead_code)]
+    pub fn database_url(&self) -> &str {
+        &self.database_url.0
+    }
+
+    #[cfg(feature = "splinter-support")]
+    pub fn admin_key_dir(&self) -> &str {
+        &self.admin_key_dir.0
+    }
+
+    #[cfg(feature = "integration")]
+    pub fn key_file_name(&self) -> &str {
+        &self.key_file_name.0
+    }
+
+    #[cfg(feature = "track-and-trace")]
+    pub fn alert_rules_file(&self) -> Option<&str> {
+        self.alert_rules_file
+            .as_ref()
+            .map(|(file, _)| file.as_str())
+    }
+
+    /// The commit height from which the database is reindexed on startup, if any
+    pub fn reindex_from_commit(&self) -> Option<i64> {
+        self.reindex_from_commit
+    }
+
+    /// The namespaces that are reindexed; all namespaces are reindexed if empty
+    pub fn reindex_namespaces(&self) -> &[IndexedNamespace] {
+        &self.reindex_namespaces
+    }
+
+    /// What is done when an event handler fails to handle a commit
+    #[cfg(feature = "event")]
+    pub fn event_failure_policy(&self) -> FailurePolicy {
+        self.event_failure_policy.0
+    }
+
+    /// The sinks that entity changes are published to
+    #[cfg(feature = "event-sinks")]
+    pub fn event_sinks(&self) -> &[EventSinkConfig] {
+        &self.event_sinks.0
+    }
+
+    /// What is done with a service's data once its circuit is disbanded or abandoned
+    #[cfg(feature = "splinter-support")]
+    pub fn service_data_policy(&self) -> ServiceDataPolicy {
+        self.service_data_policy.0
+    }
+
+    /// Logs each value along with its source. The database URL is not logged, as it may contain
+    /// a password.
+    pub fn log_as_debug(&self) {
+        debug!(
+            "Grid Config: connect: {} (source: {})",
+            self.endpoint(),
+            self.endpoint.1
+        );
+        #[cfg(feature = "multi-source")]
+        debug!("Grid Config: endpoints: {:?}", self.endpoints());
+        debug!(
+            "Grid Config: bind: {} (source: {})",
+            self.rest_api_endpoint(),
+            self.rest_api_endpoint.1
+        );
+        debug!(
+            "Grid Config: database_url: (source: {})",
+            self.database_url.1
+        );
+        #[cfg(feature = "splinter-support")]
+        {
+            debug!(
+                "Grid Config: admin_key_dir: {} (source: {})",
+                self.admin_key_dir(),
+                self.admin_key_dir.1
+            );
+            debug!(
+                "Grid Config: disbanded_circuit_data: {:?} (source: {})",
+                self.service_data_policy(),
+                self.service_data_policy.1
+            );
+        }
+        #[cfg(feature = "integration")]
+        debug!(
+            "Grid Config: key: {} (source: {})",
+            self.key_file_name(),
+            self.key_file_name.1
+        );
+        #[cfg(feature = "track-and-trace")]
+        {
+            if let Some((file, source)) = &self.alert_rules_file {
+                debug!("Grid Config: alert_rules: {} (source: {})", file, source);
+            }
+        }
+        #[cfg(feature = "event")]
+        debug!(
+            "Grid Config: event_failure_policy: {:?} (source: {})",
+            self.event_failure_policy(),
+            self.event_failure_policy.1
+        );
+        #[cfg(feature = "event-sinks")]
+        debug!(
+            "Grid Config: event_sinks: {:?} (source: {})",
+            self.event_sinks()
+                .iter()
+                .map(|sink| sink.name.as_str())
+                .collect::<Vec<_>>(),
+            self.event_sinks.1
+        );
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+/// Where a configuration value was set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridConfigSource {
+    Default,
+    #[cfg(feature = "config-toml")]
+    ConfigFile {
+        file: String,
+    },
+    #[cfg(feature = "config-env")]
+    Environment,
+    CommandLine,
+}
+
+impl fmt::Display for GridConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridConfigSource::Default => f.write_str("the defaults"),
+            #[cfg(feature = "config-toml")]
+            GridConfigSource::ConfigFile { file } => f.write_str(file),
+            #[cfg(feature = "config-env")]
+            GridConfigSource::Environment => f.write_str("the environment"),
+            GridConfigSource::CommandLine => f.write_str("the command line"),
+        }
+    }
+}
+
+/// The configuration values set by one source, which are validated once every source is combined
+pub struct PartialGridConfig {
+    source: GridConfigSource,
+    endpoints: Option<Vec<String>>,
+    rest_api_endpoint: Option<String>,
+    database_url: Option<String>,
+    #[cfg(feature = "splinter-support")]
+    admin_key_dir: Option<String>,
+    #[cfg(feature = "integration")]
+    key_file_name: Option<String>,
+    #[cfg(feature = "track-and-trace")]
+    alert_rules_file: Option<String>,
+    reindex_from_commit: Option<String>,
+    reindex_namespaces: Option<Vec<String>>,
+    #[cfg(feature = "event")]
+    event_failure_policy: Option<String>,
+    #[cfg(feature = "event-sinks")]
+    event_sinks: Option<Vec<String>>,
+    #[cfg(feature = "splinter-support")]
+    service_data_policy: Option<String>,
+}
+
+impl PartialGridConfig {
+    pub fn new(source: GridConfigSource) -> Self {
+        PartialGridConfig {
+            source,
+            endpoints: None,
+            rest_api_endpoint: None,
+            database_url: None,
+            #[cfg(feature = "splinter-support")]
+            admin_key_dir: None,
+            #[cfg(feature = "integration")]
+            key_file_name: None,
+            #[cfg(feature = "track-and-trace")]
+            alert_rules_file: None,
+            reindex_from_commit: None,
+            reindex_namespaces: None,
+            #[cfg(feature = "event")]
+            event_failure_policy: None,
+            #[cfg(feature = "event-sinks")]
+            event_sinks: None,
+            #[cfg(feature = "splinter-support")]
+            service_data_policy: None,
+        }
+    }
+
+    pub fn source(&self) -> GridConfigSource {
+        self.source.clone()
+    }
+
+    pub fn endpoints(&self) -> Option<Vec<String>> {
+        self.endpoints.clone()
+    }
+
+    pub fn rest_api_endpoint(&self) -> Option<String> {
+        self.rest_api_endpoint.clone()
+    }
+
+    pub fn database_url(&self) -> Option<String> {
+        self.database_url.clone()
+    }
+
+    #[cfg(feature = "splinter-support")]
+    pub fn admin_key_dir(&self) -> Option<String> {
+        self.admin_key_dir.clone()
+    }
+
+    #[cfg(feature = "integration")]
+    pub fn key_file_name(&self) -> Option<String> {
+        self.key_file_name.clone()
+    }
+
+    #[cfg(feature = "track-and-trace")]
+    pub fn alert_rules_file(&self) -> Option<String> {
+        self.alert_rules_file.clone()
+    }
+
+    pub fn reindex_from_commit(&self) -> Option<String> {
+        self.reindex_from_commit.clone()
+    }
+
+    pub fn reindex_namespaces(&self) -> Option<Vec<String>> {
+        self.reindex_namespaces.clone()
+    }
+
+    #[cfg(feature = "event")]
+    pub fn event_failure_policy(&self) -> Option<String> {
+        self.event_failure_policy.clone()
+    }
+
+    #[cfg(feature = "event-sinks")]
+    pub fn event_sinks(&self) -> Option<Vec<String>> {
+        self.event_sinks.clone()
+    }
+
+    #[cfg(feature = "splinter-support")]
+    pub fn service_data_policy(&self) -> Option<String> {
+        self.service_data_policy.clone()
+    }
+
+    /// Adds the `endpoints` that commits are received from, the first of which is the primary one
+    pub fn with_endpoints(mut self, endpoints: Option<Vec<String>>) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Adds the `rest_api_endpoint` that the REST API binds to
+    pub fn with_rest_api_endpoint(mut self, rest_api_endpoint: Option<String>) -> Self {
+        self.rest_api_endpoint = rest_api_endpoint;
+        self
+    }
+
+    /// Adds the `database_url` of the database that commits are indexed into
+    pub fn with_database_url(mut self, database_url: Option<String>) -> Self {
+        self.database_url = database_url;
+        self
+    }
+
+    /// Adds the `admin_key_dir` containing the Scabbard admin key files
+    #[cfg(feature = "splinter-support")]
+    pub fn with_admin_key_dir(mut self, admin_key_dir: Option<String>) -> Self {
+        self.admin_key_dir = admin_key_dir;
+        self
+    }
+
+    /// Adds the base name of the private signing key file used by the integration endpoints
+    #[cfg(feature = "integration")]
+    pub fn with_key_file_name(mut self, key_file_name: Option<String>) -> Self {
+        self.key_file_name = key_file_name;
+        self
+    }
+
+    /// Adds the JSON file containing the Track and Trace alert rules
+    #[cfg(feature = "track-and-trace")]
+    pub fn with_alert_rules_file(mut self, alert_rules_file: Option<String>) -> Self {
+        self.alert_rules_file = alert_rules_file;
+        self
+    }
+
+    /// Adds the commit height from which the database is reindexed on startup
+    pub fn with_reindex_from_commit(mut self, reindex_from_commit: Option<String>) -> Self {
+        self.reindex_from_commit = reindex_from_commit;
+        self
+    }
+
+    /// Adds the namespaces that are reindexed
+    pub fn with_reindex_namespaces(mut self, reindex_namespaces: Option<Vec<String>>) -> Self {
+        self.reindex_namespaces = reindex_namespaces;
+        self
+    }
+
+    /// Adds what is done when an event handler fails to handle a commit
+    #[cfg(feature = "event")]
+    pub fn with_event_failure_policy(mut self, event_failure_policy: Option<String>) -> Self {
+        self.event_failure_policy = event_failure_policy;
+        self
+    }
+
+    /// Adds the `NAME=URI` sinks that entity changes are published to
+    #[cfg(feature = "event-sinks")]
+    pub fn with_event_sinks(mut self, event_sinks: Option<Vec<String>>) -> Self {
+        self.event_sinks = event_sinks;
+        self
+    }
+
+    /// Adds what is done with a service's data once its circuit is disbanded or abandoned
+    #[cfg(feature = "splinter-support")]
+    pub fn with_service_data_policy(mut self, service_data_policy: Option<String>) -> Self {
+        self.service_data_policy = service_data_policy;
+        self
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io::ErrorKind;
+
+use crate::config::{GridConfigSource, PartialGridConfig, PartialGridConfigBuilder};
+use crate::error::ConfigurationError;
+
+/// The configuration file that is read when none is given
+const DEFAULT_CONFIG_FILE: &str = "/etc/grid/gridd.toml";
+
+/// The settings of a gridd configuration file, which are named after the command line options
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlGridConfig {
+    connect: Option<TomlEndpoints>,
+    bind: Option<String>,
+    database_url: Option<String>,
+    #[cfg(feature = "splinter-support")]
+    admin_key_dir: Option<String>,
+    #[cfg(feature = "integration")]
+    key: Option<String>,
+    #[cfg(feature = "track-and-trace")]
+    alert_rules: Option<String>,
+    #[cfg(feature = "event")]
+    event_failure_policy: Option<String>,
+    #[cfg(feature = "event-sinks")]
+    event_sinks: Option<Vec<String>>,
+    #[cfg(feature = "splinter-support")]
+    disbanded_circuit_data: Option<String>,
+}
+
+/// A single endpoint, or a list of endpoints to receive commits from several sources
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TomlEndpoints {
+    One(String),
+    Many(Vec<String>),
+}
+
+pub struct TomlPartialGridConfigBuilder {
+    file: String,
+    toml: String,
+}
+
+impl TomlPartialGridConfigBuilder {
+    /// Returns a `TomlPartialGridConfigBuilder` for the given TOML, as read from `file`
+    #[cfg(test)]
+    pub fn new(file: &str, toml: &str) -> Self {
+        TomlPartialGridConfigBuilder {
+            file: file.to_string(),
+            toml: toml.to_string(),
+        }
+    }
+
+    /// Reads the given configuration file, which must exist
+    pub fn from_file(file: &str) -> Result<Self, ConfigurationError> {
+        let toml = fs::read_to_string(file).map_err(|err| {
+            ConfigurationError::InvalidValue(format!("{}: unable to read file: {}", file, err))
+        })?;
+
+        Ok(TomlPartialGridConfigBuilder {
+            file: file.to_string(),
+            toml,
+        })
+    }
+
+    /// Reads the default configuration file, if it exists
+    pub fn from_default_file() -> Result<Self, ConfigurationError> {
+        match fs::read_to_string(DEFAULT_CONFIG_FILE) {
+            Ok(toml) => Ok(TomlPartialGridConfigBuilder {
+                file: DEFAULT_CONFIG_FILE.to_string(),
+                toml,
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(TomlPartialGridConfigBuilder {
+                file: DEFAULT_CONFIG_FILE.to_string(),
+                toml: String::new(),
+            }),
+            Err(err) => Err(ConfigurationError::InvalidValue(format!(
+                "{}: unable to read file: {}",
+                DEFAULT_CONFIG_FILE, err
+            ))),
+        }
+    }
+}
+
+impl PartialGridConfigBuilder for TomlPartialGridConfigBuilder {
+    fn build(self) -> Result<PartialGridConfig, ConfigurationError> {
+        let toml_config: TomlGridConfig = toml::from_str(&self.toml)
+            .map_err(|err| ConfigurationError::InvalidValue(format!("{}: {}", self.file, err)))?;
+
+        let mut partial_config =
+            PartialGridConfig::new(GridConfigSource::ConfigFile { file: self.file });
+
+        partial_config = partial_config
+            .with_endpoints(toml_config.connect.map(|endpoints| match endpoints {
+                TomlEndpoints::One(endpoint) => vec![endpoint],
+                TomlEndpoints::Many(endpoints) => endpoints,
+            }))
+            .with_rest_api_endpoint(toml_config.bind)
+            .with_database_url(toml_config.database_url);
+
+        #[cfg(feature = "splinter-support")]
+        {
+            partial_config = partial_config
+                .with_admin_key_dir(toml_config.admin_key_dir)
+                .with_service_data_policy(toml_config.disbanded_circuit_data);
+        }
+
+        #[cfg(feature = "integration")]
+        {
+            partial_config = partial_config.with_key_file_name(toml_config.key);
+        }
+
+        #[cfg(feature = "track-and-trace")]
+        {
+            partial_config = partial_config.with_alert_rules_file(toml_config.alert_rules);
+        }
+
+        #[cfg(feature = "event")]
+        {
+            partial_config =
+                partial_config.with_event_failure_policy(toml_config.event_failure_policy);
+        }
+
+        #[cfg(feature = "event-sinks")]
+        {
+            partial_config = partial_config.with_event_sinks(toml_config.event_sinks);
+        }
+
+        Ok(partial_config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the settings of a configuration file are set, and that those which are not in
+    /// the file are left unset.
+    #[test]
+    fn test_setup_toml_config() {
+        let generated_config = TomlPartialGridConfigBuilder::new(
+            "gridd.toml",
+            r#"
+            connect = "splinter:http://splinterd:8085"
+            database_url = "postgres://grid:secret@db/grid"
+            "#,
+        )
+        .build()
+        .expect("Unable to build `TomlPartialGridConfigBuilder`");
+
+        assert_eq!(
+            generated_config.source(),
+            GridConfigSource::ConfigFile {
+                file: "gridd.toml".to_string()
+            }
+        );
+        assert_eq!(
+            generated_config.endpoints(),
+            Some(vec!["splinter:http://splinterd:8085".to_string()])
+        );
+        assert_eq!(
+            generated_config.database_url(),
+            Some("postgres://grid:secret@db/grid".to_string())
+        );
+        assert_eq!(generated_config.rest_api_endpoint(), None);
+
+        let generated_config = TomlPartialGridConfigBuilder::new(
+            "gridd.toml",
+            r#"connect = ["tcp://validator:4004", "splinter:http://alpha:8085"]"#,
+        )
+        .build()
+        .expect("Unable to build `TomlPartialGridConfigBuilder`");
+
+        assert_eq!(
+            generated_config.endpoints(),
+            Some(vec![
+                "tcp://validator:4004".to_string(),
+                "splinter:http://alpha:8085".to_string()
+            ])
+        );
+    }
+
+    /// Verify that an unknown setting, or a setting of the wrong type, is an error that names the
+    /// configuration file.
+    #[test]
+    fn test_invalid_toml_config() {
+        match TomlPartialGridConfigBuilder::new("gridd.toml", r#"bnd = "0.0.0.0:8080""#).build() {
+            Err(ConfigurationError::InvalidValue(msg)) => assert!(msg.starts_with("gridd.toml: ")),
+            _ => panic!("An unknown setting was accepted"),
+        }
+
+        assert!(
+            TomlPartialGridConfigBuilder::new("gridd.toml", "bind = 8080")
+                .build()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,588 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! Threshold rules evaluated against reported Track and Trace property values.
+//!
+//! Rules are loaded from a JSON file when the daemon starts. Each time a commit reports new
+//! values for a property, the values are checked against every rule for that property and the
+//! resulting alert transitions are stored alongside the reported values, in the same commit.
+//!
+//! A pending alert is also triggered by a later value reported for any property of its record,
+//! if the rule's duration has passed by that value's timestamp, so that a value which stays out
+//! of bounds raises an alert even if nothing more is reported for the property. Only the
+//! timestamps of the reported values are used, so replaying the same commits always produces the
+//! same alerts.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+
+use grid_sdk::track_and_trace::store::{
+    Alert, ReportedValue as StoreReportedValue, TrackAndTraceStore,
+};
+
+use crate::error::DaemonError;
+
+use super::EventError;
+
+const MAX_COMMIT_NUM: i64 = i64::MAX;
+
+/// The value has violated the rule, but not for long enough to trigger the alert
+pub const ALERT_STATUS_PENDING: &str = "PENDING";
+/// The value has violated the rule for at least the rule's duration
+pub const ALERT_STATUS_ACTIVE: &str = "ACTIVE";
+/// The value returned within the rule's bounds after the alert was triggered
+pub const ALERT_STATUS_RESOLVED: &str = "RESOLVED";
+/// The value returned within the rule's bounds before the alert was triggered
+pub const ALERT_STATUS_CLEARED: &str = "CLEARED";
+
+/// A condition that a reported value is checked against.
+///
+/// Number thresholds are compared against the raw reported value, so they must be given in the
+/// same units as the property, i.e. scaled by the property's `number_exponent`. Geofence bounds
+/// are given in millionths of a degree, matching the way `LatLong` values are reported.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertCondition {
+    Above {
+        threshold: i64,
+    },
+    Below {
+        threshold: i64,
+    },
+    OutsideGeofence {
+        min_latitude: i64,
+        min_longitude: i64,
+        max_latitude: i64,
+        max_longitude: i64,
+    },
+}
+
+impl AlertCondition {
+    /// Returns whether the reported value violates this condition, or `None` if the value is of
+    /// a type this condition does not apply to.
+    pub fn is_violated_by(&self, value: &StoreReportedValue) -> Option<bool> {
+        match self {
+            AlertCondition::Above { threshold } => value.number_value.map(|n| n > *threshold),
+            AlertCondition::Below { threshold } => value.number_value.map(|n| n < *threshold),
+            AlertCondition::OutsideGeofence {
+                min_latitude,
+                min_longitude,
+                max_latitude,
+                max_longitude,
+            } => value.lat_long_value.as_ref().map(|lat_long| {
+                lat_long.0 < *min_latitude
+                    || lat_long.0 > *max_latitude
+                    || lat_long.1 < *min_longitude
+                    || lat_long.1 > *max_longitude
+            }),
+        }
+    }
+}
+
+/// A threshold rule for a Track and Trace property
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct AlertRule {
+    /// A unique name for the rule, used to identify the alerts it raises
+    pub name: String,
+    /// The name of the property the rule applies to
+    pub property_name: String,
+    /// The record the rule applies to; if not set, the rule applies to all records
+    #[serde(default)]
+    pub record_id: Option<String>,
+    pub condition: AlertCondition,
+    /// The number of seconds the condition must be violated before the alert is triggered
+    #[serde(default)]
+    pub duration: u64,
+}
+
+impl AlertRule {
+    fn applies_to(&self, value: &StoreReportedValue) -> bool {
+        self.property_name == value.property_name
+            && self
+                .record_id
+                .as_ref()
+                .map(|record_id| record_id == &value.record_id)
+                .unwrap_or(true)
+    }
+}
+
+/// Loads a list of alert rules from a JSON file
+///
+/// # Arguments
+///
+///  * `path` - The path of the file containing the rules
+pub fn load_alert_rules(path: &str) -> Result<Vec<AlertRule>, DaemonError> {
+    let file = File::open(path).map_err(|err| {
+        DaemonError::with_message(&format!(
+            "Unable to open alert rules file {}: {}",
+            path, err
+        ))
+    })?;
+
+    let rules: Vec<AlertRule> = serde_json::from_reader(file).map_err(|err| {
+        DaemonError::with_message(&format!(
+            "Unable to parse alert rules file {}: {}",
+            path, err
+        ))
+    })?;
+
+    let mut names = HashSet::new();
+    for rule in &rules {
+        if !names.insert(rule.name.as_str()) {
+            return Err(DaemonError::with_message(&format!(
+                "Alert rules file {} contains more than one rule named {}",
+                path, rule.name
+            )));
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Evaluates the alert rules against a set of newly reported values and stores any resulting
+/// alert transitions.
+///
+/// Only the most recent value reported for each record property is evaluated.
+pub fn evaluate_alert_rules(
+    rules: &[AlertRule],
+    store: &dyn TrackAndTraceStore,
+    reported_values: &[StoreReportedValue],
+    commit_num: i64,
+    service_id: Option<&str>,
+) -> Result<(), EventError> {
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    let mut latest_values: HashMap<(&str, &str), &StoreReportedValue> = HashMap::new();
+    for value in flatten_reported_values(reported_values) {
+        let key = (value.record_id.as_str(), value.property_name.as_str());
+        match latest_values.get(&key) {
+            Some(latest) if latest.timestamp >= value.timestamp => (),
+            _ => {
+                latest_values.insert(key, value);
+            }
+        }
+    }
+
+    let mut alerts = Vec::new();
+    for value in latest_values.values() {
+        for rule in rules.iter().filter(|rule| rule.applies_to(value)) {
+            let violated = match rule.condition.is_violated_by(value) {
+                Some(violated) => violated,
+                None => {
+                    warn!(
+                        "Alert rule {} does not apply to {} value of property {}",
+                        rule.name, value.data_type, value.property_name
+                    );
+                    continue;
+                }
+            };
+
+            let open_alert = store.get_open_alert(
+                &rule.name,
+                &value.record_id,
+                &value.property_name,
+                service_id,
+            )?;
+
+            if let Some(alert) = next_alert(rule, open_alert, value, violated) {
+                alerts.push(Alert {
+                    id: None,
+                    start_commit_num: commit_num,
+                    end_commit_num: MAX_COMMIT_NUM,
+                    service_id: service_id.map(String::from),
+                    ..alert
+                });
+            }
+        }
+    }
+
+    if !alerts.is_empty() {
+        debug!("Inserting {} alerts", alerts.len());
+        store.add_alerts(alerts)?;
+    }
+
+    Ok(())
+}
+
+/// Triggers the pending alerts whose rule has been violated for at least its duration by the
+/// time of the latest value reported for their record in the given values, and stores them.
+///
+/// This is run after the reported values have been evaluated, so a value that returns within
+/// bounds clears the alert instead.
+pub fn escalate_pending_alerts(
+    rules: &[AlertRule],
+    store: &dyn TrackAndTraceStore,
+    reported_values: &[StoreReportedValue],
+    commit_num: i64,
+    service_id: Option<&str>,
+) -> Result<(), EventError> {
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    let rules: HashMap<&str, &AlertRule> = rules
+        .iter()
+        .map(|rule| (rule.name.as_str(), rule))
+        .collect();
+
+    let mut reported_at: HashMap<&str, i64> = HashMap::new();
+    for value in flatten_reported_values(reported_values) {
+        let time = reported_at
+            .entry(value.record_id.as_str())
+            .or_insert(value.timestamp);
+        *time = (*time).max(value.timestamp);
+    }
+
+    let alerts = store
+        .list_alerts_by_status(ALERT_STATUS_PENDING, service_id)?
+        .into_iter()
+        .filter_map(|alert| {
+            let time = *reported_at.get(alert.record_id.as_str())?;
+            // The rule may have been removed from the rules file since the alert was started
+            let rule = rules.get(alert.rule_name.as_str())?;
+            escalated_alert(rule, alert, time)
+        })
+        .map(|alert| Alert {
+            id: None,
+            start_commit_num: commit_num,
+            end_commit_num: MAX_COMMIT_NUM,
+            ..alert
+        })
+        .collect::<Vec<_>>();
+
+    if !alerts.is_empty() {
+        debug!("Triggering {} pending alerts", alerts.len());
+        store.add_alerts(alerts)?;
+    }
+
+    Ok(())
+}
+
+fn flatten_reported_values(values: &[StoreReportedValue]) -> Vec<&StoreReportedValue> {
+    values
+        .iter()
+        .flat_map(|value| {
+            let mut values = vec![value];
+            if let Some(struct_values) = &value.struct_values {
+                values.append(&mut flatten_reported_values(struct_values));
+            }
+            values
+        })
+        .collect()
+}
+
+/// Determines the new state of an alert, given the currently open alert for the rule and the
+/// latest reported value. Returns `None` if the alert does not change.
+fn next_alert(
+    rule: &AlertRule,
+    open_alert: Option<Alert>,
+    value: &StoreReportedValue,
+    violated: bool,
+) -> Option<Alert> {
+    match (open_alert, violated) {
+        (None, true) => {
+            let (status, triggered_at) = if rule.duration == 0 {
+                (ALERT_STATUS_ACTIVE, Some(value.timestamp))
+            } else {
+                (ALERT_STATUS_PENDING, None)
+            };
+
+            Some(Alert {
+                id: None,
+                rule_name: rule.name.clone(),
+                record_id: value.record_id.clone(),
+                property_name: value.property_name.clone(),
+                status: status.to_string(),
+                started_at: value.timestamp,
+                triggered_at,
+                resolved_at: None,
+                start_commit_num: 0,
+                end_commit_num: MAX_COMMIT_NUM,
+                service_id: None,
+            })
+        }
+        (None, false) => None,
+        // Values reported out of order do not change an alert that has already started
+        (Some(alert), _) if value.timestamp < alert.started_at => None,
+        (Some(alert), true) => {
+            if alert.status == ALERT_STATUS_PENDING
+                && value.timestamp - alert.started_at >= rule.duration as i64
+            {
+                Some(Alert {
+                    status: ALERT_STATUS_ACTIVE.to_string(),
+                    triggered_at: Some(value.timestamp),
+                    ..alert
+                })
+            } else {
+                None
+            }
+        }
+        (Some(alert), false) => {
+            let status = if alert.status == ALERT_STATUS_PENDING {
+                ALERT_STATUS_CLEARED
+            } else {
+                ALERT_STATUS_RESOLVED
+            };
+
+            Some(Alert {
+                status: status.to_string(),
+                resolved_at: Some(value.timestamp),
+                ..alert
+            })
+        }
+    }
+}
+
+/// Returns the pending alert as triggered if its rule's duration has passed by the given time.
+/// The alert is triggered at the moment the duration passed, rather than when it was noticed.
+fn escalated_alert(rule: &AlertRule, alert: Alert, time: i64) -> Option<Alert> {
+    let triggered_at = alert.started_at + rule.duration as i64;
+    if alert.status == ALERT_STATUS_PENDING && time >= triggered_at {
+        Some(Alert {
+            status: ALERT_STATUS_ACTIVE.to_string(),
+            triggered_at: Some(triggered_at),
+            ..alert
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "database-sqlite")]
+    use diesel::{
+        r2d2::{ConnectionManager, Pool},
+        sqlite::SqliteConnection,
+    };
+    #[cfg(feature = "database-sqlite")]
+    use grid_sdk::migrations::run_sqlite_migrations;
+    #[cfg(feature = "database-sqlite")]
+    use grid_sdk::track_and_trace::store::DieselTrackAndTraceStore;
+    use grid_sdk::track_and_trace::store::LatLongValue;
+
+    fn number_value(number: i64, timestamp: i64) -> StoreReportedValue {
+        StoreReportedValue {
+            property_name: "temperature".to_string(),
+            record_id: "record-1".to_string(),
+            timestamp,
+            data_type: "Number".to_string(),
+            number_value: Some(number),
+            ..StoreReportedValue::default()
+        }
+    }
+
+    fn rule(duration: u64) -> AlertRule {
+        AlertRule {
+            name: "too-hot".to_string(),
+            property_name: "temperature".to_string(),
+            record_id: None,
+            condition: AlertCondition::Above { threshold: 100 },
+            duration,
+        }
+    }
+
+    /// Verifies that number and geofence conditions are evaluated correctly, and that a
+    /// condition does not apply to values of another type
+    #[test]
+    fn test_condition_violation() {
+        let above = AlertCondition::Above { threshold: 100 };
+        assert_eq!(above.is_violated_by(&number_value(101, 0)), Some(true));
+        assert_eq!(above.is_violated_by(&number_value(100, 0)), Some(false));
+
+        let below = AlertCondition::Below { threshold: 100 };
+        assert_eq!(below.is_violated_by(&number_value(99, 0)), Some(true));
+        assert_eq!(below.is_violated_by(&number_value(100, 0)), Some(false));
+
+        let geofence = AlertCondition::OutsideGeofence {
+            min_latitude: -1_000_000,
+            min_longitude: -1_000_000,
+            max_latitude: 1_000_000,
+            max_longitude: 1_000_000,
+        };
+        let mut position = StoreReportedValue {
+            data_type: "LatLong".to_string(),
+            lat_long_value: Some(LatLongValue(0, 0)),
+            ..StoreReportedValue::default()
+        };
+        assert_eq!(geofence.is_violated_by(&position), Some(false));
+        position.lat_long_value = Some(LatLongValue(0, 2_000_000));
+        assert_eq!(geofence.is_violated_by(&position), Some(true));
+
+        assert_eq!(geofence.is_violated_by(&number_value(0, 0)), None);
+    }
+
+    /// Verifies that an alert with a duration stays pending until the value has been in
+    /// violation for the duration, and is resolved once the value returns within bounds
+    #[test]
+    fn test_alert_lifecycle() {
+        let rule = rule(60);
+
+        let pending =
+            next_alert(&rule, None, &number_value(101, 1000), true).expect("Alert was not started");
+        assert_eq!(pending.status, ALERT_STATUS_PENDING);
+        assert_eq!(pending.started_at, 1000);
+        assert_eq!(pending.triggered_at, None);
+
+        assert!(next_alert(&rule, Some(pending.clone()), &number_value(105, 1030), true).is_none());
+
+        let active = next_alert(&rule, Some(pending), &number_value(105, 1060), true)
+            .expect("Alert was not triggered");
+        assert_eq!(active.status, ALERT_STATUS_ACTIVE);
+        assert_eq!(active.triggered_at, Some(1060));
+
+        let resolved = next_alert(&rule, Some(active), &number_value(90, 1100), false)
+            .expect("Alert was not resolved");
+        assert_eq!(resolved.status, ALERT_STATUS_RESOLVED);
+        assert_eq!(resolved.started_at, 1000);
+        assert_eq!(resolved.resolved_at, Some(1100));
+    }
+
+    /// Verifies that an alert without a duration is triggered immediately, and that a pending
+    /// alert is cleared if the value returns within bounds before it is triggered
+    #[test]
+    fn test_alert_immediate_and_cleared() {
+        let active = next_alert(&rule(0), None, &number_value(101, 1000), true)
+            .expect("Alert was not started");
+        assert_eq!(active.status, ALERT_STATUS_ACTIVE);
+        assert_eq!(active.triggered_at, Some(1000));
+
+        let rule = rule(60);
+        let pending =
+            next_alert(&rule, None, &number_value(101, 1000), true).expect("Alert was not started");
+        let cleared = next_alert(&rule, Some(pending), &number_value(99, 1010), false)
+            .expect("Alert was not cleared");
+        assert_eq!(cleared.status, ALERT_STATUS_CLEARED);
+        assert_eq!(cleared.triggered_at, None);
+        assert_eq!(cleared.resolved_at, Some(1010));
+
+        assert!(next_alert(&rule, None, &number_value(99, 1020), false).is_none());
+    }
+
+    /// Verifies that a pending alert is triggered once its duration has passed, even though no
+    /// new value was reported, and that it is triggered at the time the duration passed
+    #[test]
+    fn test_escalate_pending_alert() {
+        let rule = rule(60);
+        let pending =
+            next_alert(&rule, None, &number_value(101, 1000), true).expect("Alert was not started");
+
+        assert!(escalated_alert(&rule, pending.clone(), 1059).is_none());
+
+        let active = escalated_alert(&rule, pending, 1200).expect("Alert was not triggered");
+        assert_eq!(active.status, ALERT_STATUS_ACTIVE);
+        assert_eq!(active.started_at, 1000);
+        assert_eq!(active.triggered_at, Some(1060));
+
+        assert!(escalated_alert(&rule, active, 1300).is_none());
+    }
+
+    /// Verifies that a pending alert is only triggered by a value reported for its record after
+    /// its duration has passed, whatever the property, so that the alerts depend on nothing but
+    /// the reported values
+    #[cfg(feature = "database-sqlite")]
+    #[test]
+    fn test_escalate_pending_alerts_by_reported_time() {
+        let store = DieselTrackAndTraceStore::new(create_connection_pool_and_migrate());
+        let rules = vec![rule(60)];
+
+        evaluate_alert_rules(&rules, &store, &[number_value(101, 1000)], 1, None)
+            .expect("Unable to evaluate alert rules");
+
+        let humidity = |record_id: &str, timestamp| StoreReportedValue {
+            property_name: "humidity".to_string(),
+            record_id: record_id.to_string(),
+            ..number_value(50, timestamp)
+        };
+
+        escalate_pending_alerts(&rules, &store, &[humidity("record-1", 1030)], 2, None)
+            .expect("Unable to escalate alerts");
+        escalate_pending_alerts(&rules, &store, &[humidity("record-2", 1100)], 3, None)
+            .expect("Unable to escalate alerts");
+        assert_eq!(
+            store
+                .list_alerts_by_status(ALERT_STATUS_PENDING, None)
+                .expect("Unable to list alerts")
+                .len(),
+            1
+        );
+
+        escalate_pending_alerts(&rules, &store, &[humidity("record-1", 1100)], 4, None)
+            .expect("Unable to escalate alerts");
+        assert!(store
+            .list_alerts_by_status(ALERT_STATUS_PENDING, None)
+            .expect("Unable to list alerts")
+            .is_empty());
+
+        let active = store
+            .list_alerts_by_status(ALERT_STATUS_ACTIVE, None)
+            .expect("Unable to list alerts");
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].triggered_at, Some(1060));
+        assert_eq!(active[0].start_commit_num, 4);
+    }
+
+    /// Verifies that rules are parsed from their JSON representation
+    #[test]
+    fn test_parse_rules() {
+        let rules: Vec<AlertRule> = serde_json::from_str(
+            r#"[
+                {
+                    "name": "too-hot",
+                    "property_name": "temperature",
+                    "condition": { "type": "above", "threshold": 100 },
+                    "duration": 60
+                },
+                {
+                    "name": "off-route",
+                    "property_name": "location",
+                    "record_id": "record-1",
+                    "condition": {
+                        "type": "outside_geofence",
+                        "min_latitude": -1000000,
+                        "min_longitude": -1000000,
+                        "max_latitude": 1000000,
+                        "max_longitude": 1000000
+                    }
+                }
+            ]"#,
+        )
+        .expect("Unable to parse rules");
+
+        assert_eq!(rules[0], rule(60));
+        assert_eq!(rules[1].record_id, Some("record-1".to_string()));
+        assert_eq!(rules[1].duration, 0);
+    }
+
+    #[cfg(feature = "database-sqlite")]
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
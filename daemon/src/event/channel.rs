@@ -0,0 +1,231 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! Passes the commits received by several event processors to a single dispatcher, which runs on
+//! its own thread
+
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+
+use super::{Checkpoint, CommitEvent, EventDispatcher, EventError, EventHandler};
+
+pub enum EventCmd {
+    Event(CommitEvent),
+    /// Stops the commits of a service from being dispatched. The given sender is sent `()` once
+    /// the commits sent before the fence have been dispatched.
+    Fence(String, Sender<()>),
+    Exit,
+}
+
+/// Forwards each commit to the dispatcher thread
+pub struct ChannelEventHandler {
+    sender: Sender<EventCmd>,
+}
+
+impl ChannelEventHandler {
+    pub fn new(sender: Sender<EventCmd>) -> Self {
+        Self { sender }
+    }
+}
+
+impl EventHandler for ChannelEventHandler {
+    fn name(&self) -> &str {
+        "channel"
+    }
+
+    fn handle_event(&self, event: &CommitEvent) -> Result<(), EventError> {
+        self.sender
+            .send(EventCmd::Event(event.clone()))
+            .map_err(|_| EventError("Unable to send event due to closed channel".into()))
+    }
+
+    /// The checkpoints are kept by the handlers that the events are forwarded to
+    fn checkpoint(&self, _service_id: Option<&str>) -> Result<Option<Checkpoint>, EventError> {
+        Ok(None)
+    }
+
+    fn cloned_box(&self) -> Box<dyn EventHandler> {
+        Box::new(ChannelEventHandler {
+            sender: self.sender.clone(),
+        })
+    }
+}
+
+/// Starts a thread with the given name that dispatches the commits sent to it, until it is sent
+/// `EventCmd::Exit` or every sender is dropped
+pub fn spawn_dispatcher(
+    name: &str,
+    mut dispatcher: EventDispatcher,
+) -> Result<(Sender<EventCmd>, thread::JoinHandle<()>), EventError> {
+    let (sender, receiver) = channel();
+
+    let join_handle = thread::Builder::new()
+        .name(name.into())
+        .spawn(move || {
+            let mut fenced = HashSet::new();
+            loop {
+                match receiver.recv() {
+                    Ok(EventCmd::Event(event)) => match &event.service_id {
+                        Some(service_id) if fenced.contains(service_id) => debug!(
+                            "Service {} is fenced; dropping commit {}",
+                            service_id, event.id
+                        ),
+                        _ => dispatcher.dispatch(&event),
+                    },
+                    Ok(EventCmd::Fence(service_id, done)) => {
+                        fenced.insert(service_id);
+                        // The fence is in place whether or not it is still waited on
+                        let _ = done.send(());
+                    }
+                    Ok(EventCmd::Exit) | Err(_) => break,
+                }
+            }
+        })
+        .map_err(|err| EventError(format!("Unable to spawn {} thread: {}", name, err)))?;
+
+    Ok((sender, join_handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use crate::event::checkpoint::FailurePolicy;
+
+    /// Records the commits it handles
+    #[derive(Clone, Default)]
+    struct RecordingHandler {
+        handled: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl EventHandler for RecordingHandler {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn handle_event(&self, event: &CommitEvent) -> Result<(), EventError> {
+            self.handled
+                .lock()
+                .expect("Lock was poisoned")
+                .push(event.id.clone());
+            Ok(())
+        }
+
+        fn checkpoint(&self, _service_id: Option<&str>) -> Result<Option<Checkpoint>, EventError> {
+            Ok(None)
+        }
+
+        fn cloned_box(&self) -> Box<dyn EventHandler> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn commit(service_id: &str, id: &str) -> CommitEvent {
+        CommitEvent {
+            service_id: Some(service_id.to_string()),
+            id: id.to_string(),
+            height: None,
+            state_changes: vec![],
+        }
+    }
+
+    /// Verify that the commits of a service sent before it is fenced are dispatched before the
+    /// fence is acknowledged, and that its commits sent after are dropped while the other
+    /// services' commits are still dispatched
+    #[test]
+    fn fence_service() {
+        let handler = RecordingHandler::default();
+        let (sender, join_handle) = spawn_dispatcher(
+            "test-dispatcher",
+            EventDispatcher::new(vec![handler.cloned_box()], FailurePolicy::Retry),
+        )
+        .expect("Unable to spawn dispatcher");
+
+        let channel_handler = ChannelEventHandler::new(sender.clone());
+        channel_handler
+            .handle_event(&commit("circuit-a::a000", "commit-1"))
+            .expect("Unable to send commit");
+
+        let (done_sender, done_receiver) = channel();
+        sender
+            .send(EventCmd::Fence("circuit-a::a000".into(), done_sender))
+            .expect("Unable to send fence");
+        done_receiver.recv().expect("Fence was not acknowledged");
+        assert_eq!(
+            *handler.handled.lock().expect("Lock was poisoned"),
+            vec!["commit-1"]
+        );
+
+        channel_handler
+            .handle_event(&commit("circuit-a::a000", "commit-2"))
+            .expect("Unable to send commit");
+        channel_handler
+            .handle_event(&commit("circuit-b::b000", "commit-3"))
+            .expect("Unable to send commit");
+        sender.send(EventCmd::Exit).expect("Unable to send exit");
+        join_handle.join().expect("Unable to join dispatcher");
+
+        assert_eq!(
+            *handler.handled.lock().expect("Lock was poisoned"),
+            vec!["commit-1", "commit-3"]
+        );
+    }
+
+    /// Verify that the dispatcher thread stops once every sender is dropped, after dispatching
+    /// the commits sent to it
+    #[test]
+    fn stop_when_senders_dropped() {
+        let handler = RecordingHandler::default();
+        let (sender, join_handle) = spawn_dispatcher(
+            "test-dispatcher",
+            EventDispatcher::new(vec![handler.cloned_box()], FailurePolicy::Retry),
+        )
+        .expect("Unable to spawn dispatcher");
+
+        let channel_handler = ChannelEventHandler::new(sender);
+        let cloned_handler = channel_handler.cloned_box();
+        channel_handler
+            .handle_event(&commit("circuit-a::a000", "commit-1"))
+            .expect("Unable to send commit");
+        drop(channel_handler);
+        cloned_handler
+            .handle_event(&commit("circuit-a::a000", "commit-2"))
+            .expect("Unable to send commit");
+
+        drop(cloned_handler);
+        join_handle.join().expect("Unable to join dispatcher");
+
+        assert_eq!(
+            *handler.handled.lock().expect("Lock was poisoned"),
+            vec!["commit-1", "commit-2"]
+        );
+    }
+
+    /// Verify that a commit cannot be forwarded once the dispatcher has stopped
+    #[test]
+    fn forward_to_stopped_dispatcher() {
+        let (sender, receiver) = channel();
+        drop(receiver);
+
+        assert!(ChannelEventHandler::new(sender)
+            .handle_event(&commit("circuit-a::a000", "commit-1"))
+            .is_err());
+    }
+}
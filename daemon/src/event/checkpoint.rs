@@ -0,0 +1,483 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! At-least-once delivery of commit events to event handlers.
+//!
+//! Each handler keeps a durable checkpoint of the last commit it has processed from each service.
+//! When events are requested again, they are requested from the earliest of the handlers'
+//! checkpoints, and each handler skips the commits up to and including its own checkpoint. Commits
+//! that carry a height are skipped by height, so a commit that replaces the checkpoint, or one
+//! that replaces a commit already skipped, ends the skipping. A handler that fails to handle a
+//! commit is retried or halted according to the failure policy, so a commit is never skipped.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use super::{CommitEvent, EventError, EventHandler};
+
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// The last commit from a service that an event handler has durably processed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub commit_id: String,
+    /// The number of the commit in the commit store, or `None` if the commit is no longer known
+    pub commit_num: Option<i64>,
+}
+
+/// What is done when an event handler fails to handle a commit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// The commit is retried, with an increasing delay, until it is handled
+    Retry,
+    /// No further commits are passed to the handler until gridd is restarted, at which point the
+    /// handler resumes from its checkpoint
+    Halt,
+}
+
+impl FailurePolicy {
+    /// Runs the operation until it succeeds. Under the halt policy, the first error is returned.
+    pub fn run<E, F>(&self, description: &str, mut operation: F) -> Result<(), E>
+    where
+        E: fmt::Display,
+        F: FnMut() -> Result<(), E>,
+    {
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+        loop {
+            match operation() {
+                Ok(()) => return Ok(()),
+                Err(err) if *self == FailurePolicy::Retry => {
+                    warn!(
+                        "Unable to {}, retrying in {}s: {}",
+                        description,
+                        retry_delay.as_secs(),
+                        err
+                    );
+                    thread::sleep(retry_delay);
+                    retry_delay = std::cmp::min(retry_delay * 2, MAX_RETRY_DELAY);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl FromStr for FailurePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "retry" => Ok(FailurePolicy::Retry),
+            "halt" => Ok(FailurePolicy::Halt),
+            _ => Err(format!("'{}' is not one of retry or halt", s)),
+        }
+    }
+}
+
+/// Passes commit events to event handlers, resuming each handler after its checkpoint
+pub struct EventDispatcher {
+    handlers: Vec<DispatchedHandler>,
+    failure_policy: FailurePolicy,
+}
+
+struct DispatchedHandler {
+    handler: Box<dyn EventHandler>,
+    /// The checkpoint of each service whose events are being requested again; the handler is
+    /// not passed the service's events until its checkpoint has been passed
+    skip_through: HashMap<Option<String>, SkipThrough>,
+    halted: bool,
+}
+
+/// The progress of a handler through the events that precede its checkpoint
+struct SkipThrough {
+    checkpoint: Checkpoint,
+    /// The height of the last event skipped
+    skipped_height: Option<u64>,
+}
+
+/// What is done with an event while a handler is skipping through its checkpoint
+#[derive(Debug, PartialEq, Eq)]
+enum Skip {
+    /// The event precedes the checkpoint, so it has already been processed
+    Processed,
+    /// The event is the checkpoint; the events after it are passed to the handler
+    Checkpoint,
+    /// The event follows the checkpoint or replaces a commit the handler processed, so it and
+    /// the events after it are passed to the handler
+    Replaced,
+}
+
+impl SkipThrough {
+    fn new(checkpoint: Checkpoint) -> Self {
+        Self {
+            checkpoint,
+            skipped_height: None,
+        }
+    }
+
+    fn skip(&mut self, event: &CommitEvent) -> Skip {
+        if event.id == self.checkpoint.commit_id {
+            return Skip::Checkpoint;
+        }
+
+        // Without heights, the events are skipped until the checkpoint is seen
+        let (height, checkpoint_height) = match (event.height, self.checkpoint.commit_num) {
+            (Some(height), Some(commit_num)) => (height, commit_num),
+            _ => return Skip::Processed,
+        };
+
+        // An event at or below a height that was already skipped is from a fork
+        if matches!(self.skipped_height, Some(skipped) if height <= skipped) {
+            return Skip::Replaced;
+        }
+
+        if i64::try_from(height).map_or(false, |height| height < checkpoint_height) {
+            self.skipped_height = Some(height);
+            Skip::Processed
+        } else {
+            Skip::Replaced
+        }
+    }
+}
+
+impl EventDispatcher {
+    pub fn new(handlers: Vec<Box<dyn EventHandler>>, failure_policy: FailurePolicy) -> Self {
+        Self {
+            handlers: handlers
+                .into_iter()
+                .map(|handler| DispatchedHandler {
+                    handler,
+                    skip_through: HashMap::new(),
+                    halted: false,
+                })
+                .collect(),
+            failure_policy,
+        }
+    }
+
+    /// Returns the commit after which a service's events are requested: the earliest of the
+    /// handlers' checkpoints, or `None` to request all of the service's events if a handler has
+    /// not processed any of them. The handlers with a later checkpoint skip the events up to and
+    /// including it.
+    pub fn resume_point(&mut self, service_id: Option<&str>) -> Result<Option<String>, EventError> {
+        let mut checkpoints = Vec::with_capacity(self.handlers.len());
+        for dispatched in &self.handlers {
+            let checkpoint = dispatched.handler.checkpoint(service_id)?;
+            if let Some(Checkpoint {
+                ref commit_id,
+                commit_num: None,
+            }) = checkpoint
+            {
+                warn!(
+                    "Checkpoint {} of event handler {} is no longer known; the handler will \
+                    resume from the earliest of the other checkpoints",
+                    commit_id,
+                    dispatched.handler.name()
+                );
+            }
+            checkpoints.push(checkpoint);
+        }
+
+        let resume_point = if checkpoints.iter().any(Option::is_none) {
+            None
+        } else {
+            checkpoints
+                .iter()
+                .flatten()
+                .filter_map(|checkpoint| Some((checkpoint.commit_num?, &checkpoint.commit_id)))
+                .min()
+                .map(|(_, commit_id)| commit_id.to_string())
+        };
+
+        for (dispatched, checkpoint) in self.handlers.iter_mut().zip(checkpoints) {
+            let service_id = service_id.map(ToString::to_string);
+            match checkpoint {
+                Some(
+                    checkpoint @ Checkpoint {
+                        commit_num: Some(_),
+                        ..
+                    },
+                ) if Some(&checkpoint.commit_id) != resume_point.as_ref() => {
+                    dispatched
+                        .skip_through
+                        .insert(service_id, SkipThrough::new(checkpoint));
+                }
+                _ => {
+                    dispatched.skip_through.remove(&service_id);
+                }
+            }
+        }
+
+        Ok(resume_point)
+    }
+
+    /// Passes the event to each handler that has not already processed it
+    pub fn dispatch(&mut self, event: &CommitEvent) {
+        let failure_policy = self.failure_policy;
+
+        for dispatched in self.handlers.iter_mut().filter(|handler| !handler.halted) {
+            let name = dispatched.handler.name().to_string();
+
+            if let Some(skip_through) = dispatched.skip_through.get_mut(&event.service_id) {
+                match skip_through.skip(event) {
+                    Skip::Processed => {
+                        debug!(
+                            "Event handler {} has already processed commit {}; skipping",
+                            name, event.id
+                        );
+                        continue;
+                    }
+                    Skip::Checkpoint => {
+                        dispatched.skip_through.remove(&event.service_id);
+                        debug!(
+                            "Event handler {} has already processed commit {}; skipping",
+                            name, event.id
+                        );
+                        continue;
+                    }
+                    Skip::Replaced => {
+                        info!(
+                            "Commit {} follows or replaces the commits event handler {} has \
+                            processed; resuming the handler from it",
+                            event.id, name
+                        );
+                        dispatched.skip_through.remove(&event.service_id);
+                    }
+                }
+            }
+
+            let handler = &dispatched.handler;
+            if let Err(err) = failure_policy.run(
+                &format!("handle commit {} with event handler {}", event.id, name),
+                || handler.handle_event(event),
+            ) {
+                error!(
+                    "Event handler {} failed to handle commit {}; no further commits will be \
+                    passed to it until gridd is restarted: {}",
+                    name, event.id, err
+                );
+                dispatched.halted = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    /// Records the commits it handles, failing the commits it is told to fail
+    #[derive(Clone)]
+    struct TestHandler {
+        name: String,
+        checkpoint: Option<Checkpoint>,
+        failing_commit: Option<String>,
+        handled: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl TestHandler {
+        fn new(name: &str, checkpoint: Option<(&str, Option<i64>)>) -> Self {
+            Self {
+                name: name.to_string(),
+                checkpoint: checkpoint.map(|(commit_id, commit_num)| Checkpoint {
+                    commit_id: commit_id.to_string(),
+                    commit_num,
+                }),
+                failing_commit: None,
+                handled: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+
+        fn handled(&self) -> Vec<String> {
+            self.handled.lock().expect("Lock was poisoned").clone()
+        }
+    }
+
+    impl EventHandler for TestHandler {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn handle_event(&self, event: &CommitEvent) -> Result<(), EventError> {
+            if self.failing_commit.as_ref() == Some(&event.id) {
+                return Err(EventError(format!("Unable to handle {}", event.id)));
+            }
+            self.handled
+                .lock()
+                .expect("Lock was poisoned")
+                .push(event.id.clone());
+            Ok(())
+        }
+
+        fn checkpoint(&self, _service_id: Option<&str>) -> Result<Option<Checkpoint>, EventError> {
+            Ok(self.checkpoint.clone())
+        }
+
+        fn cloned_box(&self) -> Box<dyn EventHandler> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn commit(id: &str) -> CommitEvent {
+        CommitEvent {
+            service_id: None,
+            id: id.to_string(),
+            height: None,
+            state_changes: vec![],
+        }
+    }
+
+    fn commit_at(id: &str, height: u64) -> CommitEvent {
+        CommitEvent {
+            height: Some(height),
+            ..commit(id)
+        }
+    }
+
+    /// Verify that events are requested from the earliest checkpoint, and that each handler is
+    /// only passed the commits after its own checkpoint
+    #[test]
+    fn resume_from_earliest_checkpoint() {
+        let database = TestHandler::new("database", Some(("commit-3", Some(3))));
+        let sink = TestHandler::new("sink", Some(("commit-1", Some(1))));
+        let mut dispatcher = EventDispatcher::new(
+            vec![database.cloned_box(), sink.cloned_box()],
+            FailurePolicy::Retry,
+        );
+
+        assert_eq!(
+            dispatcher.resume_point(None).expect("Unable to resume"),
+            Some("commit-1".to_string())
+        );
+
+        for id in &["commit-2", "commit-3", "commit-4"] {
+            dispatcher.dispatch(&commit(id));
+        }
+
+        assert_eq!(database.handled(), vec!["commit-4"]);
+        assert_eq!(sink.handled(), vec!["commit-2", "commit-3", "commit-4"]);
+    }
+
+    /// Verify that a handler whose checkpoint was replaced by a fork is passed the commit that
+    /// replaced it, and that a fork below the checkpoint ends the skipping where it begins
+    #[test]
+    fn resume_after_fork() {
+        let database = TestHandler::new("database", Some(("commit-3", Some(3))));
+        let sink = TestHandler::new("sink", Some(("commit-1", Some(1))));
+        let mut dispatcher = EventDispatcher::new(
+            vec![database.cloned_box(), sink.cloned_box()],
+            FailurePolicy::Retry,
+        );
+
+        assert_eq!(
+            dispatcher.resume_point(None).expect("Unable to resume"),
+            Some("commit-1".to_string())
+        );
+
+        for (id, height) in &[("commit-2", 2), ("commit-3b", 3), ("commit-4b", 4)] {
+            dispatcher.dispatch(&commit_at(id, *height));
+        }
+
+        assert_eq!(database.handled(), vec!["commit-3b", "commit-4b"]);
+        assert_eq!(sink.handled(), vec!["commit-2", "commit-3b", "commit-4b"]);
+
+        let database = TestHandler::new("database", Some(("commit-4", Some(4))));
+        let sink = TestHandler::new("sink", Some(("commit-1", Some(1))));
+        let mut dispatcher = EventDispatcher::new(
+            vec![database.cloned_box(), sink.cloned_box()],
+            FailurePolicy::Retry,
+        );
+        dispatcher.resume_point(None).expect("Unable to resume");
+
+        for (id, height) in &[
+            ("commit-2", 2),
+            ("commit-3", 3),
+            ("commit-2b", 2),
+            ("commit-3b", 3),
+            ("commit-4b", 4),
+        ] {
+            dispatcher.dispatch(&commit_at(id, *height));
+        }
+
+        assert_eq!(
+            database.handled(),
+            vec!["commit-2b", "commit-3b", "commit-4b"]
+        );
+    }
+
+    /// Verify that all events are requested if a handler has not processed any, and that a
+    /// handler whose checkpoint is no longer known is not made to skip events
+    #[test]
+    fn resume_without_checkpoint() {
+        let database = TestHandler::new("database", Some(("commit-2", Some(2))));
+        let sink = TestHandler::new("sink", None);
+        let mut dispatcher = EventDispatcher::new(
+            vec![database.cloned_box(), sink.cloned_box()],
+            FailurePolicy::Retry,
+        );
+
+        assert_eq!(
+            dispatcher.resume_point(None).expect("Unable to resume"),
+            None
+        );
+
+        let forked = TestHandler::new("forked", Some(("orphan", None)));
+        let mut forked_dispatcher = EventDispatcher::new(
+            vec![database.cloned_box(), forked.cloned_box()],
+            FailurePolicy::Retry,
+        );
+
+        assert_eq!(
+            forked_dispatcher
+                .resume_point(None)
+                .expect("Unable to resume"),
+            Some("commit-2".to_string())
+        );
+
+        forked_dispatcher.dispatch(&commit("commit-3"));
+
+        assert_eq!(database.handled(), vec!["commit-3"]);
+        assert_eq!(forked.handled(), vec!["commit-3"]);
+    }
+
+    /// Verify that under the halt policy, a handler that fails is passed no further commits while
+    /// the other handlers continue
+    #[test]
+    fn halt_failed_handler() {
+        let database = TestHandler::new("database", None);
+        let mut sink = TestHandler::new("sink", None);
+        sink.failing_commit = Some("commit-2".to_string());
+        let mut dispatcher = EventDispatcher::new(
+            vec![database.cloned_box(), sink.cloned_box()],
+            FailurePolicy::Halt,
+        );
+
+        for id in &["commit-1", "commit-2", "commit-3"] {
+            dispatcher.dispatch(&commit(id));
+        }
+
+        assert_eq!(database.handled(), vec!["commit-1", "commit-2", "commit-3"]);
+        assert_eq!(sink.handled(), vec!["commit-1"]);
+    }
+}
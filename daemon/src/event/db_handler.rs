@@ -24,8 +24,17 @@ use grid_sdk::protocol::schema::state::PropertyValue;
     feature = "location"
 ))]
 use grid_sdk::protos::FromBytes;
+#[cfg(feature = "event-stream")]
+use grid_sdk::rest_api::resources::event_stream::v1::{
+    EventBroadcaster, StreamEvent, StreamEventType,
+};
+#[cfg(feature = "event-stream")]
+use grid_sdk::store::StoreFactory;
 use grid_sdk::{
-    commits::{store::CommitEvent as DbCommitEvent, CommitStore},
+    commits::{
+        store::{CommitEvent as DbCommitEvent, IndexedNamespace},
+        CommitStore,
+    },
     store::TransactionalStoreFactory,
 };
 #[cfg(feature = "location")]
@@ -41,8 +50,8 @@ use grid_sdk::{
 use grid_sdk::{
     pike::{
         addressing::{
-            GRID_PIKE_AGENT_NAMESPACE, GRID_PIKE_NAMESPACE, GRID_PIKE_ORGANIZATION_NAMESPACE,
-            GRID_PIKE_ROLE_NAMESPACE,
+            GRID_PIKE_AGENT_NAMESPACE, GRID_PIKE_ALTERNATE_ID_INDEX_ENTRY_NAMESPACE,
+            GRID_PIKE_NAMESPACE, GRID_PIKE_ORGANIZATION_NAMESPACE, GRID_PIKE_ROLE_NAMESPACE,
         },
         store::{
             Agent, AgentBuilder, AlternateId, AlternateIdBuilder, Organization,
@@ -71,7 +80,10 @@ use grid_sdk::{
         PurchaseOrderRevision as ProtocolPORevision, PurchaseOrderVersion as ProtocolPOVersion,
     },
     purchase_order::{
-        addressing::{GRID_PURCHASE_ORDER_NAMESPACE, GRID_PURCHASE_ORDER_PO_NAMESPACE},
+        addressing::{
+            GRID_PURCHASE_ORDER_ALT_ID_NAMESPACE, GRID_PURCHASE_ORDER_NAMESPACE,
+            GRID_PURCHASE_ORDER_PO_NAMESPACE,
+        },
         store::{
             PurchaseOrder, PurchaseOrderAlternateId, PurchaseOrderAlternateIdBuilder,
             PurchaseOrderBuilder, PurchaseOrderBuilderError, PurchaseOrderStore,
@@ -105,13 +117,17 @@ use grid_sdk::{
         store::{PropertyDefinition as StorePropertyDefinition, Schema, SchemaStore},
     },
 };
+#[cfg(feature = "event-stream")]
+use std::cell::RefCell;
 #[cfg(feature = "pike")]
 use std::collections::HashMap;
 #[cfg(feature = "purchase-order")]
 use std::convert::TryInto;
 use std::i64;
 
-use super::{CommitEvent, EventError, EventHandler, StateChange, IGNORED_NAMESPACES};
+#[cfg(feature = "track-and-trace")]
+use super::alert::{escalate_pending_alerts, evaluate_alert_rules, AlertRule};
+use super::{Checkpoint, CommitEvent, EventError, EventHandler, StateChange, IGNORED_NAMESPACES};
 
 #[cfg(any(
     feature = "pike",
@@ -123,23 +139,72 @@ pub const MAX_COMMIT_NUM: i64 = i64::MAX;
 
 pub struct DatabaseEventHandler {
     store_factory: Box<dyn TransactionalStoreFactory>,
+    #[cfg(feature = "track-and-trace")]
+    alert_rules: Vec<AlertRule>,
+    #[cfg(feature = "event-stream")]
+    event_broadcaster: Option<EventBroadcaster>,
 }
 
 impl Clone for DatabaseEventHandler {
     fn clone(&self) -> Self {
         let store_factory = self.store_factory.clone_box();
 
-        Self { store_factory }
+        Self {
+            store_factory,
+            #[cfg(feature = "track-and-trace")]
+            alert_rules: self.alert_rules.clone(),
+            #[cfg(feature = "event-stream")]
+            event_broadcaster: self.event_broadcaster.clone(),
+        }
     }
 }
 
 impl DatabaseEventHandler {
     pub fn new(store_factory: Box<dyn TransactionalStoreFactory>) -> Self {
-        Self { store_factory }
+        Self {
+            store_factory,
+            #[cfg(feature = "track-and-trace")]
+            alert_rules: Vec::new(),
+            #[cfg(feature = "event-stream")]
+            event_broadcaster: None,
+        }
+    }
+
+    /// Sets the rules that reported Track and Trace values are checked against
+    #[cfg(feature = "track-and-trace")]
+    pub fn with_alert_rules(mut self, alert_rules: Vec<AlertRule>) -> Self {
+        self.alert_rules = alert_rules;
+        self
+    }
+
+    /// Sets the broadcaster that changes to products, locations and purchase orders are
+    /// published to once they are committed to the database
+    #[cfg(feature = "event-stream")]
+    pub fn with_event_broadcaster(mut self, event_broadcaster: EventBroadcaster) -> Self {
+        self.event_broadcaster = Some(event_broadcaster);
+        self
     }
 }
 
 impl EventHandler for DatabaseEventHandler {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    /// The commits are recorded in the commit store in the same transaction as their changes,
+    /// so the checkpoint is the current commit of the service
+    fn checkpoint(&self, service_id: Option<&str>) -> Result<Option<Checkpoint>, EventError> {
+        let commit = self
+            .store_factory
+            .get_grid_commit_store()
+            .get_current_commit(service_id)?;
+
+        Ok(commit.map(|commit| Checkpoint {
+            commit_id: commit.commit_id,
+            commit_num: Some(commit.commit_num),
+        }))
+    }
+
     fn handle_event(&self, event: &CommitEvent) -> Result<(), EventError> {
         debug!("Received commit event: {}", event);
 
@@ -148,6 +213,9 @@ impl EventHandler for DatabaseEventHandler {
             .begin_transaction()
             .map_err(|err| EventError(format!("Unable to start database transaction: {}", err)))?;
 
+        #[cfg(feature = "event-stream")]
+        let stream_events = RefCell::new(Vec::new());
+
         let try_handle_event = || {
             let commit = txn
                 .get_grid_commit_store()
@@ -157,20 +225,50 @@ impl EventHandler for DatabaseEventHandler {
                     EventError("Commit could not be constructed from event data".into())
                 })?;
 
-            let db_ops = create_db_operations_from_state_changes(
+            let mut db_ops = create_db_operations_from_state_changes(
                 &event.state_changes,
                 commit.commit_num,
                 commit.service_id.as_ref(),
             )?;
 
+            // A commit replayed after a reindex only rebuilds the reindexed namespaces; the
+            // records of the other namespaces were kept
+            if let Some(namespaces) = txn
+                .get_grid_commit_store()
+                .take_pending_reindex(&commit.commit_id, commit.service_id.as_deref())?
+            {
+                info!(
+                    "Reindexing {} for commit {}",
+                    namespaces
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    commit.commit_id
+                );
+                db_ops.retain(|op| namespaces.contains(&op.namespace()));
+            }
+
+            // Deleted entities are looked up before the operations remove them
+            #[cfg(feature = "event-stream")]
+            stream_events.replace(create_stream_events_from_db_operations(
+                &db_ops,
+                commit.commit_num,
+                commit.service_id.as_deref(),
+                &*txn,
+            )?);
+
+            #[cfg(feature = "track-and-trace")]
+            let (commit_num, service_id) = (commit.commit_num, commit.service_id.clone());
+
             trace!("The following operations will be performed: {:#?}", db_ops);
             match txn
                 .get_grid_commit_store()
-                .get_commit_by_commit_num(commit.commit_num)?
+                .get_commit_by_commit_num(commit.commit_num, commit.service_id.as_deref())?
             {
                 Some(ref b) if b.commit_id != commit.commit_id => {
                     txn.get_grid_commit_store()
-                        .resolve_fork(commit.commit_num)?;
+                        .resolve_fork(commit.commit_num, commit.service_id.as_deref())?;
                     info!(
                         "Fork detected. Replaced {} at height {}, with commit {}.",
                         &b.commit_id, &b.commit_num, &commit.commit_id
@@ -214,10 +312,56 @@ impl EventHandler for DatabaseEventHandler {
                             .try_for_each(|role| txn.get_grid_pike_store().add_role(role))?;
                     }
                     #[cfg(feature = "pike")]
-                    DbInsertOperation::RemoveRole(ref address, current_commit_num) => {
+                    DbInsertOperation::RemoveAgent(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        debug!("Removing agent at address {}", address);
+                        txn.get_grid_pike_store().delete_agent(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
+                    }
+                    #[cfg(feature = "pike")]
+                    DbInsertOperation::RemoveOrganization(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        debug!("Removing organization at address {}", address);
+                        txn.get_grid_pike_store().delete_organization(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
+                    }
+                    #[cfg(feature = "pike")]
+                    DbInsertOperation::RemoveRole(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
                         debug!("Removing role at address {}", address);
-                        txn.get_grid_pike_store()
-                            .delete_role(address, current_commit_num)?;
+                        txn.get_grid_pike_store().delete_role(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
+                    }
+                    #[cfg(feature = "pike")]
+                    DbInsertOperation::RemoveAlternateIdIndexEntry(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        debug!("Removing alternate ID index entry at address {}", address);
+                        txn.get_grid_pike_store().delete_alternate_id_index_entry(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
                     }
                     #[cfg(feature = "schema")]
                     DbInsertOperation::GridSchemas(schemas) => {
@@ -226,6 +370,19 @@ impl EventHandler for DatabaseEventHandler {
                             txn.get_grid_schema_store().add_schema(schema)
                         })?;
                     }
+                    #[cfg(feature = "schema")]
+                    DbInsertOperation::RemoveGridSchema(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        debug!("Removing schema at address {}", address);
+                        txn.get_grid_schema_store().delete_schema(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
+                    }
                     #[cfg(feature = "track-and-trace")]
                     DbInsertOperation::Properties(properties, reporters) => {
                         debug!("Inserting {} properties", properties.len());
@@ -239,7 +396,21 @@ impl EventHandler for DatabaseEventHandler {
                     DbInsertOperation::ReportedValues(reported_values) => {
                         debug!("Inserting {} reported values", reported_values.len());
                         txn.get_grid_track_and_trace_store()
-                            .add_reported_values(reported_values)?;
+                            .add_reported_values(reported_values.clone())?;
+                        evaluate_alert_rules(
+                            &self.alert_rules,
+                            &*txn.get_grid_track_and_trace_store(),
+                            &reported_values,
+                            commit_num,
+                            service_id.as_deref(),
+                        )?;
+                        escalate_pending_alerts(
+                            &self.alert_rules,
+                            &*txn.get_grid_track_and_trace_store(),
+                            &reported_values,
+                            commit_num,
+                            service_id.as_deref(),
+                        )?;
                     }
                     #[cfg(feature = "track-and-trace")]
                     DbInsertOperation::Proposals(proposals) => {
@@ -255,6 +426,45 @@ impl EventHandler for DatabaseEventHandler {
                         txn.get_grid_track_and_trace_store()
                             .add_associated_agents(associated_agents)?;
                     }
+                    #[cfg(feature = "track-and-trace")]
+                    DbInsertOperation::RemoveProperty(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        debug!("Removing property at address {}", address);
+                        txn.get_grid_track_and_trace_store().delete_property(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
+                    }
+                    #[cfg(feature = "track-and-trace")]
+                    DbInsertOperation::RemoveProposals(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        debug!("Removing proposals at address {}", address);
+                        txn.get_grid_track_and_trace_store().delete_proposals(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
+                    }
+                    #[cfg(feature = "track-and-trace")]
+                    DbInsertOperation::RemoveRecord(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        debug!("Removing record at address {}", address);
+                        txn.get_grid_track_and_trace_store().delete_record(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
+                    }
                     #[cfg(feature = "location")]
                     DbInsertOperation::Locations(locations) => {
                         debug!("Inserting {} locations", locations.len());
@@ -263,9 +473,16 @@ impl EventHandler for DatabaseEventHandler {
                         })?;
                     }
                     #[cfg(feature = "location")]
-                    DbInsertOperation::RemoveLocation(ref address, current_commit_num) => {
-                        txn.get_grid_location_store()
-                            .delete_location(address, current_commit_num)?;
+                    DbInsertOperation::RemoveLocation(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        txn.get_grid_location_store().delete_location(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
                     }
                     #[cfg(feature = "product")]
                     DbInsertOperation::Products(products) => {
@@ -275,9 +492,16 @@ impl EventHandler for DatabaseEventHandler {
                         })?;
                     }
                     #[cfg(feature = "product")]
-                    DbInsertOperation::RemoveProduct(ref address, current_commit_num) => {
-                        txn.get_grid_product_store()
-                            .delete_product(address, current_commit_num)?;
+                    DbInsertOperation::RemoveProduct(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        txn.get_grid_product_store().delete_product(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
                     }
                     #[cfg(feature = "purchase-order")]
                     DbInsertOperation::PurchaseOrders(pos) => {
@@ -286,13 +510,49 @@ impl EventHandler for DatabaseEventHandler {
                             txn.get_grid_purchase_order_store().add_purchase_order(po)
                         })?;
                     }
+                    #[cfg(feature = "purchase-order")]
+                    DbInsertOperation::RemovePurchaseOrder(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        debug!("Removing purchase order at address {}", address);
+                        txn.get_grid_purchase_order_store().delete_purchase_order(
+                            address,
+                            current_commit_num,
+                            service_id.as_deref(),
+                        )?;
+                    }
+                    #[cfg(feature = "purchase-order")]
+                    DbInsertOperation::RemovePurchaseOrderAlternateId(
+                        ref address,
+                        current_commit_num,
+                        ref service_id,
+                    ) => {
+                        debug!(
+                            "Removing purchase order alternate ID at address {}",
+                            address
+                        );
+                        txn.get_grid_purchase_order_store()
+                            .delete_purchase_order_alternate_id(
+                                address,
+                                current_commit_num,
+                                service_id.as_deref(),
+                            )?;
+                    }
                 };
             }
+
             Ok(true) as Result<_, EventError>
         };
 
         match try_handle_event() {
-            Ok(true) => txn.commit().map_err(|err| EventError(err.to_string()))?,
+            Ok(true) => {
+                txn.commit().map_err(|err| EventError(err.to_string()))?;
+
+                #[cfg(feature = "event-stream")]
+                self.publish_stream_events(stream_events.take());
+            }
             Ok(false) => txn.rollback().map_err(|err| EventError(err.to_string()))?,
             Err(err) => {
                 if let Err(e) = txn.rollback() {
@@ -306,11 +566,145 @@ impl EventHandler for DatabaseEventHandler {
     }
 
     fn cloned_box(&self) -> Box<dyn EventHandler> {
-        Box::new(Self::new(self.store_factory.clone_box()))
+        Box::new(self.clone())
     }
 }
 
-fn create_db_operations_from_state_changes(
+#[cfg(feature = "event-stream")]
+impl DatabaseEventHandler {
+    fn publish_stream_events(&self, stream_events: Vec<StreamEvent>) {
+        if let Some(ref event_broadcaster) = self.event_broadcaster {
+            for stream_event in stream_events {
+                if let Err(err) = event_broadcaster.publish(stream_event) {
+                    error!("Unable to publish committed change: {}", err);
+                }
+            }
+        }
+    }
+}
+
+/// Creates the events that are streamed to clients for the entities changed by a commit
+///
+/// A deleted entity is identified by the ID and owners it was stored with, so the removals must
+/// be looked up in the given stores before they are applied. Removals of entities that were never
+/// stored are not streamed.
+#[cfg(feature = "event-stream")]
+#[allow(unused_variables)]
+fn create_stream_events_from_db_operations<S: StoreFactory + ?Sized>(
+    db_ops: &[DbInsertOperation],
+    commit_num: i64,
+    service_id: Option<&str>,
+    stores: &S,
+) -> Result<Vec<StreamEvent>, EventError> {
+    let mut events = Vec::new();
+
+    for op in db_ops {
+        match op {
+            #[cfg(feature = "location")]
+            DbInsertOperation::Locations(locations) => {
+                events.extend(locations.iter().map(|location| {
+                    StreamEvent::entity_updated(
+                        StreamEventType::Location,
+                        &location.location_id,
+                        vec![location.owner.clone()],
+                        commit_num,
+                        service_id,
+                    )
+                }))
+            }
+            #[cfg(feature = "location")]
+            DbInsertOperation::RemoveLocation(address, ..) => {
+                let location_store = stores.get_grid_location_store();
+                if let Some(location_id) =
+                    location_store.get_location_id_from_address(address, service_id)?
+                {
+                    let owners = location_store
+                        .get_location(&location_id, service_id)?
+                        .map(|location| vec![location.owner])
+                        .unwrap_or_default();
+                    events.push(StreamEvent::entity_deleted(
+                        StreamEventType::Location,
+                        &location_id,
+                        owners,
+                        commit_num,
+                        service_id,
+                    ));
+                }
+            }
+            #[cfg(feature = "product")]
+            DbInsertOperation::Products(products) => {
+                events.extend(products.iter().map(|product| {
+                    StreamEvent::entity_updated(
+                        StreamEventType::Product,
+                        product.product_id(),
+                        vec![product.owner().to_string()],
+                        commit_num,
+                        service_id,
+                    )
+                }))
+            }
+            #[cfg(feature = "product")]
+            DbInsertOperation::RemoveProduct(address, ..) => {
+                let product_store = stores.get_grid_product_store();
+                if let Some(product_id) =
+                    product_store.get_product_id_from_address(address, service_id)?
+                {
+                    let owners = product_store
+                        .get_product(&product_id, service_id)?
+                        .map(|product| vec![product.owner().to_string()])
+                        .unwrap_or_default();
+                    events.push(StreamEvent::entity_deleted(
+                        StreamEventType::Product,
+                        &product_id,
+                        owners,
+                        commit_num,
+                        service_id,
+                    ));
+                }
+            }
+            #[cfg(feature = "purchase-order")]
+            DbInsertOperation::PurchaseOrders(pos) => events.extend(pos.iter().map(|po| {
+                StreamEvent::entity_updated(
+                    StreamEventType::PurchaseOrder,
+                    po.purchase_order_uid(),
+                    vec![
+                        po.buyer_org_id().to_string(),
+                        po.seller_org_id().to_string(),
+                    ],
+                    commit_num,
+                    service_id,
+                )
+            })),
+            #[cfg(feature = "purchase-order")]
+            DbInsertOperation::RemovePurchaseOrder(address, ..) => {
+                let po_store = stores.get_grid_purchase_order_store();
+                if let Some(uid) = po_store.get_uid_from_address(address, service_id)? {
+                    let owners = po_store
+                        .get_purchase_order(&uid, None, None, service_id)?
+                        .map(|po| {
+                            vec![
+                                po.buyer_org_id().to_string(),
+                                po.seller_org_id().to_string(),
+                            ]
+                        })
+                        .unwrap_or_default();
+                    events.push(StreamEvent::entity_deleted(
+                        StreamEventType::PurchaseOrder,
+                        &uid,
+                        owners,
+                        commit_num,
+                        service_id,
+                    ));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(events)
+}
+
+pub(super) fn create_db_operations_from_state_changes(
     state_changes: &[StateChange],
     commit_num: i64,
     service_id: Option<&String>,
@@ -579,6 +973,10 @@ fn state_change_to_db_operation(
                         start_commit_num: commit_num,
                         end_commit_num: MAX_COMMIT_NUM,
                         service_id: service_id.cloned(),
+                        expires_at: match *proposal.expires_at() {
+                            0 => None,
+                            expires_at => Some(expires_at as i64),
+                        },
                     })
                     .collect::<Vec<Proposal>>();
 
@@ -611,6 +1009,12 @@ fn state_change_to_db_operation(
                         start_commit_num: commit_num,
                         end_commit_num: MAX_COMMIT_NUM,
                         service_id: service_id.cloned(),
+                        product_id: Some(record.product_id())
+                            .filter(|product_id| !product_id.is_empty())
+                            .map(String::from),
+                        location_id: Some(record.location_id())
+                            .filter(|location_id| !location_id.is_empty())
+                            .map(String::from),
                     })
                     .collect::<Vec<Record>>();
 
@@ -776,22 +1180,93 @@ fn state_change_to_db_operation(
         StateChange::Delete { key } => match &key[0..8] {
             #[cfg(feature = "pike")]
             GRID_PIKE_NAMESPACE => match &key[0..10] {
+                GRID_PIKE_AGENT_NAMESPACE => Ok(Some(DbInsertOperation::RemoveAgent(
+                    key.to_string(),
+                    commit_num,
+                    service_id.cloned(),
+                ))),
+                GRID_PIKE_ORGANIZATION_NAMESPACE => {
+                    Ok(Some(DbInsertOperation::RemoveOrganization(
+                        key.to_string(),
+                        commit_num,
+                        service_id.cloned(),
+                    )))
+                }
                 GRID_PIKE_ROLE_NAMESPACE => Ok(Some(DbInsertOperation::RemoveRole(
                     key.to_string(),
                     commit_num,
+                    service_id.cloned(),
                 ))),
-                _ => Ok(None),
+                GRID_PIKE_ALTERNATE_ID_INDEX_ENTRY_NAMESPACE => {
+                    Ok(Some(DbInsertOperation::RemoveAlternateIdIndexEntry(
+                        key.to_string(),
+                        commit_num,
+                        service_id.cloned(),
+                    )))
+                }
+                _ => {
+                    debug!("received state change for unknown address: {}", key);
+                    Ok(None)
+                }
             },
+            #[cfg(feature = "schema")]
+            GRID_SCHEMA_NAMESPACE => Ok(Some(DbInsertOperation::RemoveGridSchema(
+                key.to_string(),
+                commit_num,
+                service_id.cloned(),
+            ))),
+            #[cfg(feature = "track-and-trace")]
+            TRACK_AND_TRACE_PROPERTY_NAMESPACE if &key[66..] == "0000" => Ok(Some(
+                DbInsertOperation::RemoveProperty(key.to_string(), commit_num, service_id.cloned()),
+            )),
+            // The values reported on a property page are ended along with the property
+            #[cfg(feature = "track-and-trace")]
+            TRACK_AND_TRACE_PROPERTY_NAMESPACE => Ok(None),
+            #[cfg(feature = "track-and-trace")]
+            TRACK_AND_TRACE_PROPOSAL_NAMESPACE => Ok(Some(DbInsertOperation::RemoveProposals(
+                key.to_string(),
+                commit_num,
+                service_id.cloned(),
+            ))),
+            #[cfg(feature = "track-and-trace")]
+            TRACK_AND_TRACE_RECORD_NAMESPACE => Ok(Some(DbInsertOperation::RemoveRecord(
+                key.to_string(),
+                commit_num,
+                service_id.cloned(),
+            ))),
             #[cfg(feature = "product")]
             GRID_PRODUCT_NAMESPACE => Ok(Some(DbInsertOperation::RemoveProduct(
                 key.to_string(),
                 commit_num,
+                service_id.cloned(),
             ))),
             #[cfg(feature = "location")]
             GRID_LOCATION_NAMESPACE => Ok(Some(DbInsertOperation::RemoveLocation(
                 key.to_string(),
                 commit_num,
+                service_id.cloned(),
             ))),
+            #[cfg(feature = "purchase-order")]
+            GRID_PURCHASE_ORDER_NAMESPACE => match &key[0..10] {
+                GRID_PURCHASE_ORDER_PO_NAMESPACE => {
+                    Ok(Some(DbInsertOperation::RemovePurchaseOrder(
+                        key.to_string(),
+                        commit_num,
+                        service_id.cloned(),
+                    )))
+                }
+                GRID_PURCHASE_ORDER_ALT_ID_NAMESPACE => {
+                    Ok(Some(DbInsertOperation::RemovePurchaseOrderAlternateId(
+                        key.to_string(),
+                        commit_num,
+                        service_id.cloned(),
+                    )))
+                }
+                _ => {
+                    debug!("received state change for unknown address: {}", key);
+                    Ok(None)
+                }
+            },
             _ => Err(EventError(format!(
                 "could not handle state change; unexpected delete of key {}",
                 key
@@ -801,7 +1276,7 @@ fn state_change_to_db_operation(
 }
 
 #[derive(Debug)]
-enum DbInsertOperation {
+pub(super) enum DbInsertOperation {
     #[cfg(feature = "pike")]
     Agents(Vec<Agent>),
     #[cfg(feature = "pike")]
@@ -809,9 +1284,17 @@ enum DbInsertOperation {
     #[cfg(feature = "pike")]
     Roles(Vec<Role>),
     #[cfg(feature = "pike")]
-    RemoveRole(String, i64),
+    RemoveAgent(String, i64, Option<String>),
+    #[cfg(feature = "pike")]
+    RemoveOrganization(String, i64, Option<String>),
+    #[cfg(feature = "pike")]
+    RemoveRole(String, i64, Option<String>),
+    #[cfg(feature = "pike")]
+    RemoveAlternateIdIndexEntry(String, i64, Option<String>),
     #[cfg(feature = "schema")]
     GridSchemas(Vec<Schema>),
+    #[cfg(feature = "schema")]
+    RemoveGridSchema(String, i64, Option<String>),
     #[cfg(feature = "location")]
     Locations(Vec<Location>),
     #[cfg(feature = "track-and-trace")]
@@ -822,14 +1305,66 @@ enum DbInsertOperation {
     Proposals(Vec<Proposal>),
     #[cfg(feature = "track-and-trace")]
     Records(Vec<Record>, Vec<AssociatedAgent>),
+    #[cfg(feature = "track-and-trace")]
+    RemoveProperty(String, i64, Option<String>),
+    #[cfg(feature = "track-and-trace")]
+    RemoveProposals(String, i64, Option<String>),
+    #[cfg(feature = "track-and-trace")]
+    RemoveRecord(String, i64, Option<String>),
     #[cfg(feature = "product")]
     Products(Vec<Product>),
     #[cfg(feature = "location")]
-    RemoveLocation(String, i64),
+    RemoveLocation(String, i64, Option<String>),
     #[cfg(feature = "product")]
-    RemoveProduct(String, i64),
+    RemoveProduct(String, i64, Option<String>),
     #[cfg(feature = "purchase-order")]
     PurchaseOrders(Vec<PurchaseOrder>),
+    #[cfg(feature = "purchase-order")]
+    RemovePurchaseOrder(String, i64, Option<String>),
+    #[cfg(feature = "purchase-order")]
+    RemovePurchaseOrderAlternateId(String, i64, Option<String>),
+}
+
+impl DbInsertOperation {
+    /// Returns the namespace whose records the operation changes
+    fn namespace(&self) -> IndexedNamespace {
+        match *self {
+            #[cfg(feature = "pike")]
+            DbInsertOperation::Agents(_)
+            | DbInsertOperation::Organizations(_)
+            | DbInsertOperation::Roles(_)
+            | DbInsertOperation::RemoveAgent(..)
+            | DbInsertOperation::RemoveOrganization(..)
+            | DbInsertOperation::RemoveRole(..)
+            | DbInsertOperation::RemoveAlternateIdIndexEntry(..) => IndexedNamespace::Pike,
+            #[cfg(feature = "schema")]
+            DbInsertOperation::GridSchemas(_) | DbInsertOperation::RemoveGridSchema(..) => {
+                IndexedNamespace::Schema
+            }
+            #[cfg(feature = "location")]
+            DbInsertOperation::Locations(_) | DbInsertOperation::RemoveLocation(..) => {
+                IndexedNamespace::Location
+            }
+            #[cfg(feature = "track-and-trace")]
+            DbInsertOperation::Properties(_, _)
+            | DbInsertOperation::ReportedValues(_)
+            | DbInsertOperation::Proposals(_)
+            | DbInsertOperation::Records(_, _)
+            | DbInsertOperation::RemoveProperty(..)
+            | DbInsertOperation::RemoveProposals(..)
+            | DbInsertOperation::RemoveRecord(..) => IndexedNamespace::TrackAndTrace,
+            #[cfg(feature = "product")]
+            DbInsertOperation::Products(_) | DbInsertOperation::RemoveProduct(..) => {
+                IndexedNamespace::Product
+            }
+            #[cfg(feature = "purchase-order")]
+            DbInsertOperation::PurchaseOrders(_)
+            | DbInsertOperation::RemovePurchaseOrder(..)
+            | DbInsertOperation::RemovePurchaseOrderAlternateId(..) => {
+                IndexedNamespace::PurchaseOrder
+            }
+        }
+    }
 }
 
 #[cfg(feature = "track-and-trace")]
@@ -1113,3 +1648,343 @@ fn make_po_alternate_ids(
 
     Ok(ids)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "location")]
+    use grid_sdk::location::addressing::compute_gs1_location_address;
+    #[cfg(feature = "pike")]
+    use grid_sdk::pike::addressing::{
+        compute_agent_address, compute_alternate_id_index_entry_address,
+        compute_organization_address, compute_role_address,
+    };
+    #[cfg(feature = "product")]
+    use grid_sdk::product::addressing::compute_gs1_product_address;
+    #[cfg(feature = "purchase-order")]
+    use grid_sdk::purchase_order::addressing::{
+        compute_alternate_id_address, compute_purchase_order_address,
+    };
+    #[cfg(feature = "schema")]
+    use grid_sdk::schema::addressing::compute_schema_address;
+    #[cfg(feature = "track-and-trace")]
+    use grid_sdk::track_and_trace::addressing::{
+        make_property_address, make_proposal_address, make_record_address,
+    };
+
+    const COMMIT_NUM: i64 = 7;
+
+    fn delete(key: &str) -> Result<Option<DbInsertOperation>, EventError> {
+        state_change_to_db_operation(
+            &StateChange::Delete {
+                key: key.to_string(),
+            },
+            COMMIT_NUM,
+            None,
+        )
+    }
+
+    /// Verify that deleting an agent, organization, role or alternate ID index entry removes
+    /// the record stored at the deleted address at the commit of the state change.
+    #[cfg(feature = "pike")]
+    #[test]
+    fn delete_pike_entities() {
+        let agent = compute_agent_address("agent_public_key");
+        assert!(matches!(
+            delete(&agent),
+            Ok(Some(DbInsertOperation::RemoveAgent(ref address, COMMIT_NUM, None))) if *address == agent
+        ));
+
+        let org = compute_organization_address("org_id");
+        assert!(matches!(
+            delete(&org),
+            Ok(Some(DbInsertOperation::RemoveOrganization(ref address, COMMIT_NUM, None)))
+                if *address == org
+        ));
+
+        let role = compute_role_address("admin", "org_id");
+        assert!(matches!(
+            delete(&role),
+            Ok(Some(DbInsertOperation::RemoveRole(ref address, COMMIT_NUM, None))) if *address == role
+        ));
+
+        let index_entry = compute_alternate_id_index_entry_address("gs1_company_prefix", "0123");
+        assert!(matches!(
+            delete(&index_entry),
+            Ok(Some(DbInsertOperation::RemoveAlternateIdIndexEntry(ref address, COMMIT_NUM, None)))
+                if *address == index_entry
+        ));
+    }
+
+    /// Verify that deleting a schema removes the schema stored at the deleted address.
+    #[cfg(feature = "schema")]
+    #[test]
+    fn delete_schema() {
+        let schema = compute_schema_address("gs1_product");
+        assert!(matches!(
+            delete(&schema),
+            Ok(Some(DbInsertOperation::RemoveGridSchema(ref address, COMMIT_NUM, None)))
+                if *address == schema
+        ));
+    }
+
+    /// Verify that deleting a record, property or proposal removes the entity stored at the
+    /// deleted address, while a property page's reported values are left for the property's
+    /// deletion to end.
+    #[cfg(feature = "track-and-trace")]
+    #[test]
+    fn delete_track_and_trace_entities() {
+        let record = make_record_address("record_id");
+        assert!(matches!(
+            delete(&record),
+            Ok(Some(DbInsertOperation::RemoveRecord(ref address, COMMIT_NUM, None)))
+                if *address == record
+        ));
+
+        let property = make_property_address("record_id", "temperature", 0);
+        assert!(matches!(
+            delete(&property),
+            Ok(Some(DbInsertOperation::RemoveProperty(ref address, COMMIT_NUM, None)))
+                if *address == property
+        ));
+
+        let page = make_property_address("record_id", "temperature", 1);
+        assert!(matches!(delete(&page), Ok(None)));
+
+        let proposal = make_proposal_address("record_id", "agent_public_key");
+        assert!(matches!(
+            delete(&proposal),
+            Ok(Some(DbInsertOperation::RemoveProposals(ref address, COMMIT_NUM, None)))
+                if *address == proposal
+        ));
+    }
+
+    /// Verify that deleting a product removes the product stored at the deleted address.
+    #[cfg(feature = "product")]
+    #[test]
+    fn delete_product() {
+        let product = compute_gs1_product_address("00012345600012");
+        assert!(matches!(
+            delete(&product),
+            Ok(Some(DbInsertOperation::RemoveProduct(ref address, COMMIT_NUM, None)))
+                if *address == product
+        ));
+    }
+
+    /// Verify that deleting a location removes the location stored at the deleted address.
+    #[cfg(feature = "location")]
+    #[test]
+    fn delete_location() {
+        let location = compute_gs1_location_address("0123456789012");
+        assert!(matches!(
+            delete(&location),
+            Ok(Some(DbInsertOperation::RemoveLocation(ref address, COMMIT_NUM, None)))
+                if *address == location
+        ));
+    }
+
+    /// Verify that deleting a purchase order or one of its alternate IDs removes the entity
+    /// stored at the deleted address.
+    #[cfg(feature = "purchase-order")]
+    #[test]
+    fn delete_purchase_order_entities() {
+        let po = compute_purchase_order_address("PO-00000-0000");
+        assert!(matches!(
+            delete(&po),
+            Ok(Some(DbInsertOperation::RemovePurchaseOrder(ref address, COMMIT_NUM, None)))
+                if *address == po
+        ));
+
+        let alternate_id = compute_alternate_id_address("PO-00000-0000", "po_number", "1");
+        assert!(matches!(
+            delete(&alternate_id),
+            Ok(Some(DbInsertOperation::RemovePurchaseOrderAlternateId(ref address, COMMIT_NUM, None)))
+                if *address == alternate_id
+        ));
+    }
+
+    /// Verify that deleting an address outside of the Grid namespaces is an error.
+    #[test]
+    fn delete_unknown_namespace() {
+        assert!(delete(&format!("ffffff{}", "0".repeat(64))).is_err());
+    }
+
+    /// Verify that each reported value is aggregated once, although every later update of its
+    /// property page commits it again, and that the Number aggregates are scaled by the
+    /// property's number exponent.
+    #[cfg(all(
+        feature = "track-and-trace",
+        feature = "schema",
+        feature = "database-sqlite"
+    ))]
+    #[test]
+    fn aggregate_values_across_page_updates() {
+        use diesel::{
+            r2d2::{ConnectionManager, Pool},
+            sqlite::SqliteConnection,
+        };
+        use grid_sdk::{
+            migrations::run_sqlite_migrations,
+            protocol::{
+                schema::state::{
+                    PropertyDefinitionBuilder, PropertyValueBuilder, SchemaBuilder,
+                    SchemaListBuilder,
+                },
+                track_and_trace::state::{
+                    PropertyBuilder, PropertyListBuilder, PropertyPageBuilder,
+                    PropertyPageListBuilder, RecordBuilder, RecordListBuilder,
+                    ReportedValueBuilder, ReporterBuilder,
+                },
+            },
+            protos::IntoBytes,
+            store::sqlite::SqliteStoreFactory,
+            track_and_trace::store::ReportedValueAggregate,
+        };
+
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::<SqliteConnection>::new(":memory:"))
+            .expect("Failed to build connection pool");
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+        let store_factory = SqliteStoreFactory::new(pool);
+        let handler = DatabaseEventHandler::new(store_factory.clone_box());
+
+        let definition = PropertyDefinitionBuilder::new()
+            .with_name("temperature".to_string())
+            .with_data_type(DataType::Number)
+            .with_number_exponent(-2)
+            .build()
+            .expect("Unable to build property definition");
+        let schema = SchemaListBuilder::new()
+            .with_schemas(vec![SchemaBuilder::new()
+                .with_name("thermometer".to_string())
+                .with_owner("org_id".to_string())
+                .with_properties(vec![definition.clone()])
+                .build()
+                .expect("Unable to build schema")])
+            .build()
+            .expect("Unable to build schema list");
+        let record = RecordListBuilder::new()
+            .with_records(vec![RecordBuilder::new()
+                .with_record_id("fridge".to_string())
+                .with_schema("thermometer".to_string())
+                .with_owners(vec![])
+                .with_custodians(vec![])
+                .with_field_final(false)
+                .build()
+                .expect("Unable to build record")])
+            .build()
+            .expect("Unable to build record list");
+        let property = PropertyListBuilder::new()
+            .with_properties(vec![PropertyBuilder::new()
+                .with_name("temperature".to_string())
+                .with_record_id("fridge".to_string())
+                .with_property_definition(definition)
+                .with_reporters(vec![ReporterBuilder::new()
+                    .with_public_key("reporter_public_key".to_string())
+                    .with_authorized(true)
+                    .with_index(0)
+                    .build()
+                    .expect("Unable to build reporter")])
+                .with_current_page(1)
+                .with_wrapped(false)
+                .build()
+                .expect("Unable to build property")])
+            .build()
+            .expect("Unable to build property list");
+
+        let mut events = vec![CommitEvent {
+            service_id: None,
+            id: "c0".to_string(),
+            height: Some(0),
+            state_changes: vec![
+                StateChange::Set {
+                    key: compute_schema_address("thermometer"),
+                    value: schema.into_bytes().expect("Unable to serialize schema"),
+                },
+                StateChange::Set {
+                    key: make_record_address("fridge"),
+                    value: record.into_bytes().expect("Unable to serialize record"),
+                },
+                StateChange::Set {
+                    key: make_property_address("fridge", "temperature", 0),
+                    value: property.into_bytes().expect("Unable to serialize property"),
+                },
+            ],
+        }];
+
+        // Each commit reports one more value, committing the whole page again
+        let mut reported_values = vec![];
+        for (height, (timestamp, value)) in [(10, 2010), (20, 2030), (70, 2500)].iter().enumerate()
+        {
+            reported_values.push(
+                ReportedValueBuilder::new()
+                    .with_reporter_index(0)
+                    .with_timestamp(*timestamp)
+                    .with_value(
+                        PropertyValueBuilder::new()
+                            .with_name("temperature".to_string())
+                            .with_data_type(DataType::Number)
+                            .with_number_value(*value)
+                            .build()
+                            .expect("Unable to build property value"),
+                    )
+                    .build()
+                    .expect("Unable to build reported value"),
+            );
+            let page = PropertyPageListBuilder::new()
+                .with_property_pages(vec![PropertyPageBuilder::new()
+                    .with_name("temperature".to_string())
+                    .with_record_id("fridge".to_string())
+                    .with_reported_values(reported_values.clone())
+                    .build()
+                    .expect("Unable to build property page")])
+                .build()
+                .expect("Unable to build property page list");
+
+            events.push(CommitEvent {
+                service_id: None,
+                id: format!("c{}", height + 1),
+                height: Some(height as u64 + 1),
+                state_changes: vec![StateChange::Set {
+                    key: make_property_address("fridge", "temperature", 1),
+                    value: page
+                        .into_bytes()
+                        .expect("Unable to serialize property page"),
+                }],
+            });
+        }
+
+        for event in &events {
+            handler.handle_event(event).expect("Unable to handle event");
+        }
+
+        let aggregation = store_factory
+            .get_grid_track_and_trace_store()
+            .aggregate_reported_values("fridge", "temperature", 60, None, None, None)
+            .expect("Unable to aggregate reported values")
+            .expect("Property not found");
+
+        assert_eq!(aggregation.number_exponent, -2);
+        assert_eq!(aggregation.buckets.len(), 2);
+
+        assert_eq!(aggregation.buckets[0].bucket_start, 0);
+        assert_eq!(aggregation.buckets[0].value_count, 2);
+        assert_eq!(aggregation.buckets[0].last_timestamp, 20);
+        assert!(matches!(
+            aggregation.buckets[0].aggregate,
+            ReportedValueAggregate::Number { min, max, avg, last }
+                if min == 20.1 && max == 20.3 && avg == 20.2 && last == 20.3
+        ));
+
+        assert_eq!(aggregation.buckets[1].bucket_start, 60);
+        assert_eq!(aggregation.buckets[1].value_count, 1);
+        assert!(matches!(
+            aggregation.buckets[1].aggregate,
+            ReportedValueAggregate::Number { min, max, avg, last }
+                if min == 25.0 && max == 25.0 && avg == 25.0 && last == 25.0
+        ));
+    }
+}
@@ -56,6 +56,20 @@ impl fmt::Display for EventError {
     }
 }
 
+#[cfg(feature = "event-sinks")]
+#[derive(Debug)]
+pub struct EventSinkError(pub String);
+
+#[cfg(feature = "event-sinks")]
+impl Error for EventSinkError {}
+
+#[cfg(feature = "event-sinks")]
+impl fmt::Display for EventSinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Event Sink Error: {}", self.0)
+    }
+}
+
 impl From<InternalError> for EventError {
     fn from(err: InternalError) -> Self {
         EventError(format!("{}", err))
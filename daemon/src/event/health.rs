@@ -0,0 +1,473 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! The state of gridd's event subscriptions, and how far the commits received from them have
+//! been indexed
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{Checkpoint, CommitEvent, EventError, EventHandler, EventProcessorError};
+
+/// The state of the subscription to a source's commits
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionState {
+    Subscribed,
+    /// The subscription ended, so no further commits will be received from the source
+    Unsubscribed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SubscriptionReport {
+    pub source: String,
+    pub state: SubscriptionState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A commit that reached a stage, along with when it did
+#[derive(Clone, Debug, Serialize)]
+pub struct CommitMark {
+    pub commit_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    /// In seconds since the Unix epoch
+    pub time: Option<u64>,
+}
+
+/// How far the commits of a service, or of Sawtooth if it has no service ID, have been indexed
+#[derive(Clone, Debug, Serialize)]
+pub struct StreamReport {
+    pub service_id: Option<String>,
+    /// The number of commits received since gridd started
+    pub commits_received: u64,
+    /// The number of commits indexed since gridd started
+    pub commits_indexed: u64,
+    /// The number of received commits that are waiting to be indexed
+    pub pending: usize,
+    pub last_received: Option<CommitMark>,
+    pub last_indexed: Option<CommitMark>,
+    /// Why the last commit could not be indexed; cleared once a commit is indexed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EventHealthReport {
+    pub subscriptions: Vec<SubscriptionReport>,
+    pub streams: Vec<StreamReport>,
+}
+
+impl EventHealthReport {
+    /// Returns why commits are not being indexed: an ended subscription, or a commit that could
+    /// not be indexed
+    pub fn errors(&self) -> Vec<String> {
+        self.subscriptions
+            .iter()
+            .filter(|subscription| subscription.state == SubscriptionState::Unsubscribed)
+            .map(|subscription| {
+                format!(
+                    "Not subscribed to {}: {}",
+                    subscription.source,
+                    subscription.error.as_deref().unwrap_or("unknown error")
+                )
+            })
+            .chain(
+                self.streams
+                    .iter()
+                    .filter_map(|stream| stream.error.clone()),
+            )
+            .collect()
+    }
+}
+
+/// Shared record of the event subscriptions and of the commits passing through the handlers
+#[derive(Clone, Default)]
+pub struct EventHealth {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    subscriptions: Vec<SubscriptionReport>,
+    streams: BTreeMap<Option<String>, Stream>,
+}
+
+struct Stream {
+    report: StreamReport,
+    /// The IDs of the received commits that have not been indexed, oldest first
+    pending: VecDeque<String>,
+}
+
+impl EventHealth {
+    pub fn subscribed(&self, source: &str) {
+        self.set_subscription(source, SubscriptionState::Subscribed, None);
+    }
+
+    pub fn unsubscribed(&self, source: &str, error: &str) {
+        self.set_subscription(
+            source,
+            SubscriptionState::Unsubscribed,
+            Some(error.to_string()),
+        );
+    }
+
+    /// Marks the source as unsubscribed once its event processor stops, returning a handle that
+    /// joins the processor in place of its own
+    pub fn watch_processor(
+        &self,
+        source: &str,
+        join_handle: thread::JoinHandle<Result<(), EventProcessorError>>,
+    ) -> Result<thread::JoinHandle<Result<(), EventProcessorError>>, EventProcessorError> {
+        let health = self.clone();
+        let source = source.to_string();
+        thread::Builder::new()
+            .name(format!("EventProcessorWatcher[{}]", source))
+            .spawn(move || {
+                let result = join_handle.join().unwrap_or_else(|_| {
+                    Err(EventProcessorError(
+                        "The event processor thread panicked".into(),
+                    ))
+                });
+                health.unsubscribed(&source, "Stopped receiving commits");
+                result
+            })
+            .map_err(|err| {
+                EventProcessorError(format!(
+                    "Unable to start EventProcessorWatcher thread: {}",
+                    err
+                ))
+            })
+    }
+
+    /// Records a received commit. A commit that is received again as its handling is retried is
+    /// only counted once.
+    pub fn record_received(&self, event: &CommitEvent) {
+        let mark = commit_mark(event);
+        let mut inner = self.lock();
+        let stream = inner.stream(event);
+        if stream.pending.back() == Some(&event.id) {
+            return;
+        }
+        stream.report.commits_received += 1;
+        stream.report.last_received = Some(mark);
+        stream.pending.push_back(event.id.clone());
+    }
+
+    /// Records an indexed commit. The commits received before it are no longer pending, whether
+    /// they were indexed or skipped, as commits are indexed in the order they are received.
+    pub fn record_indexed(&self, event: &CommitEvent) {
+        let mark = commit_mark(event);
+        let mut inner = self.lock();
+        let stream = inner.stream(event);
+        stream.report.commits_indexed += 1;
+        stream.report.last_indexed = Some(mark);
+        stream.report.error = None;
+        if stream.pending.contains(&event.id) {
+            while let Some(commit_id) = stream.pending.pop_front() {
+                if commit_id == event.id {
+                    break;
+                }
+            }
+        }
+    }
+
+    pub fn record_failure(&self, event: &CommitEvent, err: &EventError) {
+        let mut inner = self.lock();
+        inner.stream(event).report.error =
+            Some(format!("Unable to index commit {}: {}", event.id, err));
+    }
+
+    pub fn report(&self) -> EventHealthReport {
+        let inner = self.lock();
+        EventHealthReport {
+            subscriptions: inner.subscriptions.clone(),
+            streams: inner
+                .streams
+                .values()
+                .map(|stream| StreamReport {
+                    pending: stream.pending.len(),
+                    ..stream.report.clone()
+                })
+                .collect(),
+        }
+    }
+
+    fn set_subscription(&self, source: &str, state: SubscriptionState, error: Option<String>) {
+        let mut inner = self.lock();
+        match inner
+            .subscriptions
+            .iter_mut()
+            .find(|subscription| subscription.source == source)
+        {
+            Some(subscription) => {
+                subscription.state = state;
+                subscription.error = error;
+            }
+            None => inner.subscriptions.push(SubscriptionReport {
+                source: source.to_string(),
+                state,
+                error,
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<Inner> {
+        self.inner.lock().unwrap_or_else(|err| {
+            warn!("Attempting to recover from a poisoned lock in event health");
+            err.into_inner()
+        })
+    }
+}
+
+impl Inner {
+    fn stream(&mut self, event: &CommitEvent) -> &mut Stream {
+        self.streams
+            .entry(event.service_id.clone())
+            .or_insert_with(|| Stream {
+                report: StreamReport {
+                    service_id: event.service_id.clone(),
+                    commits_received: 0,
+                    commits_indexed: 0,
+                    pending: 0,
+                    last_received: None,
+                    last_indexed: None,
+                    error: None,
+                },
+                pending: VecDeque::new(),
+            })
+    }
+}
+
+fn commit_mark(event: &CommitEvent) -> CommitMark {
+    CommitMark {
+        commit_id: event.id.clone(),
+        height: event.height,
+        time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .ok(),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Stage {
+    Received,
+    Indexed,
+}
+
+/// Records the commits passing through a handler in the event health
+pub struct EventHealthHandler {
+    stage: Stage,
+    health: EventHealth,
+    handler: Box<dyn EventHandler>,
+}
+
+impl EventHealthHandler {
+    /// Wraps a handler that passes commits on to be indexed, such as a `ChannelEventHandler`
+    pub fn received(health: EventHealth, handler: Box<dyn EventHandler>) -> Self {
+        Self {
+            stage: Stage::Received,
+            health,
+            handler,
+        }
+    }
+
+    /// Wraps the handler that indexes commits into the database
+    pub fn indexed(health: EventHealth, handler: Box<dyn EventHandler>) -> Self {
+        Self {
+            stage: Stage::Indexed,
+            health,
+            handler,
+        }
+    }
+}
+
+impl EventHandler for EventHealthHandler {
+    fn name(&self) -> &str {
+        self.handler.name()
+    }
+
+    fn handle_event(&self, event: &CommitEvent) -> Result<(), EventError> {
+        match self.stage {
+            Stage::Received => {
+                // Recorded first, as the commit may be indexed before this returns
+                self.health.record_received(event);
+                self.handler.handle_event(event)
+            }
+            Stage::Indexed => match self.handler.handle_event(event) {
+                Ok(()) => {
+                    self.health.record_indexed(event);
+                    Ok(())
+                }
+                Err(err) => {
+                    self.health.record_failure(event, &err);
+                    Err(err)
+                }
+            },
+        }
+    }
+
+    fn checkpoint(&self, service_id: Option<&str>) -> Result<Option<Checkpoint>, EventError> {
+        self.handler.checkpoint(service_id)
+    }
+
+    fn cloned_box(&self) -> Box<dyn EventHandler> {
+        Box::new(EventHealthHandler {
+            stage: self.stage,
+            health: self.health.clone(),
+            handler: self.handler.cloned_box(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Clone, Default)]
+    struct TestHandler {
+        failing: Arc<AtomicBool>,
+    }
+
+    impl EventHandler for TestHandler {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn handle_event(&self, event: &CommitEvent) -> Result<(), EventError> {
+            if self.failing.load(Ordering::SeqCst) {
+                Err(EventError(format!("Unable to handle {}", event.id)))
+            } else {
+                Ok(())
+            }
+        }
+
+        fn checkpoint(&self, _service_id: Option<&str>) -> Result<Option<Checkpoint>, EventError> {
+            Ok(None)
+        }
+
+        fn cloned_box(&self) -> Box<dyn EventHandler> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn commit(service_id: &str, id: &str) -> CommitEvent {
+        CommitEvent {
+            service_id: Some(service_id.to_string()),
+            id: id.to_string(),
+            height: None,
+            state_changes: vec![],
+        }
+    }
+
+    /// Verify that the commits received and not yet indexed are pending, including those that
+    /// were skipped by the indexing handler, and that a failure to index a commit is reported
+    /// until a commit is indexed.
+    #[test]
+    fn test_record_progress() {
+        let health = EventHealth::default();
+        let indexer = TestHandler::default();
+        let received =
+            EventHealthHandler::received(health.clone(), Box::new(TestHandler::default()));
+        let indexed = EventHealthHandler::indexed(health.clone(), indexer.cloned_box());
+
+        for id in &["commit-1", "commit-2", "commit-3"] {
+            received
+                .handle_event(&commit("circuit::gsAA", id))
+                .expect("Unable to receive commit");
+        }
+        received
+            .handle_event(&commit("circuit::gsBB", "commit-a"))
+            .expect("Unable to receive commit");
+
+        // commit-1 was indexed before gridd restarted, so it is skipped
+        indexed
+            .handle_event(&commit("circuit::gsAA", "commit-2"))
+            .expect("Unable to index commit");
+
+        let report = health.report();
+        assert_eq!(2, report.streams.len());
+        assert_eq!(
+            Some("circuit::gsAA"),
+            report.streams[0].service_id.as_deref()
+        );
+        assert_eq!(3, report.streams[0].commits_received);
+        assert_eq!(1, report.streams[0].commits_indexed);
+        assert_eq!(1, report.streams[0].pending);
+        assert_eq!(
+            Some("commit-2"),
+            report.streams[0]
+                .last_indexed
+                .as_ref()
+                .map(|mark| mark.commit_id.as_str())
+        );
+        assert_eq!(1, report.streams[1].pending);
+        assert!(report.errors().is_empty());
+
+        indexer.failing.store(true, Ordering::SeqCst);
+        assert!(indexed
+            .cloned_box()
+            .handle_event(&commit("circuit::gsAA", "commit-3"))
+            .is_err());
+        let report = health.report();
+        assert_eq!(1, report.streams[0].pending);
+        assert_eq!(
+            vec!["Unable to index commit commit-3: Event Error: Unable to handle commit-3"],
+            report.errors()
+        );
+
+        indexer.failing.store(false, Ordering::SeqCst);
+        indexed
+            .handle_event(&commit("circuit::gsAA", "commit-3"))
+            .expect("Unable to index commit");
+        let report = health.report();
+        assert_eq!(0, report.streams[0].pending);
+        assert!(report.errors().is_empty());
+    }
+
+    /// Verify that a source is reported as unsubscribed once its event processor stops.
+    #[test]
+    fn test_watch_processor() {
+        let health = EventHealth::default();
+        health.subscribed("tcp://validator:4004");
+        assert!(health.report().errors().is_empty());
+
+        let join_handle = thread::spawn(|| Ok(()));
+        health
+            .watch_processor("tcp://validator:4004", join_handle)
+            .expect("Unable to watch processor")
+            .join()
+            .expect("Unable to join watcher")
+            .expect("Event processor failed");
+
+        let report = health.report();
+        assert_eq!(
+            SubscriptionState::Unsubscribed,
+            report.subscriptions[0].state
+        );
+        assert_eq!(
+            vec!["Not subscribed to tcp://validator:4004: Stopped receiving commits"],
+            report.errors()
+        );
+    }
+}
@@ -15,8 +15,27 @@
  * -----------------------------------------------------------------------------
  */
 
+#[cfg(feature = "track-and-trace")]
+pub mod alert;
+#[cfg(feature = "splinter-support")]
+pub mod channel;
+pub mod checkpoint;
 pub mod db_handler;
 mod error;
+#[cfg(all(
+    test,
+    feature = "pike",
+    any(
+        feature = "database-sqlite",
+        all(feature = "test-postgres", feature = "database-postgres")
+    )
+))]
+mod fork_replay;
+#[cfg(feature = "health")]
+pub mod health;
+pub mod registry;
+#[cfg(feature = "event-sinks")]
+pub mod sink;
 
 use std::cell::RefCell;
 use std::thread;
@@ -38,7 +57,14 @@ cfg_if! {
 use grid_sdk::track_and_trace::addressing::TRACK_AND_TRACE_NAMESPACE;
 
 use grid_sdk::commits::store::{CommitEvent as DbCommitEvent, StateChange as DbStateChange};
-
+#[cfg(feature = "event-stream")]
+use grid_sdk::{
+    rest_api::resources::event_stream::v1::EventBroadcaster, store::TransactionalStoreFactory,
+};
+
+pub use self::checkpoint::{Checkpoint, EventDispatcher};
+#[cfg(feature = "event-sinks")]
+pub use self::error::EventSinkError;
 pub use self::error::{EventError, EventIoError, EventProcessorError};
 
 const ALL_GRID_NAMESPACES: &[&str] = &[
@@ -159,8 +185,15 @@ impl From<StateChange> for DbStateChange {
 }
 
 pub trait EventHandler: Send {
+    /// A name for the handler, used when reporting its progress
+    fn name(&self) -> &str;
+
     fn handle_event(&self, event: &CommitEvent) -> Result<(), EventError>;
 
+    /// Returns the last commit from the given service that the handler has durably processed,
+    /// or `None` if it has not processed any of the service's commits
+    fn checkpoint(&self, service_id: Option<&str>) -> Result<Option<Checkpoint>, EventError>;
+
     fn cloned_box(&self) -> Box<dyn EventHandler>;
 }
 
@@ -177,6 +210,20 @@ macro_rules! event_handlers {
     };
 }
 
+/// Creates the broadcaster that committed changes are published to for the event stream. The
+/// changes committed before the daemon started are not kept, so clients resuming from them are
+/// told to resynchronize.
+#[cfg(feature = "event-stream")]
+pub fn create_event_broadcaster(
+    store_factory: &dyn TransactionalStoreFactory,
+) -> Result<EventBroadcaster, EventError> {
+    let next_commit_num = store_factory
+        .get_grid_commit_store()
+        .get_next_commit_num()?;
+
+    Ok(EventBroadcaster::new().with_committed_through(next_commit_num - 1))
+}
+
 pub trait EventConnectionUnsubscriber: Send {
     fn unsubscribe(self) -> Result<(), EventIoError>;
 }
@@ -246,7 +293,7 @@ impl<Conn: EventConnection + 'static> EventProcessor<Conn> {
     pub fn start(
         mut connection: Conn,
         last_known_commit_id: Option<&str>,
-        event_handlers: Vec<Box<dyn EventHandler>>,
+        mut dispatcher: EventDispatcher,
     ) -> Result<Self, EventProcessorError> {
         let unsubscriber = connection
             .subscribe(ALL_GRID_NAMESPACES, last_known_commit_id)
@@ -257,7 +304,7 @@ impl<Conn: EventConnection + 'static> EventProcessor<Conn> {
             .spawn(move || {
                 loop {
                     match connection.recv() {
-                        Ok(commit_event) => handle_message(commit_event, &event_handlers),
+                        Ok(commit_event) => dispatcher.dispatch(&commit_event),
                         Err(EventIoError::InvalidMessage(msg)) => {
                             warn!("{}; ignoring...", msg);
                         }
@@ -303,11 +350,3 @@ impl<Conn: EventConnection + 'static> EventProcessor<Conn> {
         )
     }
 }
-
-fn handle_message(event: CommitEvent, event_handlers: &[Box<dyn EventHandler>]) {
-    for handler in event_handlers {
-        if let Err(err) = handler.handle_event(&event) {
-            error!("An error occurred while handling events: {}", err);
-        }
-    }
-}
@@ -0,0 +1,77 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! The set of handlers that every commit received by the daemon is passed to
+
+#[cfg(feature = "event-sinks")]
+use grid_sdk::store::TransactionalStoreFactory;
+
+use super::checkpoint::{EventDispatcher, FailurePolicy};
+#[cfg(feature = "event-sinks")]
+use super::sink::{CommitReplaySource, EventSinkConfig, SinkEventHandler};
+#[cfg(feature = "event-sinks")]
+use super::EventError;
+use super::EventHandler;
+
+/// Collects the event handlers installed for an event source.
+///
+/// Handlers are called in the order they were added, so the database handler should be added
+/// first.
+pub struct EventHandlerRegistry {
+    handlers: Vec<Box<dyn EventHandler>>,
+    failure_policy: FailurePolicy,
+}
+
+impl EventHandlerRegistry {
+    /// Creates a registry whose handlers are retried or halted on failure by the given policy
+    pub fn new(failure_policy: FailurePolicy) -> Self {
+        Self {
+            handlers: Vec::new(),
+            failure_policy,
+        }
+    }
+
+    pub fn with_handler(mut self, handler: Box<dyn EventHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    /// Starts a handler for each of the configured sinks, whose checkpoints are kept in the
+    /// given store. A sink that falls behind catches up through the given replay source.
+    #[cfg(feature = "event-sinks")]
+    pub fn with_sinks(
+        mut self,
+        sinks: &[EventSinkConfig],
+        store_factory: &dyn TransactionalStoreFactory,
+        replay_source: &dyn CommitReplaySource,
+    ) -> Result<Self, EventError> {
+        for sink in sinks {
+            info!("Publishing entity changes to event sink {}", sink.name);
+            self.handlers.push(Box::new(SinkEventHandler::start(
+                sink,
+                store_factory,
+                replay_source,
+                self.failure_policy,
+            )?));
+        }
+        Ok(self)
+    }
+
+    pub fn into_dispatcher(self) -> EventDispatcher {
+        EventDispatcher::new(self.handlers, self.failure_policy)
+    }
+}
@@ -0,0 +1,107 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! A sink that appends changes to a newline-delimited JSON file
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::{to_ndjson, EntityChange, EventSink, EventSinkError};
+
+/// Appends each commit's changes to a file, creating the file if it does not exist
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl EventSink for FileSink {
+    fn publish(&mut self, changes: &[EntityChange]) -> Result<(), EventSinkError> {
+        let bytes = to_ndjson(changes)?;
+
+        // The file is reopened for every commit so that it may be rotated while gridd runs
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|err| {
+                EventSinkError(format!("Unable to open {}: {}", self.path.display(), err))
+            })?;
+
+        file.write_all(&bytes)
+            .and_then(|_| file.flush())
+            .map_err(|err| {
+                EventSinkError(format!(
+                    "Unable to write to {}: {}",
+                    self.path.display(),
+                    err
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs;
+
+    use uuid::Uuid;
+
+    use super::super::{ChangeType, EntityType};
+
+    /// Verify that each published change is appended to the file as one line of JSON
+    #[test]
+    fn publish_appends_lines() {
+        let path = env::temp_dir().join(format!("grid-sink-{}.ndjson", Uuid::new_v4()));
+        let mut sink = FileSink::new(path.clone());
+
+        let change = |commit_id: &str| EntityChange {
+            commit_id: commit_id.to_string(),
+            height: None,
+            service_id: None,
+            entity_type: EntityType::Location,
+            change: ChangeType::Updated,
+            id: Some("0001".to_string()),
+            address: None,
+            data: Some(serde_json::json!({"location_id": "0001"})),
+        };
+
+        sink.publish(&[change("commit-1")])
+            .expect("Unable to publish first commit");
+        sink.publish(&[change("commit-2"), change("commit-2")])
+            .expect("Unable to publish second commit");
+
+        let contents = fs::read_to_string(&path).expect("Unable to read sink file");
+        fs::remove_file(&path).expect("Unable to remove sink file");
+
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "{\"commit_id\":\"commit-1\",\"entity_type\":\"location\",\"change\":\"updated\",\
+            \"id\":\"0001\",\"data\":{\"location_id\":\"0001\"}}"
+        );
+        assert!(lines[2].starts_with("{\"commit_id\":\"commit-2\""));
+    }
+}
@@ -0,0 +1,70 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! A sink that posts changes to an HTTP endpoint
+
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+
+use super::{to_ndjson, EntityChange, EventSink, EventSinkError};
+
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// POSTs each commit's changes to a URL as a newline-delimited JSON body.
+///
+/// A commit is delivered once the endpoint responds with a success status.
+pub struct HttpSink {
+    url: String,
+    client: Client,
+}
+
+impl HttpSink {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: Client::new(),
+        }
+    }
+}
+
+impl EventSink for HttpSink {
+    fn publish(&mut self, changes: &[EntityChange]) -> Result<(), EventSinkError> {
+        let body = to_ndjson(changes)?;
+
+        let response = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, NDJSON_CONTENT_TYPE)
+            .timeout(REQUEST_TIMEOUT)
+            .body(body)
+            .send()
+            .map_err(|err| EventSinkError(format!("Unable to post to {}: {}", self.url, err)))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(EventSinkError(format!(
+                "{} responded with {}",
+                self.url,
+                response.status()
+            )))
+        }
+    }
+}
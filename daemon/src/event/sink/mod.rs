@@ -0,0 +1,1176 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! Event handlers that publish the Grid entities changed by each commit to external sinks.
+//!
+//! Every sink is fed by its own thread and queue, and keeps its own checkpoint of the last
+//! commit it published, so a slow or unavailable sink falls behind on its own without blocking
+//! the database or the other sinks. A sink whose queue fills up stops being passed a service's
+//! commits, and catches up on them from its checkpoint over a subscription of its own once the
+//! service's queued commits have been published. Changes are published as newline-delimited
+//! JSON, one entity change per line.
+
+mod file;
+mod http;
+mod replay;
+#[cfg(unix)]
+mod unix_socket;
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+use grid_sdk::commits::CommitStore;
+use grid_sdk::store::{StoreFactory, TransactionalStoreFactory};
+use serde_json::Value;
+
+use super::checkpoint::{Checkpoint, FailurePolicy};
+use super::db_handler::{create_db_operations_from_state_changes, DbInsertOperation};
+use super::{CommitEvent, EventError, EventHandler, EventIoError, EventSinkError};
+
+pub use self::file::FileSink;
+pub use self::http::HttpSink;
+pub use self::replay::{CommitReplay, CommitReplaySource, ConnectionReplay};
+#[cfg(unix)]
+pub use self::unix_socket::UnixSocketSink;
+
+/// The number of commits queued for a sink before it is considered to have fallen behind
+const SINK_QUEUE_SIZE: usize = 1000;
+
+/// The chain record columns describe how an entity is stored, not the entity itself
+const CHAIN_RECORD_FIELDS: &[&str] = &["start_commit_num", "end_commit_num", "last_updated"];
+
+/// The kind of Grid entity that was changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType {
+    Agent,
+    Organization,
+    Role,
+    AlternateIdIndexEntry,
+    Schema,
+    Location,
+    Product,
+    PurchaseOrder,
+    PurchaseOrderAlternateId,
+    Property,
+    Reporter,
+    ReportedValue,
+    Proposal,
+    Record,
+    AssociatedAgent,
+}
+
+/// How an entity was changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeType {
+    Updated,
+    Deleted,
+}
+
+/// A Grid entity changed by a commit.
+///
+/// Updated entities carry their ID and their decoded contents. Deleted entities carry their ID
+/// if it is known to the database, which it is for locations, products and purchase orders, and
+/// otherwise the state address they were removed from.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EntityChange {
+    pub commit_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_id: Option<String>,
+    pub entity_type: EntityType,
+    pub change: ChangeType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// Encodes changes as newline-delimited JSON
+pub fn to_ndjson(changes: &[EntityChange]) -> Result<Vec<u8>, EventSinkError> {
+    let mut bytes = Vec::new();
+    for change in changes {
+        serde_json::to_writer(&mut bytes, change)
+            .map_err(|err| EventSinkError(format!("Unable to encode change: {}", err)))?;
+        bytes.push(b'\n');
+    }
+    Ok(bytes)
+}
+
+/// A destination that entity changes are published to
+pub trait EventSink: Send {
+    /// Delivers the changes of a single commit, returning once they have been accepted
+    fn publish(&mut self, changes: &[EntityChange]) -> Result<(), EventSinkError>;
+}
+
+/// Where a sink publishes its changes to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EventSinkTarget {
+    /// Appends to a newline-delimited JSON file
+    File(PathBuf),
+    /// Writes to a Unix domain socket that another process listens on
+    UnixSocket(PathBuf),
+    /// POSTs each commit's changes to an HTTP endpoint
+    Http(String),
+}
+
+/// A named sink, given as `NAME=URI` where the URI is a `file://`, `unix://`, `http://` or
+/// `https://` URI
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventSinkConfig {
+    pub name: String,
+    pub target: EventSinkTarget,
+}
+
+impl EventSinkConfig {
+    fn create_sink(&self) -> Result<Box<dyn EventSink>, EventError> {
+        match &self.target {
+            EventSinkTarget::File(path) => Ok(Box::new(FileSink::new(path.clone()))),
+            #[cfg(unix)]
+            EventSinkTarget::UnixSocket(path) => Ok(Box::new(UnixSocketSink::new(path.clone()))),
+            #[cfg(not(unix))]
+            EventSinkTarget::UnixSocket(_) => Err(EventError(format!(
+                "Event sink {} requires Unix domain sockets, which are not supported on this \
+                platform",
+                self.name
+            ))),
+            EventSinkTarget::Http(url) => Ok(Box::new(HttpSink::new(url.to_string()))),
+        }
+    }
+}
+
+impl FromStr for EventSinkConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, uri) = s
+            .split_once('=')
+            .ok_or_else(|| format!("'{}' is not of the form NAME=URI", s))?;
+
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "'{}' is not a valid sink name; use letters, digits, '-' and '_'",
+                name
+            ));
+        }
+
+        let target = if let Some(path) = uri.strip_prefix("file://") {
+            EventSinkTarget::File(PathBuf::from(path))
+        } else if let Some(path) = uri.strip_prefix("unix://") {
+            EventSinkTarget::UnixSocket(PathBuf::from(path))
+        } else if uri.starts_with("http://") || uri.starts_with("https://") {
+            EventSinkTarget::Http(uri.to_string())
+        } else {
+            return Err(format!(
+                "'{}' is not a file://, unix://, http:// or https:// URI",
+                uri
+            ));
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            target,
+        })
+    }
+}
+
+/// An event handler that hands each commit to a sink running on its own thread.
+///
+/// The sink's checkpoint is recorded in the commit store once a commit has been published, so
+/// the commits that were queued but not published when gridd stopped are published again.
+///
+/// The queue holds a bounded number of commits. Once it is full, the sink has fallen behind on
+/// the service whose commit did not fit: no further commits of the service are queued, and once
+/// those already queued have been published, the sink replays the rest from its checkpoint
+/// until it has published the last commit passed to it. The commits of the other services are
+/// still queued while it catches up.
+pub struct SinkEventHandler {
+    name: String,
+    sender: SyncSender<CommitEvent>,
+    progress: Arc<Mutex<SinkProgress>>,
+    store_factory: Box<dyn TransactionalStoreFactory>,
+}
+
+/// The progress of a sink through the commits of each service
+type SinkProgress = HashMap<Option<String>, ServiceProgress>;
+
+/// The progress of a sink through the commits of a service
+#[derive(Default)]
+struct ServiceProgress {
+    /// The number of the service's commits in the sink's queue
+    queued: usize,
+    /// The last commit passed to the sink, if the sink has fallen behind on the service
+    behind: Option<String>,
+    /// The commits the sink replayed past the last commit passed to it, which are skipped when
+    /// they are passed
+    replayed: VecDeque<String>,
+}
+
+impl SinkEventHandler {
+    /// Starts the thread that publishes to the configured sink, which catches up through the
+    /// given replay source whenever it falls behind
+    pub fn start(
+        config: &EventSinkConfig,
+        store_factory: &dyn TransactionalStoreFactory,
+        replay_source: &dyn CommitReplaySource,
+        failure_policy: FailurePolicy,
+    ) -> Result<Self, EventError> {
+        let sink = config.create_sink()?;
+        let (sender, receiver) = sync_channel(SINK_QUEUE_SIZE);
+        let progress = Arc::new(Mutex::new(SinkProgress::new()));
+
+        let worker = SinkWorker {
+            name: config.name.clone(),
+            sink,
+            replay_source: replay_source.cloned_box(),
+            progress: progress.clone(),
+            store_factory: store_factory.clone_box(),
+            failure_policy,
+        };
+        thread::Builder::new()
+            .name(format!("EventSink[{}]", config.name))
+            .spawn(move || worker.run(receiver))
+            .map_err(|err| {
+                EventError(format!(
+                    "Unable to start thread for event sink {}: {}",
+                    config.name, err
+                ))
+            })?;
+
+        Ok(Self {
+            name: config.name.clone(),
+            sender,
+            progress,
+            store_factory: store_factory.clone_box(),
+        })
+    }
+}
+
+impl Clone for SinkEventHandler {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            sender: self.sender.clone(),
+            progress: self.progress.clone(),
+            store_factory: self.store_factory.clone_box(),
+        }
+    }
+}
+
+impl EventHandler for SinkEventHandler {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn handle_event(&self, event: &CommitEvent) -> Result<(), EventError> {
+        let mut progress = lock_progress(&self.progress)?;
+        let service = progress.entry(event.service_id.clone()).or_default();
+
+        // Commits after the one that did not fit in the queue are replayed from the sink's
+        // checkpoint, so that it never skips a commit
+        if service.behind.is_some() {
+            service.behind = Some(event.id.clone());
+            return Ok(());
+        }
+
+        if let Some(replayed) = service.replayed.pop_front() {
+            if replayed == event.id {
+                return Ok(());
+            }
+            // The commits passed no longer follow the replayed commits, as after a fork
+            service.replayed.clear();
+        }
+
+        match self.sender.try_send(event.clone()) {
+            Ok(()) => {
+                service.queued += 1;
+                Ok(())
+            }
+            Err(TrySendError::Full(_)) => {
+                service.behind = Some(event.id.clone());
+                error!(
+                    "Sink {} has fallen {} commits behind on {}; commits from {} on will be \
+                    replayed from its checkpoint once the queued commits are published",
+                    self.name,
+                    SINK_QUEUE_SIZE,
+                    describe_service(event.service_id.as_deref()),
+                    event.id
+                );
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(_)) => Err(EventError(format!(
+                "Unable to queue event for sink {}; the sink has stopped",
+                self.name
+            ))),
+        }
+    }
+
+    fn checkpoint(&self, service_id: Option<&str>) -> Result<Option<Checkpoint>, EventError> {
+        sink_checkpoint(&*self.store_factory, &self.name, service_id)
+    }
+
+    fn cloned_box(&self) -> Box<dyn EventHandler> {
+        Box::new(self.clone())
+    }
+}
+
+/// Publishes the commits queued for a sink on the sink's own thread
+struct SinkWorker {
+    name: String,
+    sink: Box<dyn EventSink>,
+    replay_source: Box<dyn CommitReplaySource>,
+    progress: Arc<Mutex<SinkProgress>>,
+    store_factory: Box<dyn TransactionalStoreFactory>,
+    failure_policy: FailurePolicy,
+}
+
+impl SinkWorker {
+    /// Publishes every queued commit in order, then records it as the sink's checkpoint. Once
+    /// the queued commits of a service the sink has fallen behind on are published, the sink
+    /// catches up on the service before publishing the rest of the queue.
+    fn run(mut self, receiver: Receiver<CommitEvent>) {
+        let failure_policy = self.failure_policy;
+
+        while let Ok(event) = receiver.recv() {
+            if let Err(err) = failure_policy.run(
+                &format!("publish commit {} to sink {}", event.id, self.name),
+                || self.publish(&event),
+            ) {
+                error!(
+                    "Unable to publish commit {} to sink {}; no further commits will be \
+                    published to it until gridd is restarted: {}",
+                    event.id, self.name, err
+                );
+                return;
+            }
+            self.record_checkpoint(&event);
+
+            let behind = match self.dequeued(&event) {
+                Ok(behind) => behind,
+                Err(err) => {
+                    error!(
+                        "Unable to update the progress of sink {}: {}",
+                        self.name, err
+                    );
+                    return;
+                }
+            };
+
+            for service_id in behind {
+                if let Err(err) = failure_policy.run(
+                    &format!(
+                        "catch sink {} up on {}",
+                        self.name,
+                        describe_service(service_id.as_deref())
+                    ),
+                    || self.catch_up(service_id.as_deref()),
+                ) {
+                    error!(
+                        "Unable to catch sink {} up on {}; no further commits will be \
+                        published to it until gridd is restarted: {}",
+                        self.name,
+                        describe_service(service_id.as_deref()),
+                        err
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    fn publish(&mut self, event: &CommitEvent) -> Result<(), EventError> {
+        let changes = decode_commit_event(event, &*self.store_factory)?;
+        if !changes.is_empty() {
+            self.sink
+                .publish(&changes)
+                .map_err(|err| EventError(err.to_string()))?;
+        }
+        debug!(
+            "Sink {} published {} change(s) from commit {}",
+            self.name,
+            changes.len(),
+            event.id
+        );
+        Ok(())
+    }
+
+    fn record_checkpoint(&self, event: &CommitEvent) {
+        // A commit whose checkpoint is not recorded is published again when gridd restarts
+        if let Err(err) = self
+            .store_factory
+            .get_grid_commit_store()
+            .update_event_handler_checkpoint(&self.name, event.service_id.as_deref(), &event.id)
+        {
+            error!(
+                "Unable to record commit {} as the checkpoint of sink {}: {}",
+                event.id, self.name, err
+            );
+        }
+    }
+
+    /// Records that a queued commit has been published, returning the services the sink has
+    /// fallen behind on that have no more commits in the queue
+    fn dequeued(&self, event: &CommitEvent) -> Result<Vec<Option<String>>, EventError> {
+        let mut progress = lock_progress(&self.progress)?;
+        if let Some(service) = progress.get_mut(&event.service_id) {
+            service.queued = service.queued.saturating_sub(1);
+        }
+
+        Ok(progress
+            .iter()
+            .filter(|(_, service)| service.behind.is_some() && service.queued == 0)
+            .map(|(service_id, _)| service_id.clone())
+            .collect())
+    }
+
+    /// Publishes the service's commits that follow the sink's checkpoint, until the last commit
+    /// passed to the sink has been published
+    fn catch_up(&mut self, service_id: Option<&str>) -> Result<(), EventError> {
+        let checkpoint = sink_checkpoint(&*self.store_factory, &self.name, service_id)?
+            .map(|checkpoint| checkpoint.commit_id);
+        info!(
+            "Sink {} is catching up on {} from {}",
+            self.name,
+            describe_service(service_id),
+            checkpoint.as_deref().unwrap_or("the first commit")
+        );
+
+        let mut replay = self
+            .replay_source
+            .replay(service_id, checkpoint.as_deref())
+            .map_err(|err| EventError(format!("Unable to replay commits: {}", err)))?;
+
+        let mut replayed = VecDeque::new();
+        loop {
+            let event = match replay.recv() {
+                Ok(event) => event,
+                Err(EventIoError::InvalidMessage(msg)) => {
+                    warn!("{}; ignoring...", msg);
+                    continue;
+                }
+                Err(err) => return Err(EventError(format!("Unable to replay commits: {}", err))),
+            };
+
+            self.publish(&event)?;
+            self.record_checkpoint(&event);
+            replayed.push_back(event.id);
+
+            let mut progress = lock_progress(&self.progress)?;
+            let service = progress
+                .entry(service_id.map(ToString::to_string))
+                .or_default();
+            if let Some(position) = replayed
+                .iter()
+                .position(|id| service.behind.as_ref() == Some(id))
+            {
+                replayed.drain(..=position);
+                service.behind = None;
+                service.replayed = replayed;
+                info!(
+                    "Sink {} has caught up on {}",
+                    self.name,
+                    describe_service(service_id)
+                );
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Returns the last commit from the service that the sink has published
+fn sink_checkpoint(
+    store_factory: &dyn TransactionalStoreFactory,
+    name: &str,
+    service_id: Option<&str>,
+) -> Result<Option<Checkpoint>, EventError> {
+    Ok(store_factory
+        .get_grid_commit_store()
+        .get_event_handler_checkpoints(name)?
+        .into_iter()
+        .find(|checkpoint| checkpoint.service_id.as_deref() == service_id)
+        .map(|checkpoint| Checkpoint {
+            commit_id: checkpoint.commit_id,
+            commit_num: checkpoint.commit_num,
+        }))
+}
+
+fn lock_progress(progress: &Mutex<SinkProgress>) -> Result<MutexGuard<SinkProgress>, EventError> {
+    progress
+        .lock()
+        .map_err(|_| EventError("Sink progress lock was poisoned".to_string()))
+}
+
+fn describe_service(service_id: Option<&str>) -> String {
+    match service_id {
+        Some(service_id) => format!("service {}", service_id),
+        None => "the commits without a service".to_string(),
+    }
+}
+
+/// Decodes the Grid entities changed by a commit, looking up the IDs of deleted entities in the
+/// given stores
+pub fn decode_commit_event<S: StoreFactory + ?Sized>(
+    event: &CommitEvent,
+    stores: &S,
+) -> Result<Vec<EntityChange>, EventError> {
+    // The commit number only fills in the chain record columns, which are not published
+    let ops = create_db_operations_from_state_changes(
+        &event.state_changes,
+        0,
+        event.service_id.as_ref(),
+    )?;
+
+    let mut changes = Vec::new();
+    for op in ops {
+        for entity in decode_operation(op, event.service_id.as_deref(), stores)? {
+            let change = if entity.data.is_some() {
+                ChangeType::Updated
+            } else {
+                ChangeType::Deleted
+            };
+            changes.push(EntityChange {
+                commit_id: event.id.clone(),
+                height: event.height,
+                service_id: event.service_id.clone(),
+                entity_type: entity.entity_type,
+                change,
+                id: entity.id,
+                address: entity.address,
+                data: entity.data,
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+/// A changed entity: its ID, or the address a deleted entity whose ID is not known was removed
+/// from, and its contents if it was updated
+struct DecodedEntity {
+    entity_type: EntityType,
+    id: Option<String>,
+    address: Option<String>,
+    data: Option<Value>,
+}
+
+fn updated<T: serde::Serialize>(
+    entity_type: EntityType,
+    id: String,
+    entity: &T,
+) -> Result<DecodedEntity, EventError> {
+    let mut data = serde_json::to_value(entity)
+        .map_err(|err| EventError(format!("Unable to encode {:?}: {}", entity_type, err)))?;
+    strip_chain_record_fields(&mut data);
+    Ok(DecodedEntity {
+        entity_type,
+        id: Some(id),
+        address: None,
+        data: Some(data),
+    })
+}
+
+fn deleted(entity_type: EntityType, address: String, id: Option<String>) -> DecodedEntity {
+    match id {
+        Some(id) => DecodedEntity {
+            entity_type,
+            id: Some(id),
+            address: None,
+            data: None,
+        },
+        None => DecodedEntity {
+            entity_type,
+            id: None,
+            address: Some(address),
+            data: None,
+        },
+    }
+}
+
+fn strip_chain_record_fields(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for field in CHAIN_RECORD_FIELDS {
+                map.remove(*field);
+            }
+            map.values_mut().for_each(strip_chain_record_fields);
+        }
+        Value::Array(values) => values.iter_mut().for_each(strip_chain_record_fields),
+        _ => (),
+    }
+}
+
+// Deleted entities are looked up after the database handler has handled the commit, which
+// keeps the records of deleted entities
+#[allow(unused_variables)]
+fn decode_operation<S: StoreFactory + ?Sized>(
+    op: DbInsertOperation,
+    service_id: Option<&str>,
+    stores: &S,
+) -> Result<Vec<DecodedEntity>, EventError> {
+    match op {
+        #[cfg(feature = "pike")]
+        DbInsertOperation::Agents(agents) => agents
+            .iter()
+            .map(|agent| updated(EntityType::Agent, agent.public_key().to_string(), agent))
+            .collect(),
+        #[cfg(feature = "pike")]
+        DbInsertOperation::Organizations(orgs) => orgs
+            .iter()
+            .map(|org| updated(EntityType::Organization, org.org_id().to_string(), org))
+            .collect(),
+        #[cfg(feature = "pike")]
+        DbInsertOperation::Roles(roles) => roles
+            .iter()
+            .map(|role| {
+                updated(
+                    EntityType::Role,
+                    format!("{}:{}", role.org_id(), role.name()),
+                    role,
+                )
+            })
+            .collect(),
+        #[cfg(feature = "pike")]
+        DbInsertOperation::RemoveAgent(address, ..) => {
+            Ok(vec![deleted(EntityType::Agent, address, None)])
+        }
+        #[cfg(feature = "pike")]
+        DbInsertOperation::RemoveOrganization(address, ..) => {
+            Ok(vec![deleted(EntityType::Organization, address, None)])
+        }
+        #[cfg(feature = "pike")]
+        DbInsertOperation::RemoveRole(address, ..) => {
+            Ok(vec![deleted(EntityType::Role, address, None)])
+        }
+        #[cfg(feature = "pike")]
+        DbInsertOperation::RemoveAlternateIdIndexEntry(address, ..) => Ok(vec![deleted(
+            EntityType::AlternateIdIndexEntry,
+            address,
+            None,
+        )]),
+        #[cfg(feature = "schema")]
+        DbInsertOperation::GridSchemas(schemas) => schemas
+            .iter()
+            .map(|schema| updated(EntityType::Schema, schema.name.clone(), schema))
+            .collect(),
+        #[cfg(feature = "schema")]
+        DbInsertOperation::RemoveGridSchema(address, ..) => {
+            Ok(vec![deleted(EntityType::Schema, address, None)])
+        }
+        #[cfg(feature = "location")]
+        DbInsertOperation::Locations(locations) => locations
+            .iter()
+            .map(|location| updated(EntityType::Location, location.location_id.clone(), location))
+            .collect(),
+        #[cfg(feature = "location")]
+        DbInsertOperation::RemoveLocation(address, ..) => {
+            let id = stores
+                .get_grid_location_store()
+                .get_location_id_from_address(&address, service_id)?;
+            Ok(vec![deleted(EntityType::Location, address, id)])
+        }
+        #[cfg(feature = "product")]
+        DbInsertOperation::Products(products) => products
+            .iter()
+            .map(|product| {
+                updated(
+                    EntityType::Product,
+                    product.product_id().to_string(),
+                    product,
+                )
+            })
+            .collect(),
+        #[cfg(feature = "product")]
+        DbInsertOperation::RemoveProduct(address, ..) => {
+            let id = stores
+                .get_grid_product_store()
+                .get_product_id_from_address(&address, service_id)?;
+            Ok(vec![deleted(EntityType::Product, address, id)])
+        }
+        #[cfg(feature = "purchase-order")]
+        DbInsertOperation::PurchaseOrders(pos) => pos
+            .iter()
+            .map(|po| {
+                updated(
+                    EntityType::PurchaseOrder,
+                    po.purchase_order_uid().to_string(),
+                    po,
+                )
+            })
+            .collect(),
+        #[cfg(feature = "purchase-order")]
+        DbInsertOperation::RemovePurchaseOrder(address, ..) => {
+            let id = stores
+                .get_grid_purchase_order_store()
+                .get_uid_from_address(&address, service_id)?;
+            Ok(vec![deleted(EntityType::PurchaseOrder, address, id)])
+        }
+        #[cfg(feature = "purchase-order")]
+        DbInsertOperation::RemovePurchaseOrderAlternateId(address, ..) => Ok(vec![deleted(
+            EntityType::PurchaseOrderAlternateId,
+            address,
+            None,
+        )]),
+        #[cfg(feature = "track-and-trace")]
+        DbInsertOperation::Properties(properties, reporters) => properties
+            .iter()
+            .map(|property| {
+                updated(
+                    EntityType::Property,
+                    format!("{}:{}", property.record_id, property.name),
+                    property,
+                )
+            })
+            .chain(reporters.iter().map(|reporter| {
+                updated(
+                    EntityType::Reporter,
+                    format!(
+                        "{}:{}:{}",
+                        reporter.record_id, reporter.property_name, reporter.public_key
+                    ),
+                    reporter,
+                )
+            }))
+            .collect(),
+        #[cfg(feature = "track-and-trace")]
+        DbInsertOperation::ReportedValues(values) => values
+            .iter()
+            .map(|value| {
+                updated(
+                    EntityType::ReportedValue,
+                    format!("{}:{}", value.record_id, value.property_name),
+                    value,
+                )
+            })
+            .collect(),
+        #[cfg(feature = "track-and-trace")]
+        DbInsertOperation::Proposals(proposals) => proposals
+            .iter()
+            .map(|proposal| {
+                updated(
+                    EntityType::Proposal,
+                    format!("{}:{}", proposal.record_id, proposal.receiving_agent),
+                    proposal,
+                )
+            })
+            .collect(),
+        #[cfg(feature = "track-and-trace")]
+        DbInsertOperation::Records(records, associated_agents) => records
+            .iter()
+            .map(|record| updated(EntityType::Record, record.record_id.clone(), record))
+            .chain(associated_agents.iter().map(|agent| {
+                updated(
+                    EntityType::AssociatedAgent,
+                    format!("{}:{}", agent.record_id, agent.agent_id),
+                    agent,
+                )
+            }))
+            .collect(),
+        #[cfg(feature = "track-and-trace")]
+        DbInsertOperation::RemoveProperty(address, ..) => {
+            Ok(vec![deleted(EntityType::Property, address, None)])
+        }
+        #[cfg(feature = "track-and-trace")]
+        DbInsertOperation::RemoveProposals(address, ..) => {
+            Ok(vec![deleted(EntityType::Proposal, address, None)])
+        }
+        #[cfg(feature = "track-and-trace")]
+        DbInsertOperation::RemoveRecord(address, ..) => {
+            Ok(vec![deleted(EntityType::Record, address, None)])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::StateChange;
+
+    /// Verify that sink configurations are parsed from `NAME=URI`, and that names and URIs that
+    /// do not name a supported sink are rejected
+    #[test]
+    fn parse_event_sink_config() {
+        assert_eq!(
+            "warehouse=file:///var/lib/grid/changes.ndjson".parse(),
+            Ok(EventSinkConfig {
+                name: "warehouse".to_string(),
+                target: EventSinkTarget::File(PathBuf::from("/var/lib/grid/changes.ndjson")),
+            })
+        );
+        assert_eq!(
+            "feed=unix:///run/grid/feed.sock".parse(),
+            Ok(EventSinkConfig {
+                name: "feed".to_string(),
+                target: EventSinkTarget::UnixSocket(PathBuf::from("/run/grid/feed.sock")),
+            })
+        );
+        assert_eq!(
+            "ingest=https://warehouse.example.com/grid?token=a=b".parse(),
+            Ok(EventSinkConfig {
+                name: "ingest".to_string(),
+                target: EventSinkTarget::Http(
+                    "https://warehouse.example.com/grid?token=a=b".to_string()
+                ),
+            })
+        );
+
+        assert!("file:///var/lib/grid/changes.ndjson"
+            .parse::<EventSinkConfig>()
+            .is_err());
+        assert!("=file:///changes.ndjson"
+            .parse::<EventSinkConfig>()
+            .is_err());
+        assert!("my sink=file:///changes.ndjson"
+            .parse::<EventSinkConfig>()
+            .is_err());
+        assert!("warehouse=ftp://warehouse.example.com"
+            .parse::<EventSinkConfig>()
+            .is_err());
+    }
+
+    /// Verify that a deleted entity is published with its ID and without contents, that a
+    /// deleted entity whose ID is not known is published with its address, and that changes
+    /// outside of the Grid namespaces are not published
+    #[cfg(all(feature = "product", feature = "database-sqlite"))]
+    #[test]
+    fn decode_deleted_entity() {
+        use diesel::{
+            r2d2::{ConnectionManager, Pool},
+            sqlite::SqliteConnection,
+        };
+        use grid_sdk::{
+            migrations::run_sqlite_migrations,
+            product::{
+                addressing::compute_gs1_product_address,
+                store::{ProductBuilder, ProductStore},
+            },
+            store::sqlite::SqliteStoreFactory,
+        };
+
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::<SqliteConnection>::new(":memory:"))
+            .expect("Failed to build connection pool");
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+        let store_factory = SqliteStoreFactory::new(pool);
+
+        let address = compute_gs1_product_address("00012345600012");
+        store_factory
+            .get_grid_product_store()
+            .add_product(
+                ProductBuilder::default()
+                    .with_product_id("00012345600012".to_string())
+                    .with_product_address(address.clone())
+                    .with_product_namespace("GS1".to_string())
+                    .with_owner("org".to_string())
+                    .with_start_commit_number(1)
+                    .with_end_commit_number(i64::MAX)
+                    .with_service_id(Some("circuit::service".to_string()))
+                    .build()
+                    .expect("Unable to build product"),
+            )
+            .expect("Unable to add product");
+
+        let unknown_address = compute_gs1_product_address("00012345600029");
+        let event = CommitEvent {
+            service_id: Some("circuit::service".to_string()),
+            id: "commit-1".to_string(),
+            height: Some(4),
+            state_changes: vec![
+                StateChange::Delete {
+                    key: address.clone(),
+                },
+                StateChange::Delete {
+                    key: unknown_address.clone(),
+                },
+                StateChange::Set {
+                    key: format!("00ec00{}", "0".repeat(64)),
+                    value: vec![],
+                },
+            ],
+        };
+
+        let changes = decode_commit_event(&event, &store_factory).expect("Unable to decode event");
+        assert_eq!(
+            changes,
+            vec![
+                EntityChange {
+                    commit_id: "commit-1".to_string(),
+                    height: Some(4),
+                    service_id: Some("circuit::service".to_string()),
+                    entity_type: EntityType::Product,
+                    change: ChangeType::Deleted,
+                    id: Some("00012345600012".to_string()),
+                    address: None,
+                    data: None,
+                },
+                EntityChange {
+                    commit_id: "commit-1".to_string(),
+                    height: Some(4),
+                    service_id: Some("circuit::service".to_string()),
+                    entity_type: EntityType::Product,
+                    change: ChangeType::Deleted,
+                    id: None,
+                    address: Some(unknown_address.clone()),
+                    data: None,
+                },
+            ]
+        );
+
+        assert_eq!(
+            String::from_utf8(to_ndjson(&changes).expect("Unable to encode changes"))
+                .expect("Changes are not UTF-8"),
+            format!(
+                "{{\"commit_id\":\"commit-1\",\"height\":4,\"service_id\":\"circuit::service\",\
+                \"entity_type\":\"product\",\"change\":\"deleted\",\"id\":\"00012345600012\"}}\n\
+                {{\"commit_id\":\"commit-1\",\"height\":4,\"service_id\":\"circuit::service\",\
+                \"entity_type\":\"product\",\"change\":\"deleted\",\"address\":\"{}\"}}\n",
+                unknown_address
+            )
+        );
+    }
+
+    /// Verify that a sink whose queue is full is no longer passed the commits of the service
+    /// whose commit did not fit, so that they are replayed rather than skipped, and that the
+    /// commits of other services are still queued
+    #[cfg(feature = "database-sqlite")]
+    #[test]
+    fn sink_falls_behind_per_service() {
+        use diesel::{
+            r2d2::{ConnectionManager, Pool},
+            sqlite::SqliteConnection,
+        };
+        use grid_sdk::store::sqlite::SqliteStoreFactory;
+
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::<SqliteConnection>::new(":memory:"))
+            .expect("Failed to build connection pool");
+        let (sender, receiver) = sync_channel(1);
+        let handler = SinkEventHandler {
+            name: "warehouse".to_string(),
+            sender,
+            progress: Arc::new(Mutex::new(SinkProgress::new())),
+            store_factory: Box::new(SqliteStoreFactory::new(pool)),
+        };
+
+        let event = |service_id: Option<&str>, id: &str| CommitEvent {
+            service_id: service_id.map(ToString::to_string),
+            id: id.to_string(),
+            height: None,
+            state_changes: vec![],
+        };
+        for id in &["commit-1", "commit-2", "commit-3"] {
+            handler
+                .handle_event(&event(None, id))
+                .expect("Unable to handle event");
+        }
+        assert_eq!(
+            receiver
+                .try_iter()
+                .map(|event| event.id)
+                .collect::<Vec<_>>(),
+            vec!["commit-1".to_string()]
+        );
+
+        for (service_id, id) in &[(Some("circuit::service"), "commit-a"), (None, "commit-4")] {
+            handler
+                .handle_event(&event(*service_id, id))
+                .expect("Unable to handle event");
+        }
+        assert_eq!(
+            receiver
+                .try_iter()
+                .map(|event| event.id)
+                .collect::<Vec<_>>(),
+            vec!["commit-a".to_string()]
+        );
+
+        let progress = handler.progress.lock().expect("Lock was poisoned");
+        assert_eq!(progress[&None].behind.as_deref(), Some("commit-4"));
+        assert_eq!(progress[&Some("circuit::service".to_string())].behind, None);
+    }
+
+    /// Verify that a sink that has fallen behind replays the commits that did not fit in its
+    /// queue from its checkpoint once the queued commits are published, publishing each commit
+    /// once, and that it is passed commits again once it has caught up
+    #[cfg(all(feature = "product", feature = "database-sqlite"))]
+    #[test]
+    fn sink_catches_up_from_checkpoint() {
+        use std::time::Duration;
+
+        use diesel::{
+            r2d2::{ConnectionManager, Pool},
+            sqlite::SqliteConnection,
+        };
+        use grid_sdk::{
+            migrations::run_sqlite_migrations, product::addressing::compute_gs1_product_address,
+            store::sqlite::SqliteStoreFactory,
+        };
+
+        /// Records the commits whose changes it publishes
+        struct RecordingSink(Arc<Mutex<Vec<String>>>);
+
+        impl EventSink for RecordingSink {
+            fn publish(&mut self, changes: &[EntityChange]) -> Result<(), EventSinkError> {
+                self.0
+                    .lock()
+                    .expect("Lock was poisoned")
+                    .extend(changes.iter().map(|change| change.commit_id.clone()));
+                Ok(())
+            }
+        }
+
+        /// Replays the commits that follow the checkpoint from a list of commits
+        #[derive(Clone)]
+        struct ListReplaySource(Vec<CommitEvent>);
+
+        impl CommitReplaySource for ListReplaySource {
+            fn replay(
+                &self,
+                _service_id: Option<&str>,
+                last_commit_id: Option<&str>,
+            ) -> Result<Box<dyn CommitReplay>, EventIoError> {
+                let start = self
+                    .0
+                    .iter()
+                    .position(|commit| Some(commit.id.as_str()) == last_commit_id)
+                    .map_or(0, |position| position + 1);
+                Ok(Box::new(ListReplay(
+                    self.0[start..].iter().cloned().collect(),
+                )))
+            }
+
+            fn cloned_box(&self) -> Box<dyn CommitReplaySource> {
+                Box::new(self.clone())
+            }
+        }
+
+        struct ListReplay(VecDeque<CommitEvent>);
+
+        impl CommitReplay for ListReplay {
+            fn recv(&mut self) -> Result<CommitEvent, EventIoError> {
+                self.0.pop_front().ok_or_else(|| {
+                    EventIoError::ConnectionError("No commits left to replay".to_string())
+                })
+            }
+        }
+
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::<SqliteConnection>::new(":memory:"))
+            .expect("Failed to build connection pool");
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+        let store_factory = SqliteStoreFactory::new(pool);
+
+        let commits = (1..=4)
+            .map(|num| CommitEvent {
+                service_id: None,
+                id: format!("commit-{}", num),
+                height: Some(num),
+                state_changes: vec![StateChange::Delete {
+                    key: compute_gs1_product_address(&format!("{:014}", num)),
+                }],
+            })
+            .collect::<Vec<_>>();
+
+        let (sender, receiver) = sync_channel(1);
+        let progress = Arc::new(Mutex::new(SinkProgress::new()));
+        let handler = SinkEventHandler {
+            name: "warehouse".to_string(),
+            sender,
+            progress: progress.clone(),
+            store_factory: store_factory.clone_box(),
+        };
+        // The second and third commits do not fit in the queue
+        for commit in &commits[..3] {
+            handler
+                .handle_event(commit)
+                .expect("Unable to handle event");
+        }
+
+        let published = Arc::new(Mutex::new(Vec::new()));
+        let worker = SinkWorker {
+            name: "warehouse".to_string(),
+            sink: Box::new(RecordingSink(published.clone())),
+            replay_source: Box::new(ListReplaySource(commits.clone())),
+            progress: progress.clone(),
+            store_factory: store_factory.clone_box(),
+            failure_policy: FailurePolicy::Halt,
+        };
+        let join_handle = thread::spawn(move || worker.run(receiver));
+
+        let mut attempts = 0;
+        while progress.lock().expect("Lock was poisoned")[&None]
+            .behind
+            .is_some()
+        {
+            attempts += 1;
+            assert!(attempts < 500, "Sink did not catch up");
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        handler
+            .handle_event(&commits[3])
+            .expect("Unable to handle event");
+        drop(handler);
+        join_handle.join().expect("Sink thread panicked");
+
+        assert_eq!(
+            *published.lock().expect("Lock was poisoned"),
+            vec!["commit-1", "commit-2", "commit-3", "commit-4"]
+        );
+        assert_eq!(
+            sink_checkpoint(&store_factory, "warehouse", None)
+                .expect("Unable to get checkpoint")
+                .map(|checkpoint| checkpoint.commit_id),
+            Some("commit-4".to_string())
+        );
+    }
+
+    /// Verify that the chain record columns are removed from published entities, at any depth
+    #[test]
+    fn strip_chain_record() {
+        let mut value = serde_json::json!({
+            "product_id": "0001",
+            "start_commit_num": 1,
+            "end_commit_num": 9,
+            "properties": [{"name": "weight", "start_commit_num": 1}],
+        });
+
+        strip_chain_record_fields(&mut value);
+
+        assert_eq!(
+            value,
+            serde_json::json!({"product_id": "0001", "properties": [{"name": "weight"}]})
+        );
+    }
+}
@@ -0,0 +1,88 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! The commits a sink that has fallen behind reads to catch up from its checkpoint
+
+use super::super::{
+    CommitEvent, EventConnection, EventConnectionUnsubscriber, EventIoError, ALL_GRID_NAMESPACES,
+};
+
+/// Subscribes sinks that have fallen behind to the commits that follow their checkpoints
+pub trait CommitReplaySource: Send {
+    /// Subscribes to the commits of the given service, or to the commits without a service if
+    /// `None`, that follow the given commit, or to all of them if the commit is `None`
+    fn replay(
+        &self,
+        service_id: Option<&str>,
+        last_commit_id: Option<&str>,
+    ) -> Result<Box<dyn CommitReplay>, EventIoError>;
+
+    fn cloned_box(&self) -> Box<dyn CommitReplaySource>;
+}
+
+/// The commits that follow a sink's checkpoint, received in the order they were committed. The
+/// subscription is closed when the replay is dropped.
+pub trait CommitReplay {
+    /// Returns the next commit, waiting for it to be committed if necessary
+    fn recv(&mut self) -> Result<CommitEvent, EventIoError>;
+}
+
+/// Replays commits over an event connection of its own
+pub struct ConnectionReplay<Conn: EventConnection> {
+    connection: Option<Conn>,
+    unsubscriber: Option<Conn::Unsubscriber>,
+}
+
+impl<Conn: EventConnection> ConnectionReplay<Conn> {
+    /// Subscribes the connection to the Grid commits that follow the given commit
+    pub fn subscribe(
+        mut connection: Conn,
+        last_commit_id: Option<&str>,
+    ) -> Result<Self, EventIoError> {
+        let unsubscriber = connection.subscribe(ALL_GRID_NAMESPACES, last_commit_id)?;
+        Ok(Self {
+            connection: Some(connection),
+            unsubscriber: Some(unsubscriber),
+        })
+    }
+}
+
+impl<Conn: EventConnection> CommitReplay for ConnectionReplay<Conn> {
+    fn recv(&mut self) -> Result<CommitEvent, EventIoError> {
+        match &self.connection {
+            Some(connection) => connection.recv(),
+            None => Err(EventIoError::ConnectionError(
+                "Replay connection has been closed".to_string(),
+            )),
+        }
+    }
+}
+
+impl<Conn: EventConnection> Drop for ConnectionReplay<Conn> {
+    fn drop(&mut self) {
+        if let Some(unsubscriber) = self.unsubscriber.take() {
+            if let Err(err) = unsubscriber.unsubscribe() {
+                warn!("Unable to unsubscribe replay connection: {}", err);
+            }
+        }
+        if let Some(connection) = self.connection.take() {
+            if let Err(err) = connection.close() {
+                warn!("Unable to close replay connection: {}", err);
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! A sink that writes changes to a Unix domain socket
+
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+use super::{to_ndjson, EntityChange, EventSink, EventSinkError};
+
+/// Writes each commit's changes to a socket that another process listens on.
+///
+/// The connection is opened when the first commit is published and reopened after any error,
+/// so the listener may be started or restarted independently of gridd.
+pub struct UnixSocketSink {
+    path: PathBuf,
+    stream: Option<UnixStream>,
+}
+
+impl UnixSocketSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, stream: None }
+    }
+}
+
+impl EventSink for UnixSocketSink {
+    fn publish(&mut self, changes: &[EntityChange]) -> Result<(), EventSinkError> {
+        let bytes = to_ndjson(changes)?;
+
+        let mut stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => UnixStream::connect(&self.path).map_err(|err| {
+                EventSinkError(format!(
+                    "Unable to connect to {}: {}",
+                    self.path.display(),
+                    err
+                ))
+            })?,
+        };
+
+        // The connection is only kept if the write succeeds
+        stream
+            .write_all(&bytes)
+            .and_then(|_| stream.flush())
+            .map_err(|err| {
+                EventSinkError(format!(
+                    "Unable to write to {}: {}",
+                    self.path.display(),
+                    err
+                ))
+            })?;
+        self.stream = Some(stream);
+
+        Ok(())
+    }
+}
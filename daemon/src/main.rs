@@ -39,6 +39,8 @@ mod error;
 #[cfg(feature = "event")]
 #[macro_use]
 mod event;
+#[cfg(feature = "multi-source")]
+mod multi_source;
 #[cfg(feature = "rest-api")]
 mod rest_api;
 #[cfg(feature = "sawtooth-support")]
@@ -48,10 +50,27 @@ mod splinter;
 
 use flexi_logger::{LogSpecBuilder, Logger};
 
-use crate::config::GridConfigBuilder;
+#[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+use grid_sdk::{
+    commits::store::IndexedNamespace,
+    store::{create_store_factory, ConnectionUri},
+};
+
+#[cfg(feature = "config-env")]
+use crate::config::EnvPartialGridConfigBuilder;
+#[cfg(feature = "config-toml")]
+use crate::config::TomlPartialGridConfigBuilder;
+use crate::config::{
+    ClapPartialGridConfigBuilder, DefaultPartialGridConfigBuilder, GridConfig, GridConfigBuilder,
+    PartialGridConfigBuilder,
+};
 use crate::error::DaemonError;
+#[cfg(feature = "multi-source")]
+use crate::multi_source::run_multi_source;
 #[cfg(feature = "sawtooth-support")]
 use crate::sawtooth::run_sawtooth;
+#[cfg(feature = "contract-upgrade")]
+use crate::splinter::run_contract_upgrade;
 #[cfg(feature = "splinter-support")]
 use crate::splinter::run_splinter;
 
@@ -65,7 +84,6 @@ fn run() -> Result<(), DaemonError> {
         (version: VERSION)
         (author: "Contributors to Hyperledger Grid")
         (about: "Daemon Package for Hyperledger Grid")
-        (@arg connect: -C --connect +takes_value "connection endpoint for sawtooth or splinter")
         (@arg verbose: -v +multiple "Log verbosely")
         (@arg database_url: --("database-url") +takes_value
         "specifies the database URL to connect to.")
@@ -73,6 +91,37 @@ fn run() -> Result<(), DaemonError> {
         (@arg admin_key_dir: --("admin-key-dir") +takes_value
             "directory containing the Scabbard admin key files"));
 
+    {
+        use clap::Arg;
+        let connect = Arg::with_name("connect")
+            .short("C")
+            .long("connect")
+            .takes_value(true);
+        #[cfg(not(feature = "multi-source"))]
+        let connect = connect.help("connection endpoint for sawtooth or splinter");
+        #[cfg(feature = "multi-source")]
+        let connect = connect.multiple(true).number_of_values(1).help(
+            "connection endpoint for sawtooth or splinter; may be given more than once to \
+            receive commits from several endpoints, of which the first is used by the REST API",
+        );
+        app = app.arg(connect);
+    }
+
+    #[cfg(feature = "config-toml")]
+    {
+        use clap::Arg;
+        app = app.arg(
+            Arg::with_name("config")
+                .long("config")
+                .takes_value(true)
+                .value_name("FILE")
+                .help(
+                    "TOML file that configuration values are read from when they are not given \
+                    as arguments or environment variables (Default: /etc/grid/gridd.toml)",
+                ),
+        );
+    }
+
     #[cfg(feature = "integration")]
     {
         use clap::Arg;
@@ -85,6 +134,119 @@ fn run() -> Result<(), DaemonError> {
         );
     }
 
+    #[cfg(feature = "track-and-trace")]
+    {
+        use clap::Arg;
+        app = app.arg(
+            Arg::with_name("alert_rules")
+                .long("alert-rules")
+                .takes_value(true)
+                .help("JSON file containing alert rules for reported Track and Trace values"),
+        );
+    }
+
+    #[cfg(feature = "event")]
+    {
+        use clap::Arg;
+        app = app.arg(
+            Arg::with_name("event_failure_policy")
+                .long("event-failure-policy")
+                .takes_value(true)
+                .possible_values(&["retry", "halt"])
+                .help(
+                    "Retry a commit that an event handler fails to handle until it succeeds, or \
+                    halt the handler until gridd is restarted (Default: retry)",
+                ),
+        );
+    }
+
+    #[cfg(feature = "event-sinks")]
+    {
+        use clap::Arg;
+        app = app.arg(
+            Arg::with_name("event_sink")
+                .long("event-sink")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("NAME=URI")
+                .help(
+                    "Publish entity changes to a file://, unix:// or http(s):// sink; may be \
+                    given more than once",
+                ),
+        );
+    }
+
+    #[cfg(feature = "splinter-support")]
+    {
+        use clap::Arg;
+        app = app.arg(
+            Arg::with_name("disbanded_circuit_data")
+                .long("disbanded-circuit-data")
+                .takes_value(true)
+                .possible_values(&["archive", "purge"])
+                .help(
+                    "Archive or purge the data of a service whose circuit is disbanded or \
+                    abandoned (Default: archive)",
+                ),
+        );
+    }
+
+    #[cfg(feature = "contract-upgrade")]
+    {
+        use clap::{Arg, SubCommand};
+        app = app.subcommand(
+            SubCommand::with_name("upgrade-contracts")
+                .about(
+                    "Upgrade the Grid smart contracts on existing Splinter circuits to the \
+                    versions in the available .scar files",
+                )
+                .arg(
+                    Arg::with_name("dry_run")
+                        .long("dry-run")
+                        .help("Report the changes that would be made without submitting them"),
+                )
+                .arg(
+                    Arg::with_name("circuit_id")
+                        .long("circuit-id")
+                        .takes_value(true)
+                        .help("Only upgrade the contracts on the given circuit"),
+                ),
+        );
+    }
+
+    {
+        use clap::Arg;
+        app = app
+            .arg(
+                Arg::with_name("reindex_from_commit")
+                    .long("reindex-from-commit")
+                    .takes_value(true)
+                    .value_name("COMMIT_NUM")
+                    .help(
+                        "Rebuild the database from the given commit height, requesting the \
+                        events from that height again",
+                    ),
+            )
+            .arg(
+                Arg::with_name("reindex_namespace")
+                    .long("reindex-namespace")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .requires("reindex_from_commit")
+                    .possible_values(&[
+                        "pike",
+                        "schema",
+                        "product",
+                        "location",
+                        "purchase_order",
+                        "track_and_trace",
+                    ])
+                    .help("Namespace to rebuild; all namespaces are rebuilt if unset"),
+            );
+    }
+
     let matches = app.get_matches();
 
     let log_level = match matches.occurrences_of("verbose") {
@@ -102,9 +264,53 @@ fn run() -> Result<(), DaemonError> {
         .start()
         .map_err(|err| DaemonError::from_source(Box::new(err)))?;
 
-    let config = GridConfigBuilder::default()
-        .with_cli_args(&matches)
+    // Each value is taken from the command line, then the environment, then the configuration
+    // file, and otherwise from the defaults
+    #[allow(unused_mut)]
+    let mut config_builder = GridConfigBuilder::new()
+        .with_partial_config(ClapPartialGridConfigBuilder::new(&matches).build()?);
+
+    #[cfg(feature = "config-env")]
+    {
+        config_builder =
+            config_builder.with_partial_config(EnvPartialGridConfigBuilder::new().build()?);
+    }
+
+    #[cfg(feature = "config-toml")]
+    {
+        let toml_config_builder = match matches.value_of("config") {
+            Some(file) => TomlPartialGridConfigBuilder::from_file(file)?,
+            None => TomlPartialGridConfigBuilder::from_default_file()?,
+        };
+        config_builder = config_builder.with_partial_config(toml_config_builder.build()?);
+    }
+
+    let config = config_builder
+        .with_partial_config(DefaultPartialGridConfigBuilder::new().build()?)
         .build()?;
+    config.log_as_debug();
+
+    #[cfg(feature = "contract-upgrade")]
+    {
+        if let Some(upgrade_matches) = matches.subcommand_matches("upgrade-contracts") {
+            return upgrade_contracts(
+                &config,
+                upgrade_matches.value_of("circuit_id"),
+                upgrade_matches.is_present("dry_run"),
+            );
+        }
+    }
+
+    if let Some(commit_num) = config.reindex_from_commit() {
+        reindex_database(&config, commit_num)?;
+    }
+
+    #[cfg(feature = "multi-source")]
+    {
+        if config.endpoints().len() > 1 {
+            return run_multi_source(config);
+        }
+    }
 
     if config.endpoint().starts_with("splinter:") {
         #[cfg(feature = "splinter-support")]
@@ -133,6 +339,75 @@ fn run() -> Result<(), DaemonError> {
     }
 }
 
+/// Compares the Grid smart contracts deployed on the Splinter circuits with the available `.scar`
+/// files and, unless it is a dry run, upgrades them, printing the report
+#[cfg(feature = "contract-upgrade")]
+fn upgrade_contracts(
+    config: &GridConfig,
+    circuit_id: Option<&str>,
+    dry_run: bool,
+) -> Result<(), DaemonError> {
+    if !config.endpoint().starts_with("splinter:") {
+        return Err(DaemonError::with_message(&format!(
+            "Contracts can only be upgraded on a Splinter backend; {} is not a Splinter \
+            connection endpoint",
+            config.endpoint()
+        )));
+    }
+
+    let report = run_contract_upgrade(config, circuit_id, dry_run)?;
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report)
+            .map_err(|err| DaemonError::from_source(Box::new(err)))?
+    );
+
+    Ok(())
+}
+
+/// Rewinds the configured namespaces to the given commit height; they are rebuilt from the
+/// commit events that are requested again once the daemon connects
+#[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+fn reindex_database(config: &GridConfig, commit_num: i64) -> Result<(), DaemonError> {
+    let namespaces = if config.reindex_namespaces().is_empty() {
+        &IndexedNamespace::ALL[..]
+    } else {
+        config.reindex_namespaces()
+    };
+
+    let connection_uri: ConnectionUri = config
+        .database_url()
+        .parse()
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+    let store_factory = create_store_factory(&connection_uri)
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+    store_factory
+        .get_grid_commit_store()
+        .reindex(commit_num, namespaces)
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+    info!(
+        "Reindexing {} from commit {}",
+        namespaces
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        commit_num
+    );
+
+    Ok(())
+}
+
+#[cfg(not(any(feature = "database-postgres", feature = "database-sqlite")))]
+fn reindex_database(_config: &GridConfig, _commit_num: i64) -> Result<(), DaemonError> {
+    Err(DaemonError::with_message(
+        "A database backend is required to be active to reindex the database",
+    ))
+}
+
 fn main() {
     if let Err(e) = run() {
         error!("{}", e);
@@ -0,0 +1,281 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! The health of each source that gridd receives commits from
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::event::{Checkpoint, CommitEvent, EventError, EventHandler};
+
+/// The network that a source is a node of
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    Sawtooth,
+    Splinter,
+}
+
+/// The state of the connection to a source
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceState {
+    /// The source is being connected to
+    Starting,
+    /// Commits are being received from the source
+    Running,
+    /// The source could not be connected to, or stopped sending commits
+    Failed,
+}
+
+/// The health of a source, along with the last commit received from it
+#[derive(Clone, Debug, Serialize)]
+pub struct SourceReport {
+    pub endpoint: String,
+    pub kind: SourceKind,
+    pub state: SourceState,
+    pub error: Option<String>,
+    /// The number of commits received since gridd started
+    pub commits_received: u64,
+    pub last_commit_id: Option<String>,
+    pub last_commit_service_id: Option<String>,
+    /// When the last commit was received, in seconds since the Unix epoch
+    pub last_commit_time: Option<u64>,
+}
+
+/// The health of the configured sources, in the order they were configured
+#[derive(Clone, Default)]
+pub struct SourceHealth {
+    sources: Arc<Mutex<Vec<SourceReport>>>,
+}
+
+impl SourceHealth {
+    /// Adds a source, which is starting
+    pub fn register(&self, endpoint: &str, kind: SourceKind) {
+        self.lock().push(SourceReport {
+            endpoint: endpoint.to_string(),
+            kind,
+            state: SourceState::Starting,
+            error: None,
+            commits_received: 0,
+            last_commit_id: None,
+            last_commit_service_id: None,
+            last_commit_time: None,
+        });
+    }
+
+    pub fn set_running(&self, endpoint: &str) {
+        self.update(endpoint, |source| {
+            source.state = SourceState::Running;
+            source.error = None;
+        });
+    }
+
+    pub fn set_failed(&self, endpoint: &str, error: &str) {
+        self.update(endpoint, |source| {
+            source.state = SourceState::Failed;
+            source.error = Some(error.to_string());
+        });
+    }
+
+    /// Records a commit received from the source
+    pub fn record_commit(&self, endpoint: &str, event: &CommitEvent) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .ok();
+
+        self.update(endpoint, |source| {
+            source.commits_received += 1;
+            source.last_commit_id = Some(event.id.clone());
+            source.last_commit_service_id = event.service_id.clone();
+            source.last_commit_time = now;
+        });
+    }
+
+    /// Returns the ID of the last commit received from the source
+    pub fn last_commit_id(&self, endpoint: &str) -> Option<String> {
+        self.lock()
+            .iter()
+            .find(|source| source.endpoint == endpoint)
+            .and_then(|source| source.last_commit_id.clone())
+    }
+
+    pub fn reports(&self) -> Vec<SourceReport> {
+        self.lock().clone()
+    }
+
+    fn update<F: FnOnce(&mut SourceReport)>(&self, endpoint: &str, f: F) {
+        if let Some(source) = self
+            .lock()
+            .iter_mut()
+            .find(|source| source.endpoint == endpoint)
+        {
+            f(source);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<Vec<SourceReport>> {
+        self.sources.lock().unwrap_or_else(|err| {
+            warn!("Attempting to recover from a poisoned lock in source health");
+            err.into_inner()
+        })
+    }
+}
+
+/// Records each commit received from a source in its health, then passes it on
+pub struct SourceHealthHandler {
+    endpoint: String,
+    health: SourceHealth,
+    handler: Box<dyn EventHandler>,
+}
+
+impl SourceHealthHandler {
+    pub fn new(endpoint: &str, health: SourceHealth, handler: Box<dyn EventHandler>) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            health,
+            handler,
+        }
+    }
+}
+
+impl EventHandler for SourceHealthHandler {
+    fn name(&self) -> &str {
+        self.handler.name()
+    }
+
+    fn handle_event(&self, event: &CommitEvent) -> Result<(), EventError> {
+        self.handler.handle_event(event)?;
+        self.health.record_commit(&self.endpoint, event);
+        Ok(())
+    }
+
+    fn checkpoint(&self, service_id: Option<&str>) -> Result<Option<Checkpoint>, EventError> {
+        self.handler.checkpoint(service_id)
+    }
+
+    fn cloned_box(&self) -> Box<dyn EventHandler> {
+        Box::new(SourceHealthHandler {
+            endpoint: self.endpoint.clone(),
+            health: self.health.clone(),
+            handler: self.handler.cloned_box(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::channel;
+
+    use crate::event::channel::{ChannelEventHandler, EventCmd};
+
+    /// Verify that a source starts out starting, and that it records the error it fails with
+    /// until it is running again
+    #[test]
+    fn test_state_transitions() {
+        let health = SourceHealth::default();
+        health.register("tcp://validator:4004", SourceKind::Sawtooth);
+
+        let report = health.reports().remove(0);
+        assert_eq!(SourceState::Starting, report.state);
+        assert_eq!(None, report.error);
+
+        health.set_running("tcp://validator:4004");
+        assert_eq!(SourceState::Running, health.reports()[0].state);
+
+        health.set_failed("tcp://validator:4004", "Stopped receiving commits");
+        let report = health.reports().remove(0);
+        assert_eq!(SourceState::Failed, report.state);
+        assert_eq!(Some("Stopped receiving commits"), report.error.as_deref());
+
+        health.set_running("tcp://validator:4004");
+        let report = health.reports().remove(0);
+        assert_eq!(SourceState::Running, report.state);
+        assert_eq!(None, report.error);
+
+        // Sources that were not registered are ignored
+        health.set_failed("tcp://other:4004", "Unable to subscribe");
+        assert_eq!(1, health.reports().len());
+        assert_eq!(None, health.last_commit_id("tcp://other:4004"));
+    }
+
+    /// Verify that the commits passed on by a source's handler are recorded in the source's
+    /// health, and not in that of the other sources.
+    #[test]
+    fn test_record_commits() {
+        let health = SourceHealth::default();
+        health.register("tcp://validator:4004", SourceKind::Sawtooth);
+        health.register("splinter:http://alpha:8085", SourceKind::Splinter);
+        health.set_running("splinter:http://alpha:8085");
+
+        let (sender, receiver) = channel();
+        let handler = SourceHealthHandler::new(
+            "splinter:http://alpha:8085",
+            health.clone(),
+            Box::new(ChannelEventHandler::new(sender)),
+        );
+
+        handler
+            .cloned_box()
+            .handle_event(&CommitEvent {
+                service_id: Some("circuit::gsAA".into()),
+                id: "commit-1".into(),
+                height: None,
+                state_changes: vec![],
+            })
+            .expect("Unable to handle event");
+
+        match receiver.try_recv() {
+            Ok(EventCmd::Event(event)) => assert_eq!("commit-1", event.id),
+            _ => panic!("The commit was not passed on"),
+        }
+
+        let reports = health.reports();
+        assert_eq!(SourceState::Starting, reports[0].state);
+        assert_eq!(0, reports[0].commits_received);
+        assert_eq!(SourceState::Running, reports[1].state);
+        assert_eq!(1, reports[1].commits_received);
+        assert_eq!(Some("commit-1"), reports[1].last_commit_id.as_deref());
+        assert_eq!(
+            Some("circuit::gsAA"),
+            reports[1].last_commit_service_id.as_deref()
+        );
+        assert!(reports[1].last_commit_time.is_some());
+        assert_eq!(
+            Some("commit-1".to_string()),
+            health.last_commit_id("splinter:http://alpha:8085")
+        );
+        assert_eq!(None, health.last_commit_id("tcp://validator:4004"));
+
+        drop(receiver);
+        assert!(handler
+            .handle_event(&CommitEvent {
+                service_id: Some("circuit::gsAA".into()),
+                id: "commit-2".into(),
+                height: None,
+                state_changes: vec![],
+            })
+            .is_err());
+        assert_eq!(1, health.reports()[1].commits_received);
+
+        health.set_failed("tcp://validator:4004", "Unable to subscribe");
+        assert_eq!(SourceState::Failed, health.reports()[0].state);
+    }
+}
@@ -0,0 +1,582 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! Receives commits from a Sawtooth network and any number of Splinter nodes at once.
+//!
+//! Each source has its own event processors, which pass their commits to a single dispatcher, so
+//! that the database and any sinks are written to from one thread. The commits of Sawtooth have
+//! no service ID and those of Splinter are partitioned by service, so the sources do not replace
+//! each other's commits when a fork is resolved.
+
+mod health;
+
+use std::fmt::Display;
+use std::process;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{channel, Receiver, RecvTimeoutError},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::Duration;
+
+use grid_sdk::backend::{SawtoothBackendClient, SplinterBackendClient};
+#[cfg(feature = "integration")]
+use grid_sdk::rest_api::actix_web_4::KeyState;
+use grid_sdk::rest_api::actix_web_4::{BackendState, Endpoint};
+use splinter::events::Reactor;
+
+use crate::config::GridConfig;
+use crate::error::DaemonError;
+#[cfg(feature = "event-stream")]
+use crate::event::create_event_broadcaster;
+#[cfg(feature = "health")]
+use crate::event::health::{EventHealth, EventHealthHandler};
+use crate::event::{
+    channel::{spawn_dispatcher, ChannelEventHandler, EventCmd},
+    checkpoint::FailurePolicy,
+    EventDispatcher, EventHandler, EventProcessor, EventProcessorError,
+};
+#[cfg(feature = "event-sinks")]
+use crate::event::{
+    sink::{CommitReplay, CommitReplaySource},
+    EventIoError,
+};
+use crate::rest_api;
+#[cfg(feature = "health")]
+use crate::rest_api::{HealthState, NodeHealthCheck};
+use crate::sawtooth::connection::SawtoothConnection;
+#[cfg(feature = "health")]
+use crate::sawtooth::health::SawtoothHealthCheck;
+#[cfg(feature = "event-sinks")]
+use crate::sawtooth::SawtoothReplaySource;
+#[cfg(feature = "event-sinks")]
+use crate::splinter::event::ScabbardReplaySource;
+#[cfg(feature = "health")]
+use crate::splinter::health::SplinterHealthCheck;
+use crate::splinter::{
+    create_database_store_factory, load_credentials, open_database, service_resume_points,
+    start_splinter_source,
+};
+
+use health::SourceHealthHandler;
+#[cfg(feature = "health")]
+pub use health::SourceState;
+pub use health::{SourceHealth, SourceKind};
+
+/// The delay before a Sawtooth source that could not be started, or that stopped sending commits,
+/// is first started again; the delay doubles with each attempt that fails, up to
+/// `MAX_RESTART_DELAY`
+const INITIAL_RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
+pub fn run_multi_source(config: GridConfig) -> Result<(), DaemonError> {
+    let primary_endpoint = Endpoint::from(config.endpoint());
+    let endpoints = config
+        .endpoints()
+        .iter()
+        .map(|endpoint| Endpoint::from(endpoint.as_str()))
+        .collect::<Vec<_>>();
+
+    let source_health = SourceHealth::default();
+    for (name, endpoint) in config.endpoints().iter().zip(&endpoints) {
+        let kind = if endpoint.is_sawtooth() {
+            SourceKind::Sawtooth
+        } else {
+            SourceKind::Splinter
+        };
+        source_health.register(name, kind);
+    }
+
+    let (scabbard_admin_key, authorization) = load_credentials(&config)?;
+
+    let store_factory = create_database_store_factory(&config)?;
+
+    #[cfg(feature = "event-stream")]
+    let event_broadcaster = create_event_broadcaster(&*store_factory)
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+    // The subscriptions are reported by the source health, so only the progress of the commits
+    // is recorded
+    #[cfg(feature = "health")]
+    let event_health = EventHealth::default();
+    #[cfg(feature = "health")]
+    let mut health_state =
+        HealthState::new(&config, event_health.clone())?.with_source_health(source_health.clone());
+
+    let reactor = Reactor::new();
+
+    // A sink that falls behind catches up from the source its commits were received from
+    #[cfg(feature = "event-sinks")]
+    let replay_source = SourceReplay {
+        sawtooth: endpoints
+            .iter()
+            .find(|endpoint| endpoint.is_sawtooth())
+            .map(|endpoint| SawtoothReplaySource::new(&endpoint.url())),
+        splinter: ScabbardReplaySource::new(
+            endpoints
+                .iter()
+                .filter(|endpoint| !endpoint.is_sawtooth())
+                .map(Endpoint::url)
+                .collect(),
+            reactor.igniter(),
+            &authorization,
+        ),
+    };
+
+    let (store_state, mut dispatcher, previous_commits, service_lifecycles) = open_database(
+        &config,
+        store_factory,
+        #[cfg(feature = "event-sinks")]
+        &replay_source,
+        #[cfg(feature = "event-stream")]
+        &event_broadcaster,
+        #[cfg(feature = "health")]
+        &event_health,
+    )?;
+
+    // The resume points are found before the dispatcher is moved to its thread
+    let sawtooth_resume_point = dispatcher
+        .resume_point(None)
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+    let service_resume_points =
+        service_resume_points(&mut dispatcher, previous_commits, &service_lifecycles)?;
+
+    let (event_tx, dispatcher_join_handle) = spawn_dispatcher("db-event-handler", dispatcher)
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+    let service_lifecycles = service_lifecycles.with_event_sender(event_tx.clone());
+
+    let shutting_down = Arc::new(AtomicBool::new(false));
+    let mut backend_state = None;
+    let mut sawtooth_controls = None;
+    let mut splinter_join_handles = Vec::new();
+
+    for (name, endpoint) in config.endpoints().iter().zip(endpoints) {
+        let handler: Box<dyn EventHandler> = Box::new(ChannelEventHandler::new(event_tx.clone()));
+        #[cfg(feature = "health")]
+        let handler: Box<dyn EventHandler> =
+            Box::new(EventHealthHandler::received(event_health.clone(), handler));
+        let handler: Box<dyn EventHandler> = Box::new(SourceHealthHandler::new(
+            name,
+            source_health.clone(),
+            handler,
+        ));
+
+        if endpoint.is_sawtooth() {
+            let connection = SawtoothConnection::new(&endpoint.url());
+            #[cfg(feature = "health")]
+            {
+                health_state = health_state.with_node(NodeHealthCheck::Sawtooth(
+                    SawtoothHealthCheck::new(&endpoint.url(), connection.get_sender()),
+                ));
+            }
+            if endpoint == primary_endpoint {
+                backend_state = Some(BackendState::new(Arc::new(SawtoothBackendClient::new(
+                    connection.get_sender(),
+                ))));
+            }
+
+            let (stop_sender, stop_receiver) = channel();
+            let shutdown_handle = Arc::new(Mutex::new(None));
+            let watcher = {
+                let name = name.clone();
+                let url = endpoint.url();
+                let resume_point = sawtooth_resume_point.clone();
+                let source_health = source_health.clone();
+                let shutting_down = shutting_down.clone();
+                let shutdown_handle = shutdown_handle.clone();
+                let mut connection = Some(connection);
+                thread::Builder::new()
+                    .name(format!("SourceWatcher[{}]", name))
+                    .spawn(move || {
+                        keep_source_running(
+                            &name,
+                            &source_health,
+                            &shutting_down,
+                            &stop_receiver,
+                            INITIAL_RESTART_DELAY,
+                            || {
+                                let connection = connection
+                                    .take()
+                                    .unwrap_or_else(|| SawtoothConnection::new(&url));
+                                // A source that is started again resumes after the last commit
+                                // received from it
+                                let resume_point = source_health
+                                    .last_commit_id(&name)
+                                    .or_else(|| resume_point.clone());
+
+                                // The commits are only passed on to the dispatcher thread, which
+                                // applies the configured failure policy
+                                let processor = EventProcessor::start(
+                                    connection,
+                                    resume_point.as_deref(),
+                                    EventDispatcher::new(
+                                        vec![handler.cloned_box()],
+                                        FailurePolicy::Halt,
+                                    ),
+                                )?;
+
+                                let (handle, join_handle) = processor.take_shutdown_controls();
+                                // The source is shut down here if gridd began shutting down
+                                // while it was starting
+                                let mut slot = shutdown_handle
+                                    .lock()
+                                    .unwrap_or_else(|err| err.into_inner());
+                                if shutting_down.load(Ordering::SeqCst) {
+                                    handle.shutdown()?;
+                                } else {
+                                    *slot = Some(handle);
+                                }
+                                Ok(join_handle)
+                            },
+                        )
+                    })
+                    .map_err(|_| DaemonError::with_message("Unable to spawn source watcher"))?
+            };
+            sawtooth_controls = Some((stop_sender, shutdown_handle, watcher));
+        } else {
+            if endpoint == primary_endpoint {
+                backend_state = Some(BackendState::new(Arc::new(SplinterBackendClient::new(
+                    endpoint.url(),
+                    authorization.clone(),
+                ))));
+            }
+            #[cfg(feature = "health")]
+            {
+                health_state = health_state.with_node(NodeHealthCheck::Splinter(
+                    SplinterHealthCheck::new(&endpoint.url(), &authorization),
+                ));
+            }
+
+            // A node that cannot be reached is waited for without holding up the other sources
+            let name = name.clone();
+            let igniter = reactor.igniter();
+            let resume_points = service_resume_points.clone();
+            let service_lifecycles = service_lifecycles.clone();
+            let scabbard_admin_key = scabbard_admin_key.clone();
+            let authorization = authorization.clone();
+            let source_health = source_health.clone();
+            let join_handle = thread::Builder::new()
+                .name(format!("SourceStart[{}]", name))
+                .spawn(move || {
+                    match start_splinter_source(
+                        &endpoint.url(),
+                        igniter,
+                        handler,
+                        resume_points,
+                        service_lifecycles,
+                        scabbard_admin_key,
+                        authorization,
+                    ) {
+                        Ok(()) => source_health.set_running(&name),
+                        Err(err) => {
+                            error!("Unable to receive commits from {}: {}", name, err);
+                            source_health.set_failed(&name, &err.to_string());
+                        }
+                    }
+                })
+                .map_err(|_| DaemonError::with_message("Unable to spawn source start thread"))?;
+            splinter_join_handles.push(join_handle);
+        }
+    }
+
+    let backend_state = backend_state.ok_or_else(|| {
+        DaemonError::with_message("No backend is available for the primary connection endpoint")
+    })?;
+
+    #[cfg(feature = "integration")]
+    let key_state = KeyState::new(config.key_file_name());
+
+    #[cfg(feature = "rest-api")]
+    let (rest_api_shutdown_handle, rest_api_join_handle) = rest_api::run(
+        config.rest_api_endpoint(),
+        store_state,
+        backend_state,
+        #[cfg(feature = "integration")]
+        key_state,
+        #[cfg(feature = "event-stream")]
+        event_broadcaster,
+        // The services of every node are active, so contracts are upgraded by running
+        // upgrade-contracts against each node
+        #[cfg(feature = "contract-upgrade")]
+        None,
+        #[cfg(feature = "multi-source")]
+        Some(source_health),
+        #[cfg(feature = "health")]
+        health_state,
+        primary_endpoint,
+    )
+    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+    let (sawtooth_shutdown, sawtooth_watcher) = match sawtooth_controls {
+        Some((stop_sender, shutdown_handle, watcher)) => {
+            (Some((stop_sender, shutdown_handle)), Some(watcher))
+        }
+        None => (None, None),
+    };
+    let reactor_shutdown_signaler = reactor.shutdown_signaler();
+
+    let ctrlc_triggered = AtomicBool::new(false);
+    ctrlc::set_handler(move || {
+        if ctrlc_triggered.load(Ordering::SeqCst) {
+            eprintln!("Aborting due to multiple Ctrl-C events");
+            process::exit(1);
+        }
+
+        ctrlc_triggered.store(true, Ordering::SeqCst);
+        shutting_down.store(true, Ordering::SeqCst);
+
+        if let Err(err) = reactor_shutdown_signaler.signal_shutdown() {
+            error!(
+                "Unable to signal shutdown to splinter event reactor: {}",
+                err
+            );
+        }
+
+        if let Some((stop_sender, shutdown_handle)) = &sawtooth_shutdown {
+            // The watcher may be waiting to start the source again
+            let _ = stop_sender.send(());
+            let shutdown_handle = shutdown_handle
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .take();
+            if let Some(shutdown_handle) = shutdown_handle {
+                if let Err(err) = shutdown_handle.shutdown() {
+                    error!("Unable to gracefully shutdown Event Processor: {}", err);
+                }
+            }
+        }
+
+        #[cfg(feature = "rest-api")]
+        rest_api_shutdown_handle.shutdown();
+
+        if let Err(err) = event_tx.send(EventCmd::Exit) {
+            error!(
+                "Unable to signal shutdown to the DB event handler thread: {}",
+                err
+            );
+        }
+    })
+    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+    #[cfg(feature = "rest-api")]
+    rest_api_join_handle
+        .join()
+        .map_err(|_| DaemonError::with_message("Unable to cleanly join the REST API thread"))
+        .and_then(|res| res.map_err(|err| DaemonError::from_source(Box::new(err))))?;
+
+    for join_handle in splinter_join_handles {
+        if join_handle.join().is_err() {
+            error!("Unable to cleanly join a source start thread");
+        }
+    }
+
+    if let Some(watcher) = sawtooth_watcher {
+        if watcher.join().is_err() {
+            error!("Unable to cleanly join the source watcher");
+        }
+    }
+
+    if dispatcher_join_handle.join().is_err() {
+        error!("Unable to cleanly join the DB event handler thread");
+    }
+
+    if let Err(err) = reactor.wait_for_shutdown() {
+        error!("Unable to shutdown splinter event reactor: {}", err);
+    }
+
+    Ok(())
+}
+
+/// Replays the commits without a service from the Sawtooth source, and the commits of a service
+/// from the first of the Splinter nodes it runs on
+#[cfg(feature = "event-sinks")]
+#[derive(Clone)]
+struct SourceReplay {
+    sawtooth: Option<SawtoothReplaySource>,
+    splinter: ScabbardReplaySource,
+}
+
+#[cfg(feature = "event-sinks")]
+impl CommitReplaySource for SourceReplay {
+    fn replay(
+        &self,
+        service_id: Option<&str>,
+        last_commit_id: Option<&str>,
+    ) -> Result<Box<dyn CommitReplay>, EventIoError> {
+        match (service_id, &self.sawtooth) {
+            (Some(_), _) => self.splinter.replay(service_id, last_commit_id),
+            (None, Some(sawtooth)) => sawtooth.replay(None, last_commit_id),
+            (None, None) => Err(EventIoError::ConnectionError(
+                "Unable to replay commits without a service; no Sawtooth source is configured"
+                    .to_string(),
+            )),
+        }
+    }
+
+    fn cloned_box(&self) -> Box<dyn CommitReplaySource> {
+        Box::new(self.clone())
+    }
+}
+
+/// Keeps a source running until gridd shuts down. `start` starts receiving the source's commits,
+/// returning the thread that receives them. A source that could not be started, or whose thread
+/// ends, is started again after a delay that doubles with each failed attempt, up to
+/// `MAX_RESTART_DELAY`. The delay is cut short, and the source is no longer started, once `stop`
+/// is sent a value or dropped.
+fn keep_source_running<F, E>(
+    name: &str,
+    source_health: &SourceHealth,
+    shutting_down: &AtomicBool,
+    stop: &Receiver<()>,
+    initial_delay: Duration,
+    mut start: F,
+) where
+    F: FnMut() -> Result<thread::JoinHandle<Result<(), EventProcessorError>>, E>,
+    E: Display,
+{
+    let mut delay = initial_delay;
+
+    while !shutting_down.load(Ordering::SeqCst) {
+        match start() {
+            Ok(join_handle) => {
+                source_health.set_running(name);
+                delay = initial_delay;
+
+                let error = match join_handle.join() {
+                    Ok(Ok(())) => "Stopped receiving commits".to_string(),
+                    Ok(Err(err)) => format!("Stopped receiving commits: {}", err),
+                    Err(_) => "Stopped receiving commits: the event processor panicked".into(),
+                };
+                if shutting_down.load(Ordering::SeqCst) {
+                    return;
+                }
+                error!("{} from {}", error, name);
+                source_health.set_failed(name, &error);
+            }
+            Err(err) => {
+                error!("Unable to receive commits from {}: {}", name, err);
+                source_health.set_failed(name, &err.to_string());
+            }
+        }
+
+        match stop.recv_timeout(delay) {
+            Err(RecvTimeoutError::Timeout) => (),
+            _ => return,
+        }
+        info!("Starting to receive commits from {} again", name);
+        delay = (delay * 2).min(MAX_RESTART_DELAY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+    use super::health::SourceState;
+
+    const ENDPOINT: &str = "tcp://validator:4004";
+
+    fn source_health() -> SourceHealth {
+        let source_health = SourceHealth::default();
+        source_health.register(ENDPOINT, SourceKind::Sawtooth);
+        source_health
+    }
+
+    fn stopped_processor(
+        result: Result<(), EventProcessorError>,
+    ) -> thread::JoinHandle<Result<(), EventProcessorError>> {
+        thread::spawn(move || result)
+    }
+
+    /// Verify that a source that could not be started, or that stopped sending commits, is marked
+    /// failed and started again, until gridd shuts down
+    #[test]
+    fn restart_stopped_source() {
+        let source_health = source_health();
+        let shutting_down = AtomicBool::new(false);
+        let (_stop_sender, stop_receiver) = channel();
+        let attempts = Cell::new(0);
+
+        keep_source_running(
+            ENDPOINT,
+            &source_health,
+            &shutting_down,
+            &stop_receiver,
+            Duration::from_millis(1),
+            || {
+                attempts.set(attempts.get() + 1);
+                let report = source_health.reports().remove(0);
+                match attempts.get() {
+                    1 => {
+                        assert_eq!(SourceState::Starting, report.state);
+                        Err("Unable to connect")
+                    }
+                    2 => {
+                        assert_eq!(SourceState::Failed, report.state);
+                        assert_eq!(Some("Unable to connect"), report.error.as_deref());
+                        Ok(stopped_processor(Err(EventProcessorError(
+                            "Connection lost".into(),
+                        ))))
+                    }
+                    _ => {
+                        assert_eq!(SourceState::Failed, report.state);
+                        assert_eq!(
+                            Some("Stopped receiving commits: Connection lost"),
+                            report.error.as_deref()
+                        );
+                        shutting_down.store(true, Ordering::SeqCst);
+                        Ok(stopped_processor(Ok(())))
+                    }
+                }
+            },
+        );
+
+        assert_eq!(3, attempts.get());
+        let report = source_health.reports().remove(0);
+        assert_eq!(SourceState::Running, report.state);
+        assert_eq!(None, report.error);
+    }
+
+    /// Verify that a source waiting to be started again is no longer started once it is told to
+    /// stop
+    #[test]
+    fn stop_restarting_source() {
+        let source_health = source_health();
+        let shutting_down = AtomicBool::new(false);
+        let (stop_sender, stop_receiver) = channel();
+        let attempts = Cell::new(0);
+
+        stop_sender.send(()).expect("Unable to send stop");
+        keep_source_running(
+            ENDPOINT,
+            &source_health,
+            &shutting_down,
+            &stop_receiver,
+            Duration::from_secs(60),
+            || {
+                attempts.set(attempts.get() + 1);
+                Err("Unable to connect")
+            },
+        );
+
+        assert_eq!(1, attempts.get());
+        assert_eq!(SourceState::Failed, source_health.reports()[0].state);
+    }
+}
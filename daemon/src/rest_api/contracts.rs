@@ -0,0 +1,114 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use cylinder::{jwt::JsonWebTokenParser, secp256k1::Secp256k1Context, Context};
+use grid_sdk::rest_api::resources::error::ErrorResponse;
+
+use crate::splinter::contracts::ContractUpgrader;
+
+/// Represents a `circuit_id` passed to the endpoint in the query string
+#[derive(Debug, Deserialize)]
+pub struct QueryCircuitId {
+    pub circuit_id: Option<String>,
+}
+
+/// Reports the changes that would upgrade the Grid smart contracts of each circuit, optionally
+/// only those of the circuit given by `circuit_id`
+pub async fn get_contract_upgrades(
+    req: HttpRequest,
+    upgrader: web::Data<ContractUpgrader>,
+    query: web::Query<QueryCircuitId>,
+) -> HttpResponse {
+    if let Err(res) = check_gridd_key(&req, &upgrader) {
+        return res;
+    }
+    upgrade(upgrader, query.into_inner().circuit_id, true).await
+}
+
+/// Upgrades the Grid smart contracts of each circuit, optionally only those of the circuit given
+/// by `circuit_id`
+pub async fn upgrade_contracts(
+    req: HttpRequest,
+    upgrader: web::Data<ContractUpgrader>,
+    query: web::Query<QueryCircuitId>,
+) -> HttpResponse {
+    if let Err(res) = check_gridd_key(&req, &upgrader) {
+        return res;
+    }
+    upgrade(upgrader, query.into_inner().circuit_id, false).await
+}
+
+/// Checks that a request's `Authorization` header holds a Cylinder JWT signed by the gridd key,
+/// as the upgrades are signed with that key
+fn check_gridd_key(req: &HttpRequest, upgrader: &ContractUpgrader) -> Result<(), HttpResponse> {
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer Cylinder:"))
+        .ok_or_else(|| {
+            HttpResponse::Unauthorized().json(ErrorResponse::new(
+                401,
+                "Authorization header with a Cylinder JWT signed by the gridd key is required",
+            ))
+        })?;
+
+    let verifier = Secp256k1Context::new().new_verifier();
+    let jwt = JsonWebTokenParser::new(&*verifier)
+        .parse(token)
+        .map_err(|err| {
+            HttpResponse::Unauthorized().json(ErrorResponse::new(
+                401,
+                &format!("Invalid authorization: {}", err),
+            ))
+        })?;
+
+    match upgrader.is_gridd_key(&jwt.issuer().as_hex()) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(HttpResponse::Forbidden().json(ErrorResponse::new(
+            403,
+            "Only the gridd key may upgrade the Grid smart contracts",
+        ))),
+        Err(err) => Err(upgrade_error(&err.to_string())),
+    }
+}
+
+async fn upgrade(
+    upgrader: web::Data<ContractUpgrader>,
+    circuit_id: Option<String>,
+    dry_run: bool,
+) -> HttpResponse {
+    // The upgrader reads and submits to Scabbard with a blocking client
+    let result = web::block(move || {
+        upgrader
+            .upgrade(circuit_id.as_deref(), dry_run)
+            .map_err(|err| err.to_string())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(report)) => HttpResponse::Ok().json(report),
+        Ok(Err(err)) => upgrade_error(&err),
+        Err(err) => upgrade_error(&err.to_string()),
+    }
+}
+
+fn upgrade_error(err: &str) -> HttpResponse {
+    error!("Unable to upgrade the Grid smart contracts: {}", err);
+    HttpResponse::InternalServerError().json(ErrorResponse::new(
+        500,
+        "Unable to upgrade the Grid smart contracts",
+    ))
+}
@@ -0,0 +1,349 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The liveness and readiness resources, which are polled by container orchestrators
+
+use actix_web::{web, HttpResponse};
+#[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+use diesel::r2d2::{ConnectionManager, Pool};
+use grid_sdk::rest_api::actix_web_4::StoreState;
+#[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+use grid_sdk::rest_api::resources::health::v1::DatabaseHealthCheck;
+use grid_sdk::rest_api::resources::health::v1::{HealthCheck, HealthResponse};
+#[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+use grid_sdk::store::ConnectionUri;
+
+use crate::config::GridConfig;
+use crate::error::DaemonError;
+use crate::event::health::{EventHealth, EventHealthReport};
+#[cfg(feature = "multi-source")]
+use crate::multi_source::{SourceHealth, SourceState};
+#[cfg(feature = "sawtooth-support")]
+use crate::sawtooth::health::SawtoothHealthCheck;
+#[cfg(feature = "splinter-support")]
+use crate::splinter::health::SplinterHealthCheck;
+
+/// A node that gridd receives commits from or submits batches to
+#[derive(Clone)]
+pub enum NodeHealthCheck {
+    #[cfg(feature = "sawtooth-support")]
+    Sawtooth(SawtoothHealthCheck),
+    #[cfg(feature = "splinter-support")]
+    Splinter(SplinterHealthCheck),
+}
+
+/// What the readiness resource checks.
+///
+/// gridd is ready when its database can be reached and has been migrated, it is subscribed to
+/// each of its sources, the commits it receives are being indexed, and its nodes answer
+/// requests. How far indexing lags behind is reported, but does not make gridd unready, as a
+/// node that has just started is expected to catch up.
+#[derive(Clone)]
+pub struct HealthState {
+    #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+    database_check: DatabaseHealthCheck,
+    event_health: EventHealth,
+    nodes: Vec<NodeHealthCheck>,
+    #[cfg(feature = "multi-source")]
+    source_health: Option<SourceHealth>,
+}
+
+impl HealthState {
+    pub fn new(config: &GridConfig, event_health: EventHealth) -> Result<Self, DaemonError> {
+        Ok(Self {
+            #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+            database_check: database_health_check(config)?,
+            event_health,
+            nodes: Vec::new(),
+            #[cfg(feature = "multi-source")]
+            source_health: None,
+        })
+    }
+
+    pub fn with_node(mut self, node: NodeHealthCheck) -> Self {
+        self.nodes.push(node);
+        self
+    }
+
+    /// Reports the sources of a multi-source gridd, which are subscribed to in place of those
+    /// recorded in the event health
+    #[cfg(feature = "multi-source")]
+    pub fn with_source_health(mut self, source_health: SourceHealth) -> Self {
+        self.source_health = Some(source_health);
+        self
+    }
+
+    fn checks(&self, store_state: &StoreState) -> Vec<HealthCheck> {
+        let report = self.event_health.report();
+        let mut checks = vec![
+            #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+            self.database_check.check(),
+            check_events(&report),
+        ];
+
+        #[cfg(feature = "multi-source")]
+        {
+            if let Some(source_health) = &self.source_health {
+                checks.push(check_sources(source_health));
+            }
+        }
+
+        checks.extend(self.nodes.iter().map(|node| match node {
+            #[cfg(feature = "sawtooth-support")]
+            NodeHealthCheck::Sawtooth(check) => {
+                check.check(&*store_state.store_factory.get_grid_commit_store())
+            }
+            #[cfg(feature = "splinter-support")]
+            NodeHealthCheck::Splinter(check) => check.check(&report.streams),
+        }));
+
+        checks
+    }
+}
+
+/// Returns the `events` check, which fails if a subscription has ended or the last commit
+/// received from a service could not be indexed
+fn check_events(report: &EventHealthReport) -> HealthCheck {
+    let errors = report.errors();
+
+    let check = if errors.is_empty() {
+        HealthCheck::pass("events")
+    } else {
+        HealthCheck::fail("events", &errors.join("; "))
+    };
+
+    check
+        .with_detail("subscriptions", &report.subscriptions)
+        .with_detail("streams", &report.streams)
+}
+
+/// Returns the `sources` check, which fails until every source is running
+#[cfg(feature = "multi-source")]
+fn check_sources(source_health: &SourceHealth) -> HealthCheck {
+    let sources = source_health.reports();
+    let not_running = sources
+        .iter()
+        .filter(|source| source.state != SourceState::Running)
+        .map(|source| match &source.error {
+            Some(err) => format!("{} is {:?}: {}", source.endpoint, source.state, err),
+            None => format!("{} is {:?}", source.endpoint, source.state),
+        })
+        .collect::<Vec<_>>();
+
+    let check = if not_running.is_empty() {
+        HealthCheck::pass("sources")
+    } else {
+        HealthCheck::fail("sources", &not_running.join("; "))
+    };
+
+    check.with_detail("sources", &sources)
+}
+
+/// Connects to the configured database for the `database` check. The pool is kept apart from
+/// that of the stores, so that a busy REST API does not make gridd unready.
+#[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+fn database_health_check(config: &GridConfig) -> Result<DatabaseHealthCheck, DaemonError> {
+    let connection_uri = config
+        .database_url()
+        .parse()
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+    // The pool is built without connecting, so that a database that cannot be reached is
+    // reported by the check
+    Ok(match connection_uri {
+        #[cfg(feature = "database-postgres")]
+        ConnectionUri::Postgres(_) => DatabaseHealthCheck::with_pg_pool(
+            Pool::builder()
+                .max_size(1)
+                .build_unchecked(ConnectionManager::new(config.database_url())),
+        ),
+        #[cfg(feature = "database-sqlite")]
+        ConnectionUri::Sqlite(_) => DatabaseHealthCheck::with_sqlite_pool(
+            Pool::builder()
+                .max_size(1)
+                .build_unchecked(ConnectionManager::new(config.database_url())),
+        ),
+    })
+}
+
+pub async fn get_liveness() -> HttpResponse {
+    HttpResponse::Ok().json(HealthResponse::live())
+}
+
+/// Runs each check, responding with `503 Service Unavailable` if any of them fails
+pub async fn get_readiness(
+    health_state: web::Data<HealthState>,
+    store_state: web::Data<StoreState>,
+) -> HttpResponse {
+    let health_state = health_state.get_ref().clone();
+    let store_state = store_state.get_ref().clone();
+    // The checks block on the database and the nodes
+    let response =
+        match web::block(move || HealthResponse::from_checks(health_state.checks(&store_state)))
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => HealthResponse::from_checks(vec![HealthCheck::fail(
+                "readiness",
+                &format!("Unable to run readiness checks: {}", err),
+            )]),
+        };
+
+    if response.passed() {
+        HttpResponse::Ok().json(response)
+    } else {
+        HttpResponse::ServiceUnavailable().json(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use actix_web::{http, test, App};
+    #[cfg(feature = "database-sqlite")]
+    use diesel::sqlite::SqliteConnection;
+    #[cfg(feature = "database-sqlite")]
+    use grid_sdk::migrations::run_sqlite_migrations;
+    use grid_sdk::rest_api::resources::health::v1::HealthStatus;
+
+    #[cfg(feature = "multi-source")]
+    use crate::multi_source::SourceKind;
+
+    /// Verify that the `events` check passes while gridd is subscribed to its sources, and fails
+    /// once a subscription ends.
+    #[test]
+    fn events_check() {
+        let event_health = EventHealth::default();
+        event_health.subscribed("tcp://localhost:4004");
+
+        let check = check_events(&event_health.report());
+        assert!(check.passed());
+        assert!(check.details.contains_key("subscriptions"));
+        assert!(check.details.contains_key("streams"));
+
+        event_health.unsubscribed("tcp://localhost:4004", "Stopped receiving commits");
+
+        let check = check_events(&event_health.report());
+        assert!(!check.passed());
+        assert_eq!(
+            check.error.as_deref(),
+            Some("Not subscribed to tcp://localhost:4004: Stopped receiving commits")
+        );
+    }
+
+    /// Verify that the `sources` check fails until every source is running.
+    #[cfg(feature = "multi-source")]
+    #[test]
+    fn sources_check() {
+        let source_health = SourceHealth::default();
+        source_health.register("tcp://localhost:4004", SourceKind::Sawtooth);
+        source_health.register("http://localhost:8085", SourceKind::Splinter);
+        source_health.set_running("tcp://localhost:4004");
+        source_health.set_failed("http://localhost:8085", "Connection refused");
+
+        let check = check_sources(&source_health);
+        assert!(!check.passed());
+        assert_eq!(
+            check.error.as_deref(),
+            Some("http://localhost:8085 is Failed: Connection refused")
+        );
+
+        source_health.set_running("http://localhost:8085");
+
+        assert!(check_sources(&source_health).passed());
+    }
+
+    /// Verify that the liveness resource responds with `200 OK` and no checks.
+    #[actix_web::test]
+    async fn liveness() {
+        let app =
+            test::init_service(App::new().route("/health/live", web::get().to(get_liveness))).await;
+
+        let req = test::TestRequest::get().uri("/health/live").to_request();
+        let response = test::call_service(&app, req).await;
+        assert_eq!(response.status(), http::StatusCode::OK);
+
+        let body: HealthResponse = test::read_body_json(response).await;
+        assert_eq!(body, HealthResponse::live());
+    }
+
+    /// Verify that the readiness resource responds with `200 OK` while the database is migrated
+    /// and gridd is subscribed to its sources, and with `503 Service Unavailable` once a
+    /// subscription ends.
+    #[cfg(feature = "database-sqlite")]
+    #[actix_web::test]
+    async fn readiness() {
+        let pool = create_connection_pool_and_migrate();
+        let event_health = EventHealth::default();
+        event_health.subscribed("tcp://localhost:4004");
+
+        let health_state = HealthState {
+            database_check: DatabaseHealthCheck::with_sqlite_pool(pool.clone()),
+            event_health: event_health.clone(),
+            nodes: Vec::new(),
+            #[cfg(feature = "multi-source")]
+            source_health: None,
+        };
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(health_state))
+                .app_data(web::Data::new(StoreState::with_sqlite_pool(pool)))
+                .route("/health/ready", web::get().to(get_readiness)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/health/ready").to_request();
+        let response = test::call_service(&app, req).await;
+        assert_eq!(response.status(), http::StatusCode::OK);
+
+        let body: HealthResponse = test::read_body_json(response).await;
+        assert_eq!(body.status, HealthStatus::Pass);
+        assert_eq!(
+            body.checks
+                .iter()
+                .map(|check| check.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["database", "events"]
+        );
+
+        event_health.unsubscribed("tcp://localhost:4004", "Stopped receiving commits");
+
+        let req = test::TestRequest::get().uri("/health/ready").to_request();
+        let response = test::call_service(&app, req).await;
+        assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+
+        let body: HealthResponse = test::read_body_json(response).await;
+        assert_eq!(body.status, HealthStatus::Fail);
+        assert!(body
+            .checks
+            .iter()
+            .any(|check| check.name == "events" && !check.passed()));
+    }
+
+    #[cfg(feature = "database-sqlite")]
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
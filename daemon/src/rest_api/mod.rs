@@ -13,12 +13,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "contract-upgrade")]
+mod contracts;
 pub mod error;
+#[cfg(feature = "health")]
+mod health;
+#[cfg(feature = "multi-source")]
+mod sources;
 
 use std::sync::mpsc;
 use std::thread;
 
 pub use crate::rest_api::error::RestApiServerError;
+#[cfg(feature = "health")]
+pub use crate::rest_api::health::{HealthState, NodeHealthCheck};
 
 use actix_web::{
     dev::ServerHandle,
@@ -31,6 +39,13 @@ use futures::executor::block_on;
 #[cfg(feature = "integration")]
 use grid_sdk::rest_api::actix_web_4::KeyState;
 use grid_sdk::rest_api::actix_web_4::{routes, BackendState, Endpoint, StoreState};
+#[cfg(feature = "event-stream")]
+use grid_sdk::rest_api::resources::event_stream::v1::EventBroadcaster;
+
+#[cfg(feature = "multi-source")]
+use crate::multi_source::SourceHealth;
+#[cfg(feature = "contract-upgrade")]
+use crate::splinter::contracts::ContractUpgrader;
 
 pub struct RestApiShutdownHandle {
     server_handle: ServerHandle,
@@ -42,11 +57,16 @@ impl RestApiShutdownHandle {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     bind_url: &str,
     store_state: StoreState,
     backend_state: BackendState,
     #[cfg(feature = "integration")] key_state: KeyState,
+    #[cfg(feature = "event-stream")] event_broadcaster: EventBroadcaster,
+    #[cfg(feature = "contract-upgrade")] contract_upgrader: Option<ContractUpgrader>,
+    #[cfg(feature = "multi-source")] source_health: Option<SourceHealth>,
+    #[cfg(feature = "health")] health_state: HealthState,
     endpoint: Endpoint,
 ) -> Result<
     (
@@ -78,6 +98,57 @@ pub fn run(
                             .route(web::get().to(routes::get_batch_statuses)),
                     );
 
+                #[cfg(feature = "health")]
+                {
+                    app = app
+                        .service(
+                            web::resource("/health/live")
+                                .route(web::get().to(health::get_liveness)),
+                        )
+                        .service(
+                            web::resource("/health/ready")
+                                .app_data(Data::new(health_state.clone()))
+                                .route(web::get().to(health::get_readiness)),
+                        );
+                }
+
+                #[cfg(feature = "event-stream")]
+                {
+                    app = app.service(
+                        web::resource("/events")
+                            .app_data(Data::new(event_broadcaster.clone()))
+                            .route(web::get().to(routes::stream_events)),
+                    );
+                }
+
+                #[cfg(feature = "contract-upgrade")]
+                {
+                    if let Some(contract_upgrader) = &contract_upgrader {
+                        app = app.service(
+                            web::scope("/admin/contracts")
+                                .app_data(Data::new(contract_upgrader.clone()))
+                                .route("", web::get().to(contracts::get_contract_upgrades))
+                                .route("/upgrade", web::post().to(contracts::upgrade_contracts)),
+                        );
+                    }
+                }
+
+                #[cfg(feature = "multi-source")]
+                {
+                    if let Some(source_health) = &source_health {
+                        app = app.service(
+                            web::resource("/admin/sources")
+                                .app_data(Data::new(source_health.clone()))
+                                .route(web::get().to(sources::list_sources)),
+                        );
+                    }
+                }
+
+                #[cfg(feature = "circuit-lifecycle")]
+                {
+                    app = app.route("/service", web::get().to(routes::list_services));
+                }
+
                 #[cfg(feature = "pike")]
                 {
                     app = app
@@ -121,6 +192,14 @@ pub fn run(
                     app = app
                         .route("/record", web::get().to(routes::list_records))
                         .route("/record/{record_id}", web::get().to(routes::get_record))
+                        .route(
+                            "/record/{record_id}/alerts",
+                            web::get().to(routes::list_record_alerts),
+                        )
+                        .route(
+                            "/record/{record_id}/proposals",
+                            web::get().to(routes::list_record_proposals),
+                        )
                         .route(
                             "record/{record_id}/property/{property_name}",
                             web::get().to(routes::get_record_property_name),
@@ -504,6 +583,14 @@ mod test {
             app = app
                 .route("/record", web::get().to(routes::list_records))
                 .route("/record/{record_id}", web::get().to(routes::get_record))
+                .route(
+                    "/record/{record_id}/alerts",
+                    web::get().to(routes::list_record_alerts),
+                )
+                .route(
+                    "/record/{record_id}/proposals",
+                    web::get().to(routes::list_record_proposals),
+                )
                 .route(
                     "record/{record_id}/property/{property_name}",
                     web::get().to(routes::get_record_property_name),
@@ -2172,6 +2259,101 @@ mod test {
         assert!(record_2.properties.is_empty());
     }
 
+    ///
+    /// Verifies a GET /record?location_id=... responds with an OK response
+    ///     and only the records linked to that location.
+    ///
+    #[actix_web::test]
+    #[cfg(feature = "track-and-trace")]
+    async fn test_list_records_location_filter() {
+        let pool = create_connection_pool_and_migrate();
+        let srv = create_test_server(
+            Backend::Sawtooth,
+            ResponseType::ClientBatchStatusResponseOK,
+            pool.clone(),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/record?location_id=0123456789012")
+            .to_request();
+
+        let mut records = get_multiple_records();
+        records[2].location_id = Some("0123456789012".to_string());
+        populate_record_table(records, pool.clone());
+
+        let response = test::call_service(&srv, req).await;
+
+        assert!(response.status().is_success());
+        let body: RecordListSlice = test::read_body_json(response).await;
+
+        assert_eq!(body.data.len(), 1);
+        assert_eq!(body.data[0].record_id, "TestRecord 2".to_string());
+        assert_eq!(body.data[0].location_id, Some("0123456789012".to_string()));
+    }
+
+    ///
+    /// Verifies a GET /record/{record_id}/proposals responds with an OK response
+    ///     and only the proposals matching the status and expiry filters.
+    ///
+    #[actix_web::test]
+    #[cfg(feature = "track-and-trace")]
+    async fn test_list_record_proposals_filtered() {
+        let pool = create_connection_pool_and_migrate();
+        let srv = create_test_server(
+            Backend::Sawtooth,
+            ResponseType::ClientBatchStatusResponseOK,
+            pool.clone(),
+        )
+        .await;
+
+        populate_record_table(get_record("TestRecord", None), pool.clone());
+        let proposal = get_proposal(None).remove(0);
+        populate_proposal_table(
+            vec![
+                proposal.clone(),
+                Proposal {
+                    receiving_agent: "expiring".to_string(),
+                    expires_at: Some(100),
+                    ..proposal.clone()
+                },
+                Proposal {
+                    receiving_agent: "canceled".to_string(),
+                    status: "CANCELED".to_string(),
+                    ..proposal
+                },
+            ],
+            pool.clone(),
+        );
+
+        let req = test::TestRequest::get()
+            .uri("/record/TestRecord/proposals?status=OPEN&active_as_of=150")
+            .to_request();
+        let response = test::call_service(&srv, req).await;
+
+        assert!(response.status().is_success());
+        let proposals: Vec<ProposalSlice> = test::read_body_json(response).await;
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[0].receiving_agent, KEY2.to_string());
+
+        let req = test::TestRequest::get()
+            .uri("/record/TestRecord/proposals?expired_as_of=150")
+            .to_request();
+        let response = test::call_service(&srv, req).await;
+
+        assert!(response.status().is_success());
+        let proposals: Vec<ProposalSlice> = test::read_body_json(response).await;
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[0].receiving_agent, "expiring".to_string());
+        assert_eq!(proposals[0].expires_at, Some(100));
+
+        let req = test::TestRequest::get()
+            .uri("/record/NotARecord/proposals")
+            .to_request();
+        let response = test::call_service(&srv, req).await;
+
+        assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    }
+
     ///
     /// Verifies a GET /record/{record_id} responds with an OK response
     ///     and the Record with the specified record ID.
@@ -3005,6 +3187,79 @@ mod test {
         assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
     }
 
+    ///
+    /// Verifies a GET /record/{record_id}/property/{property_name}?bucket=... responds with an OK
+    ///     response and the reported values of the property aggregated into time buckets
+    ///
+    #[actix_web::test]
+    #[cfg(feature = "track-and-trace")]
+    async fn test_fetch_record_property_buckets_ok() {
+        let pool = create_connection_pool_and_migrate();
+        let srv = create_test_server(
+            Backend::Sawtooth,
+            ResponseType::ClientBatchStatusResponseOK,
+            pool.clone(),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/record/TestRecord/property/Temperature?bucket=60")
+            .to_request();
+
+        populate_grid_schema_table(get_grid_schema_for_number_record(None), pool.clone());
+        populate_record_table(get_record("TestRecord", None), pool.clone());
+        populate_tnt_property_table(
+            get_number_property_for_record(None),
+            get_number_reported_values_for_record(None),
+            get_number_reporter_for_record(None),
+            pool.clone(),
+        );
+
+        let response = test::call_service(&srv, req).await;
+
+        assert!(response.status().is_success());
+        let aggregate: PropertyAggregateSlice = test::read_body_json(response).await;
+
+        assert_eq!(aggregate.name, "Temperature".to_string());
+        assert_eq!(aggregate.data_type, "Number".to_string());
+        assert_eq!(aggregate.bucket_size, 60);
+        assert_eq!(aggregate.buckets.len(), 2);
+
+        assert_eq!(aggregate.buckets[0].start, 0);
+        assert_eq!(aggregate.buckets[0].count, 2);
+        assert_eq!(aggregate.buckets[0].last_timestamp, 20);
+        assert_eq!(aggregate.buckets[0].min, Some(1.0));
+        assert_eq!(aggregate.buckets[0].max, Some(3.0));
+        assert_eq!(aggregate.buckets[0].avg, Some(2.0));
+        assert_eq!(aggregate.buckets[0].last, BucketValue::Number(3.0));
+
+        assert_eq!(aggregate.buckets[1].start, 60);
+        assert_eq!(aggregate.buckets[1].count, 1);
+        assert_eq!(aggregate.buckets[1].last, BucketValue::Number(5.0));
+    }
+
+    ///
+    /// Verifies a GET /record/{record_id}/property/{property_name}?bucket=0 responds with a
+    ///     Bad Request error
+    ///
+    #[actix_web::test]
+    #[cfg(feature = "track-and-trace")]
+    async fn test_fetch_record_property_buckets_zero_size() {
+        let pool = create_connection_pool_and_migrate();
+        let srv = create_test_server(
+            Backend::Sawtooth,
+            ResponseType::ClientBatchStatusResponseOK,
+            pool.clone(),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/record/TestRecord/property/Temperature?bucket=0")
+            .to_request();
+
+        let response = test::call_service(&srv, req).await;
+
+        assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    }
+
     /// Verifies a GET /purchase_order responds with an OK response with a
     ///     list_purchase_orders request.
     ///
@@ -4118,6 +4373,7 @@ mod test {
             status: "OPEN".to_string(),
             terms: "Proposal Terms".to_string(),
             service_id,
+            expires_at: None,
         }]
     }
 
@@ -4137,6 +4393,7 @@ mod test {
                 status: "OPEN".to_string(),
                 terms: "Proposal Terms".to_string(),
                 service_id: None,
+                expires_at: None,
             },
             Proposal {
                 id: None,
@@ -4151,6 +4408,7 @@ mod test {
                 status: "CANCELED".to_string(),
                 terms: "Proposal Terms".to_string(),
                 service_id: None,
+                expires_at: None,
             },
         ]
     }
@@ -4167,6 +4425,8 @@ mod test {
             owners: vec![KEY1.to_string()],
             custodians: vec![KEY2.to_string()],
             service_id,
+            product_id: None,
+            location_id: None,
         }]
     }
 
@@ -4183,6 +4443,8 @@ mod test {
                 owners: vec![KEY1.to_string()],
                 custodians: vec![KEY2.to_string()],
                 service_id: None,
+                product_id: None,
+                location_id: None,
             },
             Record {
                 id: None,
@@ -4194,6 +4456,8 @@ mod test {
                 owners: vec![KEY2.to_string(), KEY1.to_string()],
                 custodians: vec![KEY1.to_string(), KEY2.to_string()],
                 service_id: None,
+                product_id: None,
+                location_id: None,
             },
         ]
     }
@@ -4211,6 +4475,8 @@ mod test {
                 owners: vec![KEY1.to_string()],
                 custodians: vec![KEY2.to_string()],
                 service_id: None,
+                product_id: None,
+                location_id: None,
             },
             Record {
                 id: None,
@@ -4222,6 +4488,8 @@ mod test {
                 owners: vec![KEY2.to_string(), KEY1.to_string()],
                 custodians: vec![KEY1.to_string(), KEY2.to_string()],
                 service_id: None,
+                product_id: None,
+                location_id: None,
             },
             Record {
                 id: None,
@@ -4233,6 +4501,8 @@ mod test {
                 owners: vec![KEY1.to_string()],
                 custodians: vec![KEY2.to_string()],
                 service_id: None,
+                product_id: None,
+                location_id: None,
             },
         ]
     }
@@ -4524,6 +4794,87 @@ mod test {
         ]
     }
 
+    #[cfg(feature = "track-and-trace")]
+    fn get_grid_schema_for_number_record(service_id: Option<String>) -> Vec<Schema> {
+        vec![Schema {
+            start_commit_num: 0,
+            end_commit_num: i64::MAX,
+            name: "TestGridSchema".to_string(),
+            properties: vec![PropertyDefinition {
+                start_commit_num: 0,
+                end_commit_num: i64::MAX,
+                name: "Temperature".to_string(),
+                schema_name: "TestGridSchema".to_string(),
+                data_type: "Number".to_string(),
+                required: false,
+                description: "Definition Description".to_string(),
+                number_exponent: 0,
+                enum_options: vec![],
+                struct_properties: vec![],
+                service_id: service_id.clone(),
+            }],
+            description: "Example test grid schema".to_string(),
+            owner: "phillips001".to_string(),
+            service_id,
+            last_updated: None,
+        }]
+    }
+
+    #[cfg(feature = "track-and-trace")]
+    fn get_number_property_for_record(service_id: Option<String>) -> Vec<Property> {
+        vec![Property {
+            id: None,
+            start_commit_num: 0,
+            end_commit_num: i64::MAX,
+            name: "Temperature".to_string(),
+            record_id: "TestRecord".to_string(),
+            property_definition: "property_definition_1".to_string(),
+            current_page: 1,
+            wrapped: false,
+            service_id,
+        }]
+    }
+
+    #[cfg(feature = "track-and-trace")]
+    fn get_number_reporter_for_record(service_id: Option<String>) -> Vec<Reporter> {
+        vec![Reporter {
+            id: None,
+            start_commit_num: 0,
+            end_commit_num: i64::MAX,
+            property_name: "Temperature".to_string(),
+            record_id: "TestRecord".to_string(),
+            public_key: KEY1.to_string(),
+            authorized: true,
+            reporter_index: 0,
+            service_id,
+        }]
+    }
+
+    #[cfg(feature = "track-and-trace")]
+    fn get_number_reported_values_for_record(service_id: Option<String>) -> Vec<ReportedValue> {
+        [(10, 1), (20, 3), (70, 5)]
+            .iter()
+            .map(|(timestamp, value)| ReportedValue {
+                id: None,
+                start_commit_num: 0,
+                end_commit_num: i64::MAX,
+                property_name: "Temperature".to_string(),
+                record_id: "TestRecord".to_string(),
+                reporter_index: 0,
+                timestamp: *timestamp,
+                data_type: "Number".to_string(),
+                bytes_value: None,
+                boolean_value: None,
+                number_value: Some(*value),
+                string_value: None,
+                enum_value: None,
+                struct_values: None,
+                lat_long_value: None,
+                service_id: service_id.clone(),
+            })
+            .collect()
+    }
+
     fn get_product_property_value(service_id: Option<String>) -> Vec<PropertyValue> {
         vec![
             PropertyValueBuilder::default()
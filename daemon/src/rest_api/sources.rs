@@ -0,0 +1,23 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use actix_web::{web, HttpResponse};
+
+use crate::multi_source::SourceHealth;
+
+/// Reports the health of each source that commits are received from, in the order they were
+/// configured
+pub async fn list_sources(source_health: web::Data<SourceHealth>) -> HttpResponse {
+    HttpResponse::Ok().json(source_health.reports())
+}
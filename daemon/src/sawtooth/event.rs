@@ -43,6 +43,8 @@ use sawtooth_sdk::{
     },
 };
 
+#[cfg(feature = "event-sinks")]
+use crate::event::sink::{CommitReplay, CommitReplaySource, ConnectionReplay};
 use crate::event::{
     CommitEvent, EventConnection, EventConnectionUnsubscriber, EventIoError, StateChange,
 };
@@ -145,6 +147,47 @@ impl EventConnection for SawtoothConnection {
     }
 }
 
+/// Replays the commits of a Sawtooth validator over a connection of its own
+#[cfg(feature = "event-sinks")]
+#[derive(Clone)]
+pub struct SawtoothReplaySource {
+    validator_url: String,
+}
+
+#[cfg(feature = "event-sinks")]
+impl SawtoothReplaySource {
+    pub fn new(validator_url: &str) -> Self {
+        Self {
+            validator_url: validator_url.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "event-sinks")]
+impl CommitReplaySource for SawtoothReplaySource {
+    fn replay(
+        &self,
+        service_id: Option<&str>,
+        last_commit_id: Option<&str>,
+    ) -> Result<Box<dyn CommitReplay>, EventIoError> {
+        if let Some(service_id) = service_id {
+            return Err(EventIoError::ConnectionError(format!(
+                "Unable to replay the commits of service {}; Sawtooth commits have no service",
+                service_id
+            )));
+        }
+
+        Ok(Box::new(ConnectionReplay::subscribe(
+            SawtoothConnection::new(&self.validator_url),
+            last_commit_id,
+        )?))
+    }
+
+    fn cloned_box(&self) -> Box<dyn CommitReplaySource> {
+        Box::new(self.clone())
+    }
+}
+
 pub struct SawtoothEventUnsubscriber {
     message_sender: ZmqMessageSender,
 }
@@ -0,0 +1,144 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+use std::time::Duration;
+
+use grid_sdk::commits::CommitStore;
+use grid_sdk::rest_api::resources::health::v1::HealthCheck;
+use protobuf::Message as _;
+use sawtooth_sdk::{
+    messages::{
+        block::BlockHeader,
+        client_block::{
+            ClientBlockListRequest, ClientBlockListResponse, ClientBlockListResponse_Status,
+        },
+        client_list_control::ClientPagingControls,
+        validator::Message_MessageType,
+    },
+    messaging::{stream::MessageSender, zmq_stream::ZmqMessageSender},
+};
+use uuid::Uuid;
+
+/// How long the validator is waited on, so that a readiness probe is answered before it times
+/// out
+const VALIDATOR_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The block at the head of the validator's chain
+struct ChainHead {
+    block_id: String,
+    block_num: u64,
+}
+
+/// Checks that the Sawtooth validator answers requests, and reports how many blocks the commits
+/// indexed by gridd are behind the head of its chain
+#[derive(Clone)]
+pub struct SawtoothHealthCheck {
+    endpoint: String,
+    sender: ZmqMessageSender,
+}
+
+impl SawtoothHealthCheck {
+    pub fn new(endpoint: &str, sender: ZmqMessageSender) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            sender,
+        }
+    }
+
+    /// Returns the `sawtooth` check, comparing the validator's chain head with the last block
+    /// in the given commit store
+    pub fn check(&self, commit_store: &dyn CommitStore) -> HealthCheck {
+        let head = match self.get_chain_head() {
+            Ok(head) => head,
+            Err(err) => {
+                return HealthCheck::fail("sawtooth", &err).with_detail("endpoint", &self.endpoint)
+            }
+        };
+
+        let indexed_block_num = match commit_store.get_current_commit(None) {
+            Ok(commit) => commit.map(|commit| commit.commit_num),
+            Err(err) => {
+                return HealthCheck::fail(
+                    "sawtooth",
+                    &format!("Unable to get the last indexed block: {}", err),
+                )
+                .with_detail("endpoint", &self.endpoint)
+            }
+        };
+
+        // Blocks are numbered from 0, so nothing indexed is one behind the genesis block
+        let lag = head.block_num as i64 - indexed_block_num.unwrap_or(-1);
+
+        HealthCheck::pass("sawtooth")
+            .with_detail("endpoint", &self.endpoint)
+            .with_detail("head_block_id", head.block_id)
+            .with_detail("head_block_num", head.block_num)
+            .with_detail("indexed_block_num", indexed_block_num)
+            .with_detail("lag", lag.max(0))
+    }
+
+    fn get_chain_head(&self) -> Result<ChainHead, String> {
+        let mut paging = ClientPagingControls::new();
+        paging.set_limit(1);
+        let mut request = ClientBlockListRequest::new();
+        request.set_paging(paging);
+
+        let content = request
+            .write_to_bytes()
+            .map_err(|err| format!("Unable to serialize block list request: {}", err))?;
+        let response = self
+            .sender
+            .send(
+                Message_MessageType::CLIENT_BLOCK_LIST_REQUEST,
+                &Uuid::new_v4().to_string(),
+                &content,
+            )
+            .map_err(|err| format!("Unable to send request to {}: {}", self.endpoint, err))?
+            .get_timeout(VALIDATOR_TIMEOUT)
+            .map_err(|err| format!("No response from {}: {}", self.endpoint, err))?;
+
+        if response.get_message_type() != Message_MessageType::CLIENT_BLOCK_LIST_RESPONSE {
+            return Err(format!(
+                "Unexpected message type: expected {:?} but was {:?}",
+                Message_MessageType::CLIENT_BLOCK_LIST_RESPONSE,
+                response.get_message_type()
+            ));
+        }
+        let response = ClientBlockListResponse::parse_from_bytes(response.get_content())
+            .map_err(|err| format!("Unable to parse block list response: {}", err))?;
+
+        if response.get_status() != ClientBlockListResponse_Status::OK {
+            return Err(format!(
+                "Unable to get the chain head from {}: {:?}",
+                self.endpoint,
+                response.get_status()
+            ));
+        }
+
+        let block = response
+            .get_blocks()
+            .first()
+            .ok_or_else(|| format!("{} has no blocks", self.endpoint))?;
+        let header = BlockHeader::parse_from_bytes(block.get_header())
+            .map_err(|err| format!("Unable to parse block header: {}", err))?;
+
+        Ok(ChainHead {
+            block_id: block.get_header_signature().to_string(),
+            block_num: header.get_block_num(),
+        })
+    }
+}
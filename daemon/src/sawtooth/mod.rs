@@ -17,6 +17,10 @@
 
 pub mod connection;
 mod event;
+#[cfg(feature = "health")]
+pub mod health;
 mod run;
 
+#[cfg(feature = "event-sinks")]
+pub use event::SawtoothReplaySource;
 pub use run::run_sawtooth;
@@ -33,10 +33,24 @@ use grid_sdk::store::{create_store_factory, ConnectionUri};
 use crate::config::GridConfig;
 use crate::database::ConnectionPool;
 use crate::error::DaemonError;
-use crate::event::{db_handler::DatabaseEventHandler, EventProcessor};
+#[cfg(feature = "track-and-trace")]
+use crate::event::alert::load_alert_rules;
+#[cfg(feature = "event-stream")]
+use crate::event::create_event_broadcaster;
+#[cfg(feature = "health")]
+use crate::event::health::{EventHealth, EventHealthHandler};
+use crate::event::{
+    db_handler::DatabaseEventHandler, registry::EventHandlerRegistry, EventProcessor,
+};
 use crate::rest_api;
+#[cfg(feature = "health")]
+use crate::rest_api::{HealthState, NodeHealthCheck};
 
 use super::connection::SawtoothConnection;
+#[cfg(feature = "event-sinks")]
+use super::event::SawtoothReplaySource;
+#[cfg(feature = "health")]
+use super::health::SawtoothHealthCheck;
 
 pub fn run_sawtooth(config: GridConfig) -> Result<(), DaemonError> {
     let sawtooth_endpoint = Endpoint::from(config.endpoint());
@@ -52,6 +66,24 @@ pub fn run_sawtooth(config: GridConfig) -> Result<(), DaemonError> {
     let backend_client = SawtoothBackendClient::new(sawtooth_connection.get_sender());
     let backend_state = BackendState::new(Arc::new(backend_client));
 
+    #[cfg(feature = "health")]
+    let event_health = EventHealth::default();
+    #[cfg(feature = "health")]
+    let health_state =
+        HealthState::new(&config, event_health.clone())?.with_node(NodeHealthCheck::Sawtooth(
+            SawtoothHealthCheck::new(&sawtooth_endpoint.url(), sawtooth_connection.get_sender()),
+        ));
+
+    #[cfg(feature = "track-and-trace")]
+    let alert_rules = match config.alert_rules_file() {
+        Some(path) => load_alert_rules(path)?,
+        None => Vec::new(),
+    };
+
+    #[cfg(feature = "event-stream")]
+    let event_broadcaster = create_event_broadcaster(&*store_factory)
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
     #[cfg(not(any(feature = "database-postgres", feature = "database-sqlite")))]
     return Err(DaemonError::with_message(
         "A database backend is required to be active. Supported backends are postgreSQL and SQLite",
@@ -59,10 +91,10 @@ pub fn run_sawtooth(config: GridConfig) -> Result<(), DaemonError> {
 
     #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
     let (store_state, evt_processor) = {
-        let commit_store = store_factory.get_grid_commit_store();
-        let current_commit = commit_store
-            .get_current_commit_id()
-            .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+        #[cfg(feature = "event-sinks")]
+        let sink_store_factory = store_factory.clone_box();
+        #[cfg(feature = "event-sinks")]
+        let replay_source = SawtoothReplaySource::new(&sawtooth_endpoint.url());
 
         match connection_uri {
             #[cfg(feature = "database-postgres")]
@@ -70,15 +102,33 @@ pub fn run_sawtooth(config: GridConfig) -> Result<(), DaemonError> {
                 let connection_pool: ConnectionPool<diesel::pg::PgConnection> =
                     ConnectionPool::new(config.database_url())
                         .map_err(|err| DaemonError::from_source(Box::new(err)))?;
-                let store_factory = create_store_factory(&connection_uri)
-                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
                 let event_handler = DatabaseEventHandler::new(store_factory);
-                let evt_processor = EventProcessor::start(
-                    sawtooth_connection,
-                    current_commit.as_deref(),
-                    event_handlers![event_handler],
-                )
-                .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+                #[cfg(feature = "track-and-trace")]
+                let event_handler = event_handler.with_alert_rules(alert_rules);
+                #[cfg(feature = "event-stream")]
+                let event_handler = event_handler.with_event_broadcaster(event_broadcaster.clone());
+                // The commits are indexed as they are received
+                #[cfg(feature = "health")]
+                let event_handler = EventHealthHandler::received(
+                    event_health.clone(),
+                    Box::new(EventHealthHandler::indexed(
+                        event_health.clone(),
+                        Box::new(event_handler),
+                    )),
+                );
+                let registry = EventHandlerRegistry::new(config.event_failure_policy())
+                    .with_handler(Box::new(event_handler));
+                #[cfg(feature = "event-sinks")]
+                let registry = registry
+                    .with_sinks(config.event_sinks(), &*sink_store_factory, &replay_source)
+                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+                let mut dispatcher = registry.into_dispatcher();
+                let resume_point = dispatcher
+                    .resume_point(None)
+                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+                let evt_processor =
+                    EventProcessor::start(sawtooth_connection, resume_point.as_deref(), dispatcher)
+                        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
 
                 (
                     StoreState::with_pg_pool(connection_pool.pool),
@@ -90,15 +140,33 @@ pub fn run_sawtooth(config: GridConfig) -> Result<(), DaemonError> {
                 let connection_pool: ConnectionPool<diesel::sqlite::SqliteConnection> =
                     ConnectionPool::new(config.database_url())
                         .map_err(|err| DaemonError::from_source(Box::new(err)))?;
-                let store_factory = create_store_factory(&connection_uri)
-                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
                 let event_handler = DatabaseEventHandler::new(store_factory);
-                let evt_processor = EventProcessor::start(
-                    sawtooth_connection,
-                    current_commit.as_deref(),
-                    event_handlers![event_handler],
-                )
-                .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+                #[cfg(feature = "track-and-trace")]
+                let event_handler = event_handler.with_alert_rules(alert_rules);
+                #[cfg(feature = "event-stream")]
+                let event_handler = event_handler.with_event_broadcaster(event_broadcaster.clone());
+                // The commits are indexed as they are received
+                #[cfg(feature = "health")]
+                let event_handler = EventHealthHandler::received(
+                    event_health.clone(),
+                    Box::new(EventHealthHandler::indexed(
+                        event_health.clone(),
+                        Box::new(event_handler),
+                    )),
+                );
+                let registry = EventHandlerRegistry::new(config.event_failure_policy())
+                    .with_handler(Box::new(event_handler));
+                #[cfg(feature = "event-sinks")]
+                let registry = registry
+                    .with_sinks(config.event_sinks(), &*sink_store_factory, &replay_source)
+                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+                let mut dispatcher = registry.into_dispatcher();
+                let resume_point = dispatcher
+                    .resume_point(None)
+                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+                let evt_processor =
+                    EventProcessor::start(sawtooth_connection, resume_point.as_deref(), dispatcher)
+                        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
 
                 (
                     StoreState::with_sqlite_pool(connection_pool.pool),
@@ -108,6 +176,11 @@ pub fn run_sawtooth(config: GridConfig) -> Result<(), DaemonError> {
         }
     };
 
+    #[cfg(feature = "health")]
+    let subscription_source = sawtooth_endpoint.url();
+    #[cfg(feature = "health")]
+    event_health.subscribed(&subscription_source);
+
     #[cfg(feature = "integration")]
     let key_state = KeyState::new(config.key_file_name());
 
@@ -118,12 +191,24 @@ pub fn run_sawtooth(config: GridConfig) -> Result<(), DaemonError> {
         backend_state,
         #[cfg(feature = "integration")]
         key_state,
+        #[cfg(feature = "event-stream")]
+        event_broadcaster,
+        #[cfg(feature = "contract-upgrade")]
+        None,
+        #[cfg(feature = "multi-source")]
+        None,
+        #[cfg(feature = "health")]
+        health_state,
         sawtooth_endpoint,
     )
     .map_err(|err| DaemonError::from_source(Box::new(err)))?;
 
     let (event_processor_shutdown_handle, event_processor_join_handle) =
         evt_processor.take_shutdown_controls();
+    #[cfg(feature = "health")]
+    let event_processor_join_handle = event_health
+        .watch_processor(&subscription_source, event_processor_join_handle)
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
 
     let ctrlc_triggered = AtomicBool::new(false);
     ctrlc::set_handler(move || {
@@ -0,0 +1,84 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+use reqwest::StatusCode;
+use splinter::admin::messages::CircuitStatus;
+
+use super::{error::AppAuthHandlerError, SPLINTER_PROTOCOL_VERSION};
+
+#[derive(Deserialize)]
+struct CircuitResponse {
+    circuit_status: CircuitStatus,
+    #[serde(default)]
+    roster: Vec<ServiceResponse>,
+}
+
+#[derive(Deserialize)]
+struct ServiceResponse {
+    service_id: String,
+    node_id: String,
+}
+
+/// The status of a circuit and the nodes its services run on
+pub struct Circuit {
+    pub status: CircuitStatus,
+    /// The ID of each service on the circuit, along with the ID of the node it runs on
+    pub services: Vec<(String, String)>,
+}
+
+impl Circuit {
+    /// Returns whether the given service of the circuit runs on the given node
+    pub fn runs_on(&self, service_id: &str, node_id: &str) -> bool {
+        self.services
+            .iter()
+            .any(|(service, node)| service == service_id && node == node_id)
+    }
+}
+
+/// Fetches a circuit from splinterd, or `None` if the node has no such circuit
+pub fn get_circuit(
+    splinterd_url: &str,
+    circuit_id: &str,
+    authorization: &str,
+) -> Result<Option<Circuit>, AppAuthHandlerError> {
+    let uri = format!("{}/admin/circuits/{}", splinterd_url, circuit_id);
+
+    let res = reqwest::blocking::Client::new()
+        .get(&uri)
+        .header("Authorization", authorization)
+        .header("SplinterProtocolVersion", SPLINTER_PROTOCOL_VERSION)
+        .send()
+        .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
+
+    if res.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    let circuit = res
+        .error_for_status()
+        .and_then(|res| res.json::<CircuitResponse>())
+        .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
+
+    Ok(Some(Circuit {
+        status: circuit.circuit_status,
+        services: circuit
+            .roster
+            .into_iter()
+            .map(|service| (service.service_id, service.node_id))
+            .collect(),
+    }))
+}
@@ -15,24 +15,30 @@
  * -----------------------------------------------------------------------------
  */
 
+mod circuit;
 pub mod error;
 mod node;
-mod sabre;
+pub mod sabre;
 
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
+use grid_sdk::commits::store::ServiceStatus;
 use splinter::{
-    admin::messages::AdminServiceEvent,
+    admin::messages::{AdminServiceEvent, CircuitProposal, SplinterService},
     events::{Igniter, ParseBytes, ParseError, WebSocketClient, WebSocketError, WsResponse},
 };
 
 use crate::event::EventHandler;
 use crate::splinter::{
-    app_auth_handler::{error::AppAuthHandlerError, node::get_node_id, sabre::setup_grid},
+    app_auth_handler::{error::AppAuthHandlerError, sabre::setup_grid},
     event::processors::EventProcessors,
+    lifecycle::ServiceLifecycles,
 };
 
+pub use circuit::get_circuit;
+pub use node::get_node_id;
+
 /// default value if the client should attempt to reconnect if ws connection is lost
 const RECONNECT: bool = true;
 
@@ -63,6 +69,7 @@ pub fn run(
     splinterd_url: String,
     event_processors: EventProcessors,
     handler: Box<dyn EventHandler>,
+    service_lifecycles: ServiceLifecycles,
     igniter: Igniter,
     scabbard_admin_key: String,
     authorization: String,
@@ -87,6 +94,7 @@ pub fn run(
             event,
             event_processors.clone(),
             handler,
+            &service_lifecycles,
             &node_id,
             &scabbard_admin_key,
             &splinterd_url,
@@ -131,6 +139,7 @@ fn process_admin_event(
     event: AdminEvent,
     event_processors: EventProcessors,
     handler: Box<dyn EventHandler>,
+    service_lifecycles: &ServiceLifecycles,
     node_id: &str,
     scabbard_admin_key: &str,
     splinterd_url: &str,
@@ -139,12 +148,7 @@ fn process_admin_event(
     debug!("Received the event at {}", event.timestamp);
     match event.admin_event {
         AdminServiceEvent::CircuitReady(msg_proposal) => {
-            let service = match msg_proposal
-                .circuit
-                .roster
-                .iter()
-                .find(|service| service.allowed_nodes.contains(&node_id.to_string()))
-            {
+            let service = match find_node_service(&msg_proposal, node_id) {
                 Some(service) => service,
                 None => {
                     debug!(
@@ -175,6 +179,13 @@ fn process_admin_event(
                 )
                 .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
 
+            service_lifecycles
+                .activate(&format!(
+                    "{}::{}",
+                    msg_proposal.circuit_id, service.service_id
+                ))
+                .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
+
             setup_grid(
                 scabbard_admin_key,
                 &proposed_admin_pubkey,
@@ -185,6 +196,45 @@ fn process_admin_event(
             )?;
             Ok(())
         }
+        AdminServiceEvent::CircuitDisbanded(msg_proposal) => {
+            let service = match find_node_service(&msg_proposal, node_id) {
+                Some(service) => service,
+                None => {
+                    debug!(
+                        "Disbanded circuit does not have any services for this node: {}",
+                        node_id
+                    );
+                    return Ok(());
+                }
+            };
+
+            event_processors
+                .remove(&msg_proposal.circuit_id, &service.service_id)
+                .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
+
+            let service_id = format!("{}::{}", msg_proposal.circuit_id, service.service_id);
+            let data_status = service_lifecycles
+                .retire(&service_id, ServiceStatus::Disbanded)
+                .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
+
+            info!(
+                "Circuit {} was disbanded; the data of service {} is {}",
+                msg_proposal.circuit_id, service_id, data_status
+            );
+            Ok(())
+        }
         _ => Ok(()),
     }
 }
+
+/// Finds the service on the proposed circuit that runs on this node
+fn find_node_service<'a>(
+    proposal: &'a CircuitProposal,
+    node_id: &str,
+) -> Option<&'a SplinterService> {
+    proposal
+        .circuit
+        .roster
+        .iter()
+        .find(|service| service.allowed_nodes.contains(&node_id.to_string()))
+}
@@ -30,40 +30,203 @@ use grid_sdk::schema::addressing::GRID_SCHEMA_NAMESPACE;
 
 use cylinder::{secp256k1::Secp256k1Context, Context, PrivateKey, Signer};
 
-#[cfg(any(
-    feature = "location",
-    feature = "pike",
-    feature = "product",
-    feature = "purchase-order",
-    feature = "schema"
-))]
+#[cfg(feature = "pike")]
 use sabre_sdk::protocol::payload::{
     CreateContractActionBuilder, CreateContractRegistryActionBuilder,
     CreateNamespaceRegistryActionBuilder, CreateNamespaceRegistryPermissionActionBuilder,
 };
 use scabbard::client::{ReqwestScabbardClientBuilder, ScabbardClient, ServiceId};
-#[cfg(any(
-    feature = "location",
-    feature = "pike",
-    feature = "product",
-    feature = "purchase-order",
-    feature = "schema"
-))]
+#[cfg(feature = "pike")]
+use semver::Version;
+#[cfg(feature = "pike")]
 use transact::contract::archive::default_scar_path;
-use transact::protocol::batch::BatchBuilder;
-#[cfg(any(
-    feature = "location",
-    feature = "pike",
-    feature = "product",
-    feature = "purchase-order",
-    feature = "schema"
-))]
+use transact::protocol::batch::{Batch, BatchBuilder};
+#[cfg(feature = "pike")]
 use transact::{contract::archive::SmartContractArchive, protocol::transaction::Transaction};
 
 use crate::splinter::app_auth_handler::error::AppAuthHandlerError;
 
 const SCABBARD_SUBMISSION_WAIT_SECS: u64 = 10;
 
+/// A Grid smart contract, along with the namespace it owns and the namespaces it is permitted to
+/// read and write
+#[cfg(feature = "pike")]
+pub struct GridContract {
+    pub name: &'static str,
+    pub namespace: &'static str,
+    pub permitted_namespaces: &'static [&'static str],
+}
+
+/// Returns the Grid smart contracts enabled for this binary, in the order they are deployed
+#[cfg(feature = "pike")]
+pub fn grid_contracts() -> Vec<GridContract> {
+    let mut contracts = Vec::new();
+
+    contracts.push(GridContract {
+        name: "grid-pike",
+        namespace: GRID_PIKE_NAMESPACE,
+        permitted_namespaces: &[GRID_PIKE_NAMESPACE],
+    });
+
+    #[cfg(feature = "schema")]
+    contracts.push(GridContract {
+        name: "grid-schema",
+        namespace: GRID_SCHEMA_NAMESPACE,
+        permitted_namespaces: &[GRID_SCHEMA_NAMESPACE, GRID_PIKE_NAMESPACE],
+    });
+
+    #[cfg(feature = "product")]
+    contracts.push(GridContract {
+        name: "grid-product",
+        namespace: GRID_PRODUCT_NAMESPACE,
+        permitted_namespaces: &[
+            GRID_PRODUCT_NAMESPACE,
+            GRID_PIKE_NAMESPACE,
+            GRID_SCHEMA_NAMESPACE,
+        ],
+    });
+
+    #[cfg(feature = "location")]
+    contracts.push(GridContract {
+        name: "grid-location",
+        namespace: GRID_LOCATION_NAMESPACE,
+        permitted_namespaces: &[
+            GRID_LOCATION_NAMESPACE,
+            GRID_PIKE_NAMESPACE,
+            GRID_SCHEMA_NAMESPACE,
+        ],
+    });
+
+    #[cfg(feature = "purchase-order")]
+    contracts.push(GridContract {
+        name: "grid-purchase-order",
+        namespace: GRID_PURCHASE_ORDER_NAMESPACE,
+        permitted_namespaces: &[GRID_PURCHASE_ORDER_NAMESPACE, GRID_PIKE_NAMESPACE],
+    });
+
+    contracts
+}
+
+/// The Sabre state of a contract that is already deployed
+#[cfg(feature = "pike")]
+#[derive(Default)]
+pub struct DeployedRegistries {
+    /// Whether the contract's registry exists
+    pub contract_registry: bool,
+    /// The versions of the contract that have been uploaded
+    pub versions: Vec<String>,
+    /// Whether the registry of the contract's namespace exists
+    pub namespace_registry: bool,
+    /// The namespaces the contract is already permitted to read and write
+    pub permitted_namespaces: Vec<String>,
+}
+
+/// A change to the Sabre state of a contract
+#[cfg(feature = "pike")]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ContractAction {
+    CreateContractRegistry,
+    UploadContract { version: String },
+    CreateNamespaceRegistry { namespace: String },
+    GrantPermission { namespace: String },
+}
+
+/// Returns the changes needed to bring the deployed state of a contract up to the version in the
+/// given archive. The archive's version is only uploaded if it is newer than every deployed
+/// version, so that an older archive never replaces the version a circuit already runs.
+#[cfg(feature = "pike")]
+pub fn plan_contract_actions(
+    contract: &GridContract,
+    archive: &SmartContractArchive,
+    deployed: &DeployedRegistries,
+) -> Vec<ContractAction> {
+    let mut actions = Vec::new();
+
+    if !deployed.contract_registry {
+        actions.push(ContractAction::CreateContractRegistry);
+    }
+
+    if is_upgrade(archive, deployed) {
+        actions.push(ContractAction::UploadContract {
+            version: archive.metadata.version.clone(),
+        });
+    }
+
+    if !deployed.namespace_registry {
+        actions.push(ContractAction::CreateNamespaceRegistry {
+            namespace: contract.namespace.to_string(),
+        });
+    }
+
+    actions.extend(
+        contract
+            .permitted_namespaces
+            .iter()
+            .filter(|namespace| {
+                !deployed
+                    .permitted_namespaces
+                    .iter()
+                    .any(|permitted| permitted == *namespace)
+            })
+            .map(|namespace| ContractAction::GrantPermission {
+                namespace: namespace.to_string(),
+            }),
+    );
+
+    actions
+}
+
+/// Returns the deployed versions of a contract that are newer than the version in the given
+/// archive
+#[cfg(feature = "pike")]
+pub fn newer_deployed_versions(
+    archive: &SmartContractArchive,
+    deployed: &DeployedRegistries,
+) -> Vec<String> {
+    let available = match parse_version(&archive.metadata.version) {
+        Some(available) => available,
+        None => return Vec::new(),
+    };
+
+    deployed
+        .versions
+        .iter()
+        .filter(|version| matches!(parse_version(version), Some(version) if version > available))
+        .cloned()
+        .collect()
+}
+
+/// Returns whether the version in the archive is newer than every deployed version of the
+/// contract. Deployed versions that are not semantic versions are not compared.
+#[cfg(feature = "pike")]
+fn is_upgrade(archive: &SmartContractArchive, deployed: &DeployedRegistries) -> bool {
+    let available = match parse_version(&archive.metadata.version) {
+        Some(available) => available,
+        None => return false,
+    };
+
+    deployed
+        .versions
+        .iter()
+        .filter_map(|version| parse_version(version))
+        .all(|version| version < available)
+}
+
+#[cfg(feature = "pike")]
+fn parse_version(version: &str) -> Option<Version> {
+    match Version::parse(version) {
+        Ok(version) => Some(version),
+        Err(err) => {
+            warn!(
+                "Contract version {} is not a semantic version: {}",
+                version, err
+            );
+            None
+        }
+    }
+}
+
 pub fn setup_grid(
     scabbard_admin_key: &str,
     proposed_admin_pubkey: &str,
@@ -72,13 +235,7 @@ pub fn setup_grid(
     circuit_id: &str,
     authorization: &str,
 ) -> Result<(), AppAuthHandlerError> {
-    #[cfg(any(
-        feature = "location",
-        feature = "pike",
-        feature = "product",
-        feature = "purchase-order",
-        feature = "schema"
-    ))]
+    #[cfg(feature = "pike")]
     let version = env!("CARGO_PKG_VERSION");
 
     let signer = new_signer(scabbard_admin_key)?;
@@ -94,30 +251,31 @@ pub fn setup_grid(
     #[allow(unused_mut)]
     let mut txns = Vec::new();
 
-    // Make Pike transactions
     #[cfg(feature = "pike")]
-    make_pike_txns(&mut txns, version, &*signer)?;
-
-    // Make schema transactions
-    #[cfg(feature = "schema")]
-    make_schema_txns(&mut txns, version, &*signer)?;
-
-    // Make Product transactions
-    #[cfg(feature = "product")]
-    make_product_txns(&mut txns, version, &*signer)?;
-
-    // Make Location transactions
-    #[cfg(feature = "location")]
-    make_location_txns(&mut txns, version, &*signer)?;
-
-    // Make Purchase Order transactions
-    #[cfg(feature = "purchase-order")]
-    make_purchase_order_txns(&mut txns, version, &*signer)?;
+    for contract in grid_contracts() {
+        let archive =
+            SmartContractArchive::from_scar_file(contract.name, version, &default_scar_path())?;
+        let actions = plan_contract_actions(&contract, &archive, &DeployedRegistries::default());
+        txns.append(&mut make_contract_txns(
+            &contract, &archive, &actions, &*signer,
+        )?);
+    }
 
     let batch = BatchBuilder::new()
         .with_transactions(txns)
         .build(&*signer)?;
 
+    submit_batch(splinterd_url, authorization, circuit_id, service_id, batch)
+}
+
+/// Submits a batch to a Scabbard service, waiting for it to be committed
+pub fn submit_batch(
+    splinterd_url: &str,
+    authorization: &str,
+    circuit_id: &str,
+    service_id: &str,
+    batch: Batch,
+) -> Result<(), AppAuthHandlerError> {
     let client_builder = ReqwestScabbardClientBuilder::new()
         .with_url(splinterd_url)
         .with_auth(authorization);
@@ -137,276 +295,53 @@ pub fn setup_grid(
     Ok(())
 }
 
+/// Makes the transactions that apply the given changes to the Sabre state of a contract
 #[cfg(feature = "pike")]
-fn make_pike_txns(
-    txns: &mut Vec<Transaction>,
-    version: &str,
+pub fn make_contract_txns(
+    contract: &GridContract,
+    archive: &SmartContractArchive,
+    actions: &[ContractAction],
     signer: &dyn Signer,
-) -> Result<(), AppAuthHandlerError> {
-    let pike_contract =
-        SmartContractArchive::from_scar_file("grid-pike", version, &default_scar_path())?;
-    let pike_contract_registry_txn = CreateContractRegistryActionBuilder::new()
-        .with_name(String::from(&pike_contract.metadata.name))
-        .with_owners(vec![bytes_to_hex_str(signer.public_key()?.as_slice())])
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let pike_contract_txn = make_upload_contract_txn(signer, &pike_contract, GRID_PIKE_NAMESPACE)?;
-    let pike_namespace_registry_txn = CreateNamespaceRegistryActionBuilder::new()
-        .with_namespace(GRID_PIKE_NAMESPACE.into())
-        .with_owners(vec![bytes_to_hex_str(signer.public_key()?.as_slice())])
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-
-    let pike_namespace_permissions_txn = CreateNamespaceRegistryPermissionActionBuilder::new()
-        .with_namespace(GRID_PIKE_NAMESPACE.into())
-        .with_contract_name(pike_contract.metadata.name)
-        .with_read(true)
-        .with_write(true)
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-
-    txns.append(&mut vec![
-        pike_contract_registry_txn,
-        pike_contract_txn,
-        pike_namespace_registry_txn,
-        pike_namespace_permissions_txn,
-    ]);
-
-    Ok(())
-}
-
-#[cfg(feature = "product")]
-fn make_product_txns(
-    txns: &mut Vec<Transaction>,
-    version: &str,
-    signer: &dyn Signer,
-) -> Result<(), AppAuthHandlerError> {
-    let product_contract =
-        SmartContractArchive::from_scar_file("grid-product", version, &default_scar_path())?;
-    let product_contract_registry_txn = CreateContractRegistryActionBuilder::new()
-        .with_name(String::from(&product_contract.metadata.name))
-        .with_owners(vec![bytes_to_hex_str(signer.public_key()?.as_slice())])
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let product_contract_txn =
-        make_upload_contract_txn(signer, &product_contract, GRID_PRODUCT_NAMESPACE)?;
-    let product_namespace_registry_txn = CreateNamespaceRegistryActionBuilder::new()
-        .with_namespace(GRID_PRODUCT_NAMESPACE.into())
-        .with_owners(vec![bytes_to_hex_str(signer.public_key()?.as_slice())])
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let product_namespace_permissions_txn = CreateNamespaceRegistryPermissionActionBuilder::new()
-        .with_namespace(GRID_PRODUCT_NAMESPACE.into())
-        .with_contract_name(product_contract.metadata.name.clone())
-        .with_read(true)
-        .with_write(true)
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let product_pike_namespace_permissions_txn =
-        CreateNamespaceRegistryPermissionActionBuilder::new()
-            .with_namespace(GRID_PIKE_NAMESPACE.into())
-            .with_contract_name(product_contract.metadata.name.clone())
-            .with_read(true)
-            .with_write(true)
-            .into_payload_builder()?
-            .into_transaction_builder()?
-            .build(signer)?;
-    let product_schema_namespace_permissions_txn =
-        CreateNamespaceRegistryPermissionActionBuilder::new()
-            .with_namespace(GRID_SCHEMA_NAMESPACE.into())
-            .with_contract_name(product_contract.metadata.name)
-            .with_read(true)
-            .with_write(true)
-            .into_payload_builder()?
-            .into_transaction_builder()?
-            .build(signer)?;
-
-    txns.append(&mut vec![
-        product_contract_registry_txn,
-        product_contract_txn,
-        product_namespace_registry_txn,
-        product_namespace_permissions_txn,
-        product_pike_namespace_permissions_txn,
-        product_schema_namespace_permissions_txn,
-    ]);
-
-    Ok(())
-}
-
-#[cfg(feature = "location")]
-fn make_location_txns(
-    txns: &mut Vec<Transaction>,
-    version: &str,
-    signer: &dyn Signer,
-) -> Result<(), AppAuthHandlerError> {
-    let location_contract =
-        SmartContractArchive::from_scar_file("grid-location", version, &default_scar_path())?;
-    let location_contract_registry_txn = CreateContractRegistryActionBuilder::new()
-        .with_name(String::from(&location_contract.metadata.name))
-        .with_owners(vec![bytes_to_hex_str(signer.public_key()?.as_slice())])
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let location_contract_txn =
-        make_upload_contract_txn(signer, &location_contract, GRID_LOCATION_NAMESPACE)?;
-    let location_namespace_registry_txn = CreateNamespaceRegistryActionBuilder::new()
-        .with_namespace(GRID_LOCATION_NAMESPACE.into())
-        .with_owners(vec![bytes_to_hex_str(signer.public_key()?.as_slice())])
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let location_namespace_permissions_txn = CreateNamespaceRegistryPermissionActionBuilder::new()
-        .with_namespace(GRID_LOCATION_NAMESPACE.into())
-        .with_contract_name(location_contract.metadata.name.clone())
-        .with_read(true)
-        .with_write(true)
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let location_pike_namespace_permissions_txn =
-        CreateNamespaceRegistryPermissionActionBuilder::new()
-            .with_namespace(GRID_PIKE_NAMESPACE.into())
-            .with_contract_name(location_contract.metadata.name.clone())
-            .with_read(true)
-            .with_write(true)
-            .into_payload_builder()?
-            .into_transaction_builder()?
-            .build(signer)?;
-    let location_schema_namespace_permissions_txn =
-        CreateNamespaceRegistryPermissionActionBuilder::new()
-            .with_namespace(GRID_SCHEMA_NAMESPACE.into())
-            .with_contract_name(location_contract.metadata.name)
-            .with_read(true)
-            .with_write(true)
-            .into_payload_builder()?
-            .into_transaction_builder()?
-            .build(signer)?;
-
-    txns.append(&mut vec![
-        location_contract_registry_txn,
-        location_contract_txn,
-        location_namespace_registry_txn,
-        location_namespace_permissions_txn,
-        location_pike_namespace_permissions_txn,
-        location_schema_namespace_permissions_txn,
-    ]);
-
-    Ok(())
-}
-
-#[cfg(feature = "schema")]
-fn make_schema_txns(
-    txns: &mut Vec<Transaction>,
-    version: &str,
-    signer: &dyn Signer,
-) -> Result<(), AppAuthHandlerError> {
-    let schema_contract =
-        SmartContractArchive::from_scar_file("grid-schema", version, &default_scar_path())?;
-    let schema_contract_registry_txn = CreateContractRegistryActionBuilder::new()
-        .with_name(String::from(&schema_contract.metadata.name))
-        .with_owners(vec![bytes_to_hex_str(signer.public_key()?.as_slice())])
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let schema_contract_txn =
-        make_upload_contract_txn(signer, &schema_contract, GRID_SCHEMA_NAMESPACE)?;
-    let schema_namespace_registry_txn = CreateNamespaceRegistryActionBuilder::new()
-        .with_namespace(GRID_SCHEMA_NAMESPACE.into())
-        .with_owners(vec![bytes_to_hex_str(signer.public_key()?.as_slice())])
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let schema_namespace_permissions_txn = CreateNamespaceRegistryPermissionActionBuilder::new()
-        .with_namespace(GRID_SCHEMA_NAMESPACE.into())
-        .with_contract_name(schema_contract.metadata.name.clone())
-        .with_read(true)
-        .with_write(true)
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let schema_pike_namespace_permissions_txn =
-        CreateNamespaceRegistryPermissionActionBuilder::new()
-            .with_namespace(GRID_PIKE_NAMESPACE.into())
-            .with_contract_name(schema_contract.metadata.name)
-            .with_read(true)
-            .with_write(true)
-            .into_payload_builder()?
-            .into_transaction_builder()?
-            .build(signer)?;
-
-    txns.append(&mut vec![
-        schema_contract_registry_txn,
-        schema_contract_txn,
-        schema_namespace_registry_txn,
-        schema_namespace_permissions_txn,
-        schema_pike_namespace_permissions_txn,
-    ]);
-
-    Ok(())
+) -> Result<Vec<Transaction>, AppAuthHandlerError> {
+    let owner = bytes_to_hex_str(signer.public_key()?.as_slice());
+
+    actions
+        .iter()
+        .map(|action| match action {
+            ContractAction::CreateContractRegistry => {
+                Ok(CreateContractRegistryActionBuilder::new()
+                    .with_name(archive.metadata.name.clone())
+                    .with_owners(vec![owner.clone()])
+                    .into_payload_builder()?
+                    .into_transaction_builder()?
+                    .build(signer)?)
+            }
+            ContractAction::UploadContract { .. } => {
+                make_upload_contract_txn(signer, archive, contract.namespace)
+            }
+            ContractAction::CreateNamespaceRegistry { namespace } => {
+                Ok(CreateNamespaceRegistryActionBuilder::new()
+                    .with_namespace(namespace.clone())
+                    .with_owners(vec![owner.clone()])
+                    .into_payload_builder()?
+                    .into_transaction_builder()?
+                    .build(signer)?)
+            }
+            ContractAction::GrantPermission { namespace } => {
+                Ok(CreateNamespaceRegistryPermissionActionBuilder::new()
+                    .with_namespace(namespace.clone())
+                    .with_contract_name(archive.metadata.name.clone())
+                    .with_read(true)
+                    .with_write(true)
+                    .into_payload_builder()?
+                    .into_transaction_builder()?
+                    .build(signer)?)
+            }
+        })
+        .collect()
 }
 
-#[cfg(feature = "purchase-order")]
-fn make_purchase_order_txns(
-    txns: &mut Vec<Transaction>,
-    version: &str,
-    signer: &dyn Signer,
-) -> Result<(), AppAuthHandlerError> {
-    let purchase_order_contract =
-        SmartContractArchive::from_scar_file("grid-purchase-order", version, &default_scar_path())?;
-    let purchase_order_contract_registry_txn = CreateContractRegistryActionBuilder::new()
-        .with_name(String::from(&purchase_order_contract.metadata.name))
-        .with_owners(vec![bytes_to_hex_str(signer.public_key()?.as_slice())])
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let purchase_order_contract_txn = make_upload_contract_txn(
-        signer,
-        &purchase_order_contract,
-        GRID_PURCHASE_ORDER_NAMESPACE,
-    )?;
-    let purchase_order_namespace_registry_txn = CreateNamespaceRegistryActionBuilder::new()
-        .with_namespace(GRID_PURCHASE_ORDER_NAMESPACE.into())
-        .with_owners(vec![bytes_to_hex_str(signer.public_key()?.as_slice())])
-        .into_payload_builder()?
-        .into_transaction_builder()?
-        .build(signer)?;
-    let purchase_order_namespace_permissions_txn =
-        CreateNamespaceRegistryPermissionActionBuilder::new()
-            .with_namespace(GRID_PURCHASE_ORDER_NAMESPACE.into())
-            .with_contract_name(purchase_order_contract.metadata.name.clone())
-            .with_read(true)
-            .with_write(true)
-            .into_payload_builder()?
-            .into_transaction_builder()?
-            .build(signer)?;
-    let purchase_order_pike_namespace_permissions_txn =
-        CreateNamespaceRegistryPermissionActionBuilder::new()
-            .with_namespace(GRID_PIKE_NAMESPACE.into())
-            .with_contract_name(purchase_order_contract.metadata.name)
-            .with_read(true)
-            .with_write(true)
-            .into_payload_builder()?
-            .into_transaction_builder()?
-            .build(signer)?;
-
-    txns.append(&mut vec![
-        purchase_order_contract_registry_txn,
-        purchase_order_contract_txn,
-        purchase_order_namespace_registry_txn,
-        purchase_order_namespace_permissions_txn,
-        purchase_order_pike_namespace_permissions_txn,
-    ]);
-
-    Ok(())
-}
-
-fn new_signer(private_key: &str) -> Result<Box<dyn Signer>, AppAuthHandlerError> {
+pub fn new_signer(private_key: &str) -> Result<Box<dyn Signer>, AppAuthHandlerError> {
     let context = Secp256k1Context::new();
     let private_key = PrivateKey::new_from_hex(private_key)?;
     Ok(context.new_signer(private_key))
@@ -435,9 +370,120 @@ fn make_upload_contract_txn(
 /// # Arguments
 ///
 /// * `b` - input bytes
-fn bytes_to_hex_str(b: &[u8]) -> String {
+#[cfg(feature = "pike")]
+pub fn bytes_to_hex_str(b: &[u8]) -> String {
     b.iter()
         .map(|b| format!("{:02x}", b))
         .collect::<Vec<_>>()
         .join("")
 }
+
+#[cfg(all(test, feature = "pike"))]
+mod tests {
+    use super::*;
+
+    use transact::contract::archive::SmartContractMetadata;
+
+    const CONTRACT: GridContract = GridContract {
+        name: "grid-test",
+        namespace: "aaaaaa",
+        permitted_namespaces: &["aaaaaa", "bbbbbb"],
+    };
+
+    fn archive(version: &str) -> SmartContractArchive {
+        SmartContractArchive {
+            contract: vec![],
+            metadata: SmartContractMetadata {
+                name: CONTRACT.name.to_string(),
+                version: version.to_string(),
+                inputs: vec![],
+                outputs: vec![],
+            },
+        }
+    }
+
+    /// Verify that a contract that has not been deployed is given its registries, its version
+    /// and each of its permissions
+    #[test]
+    fn plan_new_contract() {
+        assert_eq!(
+            plan_contract_actions(&CONTRACT, &archive("1.0.0"), &DeployedRegistries::default()),
+            vec![
+                ContractAction::CreateContractRegistry,
+                ContractAction::UploadContract {
+                    version: "1.0.0".to_string()
+                },
+                ContractAction::CreateNamespaceRegistry {
+                    namespace: "aaaaaa".to_string()
+                },
+                ContractAction::GrantPermission {
+                    namespace: "aaaaaa".to_string()
+                },
+                ContractAction::GrantPermission {
+                    namespace: "bbbbbb".to_string()
+                },
+            ]
+        );
+    }
+
+    /// Verify that only the missing version and permissions of a deployed contract are planned,
+    /// and that nothing is planned for a contract that is up to date
+    #[test]
+    fn plan_deployed_contract() {
+        let deployed = DeployedRegistries {
+            contract_registry: true,
+            versions: vec!["1.0.0".to_string()],
+            namespace_registry: true,
+            permitted_namespaces: vec!["aaaaaa".to_string()],
+        };
+
+        assert_eq!(
+            plan_contract_actions(&CONTRACT, &archive("2.0.0"), &deployed),
+            vec![
+                ContractAction::UploadContract {
+                    version: "2.0.0".to_string()
+                },
+                ContractAction::GrantPermission {
+                    namespace: "bbbbbb".to_string()
+                },
+            ]
+        );
+
+        let deployed = DeployedRegistries {
+            permitted_namespaces: vec!["aaaaaa".to_string(), "bbbbbb".to_string()],
+            ..deployed
+        };
+
+        assert!(plan_contract_actions(&CONTRACT, &archive("1.0.0"), &deployed).is_empty());
+    }
+
+    /// Verify that an archive older than a deployed version is not uploaded, and that the newer
+    /// deployed versions are reported
+    #[test]
+    fn plan_older_contract() {
+        let deployed = DeployedRegistries {
+            contract_registry: true,
+            versions: vec![
+                "0.9.0".to_string(),
+                "1.10.0".to_string(),
+                "1.2.0".to_string(),
+            ],
+            namespace_registry: true,
+            permitted_namespaces: vec!["aaaaaa".to_string(), "bbbbbb".to_string()],
+        };
+
+        assert!(plan_contract_actions(&CONTRACT, &archive("1.9.0"), &deployed).is_empty());
+        assert_eq!(
+            newer_deployed_versions(&archive("1.9.0"), &deployed),
+            vec!["1.10.0"]
+        );
+
+        assert_eq!(
+            plan_contract_actions(&CONTRACT, &archive("1.11.0"), &deployed),
+            vec![ContractAction::UploadContract {
+                version: "1.11.0".to_string()
+            }]
+        );
+        assert!(newer_deployed_versions(&archive("1.11.0"), &deployed).is_empty());
+    }
+}
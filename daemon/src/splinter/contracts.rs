@@ -0,0 +1,463 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+//! Upgrades the Grid smart contracts deployed on existing circuits to the versions in the
+//! available `.scar` files
+
+use std::collections::HashMap;
+
+use sabre_sdk::protocol::{
+    compute_contract_registry_address, compute_namespace_registry_address,
+    state::{ContractRegistry, ContractRegistryList, NamespaceRegistry, NamespaceRegistryList},
+};
+use sabre_sdk::protos::FromBytes;
+use scabbard::client::{ReqwestScabbardClientBuilder, ScabbardClient, ServiceId};
+use transact::contract::archive::{default_scar_path, SmartContractArchive};
+use transact::protocol::batch::BatchBuilder;
+
+use super::app_auth_handler::{
+    error::AppAuthHandlerError,
+    sabre::{
+        bytes_to_hex_str, grid_contracts, make_contract_txns, new_signer, newer_deployed_versions,
+        plan_contract_actions, submit_batch, ContractAction, DeployedRegistries, GridContract,
+    },
+};
+use super::lifecycle::ServiceLifecycles;
+
+/// The state of a contract on a circuit, compared with the available `.scar` file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractStatus {
+    /// The available version is deployed along with its registries and permissions
+    UpToDate,
+    /// The contract has not been deployed
+    Deploy,
+    /// The available version has not been uploaded
+    Upgrade,
+    /// The available version is deployed, but a registry or permission is missing
+    Update,
+    /// The contract needs changes to registries that the gridd key does not own
+    NotOwner,
+    /// A version newer than the available one is deployed along with its registries and
+    /// permissions, and is left in place
+    NewerDeployed,
+}
+
+/// The comparison of a contract deployed on a circuit with the available `.scar` file
+#[derive(Debug, Serialize)]
+pub struct ContractReport {
+    pub name: String,
+    pub deployed_versions: Vec<String>,
+    pub available_version: String,
+    /// The deployed versions that are newer than the available version
+    pub newer_deployed_versions: Vec<String>,
+    pub status: ContractStatus,
+    pub actions: Vec<ContractAction>,
+}
+
+/// The contracts of one Scabbard service, and whether their upgrade batch was submitted
+#[derive(Debug, Serialize)]
+pub struct ServiceReport {
+    pub service_id: String,
+    pub contracts: Vec<ContractReport>,
+    pub submitted: bool,
+}
+
+/// The result of comparing, and unless it is a dry run upgrading, the contracts of each circuit
+#[derive(Debug, Serialize)]
+pub struct UpgradeReport {
+    pub dry_run: bool,
+    pub services: Vec<ServiceReport>,
+}
+
+/// Compares the Grid smart contracts deployed on each active circuit with the available `.scar`
+/// files, and submits the batches that upgrade them
+#[derive(Clone)]
+pub struct ContractUpgrader {
+    splinterd_url: String,
+    scabbard_admin_key: String,
+    authorization: String,
+    service_lifecycles: ServiceLifecycles,
+}
+
+impl ContractUpgrader {
+    pub fn new(
+        splinterd_url: String,
+        scabbard_admin_key: String,
+        authorization: String,
+        service_lifecycles: ServiceLifecycles,
+    ) -> Self {
+        Self {
+            splinterd_url,
+            scabbard_admin_key,
+            authorization,
+            service_lifecycles,
+        }
+    }
+
+    /// Returns whether the given public key is that of the gridd key, which owns the registries
+    /// of the contracts it deployed and signs their upgrades
+    pub fn is_gridd_key(&self, public_key: &str) -> Result<bool, AppAuthHandlerError> {
+        Ok(new_signer(&self.scabbard_admin_key)?.public_key()?.as_hex() == public_key)
+    }
+
+    /// Compares the contracts of the active services, or of the services of the given circuit,
+    /// with the latest available `.scar` files. Unless `dry_run` is set, a batch that uploads the
+    /// new versions and creates the missing registries and permissions is submitted to each
+    /// service that needs one.
+    pub fn upgrade(
+        &self,
+        circuit_id: Option<&str>,
+        dry_run: bool,
+    ) -> Result<UpgradeReport, AppAuthHandlerError> {
+        let signer = new_signer(&self.scabbard_admin_key)?;
+        let public_key = signer.public_key()?.as_hex();
+
+        let client = ReqwestScabbardClientBuilder::new()
+            .with_url(&self.splinterd_url)
+            .with_auth(&self.authorization)
+            .build()
+            .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
+
+        let archives = grid_contracts()
+            .into_iter()
+            .map(|contract| {
+                let archive =
+                    SmartContractArchive::from_scar_file(contract.name, "*", &default_scar_path())?;
+                Ok((contract, archive))
+            })
+            .collect::<Result<Vec<_>, AppAuthHandlerError>>()?;
+
+        let mut service_ids = self
+            .service_lifecycles
+            .active()
+            .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?
+            .into_iter()
+            .map(|id| Ok((ServiceId::from_string(&id)?, id)))
+            .collect::<Result<Vec<_>, AppAuthHandlerError>>()?;
+        if let Some(circuit_id) = circuit_id {
+            service_ids.retain(|(service_id, _)| service_id.circuit() == circuit_id);
+        }
+
+        let mut services = Vec::with_capacity(service_ids.len());
+        for (service_id, id) in service_ids {
+            let state = DeployedState::read(&client, &service_id)?;
+
+            let mut txns = Vec::new();
+            let mut contracts = Vec::with_capacity(archives.len());
+            for (contract, archive) in &archives {
+                let deployed = state.deployed_registries(contract);
+                let actions = plan_contract_actions(contract, archive, &deployed);
+                let newer_deployed_versions = newer_deployed_versions(archive, &deployed);
+                if !newer_deployed_versions.is_empty() {
+                    warn!(
+                        "Service {} runs {} version(s) {}, newer than the available {}; the \
+                        available version is not uploaded",
+                        id,
+                        contract.name,
+                        newer_deployed_versions.join(", "),
+                        archive.metadata.version
+                    );
+                }
+
+                let status = if actions.is_empty() && newer_deployed_versions.is_empty() {
+                    ContractStatus::UpToDate
+                } else if actions.is_empty() {
+                    ContractStatus::NewerDeployed
+                } else if !state.is_owner(contract, &actions, &public_key) {
+                    ContractStatus::NotOwner
+                } else if !deployed.contract_registry {
+                    ContractStatus::Deploy
+                } else if actions
+                    .iter()
+                    .any(|action| matches!(action, ContractAction::UploadContract { .. }))
+                {
+                    ContractStatus::Upgrade
+                } else {
+                    ContractStatus::Update
+                };
+
+                if !actions.is_empty() && status != ContractStatus::NotOwner {
+                    txns.append(&mut make_contract_txns(
+                        contract, archive, &actions, &*signer,
+                    )?);
+                }
+
+                contracts.push(ContractReport {
+                    name: contract.name.to_string(),
+                    deployed_versions: deployed.versions,
+                    available_version: archive.metadata.version.clone(),
+                    newer_deployed_versions,
+                    status,
+                    actions,
+                });
+            }
+
+            let submitted = !dry_run && !txns.is_empty();
+            if submitted {
+                let batch = BatchBuilder::new()
+                    .with_transactions(txns)
+                    .build(&*signer)?;
+                submit_batch(
+                    &self.splinterd_url,
+                    &self.authorization,
+                    service_id.circuit(),
+                    service_id.service_id(),
+                    batch,
+                )?;
+                info!("Submitted contract upgrades to service {}", id);
+            }
+
+            services.push(ServiceReport {
+                service_id: id,
+                contracts,
+                submitted,
+            });
+        }
+
+        Ok(UpgradeReport { dry_run, services })
+    }
+}
+
+/// The Sabre registries of the Grid contracts and namespaces in a service's state
+struct DeployedState {
+    contract_registries: HashMap<String, ContractRegistry>,
+    namespace_registries: HashMap<String, NamespaceRegistry>,
+}
+
+impl DeployedState {
+    fn read(
+        client: &dyn ScabbardClient,
+        service_id: &ServiceId,
+    ) -> Result<Self, AppAuthHandlerError> {
+        let mut contract_registries = HashMap::new();
+        let mut namespace_registries = HashMap::new();
+
+        for contract in grid_contracts() {
+            let address = compute_contract_registry_address(contract.name)
+                .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
+            if let Some(bytes) =
+                client.get_state_at_address(service_id, &bytes_to_hex_str(&address))?
+            {
+                let list = ContractRegistryList::from_bytes(&bytes)
+                    .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
+                contract_registries.extend(
+                    list.registries()
+                        .iter()
+                        .filter(|registry| registry.name() == contract.name)
+                        .map(|registry| (registry.name().clone(), registry.clone())),
+                );
+            }
+
+            let address = compute_namespace_registry_address(contract.namespace)
+                .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
+            if let Some(bytes) =
+                client.get_state_at_address(service_id, &bytes_to_hex_str(&address))?
+            {
+                let list = NamespaceRegistryList::from_bytes(&bytes)
+                    .map_err(|err| AppAuthHandlerError::from_source(Box::new(err)))?;
+                namespace_registries.extend(
+                    list.registries()
+                        .iter()
+                        .filter(|registry| registry.namespace() == contract.namespace)
+                        .map(|registry| (registry.namespace().clone(), registry.clone())),
+                );
+            }
+        }
+
+        Ok(Self {
+            contract_registries,
+            namespace_registries,
+        })
+    }
+
+    fn deployed_registries(&self, contract: &GridContract) -> DeployedRegistries {
+        let contract_registry = self.contract_registries.get(contract.name);
+
+        DeployedRegistries {
+            contract_registry: contract_registry.is_some(),
+            versions: contract_registry
+                .map(|registry| {
+                    registry
+                        .versions()
+                        .iter()
+                        .map(|version| version.version().clone())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            namespace_registry: self.namespace_registries.contains_key(contract.namespace),
+            permitted_namespaces: contract
+                .permitted_namespaces
+                .iter()
+                .filter(|namespace| {
+                    self.namespace_registries
+                        .get(**namespace)
+                        .map(|registry| {
+                            registry.permissions().iter().any(|permission| {
+                                permission.contract_name() == contract.name
+                                    && permission.read()
+                                    && permission.write()
+                            })
+                        })
+                        .unwrap_or(false)
+                })
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+
+    /// Returns whether the given key owns the existing registries that the actions change
+    fn is_owner(&self, contract: &GridContract, actions: &[ContractAction], key: &str) -> bool {
+        actions.iter().all(|action| match action {
+            ContractAction::UploadContract { .. } => self
+                .contract_registries
+                .get(contract.name)
+                .map(|registry| registry.owners().iter().any(|owner| owner == key))
+                .unwrap_or(true),
+            ContractAction::GrantPermission { namespace } => self
+                .namespace_registries
+                .get(namespace)
+                .map(|registry| registry.owners().iter().any(|owner| owner == key))
+                .unwrap_or(true),
+            ContractAction::CreateContractRegistry
+            | ContractAction::CreateNamespaceRegistry { .. } => true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sabre_sdk::protocol::state::{
+        ContractRegistryBuilder, NamespaceRegistryBuilder, PermissionBuilder, VersionBuilder,
+    };
+
+    const CONTRACT: GridContract = GridContract {
+        name: "grid-test",
+        namespace: "aaaaaa",
+        permitted_namespaces: &["aaaaaa", "bbbbbb", "cccccc"],
+    };
+
+    /// The test contract's registry, with versions 1.0 and 2.0, owned by `owner`; the registry of
+    /// its namespace, owned by `owner`, which permits it to read and write; the registry of
+    /// `bbbbbb`, owned by `other`, which only permits it to read; and no registry for `cccccc`
+    fn deployed_state() -> DeployedState {
+        let versions = ["1.0", "2.0"]
+            .iter()
+            .map(|version| {
+                VersionBuilder::new()
+                    .with_version(version.to_string())
+                    .with_contract_sha512("sha512".to_string())
+                    .with_creator("owner".to_string())
+                    .build()
+                    .expect("Unable to build version")
+            })
+            .collect();
+        let contract_registry = ContractRegistryBuilder::new()
+            .with_name(CONTRACT.name.to_string())
+            .with_versions(versions)
+            .with_owners(vec!["owner".to_string()])
+            .build()
+            .expect("Unable to build contract registry");
+
+        let namespace_registry = |namespace: &str, owner: &str, write: bool| {
+            let permission = PermissionBuilder::new()
+                .with_contract_name(CONTRACT.name.to_string())
+                .with_read(true)
+                .with_write(write)
+                .build()
+                .expect("Unable to build permission");
+            NamespaceRegistryBuilder::new()
+                .with_namespace(namespace.to_string())
+                .with_owners(vec![owner.to_string()])
+                .with_permissions(vec![permission])
+                .build()
+                .expect("Unable to build namespace registry")
+        };
+
+        let mut contract_registries = HashMap::new();
+        contract_registries.insert(CONTRACT.name.to_string(), contract_registry);
+        let mut namespace_registries = HashMap::new();
+        namespace_registries.insert(
+            "aaaaaa".to_string(),
+            namespace_registry("aaaaaa", "owner", true),
+        );
+        namespace_registries.insert(
+            "bbbbbb".to_string(),
+            namespace_registry("bbbbbb", "other", false),
+        );
+
+        DeployedState {
+            contract_registries,
+            namespace_registries,
+        }
+    }
+
+    /// Verify that a contract's deployed versions and registries are read from the state, and
+    /// that only the namespaces it may both read and write are counted as permitted
+    #[test]
+    fn deployed_registries() {
+        let deployed = deployed_state().deployed_registries(&CONTRACT);
+
+        assert!(deployed.contract_registry);
+        assert_eq!(deployed.versions, vec!["1.0", "2.0"]);
+        assert!(deployed.namespace_registry);
+        assert_eq!(deployed.permitted_namespaces, vec!["aaaaaa"]);
+
+        let missing = GridContract {
+            name: "grid-missing",
+            namespace: "dddddd",
+            permitted_namespaces: &["dddddd"],
+        };
+        let deployed = deployed_state().deployed_registries(&missing);
+
+        assert!(!deployed.contract_registry);
+        assert!(deployed.versions.is_empty());
+        assert!(!deployed.namespace_registry);
+        assert!(deployed.permitted_namespaces.is_empty());
+    }
+
+    /// Verify that a key owns the actions that change registries it owns or that do not exist
+    /// yet, but not those that change registries owned by other keys
+    #[test]
+    fn is_owner() {
+        let state = deployed_state();
+        let upload = ContractAction::UploadContract {
+            version: "3.0".to_string(),
+        };
+        let grant = |namespace: &str| ContractAction::GrantPermission {
+            namespace: namespace.to_string(),
+        };
+
+        assert!(state.is_owner(&CONTRACT, &[upload.clone(), grant("aaaaaa")], "owner"));
+        assert!(!state.is_owner(&CONTRACT, &[upload], "other"));
+        assert!(!state.is_owner(&CONTRACT, &[grant("bbbbbb")], "owner"));
+        assert!(state.is_owner(&CONTRACT, &[grant("bbbbbb")], "other"));
+        assert!(state.is_owner(&CONTRACT, &[grant("cccccc")], "owner"));
+        assert!(state.is_owner(
+            &CONTRACT,
+            &[
+                ContractAction::CreateContractRegistry,
+                ContractAction::CreateNamespaceRegistry {
+                    namespace: "cccccc".to_string(),
+                },
+            ],
+            "other"
+        ));
+    }
+}
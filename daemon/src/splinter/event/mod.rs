@@ -24,9 +24,13 @@ use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
 use scabbard::service::{StateChange as ScabbardStateChange, StateChangeEvent};
 use splinter::events::{Igniter, WebSocketClient, WebSocketError, WsResponse};
 
+#[cfg(feature = "event-sinks")]
+use crate::event::sink::{CommitReplay, CommitReplaySource, ConnectionReplay};
 use crate::event::{
     CommitEvent, EventConnection, EventConnectionUnsubscriber, EventIoError, StateChange,
 };
+#[cfg(feature = "event-sinks")]
+use crate::splinter::app_auth_handler::{get_circuit, get_node_id};
 
 pub use error::ScabbardEventConnectionError;
 
@@ -67,6 +71,92 @@ impl ScabbardEventConnectionFactory {
     }
 }
 
+/// Replays the commits of Scabbard services over connections of their own, from the first of
+/// the given Splinter nodes that each service runs on
+#[cfg(feature = "event-sinks")]
+#[derive(Clone)]
+pub struct ScabbardReplaySource {
+    node_endpoints: Vec<String>,
+    igniter: Igniter,
+    authorization: String,
+}
+
+#[cfg(feature = "event-sinks")]
+impl ScabbardReplaySource {
+    pub fn new(node_endpoints: Vec<String>, igniter: Igniter, authorization: &str) -> Self {
+        Self {
+            node_endpoints,
+            igniter,
+            authorization: authorization.to_string(),
+        }
+    }
+
+    /// Returns the endpoint of the first node the service runs on
+    fn find_node(&self, circuit_id: &str, service_id: &str) -> Result<&str, EventIoError> {
+        // Every service is received from the only node
+        if let [node_endpoint] = self.node_endpoints.as_slice() {
+            return Ok(node_endpoint);
+        }
+
+        for node_endpoint in &self.node_endpoints {
+            match get_circuit(node_endpoint, circuit_id, &self.authorization) {
+                Ok(Some(circuit)) => {
+                    let node_id = get_node_id(node_endpoint.to_string(), &self.authorization)
+                        .map_err(|err| EventIoError::ConnectionError(err.to_string()))?;
+                    if circuit.runs_on(service_id, &node_id) {
+                        return Ok(node_endpoint);
+                    }
+                }
+                Ok(None) => (),
+                Err(err) => warn!(
+                    "Unable to get circuit {} from {}: {}",
+                    circuit_id, node_endpoint, err
+                ),
+            }
+        }
+
+        Err(EventIoError::ConnectionError(format!(
+            "Service {}::{} does not run on any of the Splinter nodes",
+            circuit_id, service_id
+        )))
+    }
+}
+
+#[cfg(feature = "event-sinks")]
+impl CommitReplaySource for ScabbardReplaySource {
+    fn replay(
+        &self,
+        service_id: Option<&str>,
+        last_commit_id: Option<&str>,
+    ) -> Result<Box<dyn CommitReplay>, EventIoError> {
+        let service_id = service_id.ok_or_else(|| {
+            EventIoError::ConnectionError(
+                "Unable to replay commits without a service from Splinter".to_string(),
+            )
+        })?;
+        let (circuit_id, service_id) = service_id.split_once("::").ok_or_else(|| {
+            EventIoError::ConnectionError(format!(
+                "\"{}\" does not conform to the Splinter service id format",
+                service_id
+            ))
+        })?;
+
+        let node_endpoint = self.find_node(circuit_id, service_id)?;
+        let connection = ScabbardEventConnectionFactory::new(node_endpoint, self.igniter.clone())
+            .create_connection(circuit_id, service_id, &self.authorization)
+            .map_err(|err| EventIoError::ConnectionError(err.to_string()))?;
+
+        Ok(Box::new(ConnectionReplay::subscribe(
+            connection,
+            last_commit_id,
+        )?))
+    }
+
+    fn cloned_box(&self) -> Box<dyn CommitReplaySource> {
+        Box::new(self.clone())
+    }
+}
+
 enum ConnectionState {
     Connected(Receiver<ConnectionCommand>),
     Disconnected,
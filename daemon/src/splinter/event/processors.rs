@@ -20,7 +20,7 @@ use std::sync::{Arc, Mutex};
 
 use grid_sdk::error::InternalError;
 
-use crate::event::{EventHandler, EventProcessor};
+use crate::event::{checkpoint::FailurePolicy, EventDispatcher, EventHandler, EventProcessor};
 
 use super::{ScabbardEventConnection, ScabbardEventConnectionFactory};
 
@@ -64,6 +64,26 @@ impl EventProcessors {
             handlers_factory_fn,
         )
     }
+
+    /// Remove the event processor for a given circuit_id::service_id, unsubscribing from the
+    /// service's events.
+    ///
+    /// Returns `false` if the collection has no event processor for the service.
+    pub fn remove(&self, circuit_id: &str, service_id: &str) -> Result<bool, InternalError> {
+        let mut inner = self.inner.lock().map_err(|_| {
+            InternalError::with_message("EventProcessors inner mutex was poisoned".into())
+        })?;
+        inner.remove(circuit_id, service_id)
+    }
+
+    /// Returns the circuit ID and service ID of each service with an event processor in the
+    /// collection.
+    pub fn service_ids(&self) -> Result<Vec<(String, String)>, InternalError> {
+        let inner = self.inner.lock().map_err(|_| {
+            InternalError::with_message("EventProcessors inner mutex was poisoned".into())
+        })?;
+        Ok(inner.service_ids())
+    }
 }
 
 struct Inner {
@@ -97,7 +117,10 @@ impl Inner {
                 .create_connection(circuit_id, service_id, authorization)
                 .map_err(|err| InternalError::from_source(Box::new(err)))?;
 
-            let evt_processor = EventProcessor::start(event_connection, last_seen_id, factory_fn())
+            // The handlers only fail if the events can no longer be passed on, in which case
+            // retrying would never succeed
+            let dispatcher = EventDispatcher::new(factory_fn(), FailurePolicy::Halt);
+            let evt_processor = EventProcessor::start(event_connection, last_seen_id, dispatcher)
                 .map_err(|err| InternalError::from_source(Box::new(err)))?;
 
             entry.insert(evt_processor);
@@ -105,4 +128,35 @@ impl Inner {
 
         Ok(())
     }
+
+    pub fn remove(&mut self, circuit_id: &str, service_id: &str) -> Result<bool, InternalError> {
+        let key = format!("{}::{}", circuit_id, service_id);
+        let evt_processor = match self.processors.remove(&key) {
+            Some(evt_processor) => evt_processor,
+            None => return Ok(false),
+        };
+
+        let (shutdown_handle, join_handle) = evt_processor.take_shutdown_controls();
+        shutdown_handle
+            .shutdown()
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        match join_handle.join() {
+            Ok(Ok(())) => (),
+            Ok(Err(err)) => error!("Event processor for {} failed: {}", key, err),
+            Err(_) => error!("Unable to cleanly join the event processor for {}", key),
+        }
+
+        Ok(true)
+    }
+
+    pub fn service_ids(&self) -> Vec<(String, String)> {
+        self.processors
+            .keys()
+            .filter_map(|key| {
+                let mut splits = key.splitn(2, "::");
+                Some((splits.next()?.to_string(), splits.next()?.to_string()))
+            })
+            .collect()
+    }
 }
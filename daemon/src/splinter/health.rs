@@ -0,0 +1,169 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+use std::time::Duration;
+
+use grid_sdk::rest_api::resources::health::v1::HealthCheck;
+use serde_json::Value;
+
+use crate::event::health::StreamReport;
+
+/// How long the node is waited on, so that a readiness probe is answered before it times out
+const NODE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How far the commits of a Scabbard service lag behind being indexed
+#[derive(Debug, PartialEq, Serialize)]
+struct ServiceLag {
+    service_id: String,
+    last_indexed_commit_id: Option<String>,
+    /// Scabbard does not number its commits, so this is the number of commits received from the
+    /// service that are waiting to be indexed
+    lag: usize,
+}
+
+/// Checks that the splinterd node answers gridd's requests, and reports its node ID and how far
+/// indexing lags behind each service
+#[derive(Clone)]
+pub struct SplinterHealthCheck {
+    splinterd_url: String,
+    authorization: String,
+}
+
+impl SplinterHealthCheck {
+    pub fn new(splinterd_url: &str, authorization: &str) -> Self {
+        Self {
+            splinterd_url: splinterd_url.to_string(),
+            authorization: authorization.to_string(),
+        }
+    }
+
+    /// Returns the `splinter` check, which fails unless the node's status can be fetched. The lag
+    /// of each service is taken from the given streams of the event health.
+    pub fn check(&self, streams: &[StreamReport]) -> HealthCheck {
+        match self.get_node_id() {
+            Ok(node_id) => HealthCheck::pass("splinter")
+                .with_detail("endpoint", &self.splinterd_url)
+                .with_detail("node_id", node_id)
+                .with_detail("services", service_lags(streams)),
+            Err(err) => {
+                HealthCheck::fail("splinter", &err).with_detail("endpoint", &self.splinterd_url)
+            }
+        }
+    }
+
+    fn get_node_id(&self) -> Result<Option<String>, String> {
+        let response = reqwest::blocking::Client::builder()
+            .timeout(NODE_TIMEOUT)
+            .build()
+            .and_then(|client| {
+                client
+                    .get(&format!("{}/status", self.splinterd_url))
+                    .header("Authorization", &self.authorization)
+                    .send()
+            })
+            .map_err(|err| format!("Unable to reach {}: {}", self.splinterd_url, err))?;
+
+        if !response.status().is_success() {
+            return Err(format!(
+                "{} responded with {}",
+                self.splinterd_url,
+                response.status()
+            ));
+        }
+
+        let status: Value = response
+            .json()
+            .map_err(|err| format!("Failed to parse response body: {}", err))?;
+
+        Ok(status
+            .get("node_id")
+            .and_then(Value::as_str)
+            .map(ToString::to_string))
+    }
+}
+
+/// Returns the lag of each Scabbard service; the stream without a service ID is Sawtooth's
+fn service_lags(streams: &[StreamReport]) -> Vec<ServiceLag> {
+    streams
+        .iter()
+        .filter_map(|stream| {
+            stream.service_id.as_ref().map(|service_id| ServiceLag {
+                service_id: service_id.to_string(),
+                last_indexed_commit_id: stream
+                    .last_indexed
+                    .as_ref()
+                    .map(|mark| mark.commit_id.to_string()),
+                lag: stream.pending,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::event::health::EventHealth;
+    use crate::event::CommitEvent;
+
+    /// Verify that each Scabbard service reports the commits received from it that have not been
+    /// indexed as its lag, and that the Sawtooth stream is left out.
+    #[test]
+    fn service_lag() {
+        let health = EventHealth::default();
+
+        let sawtooth_commit = commit(None, "block-0");
+        health.record_received(&sawtooth_commit);
+
+        let first = commit(Some("circuit::gsAA"), "event-0");
+        let second = commit(Some("circuit::gsAA"), "event-1");
+        let third = commit(Some("circuit::gsAA"), "event-2");
+        health.record_received(&first);
+        health.record_received(&second);
+        health.record_received(&third);
+        health.record_indexed(&first);
+
+        let other = commit(Some("circuit::gsBB"), "event-0");
+        health.record_received(&other);
+        health.record_indexed(&other);
+
+        assert_eq!(
+            service_lags(&health.report().streams),
+            vec![
+                ServiceLag {
+                    service_id: "circuit::gsAA".to_string(),
+                    last_indexed_commit_id: Some("event-0".to_string()),
+                    lag: 2,
+                },
+                ServiceLag {
+                    service_id: "circuit::gsBB".to_string(),
+                    last_indexed_commit_id: Some("event-0".to_string()),
+                    lag: 0,
+                },
+            ]
+        );
+    }
+
+    fn commit(service_id: Option<&str>, id: &str) -> CommitEvent {
+        CommitEvent {
+            service_id: service_id.map(ToString::to_string),
+            id: id.to_string(),
+            height: None,
+            state_changes: vec![],
+        }
+    }
+}
@@ -0,0 +1,247 @@
+/*
+ * Copyright 2022 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * -----------------------------------------------------------------------------
+ */
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::{mpsc::channel, mpsc::Sender, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use grid_sdk::commits::store::{
+    CommitStore, CommitStoreError, ServiceDataStatus, ServiceLifecycle, ServiceStatus,
+};
+use grid_sdk::error::InternalError;
+use splinter::admin::messages::CircuitStatus;
+
+use crate::event::channel::EventCmd;
+
+use super::{app_auth_handler::get_circuit, event::processors::EventProcessors};
+
+/// How often the circuits of the services whose events are received are checked
+const CIRCUIT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What is done with the records indexed from a service once its circuit is disbanded or
+/// abandoned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServiceDataPolicy {
+    /// The records are kept, and marked as archived. Archived records are still returned by the
+    /// REST API, along with the records of active services.
+    Archive,
+    /// The records are removed
+    Purge,
+}
+
+impl FromStr for ServiceDataPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "archive" => Ok(ServiceDataPolicy::Archive),
+            "purge" => Ok(ServiceDataPolicy::Purge),
+            _ => Err(format!("'{}' is not one of archive or purge", s)),
+        }
+    }
+}
+
+/// Records the lifecycle states of the Scabbard services whose commits are indexed
+#[derive(Clone)]
+pub struct ServiceLifecycles {
+    commit_store: Arc<dyn CommitStore + Send + Sync>,
+    data_policy: ServiceDataPolicy,
+    event_sender: Option<Arc<Mutex<Sender<EventCmd>>>>,
+}
+
+impl ServiceLifecycles {
+    pub fn new(
+        commit_store: Arc<dyn CommitStore + Send + Sync>,
+        data_policy: ServiceDataPolicy,
+    ) -> Self {
+        Self {
+            commit_store,
+            data_policy,
+            event_sender: None,
+        }
+    }
+
+    /// Fences each retired service in the dispatcher thread that the given sender sends to, so
+    /// that none of the service's commits are indexed once it is retired
+    pub fn with_event_sender(mut self, event_sender: Sender<EventCmd>) -> Self {
+        self.event_sender = Some(Arc::new(Mutex::new(event_sender)));
+        self
+    }
+
+    /// Records that a service's circuit is ready and its commits are indexed
+    pub fn activate(&self, service_id: &str) -> Result<(), CommitStoreError> {
+        self.commit_store
+            .update_service_lifecycle(ServiceLifecycle {
+                service_id: service_id.to_string(),
+                status: ServiceStatus::Active,
+                data_status: ServiceDataStatus::Indexed,
+            })
+    }
+
+    /// Records that a service's circuit has been disbanded or abandoned, archiving or purging the
+    /// records indexed from the service's commits according to the data policy
+    ///
+    /// The service's commits that are waiting to be dispatched are indexed first, and none are
+    /// indexed after, so purged records are not recreated.
+    pub fn retire(
+        &self,
+        service_id: &str,
+        status: ServiceStatus,
+    ) -> Result<ServiceDataStatus, CommitStoreError> {
+        self.fence(service_id)
+            .map_err(CommitStoreError::InternalError)?;
+
+        let data_status = match self.data_policy {
+            ServiceDataPolicy::Archive => ServiceDataStatus::Archived,
+            ServiceDataPolicy::Purge => {
+                self.commit_store.purge_service(service_id)?;
+                ServiceDataStatus::Purged
+            }
+        };
+
+        self.commit_store
+            .update_service_lifecycle(ServiceLifecycle {
+                service_id: service_id.to_string(),
+                status,
+                data_status,
+            })?;
+
+        Ok(data_status)
+    }
+
+    /// Returns the IDs of the services whose circuits are active
+    #[cfg(feature = "contract-upgrade")]
+    pub fn active(&self) -> Result<Vec<String>, CommitStoreError> {
+        Ok(self
+            .commit_store
+            .list_service_lifecycles()?
+            .into_iter()
+            .filter(|lifecycle| lifecycle.status == ServiceStatus::Active)
+            .map(|lifecycle| lifecycle.service_id)
+            .collect())
+    }
+
+    fn fence(&self, service_id: &str) -> Result<(), InternalError> {
+        let event_sender = match &self.event_sender {
+            Some(event_sender) => event_sender,
+            None => return Ok(()),
+        };
+
+        let (done_sender, done_receiver) = channel();
+        event_sender
+            .lock()
+            .map_err(|_| InternalError::with_message("Event sender lock was poisoned".into()))?
+            .send(EventCmd::Fence(service_id.to_string(), done_sender))
+            .map_err(|_| {
+                InternalError::with_message("Unable to fence service due to closed channel".into())
+            })?;
+        done_receiver.recv().map_err(|_| {
+            InternalError::with_message(format!(
+                "Dispatcher stopped before service {} was fenced",
+                service_id
+            ))
+        })
+    }
+
+    /// Returns the IDs of the services whose circuits have been disbanded or abandoned
+    pub fn retired(&self) -> Result<HashSet<String>, CommitStoreError> {
+        Ok(self
+            .commit_store
+            .list_service_lifecycles()?
+            .into_iter()
+            .filter(|lifecycle| lifecycle.status != ServiceStatus::Active)
+            .map(|lifecycle| lifecycle.service_id)
+            .collect())
+    }
+}
+
+/// Returns the status of a service whose circuit has the given status, if the service is retired
+pub fn retired_status(circuit_status: &CircuitStatus) -> Option<ServiceStatus> {
+    match circuit_status {
+        CircuitStatus::Disbanded => Some(ServiceStatus::Disbanded),
+        CircuitStatus::Abandoned => Some(ServiceStatus::Abandoned),
+        _ => None,
+    }
+}
+
+/// Starts a thread that periodically checks the circuits of the services whose events are
+/// received, unsubscribing from and retiring the services of circuits that have been disbanded or
+/// abandoned. Splinter does not announce abandoned circuits, and a disband may be missed while the
+/// admin event connection is down.
+///
+/// The thread runs until gridd exits.
+pub fn spawn_circuit_watcher(
+    splinterd_url: String,
+    event_processors: EventProcessors,
+    service_lifecycles: ServiceLifecycles,
+    authorization: String,
+) -> Result<thread::JoinHandle<()>, InternalError> {
+    thread::Builder::new()
+        .name("CircuitWatcher".into())
+        .spawn(move || loop {
+            thread::sleep(CIRCUIT_POLL_INTERVAL);
+
+            let service_ids = match event_processors.service_ids() {
+                Ok(service_ids) => service_ids,
+                Err(err) => {
+                    error!("Unable to list the services receiving events: {}", err);
+                    continue;
+                }
+            };
+
+            for (circuit_id, service_id) in service_ids {
+                let status = match get_circuit(&splinterd_url, &circuit_id, &authorization) {
+                    Ok(Some(circuit)) => retired_status(&circuit.status),
+                    Ok(None) => None,
+                    Err(err) => {
+                        warn!(
+                            "Unable to get the status of circuit {}: {}",
+                            circuit_id, err
+                        );
+                        None
+                    }
+                };
+                let status = match status {
+                    Some(status) => status,
+                    None => continue,
+                };
+
+                if let Err(err) = event_processors.remove(&circuit_id, &service_id) {
+                    error!(
+                        "Unable to stop receiving the events of service {}::{}: {}",
+                        circuit_id, service_id, err
+                    );
+                    continue;
+                }
+
+                match service_lifecycles.retire(&format!("{}::{}", circuit_id, service_id), status)
+                {
+                    Ok(data_status) => info!(
+                        "Circuit {} is {}; the data of service {}::{} is {}",
+                        circuit_id, status, circuit_id, service_id, data_status
+                    ),
+                    Err(err) => error!(
+                        "Unable to retire service {}::{}: {}",
+                        circuit_id, service_id, err
+                    ),
+                }
+            }
+        })
+        .map_err(|err| InternalError::from_source(Box::new(err)))
+}
@@ -16,7 +16,19 @@
  */
 
 pub mod app_auth_handler;
+#[cfg(feature = "contract-upgrade")]
+pub mod contracts;
 pub mod event;
+#[cfg(feature = "health")]
+pub mod health;
+pub mod lifecycle;
 mod run;
 
+#[cfg(feature = "contract-upgrade")]
+pub use run::run_contract_upgrade;
 pub use run::run_splinter;
+#[cfg(feature = "multi-source")]
+pub(crate) use run::{
+    create_database_store_factory, load_credentials, open_database, service_resume_points,
+    start_splinter_source,
+};
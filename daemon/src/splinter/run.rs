@@ -35,180 +35,116 @@ use grid_sdk::rest_api::actix_web_4::Endpoint;
 #[cfg(feature = "integration")]
 use grid_sdk::rest_api::actix_web_4::KeyState;
 use grid_sdk::rest_api::actix_web_4::{BackendState, StoreState};
-use grid_sdk::store::{create_store_factory, ConnectionUri};
-use splinter::events::Reactor;
+#[cfg(feature = "event-stream")]
+use grid_sdk::rest_api::resources::event_stream::v1::EventBroadcaster;
+use grid_sdk::store::{create_store_factory, ConnectionUri, TransactionalStoreFactory};
+use splinter::events::{Igniter, Reactor};
 
 use crate::config::GridConfig;
 use crate::database::ConnectionPool;
 use crate::error::DaemonError;
-use crate::event::{db_handler::DatabaseEventHandler, CommitEvent, EventError, EventHandler};
+#[cfg(feature = "track-and-trace")]
+use crate::event::alert::load_alert_rules;
+#[cfg(feature = "event-stream")]
+use crate::event::create_event_broadcaster;
+#[cfg(feature = "health")]
+use crate::event::health::{EventHealth, EventHealthHandler};
+#[cfg(feature = "event-sinks")]
+use crate::event::sink::CommitReplaySource;
+use crate::event::{
+    channel::{spawn_dispatcher, ChannelEventHandler, EventCmd},
+    db_handler::DatabaseEventHandler,
+    registry::EventHandlerRegistry,
+    EventDispatcher, EventError, EventHandler,
+};
 use crate::rest_api;
-
+#[cfg(feature = "health")]
+use crate::rest_api::{HealthState, NodeHealthCheck};
+
+#[cfg(feature = "contract-upgrade")]
+use super::contracts::{ContractUpgrader, UpgradeReport};
+#[cfg(feature = "event-sinks")]
+use super::event::ScabbardReplaySource;
+#[cfg(feature = "health")]
+use super::health::SplinterHealthCheck;
 use super::{
-    app_auth_handler, event::processors::EventProcessors, event::ScabbardEventConnectionFactory,
+    app_auth_handler,
+    event::processors::EventProcessors,
+    event::ScabbardEventConnectionFactory,
+    lifecycle::{retired_status, spawn_circuit_watcher, ServiceLifecycles},
 };
 
-enum EventCmd {
-    Event(CommitEvent),
-    Exit,
-}
-
-struct ChannelEventHandler {
-    sender: std::sync::mpsc::Sender<EventCmd>,
-}
-
-impl EventHandler for ChannelEventHandler {
-    fn handle_event(&self, event: &CommitEvent) -> Result<(), EventError> {
-        self.sender
-            .send(EventCmd::Event(event.clone()))
-            .map_err(|_| EventError("Unable to send event due to closed channel".into()))
-    }
-
-    fn cloned_box(&self) -> Box<dyn EventHandler> {
-        Box::new(ChannelEventHandler {
-            sender: self.sender.clone(),
-        })
-    }
-}
-
 pub fn run_splinter(config: GridConfig) -> Result<(), DaemonError> {
     let splinter_endpoint = Endpoint::from(config.endpoint());
     let reactor = Reactor::new();
-    let gridd_key = load_key("gridd", &[PathBuf::from(config.admin_key_dir())])
-        .map_err(|err| DaemonError::from_source(Box::new(err)))?
-        .ok_or_else(|| DaemonError::with_message("no private key found"))?;
+    let (scabbard_admin_key, authorization) = load_credentials(&config)?;
 
-    let scabbard_admin_key = &gridd_key.as_hex();
+    let store_factory = create_database_store_factory(&config)?;
 
-    let signer = Secp256k1Context::new().new_signer(gridd_key);
+    #[cfg(feature = "event-stream")]
+    let event_broadcaster = create_event_broadcaster(&*store_factory)
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
 
-    let authorization = {
-        let jwt = JsonWebTokenBuilder::new()
-            .build(&*signer)
-            .map_err(|err| DaemonError::from_source(Box::new(err)))?;
-        format!("Bearer Cylinder:{}", jwt)
-    };
-
-    let scabbard_event_connection_factory =
-        ScabbardEventConnectionFactory::new(&splinter_endpoint.url(), reactor.igniter());
+    #[cfg(feature = "health")]
+    let event_health = EventHealth::default();
 
-    let event_processors = EventProcessors::new(scabbard_event_connection_factory);
-
-    #[cfg(not(any(feature = "database-postgres", feature = "database-sqlite")))]
-    return Err(DaemonError::with_message(
-        "A database backend is required to be active. Supported backends are postgreSQL and SQLite",
-    ));
-
-    #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
-    let (store_state, db_handler, previous_commits): (_, Box<dyn EventHandler>, Vec<Commit>) = {
-        let connection_uri = config
-            .database_url()
-            .parse()
+    // A sink that falls behind catches up from the node
+    #[cfg(feature = "event-sinks")]
+    let replay_source = ScabbardReplaySource::new(
+        vec![splinter_endpoint.url()],
+        reactor.igniter(),
+        &authorization,
+    );
+
+    let (store_state, mut dispatcher, previous_commits, service_lifecycles) = open_database(
+        &config,
+        store_factory,
+        #[cfg(feature = "event-sinks")]
+        &replay_source,
+        #[cfg(feature = "event-stream")]
+        &event_broadcaster,
+        #[cfg(feature = "health")]
+        &event_health,
+    )?;
+
+    let resume_points =
+        service_resume_points(&mut dispatcher, previous_commits, &service_lifecycles)?;
+
+    let (event_tx, db_event_handler_join_handler) =
+        spawn_dispatcher("db-event-handler-splinter", dispatcher)
             .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+    let service_lifecycles = service_lifecycles.with_event_sender(event_tx.clone());
 
-        match connection_uri {
-            #[cfg(feature = "database-postgres")]
-            ConnectionUri::Postgres(_) => {
-                let connection_pool: ConnectionPool<diesel::pg::PgConnection> =
-                    ConnectionPool::new(config.database_url())
-                        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
-                let store_factory = create_store_factory(&connection_uri)
-                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
-                let event_handler = DatabaseEventHandler::new(store_factory);
-
-                let commit_store = DieselCommitStore::new(connection_pool.pool.clone());
-                let commits = commit_store
-                    .get_current_service_commits()
-                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
-
-                (
-                    StoreState::with_pg_pool(connection_pool.pool),
-                    Box::new(event_handler),
-                    commits,
-                )
-            }
-            #[cfg(feature = "database-sqlite")]
-            ConnectionUri::Sqlite(_) => {
-                let connection_pool: ConnectionPool<diesel::sqlite::SqliteConnection> =
-                    ConnectionPool::new(config.database_url())
-                        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
-                let store_factory = create_store_factory(&connection_uri)
-                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
-                let event_handler = DatabaseEventHandler::new(store_factory);
-
-                let commit_store = DieselCommitStore::new(connection_pool.pool.clone());
-                let commits = commit_store
-                    .get_current_service_commits()
-                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
-
-                (
-                    StoreState::with_sqlite_pool(connection_pool.pool),
-                    Box::new(event_handler),
-                    commits,
-                )
-            }
-        }
-    };
-
-    let (event_tx, event_rx) = std::sync::mpsc::channel();
-    let chan_event_handler: Box<dyn EventHandler> = Box::new(ChannelEventHandler {
-        sender: event_tx.clone(),
-    });
-
-    let db_event_handler_join_handler = std::thread::Builder::new()
-        .name("db-event-handler-splinter".into())
-        .spawn(move || loop {
-            match event_rx.recv() {
-                Ok(EventCmd::Event(evt)) => {
-                    if let Err(err) = db_handler.handle_event(&evt) {
-                        error!("{}", err.to_string());
-                    }
-                }
-                Ok(EventCmd::Exit) => break,
-                Err(_) => break,
-            }
-        })
-        .map_err(|_| DaemonError::with_message("Unable to spawn db handler thread"))?;
-
-    for commit in previous_commits {
-        if let Some(service_id) = commit.service_id {
-            let service_id = match ServiceId::try_from(service_id.deref()) {
-                Ok(service_id) => service_id,
-                Err(_) => {
-                    warn!(
-                        "\"{}\" does not conform to the Splinter service id format; skipping",
-                        service_id
-                    );
-                    continue;
-                }
-            };
-
-            debug!(
-                "Reconnecting event processing on service {} (from {})",
-                service_id, commit.commit_id
-            );
+    #[cfg(feature = "contract-upgrade")]
+    let contract_upgrader = ContractUpgrader::new(
+        splinter_endpoint.url(),
+        scabbard_admin_key.clone(),
+        authorization.clone(),
+        service_lifecycles.clone(),
+    );
 
-            event_processors
-                .add_once(
-                    service_id.circuit_id,
-                    service_id.service_id,
-                    Some(&commit.commit_id),
-                    &authorization,
-                    || vec![chan_event_handler.cloned_box()],
-                )
-                .map_err(|err| DaemonError::from_source(Box::new(err)))?;
-        }
-    }
+    let handler: Box<dyn EventHandler> = Box::new(ChannelEventHandler::new(event_tx.clone()));
+    #[cfg(feature = "health")]
+    let handler: Box<dyn EventHandler> =
+        Box::new(EventHealthHandler::received(event_health.clone(), handler));
 
-    app_auth_handler::run(
-        splinter_endpoint.url(),
-        event_processors,
-        chan_event_handler,
+    start_splinter_source(
+        &splinter_endpoint.url(),
         reactor.igniter(),
-        scabbard_admin_key.to_string(),
+        handler,
+        resume_points,
+        service_lifecycles,
+        scabbard_admin_key,
         authorization.clone(),
-    )
-    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+    )?;
+
+    #[cfg(feature = "health")]
+    event_health.subscribed(&splinter_endpoint.url());
+    #[cfg(feature = "health")]
+    let health_state =
+        HealthState::new(&config, event_health)?.with_node(NodeHealthCheck::Splinter(
+            SplinterHealthCheck::new(&splinter_endpoint.url(), &authorization),
+        ));
 
     let backend_client = SplinterBackendClient::new(splinter_endpoint.url(), authorization);
     let backend_state = BackendState::new(Arc::new(backend_client));
@@ -223,6 +159,14 @@ pub fn run_splinter(config: GridConfig) -> Result<(), DaemonError> {
         backend_state,
         #[cfg(feature = "integration")]
         key_state,
+        #[cfg(feature = "event-stream")]
+        event_broadcaster,
+        #[cfg(feature = "contract-upgrade")]
+        Some(contract_upgrader),
+        #[cfg(feature = "multi-source")]
+        None,
+        #[cfg(feature = "health")]
+        health_state,
         splinter_endpoint,
     )
     .map_err(|err| DaemonError::from_source(Box::new(err)))?;
@@ -273,6 +217,329 @@ pub fn run_splinter(config: GridConfig) -> Result<(), DaemonError> {
     Ok(())
 }
 
+/// Creates the factory of the stores in the configured database
+pub(crate) fn create_database_store_factory(
+    config: &GridConfig,
+) -> Result<Box<dyn TransactionalStoreFactory>, DaemonError> {
+    let connection_uri = config
+        .database_url()
+        .parse()
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+    create_store_factory(&connection_uri).map_err(|err| DaemonError::from_source(Box::new(err)))
+}
+
+/// Connects to the database, returning the REST API's store state, the dispatcher of the
+/// database event handler and any configured sinks, the current commit of each service and the
+/// lifecycles of the services
+///
+/// The given store factory is shared by the database event handler and any configured sinks,
+/// and a sink that falls behind catches up through the given replay source.
+pub(crate) fn open_database(
+    config: &GridConfig,
+    store_factory: Box<dyn TransactionalStoreFactory>,
+    #[cfg(feature = "event-sinks")] replay_source: &dyn CommitReplaySource,
+    #[cfg(feature = "event-stream")] event_broadcaster: &EventBroadcaster,
+    #[cfg(feature = "health")] event_health: &EventHealth,
+) -> Result<(StoreState, EventDispatcher, Vec<Commit>, ServiceLifecycles), DaemonError> {
+    #[cfg(feature = "track-and-trace")]
+    let alert_rules = match config.alert_rules_file() {
+        Some(path) => load_alert_rules(path)?,
+        None => Vec::new(),
+    };
+
+    #[cfg(not(any(feature = "database-postgres", feature = "database-sqlite")))]
+    return Err(DaemonError::with_message(
+        "A database backend is required to be active. Supported backends are postgreSQL and SQLite",
+    ));
+
+    #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+    {
+        let connection_uri = config
+            .database_url()
+            .parse()
+            .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+        match connection_uri {
+            #[cfg(feature = "database-postgres")]
+            ConnectionUri::Postgres(_) => {
+                let connection_pool: ConnectionPool<diesel::pg::PgConnection> =
+                    ConnectionPool::new(config.database_url())
+                        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+                #[cfg(feature = "event-sinks")]
+                let sink_store_factory = store_factory.clone_box();
+                let event_handler = DatabaseEventHandler::new(store_factory);
+                #[cfg(feature = "track-and-trace")]
+                let event_handler = event_handler.with_alert_rules(alert_rules);
+                #[cfg(feature = "event-stream")]
+                let event_handler = event_handler.with_event_broadcaster(event_broadcaster.clone());
+                #[cfg(feature = "health")]
+                let event_handler =
+                    EventHealthHandler::indexed(event_health.clone(), Box::new(event_handler));
+                let registry = EventHandlerRegistry::new(config.event_failure_policy())
+                    .with_handler(Box::new(event_handler));
+                #[cfg(feature = "event-sinks")]
+                let registry = registry
+                    .with_sinks(config.event_sinks(), &*sink_store_factory, replay_source)
+                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+                let commit_store = DieselCommitStore::new(connection_pool.pool.clone());
+                let commits = commit_store
+                    .get_current_service_commits()
+                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+                Ok((
+                    StoreState::with_pg_pool(connection_pool.pool),
+                    registry.into_dispatcher(),
+                    commits,
+                    ServiceLifecycles::new(Arc::new(commit_store), config.service_data_policy()),
+                ))
+            }
+            #[cfg(feature = "database-sqlite")]
+            ConnectionUri::Sqlite(_) => {
+                let connection_pool: ConnectionPool<diesel::sqlite::SqliteConnection> =
+                    ConnectionPool::new(config.database_url())
+                        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+                #[cfg(feature = "event-sinks")]
+                let sink_store_factory = store_factory.clone_box();
+                let event_handler = DatabaseEventHandler::new(store_factory);
+                #[cfg(feature = "track-and-trace")]
+                let event_handler = event_handler.with_alert_rules(alert_rules);
+                #[cfg(feature = "event-stream")]
+                let event_handler = event_handler.with_event_broadcaster(event_broadcaster.clone());
+                #[cfg(feature = "health")]
+                let event_handler =
+                    EventHealthHandler::indexed(event_health.clone(), Box::new(event_handler));
+                let registry = EventHandlerRegistry::new(config.event_failure_policy())
+                    .with_handler(Box::new(event_handler));
+                #[cfg(feature = "event-sinks")]
+                let registry = registry
+                    .with_sinks(config.event_sinks(), &*sink_store_factory, replay_source)
+                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+                let commit_store = DieselCommitStore::new(connection_pool.pool.clone());
+                let commits = commit_store
+                    .get_current_service_commits()
+                    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+                Ok((
+                    StoreState::with_sqlite_pool(connection_pool.pool),
+                    registry.into_dispatcher(),
+                    commits,
+                    ServiceLifecycles::new(Arc::new(commit_store), config.service_data_policy()),
+                ))
+            }
+        }
+    }
+}
+
+/// Returns the commit after which the events of each service with commits in the database are
+/// requested again: the earliest of the handlers' checkpoints. The services of disbanded and
+/// abandoned circuits are not included, as they are not reconnected.
+pub(crate) fn service_resume_points(
+    dispatcher: &mut EventDispatcher,
+    previous_commits: Vec<Commit>,
+    service_lifecycles: &ServiceLifecycles,
+) -> Result<Vec<(String, Option<String>)>, DaemonError> {
+    let retired_services = service_lifecycles
+        .retired()
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+    previous_commits
+        .into_iter()
+        .filter_map(|commit| commit.service_id)
+        .filter(|service_id| !retired_services.contains(service_id))
+        .map(|service_id| {
+            let resume_point = dispatcher.resume_point(Some(&service_id))?;
+            Ok((service_id, resume_point))
+        })
+        .collect::<Result<Vec<_>, EventError>>()
+        .map_err(|err| DaemonError::from_source(Box::new(err)))
+}
+
+/// Reconnects the event processing of the services that run on a splinterd node, from their
+/// resume points, and starts listening for the node's new circuits. The commits of each service
+/// are passed to the given handler.
+///
+/// Services that run on other nodes, or whose circuit is not known to the node, are skipped.
+pub(crate) fn start_splinter_source(
+    splinterd_url: &str,
+    igniter: Igniter,
+    handler: Box<dyn EventHandler>,
+    resume_points: Vec<(String, Option<String>)>,
+    service_lifecycles: ServiceLifecycles,
+    scabbard_admin_key: String,
+    authorization: String,
+) -> Result<(), DaemonError> {
+    let scabbard_event_connection_factory =
+        ScabbardEventConnectionFactory::new(splinterd_url, igniter.clone());
+
+    let event_processors = EventProcessors::new(scabbard_event_connection_factory);
+
+    let node_id = app_auth_handler::get_node_id(splinterd_url.to_string(), &authorization)
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+    for (service_id, resume_point) in resume_points {
+        let service_id = match ServiceId::try_from(service_id.deref()) {
+            Ok(service_id) => service_id,
+            Err(_) => {
+                warn!(
+                    "\"{}\" does not conform to the Splinter service id format; skipping",
+                    service_id
+                );
+                continue;
+            }
+        };
+
+        // Splinter does not announce abandoned circuits, and circuits may have been disbanded
+        // while gridd was not running; both are also watched for while gridd runs
+        let status = match app_auth_handler::get_circuit(
+            splinterd_url,
+            service_id.circuit_id,
+            &authorization,
+        ) {
+            Ok(Some(circuit)) if !circuit.runs_on(service_id.service_id, &node_id) => {
+                debug!(
+                    "Service {} does not run on node {}; skipping",
+                    service_id, node_id
+                );
+                continue;
+            }
+            Ok(Some(circuit)) => retired_status(&circuit.status),
+            Ok(None) => {
+                debug!(
+                    "Circuit {} is not known to node {}; skipping service {}",
+                    service_id.circuit_id, node_id, service_id
+                );
+                continue;
+            }
+            Err(err) => {
+                warn!(
+                    "Unable to get the status of circuit {}: {}",
+                    service_id.circuit_id, err
+                );
+                None
+            }
+        };
+        if let Some(status) = status {
+            let data_status = service_lifecycles
+                .retire(&service_id.to_string(), status)
+                .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+            info!(
+                "Circuit {} is {}; the data of service {} is {}",
+                service_id.circuit_id, status, service_id, data_status
+            );
+            continue;
+        }
+
+        debug!(
+            "Reconnecting event processing on service {} (from {})",
+            service_id,
+            resume_point.as_deref().unwrap_or("the first commit")
+        );
+
+        event_processors
+            .add_once(
+                service_id.circuit_id,
+                service_id.service_id,
+                resume_point.as_deref(),
+                &authorization,
+                || vec![handler.cloned_box()],
+            )
+            .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+    }
+
+    spawn_circuit_watcher(
+        splinterd_url.to_string(),
+        event_processors.clone(),
+        service_lifecycles.clone(),
+        authorization.clone(),
+    )
+    .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+    app_auth_handler::run(
+        splinterd_url.to_string(),
+        event_processors,
+        handler,
+        service_lifecycles,
+        igniter,
+        scabbard_admin_key,
+        authorization,
+    )
+    .map_err(|err| DaemonError::from_source(Box::new(err)))
+}
+
+/// Compares the Grid smart contracts deployed on the active circuits, or on the given circuit,
+/// with the available `.scar` files, upgrading them unless `dry_run` is set
+#[cfg(feature = "contract-upgrade")]
+pub fn run_contract_upgrade(
+    config: &GridConfig,
+    circuit_id: Option<&str>,
+    dry_run: bool,
+) -> Result<UpgradeReport, DaemonError> {
+    let splinter_endpoint = Endpoint::from(config.endpoint());
+    let (scabbard_admin_key, authorization) = load_credentials(config)?;
+
+    #[cfg(not(any(feature = "database-postgres", feature = "database-sqlite")))]
+    return Err(DaemonError::with_message(
+        "A database backend is required to be active. Supported backends are postgreSQL and SQLite",
+    ));
+
+    #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+    {
+        let connection_uri = config
+            .database_url()
+            .parse()
+            .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+
+        let commit_store: Arc<dyn CommitStore + Send + Sync> = match connection_uri {
+            #[cfg(feature = "database-postgres")]
+            ConnectionUri::Postgres(_) => {
+                let connection_pool: ConnectionPool<diesel::pg::PgConnection> =
+                    ConnectionPool::new(config.database_url())
+                        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+                Arc::new(DieselCommitStore::new(connection_pool.pool))
+            }
+            #[cfg(feature = "database-sqlite")]
+            ConnectionUri::Sqlite(_) => {
+                let connection_pool: ConnectionPool<diesel::sqlite::SqliteConnection> =
+                    ConnectionPool::new(config.database_url())
+                        .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+                Arc::new(DieselCommitStore::new(connection_pool.pool))
+            }
+        };
+
+        ContractUpgrader::new(
+            splinter_endpoint.url(),
+            scabbard_admin_key,
+            authorization,
+            ServiceLifecycles::new(commit_store, config.service_data_policy()),
+        )
+        .upgrade(circuit_id, dry_run)
+        .map_err(|err| DaemonError::from_source(Box::new(err)))
+    }
+}
+
+/// Loads the gridd key, returning it as the Scabbard admin key along with the authorization that
+/// gridd uses to make requests to splinterd
+pub(crate) fn load_credentials(config: &GridConfig) -> Result<(String, String), DaemonError> {
+    let gridd_key = load_key("gridd", &[PathBuf::from(config.admin_key_dir())])
+        .map_err(|err| DaemonError::from_source(Box::new(err)))?
+        .ok_or_else(|| DaemonError::with_message("no private key found"))?;
+
+    let scabbard_admin_key = gridd_key.as_hex();
+
+    let signer = Secp256k1Context::new().new_signer(gridd_key);
+
+    let authorization = {
+        let jwt = JsonWebTokenBuilder::new()
+            .build(&*signer)
+            .map_err(|err| DaemonError::from_source(Box::new(err)))?;
+        format!("Bearer Cylinder:{}", jwt)
+    };
+
+    Ok((scabbard_admin_key, authorization))
+}
+
 struct ServiceId<'a> {
     circuit_id: &'a str,
     service_id: &'a str,
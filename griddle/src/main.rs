@@ -23,29 +23,92 @@ mod error;
 #[cfg(feature = "rest-api")]
 mod rest_api;
 
+#[cfg(feature = "batch-submission")]
+use std::convert::TryFrom;
 use std::env;
-#[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+#[cfg(feature = "batch-submission")]
+use std::path::PathBuf;
+#[cfg(any(
+    feature = "batch-submission",
+    feature = "database-postgres",
+    feature = "database-sqlite"
+))]
 use std::str::FromStr;
-use std::sync::Arc;
+#[cfg(feature = "batch-submission")]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc, Arc,
+};
+#[cfg(feature = "batch-retention")]
+use std::time::Duration;
 
+#[cfg(feature = "batch-retention")]
+use clap::ArgMatches;
 use clap::{App, Arg};
-#[cfg(feature = "diesel")]
+#[cfg(feature = "batch-submission")]
+use cylinder::{jwt::JsonWebTokenBuilder, load_key, secp256k1::Secp256k1Context, Context, Signer};
+#[cfg(all(
+    any(not(feature = "batch-submission"), feature = "health"),
+    feature = "diesel"
+))]
 use diesel::r2d2::{ConnectionManager, Pool};
 use flexi_logger::{DeferredNow, LogSpecBuilder, Logger};
+#[cfg(feature = "batch-status-wait")]
+use grid_sdk::batch_tracking::notifier::BatchStatusNotifier;
+#[cfg(feature = "batch-retention")]
+use grid_sdk::batch_tracking::retention::{RetentionPolicy, RetentionTask, RetentionTaskBuilder};
+#[cfg(feature = "batch-tracking-webhook")]
+use grid_sdk::batch_tracking::webhook::{WebhookDeliverer, WebhookDelivererBuilder};
+#[cfg(all(feature = "batch-submission", feature = "proxy"))]
+use grid_sdk::proxy::ProxyClient;
 #[cfg(feature = "proxy")]
 use grid_sdk::proxy::ReqwestProxyClient;
-#[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+#[cfg(feature = "batch-submission")]
+use grid_sdk::rest_api::actix_web_4::Endpoint;
+#[cfg(not(feature = "batch-submission"))]
+use grid_sdk::rest_api::actix_web_4::{self, KeyState, StoreState};
+#[cfg(feature = "event-stream")]
+use grid_sdk::rest_api::resources::event_stream::v1::EventBroadcaster;
+#[cfg(all(
+    feature = "health",
+    any(feature = "database-postgres", feature = "database-sqlite")
+))]
+use grid_sdk::rest_api::resources::health::v1::DatabaseHealthCheck;
+#[cfg(any(
+    feature = "batch-submission",
+    feature = "database-postgres",
+    feature = "database-sqlite"
+))]
 use grid_sdk::store::ConnectionUri;
+#[cfg(feature = "batch-submission")]
 use grid_sdk::{
-    batch_processor::submitter::{
-        BatchSubmitter, SawtoothBatchSubmitter, SawtoothConnection, SplinterBatchSubmitter,
+    batch_submission::submission::{
+        queue::BatchTrackingQueue,
+        rate_limit::RateLimit,
+        submitter::{batch_submitter::BatchSubmitterBuilder, RunnableSubmitter},
+        submitter_observer::batch_tracking_observer::BatchTrackingObserver,
+        url_resolver::basic_url_resolver::{GlobalUrlResolver, ScabbardUrlResolver},
     },
-    rest_api::actix_web_4::{self, Endpoint, KeyState, StoreState},
+    batch_submission::Submission,
+    batch_tracking::status_poller::{StatusNode, StatusPoller, StatusPollerBuilder},
+    batch_tracking::store::TrackingBatch,
+    scope_id::{GlobalScopeId, ScopeId, ServiceScopeId},
+    store::create_store_factory,
+    threading::lifecycle::ShutdownHandle,
 };
 use log::Record;
 use users::get_current_username;
 
 use error::Error;
+#[cfg(feature = "event-stream")]
+use rest_api::actix_web_4::EventStreamResourceProvider;
+#[cfg(feature = "health")]
+use rest_api::actix_web_4::HealthResourceProvider;
+#[cfg(feature = "batch-submission")]
+use rest_api::{
+    actix_web_4::{BatchTrackingResourceProvider, GriddleRestApi, GriddleRestApiBuilder},
+    Scope,
+};
 
 fn log_format(
     w: &mut dyn std::io::Write,
@@ -55,14 +118,20 @@ fn log_format(
     write!(w, "{}", record.args(),)
 }
 
-#[cfg(not(any(feature = "database-postgres", feature = "database-sqlite")))]
+#[cfg(all(
+    not(feature = "batch-submission"),
+    not(any(feature = "database-postgres", feature = "database-sqlite"))
+))]
 fn griddle_store_state(_db_url: &str) -> Result<StoreState, Error> {
     Err(Error::from_message(
         "no database feature was enabled during compilation",
     ))
 }
 
-#[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+#[cfg(all(
+    not(feature = "batch-submission"),
+    any(feature = "database-postgres", feature = "database-sqlite")
+))]
 fn griddle_store_state(db_url: &str) -> Result<StoreState, Error> {
     let connection_url =
         ConnectionUri::from_str(db_url).map_err(|err| Error::from_message(&format!("{}", err)))?;
@@ -91,13 +160,436 @@ fn griddle_store_state(db_url: &str) -> Result<StoreState, Error> {
     })
 }
 
-fn batch_submitter(endpoint: Endpoint) -> Arc<dyn BatchSubmitter> {
+/// Returns the database check of the readiness endpoint, which connects to the database with a
+/// pool of its own so that it is not held up by the batch submitter
+#[cfg(all(
+    feature = "health",
+    any(feature = "database-postgres", feature = "database-sqlite")
+))]
+fn database_health_check(connection_uri: &ConnectionUri, db_url: &str) -> DatabaseHealthCheck {
+    match connection_uri {
+        #[cfg(feature = "database-postgres")]
+        ConnectionUri::Postgres(_) => DatabaseHealthCheck::with_pg_pool(
+            Pool::builder()
+                .max_size(1)
+                .build_unchecked(ConnectionManager::<diesel::pg::PgConnection>::new(db_url)),
+        ),
+        #[cfg(feature = "database-sqlite")]
+        ConnectionUri::Sqlite(_) => {
+            DatabaseHealthCheck::with_sqlite_pool(Pool::builder().max_size(1).build_unchecked(
+                ConnectionManager::<diesel::sqlite::SqliteConnection>::new(db_url),
+            ))
+        }
+    }
+}
+
+/// Loads the signing key with the given base name from the key directory
+#[cfg(feature = "batch-submission")]
+fn load_signer(key: &str, key_dir: &str) -> Result<Box<dyn Signer>, Error> {
+    let private_key = load_key(key, &[PathBuf::from(key_dir)])
+        .map_err(|err| Error::from_message(&format!("Unable to load signing key: {}", err)))?
+        .ok_or_else(|| Error::from_message("Signing key not found"))?;
+    Ok(Secp256k1Context::new().new_signer(private_key))
+}
+
+/// Returns the node the statuses of submitted batches are polled from. A Splinter node is sent a
+/// token signed with griddle's key.
+#[cfg(feature = "batch-submission")]
+fn status_node(endpoint: &Endpoint, signer: &dyn Signer) -> Result<StatusNode, Error> {
+    if endpoint.is_sawtooth() {
+        return Ok(StatusNode::Sawtooth {
+            url: endpoint.url(),
+        });
+    }
+
+    let jwt = JsonWebTokenBuilder::new().build(signer).map_err(|err| {
+        Error::from_message(&format!("Unable to build authorization token: {}", err))
+    })?;
+    Ok(StatusNode::Splinter {
+        url: endpoint.url(),
+        authorization: Some(format!("Bearer Cylinder:{}", jwt)),
+    })
+}
+
+/// Runs the Griddle REST API alongside a batch submitter which submits the batches recorded in
+/// the batch tracking store to the connected Sawtooth or Splinter node, until Ctrl-C is received.
+#[cfg(feature = "batch-submission")]
+#[allow(clippy::too_many_arguments)]
+async fn run_batch_submission(
+    bind: String,
+    database_url: &str,
+    signer: Box<dyn Signer>,
+    admin_keys: Vec<String>,
+    endpoint: Endpoint,
+    #[cfg(feature = "proxy")] proxy_client: Box<dyn ProxyClient>,
+    #[cfg(feature = "batch-retention")] retention: (RetentionPolicy, u64),
+    rate_limits: (Option<RateLimit>, Vec<(String, RateLimit)>),
+) -> Result<(), Error> {
+    let connection_uri = ConnectionUri::from_str(database_url)
+        .map_err(|err| Error::from_message(&format!("{}", err)))?;
+    let store_factory = create_store_factory(&connection_uri)
+        .map_err(|err| Error::from_message(&format!("{}", err)))?;
+
+    let scope = if endpoint.is_sawtooth() {
+        Scope::Global
+    } else {
+        Scope::Service
+    };
+
+    #[cfg(feature = "batch-status-wait")]
+    let status_notifier = BatchStatusNotifier::new();
+
+    let status_node = status_node(&endpoint, &*signer)?;
+
+    #[allow(unused_mut)]
+    let mut batch_tracking_resources =
+        BatchTrackingResourceProvider::new(store_factory.clone_box()).with_admin_keys(admin_keys);
+    #[cfg(feature = "batch-status-wait")]
+    {
+        batch_tracking_resources =
+            batch_tracking_resources.with_status_notifier(status_notifier.clone());
+    }
+
+    #[allow(unused_mut)]
+    let mut rest_api_builder = GriddleRestApiBuilder::new()
+        .with_bind(bind)
+        .with_signer(signer)
+        .with_scope(scope)
+        .add_resource_provider(Box::new(batch_tracking_resources));
+
+    #[cfg(feature = "event-stream")]
+    let event_broadcaster = EventBroadcaster::new();
+
+    #[cfg(feature = "event-stream")]
+    {
+        rest_api_builder = rest_api_builder.add_resource_provider(Box::new(
+            EventStreamResourceProvider::new(event_broadcaster.clone()),
+        ));
+    }
+
+    #[cfg(feature = "health")]
+    {
+        rest_api_builder =
+            rest_api_builder.add_resource_provider(Box::new(HealthResourceProvider::new(
+                store_factory.clone_box(),
+                #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+                database_health_check(&connection_uri, database_url),
+                endpoint.clone(),
+            )));
+    }
+
+    #[cfg(feature = "proxy")]
+    {
+        rest_api_builder = rest_api_builder.with_proxy_client(proxy_client);
+    }
+
+    let rest_api = rest_api_builder
+        .build()
+        .and_then(|runnable| runnable.run())
+        .map_err(|err| Error::from_message(&format!("{}", err)))?;
+
+    #[allow(unused_mut)]
+    let mut status_poller_builder =
+        StatusPollerBuilder::new(store_factory.clone_box(), status_node);
+    #[cfg(feature = "event-stream")]
+    {
+        status_poller_builder =
+            status_poller_builder.with_event_broadcaster(event_broadcaster.clone());
+    }
+    #[cfg(feature = "batch-status-wait")]
+    {
+        status_poller_builder = status_poller_builder.with_status_notifier(status_notifier.clone());
+    }
+    let status_poller = status_poller_builder.start().map_err(|err| {
+        Error::from_message(&format!("Unable to start batch status poller: {}", err))
+    })?;
+
+    #[cfg(feature = "batch-tracking-webhook")]
+    let webhook_deliverer = WebhookDelivererBuilder::new(store_factory.clone_box())
+        .start()
+        .map_err(|err| {
+            Error::from_message(&format!("Unable to start webhook deliverer: {}", err))
+        })?;
+
+    #[cfg(feature = "batch-retention")]
+    let retention_task = {
+        let (policy, interval) = retention;
+        if policy.is_empty() {
+            None
+        } else {
+            info!(
+                "Applying batch retention policy every {}s: {:?}",
+                interval, policy
+            );
+            Some(
+                RetentionTaskBuilder::new(store_factory.clone_box(), policy)
+                    .with_interval(interval)
+                    .start()
+                    .map_err(|err| {
+                        Error::from_message(&format!("Unable to start batch retention: {}", err))
+                    })?,
+            )
+        }
+    };
+
     if endpoint.is_sawtooth() {
-        let connection = SawtoothConnection::new(&endpoint.url());
-        Arc::new(SawtoothBatchSubmitter::new(connection.get_sender()))
+        #[allow(unused_mut)]
+        let mut observer = BatchTrackingObserver::new(store_factory.clone_box());
+        #[cfg(feature = "event-stream")]
+        {
+            observer = observer.with_event_broadcaster(event_broadcaster);
+        }
+        #[cfg(feature = "batch-status-wait")]
+        {
+            observer = observer.with_status_notifier(status_notifier);
+        }
+
+        let submitter = BatchSubmitterBuilder::<GlobalScopeId>::new()
+            .with_url_resolver(Arc::new(GlobalUrlResolver::new(format!(
+                "{}/batches",
+                endpoint.url()
+            ))))
+            .with_queue(Box::new(limit_queue(
+                BatchTrackingQueue::new(store_factory.clone_box()),
+                &rate_limits,
+            )))
+            .with_observer(Box::new(observer));
+
+        run_until_shutdown(
+            rest_api,
+            submitter,
+            status_poller,
+            #[cfg(feature = "batch-tracking-webhook")]
+            webhook_deliverer,
+            #[cfg(feature = "batch-retention")]
+            retention_task,
+        )
+        .await
     } else {
-        Arc::new(SplinterBatchSubmitter::new(&endpoint.url()))
+        #[allow(unused_mut)]
+        let mut observer = BatchTrackingObserver::new(store_factory.clone_box());
+        #[cfg(feature = "event-stream")]
+        {
+            observer = observer.with_event_broadcaster(event_broadcaster);
+        }
+        #[cfg(feature = "batch-status-wait")]
+        {
+            observer = observer.with_status_notifier(status_notifier);
+        }
+
+        let submitter = BatchSubmitterBuilder::<ServiceScopeId>::new()
+            .with_url_resolver(Arc::new(ScabbardUrlResolver::new(endpoint.url())))
+            .with_queue(Box::new(limit_queue(
+                BatchTrackingQueue::new(store_factory.clone_box()),
+                &rate_limits,
+            )))
+            .with_observer(Box::new(observer));
+
+        run_until_shutdown(
+            rest_api,
+            submitter,
+            status_poller,
+            #[cfg(feature = "batch-tracking-webhook")]
+            webhook_deliverer,
+            #[cfg(feature = "batch-retention")]
+            retention_task,
+        )
+        .await
+    }
+}
+
+#[cfg(feature = "batch-submission")]
+async fn run_until_shutdown<S: 'static + ScopeId>(
+    mut rest_api: GriddleRestApi,
+    submitter_builder: BatchSubmitterBuilder<S>,
+    mut status_poller: StatusPoller,
+    #[cfg(feature = "batch-tracking-webhook")] mut webhook_deliverer: WebhookDeliverer,
+    #[cfg(feature = "batch-retention")] mut retention_task: Option<RetentionTask>,
+) -> Result<(), Error> {
+    let mut submitter = submitter_builder
+        .build()
+        .and_then(|runnable| runnable.run())
+        .map_err(|err| Error::from_message(&format!("Unable to start batch submitter: {}", err)))?;
+
+    let (shutdown_tx, shutdown_rx) = mpsc::channel();
+    let ctrlc_triggered = AtomicBool::new(false);
+    ctrlc::set_handler(move || {
+        if ctrlc_triggered.load(Ordering::SeqCst) {
+            eprintln!("Aborting due to multiple Ctrl-C events");
+            std::process::exit(1);
+        }
+
+        ctrlc_triggered.store(true, Ordering::SeqCst);
+
+        if shutdown_tx.send(()).is_err() {
+            error!("Unable to signal shutdown");
+        }
+    })
+    .map_err(|err| Error::from_message(&format!("{}", err)))?;
+
+    // Wait for Ctrl-C on a blocking thread, as this runs on the runtime of `main`
+    let _ = actix_web::web::block(move || shutdown_rx.recv()).await;
+
+    rest_api.signal_shutdown();
+    submitter.signal_shutdown();
+    status_poller.signal_shutdown();
+    #[cfg(feature = "batch-tracking-webhook")]
+    webhook_deliverer.signal_shutdown();
+    #[cfg(feature = "batch-retention")]
+    if let Some(retention_task) = retention_task.as_mut() {
+        retention_task.signal_shutdown();
+    }
+
+    let rest_api_result = rest_api
+        .wait_for_shutdown()
+        .map_err(|err| Error::from_message(&format!("Unable to shutdown REST API: {}", err)));
+    let submitter_result = submitter
+        .wait_for_shutdown()
+        .map_err(|err| Error::from_message(&format!("Unable to shutdown batch submitter: {}", err)))
+        .and(status_poller.wait_for_shutdown().map_err(|err| {
+            Error::from_message(&format!("Unable to shutdown batch status poller: {}", err))
+        }));
+
+    #[cfg(feature = "batch-tracking-webhook")]
+    let submitter_result =
+        submitter_result.and(webhook_deliverer.wait_for_shutdown().map_err(|err| {
+            Error::from_message(&format!("Unable to shutdown webhook deliverer: {}", err))
+        }));
+
+    #[cfg(feature = "batch-retention")]
+    let submitter_result = match retention_task {
+        Some(retention_task) => {
+            let metrics = retention_task.metrics();
+            info!(
+                "Batch retention removed {} committed and {} invalid batches and compacted {} \
+                batches in {} passes ({} failed)",
+                metrics.committed_batches_purged(),
+                metrics.invalid_batches_purged(),
+                metrics.batches_compacted(),
+                metrics.passes(),
+                metrics.failed_passes(),
+            );
+            submitter_result.and(retention_task.wait_for_shutdown().map_err(|err| {
+                Error::from_message(&format!("Unable to shutdown batch retention: {}", err))
+            }))
+        }
+        None => submitter_result,
+    };
+
+    rest_api_result.and(submitter_result)
+}
+
+/// Applies the submission rate limits to a queue
+#[cfg(feature = "batch-submission")]
+fn limit_queue<S: ScopeId>(
+    mut queue: BatchTrackingQueue<S>,
+    rate_limits: &(Option<RateLimit>, Vec<(String, RateLimit)>),
+) -> BatchTrackingQueue<S>
+where
+    Submission<S>: TryFrom<TrackingBatch>,
+{
+    let (rate_limit, service_rate_limits) = rate_limits;
+    if let Some(rate_limit) = rate_limit {
+        queue = queue.with_rate_limit(*rate_limit);
+    }
+    for (service_id, rate_limit) in service_rate_limits {
+        queue = queue.with_service_rate_limit(service_id, *rate_limit);
+    }
+    queue
+}
+
+/// Reads the limit on batches submitted per second to each service and the limits for specific
+/// services
+#[cfg(feature = "batch-submission")]
+fn rate_limit_config(
+    matches: &ArgMatches,
+) -> Result<(Option<RateLimit>, Vec<(String, RateLimit)>), Error> {
+    let per_second = |value: &str| -> Result<RateLimit, Error> {
+        value
+            .parse::<u32>()
+            .map_err(|_| {
+                Error::from_message(&format!(
+                    "rate limit must be a number of batches per second, found '{}'",
+                    value
+                ))
+            })
+            .and_then(|batches| {
+                RateLimit::per_second(batches)
+                    .map_err(|err| Error::from_message(&format!("Invalid rate limit: {}", err)))
+            })
+    };
+
+    let rate_limit = matches
+        .value_of("submission_rate_limit")
+        .map(String::from)
+        .or_else(|| env::var("GRIDDLE_SUBMISSION_RATE_LIMIT").ok())
+        .map(|value| per_second(&value))
+        .transpose()?;
+
+    let service_values = match matches.values_of("service_rate_limit") {
+        Some(values) => values.map(String::from).collect::<Vec<_>>(),
+        None => env::var("GRIDDLE_SERVICE_RATE_LIMITS")
+            .map(|value| value.split(',').map(String::from).collect())
+            .unwrap_or_default(),
+    };
+
+    let service_rate_limits = service_values
+        .iter()
+        .filter(|value| !value.is_empty())
+        .map(|value| match value.split_once('=') {
+            Some((service_id, batches)) if !service_id.is_empty() => {
+                Ok((service_id.to_string(), per_second(batches)?))
+            }
+            _ => Err(Error::from_message(&format!(
+                "service rate limit must be of the form SERVICE_ID=BATCHES, found '{}'",
+                value
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((rate_limit, service_rate_limits))
+}
+
+/// Reads the batch retention policy and the interval at which it is applied, in seconds
+#[cfg(feature = "batch-retention")]
+fn retention_config(matches: &ArgMatches) -> Result<(RetentionPolicy, u64), Error> {
+    let seconds = |arg: &str, var: &str| -> Result<Option<u64>, Error> {
+        matches
+            .value_of(arg)
+            .map(String::from)
+            .or_else(|| env::var(var).ok())
+            .map(|value| {
+                value.parse::<u64>().map_err(|_| {
+                    Error::from_message(&format!(
+                        "{} must be a number of seconds, found '{}'",
+                        arg, value
+                    ))
+                })
+            })
+            .transpose()
+    };
+
+    let mut policy = RetentionPolicy::new();
+    if let Some(age) = seconds(
+        "batch_retention_committed",
+        "GRIDDLE_BATCH_RETENTION_COMMITTED",
+    )? {
+        policy = policy.with_committed_max_age(Duration::from_secs(age));
+    }
+    if let Some(age) = seconds("batch_retention_invalid", "GRIDDLE_BATCH_RETENTION_INVALID")? {
+        policy = policy.with_invalid_max_age(Duration::from_secs(age));
     }
+    if let Some(age) = seconds("batch_compaction_age", "GRIDDLE_BATCH_COMPACTION_AGE")? {
+        policy = policy.with_compaction_age(Duration::from_secs(age));
+    }
+
+    let interval = seconds(
+        "batch_retention_interval",
+        "GRIDDLE_BATCH_RETENTION_INTERVAL",
+    )?
+    .unwrap_or(3600);
+
+    Ok((policy, interval))
 }
 
 async fn run() -> Result<(), Error> {
@@ -149,6 +641,30 @@ async fn run() -> Result<(), Error> {
                 .help("Base name for private signing key file"),
         );
 
+    #[cfg(feature = "batch-submission")]
+    {
+        app = app
+            .arg(
+                Arg::with_name("key_dir")
+                    .long("key-dir")
+                    .takes_value(true)
+                    .value_name("DIR")
+                    .help("Directory containing the private signing key file"),
+            )
+            .arg(
+                Arg::with_name("admin_key")
+                    .long("admin-key")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .value_name("PUBLIC_KEY")
+                    .help(
+                        "Public key that may replay or discard dead-lettered batches signed by \
+                        other keys",
+                    ),
+            );
+    }
+
     #[cfg(feature = "proxy")]
     {
         app = app.arg(
@@ -159,6 +675,69 @@ async fn run() -> Result<(), Error> {
         );
     }
 
+    #[cfg(feature = "batch-submission")]
+    {
+        app = app
+            .arg(
+                Arg::with_name("submission_rate_limit")
+                    .long("submission-rate-limit")
+                    .takes_value(true)
+                    .value_name("BATCHES")
+                    .help("Maximum number of batches submitted per second to each service"),
+            )
+            .arg(
+                Arg::with_name("service_rate_limit")
+                    .long("service-rate-limit")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .value_name("SERVICE_ID=BATCHES")
+                    .help(
+                        "Maximum number of batches submitted per second to a service, in place \
+                        of --submission-rate-limit",
+                    ),
+            );
+    }
+
+    #[cfg(feature = "batch-retention")]
+    {
+        app = app
+            .arg(
+                Arg::with_name("batch_retention_committed")
+                    .long("batch-retention-committed")
+                    .takes_value(true)
+                    .value_name("SECONDS")
+                    .help("Age after which committed batches are removed; kept if unset"),
+            )
+            .arg(
+                Arg::with_name("batch_retention_invalid")
+                    .long("batch-retention-invalid")
+                    .takes_value(true)
+                    .value_name("SECONDS")
+                    .help(
+                        "Age after which invalid batches are removed; kept if unset, so they \
+                        can be kept longer than committed batches",
+                    ),
+            )
+            .arg(
+                Arg::with_name("batch_compaction_age")
+                    .long("batch-compaction-age")
+                    .takes_value(true)
+                    .value_name("SECONDS")
+                    .help(
+                        "Age after which the payloads of committed and invalid batches are \
+                        dropped, keeping their status and metadata",
+                    ),
+            )
+            .arg(
+                Arg::with_name("batch_retention_interval")
+                    .long("batch-retention-interval")
+                    .takes_value(true)
+                    .value_name("SECONDS")
+                    .help("Interval at which the batch retention policy is applied"),
+            );
+    }
+
     let matches = app.get_matches();
 
     let log_level = if matches.is_present("quiet") {
@@ -193,6 +772,28 @@ async fn run() -> Result<(), Error> {
         .or_else(|| env::var("GRIDDLE_BIND").ok())
         .unwrap_or_else(|| "localhost:8000".into());
 
+    #[cfg(feature = "batch-submission")]
+    let key_dir = matches
+        .value_of("key_dir")
+        .map(String::from)
+        .or_else(|| env::var("GRIDDLE_SIGNING_KEY_DIR").ok())
+        .unwrap_or_else(|| "/etc/grid/keys".into());
+
+    #[cfg(feature = "batch-submission")]
+    let admin_keys = match matches.values_of("admin_key") {
+        Some(values) => values.map(String::from).collect::<Vec<_>>(),
+        None => env::var("GRIDDLE_ADMIN_KEYS")
+            .map(|value| {
+                value
+                    .split(',')
+                    .filter(|key| !key.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    #[cfg(feature = "batch-submission")]
     let connect = matches
         .value_of("connect")
         .map(String::from)
@@ -216,15 +817,26 @@ async fn run() -> Result<(), Error> {
     let client = ReqwestProxyClient::new(&forward_url)
         .map_err(|err| Error::from_message(&format!("Unable to create proxy client: {err}")))?;
 
-    let store_state = griddle_store_state(&database_url)?;
-    let key_state = KeyState::new(&key);
-
-    let _batch_submitter = batch_submitter(Endpoint::from(connect.as_ref()));
+    #[cfg(feature = "batch-submission")]
+    run_batch_submission(
+        bind,
+        &database_url,
+        load_signer(&key, &key_dir)?,
+        admin_keys,
+        Endpoint::from(connect.as_ref()),
+        #[cfg(feature = "proxy")]
+        Box::new(client),
+        #[cfg(feature = "batch-retention")]
+        retention_config(&matches)?,
+        rate_limit_config(&matches)?,
+    )
+    .await?;
 
+    #[cfg(not(feature = "batch-submission"))]
     actix_web_4::run(
         &bind,
-        store_state,
-        key_state,
+        griddle_store_state(&database_url)?,
+        KeyState::new(&key),
         #[cfg(feature = "proxy")]
         Box::new(client),
     )
@@ -0,0 +1,460 @@
+// Copyright 2018-2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains the batch submission, batch status and dead letter resources, backed by a
+//! `BatchTrackingStore`
+
+use std::collections::HashMap;
+#[cfg(feature = "batch-status-wait")]
+use std::time::Duration;
+
+use actix_web::{http::StatusCode, web, HttpRequest, HttpResponse, Resource};
+use cylinder::{jwt::JsonWebTokenParser, secp256k1::Secp256k1Context, Context};
+
+#[cfg(feature = "batch-status-wait")]
+use grid_sdk::batch_tracking::notifier::BatchStatusNotifier;
+use grid_sdk::{
+    rest_api::resources::{batch_tracking::v1, error::ErrorResponse},
+    store::TransactionalStoreFactory,
+};
+
+use crate::rest_api::{actix_web_4::GriddleResourceProvider, Scope};
+
+/// The public keys that may replay or discard dead-lettered batches signed by other keys
+#[derive(Clone, Default)]
+struct AdminKeys(Vec<String>);
+
+/// The longest time a `/batch_statuses` request waits for its batches
+#[cfg(feature = "batch-status-wait")]
+const MAX_WAIT: Duration = Duration::from_secs(300);
+
+/// Provides the `/batches`, `/batch_statuses` and `/batches/dead-letter` resources.
+///
+/// Submitted batches are persisted in the batch tracking store, from which they are picked up by
+/// the batch submitter. Batches that could not be submitted are listed under
+/// `/batches/dead-letter`, where they can be replayed or discarded. Replaying or discarding a
+/// batch requires a Cylinder JWT, which identifies the user in the audit record of the action.
+/// Only the batch's signer, or one of the admin keys, may replay or discard it.
+///
+/// With the `batch-tracking-webhook` feature, submitters may also subscribe webhooks to status
+/// changes of the batches they signed under `/webhooks`, identifying themselves with a Cylinder
+/// JWT.
+pub struct BatchTrackingResourceProvider {
+    store_factory: Box<dyn TransactionalStoreFactory>,
+    admin_keys: AdminKeys,
+    #[cfg(feature = "batch-status-wait")]
+    status_notifier: BatchStatusNotifier,
+}
+
+impl BatchTrackingResourceProvider {
+    pub fn new(store_factory: Box<dyn TransactionalStoreFactory>) -> Self {
+        Self {
+            store_factory,
+            admin_keys: AdminKeys::default(),
+            #[cfg(feature = "batch-status-wait")]
+            status_notifier: BatchStatusNotifier::new(),
+        }
+    }
+
+    /// Sets the public keys that may replay or discard dead-lettered batches signed by others
+    pub fn with_admin_keys(mut self, admin_keys: Vec<String>) -> Self {
+        self.admin_keys = AdminKeys(admin_keys);
+        self
+    }
+
+    /// Sets the notifier that wakes `/batch_statuses` requests waiting on batch status changes
+    #[cfg(feature = "batch-status-wait")]
+    pub fn with_status_notifier(mut self, status_notifier: BatchStatusNotifier) -> Self {
+        self.status_notifier = status_notifier;
+        self
+    }
+}
+
+impl GriddleResourceProvider for BatchTrackingResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        vec![
+            web::resource("/batches")
+                .app_data(web::Data::new(self.store_factory.clone_box()))
+                .route(web::post().to(submit_batches)),
+            {
+                let resource = web::resource("/batch_statuses")
+                    .name("get_batch_statuses")
+                    .app_data(web::Data::new(self.store_factory.clone_box()));
+                #[cfg(feature = "batch-status-wait")]
+                let resource = resource.app_data(web::Data::new(self.status_notifier.clone()));
+                resource.route(web::get().to(get_batch_statuses))
+            },
+            web::resource("/batches/dead-letter")
+                .app_data(web::Data::new(self.store_factory.clone_box()))
+                .route(web::get().to(list_dead_letter_batches)),
+            web::resource("/batches/dead-letter/{batch_id}/replay")
+                .app_data(web::Data::new(self.store_factory.clone_box()))
+                .app_data(web::Data::new(self.admin_keys.clone()))
+                .route(web::post().to(replay_dead_letter_batch)),
+            web::resource("/batches/dead-letter/{batch_id}/discard")
+                .app_data(web::Data::new(self.store_factory.clone_box()))
+                .app_data(web::Data::new(self.admin_keys.clone()))
+                .route(web::post().to(discard_dead_letter_batch)),
+            #[cfg(feature = "batch-tracking-webhook")]
+            web::resource("/webhooks")
+                .app_data(web::Data::new(self.store_factory.clone_box()))
+                .route(web::get().to(list_webhook_subscriptions))
+                .route(web::post().to(add_webhook_subscription)),
+            #[cfg(feature = "batch-tracking-webhook")]
+            web::resource("/webhooks/{id}")
+                .app_data(web::Data::new(self.store_factory.clone_box()))
+                .route(web::delete().to(remove_webhook_subscription)),
+            #[cfg(feature = "batch-tracking-webhook")]
+            web::resource("/webhooks/{id}/deliveries")
+                .app_data(web::Data::new(self.store_factory.clone_box()))
+                .route(web::get().to(list_webhook_deliveries)),
+        ]
+    }
+}
+
+/// Submits the batches in the request body
+///
+/// The `data_change_id` query parameter may give a comma-separated list of data change IDs, one
+/// for each batch. Resubmitting a data change ID reports the batch that was already submitted for
+/// it rather than adding a new one. The `priority` query parameter, one of `interactive`, `normal`
+/// or `bulk`, sets the order in which the batches are submitted relative to other queued batches.
+async fn submit_batches(
+    req: HttpRequest,
+    body: web::Bytes,
+    store_factory: web::Data<Box<dyn TransactionalStoreFactory>>,
+    scope: web::Data<Scope>,
+    query: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    let service_id = query.get("service_id").map(String::as_str);
+
+    if let Err(err) = check_scope(&scope, service_id) {
+        return error_response(err);
+    }
+
+    let response_url = match req.url_for_static("get_batch_statuses") {
+        Ok(response_url) => response_url,
+        Err(err) => return error_response(ErrorResponse::internal_error(Box::new(err))),
+    };
+
+    match v1::submit_batches(
+        response_url,
+        store_factory.get_batch_tracking_store(),
+        &body,
+        service_id,
+        query.get("data_change_id").map(String::as_str),
+        query.get("priority").map(String::as_str),
+    ) {
+        Ok(res) => HttpResponse::Accepted().json(res),
+        Err(err) => error_response(err),
+    }
+}
+
+/// Gets the statuses of the batches in the `id` query parameter
+///
+/// With the `wait` query parameter, set to a number of seconds, the response is held until none of
+/// the batches is pending or the time has passed.
+async fn get_batch_statuses(
+    req: HttpRequest,
+    store_factory: web::Data<Box<dyn TransactionalStoreFactory>>,
+    #[cfg(feature = "batch-status-wait")] status_notifier: web::Data<BatchStatusNotifier>,
+    scope: web::Data<Scope>,
+    query: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    let service_id = query.get("service_id").map(String::as_str);
+
+    if let Err(err) = check_scope(&scope, service_id) {
+        return error_response(err);
+    }
+
+    let ids = match query.get("id") {
+        Some(ids) => ids,
+        None => {
+            return error_response(ErrorResponse::new(
+                400,
+                "Request for statuses missing id query.",
+            ))
+        }
+    };
+
+    let link = format!("{}?{}", req.uri().path(), req.query_string());
+
+    #[cfg(feature = "batch-status-wait")]
+    {
+        let wait = match parse_wait(query.get("wait").map(String::as_str)) {
+            Ok(wait) => wait,
+            Err(err) => return error_response(err),
+        };
+
+        if let Some(wait) = wait {
+            return match v1::wait_for_batch_statuses(
+                link,
+                store_factory.get_batch_tracking_store(),
+                ids,
+                service_id,
+                wait,
+                &status_notifier,
+            )
+            .await
+            {
+                Ok(res) => HttpResponse::Ok().json(res),
+                Err(err) => error_response(err),
+            };
+        }
+    }
+
+    match v1::get_batch_statuses(
+        link,
+        store_factory.get_batch_tracking_store(),
+        ids,
+        service_id,
+    ) {
+        Ok(res) => HttpResponse::Ok().json(res),
+        Err(err) => error_response(err),
+    }
+}
+
+async fn list_dead_letter_batches(
+    store_factory: web::Data<Box<dyn TransactionalStoreFactory>>,
+    scope: web::Data<Scope>,
+    query: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    let service_id = query.get("service_id").map(String::as_str);
+
+    if let Err(err) = check_scope(&scope, service_id) {
+        return error_response(err);
+    }
+
+    match v1::list_dead_letter_batches(store_factory.get_batch_tracking_store(), service_id) {
+        Ok(res) => HttpResponse::Ok().json(res),
+        Err(err) => error_response(err),
+    }
+}
+
+/// Replays a dead-lettered batch. If the request has a body, it is a `BatchList` holding a copy of
+/// the batch that was re-signed with fresh nonces, which replaces the dead-lettered batch.
+async fn replay_dead_letter_batch(
+    req: HttpRequest,
+    body: web::Bytes,
+    batch_id: web::Path<String>,
+    store_factory: web::Data<Box<dyn TransactionalStoreFactory>>,
+    admin_keys: web::Data<AdminKeys>,
+    scope: web::Data<Scope>,
+    query: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    let service_id = query.get("service_id").map(String::as_str);
+
+    if let Err(err) = check_scope(&scope, service_id) {
+        return error_response(err);
+    }
+
+    let actor = match request_actor(&req) {
+        Ok(actor) => actor,
+        Err(err) => return error_response(err),
+    };
+
+    let replacement = if body.is_empty() {
+        None
+    } else {
+        Some(&body[..])
+    };
+
+    match v1::replay_dead_letter_batch(
+        store_factory.get_batch_tracking_store(),
+        &batch_id,
+        service_id,
+        &actor,
+        &admin_keys.0,
+        replacement,
+    ) {
+        Ok(res) => HttpResponse::Ok().json(res),
+        Err(err) => error_response(err),
+    }
+}
+
+async fn discard_dead_letter_batch(
+    req: HttpRequest,
+    batch_id: web::Path<String>,
+    store_factory: web::Data<Box<dyn TransactionalStoreFactory>>,
+    admin_keys: web::Data<AdminKeys>,
+    scope: web::Data<Scope>,
+    query: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    let service_id = query.get("service_id").map(String::as_str);
+
+    if let Err(err) = check_scope(&scope, service_id) {
+        return error_response(err);
+    }
+
+    let actor = match request_actor(&req) {
+        Ok(actor) => actor,
+        Err(err) => return error_response(err),
+    };
+
+    match v1::discard_dead_letter_batch(
+        store_factory.get_batch_tracking_store(),
+        &batch_id,
+        service_id,
+        &actor,
+        &admin_keys.0,
+    ) {
+        Ok(res) => HttpResponse::Ok().json(res),
+        Err(err) => error_response(err),
+    }
+}
+
+#[cfg(feature = "batch-tracking-webhook")]
+async fn add_webhook_subscription(
+    req: HttpRequest,
+    payload: web::Json<v1::NewWebhookSubscriptionPayload>,
+    store_factory: web::Data<Box<dyn TransactionalStoreFactory>>,
+) -> HttpResponse {
+    let actor = match request_actor(&req) {
+        Ok(actor) => actor,
+        Err(err) => return error_response(err),
+    };
+
+    match v1::add_webhook_subscription(
+        store_factory.get_batch_tracking_store(),
+        &actor,
+        payload.into_inner(),
+    ) {
+        Ok(res) => HttpResponse::Created().json(res),
+        Err(err) => error_response(err),
+    }
+}
+
+#[cfg(feature = "batch-tracking-webhook")]
+async fn list_webhook_subscriptions(
+    req: HttpRequest,
+    store_factory: web::Data<Box<dyn TransactionalStoreFactory>>,
+) -> HttpResponse {
+    let actor = match request_actor(&req) {
+        Ok(actor) => actor,
+        Err(err) => return error_response(err),
+    };
+
+    match v1::list_webhook_subscriptions(store_factory.get_batch_tracking_store(), &actor) {
+        Ok(res) => HttpResponse::Ok().json(res),
+        Err(err) => error_response(err),
+    }
+}
+
+#[cfg(feature = "batch-tracking-webhook")]
+async fn remove_webhook_subscription(
+    req: HttpRequest,
+    id: web::Path<i64>,
+    store_factory: web::Data<Box<dyn TransactionalStoreFactory>>,
+) -> HttpResponse {
+    let actor = match request_actor(&req) {
+        Ok(actor) => actor,
+        Err(err) => return error_response(err),
+    };
+
+    match v1::remove_webhook_subscription(
+        store_factory.get_batch_tracking_store(),
+        id.into_inner(),
+        &actor,
+    ) {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(err) => error_response(err),
+    }
+}
+
+#[cfg(feature = "batch-tracking-webhook")]
+async fn list_webhook_deliveries(
+    req: HttpRequest,
+    id: web::Path<i64>,
+    store_factory: web::Data<Box<dyn TransactionalStoreFactory>>,
+) -> HttpResponse {
+    let actor = match request_actor(&req) {
+        Ok(actor) => actor,
+        Err(err) => return error_response(err),
+    };
+
+    match v1::list_webhook_deliveries(
+        store_factory.get_batch_tracking_store(),
+        id.into_inner(),
+        &actor,
+    ) {
+        Ok(res) => HttpResponse::Ok().json(res),
+        Err(err) => error_response(err),
+    }
+}
+
+/// Parses the `wait` query parameter of `/batch_statuses`, which is either `false` or a number of
+/// seconds, capped at `MAX_WAIT`
+#[cfg(feature = "batch-status-wait")]
+fn parse_wait(wait: Option<&str>) -> Result<Option<Duration>, ErrorResponse> {
+    match wait {
+        None | Some("false") => Ok(None),
+        Some(wait) => wait
+            .parse::<u64>()
+            .map(|secs| Some(Duration::from_secs(secs).min(MAX_WAIT)))
+            .map_err(|_| {
+                ErrorResponse::new(
+                    400,
+                    &format!(
+                        "Query wait has invalid value {}. \
+                         It should set to false or a time in seconds to wait for the commit",
+                        wait
+                    ),
+                )
+            }),
+    }
+}
+
+/// Identifies the user making a request by the public key that signed the Cylinder JWT in its
+/// `Authorization` header
+fn request_actor(req: &HttpRequest) -> Result<String, ErrorResponse> {
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer Cylinder:"))
+        .ok_or_else(|| {
+            ErrorResponse::new(
+                401,
+                "Authorization header with a Cylinder JWT is required to identify the user",
+            )
+        })?;
+
+    let verifier = Secp256k1Context::new().new_verifier();
+    let jwt = JsonWebTokenParser::new(&*verifier)
+        .parse(token)
+        .map_err(|err| ErrorResponse::new(401, &format!("Invalid authorization: {}", err)))?;
+
+    Ok(jwt.issuer().as_hex())
+}
+
+/// Checks that a service ID is provided if, and only if, Griddle is running against a service
+fn check_scope(scope: &Scope, service_id: Option<&str>) -> Result<(), ErrorResponse> {
+    match (scope, service_id) {
+        (Scope::Global, Some(_)) => Err(ErrorResponse::new(
+            400,
+            "Service ID present, but Griddle is not running in a service-aware configuration",
+        )),
+        (Scope::Service, None) => Err(ErrorResponse::new(
+            400,
+            "Service ID is not present, but Griddle is running in a service-aware configuration",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn error_response(err: ErrorResponse) -> HttpResponse {
+    HttpResponse::build(
+        StatusCode::from_u16(err.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+    )
+    .json(err)
+}
@@ -0,0 +1,45 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains the event stream resource, which streams batch status changes as server-sent events
+
+use actix_web::{web, Resource};
+
+use grid_sdk::rest_api::{
+    actix_web_4::routes::stream_events, resources::event_stream::v1::EventBroadcaster,
+};
+
+use crate::rest_api::actix_web_4::GriddleResourceProvider;
+
+/// Provides the `/events` resource, which streams the changes published to an `EventBroadcaster`
+///
+/// Griddle publishes the status changes of the batches it submits, so clients can follow their
+/// batches without polling `/batch_statuses`.
+pub struct EventStreamResourceProvider {
+    event_broadcaster: EventBroadcaster,
+}
+
+impl EventStreamResourceProvider {
+    pub fn new(event_broadcaster: EventBroadcaster) -> Self {
+        Self { event_broadcaster }
+    }
+}
+
+impl GriddleResourceProvider for EventStreamResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        vec![web::resource("/events")
+            .app_data(web::Data::new(self.event_broadcaster.clone()))
+            .route(web::get().to(stream_events))]
+    }
+}
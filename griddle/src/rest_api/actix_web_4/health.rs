@@ -0,0 +1,286 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains the liveness and readiness resources, which are polled by container orchestrators
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use actix_web::{web, HttpResponse, Resource};
+
+#[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+use grid_sdk::rest_api::resources::health::v1::DatabaseHealthCheck;
+use grid_sdk::{
+    rest_api::{
+        actix_web_4::Endpoint,
+        resources::health::v1::{HealthCheck, HealthResponse},
+    },
+    store::TransactionalStoreFactory,
+};
+
+use crate::rest_api::actix_web_4::GriddleResourceProvider;
+
+/// How long the node is waited on, so that a readiness probe is answered before it times out
+const NODE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Provides the `/health/live` and `/health/ready` resources.
+///
+/// Griddle is live while it is answering requests. It is ready when its database can be reached
+/// and has been migrated, and the Sawtooth or Splinter node that batches are submitted to answers
+/// HTTP requests. The depth of the batch queue and the age of the oldest batch in it are reported,
+/// but a backlog does not make griddle unready, as it is cleared by submitting batches.
+pub struct HealthResourceProvider {
+    state: HealthState,
+}
+
+struct HealthState {
+    store_factory: Box<dyn TransactionalStoreFactory>,
+    #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+    database_check: DatabaseHealthCheck,
+    endpoint: Endpoint,
+}
+
+impl HealthResourceProvider {
+    pub fn new(
+        store_factory: Box<dyn TransactionalStoreFactory>,
+        #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+        database_check: DatabaseHealthCheck,
+        endpoint: Endpoint,
+    ) -> Self {
+        Self {
+            state: HealthState {
+                store_factory,
+                #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+                database_check,
+                endpoint,
+            },
+        }
+    }
+}
+
+impl Clone for HealthState {
+    fn clone(&self) -> Self {
+        Self {
+            store_factory: self.store_factory.clone_box(),
+            #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+            database_check: self.database_check.clone(),
+            endpoint: self.endpoint.clone(),
+        }
+    }
+}
+
+impl GriddleResourceProvider for HealthResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        vec![
+            web::resource("/health/live").route(web::get().to(get_liveness)),
+            web::resource("/health/ready")
+                .app_data(web::Data::new(self.state.clone()))
+                .route(web::get().to(get_readiness)),
+        ]
+    }
+}
+
+async fn get_liveness() -> HttpResponse {
+    HttpResponse::Ok().json(HealthResponse::live())
+}
+
+/// Runs each check, responding with `503 Service Unavailable` if any of them fails
+async fn get_readiness(state: web::Data<HealthState>) -> HttpResponse {
+    let state = state.get_ref().clone();
+    // The checks block on the database and the node
+    let response = match web::block(move || {
+        HealthResponse::from_checks(vec![
+            #[cfg(any(feature = "database-postgres", feature = "database-sqlite"))]
+            state.database_check.check(),
+            check_batch_queue(&*state.store_factory),
+            check_node(&state.endpoint),
+        ])
+    })
+    .await
+    {
+        Ok(response) => response,
+        Err(err) => HealthResponse::from_checks(vec![HealthCheck::fail(
+            "readiness",
+            &format!("Unable to run readiness checks: {}", err),
+        )]),
+    };
+
+    if response.passed() {
+        HttpResponse::Ok().json(response)
+    } else {
+        HttpResponse::ServiceUnavailable().json(response)
+    }
+}
+
+/// Reports the number of batches waiting to be submitted and, in seconds, how long the oldest of
+/// them has waited
+fn check_batch_queue(store_factory: &dyn TransactionalStoreFactory) -> HealthCheck {
+    match store_factory.get_batch_tracking_store().get_queue_summary() {
+        Ok(summary) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or(0);
+
+            HealthCheck::pass("batch_queue")
+                .with_detail("depth", summary.depth())
+                .with_detail(
+                    "oldest_pending_age",
+                    summary
+                        .oldest_created_at()
+                        .map(|created_at| (now - created_at).max(0)),
+                )
+        }
+        Err(err) => HealthCheck::fail(
+            "batch_queue",
+            &format!("Unable to get the batch queue: {}", err),
+        ),
+    }
+}
+
+/// Checks that the node batches are submitted to can be reached. Any HTTP response will do, as
+/// the node may require authorization that griddle does not have.
+fn check_node(endpoint: &Endpoint) -> HealthCheck {
+    let name = if endpoint.is_sawtooth() {
+        "sawtooth"
+    } else {
+        "splinter"
+    };
+
+    let response = reqwest::blocking::Client::builder()
+        .timeout(NODE_TIMEOUT)
+        .build()
+        .and_then(|client| client.get(&format!("{}/status", endpoint.url())).send());
+
+    match response {
+        Ok(response) if response.status().is_server_error() => HealthCheck::fail(
+            name,
+            &format!("{} responded with {}", endpoint.url(), response.status()),
+        )
+        .with_detail("url", endpoint.url()),
+        Ok(_) => HealthCheck::pass(name).with_detail("url", endpoint.url()),
+        Err(err) => HealthCheck::fail(
+            name,
+            &format!("Unable to reach {}: {}", endpoint.url(), err),
+        )
+        .with_detail("url", endpoint.url()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[cfg(feature = "database-sqlite")]
+    use diesel::{
+        r2d2::{ConnectionManager, Pool},
+        sqlite::SqliteConnection,
+    };
+    #[cfg(feature = "database-sqlite")]
+    use grid_sdk::{migrations::run_sqlite_migrations, store::sqlite::SqliteStoreFactory};
+
+    /// Verify that the `batch_queue` check reports an empty queue as having no oldest batch, and
+    /// fails if the queue cannot be read.
+    #[cfg(feature = "database-sqlite")]
+    #[test]
+    fn batch_queue_check() {
+        let pool = create_connection_pool();
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        let check = check_batch_queue(&SqliteStoreFactory::new(pool));
+        assert!(check.passed());
+        assert_eq!(
+            check.details.get("depth").and_then(|depth| depth.as_i64()),
+            Some(0)
+        );
+        assert!(check
+            .details
+            .get("oldest_pending_age")
+            .map(|age| age.is_null())
+            .unwrap_or(false));
+
+        // The batch tracking tables are missing from a database that has not been migrated
+        let check = check_batch_queue(&SqliteStoreFactory::new(create_connection_pool()));
+        assert!(!check.passed());
+        assert!(check
+            .error
+            .expect("Failed check has no error")
+            .starts_with("Unable to get the batch queue"));
+    }
+
+    /// Verify that the node check passes when the node answers, even if it refuses the request,
+    /// and fails when the node responds with a server error or cannot be reached.
+    #[test]
+    fn node_check() {
+        let endpoint = Endpoint::from(respond_once("401 Unauthorized").as_str());
+        let check = check_node(&endpoint);
+        assert_eq!(check.name, "sawtooth");
+        assert!(check.passed());
+
+        let endpoint = Endpoint::from(
+            format!("splinter:{}", respond_once("500 Internal Server Error")).as_str(),
+        );
+        let check = check_node(&endpoint);
+        assert_eq!(check.name, "splinter");
+        assert!(!check.passed());
+
+        // Nothing listens on a port once its listener is dropped
+        let url = {
+            let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+            format!(
+                "http://{}",
+                listener.local_addr().expect("Failed to get address")
+            )
+        };
+        let check = check_node(&Endpoint::from(url.as_str()));
+        assert!(!check.passed());
+        assert_eq!(
+            check.details.get("url").and_then(|url| url.as_str()),
+            Some(url.as_str())
+        );
+    }
+
+    /// Starts a server that answers a single request with the given status, returning its URL
+    fn respond_once(status: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+        let url = format!(
+            "http://{}",
+            listener.local_addr().expect("Failed to get address")
+        );
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            status
+        );
+        thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        url
+    }
+
+    #[cfg(feature = "database-sqlite")]
+    fn create_connection_pool() -> Pool<ConnectionManager<SqliteConnection>> {
+        Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::<SqliteConnection>::new(":memory:"))
+            .expect("Failed to build connection pool")
+    }
+}
@@ -16,11 +16,23 @@
 //! backed by [Actix Web 4](https://crates.io/crates/actix-web).
 
 mod api;
+#[cfg(feature = "batch-submission")]
+mod batch_tracking;
 mod builder;
+#[cfg(feature = "event-stream")]
+mod event_stream;
+#[cfg(feature = "health")]
+mod health;
 mod runnable;
 
 pub use api::GriddleRestApi;
+#[cfg(feature = "batch-submission")]
+pub use batch_tracking::BatchTrackingResourceProvider;
 pub use builder::GriddleRestApiBuilder;
+#[cfg(feature = "event-stream")]
+pub use event_stream::EventStreamResourceProvider;
+#[cfg(feature = "health")]
+pub use health::HealthResourceProvider;
 pub use runnable::RunnableGriddleRestApi;
 
 use actix_web::Resource;
@@ -27,11 +27,17 @@ use std::path::Path;
 use protoc_rust::Customize;
 
 fn main() {
+    let out_dir = env::var("OUT_DIR").expect("No OUT_DIR env variable");
+
+    // Record the versions of the embedded migrations, so that pending migrations can be found
+    for backend in &["postgres", "sqlite"] {
+        write_migration_versions(&out_dir, backend);
+    }
+
     // Generate protobuf files
     let proto_src_files = glob_simple("protos/*.proto");
     println!("{:?}", proto_src_files);
 
-    let out_dir = env::var("OUT_DIR").expect("No OUT_DIR env variable");
     let dest_path = Path::new(&out_dir).join("protos");
     fs::create_dir_all(&dest_path).expect("Unable to create proto destination directory");
 
@@ -70,6 +76,43 @@ fn main() {
         .expect("unable to run protoc");
 }
 
+/// Writes the versions of a backend's migrations, as diesel names them, to a file that defines
+/// `MIGRATION_VERSIONS`
+fn write_migration_versions(out_dir: &str, backend: &str) {
+    let migrations_dir = format!("src/migrations/diesel/{}/migrations", backend);
+
+    let mut versions = fs::read_dir(&migrations_dir)
+        .expect("Unable to read migrations directory")
+        .map(|entry| entry.expect("Unable to read migrations directory").path())
+        .filter(|path| path.join("up.sql").is_file())
+        .map(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split('_').next())
+                .expect("Invalid migration name")
+                .replace('-', "")
+        })
+        .collect::<Vec<_>>();
+    versions.sort();
+
+    let content = format!(
+        "/// The versions of the embedded migrations, in the order they are applied\n\
+         const MIGRATION_VERSIONS: &[&str] = &[{}];\n",
+        versions
+            .iter()
+            .map(|version| format!("\"{}\"", version))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let dest_path = Path::new(out_dir).join("migrations");
+    fs::create_dir_all(&dest_path).expect("Unable to create migrations destination directory");
+    let mut file = File::create(dest_path.join(format!("{}_versions.rs", backend)))
+        .expect("Unable to create migration versions file");
+    file.write_all(content.as_bytes())
+        .expect("Unable to write migration versions file");
+}
+
 fn glob_simple(pattern: &str) -> Vec<String> {
     glob::glob(pattern)
         .expect("glob")
@@ -12,7 +12,6 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-mod pacemaker;
 pub mod submitter;
 
 use std::{
@@ -30,6 +29,7 @@ use crate::error::InternalError;
 use crate::hex;
 
 use crate::store::TransactionalStoreFactory;
+use crate::threading::pacemaker;
 
 use submitter::{BatchSubmitter, BatchSubmitterError, SubmitBatches};
 
@@ -14,10 +14,11 @@
 
 pub mod submission;
 
-use std::convert::From;
+use std::convert::{From, TryFrom};
 
 use crate::{
-    batch_tracking::store::{GlobalTrackingBatch, ServiceTrackingBatch},
+    batch_tracking::store::{GlobalTrackingBatch, ServiceTrackingBatch, TrackingBatch},
+    error::InvalidArgumentError,
     scope_id::{GlobalScopeId, ScopeId, ServiceScopeId},
 };
 
@@ -61,3 +62,19 @@ impl From<ServiceTrackingBatch> for Submission<ServiceScopeId> {
         }
     }
 }
+
+impl TryFrom<TrackingBatch> for Submission<GlobalScopeId> {
+    type Error = InvalidArgumentError;
+
+    fn try_from(batch: TrackingBatch) -> Result<Self, Self::Error> {
+        GlobalTrackingBatch::try_from(batch).map(Submission::from)
+    }
+}
+
+impl TryFrom<TrackingBatch> for Submission<ServiceScopeId> {
+    type Error = InvalidArgumentError;
+
+    fn try_from(batch: TrackingBatch) -> Result<Self, Self::Error> {
+        ServiceTrackingBatch::try_from(batch).map(Submission::from)
+    }
+}
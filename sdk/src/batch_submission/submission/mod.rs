@@ -12,6 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod queue;
+pub mod rate_limit;
+pub mod retry_policy;
 pub mod submitter;
 pub mod submitter_observer;
 pub mod url_resolver;
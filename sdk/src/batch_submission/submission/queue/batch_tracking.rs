@@ -0,0 +1,558 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    batch_submission::{
+        submission::{
+            rate_limit::{RateLimit, TokenBucket},
+            retry_policy::RetryPolicy,
+        },
+        Submission,
+    },
+    batch_tracking::store::{
+        BatchPriority, BatchTrackingStore, TrackingBatch, NON_SPLINTER_SERVICE_ID_DEFAULT,
+    },
+    scope_id::ScopeId,
+    store::TransactionalStoreFactory,
+};
+
+// Time a batch handed out by the queue is considered in flight, in seconds
+const DEFAULT_CLAIM_DURATION: u64 = 120;
+// Time after which the queue reloads unsubmitted batches even if it has some pending, in seconds
+const DEFAULT_REFRESH_INTERVAL: u64 = 5;
+// Number of batches the queue keeps pending and loads from the store at a time
+const DEFAULT_FETCH_SIZE: usize = 100;
+// Number of pages of unsubmitted batches the queue loads from the store at most per refill
+const DEFAULT_MAX_REFILL_PAGES: usize = 10;
+
+/// A submission queue backed by a `BatchTrackingStore`
+///
+/// The queue polls the store for unsubmitted batches that belong to the scope `S`. A batch that
+/// has been handed out is claimed for a period of time; if the store still reports it as
+/// unsubmitted once the claim expires, the batch is handed out again.
+///
+/// A batch whose earlier submissions failed is held back according to the queue's `RetryPolicy`:
+/// it is only handed out again once the backoff for the number of failed submissions recorded in
+/// the store has passed. Since the attempts are persisted, the backoff resumes where it left off
+/// after a restart.
+///
+/// Batches are handed out by priority: interactive batches before normal ones, and normal batches
+/// before bulk ones. Within a priority the queue alternates between signers, so a signer that
+/// queues a large number of batches does not hold back the batches of other signers. Pending
+/// batches are reloaded from the store periodically so that newly added batches are scheduled
+/// ahead of lower priority batches that are still waiting. Only the next batches to submit are
+/// loaded, a page at a time, until the queue has enough of them pending; signers are alternated
+/// among those batches. At most `max_refill_pages` pages are loaded per refill, and once a refill
+/// finds nothing to submit, for instance because every batch is claimed or backing off during an
+/// outage, the queue waits for the refresh interval before loading batches again.
+///
+/// Submissions to each service may be limited to a `RateLimit`. A batch for a service that has
+/// reached its limit is skipped in favor of batches for other services until the limit allows
+/// another submission.
+pub struct BatchTrackingQueue<S: ScopeId> {
+    store_factory: Box<dyn TransactionalStoreFactory>,
+    retry_policy: RetryPolicy,
+    claim_duration: Duration,
+    claims: HashMap<String, Instant>,
+    pending: VecDeque<QueuedSubmission<S>>,
+    refresh_interval: Duration,
+    refreshed_at: Option<Instant>,
+    idle: bool,
+    fetch_size: usize,
+    max_refill_pages: usize,
+    rate_limit: Option<RateLimit>,
+    service_rate_limits: HashMap<String, RateLimit>,
+    buckets: HashMap<String, TokenBucket>,
+}
+
+// A pending submission along with the service it is submitted to
+struct QueuedSubmission<S: ScopeId> {
+    service_id: String,
+    submission: Submission<S>,
+}
+
+impl<S: ScopeId> BatchTrackingQueue<S>
+where
+    Submission<S>: TryFrom<TrackingBatch>,
+{
+    pub fn new(store_factory: Box<dyn TransactionalStoreFactory>) -> Self {
+        Self {
+            store_factory,
+            retry_policy: RetryPolicy::default(),
+            claim_duration: Duration::from_secs(DEFAULT_CLAIM_DURATION),
+            claims: HashMap::new(),
+            pending: VecDeque::new(),
+            refresh_interval: Duration::from_secs(DEFAULT_REFRESH_INTERVAL),
+            refreshed_at: None,
+            idle: false,
+            fetch_size: DEFAULT_FETCH_SIZE,
+            max_refill_pages: DEFAULT_MAX_REFILL_PAGES,
+            rate_limit: None,
+            service_rate_limits: HashMap::new(),
+            buckets: HashMap::new(),
+        }
+    }
+
+    pub fn with_claim_duration(mut self, claim_duration: Duration) -> Self {
+        self.claim_duration = claim_duration;
+        self
+    }
+
+    /// Sets the policy used to back off batches whose submission has failed
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets how often pending batches are reloaded from the store
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    /// Sets how many batches are kept pending and loaded from the store at a time
+    pub fn with_fetch_size(mut self, fetch_size: usize) -> Self {
+        self.fetch_size = fetch_size.max(1);
+        self
+    }
+
+    /// Sets how many pages of batches are loaded from the store at most per refill
+    pub fn with_max_refill_pages(mut self, max_refill_pages: usize) -> Self {
+        self.max_refill_pages = max_refill_pages.max(1);
+        self
+    }
+
+    /// Limits the rate of submissions to each service
+    ///
+    /// The limit applies to every service separately, unless the service has its own limit.
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Limits the rate of submissions to the given service
+    ///
+    /// Batches that are not submitted to a Splinter service are limited by the service ID
+    /// `NON_SPLINTER_SERVICE_ID_DEFAULT`.
+    pub fn with_service_rate_limit(mut self, service_id: &str, rate_limit: RateLimit) -> Self {
+        self.service_rate_limits
+            .insert(service_id.to_string(), rate_limit);
+        self
+    }
+
+    // Returns true if the batch's last failed submission was too recent to submit it again
+    fn is_backing_off(&self, batch: &TrackingBatch, now: i64) -> bool {
+        match batch.submission_error() {
+            Some(err) if err.attempts() > 0 => {
+                let attempts = u32::try_from(err.attempts()).unwrap_or(u32::MAX);
+                let backoff = self.retry_policy.base_backoff(attempts).as_secs() as i64;
+                now < err.last_attempt_at() + backoff
+            }
+            _ => false,
+        }
+    }
+
+    // Reload the next unsubmitted batches for this scope that are not currently claimed, in the
+    // order they are to be submitted. Pages of batches are loaded until enough of them can be
+    // submitted, the store has no more or `max_refill_pages` pages have been loaded.
+    fn refill(&mut self) {
+        self.refreshed_at = Some(Instant::now());
+
+        let claim_duration = self.claim_duration;
+        self.claims
+            .retain(|_, claimed_at| claimed_at.elapsed() < claim_duration);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0);
+
+        let store = self.store_factory.get_batch_tracking_store();
+        let page_size = i64::try_from(self.fetch_size).unwrap_or(i64::MAX);
+        let mut offset = 0;
+        let mut eligible = Vec::new();
+        for _ in 0..self.max_refill_pages {
+            if eligible.len() >= self.fetch_size {
+                break;
+            }
+
+            let batches = match store.get_next_unsubmitted_batches(offset, page_size) {
+                Ok(list) => list.batches,
+                Err(err) => {
+                    error!("Unable to fetch unsubmitted batches: {}", err);
+                    return;
+                }
+            };
+            let is_last_page = (batches.len() as i64) < page_size;
+            offset += batches.len() as i64;
+
+            self.collect_eligible(batches, now, &mut eligible);
+
+            if is_last_page {
+                break;
+            }
+        }
+
+        self.idle = eligible.is_empty();
+        self.pending = schedule(eligible);
+    }
+
+    // Adds the batches that may be submitted now, along with their priority and signer, to
+    // `eligible`
+    fn collect_eligible(
+        &self,
+        batches: Vec<TrackingBatch>,
+        now: i64,
+        eligible: &mut Vec<(BatchPriority, String, QueuedSubmission<S>)>,
+    ) {
+        for batch in batches {
+            if self.claims.contains_key(batch.batch_header()) || self.is_backing_off(&batch, now) {
+                continue;
+            }
+
+            let priority = batch.priority();
+            let signer_public_key = batch.signer_public_key().to_string();
+            let service_id = batch
+                .service_id()
+                .unwrap_or(NON_SPLINTER_SERVICE_ID_DEFAULT)
+                .to_string();
+            // Batches that belong to a different scope are not submitted by this queue
+            if let Ok(submission) = Submission::try_from(batch) {
+                eligible.push((
+                    priority,
+                    signer_public_key,
+                    QueuedSubmission {
+                        service_id,
+                        submission,
+                    },
+                ));
+            }
+        }
+    }
+
+    // Returns the position of the first pending submission whose service is within its rate
+    // limit, taking a token from that service's bucket
+    fn next_allowed(&mut self) -> Option<usize> {
+        let now = Instant::now();
+        let rate_limit = self.rate_limit;
+        let service_rate_limits = &self.service_rate_limits;
+        let buckets = &mut self.buckets;
+        let mut limited = HashSet::new();
+
+        self.pending.iter().position(|queued| {
+            let limit = match service_rate_limits
+                .get(&queued.service_id)
+                .copied()
+                .or(rate_limit)
+            {
+                Some(limit) => limit,
+                None => return true,
+            };
+
+            if limited.contains(&queued.service_id) {
+                return false;
+            }
+
+            let allowed = buckets
+                .entry(queued.service_id.clone())
+                .or_insert_with(|| TokenBucket::new(limit, now))
+                .try_take(now);
+            if !allowed {
+                limited.insert(queued.service_id.clone());
+            }
+            allowed
+        })
+    }
+}
+
+impl<S: ScopeId> Iterator for BatchTrackingQueue<S>
+where
+    Submission<S>: TryFrom<TrackingBatch>,
+{
+    type Item = Submission<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let refresh_due = self
+            .refreshed_at
+            .map(|refreshed_at| refreshed_at.elapsed() >= self.refresh_interval)
+            .unwrap_or(true);
+        // An empty queue is only refilled early if its last refill found batches to submit
+        if refresh_due || (self.pending.is_empty() && !self.idle) {
+            self.refill();
+        }
+
+        let index = self.next_allowed()?;
+        let submission = self.pending.remove(index)?.submission;
+        self.claims
+            .insert(submission.batch_header().to_string(), Instant::now());
+
+        Some(submission)
+    }
+}
+
+// Orders items by priority and, within a priority, alternates between signers. Each signer's
+// items keep their relative order.
+fn schedule<T>(items: Vec<(BatchPriority, String, T)>) -> VecDeque<T> {
+    let mut classes: BTreeMap<BatchPriority, (Vec<String>, HashMap<String, VecDeque<T>>)> =
+        BTreeMap::new();
+
+    for (priority, signer, item) in items {
+        let (signers, by_signer) = classes.entry(priority).or_default();
+        match by_signer.get_mut(&signer) {
+            Some(signer_items) => signer_items.push_back(item),
+            None => {
+                signers.push(signer.clone());
+                by_signer.insert(signer, VecDeque::from(vec![item]));
+            }
+        }
+    }
+
+    let mut scheduled = VecDeque::new();
+    for (_, (signers, mut by_signer)) in classes {
+        loop {
+            let mut took_item = false;
+            for signer in &signers {
+                if let Some(item) = by_signer.get_mut(signer).and_then(VecDeque::pop_front) {
+                    scheduled.push_back(item);
+                    took_item = true;
+                }
+            }
+            if !took_item {
+                break;
+            }
+        }
+    }
+
+    scheduled
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::batch_submission::submission::retry_policy::RetryPolicyBuilder;
+    use crate::batch_tracking::store::{
+        BatchPriority, BatchStatus, SubmissionErrorBuilder, NON_SPLINTER_SERVICE_ID_DEFAULT,
+    };
+    use crate::batch_tracking::test_utils::{
+        add_built_batch, add_tracking_batch, create_store_factory, tracking_batch_builder,
+    };
+    use crate::scope_id::{GlobalScopeId, ServiceScopeId};
+
+    /// Verify that an unsubmitted batch is handed out once while it is claimed and handed out
+    /// again once the claim has expired.
+    #[test]
+    fn test_queue_claims_unsubmitted_batches() {
+        let store_factory = create_store_factory();
+        let batch = add_tracking_batch(&*store_factory);
+
+        let mut queue = BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box());
+        let submission = queue.next().expect("Batch was not queued");
+        assert_eq!(submission.batch_header(), batch.batch_header());
+        assert_eq!(submission.serialized_batch(), batch.serialized_batch());
+        assert!(queue.next().is_none());
+
+        let mut queue = BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box())
+            .with_claim_duration(Duration::from_secs(0));
+        assert!(queue.next().is_some());
+        assert!(queue.next().is_some());
+    }
+
+    /// Verify that a queue only hands out batches that belong to its scope.
+    #[test]
+    fn test_queue_filters_by_scope() {
+        let store_factory = create_store_factory();
+        add_tracking_batch(&*store_factory);
+
+        let mut queue = BatchTrackingQueue::<ServiceScopeId>::new(store_factory.clone_box());
+        assert!(queue.next().is_none());
+    }
+
+    /// Verify that a batch whose submission failed is held back until the backoff for its
+    /// recorded failed attempts has passed.
+    #[test]
+    fn test_queue_backs_off_failed_batches() {
+        let store_factory = create_store_factory();
+        let batch = add_tracking_batch(&*store_factory);
+
+        let submission_error = SubmissionErrorBuilder::default()
+            .with_error_type("Retryable".to_string())
+            .with_error_message("HTTP 503: Service Unavailable".to_string())
+            .build()
+            .expect("Failed to build submission error");
+        store_factory
+            .get_batch_tracking_store()
+            .update_batch_status(
+                batch.batch_header(),
+                NON_SPLINTER_SERVICE_ID_DEFAULT,
+                Some(BatchStatus::Delayed),
+                vec![],
+                Some(submission_error),
+            )
+            .expect("Failed to update batch status");
+
+        let retry_policy = RetryPolicyBuilder::new()
+            .with_initial_backoff(Duration::from_secs(60))
+            .with_max_backoff(Duration::from_secs(60))
+            .build()
+            .expect("Failed to build retry policy");
+        let mut queue = BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box())
+            .with_retry_policy(retry_policy);
+        assert!(queue.next().is_none());
+
+        let retry_policy = RetryPolicyBuilder::new()
+            .with_initial_backoff(Duration::from_secs(0))
+            .with_max_backoff(Duration::from_secs(0))
+            .build()
+            .expect("Failed to build retry policy");
+        let mut queue = BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box())
+            .with_retry_policy(retry_policy);
+        assert!(queue.next().is_some());
+    }
+
+    /// Verify that batches are handed out by priority regardless of the order they were added in.
+    #[test]
+    fn test_queue_prioritizes_batches() {
+        let store_factory = create_store_factory();
+        let bulk = add_built_batch(
+            &*store_factory,
+            tracking_batch_builder().with_priority(BatchPriority::Bulk),
+        );
+        let normal = add_built_batch(
+            &*store_factory,
+            tracking_batch_builder().with_priority(BatchPriority::Normal),
+        );
+        let interactive = add_built_batch(
+            &*store_factory,
+            tracking_batch_builder().with_priority(BatchPriority::Interactive),
+        );
+
+        let mut queue = BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box());
+        let order = queue
+            .by_ref()
+            .take(3)
+            .map(|submission| submission.batch_header().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![
+                interactive.batch_header().to_string(),
+                normal.batch_header().to_string(),
+                bulk.batch_header().to_string(),
+            ]
+        );
+        assert!(queue.next().is_none());
+    }
+
+    /// Verify that the queue loads further pages of batches when the batches it has loaded
+    /// cannot be submitted yet.
+    #[test]
+    fn test_queue_pages_past_claimed_batches() {
+        let store_factory = create_store_factory();
+        add_tracking_batch(&*store_factory);
+        add_tracking_batch(&*store_factory);
+
+        let mut queue =
+            BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box()).with_fetch_size(1);
+        let first = queue.next().expect("Batch was not queued");
+        let second = queue.next().expect("Batch was not queued");
+        assert_ne!(first.batch_header(), second.batch_header());
+        assert!(queue.next().is_none());
+    }
+
+    /// Verify that a queue whose last refill found nothing to submit does not load batches from
+    /// the store again until its refresh interval has passed.
+    #[test]
+    fn test_queue_waits_for_refresh_when_idle() {
+        let store_factory = create_store_factory();
+        add_tracking_batch(&*store_factory);
+
+        let mut queue = BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box())
+            .with_refresh_interval(Duration::from_secs(3600));
+        assert!(queue.next().is_some());
+        // The only batch is claimed, so the refill finds nothing
+        assert!(queue.next().is_none());
+
+        add_tracking_batch(&*store_factory);
+        assert!(queue.next().is_none());
+
+        let mut queue = BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box())
+            .with_refresh_interval(Duration::from_secs(0));
+        assert!(queue.next().is_some());
+        assert!(queue.next().is_some());
+    }
+
+    /// Verify that a refill loads at most the maximum number of pages of batches.
+    #[test]
+    fn test_queue_limits_refill_pages() {
+        let store_factory = create_store_factory();
+        add_tracking_batch(&*store_factory);
+        add_tracking_batch(&*store_factory);
+
+        let mut queue = BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box())
+            .with_fetch_size(1)
+            .with_max_refill_pages(1);
+        assert!(queue.next().is_some());
+        // The first page only holds the claimed batch
+        assert!(queue.next().is_none());
+    }
+
+    /// Verify that scheduling orders items by priority and alternates between signers within a
+    /// priority, keeping each signer's items in order.
+    #[test]
+    fn test_schedule() {
+        let items = vec![
+            (BatchPriority::Normal, "a".to_string(), "a1"),
+            (BatchPriority::Normal, "a".to_string(), "a2"),
+            (BatchPriority::Normal, "a".to_string(), "a3"),
+            (BatchPriority::Bulk, "c".to_string(), "c1"),
+            (BatchPriority::Normal, "b".to_string(), "b1"),
+            (BatchPriority::Interactive, "a".to_string(), "a4"),
+            (BatchPriority::Normal, "b".to_string(), "b2"),
+        ];
+
+        assert_eq!(
+            schedule(items),
+            vec!["a4", "a1", "b1", "a2", "b2", "a3", "c1"]
+        );
+    }
+
+    /// Verify that submissions to a service are held back once its rate limit is reached, and
+    /// that a service's own limit takes precedence over the queue's limit.
+    #[test]
+    fn test_queue_rate_limits_services() {
+        let store_factory = create_store_factory();
+        add_tracking_batch(&*store_factory);
+        add_tracking_batch(&*store_factory);
+
+        let rate_limit =
+            RateLimit::new(1, Duration::from_secs(3600)).expect("Failed to create rate limit");
+        let mut queue = BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box())
+            .with_rate_limit(rate_limit);
+        assert!(queue.next().is_some());
+        assert!(queue.next().is_none());
+
+        let service_rate_limit =
+            RateLimit::new(2, Duration::from_secs(3600)).expect("Failed to create rate limit");
+        let mut queue = BatchTrackingQueue::<GlobalScopeId>::new(store_factory.clone_box())
+            .with_rate_limit(rate_limit)
+            .with_service_rate_limit(NON_SPLINTER_SERVICE_ID_DEFAULT, service_rate_limit);
+        assert!(queue.next().is_some());
+        assert!(queue.next().is_some());
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Limits on the rate at which batches are submitted
+
+use std::time::{Duration, Instant};
+
+use crate::error::InvalidArgumentError;
+
+/// A limit on the number of batches submitted in a period of time
+///
+/// Up to the limit's number of batches may be submitted at once; once those are used up,
+/// submissions are spread evenly over the period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    batches: u32,
+    period: Duration,
+}
+
+impl RateLimit {
+    pub fn new(batches: u32, period: Duration) -> Result<Self, InvalidArgumentError> {
+        if batches == 0 {
+            return Err(InvalidArgumentError::new(
+                "batches".to_string(),
+                "must be greater than 0".to_string(),
+            ));
+        }
+
+        if period.as_nanos() == 0 {
+            return Err(InvalidArgumentError::new(
+                "period".to_string(),
+                "must be greater than 0".to_string(),
+            ));
+        }
+
+        Ok(Self { batches, period })
+    }
+
+    /// Creates a limit on the number of batches submitted per second
+    pub fn per_second(batches: u32) -> Result<Self, InvalidArgumentError> {
+        Self::new(batches, Duration::from_secs(1))
+    }
+
+    pub fn batches(&self) -> u32 {
+        self.batches
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+/// Tracks the submissions allowed by a `RateLimit`
+///
+/// The bucket starts full with one token per batch of the limit and is refilled continuously over
+/// the limit's period. Each submission takes a token.
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    pub(crate) fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.batches),
+            updated_at: now,
+        }
+    }
+
+    /// Takes a token if one is available, returning whether a batch may be submitted
+    pub(crate) fn try_take(&mut self, now: Instant) -> bool {
+        let capacity = f64::from(self.limit.batches);
+        let elapsed = now.saturating_duration_since(self.updated_at);
+        let refill = elapsed.as_secs_f64() * capacity / self.limit.period.as_secs_f64();
+
+        self.tokens = (self.tokens + refill).min(capacity);
+        self.updated_at = self.updated_at.max(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a rate limit must allow at least one batch in a non-empty period.
+    #[test]
+    fn test_rate_limit_validation() {
+        assert!(RateLimit::new(0, Duration::from_secs(1)).is_err());
+        assert!(RateLimit::new(1, Duration::from_secs(0)).is_err());
+        assert!(RateLimit::per_second(1).is_ok());
+    }
+
+    /// Verify that a bucket allows a burst of the limit's size and then refills at the limit's
+    /// rate.
+    #[test]
+    fn test_token_bucket() {
+        let limit = RateLimit::new(2, Duration::from_secs(10)).expect("Failed to create limit");
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(limit, start);
+
+        assert!(bucket.try_take(start));
+        assert!(bucket.try_take(start));
+        assert!(!bucket.try_take(start));
+
+        // One token is refilled every 5 seconds
+        assert!(!bucket.try_take(start + Duration::from_secs(4)));
+        assert!(bucket.try_take(start + Duration::from_secs(5)));
+        assert!(!bucket.try_take(start + Duration::from_secs(5)));
+
+        // The bucket does not fill beyond the limit
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.try_take(later));
+        assert!(bucket.try_take(later));
+        assert!(!bucket.try_take(later));
+    }
+}
@@ -0,0 +1,336 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Configuration of how failed batch submissions are retried
+
+use std::collections::{hash_map::RandomState, HashMap};
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::error::InvalidArgumentError;
+
+const DEFAULT_MAX_ATTEMPTS: u16 = 10;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const DEFAULT_MULTIPLIER: u32 = 2;
+// Kept below the claim duration of the `BatchTrackingQueue`, so that a batch is not yielded again
+// while it is still being retried
+const DEFAULT_DEADLINE: Duration = Duration::from_secs(60);
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const DEFAULT_RETRY_STATUSES: [u16; 5] = [408, 429, 502, 503, 504];
+
+/// What the submitter does after a submission attempt fails
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryBehavior {
+    /// Back off and attempt the submission again
+    Retry,
+    /// Give up and report the failure to the observer
+    Stop,
+}
+
+/// Controls how the batch submitter retries failed submissions.
+///
+/// A submission is retried while the outcome of the last attempt has `RetryBehavior::Retry`, up to
+/// a maximum number of attempts and within a total deadline. Between attempts, the submitter backs
+/// off exponentially; with jitter enabled, each wait is randomly chosen between half and all of
+/// the exponential backoff, so that submitters do not retry in lock-step.
+///
+/// The default policy retries timeouts, rate limiting (429) and gateway or availability errors
+/// (502, 503 and 504) as well as failures to get a response at all, starting at 250 milliseconds
+/// and doubling up to 30 seconds, for up to 10 attempts or 60 seconds.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u16,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: u32,
+    jitter: bool,
+    deadline: Option<Duration>,
+    request_timeout: Duration,
+    status_behaviors: HashMap<u16, RetryBehavior>,
+    no_response_behavior: RetryBehavior,
+}
+
+impl RetryPolicy {
+    /// Returns the maximum number of attempts made for a single submission
+    pub fn max_attempts(&self) -> u16 {
+        self.max_attempts
+    }
+
+    /// Returns the total time allowed for the attempts of a single submission, if limited
+    pub fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+
+    /// Returns the timeout of a single submission request
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    /// Returns the behavior for a failed attempt, given the HTTP status of the response or `None`
+    /// if no response was received.
+    ///
+    /// Statuses without a configured behavior are not retried.
+    pub fn behavior(&self, status: Option<u16>) -> RetryBehavior {
+        match status {
+            Some(status) => self
+                .status_behaviors
+                .get(&status)
+                .copied()
+                .unwrap_or(RetryBehavior::Stop),
+            None => self.no_response_behavior,
+        }
+    }
+
+    /// Returns the time to wait after the given number of failed attempts, starting at 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self.base_backoff(attempt);
+
+        if self.jitter {
+            let half = backoff / 2;
+            half + random_fraction_of(backoff - half)
+        } else {
+            backoff
+        }
+    }
+
+    /// Returns the exponential backoff after the given number of failed attempts, starting at 1,
+    /// without jitter
+    pub fn base_backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1);
+        let factor = self.multiplier.checked_pow(exponent).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            multiplier: DEFAULT_MULTIPLIER,
+            jitter: true,
+            deadline: Some(DEFAULT_DEADLINE),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            status_behaviors: DEFAULT_RETRY_STATUSES
+                .iter()
+                .map(|status| (*status, RetryBehavior::Retry))
+                .collect(),
+            no_response_behavior: RetryBehavior::Retry,
+        }
+    }
+}
+
+/// Builds a `RetryPolicy`, starting from the default policy
+#[derive(Default)]
+pub struct RetryPolicyBuilder {
+    policy: RetryPolicy,
+}
+
+impl RetryPolicyBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of attempts made for a single submission, including the first
+    pub fn with_max_attempts(mut self, max_attempts: u16) -> Self {
+        self.policy.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the time to wait after the first failed attempt
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.policy.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the longest time to wait between attempts
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.policy.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the factor the backoff is multiplied by after each failed attempt
+    pub fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.policy.multiplier = multiplier;
+        self
+    }
+
+    /// Sets whether the backoff is randomized
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.policy.jitter = jitter;
+        self
+    }
+
+    /// Sets the total time allowed for the attempts of a single submission
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.policy.deadline = Some(deadline);
+        self
+    }
+
+    /// Removes the total deadline, so that only the maximum number of attempts applies
+    pub fn without_deadline(mut self) -> Self {
+        self.policy.deadline = None;
+        self
+    }
+
+    /// Sets the timeout of a single submission request
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.policy.request_timeout = request_timeout;
+        self
+    }
+
+    /// Sets the behavior for responses with the given HTTP status
+    pub fn with_status_behavior(mut self, status: u16, behavior: RetryBehavior) -> Self {
+        self.policy.status_behaviors.insert(status, behavior);
+        self
+    }
+
+    /// Sets the behavior for attempts that do not receive a response, such as connection errors
+    /// or request timeouts
+    pub fn with_no_response_behavior(mut self, behavior: RetryBehavior) -> Self {
+        self.policy.no_response_behavior = behavior;
+        self
+    }
+
+    pub fn build(self) -> Result<RetryPolicy, InvalidArgumentError> {
+        let policy = self.policy;
+
+        if policy.max_attempts == 0 {
+            return Err(InvalidArgumentError::new(
+                "max_attempts".to_string(),
+                "At least one attempt is required".to_string(),
+            ));
+        }
+
+        if policy.multiplier == 0 {
+            return Err(InvalidArgumentError::new(
+                "multiplier".to_string(),
+                "Multiplier must be at least 1".to_string(),
+            ));
+        }
+
+        if policy.initial_backoff > policy.max_backoff {
+            return Err(InvalidArgumentError::new(
+                "initial_backoff".to_string(),
+                "Initial backoff must not exceed the maximum backoff".to_string(),
+            ));
+        }
+
+        if policy.request_timeout.as_millis() == 0 {
+            return Err(InvalidArgumentError::new(
+                "request_timeout".to_string(),
+                "Request timeout must be greater than zero".to_string(),
+            ));
+        }
+
+        Ok(policy)
+    }
+}
+
+// Returns a random duration between zero and `duration`. `RandomState` is seeded randomly, which
+// is sufficient for spreading out retries.
+fn random_fraction_of(duration: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    let millis = duration.as_millis() as u64;
+    if millis == 0 {
+        return Duration::from_millis(0);
+    }
+    Duration::from_millis(random % (millis + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the backoff grows exponentially from the initial backoff and is capped at the
+    /// maximum backoff.
+    #[test]
+    fn test_retry_policy_backoff() {
+        let policy = RetryPolicyBuilder::new()
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_secs(1))
+            .with_multiplier(2)
+            .with_jitter(false)
+            .build()
+            .expect("Failed to build policy");
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(100), Duration::from_secs(1));
+    }
+
+    /// Verify that a jittered backoff falls between half and all of the exponential backoff.
+    #[test]
+    fn test_retry_policy_backoff_jitter() {
+        let policy = RetryPolicyBuilder::new()
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_secs(1))
+            .with_jitter(true)
+            .build()
+            .expect("Failed to build policy");
+
+        for _ in 0..100 {
+            let backoff = policy.backoff(3);
+            assert!(backoff >= Duration::from_millis(200));
+            assert!(backoff <= Duration::from_millis(400));
+        }
+    }
+
+    /// Verify the default and configured behaviors for response statuses.
+    #[test]
+    fn test_retry_policy_behavior() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.behavior(None), RetryBehavior::Retry);
+        assert_eq!(policy.behavior(Some(429)), RetryBehavior::Retry);
+        assert_eq!(policy.behavior(Some(503)), RetryBehavior::Retry);
+        assert_eq!(policy.behavior(Some(400)), RetryBehavior::Stop);
+        assert_eq!(policy.behavior(Some(500)), RetryBehavior::Stop);
+
+        let policy = RetryPolicyBuilder::new()
+            .with_status_behavior(500, RetryBehavior::Retry)
+            .with_status_behavior(503, RetryBehavior::Stop)
+            .with_no_response_behavior(RetryBehavior::Stop)
+            .build()
+            .expect("Failed to build policy");
+        assert_eq!(policy.behavior(None), RetryBehavior::Stop);
+        assert_eq!(policy.behavior(Some(500)), RetryBehavior::Retry);
+        assert_eq!(policy.behavior(Some(503)), RetryBehavior::Stop);
+    }
+
+    /// Verify that invalid policies are rejected.
+    #[test]
+    fn test_retry_policy_invalid() {
+        assert!(RetryPolicyBuilder::new()
+            .with_max_attempts(0)
+            .build()
+            .is_err());
+        assert!(RetryPolicyBuilder::new()
+            .with_multiplier(0)
+            .build()
+            .is_err());
+        assert!(RetryPolicyBuilder::new()
+            .with_initial_backoff(Duration::from_secs(10))
+            .with_max_backoff(Duration::from_secs(1))
+            .build()
+            .is_err());
+    }
+}
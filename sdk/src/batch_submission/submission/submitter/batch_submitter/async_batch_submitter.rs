@@ -15,6 +15,7 @@
 use std::{
     fmt,
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use async_trait::async_trait;
@@ -23,6 +24,7 @@ use reqwest::Client;
 use crate::{
     batch_submission::{
         submission::{
+            retry_policy::{RetryBehavior, RetryPolicy},
             submitter::{RunnableSubmitter, RunningSubmitter},
             submitter_observer::SubmitterObserver,
             url_resolver::UrlResolver,
@@ -34,10 +36,8 @@ use crate::{
     threading::lifecycle::ShutdownHandle,
 };
 
-// Number of times a submitter task will retry submission in quick succession
-const RETRY_ATTEMPTS: u16 = 10;
-// Time the submitter will wait to repoll after receiving None, in milliseconds
-const POLLING_INTERVAL: u64 = 1000;
+// Default time the submitter will wait to repoll after receiving None
+const DEFAULT_POLLING_INTERVAL: Duration = Duration::from_millis(1000);
 
 #[derive(Debug, PartialEq, Eq)]
 // Carries the submission response from the http client back through the submitter to the observer
@@ -148,20 +148,20 @@ pub trait ExecuteCommand<S: ScopeId>: fmt::Debug + Sync + Send {
 struct SubmissionCommand<S: ScopeId> {
     url_resolver: Arc<dyn UrlResolver<Id = S>>,
     submission: Submission<S>,
+    request_timeout: Duration,
     attempts: u16,
 }
 
 #[async_trait]
 impl<S: ScopeId> ExecuteCommand<S> for SubmissionCommand<S> {
     async fn execute(&mut self) -> Result<SubmissionResponse<S>, reqwest::Error> {
-        let client = Client::builder()
-            .timeout(tokio::time::Duration::from_secs(15))
-            .build()?;
+        let client = Client::builder().timeout(self.request_timeout).build()?;
 
         self.attempts += 1;
 
         let res = client
             .post(&self.url_resolver.url(self.submission.scope_id()))
+            .header("Content-Type", "application/octet-stream")
             .body(self.submission.serialized_batch().clone())
             .send()
             .await?;
@@ -180,11 +180,15 @@ impl<S: ScopeId> ExecuteCommand<S> for SubmissionCommand<S> {
 // Creates a submission command inside the task
 struct SubmissionCommandFactory<S: ScopeId> {
     url_resolver: Arc<dyn UrlResolver<Id = S>>,
+    request_timeout: Duration,
 }
 
 impl<S: ScopeId> SubmissionCommandFactory<S> {
-    fn new(url_resolver: Arc<dyn UrlResolver<Id = S>>) -> Self {
-        Self { url_resolver }
+    fn new(url_resolver: Arc<dyn UrlResolver<Id = S>>, request_timeout: Duration) -> Self {
+        Self {
+            url_resolver,
+            request_timeout,
+        }
     }
 }
 
@@ -193,6 +197,7 @@ impl<S: ScopeId> ExecuteCommandFactory<S> for SubmissionCommandFactory<S> {
         Box::new(SubmissionCommand {
             url_resolver: Arc::clone(&self.url_resolver),
             submission,
+            request_timeout: self.request_timeout,
             attempts: 0,
         })
     }
@@ -205,33 +210,37 @@ struct SubmissionController;
 impl SubmissionController {
     async fn run<S: ScopeId>(
         mut command: Box<dyn ExecuteCommand<S>>,
+        retry_policy: &RetryPolicy,
     ) -> Result<SubmissionResponse<S>, ClientError> {
-        let mut wait: u64 = 250;
-        let mut response: Result<SubmissionResponse<S>, reqwest::Error> = command.execute().await;
-        for _ in 1..RETRY_ATTEMPTS {
-            match &response {
-                Ok(res) => match &res.status {
-                    200 => break,
-                    503 => {
-                        tokio::time::sleep(tokio::time::Duration::from_millis(wait)).await;
-                        response = command.execute().await;
-                    }
-                    _ => break,
-                },
-                Err(e) => {
-                    if e.is_timeout() {
-                        tokio::time::sleep(tokio::time::Duration::from_millis(wait)).await;
-                        response = command.execute().await;
-                    } else {
-                        // This error is returned outside of the loop and not behind a &
-                        break;
-                    }
+        let start = Instant::now();
+        let mut attempt: u32 = 1;
+        loop {
+            let response = command.execute().await;
+            let behavior = match &response {
+                Ok(res) if (200..300).contains(&res.status) => RetryBehavior::Stop,
+                Ok(res) => retry_policy.behavior(Some(res.status)),
+                Err(_) => retry_policy.behavior(None),
+            };
+
+            if behavior == RetryBehavior::Stop || attempt >= u32::from(retry_policy.max_attempts())
+            {
+                return response.map_err(ClientError::from);
+            }
+
+            let wait = retry_policy.backoff(attempt);
+            if let Some(deadline) = retry_policy.deadline() {
+                if start.elapsed() + wait > deadline {
+                    debug!(
+                        "Submission deadline of {:?} reached after {} attempt(s)",
+                        deadline, attempt
+                    );
+                    return response.map_err(ClientError::from);
                 }
             }
-            wait += 500;
+
+            tokio::time::sleep(wait).await;
+            attempt += 1;
         }
-        let res = response.map_err(ClientError::from)?;
-        Ok(res)
     }
 }
 
@@ -243,12 +252,13 @@ impl TaskHandler {
     async fn spawn<'a, S: ScopeId>(
         task: NewTask<S>,
         submission_command_factory: Arc<dyn ExecuteCommandFactory<S>>,
+        retry_policy: Arc<RetryPolicy>,
     ) {
         let batch_header = task.submission.batch_header().clone();
         let scope_id = task.submission.scope_id().clone();
         let submission_command = submission_command_factory.new_command(task.submission);
         let submission: Result<SubmissionResponse<S>, ClientError> =
-            SubmissionController::run(submission_command).await;
+            SubmissionController::run(submission_command, &retry_policy).await;
 
         let task_message = match submission {
             Ok(s) => BatchMessage::SubmissionResponse(s),
@@ -275,6 +285,8 @@ pub struct BatchSubmitterBuilder<S: 'static + ScopeId> {
     queue: Option<Box<(dyn Iterator<Item = Submission<S>> + Send)>>,
     observer: Option<Box<dyn SubmitterObserver<Id = S> + Send>>,
     submission_command_factory: Option<Arc<dyn ExecuteCommandFactory<S>>>,
+    retry_policy: Option<RetryPolicy>,
+    polling_interval: Option<Duration>,
 }
 
 impl<S: 'static + ScopeId> BatchSubmitterBuilder<S> {
@@ -284,6 +296,8 @@ impl<S: 'static + ScopeId> BatchSubmitterBuilder<S> {
             queue: None,
             observer: None,
             submission_command_factory: None,
+            retry_policy: None,
+            polling_interval: None,
         }
     }
 
@@ -310,6 +324,20 @@ impl<S: 'static + ScopeId> BatchSubmitterBuilder<S> {
         self
     }
 
+    /// Sets the policy used to retry failed submissions; `RetryPolicy::default()` is used if it is
+    /// not set. The policy's request timeout only applies to the default submission command.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Sets how long the submitter waits before polling the queue again after it returns `None`;
+    /// defaults to one second.
+    pub fn with_polling_interval(mut self, polling_interval: Duration) -> Self {
+        self.polling_interval = Some(polling_interval);
+        self
+    }
+
     pub fn build(self) -> Result<BatchRunnableSubmitter<S>, InternalError> {
         let queue = match self.queue {
             Some(q) => q,
@@ -327,12 +355,16 @@ impl<S: 'static + ScopeId> BatchSubmitterBuilder<S> {
                 ))
             }
         };
+        let retry_policy = Arc::new(self.retry_policy.unwrap_or_default());
+        let polling_interval = self.polling_interval.unwrap_or(DEFAULT_POLLING_INTERVAL);
         match self.submission_command_factory {
             // If a command_factory is provided, a url_resolver does not need to be
             Some(f) => Ok(BatchRunnableSubmitter {
                 queue,
                 observer,
                 command_factory: f,
+                retry_policy,
+                polling_interval,
                 leader_channel: std::sync::mpsc::channel(),
                 listener_channel: std::sync::mpsc::channel(),
                 submission_channel: std::sync::mpsc::channel(),
@@ -345,7 +377,10 @@ impl<S: 'static + ScopeId> BatchSubmitterBuilder<S> {
             }),
             None => {
                 let command_factory = match self.url_resolver {
-                    Some(u) => Arc::new(SubmissionCommandFactory::new(u)),
+                    Some(u) => Arc::new(SubmissionCommandFactory::new(
+                        u,
+                        retry_policy.request_timeout(),
+                    )),
                     None => {
                         return Err(InternalError::with_message(
                             "Cannot build BatchRunnableSubmitter, missing url resolver."
@@ -357,6 +392,8 @@ impl<S: 'static + ScopeId> BatchSubmitterBuilder<S> {
                     queue,
                     observer,
                     command_factory,
+                    retry_policy,
+                    polling_interval,
                     leader_channel: std::sync::mpsc::channel(),
                     listener_channel: std::sync::mpsc::channel(),
                     submission_channel: std::sync::mpsc::channel(),
@@ -380,6 +417,8 @@ pub struct BatchRunnableSubmitter<S: 'static + ScopeId> {
     queue: Box<(dyn Iterator<Item = Submission<S>> + Send)>,
     observer: Box<dyn SubmitterObserver<Id = S> + Send>,
     command_factory: Arc<dyn ExecuteCommandFactory<S>>,
+    retry_policy: Arc<RetryPolicy>,
+    polling_interval: Duration,
     leader_channel: (
         std::sync::mpsc::Sender<ControlMessage>,
         std::sync::mpsc::Receiver<ControlMessage>,
@@ -408,6 +447,9 @@ impl<S: 'static + ScopeId> RunnableSubmitter<S> for BatchRunnableSubmitter<S> {
         let mut queue = self.queue;
         let observer = self.observer;
         let submitter_command_factory = self.command_factory;
+        let retry_policy = self.retry_policy;
+        let task_retry_policy = Arc::clone(&retry_policy);
+        let polling_interval = self.polling_interval;
 
         // Create channels for termination messages
         let (leader_tx, leader_rx) = self.leader_channel;
@@ -502,6 +544,7 @@ impl<S: 'static + ScopeId> RunnableSubmitter<S> for BatchRunnableSubmitter<S> {
                                 tokio::spawn(TaskHandler::spawn(
                                     t,
                                     Arc::clone(&submitter_command_factory),
+                                    Arc::clone(&task_retry_policy),
                                 ));
                             }
                             CentralMessage::Stop => {
@@ -561,9 +604,7 @@ impl<S: 'static + ScopeId> RunnableSubmitter<S> for BatchRunnableSubmitter<S> {
                                 error!("Error sending NewTask message: {:?}", e)
                             };
                         }
-                        None => {
-                            std::thread::sleep(std::time::Duration::from_millis(POLLING_INTERVAL))
-                        }
+                        None => std::thread::sleep(polling_interval),
                     }
                 }
                 // Begin stop sequence
@@ -587,6 +628,8 @@ impl<S: 'static + ScopeId> RunnableSubmitter<S> for BatchRunnableSubmitter<S> {
             runtime_handle,
             listener_handle,
             collector,
+            retry_policy,
+            polling_interval,
         })
     }
 }
@@ -598,6 +641,8 @@ pub struct BatchRunningSubmitter<S: ScopeId> {
     runtime_handle: std::thread::JoinHandle<()>,
     listener_handle: std::thread::JoinHandle<()>,
     collector: Arc<Mutex<Collector<S>>>,
+    retry_policy: Arc<RetryPolicy>,
+    polling_interval: Duration,
 }
 
 impl<S: ScopeId> RunningSubmitter<S> for BatchRunningSubmitter<S> {
@@ -647,6 +692,8 @@ impl<S: ScopeId> RunningSubmitter<S> for BatchRunningSubmitter<S> {
             queue,
             observer,
             command_factory,
+            retry_policy: self.retry_policy,
+            polling_interval: self.polling_interval,
             leader_channel: std::sync::mpsc::channel(),
             listener_channel: std::sync::mpsc::channel(),
             submission_channel: std::sync::mpsc::channel(),
@@ -696,6 +743,7 @@ mod tests {
     use std::sync::Mutex;
 
     use super::*;
+    use crate::batch_submission::submission::retry_policy::RetryPolicyBuilder;
     use crate::scope_id::GlobalScopeId;
     use mockito;
 
@@ -828,7 +876,8 @@ mod tests {
         let _m1 = mockito::mock("POST", "/test").with_body("success").create();
         let mock_submission = MockSubmission::new();
         let mock_url_resolver = Arc::new(MockUrlResolver::new(url));
-        let test_submission_command_factory = SubmissionCommandFactory::new(mock_url_resolver);
+        let test_submission_command_factory =
+            SubmissionCommandFactory::new(mock_url_resolver, Duration::from_secs(15));
         let mut test_command = test_submission_command_factory.new_command(mock_submission);
         let expected_response = SubmissionResponse::new(
             "test".to_string(),
@@ -862,7 +911,7 @@ mod tests {
         let response = tokio::runtime::Runtime::new()
             .unwrap()
             .block_on(async move {
-                SubmissionController::run(mock_submission_command)
+                SubmissionController::run(mock_submission_command, &RetryPolicy::default())
                     .await
                     .unwrap()
             });
@@ -870,6 +919,46 @@ mod tests {
         assert_eq!(response, expected_response);
     }
 
+    #[test]
+    // Test that the submission controller gives up once the retry policy's maximum number of
+    // attempts is reached, or immediately if the policy does not retry the response status
+    fn test_batch_submitter_submission_controller_retry_policy() {
+        let mock_url_resolver = Arc::new(MockUrlResolver::new("throwaway_url".to_string()));
+        let mock_submission_command_factory = MockSubmissionCommandFactory::new(mock_url_resolver);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let retry_policy = RetryPolicyBuilder::new()
+            .with_max_attempts(2)
+            .with_initial_backoff(Duration::from_millis(10))
+            .build()
+            .unwrap();
+        let command = mock_submission_command_factory.new_command(MockSubmission::new());
+        let response = runtime
+            .block_on(async { SubmissionController::run(command, &retry_policy).await })
+            .unwrap();
+        assert_eq!(
+            response,
+            SubmissionResponse::new(
+                "test".to_string(),
+                GlobalScopeId::new(),
+                503,
+                "Busy".to_string(),
+                2,
+            )
+        );
+
+        let retry_policy = RetryPolicyBuilder::new()
+            .with_status_behavior(503, RetryBehavior::Stop)
+            .build()
+            .unwrap();
+        let command = mock_submission_command_factory.new_command(MockSubmission::new());
+        let response = runtime
+            .block_on(async { SubmissionController::run(command, &retry_policy).await })
+            .unwrap();
+        assert_eq!(response.status, 503);
+        assert_eq!(response.attempts, 1);
+    }
+
     #[test]
     // Test that the task handler successfully executes a submission task
     fn test_batch_submitter_task_handler_spawn() {
@@ -900,6 +989,7 @@ mod tests {
                     tokio::spawn(TaskHandler::spawn(
                         mock_new_task,
                         Arc::new(mock_submission_command_factory),
+                        Arc::new(RetryPolicy::default()),
                     ));
                     // Let the above task finish before dropping the runtime
                     tokio::time::sleep(std::time::Duration::from_secs(2)).await;
@@ -0,0 +1,565 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::marker::PhantomData;
+
+#[cfg(feature = "batch-tracking-wait")]
+use crate::batch_tracking::notifier::BatchStatusNotifier;
+#[cfg(feature = "rest-api-resources-event-stream")]
+use crate::rest_api::resources::event_stream::v1::{EventBroadcaster, StreamEvent};
+use crate::{
+    batch_tracking::store::{
+        BatchBuilderError, BatchStatus, BatchTrackingStore, BatchTrackingStoreError,
+        SubmissionError, SubmissionErrorBuilder, SubmissionErrorType,
+        NON_SPLINTER_SERVICE_ID_DEFAULT,
+    },
+    scope_id::{GlobalScopeId, ScopeId, ServiceScopeId},
+    store::TransactionalStoreFactory,
+};
+
+use super::SubmitterObserver;
+
+const DEFAULT_MAX_FAILED_SUBMISSIONS: i64 = 10;
+
+/// An observer that records submission updates in a `BatchTrackingStore`
+///
+/// A successful submission marks the batch as submitted with a `Pending` status. A failed
+/// submission is recorded as a `SubmissionError`, typed by whether it may be retried:
+///
+/// * A retryable failure (no response, a request timeout, rate limiting or a server error) leaves
+///   the batch `Delayed` so that it is picked up again by the submission queue
+/// * A permanent failure (any other client error) marks the batch as submitted with an `Invalid`
+///   status, so that it is not submitted again
+///
+/// Batches that are rejected, or that have failed to be submitted the maximum number of times,
+/// are moved to the dead letter queue of the store, where they wait to be replayed or discarded.
+///
+/// Since every outcome is written to the store, the submission history of a batch survives a
+/// restart.
+///
+/// If an `EventBroadcaster` is set, every status change is also published to the clients of the
+/// event stream. If a `BatchStatusNotifier` is set, it is notified of every update, waking the
+/// requests waiting on batch statuses.
+pub struct BatchTrackingObserver<S: ScopeId> {
+    store_factory: Box<dyn TransactionalStoreFactory>,
+    max_failed_submissions: i64,
+    #[cfg(feature = "rest-api-resources-event-stream")]
+    event_broadcaster: Option<EventBroadcaster>,
+    #[cfg(feature = "batch-tracking-wait")]
+    status_notifier: Option<BatchStatusNotifier>,
+    _scope: PhantomData<S>,
+}
+
+impl<S: ScopeId> BatchTrackingObserver<S> {
+    pub fn new(store_factory: Box<dyn TransactionalStoreFactory>) -> Self {
+        Self {
+            store_factory,
+            max_failed_submissions: DEFAULT_MAX_FAILED_SUBMISSIONS,
+            #[cfg(feature = "rest-api-resources-event-stream")]
+            event_broadcaster: None,
+            #[cfg(feature = "batch-tracking-wait")]
+            status_notifier: None,
+            _scope: PhantomData,
+        }
+    }
+
+    /// Sets the number of failed submissions after which a batch is dead-lettered, even if the
+    /// failures were retryable
+    pub fn with_max_failed_submissions(mut self, max_failed_submissions: u32) -> Self {
+        self.max_failed_submissions = i64::from(max_failed_submissions);
+        self
+    }
+
+    /// Sets the broadcaster that batch status changes are published to
+    #[cfg(feature = "rest-api-resources-event-stream")]
+    pub fn with_event_broadcaster(mut self, event_broadcaster: EventBroadcaster) -> Self {
+        self.event_broadcaster = Some(event_broadcaster);
+        self
+    }
+
+    /// Sets the notifier that is notified whenever a batch status is recorded
+    #[cfg(feature = "batch-tracking-wait")]
+    pub fn with_status_notifier(mut self, status_notifier: BatchStatusNotifier) -> Self {
+        self.status_notifier = Some(status_notifier);
+        self
+    }
+
+    fn record(
+        &self,
+        batch_header: &str,
+        service_id: &str,
+        status: Option<u16>,
+        message: Option<String>,
+    ) {
+        let store = self.store_factory.get_batch_tracking_store();
+
+        #[cfg(feature = "rest-api-resources-event-stream")]
+        let previous_status = match self.event_broadcaster {
+            Some(_) => batch_status_name(&*store, batch_header, service_id),
+            None => None,
+        };
+
+        let result = match status {
+            // 0 signifies that the batch is about to be submitted
+            Some(0) => {
+                debug!("Batch {}: submitting", batch_header);
+                return;
+            }
+            Some(code) if (200..300).contains(&code) => store.change_batch_to_submitted(
+                batch_header,
+                service_id,
+                Vec::new(),
+                Some(&BatchStatus::Pending.to_string()),
+                None,
+            ),
+            _ => {
+                let error_type = submission_error_type(status);
+                let submission_error = match build_submission_error(error_type, status, message) {
+                    Ok(err) => err,
+                    Err(err) => {
+                        error!("Batch {}: {}", batch_header, err);
+                        return;
+                    }
+                };
+
+                let message = submission_error.error_message().to_string();
+                let batch_status = match error_type {
+                    SubmissionErrorType::Retryable => {
+                        warn!(
+                            "Batch {}: submission failed, will retry: {}",
+                            batch_header, message
+                        );
+                        BatchStatus::Delayed
+                    }
+                    SubmissionErrorType::Permanent => {
+                        error!("Batch {}: submission rejected: {}", batch_header, message);
+                        BatchStatus::Invalid(Vec::new())
+                    }
+                };
+
+                self.record_failure(
+                    batch_header,
+                    service_id,
+                    batch_status,
+                    submission_error,
+                    error_type,
+                )
+            }
+        };
+
+        if let Err(err) = result {
+            error!(
+                "Batch {}: unable to record submission update: {}",
+                batch_header, err
+            );
+        }
+
+        #[cfg(feature = "rest-api-resources-event-stream")]
+        self.publish_status_change(&*store, batch_header, service_id, previous_status);
+
+        #[cfg(feature = "batch-tracking-wait")]
+        if let Some(ref status_notifier) = self.status_notifier {
+            status_notifier.notify();
+        }
+    }
+
+    /// Records a failed submission, dead-lettering the batch if it was rejected or has failed to be
+    /// submitted the maximum number of times.
+    ///
+    /// Both are written in one transaction, so that a batch is never left `Invalid` or out of
+    /// retries without being dead-lettered.
+    fn record_failure(
+        &self,
+        batch_header: &str,
+        service_id: &str,
+        batch_status: BatchStatus,
+        submission_error: SubmissionError,
+        error_type: SubmissionErrorType,
+    ) -> Result<(), BatchTrackingStoreError> {
+        let message = submission_error.error_message().to_string();
+
+        let txn = self
+            .store_factory
+            .begin_transaction()
+            .map_err(BatchTrackingStoreError::InternalError)?;
+        let store = txn.get_batch_tracking_store();
+
+        let result = store
+            .update_batch_status(
+                batch_header,
+                service_id,
+                Some(batch_status),
+                Vec::new(),
+                Some(submission_error),
+            )
+            .and_then(|_| match error_type {
+                SubmissionErrorType::Permanent => store.dead_letter_batch(
+                    batch_header,
+                    service_id,
+                    &format!("rejected: {}", message),
+                ),
+                SubmissionErrorType::Retryable => {
+                    self.dead_letter_if_exhausted(&*store, batch_header, service_id)
+                }
+            });
+
+        match result {
+            Ok(()) => txn.commit().map_err(BatchTrackingStoreError::InternalError),
+            Err(err) => {
+                if let Err(rollback_err) = txn.rollback() {
+                    error!(
+                        "Batch {}: unable to roll back submission update: {}",
+                        batch_header, rollback_err
+                    );
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Publishes the status of a batch to the event stream, if it has changed
+    #[cfg(feature = "rest-api-resources-event-stream")]
+    fn publish_status_change(
+        &self,
+        store: &dyn BatchTrackingStore,
+        batch_header: &str,
+        service_id: &str,
+        previous_status: Option<String>,
+    ) {
+        let event_broadcaster = match self.event_broadcaster {
+            Some(ref event_broadcaster) => event_broadcaster,
+            None => return,
+        };
+
+        let status = match batch_status_name(store, batch_header, service_id) {
+            Some(status) if Some(&status) != previous_status.as_ref() => status,
+            _ => return,
+        };
+
+        let service_id = Some(service_id).filter(|id| *id != NON_SPLINTER_SERVICE_ID_DEFAULT);
+
+        if let Err(err) = event_broadcaster.publish(StreamEvent::batch_status(
+            batch_header,
+            service_id,
+            previous_status.as_deref(),
+            &status,
+        )) {
+            error!(
+                "Batch {}: unable to publish status change: {}",
+                batch_header, err
+            );
+        }
+    }
+
+    /// Dead-letters a batch if it has failed to be submitted the maximum number of times
+    fn dead_letter_if_exhausted(
+        &self,
+        store: &dyn BatchTrackingStore,
+        batch_header: &str,
+        service_id: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        let submission_error = match store
+            .get_batch(batch_header, service_id)?
+            .and_then(|batch| batch.submission_error().cloned())
+        {
+            Some(submission_error) => submission_error,
+            None => return Ok(()),
+        };
+
+        if submission_error.attempts() < self.max_failed_submissions {
+            return Ok(());
+        }
+
+        error!(
+            "Batch {}: giving up after {} failed submissions",
+            batch_header,
+            submission_error.attempts()
+        );
+        store.dead_letter_batch(
+            batch_header,
+            service_id,
+            &format!(
+                "exhausted retries after {} failed submissions: {}",
+                submission_error.attempts(),
+                submission_error.error_message()
+            ),
+        )
+    }
+}
+
+/// Gets the name of the current status of a batch
+#[cfg(feature = "rest-api-resources-event-stream")]
+fn batch_status_name(
+    store: &dyn BatchTrackingStore,
+    batch_header: &str,
+    service_id: &str,
+) -> Option<String> {
+    match store.get_batch(batch_header, service_id) {
+        Ok(batch) => batch.and_then(|batch| batch.batch_status().map(|status| status.to_string())),
+        Err(err) => {
+            error!("Batch {}: unable to get status: {}", batch_header, err);
+            None
+        }
+    }
+}
+
+/// Determines whether a failed submission may be retried, based on the HTTP status of the
+/// response.
+///
+/// Request timeouts (408), rate limiting (429) and server errors (5xx) are transient, as is a
+/// failure to get a response at all. Any other client error (4xx) means the batch was rejected.
+fn submission_error_type(status: Option<u16>) -> SubmissionErrorType {
+    match status {
+        Some(408) | Some(429) => SubmissionErrorType::Retryable,
+        Some(code) if (400..500).contains(&code) => SubmissionErrorType::Permanent,
+        _ => SubmissionErrorType::Retryable,
+    }
+}
+
+fn build_submission_error(
+    error_type: SubmissionErrorType,
+    status: Option<u16>,
+    message: Option<String>,
+) -> Result<SubmissionError, BatchBuilderError> {
+    let message = message
+        .filter(|msg| !msg.is_empty())
+        .unwrap_or_else(|| "Batch submission failed".to_string());
+    let error_message = match status {
+        Some(code) => format!("HTTP {}: {}", code, message),
+        None => message,
+    };
+
+    SubmissionErrorBuilder::default()
+        .with_error_type(error_type.to_string())
+        .with_error_message(error_message)
+        .build()
+}
+
+impl SubmitterObserver for BatchTrackingObserver<GlobalScopeId> {
+    type Id = GlobalScopeId;
+
+    fn notify(
+        &self,
+        batch_header: String,
+        _scope_id: GlobalScopeId,
+        status: Option<u16>,
+        message: Option<String>,
+    ) {
+        self.record(
+            &batch_header,
+            NON_SPLINTER_SERVICE_ID_DEFAULT,
+            status,
+            message,
+        )
+    }
+}
+
+impl SubmitterObserver for BatchTrackingObserver<ServiceScopeId> {
+    type Id = ServiceScopeId;
+
+    fn notify(
+        &self,
+        batch_header: String,
+        scope_id: ServiceScopeId,
+        status: Option<u16>,
+        message: Option<String>,
+    ) {
+        self.record(
+            &batch_header,
+            &scope_id.service_id().to_string(),
+            status,
+            message,
+        )
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::batch_tracking::test_utils::{add_tracking_batch, create_store_factory};
+
+    /// Verify that a successful submission marks the batch as submitted with a `Pending`
+    /// status and no submission error.
+    #[test]
+    fn test_observer_records_submitted_batch() {
+        let store_factory = create_store_factory();
+        let batch = add_tracking_batch(&*store_factory);
+
+        let observer = BatchTrackingObserver::<GlobalScopeId>::new(store_factory.clone_box());
+        observer.notify(
+            batch.batch_header().to_string(),
+            GlobalScopeId::new(),
+            Some(200),
+            Some("ok".to_string()),
+        );
+
+        let stored = store_factory
+            .get_batch_tracking_store()
+            .get_batch(batch.batch_header(), NON_SPLINTER_SERVICE_ID_DEFAULT)
+            .expect("Failed to get batch")
+            .expect("Batch not found");
+
+        assert!(stored.submitted());
+        assert_eq!(stored.batch_status(), Some(&BatchStatus::Pending));
+        assert!(stored.submission_error().is_none());
+    }
+
+    /// Verify that a retryable failure leaves the batch unsubmitted and `Delayed`, and records
+    /// the response as a retryable submission error.
+    #[test]
+    fn test_observer_records_retryable_error() {
+        let store_factory = create_store_factory();
+        let batch = add_tracking_batch(&*store_factory);
+
+        let observer = BatchTrackingObserver::<GlobalScopeId>::new(store_factory.clone_box());
+        observer.notify(
+            batch.batch_header().to_string(),
+            GlobalScopeId::new(),
+            Some(503),
+            Some("Service Unavailable".to_string()),
+        );
+
+        let stored = store_factory
+            .get_batch_tracking_store()
+            .get_batch(batch.batch_header(), NON_SPLINTER_SERVICE_ID_DEFAULT)
+            .expect("Failed to get batch")
+            .expect("Batch not found");
+
+        assert!(!stored.submitted());
+        assert_eq!(stored.batch_status(), Some(&BatchStatus::Delayed));
+        let error = stored
+            .submission_error()
+            .expect("Submission error not recorded");
+        assert!(error.is_retryable());
+        assert_eq!(error.error_type(), "Retryable");
+        assert_eq!(error.error_message(), "HTTP 503: Service Unavailable");
+    }
+
+    /// Verify that a permanent failure marks the batch as submitted and `Invalid`, so that it
+    /// is not resubmitted, and records the response as a permanent submission error.
+    #[test]
+    fn test_observer_records_permanent_error() {
+        let store_factory = create_store_factory();
+        let batch = add_tracking_batch(&*store_factory);
+
+        let observer = BatchTrackingObserver::<GlobalScopeId>::new(store_factory.clone_box());
+        observer.notify(
+            batch.batch_header().to_string(),
+            GlobalScopeId::new(),
+            Some(400),
+            Some("Bad Request".to_string()),
+        );
+
+        let store = store_factory.get_batch_tracking_store();
+        let stored = store
+            .get_batch(batch.batch_header(), NON_SPLINTER_SERVICE_ID_DEFAULT)
+            .expect("Failed to get batch")
+            .expect("Batch not found");
+
+        assert!(stored.submitted());
+        assert_eq!(stored.batch_status(), Some(&BatchStatus::Invalid(vec![])));
+        let error = stored
+            .submission_error()
+            .expect("Submission error not recorded");
+        assert!(!error.is_retryable());
+        assert_eq!(error.error_type(), "Permanent");
+        assert_eq!(error.error_message(), "HTTP 400: Bad Request");
+
+        assert!(store
+            .get_unsubmitted_batches()
+            .expect("Failed to get unsubmitted batches")
+            .batches
+            .is_empty());
+
+        let dead_letters = store
+            .list_dead_letter_batches(None)
+            .expect("Failed to list dead-lettered batches");
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].reason(), "rejected: HTTP 400: Bad Request");
+    }
+
+    /// Verify that a batch is dead-lettered once it has failed to be submitted the maximum
+    /// number of times, even if the failures were retryable.
+    #[test]
+    fn test_observer_dead_letters_exhausted_batch() {
+        let store_factory = create_store_factory();
+        let batch = add_tracking_batch(&*store_factory);
+
+        let observer = BatchTrackingObserver::<GlobalScopeId>::new(store_factory.clone_box())
+            .with_max_failed_submissions(2);
+        let store = store_factory.get_batch_tracking_store();
+
+        observer.notify(
+            batch.batch_header().to_string(),
+            GlobalScopeId::new(),
+            Some(503),
+            Some("Service Unavailable".to_string()),
+        );
+        assert!(store
+            .list_dead_letter_batches(None)
+            .expect("Failed to list dead-lettered batches")
+            .is_empty());
+
+        observer.notify(
+            batch.batch_header().to_string(),
+            GlobalScopeId::new(),
+            Some(503),
+            Some("Service Unavailable".to_string()),
+        );
+        let dead_letters = store
+            .list_dead_letter_batches(None)
+            .expect("Failed to list dead-lettered batches");
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(
+            dead_letters[0].reason(),
+            "exhausted retries after 2 failed submissions: HTTP 503: Service Unavailable"
+        );
+        assert!(store
+            .get_unsubmitted_batches()
+            .expect("Failed to get unsubmitted batches")
+            .batches
+            .is_empty());
+    }
+
+    /// Verify the classification of failed submissions as retryable or permanent.
+    #[test]
+    fn test_submission_error_type() {
+        assert_eq!(submission_error_type(None), SubmissionErrorType::Retryable);
+        assert_eq!(
+            submission_error_type(Some(408)),
+            SubmissionErrorType::Retryable
+        );
+        assert_eq!(
+            submission_error_type(Some(429)),
+            SubmissionErrorType::Retryable
+        );
+        assert_eq!(
+            submission_error_type(Some(500)),
+            SubmissionErrorType::Retryable
+        );
+        assert_eq!(
+            submission_error_type(Some(503)),
+            SubmissionErrorType::Retryable
+        );
+        assert_eq!(
+            submission_error_type(Some(400)),
+            SubmissionErrorType::Permanent
+        );
+        assert_eq!(
+            submission_error_type(Some(404)),
+            SubmissionErrorType::Permanent
+        );
+    }
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod batch_tracking_observer;
+
 use crate::scope_id::ScopeId;
 
 /// An interface for interpreting and recording updates from the submitter
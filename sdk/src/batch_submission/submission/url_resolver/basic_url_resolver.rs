@@ -60,6 +60,30 @@ impl UrlResolver for ServiceUrlResolver {
     }
 }
 
+/// A url resolver for the `ServiceScopeId` that targets the batch endpoint of a scabbard service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScabbardUrlResolver {
+    base_url: String,
+}
+
+impl ScabbardUrlResolver {
+    pub fn new(base_url: String) -> Self {
+        Self { base_url }
+    }
+}
+
+impl UrlResolver for ScabbardUrlResolver {
+    type Id = ServiceScopeId;
+    fn url(&self, scope_id: &ServiceScopeId) -> String {
+        format!(
+            "{base_url}/scabbard/{circuit_id}/{service_id}/batches",
+            base_url = self.base_url,
+            circuit_id = scope_id.service_id().circuit_id(),
+            service_id = scope_id.service_id().service_id()
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,4 +106,15 @@ mod tests {
             "test.com?service_id=12345-67890::abcd".to_string()
         );
     }
+
+    #[test]
+    fn test_batch_submission_scabbard_url_resolver() {
+        let resolver = ScabbardUrlResolver::new("test.com".to_string());
+        let scope_id = ServiceScopeId::new_from_string("12345-67890::abcd".to_string()).unwrap();
+
+        assert_eq!(
+            resolver.url(&scope_id),
+            "test.com/scabbard/12345-67890/abcd/batches".to_string()
+        );
+    }
 }
@@ -12,4 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "batch-tracking-wait")]
+pub mod notifier;
+#[cfg(feature = "batch-tracking-retention")]
+pub mod retention;
+#[cfg(feature = "batch-tracking-status-poller")]
+pub mod status_poller;
 pub mod store;
+#[allow(dead_code)]
+#[cfg(all(test, feature = "sqlite"))]
+pub(crate) mod test_utils;
+#[cfg(feature = "batch-tracking-webhook")]
+pub mod webhook;
@@ -0,0 +1,62 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Notification of batch status changes within a process
+//!
+//! Requests waiting for batches to reach a final status subscribe to a `BatchStatusNotifier`,
+//! which is notified whenever a status is recorded in the `BatchTrackingStore`, so that they can
+//! read the new status as soon as it is written instead of polling the store.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use tokio::sync::watch;
+
+/// Wakes the subscribers waiting on batch status changes
+#[derive(Clone)]
+pub struct BatchStatusNotifier {
+    changes: Arc<AtomicU64>,
+    sender: Arc<watch::Sender<u64>>,
+}
+
+impl BatchStatusNotifier {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(0);
+        Self {
+            changes: Arc::new(AtomicU64::new(0)),
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Notifies the subscribers that a batch status has changed
+    pub fn notify(&self) {
+        let changes = self.changes.fetch_add(1, Ordering::SeqCst) + 1;
+        // An error only means that no one is waiting
+        let _ = self.sender.send(changes);
+    }
+
+    /// Subscribes to status changes. A change made after subscribing is seen by the receiver,
+    /// even if it is made before the receiver waits for it.
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for BatchStatusNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
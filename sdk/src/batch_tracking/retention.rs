@@ -0,0 +1,497 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Retention of the records of tracked batches
+//!
+//! Batches stay in the `BatchTrackingStore` after they reach a terminal status, along with their
+//! serialized contents. The `RetentionTask` periodically applies a `RetentionPolicy` to these
+//! batches: batches older than the maximum age for their status are removed, and batches older
+//! than the compaction age have their serialized batch and transaction payloads dropped while
+//! their status, receipts and other metadata are kept.
+//!
+//! The age of a batch is measured from the time it was added to the store. Dead-lettered batches
+//! are neither removed nor compacted until they are replayed or discarded.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc::{channel, Sender},
+    Arc,
+};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::batch_tracking::store::{BatchStatusName, BatchTrackingStore};
+use crate::error::InternalError;
+use crate::store::TransactionalStoreFactory;
+use crate::threading::lifecycle::ShutdownHandle;
+use crate::threading::pacemaker::Pacemaker;
+
+const DEFAULT_INTERVAL: u64 = 3600;
+const DEFAULT_BATCH_LIMIT: i64 = 1000;
+
+/// The statuses after which a batch's status no longer changes
+const TERMINAL_STATUSES: [BatchStatusName; 2] =
+    [BatchStatusName::Committed, BatchStatusName::Invalid];
+
+/// How long the records of batches that reached a terminal status are kept
+///
+/// By default nothing is removed or compacted. A status without a maximum age is kept
+/// indefinitely, so failed batches can be kept longer than committed ones, for investigation, by
+/// giving them a longer maximum age or none at all.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    committed_max_age: Option<Duration>,
+    invalid_max_age: Option<Duration>,
+    compaction_age: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the age after which committed batches are removed
+    pub fn with_committed_max_age(mut self, max_age: Duration) -> Self {
+        self.committed_max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the age after which invalid batches are removed
+    pub fn with_invalid_max_age(mut self, max_age: Duration) -> Self {
+        self.invalid_max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the age after which the payloads of committed and invalid batches are dropped
+    pub fn with_compaction_age(mut self, compaction_age: Duration) -> Self {
+        self.compaction_age = Some(compaction_age);
+        self
+    }
+
+    pub fn committed_max_age(&self) -> Option<Duration> {
+        self.committed_max_age
+    }
+
+    pub fn invalid_max_age(&self) -> Option<Duration> {
+        self.invalid_max_age
+    }
+
+    pub fn compaction_age(&self) -> Option<Duration> {
+        self.compaction_age
+    }
+
+    /// Returns true if the policy neither removes nor compacts any batches
+    pub fn is_empty(&self) -> bool {
+        self.committed_max_age.is_none()
+            && self.invalid_max_age.is_none()
+            && self.compaction_age.is_none()
+    }
+
+    fn max_age(&self, status: BatchStatusName) -> Option<Duration> {
+        match status {
+            BatchStatusName::Committed => self.committed_max_age,
+            BatchStatusName::Invalid => self.invalid_max_age,
+            _ => None,
+        }
+    }
+}
+
+/// Counts of the records changed by the `RetentionTask` since it was started
+///
+/// The totals are also logged after every pass.
+#[derive(Clone, Default)]
+pub struct RetentionMetrics {
+    inner: Arc<RetentionCounters>,
+}
+
+#[derive(Default)]
+struct RetentionCounters {
+    passes: AtomicU64,
+    failed_passes: AtomicU64,
+    committed_batches_purged: AtomicU64,
+    invalid_batches_purged: AtomicU64,
+    batches_compacted: AtomicU64,
+}
+
+impl RetentionMetrics {
+    /// The number of times the policy has been applied
+    pub fn passes(&self) -> u64 {
+        self.inner.passes.load(Ordering::SeqCst)
+    }
+
+    /// The number of times the policy could not be applied completely because of a store error
+    pub fn failed_passes(&self) -> u64 {
+        self.inner.failed_passes.load(Ordering::SeqCst)
+    }
+
+    pub fn committed_batches_purged(&self) -> u64 {
+        self.inner.committed_batches_purged.load(Ordering::SeqCst)
+    }
+
+    pub fn invalid_batches_purged(&self) -> u64 {
+        self.inner.invalid_batches_purged.load(Ordering::SeqCst)
+    }
+
+    pub fn batches_compacted(&self) -> u64 {
+        self.inner.batches_compacted.load(Ordering::SeqCst)
+    }
+
+    fn log(&self) {
+        info!(
+            "Batch retention totals after {} passes ({} failed): removed {} committed and {} \
+            invalid batches, compacted {} batches",
+            self.passes(),
+            self.failed_passes(),
+            self.committed_batches_purged(),
+            self.invalid_batches_purged(),
+            self.batches_compacted()
+        );
+    }
+
+    fn add_purged(&self, status: BatchStatusName, count: u64) {
+        let counter = match status {
+            BatchStatusName::Committed => &self.inner.committed_batches_purged,
+            _ => &self.inner.invalid_batches_purged,
+        };
+        counter.fetch_add(count, Ordering::SeqCst);
+    }
+}
+
+pub enum RetentionMessage {
+    Run,
+    Shutdown,
+}
+
+/// Builds and starts a `RetentionTask`
+pub struct RetentionTaskBuilder {
+    store_factory: Box<dyn TransactionalStoreFactory>,
+    policy: RetentionPolicy,
+    interval: u64,
+    batch_limit: i64,
+}
+
+impl RetentionTaskBuilder {
+    pub fn new(store_factory: Box<dyn TransactionalStoreFactory>, policy: RetentionPolicy) -> Self {
+        Self {
+            store_factory,
+            policy,
+            interval: DEFAULT_INTERVAL,
+            batch_limit: DEFAULT_BATCH_LIMIT,
+        }
+    }
+
+    /// Sets the interval, in seconds, at which the policy is applied
+    pub fn with_interval(mut self, interval: u64) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the maximum number of batches removed or compacted per status in a single
+    /// transaction
+    pub fn with_batch_limit(mut self, batch_limit: i64) -> Self {
+        self.batch_limit = batch_limit;
+        self
+    }
+
+    /// Starts the retention thread and the pacemaker that wakes it up
+    pub fn start(self) -> Result<RetentionTask, InternalError> {
+        if self.batch_limit < 1 {
+            return Err(InternalError::with_message(
+                "Retention batch limit must be greater than 0".into(),
+            ));
+        }
+
+        let (sender, recv) = channel();
+        let store_factory = self.store_factory;
+        let policy = self.policy;
+        let batch_limit = self.batch_limit;
+        let metrics = RetentionMetrics::default();
+        let thread_metrics = metrics.clone();
+
+        let join_handle = thread::Builder::new()
+            .name("Batch Retention".into())
+            .spawn(move || loop {
+                match recv.recv() {
+                    Ok(RetentionMessage::Run) => {
+                        apply_policy(
+                            &*store_factory.get_batch_tracking_store(),
+                            &policy,
+                            batch_limit,
+                            now(),
+                            &thread_metrics,
+                        );
+                        thread_metrics.log();
+                    }
+                    Ok(RetentionMessage::Shutdown) => break,
+                    Err(_) => {
+                        warn!("All senders have disconnected");
+                        break;
+                    }
+                }
+            })
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let pacemaker = Pacemaker::builder()
+            .with_interval(self.interval)
+            .with_sender(sender.clone())
+            .with_message_factory(|| RetentionMessage::Run)
+            .start()?;
+
+        Ok(RetentionTask {
+            join_handle,
+            sender,
+            pacemaker,
+            metrics,
+        })
+    }
+}
+
+/// Applies a `RetentionPolicy` to the batch tracking store on a background thread
+pub struct RetentionTask {
+    join_handle: thread::JoinHandle<()>,
+    sender: Sender<RetentionMessage>,
+    pacemaker: Pacemaker,
+    metrics: RetentionMetrics,
+}
+
+impl RetentionTask {
+    /// Returns the counts of the records changed by the task, which are updated after every pass
+    pub fn metrics(&self) -> RetentionMetrics {
+        self.metrics.clone()
+    }
+}
+
+impl ShutdownHandle for RetentionTask {
+    fn signal_shutdown(&mut self) {
+        self.pacemaker.shutdown_signaler().shutdown();
+        if self.sender.send(RetentionMessage::Shutdown).is_err() {
+            warn!("Batch retention thread has already shut down");
+        }
+    }
+
+    fn wait_for_shutdown(self) -> Result<(), InternalError> {
+        self.pacemaker.await_shutdown();
+        self.join_handle.join().map_err(|_| {
+            InternalError::with_message("Batch retention thread did not shutdown correctly".into())
+        })
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+// Removes the expired batches, then compacts the remaining old ones. Each status is processed
+// in chunks of `batch_limit` until none is left.
+fn apply_policy(
+    store: &dyn BatchTrackingStore,
+    policy: &RetentionPolicy,
+    batch_limit: i64,
+    now: i64,
+    metrics: &RetentionMetrics,
+) {
+    metrics.inner.passes.fetch_add(1, Ordering::SeqCst);
+
+    let mut purged = 0;
+    let mut compacted = 0;
+
+    for status in TERMINAL_STATUSES.iter() {
+        if let Some(max_age) = policy.max_age(*status) {
+            match drain(batch_limit, |limit| {
+                store.purge_batches(*status, cutoff(now, max_age), limit)
+            }) {
+                Ok(count) => {
+                    metrics.add_purged(*status, count);
+                    purged += count;
+                }
+                Err(err) => {
+                    error!("Unable to remove expired {} batches: {}", status, err);
+                    metrics.inner.failed_passes.fetch_add(1, Ordering::SeqCst);
+                    return;
+                }
+            }
+        }
+    }
+
+    if let Some(compaction_age) = policy.compaction_age {
+        for status in TERMINAL_STATUSES.iter() {
+            match drain(batch_limit, |limit| {
+                store.compact_batches(*status, cutoff(now, compaction_age), limit)
+            }) {
+                Ok(count) => {
+                    metrics
+                        .inner
+                        .batches_compacted
+                        .fetch_add(count, Ordering::SeqCst);
+                    compacted += count;
+                }
+                Err(err) => {
+                    error!("Unable to compact {} batches: {}", status, err);
+                    metrics.inner.failed_passes.fetch_add(1, Ordering::SeqCst);
+                    return;
+                }
+            }
+        }
+    }
+
+    if purged > 0 || compacted > 0 {
+        info!(
+            "Batch retention removed {} and compacted {} batches",
+            purged, compacted
+        );
+    } else {
+        debug!("Batch retention found no batches to remove or compact");
+    }
+}
+
+// Repeats an operation on chunks of at most `batch_limit` records until a chunk is not full,
+// returning the total number of records changed
+fn drain<E, F>(batch_limit: i64, mut operation: F) -> Result<u64, E>
+where
+    F: FnMut(i64) -> Result<usize, E>,
+{
+    let mut total = 0;
+    loop {
+        let count = operation(batch_limit)?;
+        total += count as u64;
+        if (count as i64) < batch_limit {
+            return Ok(total);
+        }
+    }
+}
+
+fn cutoff(now: i64, age: Duration) -> i64 {
+    now.saturating_sub(age.as_secs() as i64)
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::batch_tracking::store::{BatchStatus, NON_SPLINTER_SERVICE_ID_DEFAULT};
+    use crate::batch_tracking::test_utils::{
+        add_built_batch, create_store_factory, tracking_batch_builder,
+    };
+
+    /// Verify that expired committed batches are removed while invalid batches, which have no
+    /// maximum age, are kept and only compacted.
+    #[test]
+    fn test_apply_policy() {
+        let store_factory = create_store_factory();
+        let store = store_factory.get_batch_tracking_store();
+
+        let committed = add_submitted_batch(&*store_factory);
+        let invalid = add_submitted_batch(&*store_factory);
+
+        for (batch_id, status) in &[
+            (&committed, BatchStatus::Committed(vec![])),
+            (&invalid, BatchStatus::Invalid(vec![])),
+        ] {
+            store
+                .update_batch_status(
+                    batch_id,
+                    NON_SPLINTER_SERVICE_ID_DEFAULT,
+                    Some(status.clone()),
+                    vec![],
+                    None,
+                )
+                .expect("Failed to update batch status");
+        }
+
+        let policy = RetentionPolicy::new()
+            .with_committed_max_age(Duration::from_secs(50))
+            .with_compaction_age(Duration::from_secs(50));
+        let metrics = RetentionMetrics::default();
+
+        // Nothing has expired yet
+        apply_policy(&*store, &policy, 1, now(), &metrics);
+
+        assert_eq!(metrics.passes(), 1);
+        assert_eq!(metrics.committed_batches_purged(), 0);
+        assert_eq!(metrics.batches_compacted(), 0);
+
+        // A limit of 1 checks that every expired batch is processed in a single pass
+        let later = now() + 100;
+        apply_policy(&*store, &policy, 1, later, &metrics);
+
+        assert_eq!(metrics.passes(), 2);
+        assert_eq!(metrics.failed_passes(), 0);
+        assert_eq!(metrics.committed_batches_purged(), 1);
+        assert_eq!(metrics.invalid_batches_purged(), 0);
+        assert_eq!(metrics.batches_compacted(), 1);
+
+        // Nothing is left to do on the next pass
+        apply_policy(&*store, &policy, 1, later, &metrics);
+
+        assert_eq!(metrics.passes(), 3);
+        assert_eq!(metrics.committed_batches_purged(), 1);
+        assert_eq!(metrics.batches_compacted(), 1);
+    }
+
+    /// Verify that a dead-lettered invalid batch is neither removed nor compacted, so that it can
+    /// still be listed and replayed, while other expired invalid batches are removed.
+    #[test]
+    fn test_apply_policy_keeps_dead_lettered_batches() {
+        let store_factory = create_store_factory();
+        let store = store_factory.get_batch_tracking_store();
+
+        let invalid = add_submitted_batch(&*store_factory);
+        let dead_lettered = add_submitted_batch(&*store_factory);
+
+        store
+            .update_batch_status(
+                &invalid,
+                NON_SPLINTER_SERVICE_ID_DEFAULT,
+                Some(BatchStatus::Invalid(vec![])),
+                vec![],
+                None,
+            )
+            .expect("Failed to update batch status");
+        store
+            .dead_letter_batch(&dead_lettered, NON_SPLINTER_SERVICE_ID_DEFAULT, "rejected")
+            .expect("Failed to dead-letter batch");
+
+        let policy = RetentionPolicy::new()
+            .with_invalid_max_age(Duration::from_secs(50))
+            .with_compaction_age(Duration::from_secs(50));
+        let metrics = RetentionMetrics::default();
+
+        apply_policy(&*store, &policy, 1, now() + 100, &metrics);
+
+        assert_eq!(metrics.failed_passes(), 0);
+        assert_eq!(metrics.invalid_batches_purged(), 1);
+        assert_eq!(metrics.batches_compacted(), 0);
+
+        assert!(store
+            .get_batch(&invalid, NON_SPLINTER_SERVICE_ID_DEFAULT)
+            .expect("Failed to get batch")
+            .is_none());
+
+        let dead_letters = store
+            .list_dead_letter_batches(None)
+            .expect("Failed to list dead-lettered batches");
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].batch().batch_header(), dead_lettered);
+        assert!(!dead_letters[0].batch().serialized_batch().is_empty());
+    }
+
+    fn add_submitted_batch(store_factory: &dyn TransactionalStoreFactory) -> String {
+        add_built_batch(store_factory, tracking_batch_builder().with_submitted(true))
+            .batch_header()
+            .to_string()
+    }
+}
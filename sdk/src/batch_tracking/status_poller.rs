@@ -0,0 +1,669 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Polling of the statuses of submitted batches
+//!
+//! A batch that has been accepted by the node it was submitted to is recorded as `Pending`. The
+//! `StatusPoller` periodically asks the node for the statuses of the batches that have not yet
+//! reached a final status, and records those that have changed with `update_batch_status`, which
+//! also queues their webhook notifications. If an `EventBroadcaster` is set, every change is
+//! published to the clients of the event stream, and if a `BatchStatusNotifier` is set, it is
+//! notified of every change, waking the requests waiting on batch statuses.
+//!
+//! A batch the node still reports as pending, or does not know of, is asked about again on the
+//! next poll.
+
+use std::cmp::min;
+use std::collections::BTreeMap;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "batch-tracking-wait")]
+use crate::batch_tracking::notifier::BatchStatusNotifier;
+use crate::batch_tracking::store::{
+    BatchStatus, BatchTrackingStore, TransactionReceipt, TransactionReceiptBuilder,
+    NON_SPLINTER_SERVICE_ID_DEFAULT,
+};
+use crate::error::InternalError;
+#[cfg(feature = "rest-api-resources-event-stream")]
+use crate::rest_api::resources::event_stream::v1::{EventBroadcaster, StreamEvent};
+use crate::scope_id::FullyQualifiedServiceId;
+use crate::store::TransactionalStoreFactory;
+use crate::threading::lifecycle::ShutdownHandle;
+
+const DEFAULT_POLLING_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// The number of batch IDs asked about in one request, which keeps the request's URL short
+const MAX_IDS_PER_REQUEST: usize = 25;
+// The longest the poller thread sleeps before checking whether it should shut down
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The node that batches are submitted to, which is asked for their statuses
+#[derive(Clone, Debug)]
+pub enum StatusNode {
+    /// The REST API of a Sawtooth validator
+    Sawtooth { url: String },
+    /// A Splinter node running the scabbard services batches are submitted to, along with the
+    /// value of the `Authorization` header sent to it, if any
+    Splinter {
+        url: String,
+        authorization: Option<String>,
+    },
+}
+
+/// Builds and starts a `StatusPoller`
+pub struct StatusPollerBuilder {
+    store_factory: Box<dyn TransactionalStoreFactory>,
+    node: StatusNode,
+    polling_interval: Duration,
+    request_timeout: Duration,
+    #[cfg(feature = "rest-api-resources-event-stream")]
+    event_broadcaster: Option<EventBroadcaster>,
+    #[cfg(feature = "batch-tracking-wait")]
+    status_notifier: Option<BatchStatusNotifier>,
+}
+
+impl StatusPollerBuilder {
+    pub fn new(store_factory: Box<dyn TransactionalStoreFactory>, node: StatusNode) -> Self {
+        Self {
+            store_factory,
+            node,
+            polling_interval: DEFAULT_POLLING_INTERVAL,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            #[cfg(feature = "rest-api-resources-event-stream")]
+            event_broadcaster: None,
+            #[cfg(feature = "batch-tracking-wait")]
+            status_notifier: None,
+        }
+    }
+
+    /// Sets how often the node is asked for the statuses of the batches
+    pub fn with_polling_interval(mut self, polling_interval: Duration) -> Self {
+        self.polling_interval = polling_interval;
+        self
+    }
+
+    /// Sets the timeout of a single request to the node
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Sets the broadcaster that batch status changes are published to
+    #[cfg(feature = "rest-api-resources-event-stream")]
+    pub fn with_event_broadcaster(mut self, event_broadcaster: EventBroadcaster) -> Self {
+        self.event_broadcaster = Some(event_broadcaster);
+        self
+    }
+
+    /// Sets the notifier that is notified whenever a batch status changes
+    #[cfg(feature = "batch-tracking-wait")]
+    pub fn with_status_notifier(mut self, status_notifier: BatchStatusNotifier) -> Self {
+        self.status_notifier = Some(status_notifier);
+        self
+    }
+
+    /// Starts the poller thread
+    pub fn start(self) -> Result<StatusPoller, InternalError> {
+        let polling_interval = self.polling_interval;
+        let poller = self.build_poller()?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+
+        let join_handle = thread::Builder::new()
+            .name("Batch Status Poller".into())
+            .spawn(move || {
+                while thread_running.load(Ordering::SeqCst) {
+                    poller.poll();
+
+                    let start = Instant::now();
+                    while thread_running.load(Ordering::SeqCst)
+                        && start.elapsed() < polling_interval
+                    {
+                        thread::sleep(min(SHUTDOWN_CHECK_INTERVAL, polling_interval));
+                    }
+                }
+            })
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        Ok(StatusPoller {
+            running,
+            join_handle,
+        })
+    }
+
+    fn build_poller(self) -> Result<Poller, InternalError> {
+        let client = Client::builder()
+            .timeout(self.request_timeout)
+            .build()
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        Ok(Poller {
+            store_factory: self.store_factory,
+            node: self.node,
+            client,
+            #[cfg(feature = "rest-api-resources-event-stream")]
+            event_broadcaster: self.event_broadcaster,
+            #[cfg(feature = "batch-tracking-wait")]
+            status_notifier: self.status_notifier,
+        })
+    }
+}
+
+/// Records the statuses of submitted batches, as reported by the node they were submitted to, on
+/// a background thread
+pub struct StatusPoller {
+    running: Arc<AtomicBool>,
+    join_handle: thread::JoinHandle<()>,
+}
+
+impl ShutdownHandle for StatusPoller {
+    fn signal_shutdown(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    fn wait_for_shutdown(self) -> Result<(), InternalError> {
+        self.join_handle.join().map_err(|_| {
+            InternalError::with_message(
+                "Batch status poller thread did not shutdown correctly".into(),
+            )
+        })
+    }
+}
+
+/// The status of a batch as reported by the node
+struct PolledStatus {
+    batch_id: String,
+    // The status the batch has reached, if it has reached one that is recorded
+    status: Option<BatchStatus>,
+    receipts: Vec<TransactionReceipt>,
+}
+
+struct Poller {
+    store_factory: Box<dyn TransactionalStoreFactory>,
+    node: StatusNode,
+    client: Client,
+    #[cfg(feature = "rest-api-resources-event-stream")]
+    event_broadcaster: Option<EventBroadcaster>,
+    #[cfg(feature = "batch-tracking-wait")]
+    status_notifier: Option<BatchStatusNotifier>,
+}
+
+impl Poller {
+    // Asks the node for the statuses of the batches that have not reached a final status, and
+    // records those that have changed
+    fn poll(&self) {
+        let store = self.store_factory.get_batch_tracking_store();
+
+        // The batches to ask about, with their current status, by the service they were
+        // submitted to
+        let mut by_service: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+        for status in &[BatchStatus::Pending, BatchStatus::Valid(vec![])] {
+            let batches = match store.list_batches_by_status(status.clone()) {
+                Ok(list) => list.batches,
+                Err(err) => {
+                    error!("Unable to list {} batches: {}", status, err);
+                    return;
+                }
+            };
+
+            for batch in batches {
+                by_service
+                    .entry(
+                        batch
+                            .service_id()
+                            .unwrap_or(NON_SPLINTER_SERVICE_ID_DEFAULT)
+                            .to_string(),
+                    )
+                    .or_default()
+                    .push((batch.batch_header().to_string(), status.to_string()));
+            }
+        }
+
+        for (service_id, batches) in by_service {
+            for chunk in batches.chunks(MAX_IDS_PER_REQUEST) {
+                let ids = chunk.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>();
+                let polled_statuses = match self.fetch_statuses(&service_id, &ids) {
+                    Ok(polled_statuses) => polled_statuses,
+                    Err(err) => {
+                        warn!(
+                            "Unable to get the statuses of batches submitted to {}: {}",
+                            service_id, err
+                        );
+                        break;
+                    }
+                };
+
+                for polled in polled_statuses {
+                    let previous_status = match chunk.iter().find(|(id, _)| *id == polled.batch_id)
+                    {
+                        Some((_, previous_status)) => previous_status,
+                        None => continue,
+                    };
+
+                    if let Some(status) = polled
+                        .status
+                        .filter(|status| status.to_string() != *previous_status)
+                    {
+                        self.record(
+                            &*store,
+                            &service_id,
+                            &polled.batch_id,
+                            previous_status,
+                            status,
+                            polled.receipts,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // Records a batch's new status, then publishes it and wakes the requests waiting on it
+    fn record(
+        &self,
+        store: &dyn BatchTrackingStore,
+        service_id: &str,
+        batch_id: &str,
+        previous_status: &str,
+        status: BatchStatus,
+        receipts: Vec<TransactionReceipt>,
+    ) {
+        let status_name = status.to_string();
+        debug!("Batch {}: {}", batch_id, status_name);
+
+        if let Err(err) =
+            store.update_batch_status(batch_id, service_id, Some(status), receipts, None)
+        {
+            error!("Batch {}: unable to record status: {}", batch_id, err);
+            return;
+        }
+
+        #[cfg(feature = "rest-api-resources-event-stream")]
+        if let Some(ref event_broadcaster) = self.event_broadcaster {
+            let service_id = Some(service_id).filter(|id| *id != NON_SPLINTER_SERVICE_ID_DEFAULT);
+            if let Err(err) = event_broadcaster.publish(StreamEvent::batch_status(
+                batch_id,
+                service_id,
+                Some(previous_status),
+                &status_name,
+            )) {
+                error!(
+                    "Batch {}: unable to publish status change: {}",
+                    batch_id, err
+                );
+            }
+        }
+        #[cfg(not(feature = "rest-api-resources-event-stream"))]
+        let _ = previous_status;
+
+        #[cfg(feature = "batch-tracking-wait")]
+        if let Some(ref status_notifier) = self.status_notifier {
+            status_notifier.notify();
+        }
+    }
+
+    fn fetch_statuses(&self, service_id: &str, ids: &[&str]) -> Result<Vec<PolledStatus>, String> {
+        match &self.node {
+            StatusNode::Sawtooth { url } => {
+                let response: SawtoothBatchStatusResponse = send(self.client.get(&format!(
+                    "{}/batch_statuses?id={}",
+                    url,
+                    ids.join(",")
+                )))?;
+                Ok(response.data.into_iter().map(PolledStatus::from).collect())
+            }
+            StatusNode::Splinter { url, authorization } => {
+                let service_id = FullyQualifiedServiceId::new_from_string(service_id)
+                    .map_err(|err| err.to_string())?;
+                let mut request = self.client.get(&format!(
+                    "{}/scabbard/{}/{}/batch_statuses?ids={}",
+                    url,
+                    service_id.circuit_id(),
+                    service_id.service_id(),
+                    ids.join(",")
+                ));
+                if let Some(authorization) = authorization {
+                    request = request.header("Authorization", authorization);
+                }
+
+                let response: Vec<ScabbardBatchStatus> = send(request)?;
+                Ok(response.into_iter().map(PolledStatus::from).collect())
+            }
+        }
+    }
+}
+
+fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, String> {
+    request
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json())
+        .map_err(|err| err.to_string())
+}
+
+// Builds the receipt recording why a transaction is invalid
+fn invalid_receipt<T: Serialize>(
+    transaction_id: &str,
+    message: String,
+    data: Vec<u8>,
+    reported: &T,
+) -> Option<TransactionReceipt> {
+    let serialized_receipt = serde_json::to_string(reported).ok()?;
+    TransactionReceiptBuilder::default()
+        .with_transaction_id(transaction_id.to_string())
+        .with_result_valid(false)
+        .with_error_message(message)
+        .with_error_data(data)
+        .with_serialized_receipt(serialized_receipt)
+        .build()
+        .map_err(|err| {
+            error!(
+                "Unable to record why transaction {} is invalid: {}",
+                transaction_id, err
+            )
+        })
+        .ok()
+}
+
+#[derive(Deserialize)]
+struct SawtoothBatchStatusResponse {
+    data: Vec<SawtoothBatchStatus>,
+}
+
+#[derive(Deserialize)]
+struct SawtoothBatchStatus {
+    id: String,
+    status: String,
+    #[serde(default)]
+    invalid_transactions: Vec<SawtoothInvalidTransaction>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SawtoothInvalidTransaction {
+    id: String,
+    message: String,
+    // Base64-encoded
+    #[serde(default)]
+    extended_data: String,
+}
+
+impl From<SawtoothBatchStatus> for PolledStatus {
+    fn from(batch_status: SawtoothBatchStatus) -> Self {
+        let receipts = batch_status
+            .invalid_transactions
+            .iter()
+            .filter_map(|txn| {
+                let data = base64::decode(&txn.extended_data)
+                    .unwrap_or_else(|_| txn.extended_data.as_bytes().to_vec());
+                invalid_receipt(&txn.id, txn.message.clone(), data, txn)
+            })
+            .collect();
+
+        Self {
+            status: recorded_status(&batch_status.status),
+            batch_id: batch_status.id,
+            receipts,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ScabbardBatchStatus {
+    id: String,
+    status: ScabbardStatus,
+}
+
+#[derive(Deserialize)]
+struct ScabbardStatus {
+    #[serde(rename(deserialize = "statusType"))]
+    status_type: String,
+    #[serde(default)]
+    message: Vec<ScabbardErrorMessage>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ScabbardErrorMessage {
+    transaction_id: String,
+    error_message: Option<String>,
+    error_data: Option<Vec<u8>>,
+}
+
+impl From<ScabbardBatchStatus> for PolledStatus {
+    fn from(batch_status: ScabbardBatchStatus) -> Self {
+        let receipts = batch_status
+            .status
+            .message
+            .iter()
+            .filter_map(|message| {
+                invalid_receipt(
+                    &message.transaction_id,
+                    message.error_message.clone().unwrap_or_default(),
+                    message.error_data.clone().unwrap_or_default(),
+                    message,
+                )
+            })
+            .collect();
+
+        Self {
+            status: recorded_status(&batch_status.status.status_type),
+            batch_id: batch_status.id,
+            receipts,
+        }
+    }
+}
+
+// Maps a status reported by Sawtooth (`COMMITTED`) or scabbard (`Committed`) to the status that
+// is recorded for it. Pending and unknown batches have nothing new to record.
+fn recorded_status(status: &str) -> Option<BatchStatus> {
+    match status.to_lowercase().as_str() {
+        "committed" => Some(BatchStatus::Committed(vec![])),
+        "invalid" => Some(BatchStatus::Invalid(vec![])),
+        "valid" => Some(BatchStatus::Valid(vec![])),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use mockito::{mock, Matcher};
+
+    use crate::batch_tracking::store::{BatchStatusName, TrackingBatch};
+    use crate::batch_tracking::test_utils::{
+        add_built_batch, add_tracking_batch, create_store_factory, tracking_batch_builder,
+    };
+
+    /// Verify that a poll records the batches the node reports as committed or invalid, along
+    /// with why their transactions are invalid, queues their webhook notifications and wakes the
+    /// requests waiting on them, while a batch the node reports as pending is left pending.
+    #[test]
+    fn test_poll_records_final_statuses() {
+        let store_factory = create_store_factory();
+        let store = store_factory.get_batch_tracking_store();
+
+        let committed = add_pending_batch(&*store_factory);
+        let invalid = add_pending_batch(&*store_factory);
+        let pending = add_pending_batch(&*store_factory);
+        let invalid_txn_id = invalid.transactions()[0].transaction_header().to_string();
+
+        let subscription = store
+            .add_webhook_subscription(
+                committed.signer_public_key(),
+                "https://example.com/hooks",
+                "secret",
+                &[BatchStatusName::Committed],
+            )
+            .expect("Failed to add subscription");
+
+        let mock = mock("GET", "/batch_statuses")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(format!(
+                r#"{{
+                    "data": [
+                        {{"id": "{}", "status": "COMMITTED", "invalid_transactions": []}},
+                        {{
+                            "id": "{}",
+                            "status": "INVALID",
+                            "invalid_transactions": [
+                                {{"id": "{}", "message": "bad payload", "extended_data": "AQI="}}
+                            ]
+                        }},
+                        {{"id": "{}", "status": "PENDING", "invalid_transactions": []}}
+                    ],
+                    "link": "/batch_statuses"
+                }}"#,
+                committed.batch_header(),
+                invalid.batch_header(),
+                invalid_txn_id,
+                pending.batch_header(),
+            ))
+            .create();
+
+        #[allow(unused_mut)]
+        let mut builder = StatusPollerBuilder::new(
+            store_factory.clone_box(),
+            StatusNode::Sawtooth {
+                url: mockito::server_url(),
+            },
+        );
+        #[cfg(feature = "batch-tracking-wait")]
+        let status_notifier = BatchStatusNotifier::new();
+        #[cfg(feature = "batch-tracking-wait")]
+        let status_changes = status_notifier.subscribe();
+        #[cfg(feature = "batch-tracking-wait")]
+        {
+            builder = builder.with_status_notifier(status_notifier);
+        }
+
+        builder
+            .build_poller()
+            .expect("Failed to build poller")
+            .poll();
+        mock.assert();
+
+        assert_eq!(
+            stored_status(&*store, &committed),
+            Some(BatchStatus::Committed(vec![]))
+        );
+        assert_eq!(stored_status(&*store, &pending), Some(BatchStatus::Pending));
+
+        match stored_status(&*store, &invalid) {
+            Some(BatchStatus::Invalid(invalid_transactions)) => {
+                assert_eq!(invalid_transactions.len(), 1);
+                assert_eq!(invalid_transactions[0].transaction_id(), invalid_txn_id);
+                assert_eq!(invalid_transactions[0].error_message(), Some("bad payload"));
+                assert_eq!(invalid_transactions[0].error_data(), Some(&[1u8, 2][..]));
+            }
+            status => panic!("Expected an invalid status, found {:?}", status),
+        }
+
+        let deliveries = store
+            .list_webhook_deliveries(subscription.id(), committed.signer_public_key())
+            .expect("Failed to list deliveries");
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].batch_id(), committed.batch_header());
+        assert_eq!(deliveries[0].event_type(), BatchStatusName::Committed);
+
+        #[cfg(feature = "batch-tracking-wait")]
+        assert_eq!(*status_changes.borrow(), 2);
+    }
+
+    /// Verify that the statuses of batches submitted to scabbard services are asked for at the
+    /// service's batch status endpoint, with the configured authorization.
+    #[test]
+    fn test_poll_splinter_service() {
+        let store_factory = create_store_factory();
+        let store = store_factory.get_batch_tracking_store();
+
+        let service_batch = add_built_batch(
+            &*store_factory,
+            tracking_batch_builder().with_service_id("abcde-01234::gsAA".to_string()),
+        );
+        store
+            .update_batch_status(
+                service_batch.batch_header(),
+                "abcde-01234::gsAA",
+                Some(BatchStatus::Pending),
+                vec![],
+                None,
+            )
+            .expect("Failed to update batch status");
+
+        let mock = mock("GET", "/scabbard/abcde-01234/gsAA/batch_statuses")
+            .match_query(Matcher::UrlEncoded(
+                "ids".into(),
+                service_batch.batch_header().into(),
+            ))
+            .match_header("Authorization", "Bearer Cylinder:token")
+            .with_status(200)
+            .with_body(format!(
+                r#"[{{"id": "{}", "status": {{"statusType": "Committed", "message": []}}}}]"#,
+                service_batch.batch_header()
+            ))
+            .create();
+
+        StatusPollerBuilder::new(
+            store_factory.clone_box(),
+            StatusNode::Splinter {
+                url: mockito::server_url(),
+                authorization: Some("Bearer Cylinder:token".into()),
+            },
+        )
+        .build_poller()
+        .expect("Failed to build poller")
+        .poll();
+        mock.assert();
+
+        let stored = store
+            .get_batch(service_batch.batch_header(), "abcde-01234::gsAA")
+            .expect("Failed to get batch")
+            .expect("Batch not found");
+        assert_eq!(stored.batch_status(), Some(&BatchStatus::Committed(vec![])));
+    }
+
+    fn add_pending_batch(store_factory: &dyn TransactionalStoreFactory) -> TrackingBatch {
+        let batch = add_tracking_batch(store_factory);
+        store_factory
+            .get_batch_tracking_store()
+            .update_batch_status(
+                batch.batch_header(),
+                NON_SPLINTER_SERVICE_ID_DEFAULT,
+                Some(BatchStatus::Pending),
+                vec![],
+                None,
+            )
+            .expect("Failed to update batch status");
+        batch
+    }
+
+    fn stored_status(store: &dyn BatchTrackingStore, batch: &TrackingBatch) -> Option<BatchStatus> {
+        store
+            .get_batch(batch.batch_header(), NON_SPLINTER_SERVICE_ID_DEFAULT)
+            .expect("Failed to get batch")
+            .expect("Batch not found")
+            .batch_status()
+            .cloned()
+    }
+}
@@ -20,22 +20,40 @@ use diesel::connection::AnsiTransactionManager;
 use diesel::r2d2::{ConnectionManager, Pool};
 
 use super::{
-    BatchStatus, BatchStatusName, BatchTrackingStore, BatchTrackingStoreError, InvalidTransaction,
-    SubmissionError, TrackingBatch, TrackingBatchList, TrackingTransaction, TransactionReceipt,
-    ValidTransaction,
+    BatchQueueSummary, BatchStatus, BatchStatusName, BatchTrackingStore, BatchTrackingStoreError,
+    DeadLetterAction, DeadLetterAuditRecord, DeadLetterBatch, InvalidTransaction, SubmissionError,
+    TrackingBatch, TrackingBatchList, TrackingTransaction, TransactionReceipt, ValidTransaction,
+    WebhookDelivery, WebhookDeliveryAttempt, WebhookDeliveryStatus, WebhookSubscription,
 };
 
 use crate::error::ResourceTemporarilyUnavailableError;
 
 use models::{NewBatchStatusModel, NewSubmissionModel, TransactionReceiptModel};
 use operations::add_batches::BatchTrackingStoreAddBatchesOperation as _;
+use operations::add_webhook_subscription::BatchTrackingStoreAddWebhookSubscriptionOperation as _;
 use operations::change_batch_to_submitted::BatchTrackingStoreChangeBatchToSubmittedOperation as _;
 use operations::clean_stale_records::BatchTrackingCleanStaleRecordsOperation as _;
+use operations::compact_batches::BatchTrackingStoreCompactBatchesOperation as _;
+use operations::dead_letter_batch::BatchTrackingStoreDeadLetterBatchOperation as _;
+use operations::discard_dead_letter_batch::BatchTrackingStoreDiscardDeadLetterBatchOperation as _;
 use operations::get_batch::BatchTrackingStoreGetBatchOperation as _;
+use operations::get_batch_by_data_change_id::BatchTrackingStoreGetBatchByDataChangeIdOperation as _;
 use operations::get_batch_status::BatchTrackingStoreGetBatchStatusOperation as _;
 use operations::get_failed_batches::BatchTrackingStoreGetFailedBatchesOperation as _;
+use operations::get_next_unsubmitted_batches::BatchTrackingStoreGetNextUnsubmittedBatchesOperation as _;
+use operations::get_pending_webhook_deliveries::BatchTrackingStoreGetPendingWebhookDeliveriesOperation as _;
+use operations::get_queue_summary::BatchTrackingStoreGetQueueSummaryOperation as _;
 use operations::get_unsubmitted_batches::BatchTrackingStoreGetUnsubmittedBatchesOperation as _;
 use operations::list_batches_by_status::BatchTrackingStoreListBatchesByStatusOperation as _;
+use operations::list_dead_letter_audit_records::BatchTrackingStoreListDeadLetterAuditRecordsOperation as _;
+use operations::list_dead_letter_batches::BatchTrackingStoreListDeadLetterBatchesOperation as _;
+use operations::list_webhook_deliveries::BatchTrackingStoreListWebhookDeliveriesOperation as _;
+use operations::list_webhook_delivery_attempts::BatchTrackingStoreListWebhookDeliveryAttemptsOperation as _;
+use operations::list_webhook_subscriptions::BatchTrackingStoreListWebhookSubscriptionsOperation as _;
+use operations::purge_batches::BatchTrackingStorePurgeBatchesOperation as _;
+use operations::record_webhook_delivery_attempt::BatchTrackingStoreRecordWebhookDeliveryAttemptOperation as _;
+use operations::remove_webhook_subscription::BatchTrackingStoreRemoveWebhookSubscriptionOperation as _;
+use operations::replay_dead_letter_batch::BatchTrackingStoreReplayDeadLetterBatchOperation as _;
 use operations::update_batch_status::BatchTrackingStoreUpdateBatchStatusOperation as _;
 use operations::BatchTrackingStoreOperations;
 
@@ -170,6 +188,20 @@ impl BatchTrackingStore for DieselBatchTrackingStore<diesel::pg::PgConnection> {
         .get_batch(id, service_id)
     }
 
+    fn get_batch_by_data_change_id(
+        &self,
+        data_change_id: &str,
+        signer_public_key: &str,
+        service_id: &str,
+    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_batch_by_data_change_id(data_change_id, signer_public_key, service_id)
+    }
+
     fn list_batches_by_status(
         &self,
         status: BatchStatus,
@@ -191,6 +223,34 @@ impl BatchTrackingStore for DieselBatchTrackingStore<diesel::pg::PgConnection> {
         .clean_stale_records(submitted_by)
     }
 
+    fn purge_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .purge_batches(status, created_before, limit)
+    }
+
+    fn compact_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .compact_batches(status, created_before, limit)
+    }
+
     fn get_unsubmitted_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
@@ -200,6 +260,28 @@ impl BatchTrackingStore for DieselBatchTrackingStore<diesel::pg::PgConnection> {
         .get_unsubmitted_batches()
     }
 
+    fn get_next_unsubmitted_batches(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_next_unsubmitted_batches(offset, limit)
+    }
+
+    fn get_queue_summary(&self) -> Result<BatchQueueSummary, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_queue_summary()
+    }
+
     fn get_failed_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
@@ -208,188 +290,190 @@ impl BatchTrackingStore for DieselBatchTrackingStore<diesel::pg::PgConnection> {
         })?)
         .get_failed_batches()
     }
-}
 
-#[cfg(feature = "sqlite")]
-impl BatchTrackingStore for DieselBatchTrackingStore<diesel::sqlite::SqliteConnection> {
-    fn get_batch_status(
+    fn dead_letter_batch(
         &self,
         id: &str,
         service_id: &str,
-    ) -> Result<Option<BatchStatus>, BatchTrackingStoreError> {
+        reason: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .get_batch_status(id, service_id)
+        .dead_letter_batch(id, service_id, reason)
     }
 
-    fn update_batch_status(
+    fn list_dead_letter_batches(
         &self,
-        id: &str,
-        service_id: &str,
-        status: Option<BatchStatus>,
-        transaction_receipts: Vec<TransactionReceipt>,
-        submission_error: Option<SubmissionError>,
-    ) -> Result<(), BatchTrackingStoreError> {
-        let rcpts: Vec<TransactionReceiptModel> = transaction_receipts
-            .iter()
-            .map(|t| TransactionReceiptModel::from((t, service_id)))
-            .collect::<Vec<TransactionReceiptModel>>();
-
-        let stat = status.map(|s| s.to_string());
-
-        let batch_status: Option<&str> = stat.as_deref();
-
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .update_batch_status(id, service_id, batch_status, rcpts, submission_error)
+        .list_dead_letter_batches(service_id)
     }
 
-    fn add_batches(&self, batches: Vec<TrackingBatch>) -> Result<(), BatchTrackingStoreError> {
+    fn replay_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+        replacement: Option<TrackingBatch>,
+    ) -> Result<(), BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .add_batches(batches)
+        .replay_dead_letter_batch(id, service_id, actor, replacement)
     }
 
-    fn change_batch_to_submitted(
+    fn discard_dead_letter_batch(
         &self,
-        batch_id: &str,
+        id: &str,
         service_id: &str,
-        transaction_receipts: Vec<TransactionReceipt>,
-        dlt_status: Option<&str>,
-        submission_error: Option<SubmissionError>,
+        actor: &str,
     ) -> Result<(), BatchTrackingStoreError> {
-        let mut batch_status = None;
-
-        if let Some(ds) = dlt_status {
-            batch_status = Some(NewBatchStatusModel {
-                batch_id: batch_id.to_string(),
-                service_id: service_id.to_string(),
-                dlt_status: ds.to_string(),
-            });
-        }
-
-        let mut submission = NewSubmissionModel {
-            batch_id: batch_id.to_string(),
-            service_id: service_id.to_string(),
-            error_type: None,
-            error_message: None,
-        };
-
-        if let Some(s) = submission_error {
-            submission = NewSubmissionModel {
-                batch_id: batch_id.to_string(),
-                service_id: service_id.to_string(),
-                error_type: Some(s.error_type().to_string()),
-                error_message: Some(s.error_message().to_string()),
-            };
-        }
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .discard_dead_letter_batch(id, service_id, actor)
+    }
 
+    fn list_dead_letter_audit_records(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterAuditRecord>, BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .change_batch_to_submitted(
-            batch_id,
-            service_id,
-            transaction_receipts
-                .iter()
-                .map(|r| TransactionReceiptModel::from((r, service_id)))
-                .collect(),
-            batch_status,
-            submission,
-        )
+        .list_dead_letter_audit_records(service_id)
     }
 
-    fn get_batch(
+    fn add_webhook_subscription(
         &self,
-        id: &str,
-        service_id: &str,
-    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError> {
+        signer_public_key: &str,
+        url: &str,
+        secret: &str,
+        event_types: &[BatchStatusName],
+    ) -> Result<WebhookSubscription, BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .get_batch(id, service_id)
+        .add_webhook_subscription(signer_public_key, url, secret, event_types)
     }
 
-    fn list_batches_by_status(
+    fn list_webhook_subscriptions(
         &self,
-        status: BatchStatus,
-    ) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookSubscription>, BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .list_batches_by_status(&status.to_string())
+        .list_webhook_subscriptions(signer_public_key)
     }
 
-    fn clean_stale_records(&self, submitted_by: i64) -> Result<(), BatchTrackingStoreError> {
+    fn remove_webhook_subscription(
+        &self,
+        id: i64,
+        signer_public_key: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .clean_stale_records(submitted_by)
+        .remove_webhook_subscription(id, signer_public_key)
     }
 
-    fn get_unsubmitted_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+    fn get_pending_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .get_unsubmitted_batches()
+        .get_pending_webhook_deliveries(now, limit)
     }
 
-    fn get_failed_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+    fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: i64,
+        attempted_at: i64,
+        response_code: Option<i64>,
+        error: Option<&str>,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: i64,
+    ) -> Result<(), BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .get_failed_batches()
+        .record_webhook_delivery_attempt(
+            delivery_id,
+            attempted_at,
+            response_code,
+            error,
+            status,
+            next_attempt_at,
+        )
     }
-}
 
-pub struct DieselConnectionBatchTrackingStore<'a, C>
-where
-    C: diesel::Connection<TransactionManager = AnsiTransactionManager> + 'static,
-    C::Backend: diesel::backend::UsesAnsiSavepointSyntax,
-{
-    connection: &'a C,
-}
+    fn list_webhook_deliveries(
+        &self,
+        subscription_id: i64,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_webhook_deliveries(subscription_id, signer_public_key)
+    }
 
-impl<'a, C> DieselConnectionBatchTrackingStore<'a, C>
-where
-    C: diesel::Connection<TransactionManager = AnsiTransactionManager> + 'static,
-    C::Backend: diesel::backend::UsesAnsiSavepointSyntax,
-{
-    #[allow(dead_code)]
-    pub fn new(connection: &'a C) -> Self {
-        DieselConnectionBatchTrackingStore { connection }
+    fn list_webhook_delivery_attempts(
+        &self,
+        delivery_id: i64,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_webhook_delivery_attempts(delivery_id)
     }
 }
 
-#[cfg(feature = "postgres")]
-impl<'a> BatchTrackingStore for DieselConnectionBatchTrackingStore<'a, diesel::pg::PgConnection> {
+#[cfg(feature = "sqlite")]
+impl BatchTrackingStore for DieselBatchTrackingStore<diesel::sqlite::SqliteConnection> {
     fn get_batch_status(
         &self,
         id: &str,
         service_id: &str,
     ) -> Result<Option<BatchStatus>, BatchTrackingStoreError> {
-        BatchTrackingStoreOperations::new(self.connection).get_batch_status(id, service_id)
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_batch_status(id, service_id)
     }
 
     fn update_batch_status(
@@ -409,17 +493,21 @@ impl<'a> BatchTrackingStore for DieselConnectionBatchTrackingStore<'a, diesel::p
 
         let batch_status: Option<&str> = stat.as_deref();
 
-        BatchTrackingStoreOperations::new(self.connection).update_batch_status(
-            id,
-            service_id,
-            batch_status,
-            rcpts,
-            submission_error,
-        )
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .update_batch_status(id, service_id, batch_status, rcpts, submission_error)
     }
 
     fn add_batches(&self, batches: Vec<TrackingBatch>) -> Result<(), BatchTrackingStoreError> {
-        BatchTrackingStoreOperations::new(self.connection).add_batches(batches)
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .add_batches(batches)
     }
 
     fn change_batch_to_submitted(
@@ -456,7 +544,12 @@ impl<'a> BatchTrackingStore for DieselConnectionBatchTrackingStore<'a, diesel::p
             };
         }
 
-        BatchTrackingStoreOperations::new(self.connection).change_batch_to_submitted(
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .change_batch_to_submitted(
             batch_id,
             service_id,
             transaction_receipts
@@ -473,27 +566,596 @@ impl<'a> BatchTrackingStore for DieselConnectionBatchTrackingStore<'a, diesel::p
         id: &str,
         service_id: &str,
     ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError> {
-        BatchTrackingStoreOperations::new(self.connection).get_batch(id, service_id)
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_batch(id, service_id)
+    }
+
+    fn get_batch_by_data_change_id(
+        &self,
+        data_change_id: &str,
+        signer_public_key: &str,
+        service_id: &str,
+    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_batch_by_data_change_id(data_change_id, signer_public_key, service_id)
     }
 
     fn list_batches_by_status(
         &self,
         status: BatchStatus,
     ) -> Result<TrackingBatchList, BatchTrackingStoreError> {
-        BatchTrackingStoreOperations::new(self.connection)
-            .list_batches_by_status(&status.to_string())
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_batches_by_status(&status.to_string())
     }
 
     fn clean_stale_records(&self, submitted_by: i64) -> Result<(), BatchTrackingStoreError> {
-        BatchTrackingStoreOperations::new(self.connection).clean_stale_records(submitted_by)
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .clean_stale_records(submitted_by)
     }
 
-    fn get_unsubmitted_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
-        BatchTrackingStoreOperations::new(self.connection).get_unsubmitted_batches()
+    fn purge_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .purge_batches(status, created_before, limit)
     }
 
-    fn get_failed_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
-        BatchTrackingStoreOperations::new(self.connection).get_failed_batches()
+    fn compact_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .compact_batches(status, created_before, limit)
+    }
+
+    fn get_unsubmitted_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_unsubmitted_batches()
+    }
+
+    fn get_next_unsubmitted_batches(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_next_unsubmitted_batches(offset, limit)
+    }
+
+    fn get_queue_summary(&self) -> Result<BatchQueueSummary, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_queue_summary()
+    }
+
+    fn get_failed_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_failed_batches()
+    }
+
+    fn dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        reason: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .dead_letter_batch(id, service_id, reason)
+    }
+
+    fn list_dead_letter_batches(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_dead_letter_batches(service_id)
+    }
+
+    fn replay_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+        replacement: Option<TrackingBatch>,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .replay_dead_letter_batch(id, service_id, actor, replacement)
+    }
+
+    fn discard_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .discard_dead_letter_batch(id, service_id, actor)
+    }
+
+    fn list_dead_letter_audit_records(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterAuditRecord>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_dead_letter_audit_records(service_id)
+    }
+
+    fn add_webhook_subscription(
+        &self,
+        signer_public_key: &str,
+        url: &str,
+        secret: &str,
+        event_types: &[BatchStatusName],
+    ) -> Result<WebhookSubscription, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .add_webhook_subscription(signer_public_key, url, secret, event_types)
+    }
+
+    fn list_webhook_subscriptions(
+        &self,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookSubscription>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_webhook_subscriptions(signer_public_key)
+    }
+
+    fn remove_webhook_subscription(
+        &self,
+        id: i64,
+        signer_public_key: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .remove_webhook_subscription(id, signer_public_key)
+    }
+
+    fn get_pending_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_pending_webhook_deliveries(now, limit)
+    }
+
+    fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: i64,
+        attempted_at: i64,
+        response_code: Option<i64>,
+        error: Option<&str>,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: i64,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .record_webhook_delivery_attempt(
+            delivery_id,
+            attempted_at,
+            response_code,
+            error,
+            status,
+            next_attempt_at,
+        )
+    }
+
+    fn list_webhook_deliveries(
+        &self,
+        subscription_id: i64,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_webhook_deliveries(subscription_id, signer_public_key)
+    }
+
+    fn list_webhook_delivery_attempts(
+        &self,
+        delivery_id: i64,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            BatchTrackingStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_webhook_delivery_attempts(delivery_id)
+    }
+}
+
+pub struct DieselConnectionBatchTrackingStore<'a, C>
+where
+    C: diesel::Connection<TransactionManager = AnsiTransactionManager> + 'static,
+    C::Backend: diesel::backend::UsesAnsiSavepointSyntax,
+{
+    connection: &'a C,
+}
+
+impl<'a, C> DieselConnectionBatchTrackingStore<'a, C>
+where
+    C: diesel::Connection<TransactionManager = AnsiTransactionManager> + 'static,
+    C::Backend: diesel::backend::UsesAnsiSavepointSyntax,
+{
+    #[allow(dead_code)]
+    pub fn new(connection: &'a C) -> Self {
+        DieselConnectionBatchTrackingStore { connection }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStore for DieselConnectionBatchTrackingStore<'a, diesel::pg::PgConnection> {
+    fn get_batch_status(
+        &self,
+        id: &str,
+        service_id: &str,
+    ) -> Result<Option<BatchStatus>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).get_batch_status(id, service_id)
+    }
+
+    fn update_batch_status(
+        &self,
+        id: &str,
+        service_id: &str,
+        status: Option<BatchStatus>,
+        transaction_receipts: Vec<TransactionReceipt>,
+        submission_error: Option<SubmissionError>,
+    ) -> Result<(), BatchTrackingStoreError> {
+        let rcpts: Vec<TransactionReceiptModel> = transaction_receipts
+            .iter()
+            .map(|t| TransactionReceiptModel::from((t, service_id)))
+            .collect::<Vec<TransactionReceiptModel>>();
+
+        let stat = status.map(|s| s.to_string());
+
+        let batch_status: Option<&str> = stat.as_deref();
+
+        BatchTrackingStoreOperations::new(self.connection).update_batch_status(
+            id,
+            service_id,
+            batch_status,
+            rcpts,
+            submission_error,
+        )
+    }
+
+    fn add_batches(&self, batches: Vec<TrackingBatch>) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).add_batches(batches)
+    }
+
+    fn change_batch_to_submitted(
+        &self,
+        batch_id: &str,
+        service_id: &str,
+        transaction_receipts: Vec<TransactionReceipt>,
+        dlt_status: Option<&str>,
+        submission_error: Option<SubmissionError>,
+    ) -> Result<(), BatchTrackingStoreError> {
+        let mut batch_status = None;
+
+        if let Some(ds) = dlt_status {
+            batch_status = Some(NewBatchStatusModel {
+                batch_id: batch_id.to_string(),
+                service_id: service_id.to_string(),
+                dlt_status: ds.to_string(),
+            });
+        }
+
+        let mut submission = NewSubmissionModel {
+            batch_id: batch_id.to_string(),
+            service_id: service_id.to_string(),
+            error_type: None,
+            error_message: None,
+        };
+
+        if let Some(s) = submission_error {
+            submission = NewSubmissionModel {
+                batch_id: batch_id.to_string(),
+                service_id: service_id.to_string(),
+                error_type: Some(s.error_type().to_string()),
+                error_message: Some(s.error_message().to_string()),
+            };
+        }
+
+        BatchTrackingStoreOperations::new(self.connection).change_batch_to_submitted(
+            batch_id,
+            service_id,
+            transaction_receipts
+                .iter()
+                .map(|r| TransactionReceiptModel::from((r, service_id)))
+                .collect(),
+            batch_status,
+            submission,
+        )
+    }
+
+    fn get_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).get_batch(id, service_id)
+    }
+
+    fn get_batch_by_data_change_id(
+        &self,
+        data_change_id: &str,
+        signer_public_key: &str,
+        service_id: &str,
+    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).get_batch_by_data_change_id(
+            data_change_id,
+            signer_public_key,
+            service_id,
+        )
+    }
+
+    fn list_batches_by_status(
+        &self,
+        status: BatchStatus,
+    ) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .list_batches_by_status(&status.to_string())
+    }
+
+    fn clean_stale_records(&self, submitted_by: i64) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).clean_stale_records(submitted_by)
+    }
+
+    fn purge_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).purge_batches(
+            status,
+            created_before,
+            limit,
+        )
+    }
+
+    fn compact_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).compact_batches(
+            status,
+            created_before,
+            limit,
+        )
+    }
+
+    fn get_unsubmitted_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).get_unsubmitted_batches()
+    }
+
+    fn get_next_unsubmitted_batches(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .get_next_unsubmitted_batches(offset, limit)
+    }
+
+    fn get_queue_summary(&self) -> Result<BatchQueueSummary, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).get_queue_summary()
+    }
+
+    fn get_failed_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).get_failed_batches()
+    }
+
+    fn dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        reason: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).dead_letter_batch(id, service_id, reason)
+    }
+
+    fn list_dead_letter_batches(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).list_dead_letter_batches(service_id)
+    }
+
+    fn replay_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+        replacement: Option<TrackingBatch>,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).replay_dead_letter_batch(
+            id,
+            service_id,
+            actor,
+            replacement,
+        )
+    }
+
+    fn discard_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .discard_dead_letter_batch(id, service_id, actor)
+    }
+
+    fn list_dead_letter_audit_records(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterAuditRecord>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .list_dead_letter_audit_records(service_id)
+    }
+
+    fn add_webhook_subscription(
+        &self,
+        signer_public_key: &str,
+        url: &str,
+        secret: &str,
+        event_types: &[BatchStatusName],
+    ) -> Result<WebhookSubscription, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).add_webhook_subscription(
+            signer_public_key,
+            url,
+            secret,
+            event_types,
+        )
+    }
+
+    fn list_webhook_subscriptions(
+        &self,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookSubscription>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .list_webhook_subscriptions(signer_public_key)
+    }
+
+    fn remove_webhook_subscription(
+        &self,
+        id: i64,
+        signer_public_key: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .remove_webhook_subscription(id, signer_public_key)
+    }
+
+    fn get_pending_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .get_pending_webhook_deliveries(now, limit)
+    }
+
+    fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: i64,
+        attempted_at: i64,
+        response_code: Option<i64>,
+        error: Option<&str>,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: i64,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).record_webhook_delivery_attempt(
+            delivery_id,
+            attempted_at,
+            response_code,
+            error,
+            status,
+            next_attempt_at,
+        )
+    }
+
+    fn list_webhook_deliveries(
+        &self,
+        subscription_id: i64,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .list_webhook_deliveries(subscription_id, signer_public_key)
+    }
+
+    fn list_webhook_delivery_attempts(
+        &self,
+        delivery_id: i64,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .list_webhook_delivery_attempts(delivery_id)
     }
 }
 
@@ -593,6 +1255,19 @@ impl<'a> BatchTrackingStore
         BatchTrackingStoreOperations::new(self.connection).get_batch(id, service_id)
     }
 
+    fn get_batch_by_data_change_id(
+        &self,
+        data_change_id: &str,
+        signer_public_key: &str,
+        service_id: &str,
+    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).get_batch_by_data_change_id(
+            data_change_id,
+            signer_public_key,
+            service_id,
+        )
+    }
+
     fn list_batches_by_status(
         &self,
         status: BatchStatus,
@@ -605,13 +1280,178 @@ impl<'a> BatchTrackingStore
         BatchTrackingStoreOperations::new(self.connection).clean_stale_records(submitted_by)
     }
 
+    fn purge_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).purge_batches(
+            status,
+            created_before,
+            limit,
+        )
+    }
+
+    fn compact_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).compact_batches(
+            status,
+            created_before,
+            limit,
+        )
+    }
+
     fn get_unsubmitted_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(self.connection).get_unsubmitted_batches()
     }
 
+    fn get_next_unsubmitted_batches(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .get_next_unsubmitted_batches(offset, limit)
+    }
+
+    fn get_queue_summary(&self) -> Result<BatchQueueSummary, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).get_queue_summary()
+    }
+
     fn get_failed_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
         BatchTrackingStoreOperations::new(self.connection).get_failed_batches()
     }
+
+    fn dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        reason: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).dead_letter_batch(id, service_id, reason)
+    }
+
+    fn list_dead_letter_batches(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).list_dead_letter_batches(service_id)
+    }
+
+    fn replay_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+        replacement: Option<TrackingBatch>,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).replay_dead_letter_batch(
+            id,
+            service_id,
+            actor,
+            replacement,
+        )
+    }
+
+    fn discard_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .discard_dead_letter_batch(id, service_id, actor)
+    }
+
+    fn list_dead_letter_audit_records(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterAuditRecord>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .list_dead_letter_audit_records(service_id)
+    }
+
+    fn add_webhook_subscription(
+        &self,
+        signer_public_key: &str,
+        url: &str,
+        secret: &str,
+        event_types: &[BatchStatusName],
+    ) -> Result<WebhookSubscription, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).add_webhook_subscription(
+            signer_public_key,
+            url,
+            secret,
+            event_types,
+        )
+    }
+
+    fn list_webhook_subscriptions(
+        &self,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookSubscription>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .list_webhook_subscriptions(signer_public_key)
+    }
+
+    fn remove_webhook_subscription(
+        &self,
+        id: i64,
+        signer_public_key: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .remove_webhook_subscription(id, signer_public_key)
+    }
+
+    fn get_pending_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .get_pending_webhook_deliveries(now, limit)
+    }
+
+    fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: i64,
+        attempted_at: i64,
+        response_code: Option<i64>,
+        error: Option<&str>,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: i64,
+    ) -> Result<(), BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection).record_webhook_delivery_attempt(
+            delivery_id,
+            attempted_at,
+            response_code,
+            error,
+            status,
+            next_attempt_at,
+        )
+    }
+
+    fn list_webhook_deliveries(
+        &self,
+        subscription_id: i64,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .list_webhook_deliveries(subscription_id, signer_public_key)
+    }
+
+    fn list_webhook_delivery_attempts(
+        &self,
+        delivery_id: i64,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, BatchTrackingStoreError> {
+        BatchTrackingStoreOperations::new(self.connection)
+            .list_webhook_delivery_attempts(delivery_id)
+    }
 }
 
 #[cfg(test)]
@@ -627,8 +1467,8 @@ mod tests {
     };
 
     use crate::batch_tracking::store::{
-        BatchBuilderError, InvalidTransactionBuilder, SubmissionErrorBuilder, TrackingBatchBuilder,
-        TransactionReceiptBuilder,
+        BatchBuilderError, BatchPriority, InvalidTransactionBuilder, SubmissionErrorBuilder,
+        TrackingBatchBuilder, TransactionReceiptBuilder,
     };
     use crate::hex;
     use crate::migrations::run_sqlite_migrations;
@@ -719,6 +1559,84 @@ mod tests {
         assert_eq!(batch_result, expected);
     }
 
+    #[test]
+    fn data_change_id_scoped_to_signer() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselBatchTrackingStore::new(pool);
+
+        let signer = new_signer();
+
+        let dcid = "dcid:data_change".to_string();
+
+        let batch_1 = get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE)]);
+        let batch_2 =
+            get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE2)]);
+
+        let tracking_batch_1 = get_tracking_batch(batch_1.clone(), false)
+            .with_data_change_id(dcid.clone())
+            .build()
+            .expect("Failed to build batch");
+        let tracking_batch_2 = get_tracking_batch(batch_2, false)
+            .with_signer_public_key(KEY2.to_string())
+            .with_data_change_id(dcid.clone())
+            .build()
+            .expect("Failed to build batch");
+
+        // The same data change ID may be used by different signers
+        store
+            .add_batches(vec![tracking_batch_1.clone(), tracking_batch_2.clone()])
+            .expect("Failed to add batches");
+
+        let batch_result = store
+            .get_batch_by_data_change_id(&dcid, KEY1, "TEST")
+            .expect("Failed to get batch")
+            .expect("Batch not found");
+        assert_eq!(batch_result.batch_header(), tracking_batch_1.batch_header());
+
+        let batch_result = store
+            .get_batch_by_data_change_id(&dcid, KEY2, "TEST")
+            .expect("Failed to get batch")
+            .expect("Batch not found");
+        assert_eq!(batch_result.batch_header(), tracking_batch_2.batch_header());
+
+        assert!(store
+            .get_batch_by_data_change_id(&dcid, KEY3, "TEST")
+            .expect("Failed to get batch")
+            .is_none());
+        assert!(store
+            .get_batch_by_data_change_id(&dcid, KEY1, "OTHER")
+            .expect("Failed to get batch")
+            .is_none());
+
+        // A data change ID used by several signers does not identify a batch
+        match store.get_batch(&dcid, "TEST") {
+            Err(BatchTrackingStoreError::ConstraintViolationError(_)) => (),
+            res => panic!("Expected ConstraintViolationError, got {:?}", res),
+        }
+        match store.update_batch_status(&dcid, "TEST", Some(BatchStatus::Pending), vec![], None) {
+            Err(BatchTrackingStoreError::ConstraintViolationError(_)) => (),
+            res => panic!("Expected ConstraintViolationError, got {:?}", res),
+        }
+        assert!(store
+            .get_batch(tracking_batch_1.batch_header(), "TEST")
+            .expect("Failed to get batch")
+            .is_some());
+
+        // But not twice by the same signer
+        let batch_3 =
+            get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, "k9fzzd")]);
+        let tracking_batch_3 = get_tracking_batch(batch_3, false)
+            .with_data_change_id(dcid)
+            .build()
+            .expect("Failed to build batch");
+
+        match store.add_batches(vec![tracking_batch_3]) {
+            Err(BatchTrackingStoreError::ConstraintViolationError(_)) => (),
+            res => panic!("Expected ConstraintViolationError, got {:?}", res),
+        }
+    }
+
     #[test]
     fn test_invalid_dcid() {
         let signer = new_signer();
@@ -1127,12 +2045,162 @@ mod tests {
             batches: vec![expected_batch],
         };
 
-        assert_eq!(
-            store
-                .get_unsubmitted_batches()
-                .expect("Failed to get batch"),
-            expected
-        );
+        assert_eq!(
+            store
+                .get_unsubmitted_batches()
+                .expect("Failed to get batch"),
+            expected
+        );
+    }
+
+    /// Verify that the next unsubmitted batches are returned by priority a page at a time, along
+    /// with their transactions, and that submitted batches are left out.
+    #[test]
+    fn get_next_unsubmitted_batches() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselBatchTrackingStore::new(pool);
+
+        let mut tracking_batches = Vec::new();
+        for priority in &[
+            BatchPriority::Bulk,
+            BatchPriority::Normal,
+            BatchPriority::Interactive,
+        ] {
+            let signer = new_signer();
+            let pair = get_transact_transaction(&*signer, NONCE);
+            let batch = get_transact_batch(&*signer, vec![pair]);
+            tracking_batches.push(
+                get_tracking_batch(batch, false)
+                    .with_priority(*priority)
+                    .build()
+                    .expect("Failed to build batch"),
+            );
+        }
+        let submitted_signer = new_signer();
+        let submitted_pair = get_transact_transaction(&*submitted_signer, NONCE2);
+        let submitted_batch = get_transact_batch(&*submitted_signer, vec![submitted_pair]);
+        let submitted = get_tracking_batch(submitted_batch, true)
+            .with_priority(BatchPriority::Interactive)
+            .build()
+            .expect("Failed to build batch");
+
+        let mut added = tracking_batches.clone();
+        added.push(submitted);
+        store.add_batches(added).expect("Failed to add batches");
+
+        let expected_order = vec![
+            tracking_batches[2].batch_header().to_string(),
+            tracking_batches[1].batch_header().to_string(),
+            tracking_batches[0].batch_header().to_string(),
+        ];
+
+        let first_page = store
+            .get_next_unsubmitted_batches(0, 2)
+            .expect("Failed to get batches");
+        assert_eq!(
+            first_page
+                .batches
+                .iter()
+                .map(|batch| batch.batch_header().to_string())
+                .collect::<Vec<_>>(),
+            expected_order[..2].to_vec()
+        );
+        let interactive = store
+            .get_batch(tracking_batches[2].batch_header(), "TEST")
+            .expect("Failed to get batch")
+            .expect("Batch not found");
+        assert_eq!(first_page.batches[0], interactive);
+
+        let second_page = store
+            .get_next_unsubmitted_batches(2, 2)
+            .expect("Failed to get batches");
+        assert_eq!(
+            second_page
+                .batches
+                .iter()
+                .map(|batch| batch.batch_header().to_string())
+                .collect::<Vec<_>>(),
+            expected_order[2..].to_vec()
+        );
+
+        assert!(store
+            .get_next_unsubmitted_batches(3, 2)
+            .expect("Failed to get batches")
+            .batches
+            .is_empty());
+    }
+
+    /// Verify that the queue summary counts the unsubmitted and delayed batches and reports when
+    /// the oldest of them was added, and that an empty queue has no oldest batch.
+    #[test]
+    fn get_queue_summary() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselBatchTrackingStore::new(pool);
+
+        let summary = store
+            .get_queue_summary()
+            .expect("Failed to get queue summary");
+        assert_eq!(summary.depth(), 0);
+        assert_eq!(summary.oldest_created_at(), None);
+
+        let signer = new_signer();
+
+        let batch_1 = get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE)]);
+        let batch_2 =
+            get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE2)]);
+
+        let tracking_batch_1 = get_tracking_batch(batch_1, false)
+            .build()
+            .expect("Failed to build batch");
+        let id = tracking_batch_1.batch_header();
+        let tracking_batch_2 = get_tracking_batch(batch_2, true)
+            .build()
+            .expect("Failed to build batch");
+        let delayed_id = tracking_batch_2.batch_header();
+
+        store
+            .add_batches(vec![tracking_batch_1, tracking_batch_2])
+            .expect("Failed to add batches");
+
+        let created_at = store
+            .get_batch(&id, "TEST")
+            .expect("Failed to get batch")
+            .expect("Batch not found")
+            .created_at();
+
+        let summary = store
+            .get_queue_summary()
+            .expect("Failed to get queue summary");
+        assert_eq!(summary.depth(), 1);
+        assert_eq!(summary.oldest_created_at(), Some(created_at));
+
+        // A submitted batch leaves the queue, and a delayed one returns to it
+        store
+            .update_batch_status(&id, "TEST", Some(BatchStatus::Pending), Vec::new(), None)
+            .expect("Failed to update batch");
+        store
+            .update_batch_status(
+                &delayed_id,
+                "TEST",
+                Some(BatchStatus::Delayed),
+                Vec::new(),
+                None,
+            )
+            .expect("Failed to update batch");
+
+        let delayed_created_at = store
+            .get_batch(&delayed_id, "TEST")
+            .expect("Failed to get batch")
+            .expect("Batch not found")
+            .created_at();
+
+        let summary = store
+            .get_queue_summary()
+            .expect("Failed to get queue summary");
+        assert_eq!(summary.depth(), 1);
+        assert_eq!(summary.oldest_created_at(), Some(delayed_created_at));
     }
 
     #[test]
@@ -1326,6 +2394,584 @@ mod tests {
         )
     }
 
+    /// Verify that only batches with the given status created before the given time are
+    /// compacted and purged.
+    #[test]
+    fn test_compact_and_purge_batches() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselBatchTrackingStore::new(pool);
+
+        let signer = new_signer();
+
+        let invalid_batch = get_tracking_batch(
+            get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE)]),
+            true,
+        )
+        .build()
+        .expect("Failed to build batch");
+        let pending_batch = get_tracking_batch(
+            get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE2)]),
+            true,
+        )
+        .build()
+        .expect("Failed to build batch");
+
+        let invalid_id = invalid_batch.batch_header().to_string();
+        let pending_id = pending_batch.batch_header().to_string();
+        let transaction_id = invalid_batch.transactions()[0]
+            .transaction_header()
+            .to_string();
+
+        let receipt = TransactionReceiptBuilder::default()
+            .with_transaction_id(transaction_id.clone())
+            .with_result_valid(false)
+            .with_error_message("test".to_string())
+            .with_error_data(BYTES2.to_vec())
+            .with_serialized_receipt(
+                std::str::from_utf8(&BYTES2)
+                    .expect("Failed to build string")
+                    .to_string(),
+            )
+            .build()
+            .expect("Failed to build receipt");
+
+        let invalid_transactions = vec![InvalidTransactionBuilder::default()
+            .with_transaction_id(transaction_id)
+            .with_error_message("test".to_string())
+            .with_error_data(BYTES2.to_vec())
+            .build()
+            .expect("Failed to build invalid transaction")];
+
+        store
+            .add_batches(vec![invalid_batch, pending_batch])
+            .expect("Failed to add batches");
+        store
+            .update_batch_status(
+                &invalid_id,
+                "TEST",
+                Some(BatchStatus::Invalid(invalid_transactions.clone())),
+                vec![receipt],
+                None,
+            )
+            .expect("Failed to update batch");
+        store
+            .update_batch_status(
+                &pending_id,
+                "TEST",
+                Some(BatchStatus::Pending),
+                Vec::new(),
+                None,
+            )
+            .expect("Failed to update batch");
+
+        let created_at = store
+            .get_batch(&invalid_id, "TEST")
+            .expect("Failed to get batch")
+            .expect("Batch not found")
+            .created_at();
+
+        // Batches created at the cutoff are kept
+        assert_eq!(
+            store
+                .compact_batches(BatchStatusName::Invalid, created_at, 10)
+                .expect("Failed to compact batches"),
+            0
+        );
+
+        assert_eq!(
+            store
+                .compact_batches(BatchStatusName::Invalid, created_at + 1, 10)
+                .expect("Failed to compact batches"),
+            1
+        );
+        // Compacted batches are not compacted again
+        assert_eq!(
+            store
+                .compact_batches(BatchStatusName::Invalid, created_at + 1, 10)
+                .expect("Failed to compact batches"),
+            0
+        );
+
+        let compacted = store
+            .get_batch(&invalid_id, "TEST")
+            .expect("Failed to get batch")
+            .expect("Compacted batch not found");
+        assert!(compacted.serialized_batch().is_empty());
+        assert_eq!(
+            compacted.batch_status(),
+            Some(&BatchStatus::Invalid(invalid_transactions))
+        );
+
+        assert!(!store
+            .get_batch(&pending_id, "TEST")
+            .expect("Failed to get batch")
+            .expect("Pending batch not found")
+            .serialized_batch()
+            .is_empty());
+
+        assert_eq!(
+            store
+                .purge_batches(BatchStatusName::Invalid, created_at + 1, 10)
+                .expect("Failed to purge batches"),
+            1
+        );
+
+        assert_eq!(
+            store
+                .get_batch(&invalid_id, "TEST")
+                .expect("Failed to get batch"),
+            None
+        );
+        assert!(store
+            .get_batch(&pending_id, "TEST")
+            .expect("Failed to get batch")
+            .is_some());
+    }
+
+    /// Verify that a dead-lettered batch is listed with its reason and is no longer returned as
+    /// unsubmitted, and that replaying it queues it for submission again and records the actor.
+    #[test]
+    fn test_dead_letter_and_replay_batch() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselBatchTrackingStore::new(pool);
+
+        let signer = new_signer();
+
+        let pair = get_transact_transaction(&*signer, NONCE);
+
+        let batch = get_transact_batch(&*signer, vec![pair]);
+
+        let tracking_batch = get_tracking_batch(batch, false)
+            .build()
+            .expect("Failed to build batch");
+
+        let id = tracking_batch.batch_header().to_string();
+
+        store
+            .add_batches(vec![tracking_batch])
+            .expect("Failed to add batch");
+
+        store
+            .update_batch_status(
+                &id,
+                "TEST",
+                Some(BatchStatus::Delayed),
+                Vec::new(),
+                Some(
+                    SubmissionErrorBuilder::default()
+                        .with_error_type("Retryable".to_string())
+                        .with_error_message("HTTP 503: Unavailable".to_string())
+                        .build()
+                        .expect("Failed to build error"),
+                ),
+            )
+            .expect("Failed to update status");
+
+        store
+            .dead_letter_batch(&id, "TEST", "exhausted retries: HTTP 503: Unavailable")
+            .expect("Failed to dead-letter batch");
+
+        let dead_letters = store
+            .list_dead_letter_batches(Some("TEST"))
+            .expect("Failed to list dead-lettered batches");
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].batch().batch_header(), id);
+        assert_eq!(
+            dead_letters[0].reason(),
+            "exhausted retries: HTTP 503: Unavailable"
+        );
+        assert_eq!(
+            dead_letters[0].batch().batch_status(),
+            Some(&BatchStatus::Invalid(Vec::new()))
+        );
+        assert!(store
+            .list_dead_letter_batches(Some("OTHER"))
+            .expect("Failed to list dead-lettered batches")
+            .is_empty());
+        assert!(store
+            .get_unsubmitted_batches()
+            .expect("Failed to get unsubmitted batches")
+            .batches
+            .is_empty());
+
+        store
+            .replay_dead_letter_batch(&id, "TEST", KEY2, None)
+            .expect("Failed to replay batch");
+
+        assert!(store
+            .list_dead_letter_batches(None)
+            .expect("Failed to list dead-lettered batches")
+            .is_empty());
+
+        let unsubmitted = store
+            .get_unsubmitted_batches()
+            .expect("Failed to get unsubmitted batches")
+            .batches;
+        assert_eq!(unsubmitted.len(), 1);
+        assert_eq!(unsubmitted[0].batch_header(), id);
+        assert_eq!(unsubmitted[0].batch_status(), None);
+        assert_eq!(unsubmitted[0].submission_error(), None);
+
+        let audit_records = store
+            .list_dead_letter_audit_records(Some("TEST"))
+            .expect("Failed to list audit records");
+        assert_eq!(audit_records.len(), 1);
+        assert_eq!(audit_records[0].batch_id(), id);
+        assert_eq!(audit_records[0].action(), DeadLetterAction::Replay);
+        assert_eq!(audit_records[0].actor(), KEY2);
+        assert_eq!(audit_records[0].replacement_batch_id(), None);
+
+        // A batch that is not dead-lettered can not be replayed again
+        assert!(matches!(
+            store.replay_dead_letter_batch(&id, "TEST", KEY2, None),
+            Err(BatchTrackingStoreError::NotFoundError(_))
+        ));
+    }
+
+    /// Verify that replaying a dead-lettered batch with a replacement swaps the batches.
+    #[test]
+    fn test_replay_dead_letter_batch_with_replacement() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselBatchTrackingStore::new(pool);
+
+        let signer = new_signer();
+
+        let batch = get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE)]);
+        let tracking_batch = get_tracking_batch(batch, false)
+            .build()
+            .expect("Failed to build batch");
+        let id = tracking_batch.batch_header().to_string();
+
+        let replacement =
+            get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE2)]);
+        let replacement = get_tracking_batch(replacement, false)
+            .build()
+            .expect("Failed to build batch");
+        let replacement_id = replacement.batch_header().to_string();
+
+        store
+            .add_batches(vec![tracking_batch])
+            .expect("Failed to add batch");
+        store
+            .dead_letter_batch(&id, "TEST", "rejected")
+            .expect("Failed to dead-letter batch");
+        store
+            .replay_dead_letter_batch(&id, "TEST", KEY2, Some(replacement))
+            .expect("Failed to replay batch");
+
+        assert_eq!(
+            store.get_batch(&id, "TEST").expect("Failed to get batch"),
+            None
+        );
+        let unsubmitted = store
+            .get_unsubmitted_batches()
+            .expect("Failed to get unsubmitted batches")
+            .batches;
+        assert_eq!(unsubmitted.len(), 1);
+        assert_eq!(unsubmitted[0].batch_header(), replacement_id);
+
+        let audit_records = store
+            .list_dead_letter_audit_records(None)
+            .expect("Failed to list audit records");
+        assert_eq!(audit_records.len(), 1);
+        assert_eq!(
+            audit_records[0].replacement_batch_id(),
+            Some(replacement_id.as_str())
+        );
+    }
+
+    /// Verify that discarding a dead-lettered batch removes it and records the actor.
+    #[test]
+    fn test_discard_dead_letter_batch() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselBatchTrackingStore::new(pool);
+
+        let signer = new_signer();
+
+        let batch = get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE)]);
+        let tracking_batch = get_tracking_batch(batch, false)
+            .build()
+            .expect("Failed to build batch");
+        let id = tracking_batch.batch_header().to_string();
+
+        store
+            .add_batches(vec![tracking_batch])
+            .expect("Failed to add batch");
+
+        // Only dead-lettered batches can be discarded
+        assert!(matches!(
+            store.discard_dead_letter_batch(&id, "TEST", KEY2),
+            Err(BatchTrackingStoreError::NotFoundError(_))
+        ));
+
+        store
+            .dead_letter_batch(&id, "TEST", "rejected")
+            .expect("Failed to dead-letter batch");
+        store
+            .discard_dead_letter_batch(&id, "TEST", KEY2)
+            .expect("Failed to discard batch");
+
+        assert_eq!(
+            store.get_batch(&id, "TEST").expect("Failed to get batch"),
+            None
+        );
+        assert!(store
+            .list_dead_letter_batches(None)
+            .expect("Failed to list dead-lettered batches")
+            .is_empty());
+
+        let audit_records = store
+            .list_dead_letter_audit_records(None)
+            .expect("Failed to list audit records");
+        assert_eq!(audit_records.len(), 1);
+        assert_eq!(audit_records[0].action(), DeadLetterAction::Discard);
+        assert_eq!(audit_records[0].actor(), KEY2);
+    }
+
+    /// Verify that a change of batch status queues a notification for each subscription of the
+    /// batch's submitter that includes the new status, and that an unchanged status does not.
+    #[test]
+    fn test_webhook_deliveries_queued_on_status_change() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselBatchTrackingStore::new(pool);
+
+        let signer = new_signer();
+
+        let batch = get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE)]);
+        let tracking_batch = get_tracking_batch(batch, false)
+            .build()
+            .expect("Failed to build batch");
+        let id = tracking_batch.batch_header().to_string();
+
+        store
+            .add_batches(vec![tracking_batch])
+            .expect("Failed to add batch");
+
+        let committed = store
+            .add_webhook_subscription(
+                KEY1,
+                "http://localhost:9000/committed",
+                "secret",
+                &[BatchStatusName::Committed],
+            )
+            .expect("Failed to add subscription");
+        let all = store
+            .add_webhook_subscription(
+                KEY1,
+                "http://localhost:9000/all",
+                "secret",
+                &[BatchStatusName::Pending, BatchStatusName::Committed],
+            )
+            .expect("Failed to add subscription");
+        // Subscriptions of other submitters are not notified
+        store
+            .add_webhook_subscription(
+                KEY2,
+                "http://localhost:9000/other",
+                "secret",
+                &[BatchStatusName::Pending, BatchStatusName::Committed],
+            )
+            .expect("Failed to add subscription");
+
+        assert_eq!(
+            all.event_types(),
+            &[BatchStatusName::Pending, BatchStatusName::Committed]
+        );
+        assert_eq!(
+            store
+                .list_webhook_subscriptions(KEY1)
+                .expect("Failed to list subscriptions"),
+            vec![committed.clone(), all.clone()]
+        );
+
+        for status in &[
+            BatchStatus::Pending,
+            BatchStatus::Pending,
+            BatchStatus::Committed(vec![]),
+        ] {
+            store
+                .update_batch_status(&id, "TEST", Some(status.clone()), vec![], None)
+                .expect("Failed to update batch status");
+        }
+
+        let deliveries = store
+            .list_webhook_deliveries(committed.id(), KEY1)
+            .expect("Failed to list deliveries");
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].event_type(), BatchStatusName::Committed);
+        assert_eq!(
+            deliveries[0].previous_status(),
+            Some(BatchStatusName::Pending)
+        );
+        assert_eq!(deliveries[0].url(), "http://localhost:9000/committed");
+        assert_eq!(deliveries[0].status(), WebhookDeliveryStatus::Pending);
+
+        let deliveries = store
+            .list_webhook_deliveries(all.id(), KEY1)
+            .expect("Failed to list deliveries");
+        assert_eq!(
+            deliveries
+                .iter()
+                .map(|delivery| (delivery.event_type(), delivery.previous_status()))
+                .collect::<Vec<_>>(),
+            vec![
+                (BatchStatusName::Pending, None),
+                (BatchStatusName::Committed, Some(BatchStatusName::Pending)),
+            ]
+        );
+
+        assert_eq!(
+            store
+                .get_pending_webhook_deliveries(i64::MAX, 10)
+                .expect("Failed to get pending deliveries")
+                .len(),
+            3
+        );
+
+        // A subscription can only be read or removed by its owner
+        assert!(matches!(
+            store.list_webhook_deliveries(all.id(), KEY2),
+            Err(BatchTrackingStoreError::NotFoundError(_))
+        ));
+        assert!(matches!(
+            store.remove_webhook_subscription(all.id(), KEY2),
+            Err(BatchTrackingStoreError::NotFoundError(_))
+        ));
+
+        store
+            .remove_webhook_subscription(all.id(), KEY1)
+            .expect("Failed to remove subscription");
+        assert_eq!(
+            store
+                .list_webhook_subscriptions(KEY1)
+                .expect("Failed to list subscriptions"),
+            vec![committed]
+        );
+        assert_eq!(
+            store
+                .get_pending_webhook_deliveries(i64::MAX, 10)
+                .expect("Failed to get pending deliveries")
+                .len(),
+            1
+        );
+    }
+
+    /// Verify that delivery attempts are logged and update the state of the notification.
+    #[test]
+    fn test_record_webhook_delivery_attempt() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselBatchTrackingStore::new(pool);
+
+        let signer = new_signer();
+
+        let batch = get_transact_batch(&*signer, vec![get_transact_transaction(&*signer, NONCE)]);
+        let tracking_batch = get_tracking_batch(batch, false)
+            .build()
+            .expect("Failed to build batch");
+        let id = tracking_batch.batch_header().to_string();
+
+        store
+            .add_batches(vec![tracking_batch])
+            .expect("Failed to add batch");
+        let subscription = store
+            .add_webhook_subscription(
+                KEY1,
+                "http://localhost:9000/invalid",
+                "secret",
+                &[BatchStatusName::Invalid],
+            )
+            .expect("Failed to add subscription");
+        store
+            .dead_letter_batch(&id, "TEST", "rejected")
+            .expect("Failed to dead-letter batch");
+
+        let delivery = store
+            .get_pending_webhook_deliveries(i64::MAX, 10)
+            .expect("Failed to get pending deliveries")
+            .pop()
+            .expect("No delivery was queued");
+        assert_eq!(delivery.subscription_id(), subscription.id());
+        assert_eq!(delivery.event_type(), BatchStatusName::Invalid);
+
+        store
+            .record_webhook_delivery_attempt(
+                delivery.id(),
+                100,
+                None,
+                Some("connection refused"),
+                WebhookDeliveryStatus::Pending,
+                200,
+            )
+            .expect("Failed to record attempt");
+
+        // The notification is not due before its next attempt
+        assert!(store
+            .get_pending_webhook_deliveries(199, 10)
+            .expect("Failed to get pending deliveries")
+            .is_empty());
+
+        store
+            .record_webhook_delivery_attempt(
+                delivery.id(),
+                200,
+                Some(204),
+                None,
+                WebhookDeliveryStatus::Delivered,
+                200,
+            )
+            .expect("Failed to record attempt");
+
+        assert!(store
+            .get_pending_webhook_deliveries(i64::MAX, 10)
+            .expect("Failed to get pending deliveries")
+            .is_empty());
+
+        let delivery = store
+            .list_webhook_deliveries(subscription.id(), KEY1)
+            .expect("Failed to list deliveries")
+            .pop()
+            .expect("Delivery was not found");
+        assert_eq!(delivery.status(), WebhookDeliveryStatus::Delivered);
+        assert_eq!(delivery.attempts(), 2);
+        assert_eq!(delivery.delivered_at(), Some(200));
+
+        let attempts = store
+            .list_webhook_delivery_attempts(delivery.id())
+            .expect("Failed to list attempts");
+        assert_eq!(
+            attempts
+                .iter()
+                .map(|attempt| (
+                    attempt.attempted_at(),
+                    attempt.response_code(),
+                    attempt.error()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (100, None, Some("connection refused")),
+                (200, Some(204), None)
+            ]
+        );
+
+        assert!(matches!(
+            store.record_webhook_delivery_attempt(
+                delivery.id() + 1,
+                300,
+                None,
+                None,
+                WebhookDeliveryStatus::Delivered,
+                300,
+            ),
+            Err(BatchTrackingStoreError::NotFoundError(_))
+        ));
+    }
+
     /// Creates a connection pool for an in-memory SQLite database with only a single connection
     /// available. Each connection is backed by a different in-memory SQLite database, so limiting
     /// the pool to a single connection ensures that the same DB is used for all operations.
@@ -14,14 +14,17 @@
 
 use core::convert::TryFrom;
 use regex::Regex;
+use std::str::FromStr;
 
 use crate::batch_tracking::store::diesel::schema::*;
-use crate::batch_tracking::store::NON_SPLINTER_SERVICE_ID_DEFAULT;
+use crate::batch_tracking::store::{BatchPriority, NON_SPLINTER_SERVICE_ID_DEFAULT};
 use crate::error::InternalError;
 
 use super::{
-    BatchStatus, InvalidTransaction, SubmissionError, TrackingBatch, TrackingBatchList,
-    TrackingTransaction, TransactionReceipt, ValidTransaction,
+    BatchStatus, BatchStatusName, DeadLetterAction, DeadLetterAuditRecord, InvalidTransaction,
+    SubmissionError, TrackingBatch, TrackingBatchList, TrackingTransaction, TransactionReceipt,
+    ValidTransaction, WebhookDelivery, WebhookDeliveryAttempt, WebhookDeliveryStatus,
+    WebhookSubscription,
 };
 use crate::batch_tracking::store::error::BatchTrackingStoreError;
 
@@ -38,6 +41,7 @@ pub struct NewBatchModel {
     pub trace: bool,
     pub serialized_batch: Vec<u8>,
     pub submitted: bool,
+    pub priority: String,
 }
 
 #[derive(Identifiable, Insertable, Queryable, PartialEq, Eq, Debug, Clone)]
@@ -52,6 +56,7 @@ pub struct BatchModel {
     pub serialized_batch: Vec<u8>,
     pub submitted: bool,
     pub created_at: i64,
+    pub priority: String,
 }
 
 #[derive(Identifiable, Insertable, Queryable, PartialEq, Eq, Debug, QueryableByName)]
@@ -157,6 +162,8 @@ impl
             serialized_batch: batch.serialized_batch.to_vec(),
             submitted: batch.submitted,
             created_at: batch.created_at,
+            // Priorities the store does not know of are treated as the default
+            priority: BatchPriority::from_str(&batch.priority).unwrap_or_default(),
             transactions,
             batch_status,
             submission_error,
@@ -422,10 +429,215 @@ impl TryFrom<&SubmissionModel> for SubmissionError {
         Ok(Self {
             error_message,
             error_type,
+            attempts: submission.times_checked,
+            last_attempt_at: submission.last_checked,
         })
     }
 }
 
+#[derive(Insertable, Debug)]
+#[table_name = "dead_letter_batches"]
+pub struct NewDeadLetterBatchModel {
+    pub service_id: String,
+    pub batch_id: String,
+    pub reason: String,
+}
+
+#[derive(Identifiable, Queryable, PartialEq, Eq, Debug, Clone)]
+#[table_name = "dead_letter_batches"]
+#[primary_key(service_id, batch_id)]
+pub struct DeadLetterBatchModel {
+    pub service_id: String,
+    pub batch_id: String,
+    pub reason: String,
+    pub created_at: i64,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "dead_letter_actions"]
+pub struct NewDeadLetterActionModel {
+    pub service_id: String,
+    pub batch_id: String,
+    pub action: String,
+    pub actor: String,
+    pub replacement_batch_id: Option<String>,
+}
+
+#[derive(Identifiable, Queryable, PartialEq, Eq, Debug)]
+#[table_name = "dead_letter_actions"]
+pub struct DeadLetterActionModel {
+    pub id: i64,
+    pub service_id: String,
+    pub batch_id: String,
+    pub action: String,
+    pub actor: String,
+    pub replacement_batch_id: Option<String>,
+    pub created_at: i64,
+}
+
+impl TryFrom<DeadLetterActionModel> for DeadLetterAuditRecord {
+    type Error = BatchTrackingStoreError;
+
+    fn try_from(model: DeadLetterActionModel) -> Result<Self, Self::Error> {
+        Ok(Self {
+            action: DeadLetterAction::try_from_string(&model.action)?,
+            service_id: model.service_id,
+            batch_id: model.batch_id,
+            actor: model.actor,
+            replacement_batch_id: model.replacement_batch_id,
+            created_at: model.created_at,
+        })
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "webhook_subscriptions"]
+pub struct NewWebhookSubscriptionModel {
+    pub signer_public_key: String,
+    pub url: String,
+    pub secret: String,
+    pub event_types: String,
+}
+
+#[derive(Identifiable, Queryable, PartialEq, Eq, Debug, Clone)]
+#[table_name = "webhook_subscriptions"]
+pub struct WebhookSubscriptionModel {
+    pub id: i64,
+    pub signer_public_key: String,
+    pub url: String,
+    pub secret: String,
+    pub event_types: String,
+    pub created_at: i64,
+}
+
+/// Joins the event types of a subscription into the comma-separated form they are stored in
+pub fn join_event_types(event_types: &[BatchStatusName]) -> String {
+    event_types
+        .iter()
+        .map(|event_type| event_type.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn split_event_types(event_types: &str) -> Result<Vec<BatchStatusName>, BatchTrackingStoreError> {
+    event_types
+        .split(',')
+        .filter(|event_type| !event_type.is_empty())
+        .map(BatchStatusName::try_from_string)
+        .collect()
+}
+
+impl WebhookSubscriptionModel {
+    /// Returns true if the subscription is notified when a batch changes to the given status
+    pub fn subscribes_to(&self, status: &str) -> bool {
+        self.event_types
+            .split(',')
+            .any(|event_type| event_type == status)
+    }
+}
+
+impl TryFrom<WebhookSubscriptionModel> for WebhookSubscription {
+    type Error = BatchTrackingStoreError;
+
+    fn try_from(model: WebhookSubscriptionModel) -> Result<Self, Self::Error> {
+        Ok(Self {
+            event_types: split_event_types(&model.event_types)?,
+            id: model.id,
+            signer_public_key: model.signer_public_key,
+            url: model.url,
+            secret: model.secret,
+            created_at: model.created_at,
+        })
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "webhook_deliveries"]
+pub struct NewWebhookDeliveryModel {
+    pub subscription_id: i64,
+    pub service_id: String,
+    pub batch_id: String,
+    pub data_change_id: Option<String>,
+    pub event_type: String,
+    pub previous_status: Option<String>,
+}
+
+#[derive(Identifiable, Queryable, PartialEq, Eq, Debug, Clone)]
+#[table_name = "webhook_deliveries"]
+pub struct WebhookDeliveryModel {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub service_id: String,
+    pub batch_id: String,
+    pub data_change_id: Option<String>,
+    pub event_type: String,
+    pub previous_status: Option<String>,
+    pub status: String,
+    pub attempts: i64,
+    pub next_attempt_at: i64,
+    pub created_at: i64,
+    pub delivered_at: Option<i64>,
+}
+
+impl TryFrom<(WebhookDeliveryModel, WebhookSubscriptionModel)> for WebhookDelivery {
+    type Error = BatchTrackingStoreError;
+
+    fn try_from(
+        (delivery, subscription): (WebhookDeliveryModel, WebhookSubscriptionModel),
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            event_type: BatchStatusName::try_from_string(&delivery.event_type)?,
+            previous_status: delivery
+                .previous_status
+                .as_deref()
+                .map(BatchStatusName::try_from_string)
+                .transpose()?,
+            status: WebhookDeliveryStatus::try_from_string(&delivery.status)?,
+            id: delivery.id,
+            subscription_id: delivery.subscription_id,
+            url: subscription.url,
+            secret: subscription.secret,
+            service_id: delivery.service_id,
+            batch_id: delivery.batch_id,
+            data_change_id: delivery.data_change_id,
+            attempts: delivery.attempts,
+            next_attempt_at: delivery.next_attempt_at,
+            created_at: delivery.created_at,
+            delivered_at: delivery.delivered_at,
+        })
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "webhook_delivery_attempts"]
+pub struct NewWebhookDeliveryAttemptModel {
+    pub delivery_id: i64,
+    pub response_code: Option<i64>,
+    pub error: Option<String>,
+    pub attempted_at: i64,
+}
+
+#[derive(Identifiable, Queryable, PartialEq, Eq, Debug)]
+#[table_name = "webhook_delivery_attempts"]
+pub struct WebhookDeliveryAttemptModel {
+    pub id: i64,
+    pub delivery_id: i64,
+    pub response_code: Option<i64>,
+    pub error: Option<String>,
+    pub attempted_at: i64,
+}
+
+impl From<WebhookDeliveryAttemptModel> for WebhookDeliveryAttempt {
+    fn from(model: WebhookDeliveryAttemptModel) -> Self {
+        Self {
+            delivery_id: model.delivery_id,
+            response_code: model.response_code,
+            error: model.error,
+            attempted_at: model.attempted_at,
+        }
+    }
+}
+
 impl
     TryFrom<(
         Vec<BatchModel>,
@@ -527,6 +739,7 @@ pub fn make_new_batch_models(batches: &[TrackingBatch]) -> Vec<NewBatchModel> {
             trace: batch.trace(),
             serialized_batch: batch.serialized_batch().to_vec(),
             submitted: batch.submitted(),
+            priority: batch.priority().to_string(),
         };
 
         models.push(model)
@@ -0,0 +1,98 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::{
+        models::{join_event_types, NewWebhookSubscriptionModel, WebhookSubscriptionModel},
+        schema::webhook_subscriptions,
+    },
+    BatchStatusName, BatchTrackingStoreError, WebhookSubscription,
+};
+use diesel::{dsl::insert_into, prelude::*};
+use std::convert::TryFrom;
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreAddWebhookSubscriptionOperation
+{
+    fn add_webhook_subscription(
+        &self,
+        signer_public_key: &str,
+        url: &str,
+        secret: &str,
+        event_types: &[BatchStatusName],
+    ) -> Result<WebhookSubscription, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreAddWebhookSubscriptionOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn add_webhook_subscription(
+        &self,
+        signer_public_key: &str,
+        url: &str,
+        secret: &str,
+        event_types: &[BatchStatusName],
+    ) -> Result<WebhookSubscription, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            insert_into(webhook_subscriptions::table)
+                .values(NewWebhookSubscriptionModel {
+                    signer_public_key: signer_public_key.to_string(),
+                    url: url.to_string(),
+                    secret: secret.to_string(),
+                    event_types: join_event_types(event_types),
+                })
+                .execute(self.conn)?;
+
+            let subscription = webhook_subscriptions::table
+                .filter(webhook_subscriptions::signer_public_key.eq(signer_public_key))
+                .order_by(webhook_subscriptions::id.desc())
+                .first::<WebhookSubscriptionModel>(self.conn)?;
+
+            WebhookSubscription::try_from(subscription)
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreAddWebhookSubscriptionOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_webhook_subscription(
+        &self,
+        signer_public_key: &str,
+        url: &str,
+        secret: &str,
+        event_types: &[BatchStatusName],
+    ) -> Result<WebhookSubscription, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            insert_into(webhook_subscriptions::table)
+                .values(NewWebhookSubscriptionModel {
+                    signer_public_key: signer_public_key.to_string(),
+                    url: url.to_string(),
+                    secret: secret.to_string(),
+                    event_types: join_event_types(event_types),
+                })
+                .execute(self.conn)?;
+
+            let subscription = webhook_subscriptions::table
+                .filter(webhook_subscriptions::signer_public_key.eq(signer_public_key))
+                .order_by(webhook_subscriptions::id.desc())
+                .first::<WebhookSubscriptionModel>(self.conn)?;
+
+            WebhookSubscription::try_from(subscription)
+        })
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::get_batch_id_from_data_change_id::BatchTrackingStoreGetBatchIdFromDataChangeIdOperation as _;
 use super::BatchTrackingStoreOperations;
 use crate::error::InternalError;
 
@@ -56,18 +57,12 @@ impl<'a> BatchTrackingStoreChangeBatchToSubmittedOperation
         submission: NewSubmissionModel,
     ) -> Result<(), BatchTrackingStoreError> {
         self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
-            let mut batch_id = id.to_string();
-            let is_dcid = is_data_change_id(id)?;
-            if is_dcid {
-                batch_id = batches::table
-                    .select(batches::batch_id)
-                    .filter(
-                        batches::data_change_id
-                            .eq(&id)
-                            .and(batches::service_id.eq(&service_id)),
-                    )
-                    .first::<String>(self.conn)?;
-            }
+            let batch_id = if is_data_change_id(id)? {
+                self.get_batch_id_from_data_change_id(id, service_id)?
+                    .ok_or(diesel::result::Error::NotFound)?
+            } else {
+                id.to_string()
+            };
 
             let batch_exists: bool = select(exists(
                 batches::table.filter(
@@ -222,18 +217,12 @@ impl<'a> BatchTrackingStoreChangeBatchToSubmittedOperation
         submission: NewSubmissionModel,
     ) -> Result<(), BatchTrackingStoreError> {
         self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
-            let mut batch_id = id.to_string();
-            let is_dcid = is_data_change_id(id)?;
-            if is_dcid {
-                batch_id = batches::table
-                    .select(batches::batch_id)
-                    .filter(
-                        batches::data_change_id
-                            .eq(&id)
-                            .and(batches::service_id.eq(&service_id)),
-                    )
-                    .first::<String>(self.conn)?;
-            }
+            let batch_id = if is_data_change_id(id)? {
+                self.get_batch_id_from_data_change_id(id, service_id)?
+                    .ok_or(diesel::result::Error::NotFound)?
+            } else {
+                id.to_string()
+            };
             let batch_exists: bool = select(exists(
                 batches::table.filter(
                     batches::batch_id
@@ -0,0 +1,152 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::schema::{batch_statuses, batches, dead_letter_batches, transactions},
+    BatchStatusName, BatchTrackingStoreError,
+};
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreCompactBatchesOperation {
+    fn compact_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreCompactBatchesOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn compact_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            // Dead-lettered batches are kept until an operator replays or discards them
+            let expired = batches::table
+                .inner_join(
+                    batch_statuses::table.on(batches::batch_id
+                        .eq(batch_statuses::batch_id)
+                        .and(batches::service_id.eq(batch_statuses::service_id))),
+                )
+                .left_join(
+                    dead_letter_batches::table.on(batches::batch_id
+                        .eq(dead_letter_batches::batch_id)
+                        .and(batches::service_id.eq(dead_letter_batches::service_id))),
+                )
+                .select((batches::service_id, batches::batch_id))
+                .filter(
+                    batch_statuses::dlt_status
+                        .eq(status.to_string())
+                        .and(batches::created_at.lt(created_before))
+                        .and(dead_letter_batches::batch_id.is_null())
+                        .and(batches::serialized_batch.ne(Vec::<u8>::new())),
+                )
+                .order(batches::created_at.asc())
+                .limit(limit)
+                .load::<(String, String)>(self.conn)?;
+
+            let mut compacted = 0;
+            for (service_id, batch_id) in expired {
+                update(transactions::table)
+                    .filter(
+                        transactions::batch_id
+                            .eq(&batch_id)
+                            .and(transactions::service_id.eq(&service_id)),
+                    )
+                    .set(transactions::payload.eq(Vec::<u8>::new()))
+                    .execute(self.conn)?;
+
+                compacted += update(batches::table)
+                    .filter(
+                        batches::batch_id
+                            .eq(&batch_id)
+                            .and(batches::service_id.eq(&service_id)),
+                    )
+                    .set(batches::serialized_batch.eq(Vec::<u8>::new()))
+                    .execute(self.conn)?;
+            }
+
+            Ok(compacted)
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreCompactBatchesOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn compact_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            // Dead-lettered batches are kept until an operator replays or discards them
+            let expired = batches::table
+                .inner_join(
+                    batch_statuses::table.on(batches::batch_id
+                        .eq(batch_statuses::batch_id)
+                        .and(batches::service_id.eq(batch_statuses::service_id))),
+                )
+                .left_join(
+                    dead_letter_batches::table.on(batches::batch_id
+                        .eq(dead_letter_batches::batch_id)
+                        .and(batches::service_id.eq(dead_letter_batches::service_id))),
+                )
+                .select((batches::service_id, batches::batch_id))
+                .filter(
+                    batch_statuses::dlt_status
+                        .eq(status.to_string())
+                        .and(batches::created_at.lt(created_before))
+                        .and(dead_letter_batches::batch_id.is_null())
+                        .and(batches::serialized_batch.ne(Vec::<u8>::new())),
+                )
+                .order(batches::created_at.asc())
+                .limit(limit)
+                .load::<(String, String)>(self.conn)?;
+
+            let mut compacted = 0;
+            for (service_id, batch_id) in expired {
+                update(transactions::table)
+                    .filter(
+                        transactions::batch_id
+                            .eq(&batch_id)
+                            .and(transactions::service_id.eq(&service_id)),
+                    )
+                    .set(transactions::payload.eq(Vec::<u8>::new()))
+                    .execute(self.conn)?;
+
+                compacted += update(batches::table)
+                    .filter(
+                        batches::batch_id
+                            .eq(&batch_id)
+                            .and(batches::service_id.eq(&service_id)),
+                    )
+                    .set(batches::serialized_batch.eq(Vec::<u8>::new()))
+                    .execute(self.conn)?;
+            }
+
+            Ok(compacted)
+        })
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::enqueue_webhook_deliveries::BatchTrackingStoreEnqueueWebhookDeliveriesOperation as _;
+use super::get_batch_id_from_data_change_id::BatchTrackingStoreGetBatchIdFromDataChangeIdOperation as _;
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::{
+        models::{is_data_change_id, NewBatchStatusModel, NewDeadLetterBatchModel},
+        schema::{batch_statuses, batches, dead_letter_batches},
+    },
+    BatchStatusName, BatchTrackingStoreError,
+};
+use diesel::{
+    dsl::{exists, insert_into, update},
+    prelude::*,
+    select,
+};
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreDeadLetterBatchOperation {
+    fn dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        reason: &str,
+    ) -> Result<(), BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreDeadLetterBatchOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        reason: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let batch_id = if is_data_change_id(id)? {
+                self.get_batch_id_from_data_change_id(id, service_id)?
+            } else {
+                batches::table
+                    .select(batches::batch_id)
+                    .filter(
+                        batches::batch_id
+                            .eq(&id)
+                            .and(batches::service_id.eq(&service_id)),
+                    )
+                    .first::<String>(self.conn)
+                    .optional()?
+            }
+            .ok_or_else(|| {
+                BatchTrackingStoreError::NotFoundError(format!(
+                    "Could not find batch with ID {}",
+                    id
+                ))
+            })?;
+
+            let status = NewBatchStatusModel {
+                service_id: service_id.to_string(),
+                batch_id: batch_id.clone(),
+                dlt_status: BatchStatusName::Invalid.to_string(),
+            };
+
+            let previous_status = batch_statuses::table
+                .select(batch_statuses::dlt_status)
+                .filter(
+                    batch_statuses::batch_id
+                        .eq(&batch_id)
+                        .and(batch_statuses::service_id.eq(&service_id)),
+                )
+                .first::<String>(self.conn)
+                .optional()?;
+
+            if previous_status.is_some() {
+                update(batch_statuses::table)
+                    .filter(
+                        batch_statuses::batch_id
+                            .eq(&batch_id)
+                            .and(batch_statuses::service_id.eq(&service_id)),
+                    )
+                    .set(&status)
+                    .execute(self.conn)?;
+            } else {
+                insert_into(batch_statuses::table)
+                    .values(&status)
+                    .execute(self.conn)?;
+            }
+
+            self.enqueue_webhook_deliveries(
+                &batch_id,
+                service_id,
+                previous_status.as_deref(),
+                &status.dlt_status,
+            )?;
+
+            update(batches::table)
+                .filter(
+                    batches::batch_id
+                        .eq(&batch_id)
+                        .and(batches::service_id.eq(&service_id)),
+                )
+                .set(batches::submitted.eq(true))
+                .execute(self.conn)?;
+
+            let dead_lettered: bool = select(exists(
+                dead_letter_batches::table.filter(
+                    dead_letter_batches::batch_id
+                        .eq(&batch_id)
+                        .and(dead_letter_batches::service_id.eq(&service_id)),
+                ),
+            ))
+            .get_result(self.conn)?;
+
+            if dead_lettered {
+                update(dead_letter_batches::table)
+                    .filter(
+                        dead_letter_batches::batch_id
+                            .eq(&batch_id)
+                            .and(dead_letter_batches::service_id.eq(&service_id)),
+                    )
+                    .set(dead_letter_batches::reason.eq(reason))
+                    .execute(self.conn)?;
+            } else {
+                insert_into(dead_letter_batches::table)
+                    .values(NewDeadLetterBatchModel {
+                        service_id: service_id.to_string(),
+                        batch_id,
+                        reason: reason.to_string(),
+                    })
+                    .execute(self.conn)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreDeadLetterBatchOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        reason: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let batch_id = if is_data_change_id(id)? {
+                self.get_batch_id_from_data_change_id(id, service_id)?
+            } else {
+                batches::table
+                    .select(batches::batch_id)
+                    .filter(
+                        batches::batch_id
+                            .eq(&id)
+                            .and(batches::service_id.eq(&service_id)),
+                    )
+                    .first::<String>(self.conn)
+                    .optional()?
+            }
+            .ok_or_else(|| {
+                BatchTrackingStoreError::NotFoundError(format!(
+                    "Could not find batch with ID {}",
+                    id
+                ))
+            })?;
+
+            let status = NewBatchStatusModel {
+                service_id: service_id.to_string(),
+                batch_id: batch_id.clone(),
+                dlt_status: BatchStatusName::Invalid.to_string(),
+            };
+
+            let previous_status = batch_statuses::table
+                .select(batch_statuses::dlt_status)
+                .filter(
+                    batch_statuses::batch_id
+                        .eq(&batch_id)
+                        .and(batch_statuses::service_id.eq(&service_id)),
+                )
+                .first::<String>(self.conn)
+                .optional()?;
+
+            if previous_status.is_some() {
+                update(batch_statuses::table)
+                    .filter(
+                        batch_statuses::batch_id
+                            .eq(&batch_id)
+                            .and(batch_statuses::service_id.eq(&service_id)),
+                    )
+                    .set(&status)
+                    .execute(self.conn)?;
+            } else {
+                insert_into(batch_statuses::table)
+                    .values(&status)
+                    .execute(self.conn)?;
+            }
+
+            self.enqueue_webhook_deliveries(
+                &batch_id,
+                service_id,
+                previous_status.as_deref(),
+                &status.dlt_status,
+            )?;
+
+            update(batches::table)
+                .filter(
+                    batches::batch_id
+                        .eq(&batch_id)
+                        .and(batches::service_id.eq(&service_id)),
+                )
+                .set(batches::submitted.eq(true))
+                .execute(self.conn)?;
+
+            let dead_lettered: bool = select(exists(
+                dead_letter_batches::table.filter(
+                    dead_letter_batches::batch_id
+                        .eq(&batch_id)
+                        .and(dead_letter_batches::service_id.eq(&service_id)),
+                ),
+            ))
+            .get_result(self.conn)?;
+
+            if dead_lettered {
+                update(dead_letter_batches::table)
+                    .filter(
+                        dead_letter_batches::batch_id
+                            .eq(&batch_id)
+                            .and(dead_letter_batches::service_id.eq(&service_id)),
+                    )
+                    .set(dead_letter_batches::reason.eq(reason))
+                    .execute(self.conn)?;
+            } else {
+                insert_into(dead_letter_batches::table)
+                    .values(NewDeadLetterBatchModel {
+                        service_id: service_id.to_string(),
+                        batch_id,
+                        reason: reason.to_string(),
+                    })
+                    .execute(self.conn)?;
+            }
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::get_batch_id_from_data_change_id::BatchTrackingStoreGetBatchIdFromDataChangeIdOperation as _;
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::{
+        models::{is_data_change_id, NewDeadLetterActionModel},
+        schema::{
+            batch_statuses, batches, dead_letter_actions, dead_letter_batches, submissions,
+            transaction_receipts, transactions,
+        },
+    },
+    BatchTrackingStoreError, DeadLetterAction,
+};
+use diesel::{
+    dsl::{delete, exists, insert_into},
+    prelude::*,
+    select,
+};
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreDiscardDeadLetterBatchOperation
+{
+    fn discard_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+    ) -> Result<(), BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreDiscardDeadLetterBatchOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn discard_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let batch_id = if is_data_change_id(id)? {
+                self.get_batch_id_from_data_change_id(id, service_id)?
+            } else {
+                batches::table
+                    .select(batches::batch_id)
+                    .filter(
+                        batches::batch_id
+                            .eq(&id)
+                            .and(batches::service_id.eq(&service_id)),
+                    )
+                    .first::<String>(self.conn)
+                    .optional()?
+            }
+            .ok_or_else(|| {
+                BatchTrackingStoreError::NotFoundError(format!(
+                    "Could not find batch with ID {}",
+                    id
+                ))
+            })?;
+
+            let dead_lettered: bool = select(exists(
+                dead_letter_batches::table.filter(
+                    dead_letter_batches::batch_id
+                        .eq(&batch_id)
+                        .and(dead_letter_batches::service_id.eq(&service_id)),
+                ),
+            ))
+            .get_result(self.conn)?;
+
+            if !dead_lettered {
+                return Err(BatchTrackingStoreError::NotFoundError(format!(
+                    "Batch {} is not dead-lettered",
+                    batch_id
+                )));
+            }
+
+            let transaction_ids: Vec<String> = transactions::table
+                .select(transactions::transaction_id)
+                .filter(
+                    transactions::batch_id
+                        .eq(&batch_id)
+                        .and(transactions::service_id.eq(&service_id)),
+                )
+                .load(self.conn)?;
+
+            delete(
+                transaction_receipts::table.filter(
+                    transaction_receipts::service_id
+                        .eq(&service_id)
+                        .and(transaction_receipts::transaction_id.eq_any(&transaction_ids)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                batch_statuses::table.filter(
+                    batch_statuses::batch_id
+                        .eq(&batch_id)
+                        .and(batch_statuses::service_id.eq(&service_id)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                submissions::table.filter(
+                    submissions::batch_id
+                        .eq(&batch_id)
+                        .and(submissions::service_id.eq(&service_id)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                dead_letter_batches::table.filter(
+                    dead_letter_batches::batch_id
+                        .eq(&batch_id)
+                        .and(dead_letter_batches::service_id.eq(&service_id)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                transactions::table.filter(
+                    transactions::batch_id
+                        .eq(&batch_id)
+                        .and(transactions::service_id.eq(&service_id)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                batches::table.filter(
+                    batches::batch_id
+                        .eq(&batch_id)
+                        .and(batches::service_id.eq(&service_id)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            insert_into(dead_letter_actions::table)
+                .values(NewDeadLetterActionModel {
+                    service_id: service_id.to_string(),
+                    batch_id,
+                    action: DeadLetterAction::Discard.to_string(),
+                    actor: actor.to_string(),
+                    replacement_batch_id: None,
+                })
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreDiscardDeadLetterBatchOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn discard_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let batch_id = if is_data_change_id(id)? {
+                self.get_batch_id_from_data_change_id(id, service_id)?
+            } else {
+                batches::table
+                    .select(batches::batch_id)
+                    .filter(
+                        batches::batch_id
+                            .eq(&id)
+                            .and(batches::service_id.eq(&service_id)),
+                    )
+                    .first::<String>(self.conn)
+                    .optional()?
+            }
+            .ok_or_else(|| {
+                BatchTrackingStoreError::NotFoundError(format!(
+                    "Could not find batch with ID {}",
+                    id
+                ))
+            })?;
+
+            let dead_lettered: bool = select(exists(
+                dead_letter_batches::table.filter(
+                    dead_letter_batches::batch_id
+                        .eq(&batch_id)
+                        .and(dead_letter_batches::service_id.eq(&service_id)),
+                ),
+            ))
+            .get_result(self.conn)?;
+
+            if !dead_lettered {
+                return Err(BatchTrackingStoreError::NotFoundError(format!(
+                    "Batch {} is not dead-lettered",
+                    batch_id
+                )));
+            }
+
+            let transaction_ids: Vec<String> = transactions::table
+                .select(transactions::transaction_id)
+                .filter(
+                    transactions::batch_id
+                        .eq(&batch_id)
+                        .and(transactions::service_id.eq(&service_id)),
+                )
+                .load(self.conn)?;
+
+            delete(
+                transaction_receipts::table.filter(
+                    transaction_receipts::service_id
+                        .eq(&service_id)
+                        .and(transaction_receipts::transaction_id.eq_any(&transaction_ids)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                batch_statuses::table.filter(
+                    batch_statuses::batch_id
+                        .eq(&batch_id)
+                        .and(batch_statuses::service_id.eq(&service_id)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                submissions::table.filter(
+                    submissions::batch_id
+                        .eq(&batch_id)
+                        .and(submissions::service_id.eq(&service_id)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                dead_letter_batches::table.filter(
+                    dead_letter_batches::batch_id
+                        .eq(&batch_id)
+                        .and(dead_letter_batches::service_id.eq(&service_id)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                transactions::table.filter(
+                    transactions::batch_id
+                        .eq(&batch_id)
+                        .and(transactions::service_id.eq(&service_id)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                batches::table.filter(
+                    batches::batch_id
+                        .eq(&batch_id)
+                        .and(batches::service_id.eq(&service_id)),
+                ),
+            )
+            .execute(self.conn)?;
+
+            insert_into(dead_letter_actions::table)
+                .values(NewDeadLetterActionModel {
+                    service_id: service_id.to_string(),
+                    batch_id,
+                    action: DeadLetterAction::Discard.to_string(),
+                    actor: actor.to_string(),
+                    replacement_batch_id: None,
+                })
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
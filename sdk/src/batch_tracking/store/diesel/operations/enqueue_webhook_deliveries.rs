@@ -0,0 +1,145 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::{
+        models::{NewWebhookDeliveryModel, WebhookSubscriptionModel},
+        schema::{batches, webhook_deliveries, webhook_subscriptions},
+    },
+    BatchTrackingStoreError,
+};
+use diesel::{dsl::insert_into, prelude::*};
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreEnqueueWebhookDeliveriesOperation
+{
+    fn enqueue_webhook_deliveries(
+        &self,
+        batch_id: &str,
+        service_id: &str,
+        previous_status: Option<&str>,
+        status: &str,
+    ) -> Result<(), BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreEnqueueWebhookDeliveriesOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn enqueue_webhook_deliveries(
+        &self,
+        batch_id: &str,
+        service_id: &str,
+        previous_status: Option<&str>,
+        status: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        // Only an actual change of status is notified
+        if previous_status == Some(status) {
+            return Ok(());
+        }
+
+        let (signer_public_key, data_change_id) = match batches::table
+            .select((batches::signer_public_key, batches::data_change_id))
+            .filter(
+                batches::batch_id
+                    .eq(&batch_id)
+                    .and(batches::service_id.eq(&service_id)),
+            )
+            .first::<(String, Option<String>)>(self.conn)
+            .optional()?
+        {
+            Some(batch) => batch,
+            None => return Ok(()),
+        };
+
+        let deliveries = webhook_subscriptions::table
+            .filter(webhook_subscriptions::signer_public_key.eq(&signer_public_key))
+            .load::<WebhookSubscriptionModel>(self.conn)?
+            .into_iter()
+            .filter(|subscription| subscription.subscribes_to(status))
+            .map(|subscription| NewWebhookDeliveryModel {
+                subscription_id: subscription.id,
+                service_id: service_id.to_string(),
+                batch_id: batch_id.to_string(),
+                data_change_id: data_change_id.clone(),
+                event_type: status.to_string(),
+                previous_status: previous_status.map(String::from),
+            })
+            .collect::<Vec<_>>();
+
+        if !deliveries.is_empty() {
+            insert_into(webhook_deliveries::table)
+                .values(deliveries)
+                .execute(self.conn)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreEnqueueWebhookDeliveriesOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn enqueue_webhook_deliveries(
+        &self,
+        batch_id: &str,
+        service_id: &str,
+        previous_status: Option<&str>,
+        status: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        // Only an actual change of status is notified
+        if previous_status == Some(status) {
+            return Ok(());
+        }
+
+        let (signer_public_key, data_change_id) = match batches::table
+            .select((batches::signer_public_key, batches::data_change_id))
+            .filter(
+                batches::batch_id
+                    .eq(&batch_id)
+                    .and(batches::service_id.eq(&service_id)),
+            )
+            .first::<(String, Option<String>)>(self.conn)
+            .optional()?
+        {
+            Some(batch) => batch,
+            None => return Ok(()),
+        };
+
+        let deliveries = webhook_subscriptions::table
+            .filter(webhook_subscriptions::signer_public_key.eq(&signer_public_key))
+            .load::<WebhookSubscriptionModel>(self.conn)?
+            .into_iter()
+            .filter(|subscription| subscription.subscribes_to(status))
+            .map(|subscription| NewWebhookDeliveryModel {
+                subscription_id: subscription.id,
+                service_id: service_id.to_string(),
+                batch_id: batch_id.to_string(),
+                data_change_id: data_change_id.clone(),
+                event_type: status.to_string(),
+                previous_status: previous_status.map(String::from),
+            })
+            .collect::<Vec<_>>();
+
+        if !deliveries.is_empty() {
+            insert_into(webhook_deliveries::table)
+                .values(deliveries)
+                .execute(self.conn)?;
+        }
+
+        Ok(())
+    }
+}
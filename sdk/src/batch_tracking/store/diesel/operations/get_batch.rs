@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::get_batch_id_from_data_change_id::BatchTrackingStoreGetBatchIdFromDataChangeIdOperation as _;
 use super::BatchTrackingStoreOperations;
 use crate::error::InternalError;
 
@@ -68,20 +69,20 @@ impl<'a> BatchTrackingStoreGetBatchOperation
                     submissions::all_columns.nullable(),
                 ));
 
-            let is_dcid = is_data_change_id(id)?;
-            if is_dcid {
-                query = query.filter(
-                    batches::data_change_id
-                        .eq(&id)
-                        .and(batches::service_id.eq(&service_id)),
-                );
+            let batch_id = if is_data_change_id(id)? {
+                match self.get_batch_id_from_data_change_id(id, service_id)? {
+                    Some(batch_id) => batch_id,
+                    None => return Ok(None),
+                }
             } else {
-                query = query.filter(
-                    batches::batch_id
-                        .eq(&id)
-                        .and(batches::service_id.eq(&service_id)),
-                );
-            }
+                id.to_string()
+            };
+
+            query = query.filter(
+                batches::batch_id
+                    .eq(&batch_id)
+                    .and(batches::service_id.eq(&service_id)),
+            );
 
             // Diesel will deserialize the joined results into the respective
             // models for the tables in the join.
@@ -216,20 +217,20 @@ impl<'a> BatchTrackingStoreGetBatchOperation
                     submissions::all_columns.nullable(),
                 ));
 
-            let is_dcid = is_data_change_id(id)?;
-            if is_dcid {
-                query = query.filter(
-                    batches::data_change_id
-                        .eq(&id)
-                        .and(batches::service_id.eq(&service_id)),
-                );
+            let batch_id = if is_data_change_id(id)? {
+                match self.get_batch_id_from_data_change_id(id, service_id)? {
+                    Some(batch_id) => batch_id,
+                    None => return Ok(None),
+                }
             } else {
-                query = query.filter(
-                    batches::batch_id
-                        .eq(&id)
-                        .and(batches::service_id.eq(&service_id)),
-                );
-            }
+                id.to_string()
+            };
+
+            query = query.filter(
+                batches::batch_id
+                    .eq(&batch_id)
+                    .and(batches::service_id.eq(&service_id)),
+            );
 
             // Diesel will deserialize the joined results into the respective
             // models for the tables in the join.
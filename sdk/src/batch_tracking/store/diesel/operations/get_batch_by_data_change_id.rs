@@ -0,0 +1,95 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::get_batch::BatchTrackingStoreGetBatchOperation as _;
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::schema::batches, BatchTrackingStoreError, TrackingBatch,
+};
+use diesel::prelude::*;
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreGetBatchByDataChangeIdOperation
+{
+    fn get_batch_by_data_change_id(
+        &self,
+        data_change_id: &str,
+        signer_public_key: &str,
+        service_id: &str,
+    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreGetBatchByDataChangeIdOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn get_batch_by_data_change_id(
+        &self,
+        data_change_id: &str,
+        signer_public_key: &str,
+        service_id: &str,
+    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            // A data change ID is only unique for the signer and service that
+            // submitted it
+            let batch_id = batches::table
+                .select(batches::batch_id)
+                .filter(
+                    batches::data_change_id
+                        .eq(&data_change_id)
+                        .and(batches::signer_public_key.eq(&signer_public_key))
+                        .and(batches::service_id.eq(&service_id)),
+                )
+                .first::<String>(self.conn)
+                .optional()?;
+
+            match batch_id {
+                Some(batch_id) => self.get_batch(&batch_id, service_id),
+                None => Ok(None),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreGetBatchByDataChangeIdOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_batch_by_data_change_id(
+        &self,
+        data_change_id: &str,
+        signer_public_key: &str,
+        service_id: &str,
+    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            // A data change ID is only unique for the signer and service that
+            // submitted it
+            let batch_id = batches::table
+                .select(batches::batch_id)
+                .filter(
+                    batches::data_change_id
+                        .eq(&data_change_id)
+                        .and(batches::signer_public_key.eq(&signer_public_key))
+                        .and(batches::service_id.eq(&service_id)),
+                )
+                .first::<String>(self.conn)
+                .optional()?;
+
+            match batch_id {
+                Some(batch_id) => self.get_batch(&batch_id, service_id),
+                None => Ok(None),
+            }
+        })
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{diesel::schema::batches, BatchTrackingStoreError};
+use crate::error::{ConstraintViolationError, ConstraintViolationType};
+use diesel::prelude::*;
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreGetBatchIdFromDataChangeIdOperation
+{
+    fn get_batch_id_from_data_change_id(
+        &self,
+        data_change_id: &str,
+        service_id: &str,
+    ) -> Result<Option<String>, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreGetBatchIdFromDataChangeIdOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn get_batch_id_from_data_change_id(
+        &self,
+        data_change_id: &str,
+        service_id: &str,
+    ) -> Result<Option<String>, BatchTrackingStoreError> {
+        let batch_ids = batches::table
+            .select(batches::batch_id)
+            .filter(
+                batches::data_change_id
+                    .eq(&data_change_id)
+                    .and(batches::service_id.eq(&service_id)),
+            )
+            .limit(2)
+            .load::<String>(self.conn)?;
+
+        single_batch_id(data_change_id, batch_ids)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreGetBatchIdFromDataChangeIdOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_batch_id_from_data_change_id(
+        &self,
+        data_change_id: &str,
+        service_id: &str,
+    ) -> Result<Option<String>, BatchTrackingStoreError> {
+        let batch_ids = batches::table
+            .select(batches::batch_id)
+            .filter(
+                batches::data_change_id
+                    .eq(&data_change_id)
+                    .and(batches::service_id.eq(&service_id)),
+            )
+            .limit(2)
+            .load::<String>(self.conn)?;
+
+        single_batch_id(data_change_id, batch_ids)
+    }
+}
+
+/// A data change ID is only unique for the signer that submitted it, so one that was submitted by
+/// several signers of the service does not identify a batch
+fn single_batch_id(
+    data_change_id: &str,
+    mut batch_ids: Vec<String>,
+) -> Result<Option<String>, BatchTrackingStoreError> {
+    if batch_ids.len() > 1 {
+        return Err(BatchTrackingStoreError::ConstraintViolationError(
+            ConstraintViolationError::from_source_with_violation_type(
+                ConstraintViolationType::Unique,
+                format!(
+                    "Data change ID {} was submitted by more than one signer; use a batch ID",
+                    data_change_id
+                )
+                .into(),
+            ),
+        ));
+    }
+
+    Ok(batch_ids.pop())
+}
@@ -0,0 +1,204 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::diesel::{
+    models::{
+        BatchModel, BatchStatusModel, SubmissionModel, TransactionModel, TransactionReceiptModel,
+    },
+    schema::{batch_statuses, batches, submissions, transaction_receipts, transactions},
+    BatchStatus, TrackingBatchList,
+};
+
+use crate::batch_tracking::store::BatchTrackingStoreError;
+use diesel::{dsl::sql, prelude::*, sql_types::Integer};
+use std::convert::TryFrom;
+
+// Ranks a batch's priority so that the highest priority sorts first
+const PRIORITY_RANK: &str =
+    "CASE batches.priority WHEN 'interactive' THEN 0 WHEN 'bulk' THEN 2 ELSE 1 END";
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreGetNextUnsubmittedBatchesOperation
+{
+    fn get_next_unsubmitted_batches(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<TrackingBatchList, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreGetNextUnsubmittedBatchesOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn get_next_unsubmitted_batches(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let unsubmitted_statuses: Vec<String> = vec![
+                BatchStatus::Unknown.to_string(),
+                BatchStatus::Delayed.to_string(),
+            ];
+
+            let batches_and_statuses: Vec<(BatchModel, Option<BatchStatusModel>)> = batches::table
+                .into_boxed()
+                .left_join(
+                    batch_statuses::table.on(batches::batch_id
+                        .eq(batch_statuses::batch_id)
+                        .and(batches::service_id.eq(batch_statuses::service_id))),
+                )
+                .filter(batch_statuses::dlt_status.eq_any(unsubmitted_statuses))
+                .or_filter(batches::submitted.eq(false))
+                .select((batches::all_columns, batch_statuses::all_columns.nullable()))
+                .order_by((
+                    sql::<Integer>(PRIORITY_RANK),
+                    batches::created_at,
+                    batches::batch_id,
+                ))
+                .offset(offset)
+                .limit(limit)
+                .load::<(BatchModel, Option<BatchStatusModel>)>(self.conn)?;
+
+            if batches_and_statuses.is_empty() {
+                return Ok(TrackingBatchList {
+                    batches: Vec::new(),
+                });
+            }
+
+            let (batch_models, batch_status_model_options): (
+                Vec<BatchModel>,
+                Vec<Option<BatchStatusModel>>,
+            ) = batches_and_statuses.into_iter().unzip();
+            let batch_status_models: Vec<BatchStatusModel> =
+                batch_status_model_options.into_iter().flatten().collect();
+
+            // Children are matched to their batch by service ID and batch ID when the list is
+            // built, so loading them by batch ID alone is sufficient
+            let batch_ids: Vec<String> = batch_models
+                .iter()
+                .map(|batch| batch.batch_id.clone())
+                .collect();
+
+            let submission_models: Vec<SubmissionModel> = submissions::table
+                .filter(submissions::batch_id.eq_any(&batch_ids))
+                .load(self.conn)?;
+
+            let txn_models: Vec<TransactionModel> = transactions::table
+                .filter(transactions::batch_id.eq_any(&batch_ids))
+                .load(self.conn)?;
+
+            let txn_ids: Vec<String> = txn_models
+                .iter()
+                .map(|txn| txn.transaction_id.clone())
+                .collect();
+            let receipt_models: Vec<TransactionReceiptModel> = transaction_receipts::table
+                .filter(transaction_receipts::transaction_id.eq_any(&txn_ids))
+                .load(self.conn)?;
+
+            let batches = TrackingBatchList::try_from((
+                batch_models,
+                batch_status_models,
+                txn_models,
+                receipt_models,
+                submission_models,
+            ))?;
+            Ok(batches)
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreGetNextUnsubmittedBatchesOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_next_unsubmitted_batches(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let unsubmitted_statuses: Vec<String> = vec![
+                BatchStatus::Unknown.to_string(),
+                BatchStatus::Delayed.to_string(),
+            ];
+
+            let batches_and_statuses: Vec<(BatchModel, Option<BatchStatusModel>)> = batches::table
+                .into_boxed()
+                .left_join(
+                    batch_statuses::table.on(batches::batch_id
+                        .eq(batch_statuses::batch_id)
+                        .and(batches::service_id.eq(batch_statuses::service_id))),
+                )
+                .filter(batch_statuses::dlt_status.eq_any(unsubmitted_statuses))
+                .or_filter(batches::submitted.eq(false))
+                .select((batches::all_columns, batch_statuses::all_columns.nullable()))
+                .order_by((
+                    sql::<Integer>(PRIORITY_RANK),
+                    batches::created_at,
+                    batches::batch_id,
+                ))
+                .offset(offset)
+                .limit(limit)
+                .load::<(BatchModel, Option<BatchStatusModel>)>(self.conn)?;
+
+            if batches_and_statuses.is_empty() {
+                return Ok(TrackingBatchList {
+                    batches: Vec::new(),
+                });
+            }
+
+            let (batch_models, batch_status_model_options): (
+                Vec<BatchModel>,
+                Vec<Option<BatchStatusModel>>,
+            ) = batches_and_statuses.into_iter().unzip();
+            let batch_status_models: Vec<BatchStatusModel> =
+                batch_status_model_options.into_iter().flatten().collect();
+
+            // Children are matched to their batch by service ID and batch ID when the list is
+            // built, so loading them by batch ID alone is sufficient
+            let batch_ids: Vec<String> = batch_models
+                .iter()
+                .map(|batch| batch.batch_id.clone())
+                .collect();
+
+            let submission_models: Vec<SubmissionModel> = submissions::table
+                .filter(submissions::batch_id.eq_any(&batch_ids))
+                .load(self.conn)?;
+
+            let txn_models: Vec<TransactionModel> = transactions::table
+                .filter(transactions::batch_id.eq_any(&batch_ids))
+                .load(self.conn)?;
+
+            let txn_ids: Vec<String> = txn_models
+                .iter()
+                .map(|txn| txn.transaction_id.clone())
+                .collect();
+            let receipt_models: Vec<TransactionReceiptModel> = transaction_receipts::table
+                .filter(transaction_receipts::transaction_id.eq_any(&txn_ids))
+                .load(self.conn)?;
+
+            let batches = TrackingBatchList::try_from((
+                batch_models,
+                batch_status_models,
+                txn_models,
+                receipt_models,
+                submission_models,
+            ))?;
+            Ok(batches)
+        })
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::{
+        models::{WebhookDeliveryModel, WebhookSubscriptionModel},
+        schema::{webhook_deliveries, webhook_subscriptions},
+    },
+    BatchTrackingStoreError, WebhookDelivery, WebhookDeliveryStatus,
+};
+use diesel::prelude::*;
+use std::convert::TryFrom;
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreGetPendingWebhookDeliveriesOperation
+{
+    fn get_pending_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreGetPendingWebhookDeliveriesOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn get_pending_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        webhook_deliveries::table
+            .inner_join(webhook_subscriptions::table)
+            .filter(
+                webhook_deliveries::status
+                    .eq(WebhookDeliveryStatus::Pending.to_string())
+                    .and(webhook_deliveries::next_attempt_at.le(now)),
+            )
+            .order_by(webhook_deliveries::id.asc())
+            .limit(limit)
+            .load::<(WebhookDeliveryModel, WebhookSubscriptionModel)>(self.conn)?
+            .into_iter()
+            .map(WebhookDelivery::try_from)
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreGetPendingWebhookDeliveriesOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_pending_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        webhook_deliveries::table
+            .inner_join(webhook_subscriptions::table)
+            .filter(
+                webhook_deliveries::status
+                    .eq(WebhookDeliveryStatus::Pending.to_string())
+                    .and(webhook_deliveries::next_attempt_at.le(now)),
+            )
+            .order_by(webhook_deliveries::id.asc())
+            .limit(limit)
+            .load::<(WebhookDeliveryModel, WebhookSubscriptionModel)>(self.conn)?
+            .into_iter()
+            .map(WebhookDelivery::try_from)
+            .collect()
+    }
+}
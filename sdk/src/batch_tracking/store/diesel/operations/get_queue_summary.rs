@@ -0,0 +1,97 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::diesel::{
+    schema::{batch_statuses, batches},
+    BatchQueueSummary, BatchStatus,
+};
+
+use crate::batch_tracking::store::BatchTrackingStoreError;
+use diesel::prelude::*;
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreGetQueueSummaryOperation {
+    fn get_queue_summary(&self) -> Result<BatchQueueSummary, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreGetQueueSummaryOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn get_queue_summary(&self) -> Result<BatchQueueSummary, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            // The same batches as are returned by `get_unsubmitted_batches`
+            let unsubmitted_statuses: Vec<String> = vec![
+                BatchStatus::Unknown.to_string(),
+                BatchStatus::Delayed.to_string(),
+            ];
+            let unsubmitted = || {
+                batches::table
+                    .left_join(
+                        batch_statuses::table.on(batches::batch_id
+                            .eq(batch_statuses::batch_id)
+                            .and(batches::service_id.eq(batch_statuses::service_id))),
+                    )
+                    .filter(batch_statuses::dlt_status.eq_any(unsubmitted_statuses.clone()))
+                    .or_filter(batches::submitted.eq(false))
+            };
+
+            let depth = unsubmitted().count().get_result::<i64>(self.conn)?;
+            let oldest_created_at = unsubmitted()
+                .select(diesel::dsl::min(batches::created_at))
+                .first::<Option<i64>>(self.conn)?;
+
+            Ok(BatchQueueSummary {
+                depth,
+                oldest_created_at,
+            })
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreGetQueueSummaryOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_queue_summary(&self) -> Result<BatchQueueSummary, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            // The same batches as are returned by `get_unsubmitted_batches`
+            let unsubmitted_statuses: Vec<String> = vec![
+                BatchStatus::Unknown.to_string(),
+                BatchStatus::Delayed.to_string(),
+            ];
+            let unsubmitted = || {
+                batches::table
+                    .left_join(
+                        batch_statuses::table.on(batches::batch_id
+                            .eq(batch_statuses::batch_id)
+                            .and(batches::service_id.eq(batch_statuses::service_id))),
+                    )
+                    .filter(batch_statuses::dlt_status.eq_any(unsubmitted_statuses.clone()))
+                    .or_filter(batches::submitted.eq(false))
+            };
+
+            let depth = unsubmitted().count().get_result::<i64>(self.conn)?;
+            let oldest_created_at = unsubmitted()
+                .select(diesel::dsl::min(batches::created_at))
+                .first::<Option<i64>>(self.conn)?;
+
+            Ok(BatchQueueSummary {
+                depth,
+                oldest_created_at,
+            })
+        })
+    }
+}
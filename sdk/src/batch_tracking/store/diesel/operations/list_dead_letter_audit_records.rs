@@ -0,0 +1,78 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::{models::DeadLetterActionModel, schema::dead_letter_actions},
+    BatchTrackingStoreError, DeadLetterAuditRecord,
+};
+use diesel::prelude::*;
+use std::convert::TryFrom;
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreListDeadLetterAuditRecordsOperation
+{
+    fn list_dead_letter_audit_records(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterAuditRecord>, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreListDeadLetterAuditRecordsOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn list_dead_letter_audit_records(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterAuditRecord>, BatchTrackingStoreError> {
+        let mut query = dead_letter_actions::table
+            .into_boxed()
+            .order_by(dead_letter_actions::id.asc());
+
+        if let Some(service_id) = service_id {
+            query = query.filter(dead_letter_actions::service_id.eq(service_id));
+        }
+
+        query
+            .load::<DeadLetterActionModel>(self.conn)?
+            .into_iter()
+            .map(DeadLetterAuditRecord::try_from)
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreListDeadLetterAuditRecordsOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn list_dead_letter_audit_records(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterAuditRecord>, BatchTrackingStoreError> {
+        let mut query = dead_letter_actions::table
+            .into_boxed()
+            .order_by(dead_letter_actions::id.asc());
+
+        if let Some(service_id) = service_id {
+            query = query.filter(dead_letter_actions::service_id.eq(service_id));
+        }
+
+        query
+            .load::<DeadLetterActionModel>(self.conn)?
+            .into_iter()
+            .map(DeadLetterAuditRecord::try_from)
+            .collect()
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::get_batch::BatchTrackingStoreGetBatchOperation as _;
+use super::BatchTrackingStoreOperations;
+use crate::error::InternalError;
+
+use crate::batch_tracking::store::{
+    diesel::{models::DeadLetterBatchModel, schema::dead_letter_batches},
+    BatchTrackingStoreError, DeadLetterBatch,
+};
+use diesel::prelude::*;
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreListDeadLetterBatchesOperation
+{
+    fn list_dead_letter_batches(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreListDeadLetterBatchesOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn list_dead_letter_batches(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let mut query = dead_letter_batches::table
+                .into_boxed()
+                .order_by(dead_letter_batches::created_at.asc());
+
+            if let Some(service_id) = service_id {
+                query = query.filter(dead_letter_batches::service_id.eq(service_id));
+            }
+
+            query
+                .load::<DeadLetterBatchModel>(self.conn)?
+                .into_iter()
+                .map(|model| {
+                    let batch = self
+                        .get_batch(&model.batch_id, &model.service_id)?
+                        .ok_or_else(|| {
+                            BatchTrackingStoreError::InternalError(InternalError::with_message(
+                                format!(
+                                    "Dead-lettered batch {} has no batch record",
+                                    model.batch_id
+                                ),
+                            ))
+                        })?;
+
+                    Ok(DeadLetterBatch {
+                        batch,
+                        reason: model.reason,
+                        dead_lettered_at: model.created_at,
+                    })
+                })
+                .collect()
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreListDeadLetterBatchesOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn list_dead_letter_batches(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let mut query = dead_letter_batches::table
+                .into_boxed()
+                .order_by(dead_letter_batches::created_at.asc());
+
+            if let Some(service_id) = service_id {
+                query = query.filter(dead_letter_batches::service_id.eq(service_id));
+            }
+
+            query
+                .load::<DeadLetterBatchModel>(self.conn)?
+                .into_iter()
+                .map(|model| {
+                    let batch = self
+                        .get_batch(&model.batch_id, &model.service_id)?
+                        .ok_or_else(|| {
+                            BatchTrackingStoreError::InternalError(InternalError::with_message(
+                                format!(
+                                    "Dead-lettered batch {} has no batch record",
+                                    model.batch_id
+                                ),
+                            ))
+                        })?;
+
+                    Ok(DeadLetterBatch {
+                        batch,
+                        reason: model.reason,
+                        dead_lettered_at: model.created_at,
+                    })
+                })
+                .collect()
+        })
+    }
+}
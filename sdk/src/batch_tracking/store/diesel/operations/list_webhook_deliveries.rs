@@ -0,0 +1,102 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::{
+        models::{WebhookDeliveryModel, WebhookSubscriptionModel},
+        schema::{webhook_deliveries, webhook_subscriptions},
+    },
+    BatchTrackingStoreError, WebhookDelivery,
+};
+use diesel::prelude::*;
+use std::convert::TryFrom;
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreListWebhookDeliveriesOperation
+{
+    fn list_webhook_deliveries(
+        &self,
+        subscription_id: i64,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreListWebhookDeliveriesOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn list_webhook_deliveries(
+        &self,
+        subscription_id: i64,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        let subscription = webhook_subscriptions::table
+            .filter(
+                webhook_subscriptions::id
+                    .eq(subscription_id)
+                    .and(webhook_subscriptions::signer_public_key.eq(signer_public_key)),
+            )
+            .first::<WebhookSubscriptionModel>(self.conn)
+            .optional()?
+            .ok_or_else(|| {
+                BatchTrackingStoreError::NotFoundError(format!(
+                    "Could not find webhook subscription with ID {}",
+                    subscription_id
+                ))
+            })?;
+
+        webhook_deliveries::table
+            .filter(webhook_deliveries::subscription_id.eq(subscription_id))
+            .order_by(webhook_deliveries::id.asc())
+            .load::<WebhookDeliveryModel>(self.conn)?
+            .into_iter()
+            .map(|delivery| WebhookDelivery::try_from((delivery, subscription.clone())))
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreListWebhookDeliveriesOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn list_webhook_deliveries(
+        &self,
+        subscription_id: i64,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        let subscription = webhook_subscriptions::table
+            .filter(
+                webhook_subscriptions::id
+                    .eq(subscription_id)
+                    .and(webhook_subscriptions::signer_public_key.eq(signer_public_key)),
+            )
+            .first::<WebhookSubscriptionModel>(self.conn)
+            .optional()?
+            .ok_or_else(|| {
+                BatchTrackingStoreError::NotFoundError(format!(
+                    "Could not find webhook subscription with ID {}",
+                    subscription_id
+                ))
+            })?;
+
+        webhook_deliveries::table
+            .filter(webhook_deliveries::subscription_id.eq(subscription_id))
+            .order_by(webhook_deliveries::id.asc())
+            .load::<WebhookDeliveryModel>(self.conn)?
+            .into_iter()
+            .map(|delivery| WebhookDelivery::try_from((delivery, subscription.clone())))
+            .collect()
+    }
+}
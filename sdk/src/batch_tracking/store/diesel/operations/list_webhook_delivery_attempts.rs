@@ -0,0 +1,65 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::{models::WebhookDeliveryAttemptModel, schema::webhook_delivery_attempts},
+    BatchTrackingStoreError, WebhookDeliveryAttempt,
+};
+use diesel::prelude::*;
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreListWebhookDeliveryAttemptsOperation
+{
+    fn list_webhook_delivery_attempts(
+        &self,
+        delivery_id: i64,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreListWebhookDeliveryAttemptsOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn list_webhook_delivery_attempts(
+        &self,
+        delivery_id: i64,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, BatchTrackingStoreError> {
+        Ok(webhook_delivery_attempts::table
+            .filter(webhook_delivery_attempts::delivery_id.eq(delivery_id))
+            .order_by(webhook_delivery_attempts::id.asc())
+            .load::<WebhookDeliveryAttemptModel>(self.conn)?
+            .into_iter()
+            .map(WebhookDeliveryAttempt::from)
+            .collect())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreListWebhookDeliveryAttemptsOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn list_webhook_delivery_attempts(
+        &self,
+        delivery_id: i64,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, BatchTrackingStoreError> {
+        Ok(webhook_delivery_attempts::table
+            .filter(webhook_delivery_attempts::delivery_id.eq(delivery_id))
+            .order_by(webhook_delivery_attempts::id.asc())
+            .load::<WebhookDeliveryAttemptModel>(self.conn)?
+            .into_iter()
+            .map(WebhookDeliveryAttempt::from)
+            .collect())
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::{models::WebhookSubscriptionModel, schema::webhook_subscriptions},
+    BatchTrackingStoreError, WebhookSubscription,
+};
+use diesel::prelude::*;
+use std::convert::TryFrom;
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreListWebhookSubscriptionsOperation
+{
+    fn list_webhook_subscriptions(
+        &self,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookSubscription>, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreListWebhookSubscriptionsOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn list_webhook_subscriptions(
+        &self,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookSubscription>, BatchTrackingStoreError> {
+        webhook_subscriptions::table
+            .filter(webhook_subscriptions::signer_public_key.eq(signer_public_key))
+            .order_by(webhook_subscriptions::id.asc())
+            .load::<WebhookSubscriptionModel>(self.conn)?
+            .into_iter()
+            .map(WebhookSubscription::try_from)
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreListWebhookSubscriptionsOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn list_webhook_subscriptions(
+        &self,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookSubscription>, BatchTrackingStoreError> {
+        webhook_subscriptions::table
+            .filter(webhook_subscriptions::signer_public_key.eq(signer_public_key))
+            .order_by(webhook_subscriptions::id.asc())
+            .load::<WebhookSubscriptionModel>(self.conn)?
+            .into_iter()
+            .map(WebhookSubscription::try_from)
+            .collect()
+    }
+}
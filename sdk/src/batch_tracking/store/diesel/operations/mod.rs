@@ -13,13 +13,32 @@
 // limitations under the License.
 
 pub(super) mod add_batches;
+pub(super) mod add_webhook_subscription;
 pub(super) mod change_batch_to_submitted;
 pub(super) mod clean_stale_records;
+pub(super) mod compact_batches;
+pub(super) mod dead_letter_batch;
+pub(super) mod discard_dead_letter_batch;
+pub(super) mod enqueue_webhook_deliveries;
 pub(super) mod get_batch;
+pub(super) mod get_batch_by_data_change_id;
+mod get_batch_id_from_data_change_id;
 pub(super) mod get_batch_status;
 pub(super) mod get_failed_batches;
+pub(super) mod get_next_unsubmitted_batches;
+pub(super) mod get_pending_webhook_deliveries;
+pub(super) mod get_queue_summary;
 pub(super) mod get_unsubmitted_batches;
 pub(super) mod list_batches_by_status;
+pub(super) mod list_dead_letter_audit_records;
+pub(super) mod list_dead_letter_batches;
+pub(super) mod list_webhook_deliveries;
+pub(super) mod list_webhook_delivery_attempts;
+pub(super) mod list_webhook_subscriptions;
+pub(super) mod purge_batches;
+pub(super) mod record_webhook_delivery_attempt;
+pub(super) mod remove_webhook_subscription;
+pub(super) mod replay_dead_letter_batch;
 pub(super) mod update_batch_status;
 
 pub(super) struct BatchTrackingStoreOperations<'a, C> {
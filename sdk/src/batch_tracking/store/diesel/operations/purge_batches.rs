@@ -0,0 +1,229 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::schema::{
+        batch_statuses, batches, dead_letter_batches, submissions, transaction_receipts,
+        transactions,
+    },
+    BatchStatusName, BatchTrackingStoreError,
+};
+use diesel::{delete, prelude::*};
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStorePurgeBatchesOperation {
+    fn purge_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStorePurgeBatchesOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn purge_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            // Dead-lettered batches are kept until an operator replays or discards them
+            let expired = batches::table
+                .inner_join(
+                    batch_statuses::table.on(batches::batch_id
+                        .eq(batch_statuses::batch_id)
+                        .and(batches::service_id.eq(batch_statuses::service_id))),
+                )
+                .left_join(
+                    dead_letter_batches::table.on(batches::batch_id
+                        .eq(dead_letter_batches::batch_id)
+                        .and(batches::service_id.eq(dead_letter_batches::service_id))),
+                )
+                .select((batches::service_id, batches::batch_id))
+                .filter(
+                    batch_statuses::dlt_status
+                        .eq(status.to_string())
+                        .and(batches::created_at.lt(created_before))
+                        .and(dead_letter_batches::batch_id.is_null()),
+                )
+                .order(batches::created_at.asc())
+                .limit(limit)
+                .load::<(String, String)>(self.conn)?;
+
+            let mut purged = 0;
+            for (service_id, batch_id) in expired {
+                // Dependent records are removed explicitly, as foreign keys are not enforced by
+                // every database
+                let transaction_ids = transactions::table
+                    .select(transactions::transaction_id)
+                    .filter(
+                        transactions::batch_id
+                            .eq(&batch_id)
+                            .and(transactions::service_id.eq(&service_id)),
+                    )
+                    .load::<String>(self.conn)?;
+
+                delete(
+                    transaction_receipts::table.filter(
+                        transaction_receipts::transaction_id
+                            .eq_any(&transaction_ids)
+                            .and(transaction_receipts::service_id.eq(&service_id)),
+                    ),
+                )
+                .execute(self.conn)?;
+
+                delete(
+                    transactions::table.filter(
+                        transactions::batch_id
+                            .eq(&batch_id)
+                            .and(transactions::service_id.eq(&service_id)),
+                    ),
+                )
+                .execute(self.conn)?;
+
+                delete(
+                    submissions::table.filter(
+                        submissions::batch_id
+                            .eq(&batch_id)
+                            .and(submissions::service_id.eq(&service_id)),
+                    ),
+                )
+                .execute(self.conn)?;
+
+                delete(
+                    batch_statuses::table.filter(
+                        batch_statuses::batch_id
+                            .eq(&batch_id)
+                            .and(batch_statuses::service_id.eq(&service_id)),
+                    ),
+                )
+                .execute(self.conn)?;
+
+                purged += delete(
+                    batches::table.filter(
+                        batches::batch_id
+                            .eq(&batch_id)
+                            .and(batches::service_id.eq(&service_id)),
+                    ),
+                )
+                .execute(self.conn)?;
+            }
+
+            Ok(purged)
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStorePurgeBatchesOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn purge_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            // Dead-lettered batches are kept until an operator replays or discards them
+            let expired = batches::table
+                .inner_join(
+                    batch_statuses::table.on(batches::batch_id
+                        .eq(batch_statuses::batch_id)
+                        .and(batches::service_id.eq(batch_statuses::service_id))),
+                )
+                .left_join(
+                    dead_letter_batches::table.on(batches::batch_id
+                        .eq(dead_letter_batches::batch_id)
+                        .and(batches::service_id.eq(dead_letter_batches::service_id))),
+                )
+                .select((batches::service_id, batches::batch_id))
+                .filter(
+                    batch_statuses::dlt_status
+                        .eq(status.to_string())
+                        .and(batches::created_at.lt(created_before))
+                        .and(dead_letter_batches::batch_id.is_null()),
+                )
+                .order(batches::created_at.asc())
+                .limit(limit)
+                .load::<(String, String)>(self.conn)?;
+
+            let mut purged = 0;
+            for (service_id, batch_id) in expired {
+                // Dependent records are removed explicitly, as foreign keys are not enforced by
+                // every database
+                let transaction_ids = transactions::table
+                    .select(transactions::transaction_id)
+                    .filter(
+                        transactions::batch_id
+                            .eq(&batch_id)
+                            .and(transactions::service_id.eq(&service_id)),
+                    )
+                    .load::<String>(self.conn)?;
+
+                delete(
+                    transaction_receipts::table.filter(
+                        transaction_receipts::transaction_id
+                            .eq_any(&transaction_ids)
+                            .and(transaction_receipts::service_id.eq(&service_id)),
+                    ),
+                )
+                .execute(self.conn)?;
+
+                delete(
+                    transactions::table.filter(
+                        transactions::batch_id
+                            .eq(&batch_id)
+                            .and(transactions::service_id.eq(&service_id)),
+                    ),
+                )
+                .execute(self.conn)?;
+
+                delete(
+                    submissions::table.filter(
+                        submissions::batch_id
+                            .eq(&batch_id)
+                            .and(submissions::service_id.eq(&service_id)),
+                    ),
+                )
+                .execute(self.conn)?;
+
+                delete(
+                    batch_statuses::table.filter(
+                        batch_statuses::batch_id
+                            .eq(&batch_id)
+                            .and(batch_statuses::service_id.eq(&service_id)),
+                    ),
+                )
+                .execute(self.conn)?;
+
+                purged += delete(
+                    batches::table.filter(
+                        batches::batch_id
+                            .eq(&batch_id)
+                            .and(batches::service_id.eq(&service_id)),
+                    ),
+                )
+                .execute(self.conn)?;
+            }
+
+            Ok(purged)
+        })
+    }
+}
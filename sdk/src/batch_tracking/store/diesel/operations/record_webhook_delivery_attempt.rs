@@ -0,0 +1,138 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::{
+        models::NewWebhookDeliveryAttemptModel,
+        schema::{webhook_deliveries, webhook_delivery_attempts},
+    },
+    BatchTrackingStoreError, WebhookDeliveryStatus,
+};
+use diesel::{
+    dsl::{insert_into, update},
+    prelude::*,
+};
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreRecordWebhookDeliveryAttemptOperation
+{
+    fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: i64,
+        attempted_at: i64,
+        response_code: Option<i64>,
+        error: Option<&str>,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: i64,
+    ) -> Result<(), BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreRecordWebhookDeliveryAttemptOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: i64,
+        attempted_at: i64,
+        response_code: Option<i64>,
+        error: Option<&str>,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: i64,
+    ) -> Result<(), BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let updated = update(webhook_deliveries::table.find(delivery_id))
+                .set((
+                    webhook_deliveries::status.eq(status.to_string()),
+                    webhook_deliveries::attempts.eq(webhook_deliveries::attempts + 1),
+                    webhook_deliveries::next_attempt_at.eq(next_attempt_at),
+                ))
+                .execute(self.conn)?;
+
+            if updated == 0 {
+                return Err(BatchTrackingStoreError::NotFoundError(format!(
+                    "Could not find webhook delivery with ID {}",
+                    delivery_id
+                )));
+            }
+
+            if status == WebhookDeliveryStatus::Delivered {
+                update(webhook_deliveries::table.find(delivery_id))
+                    .set(webhook_deliveries::delivered_at.eq(Some(attempted_at)))
+                    .execute(self.conn)?;
+            }
+
+            insert_into(webhook_delivery_attempts::table)
+                .values(NewWebhookDeliveryAttemptModel {
+                    delivery_id,
+                    response_code,
+                    error: error.map(String::from),
+                    attempted_at,
+                })
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreRecordWebhookDeliveryAttemptOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: i64,
+        attempted_at: i64,
+        response_code: Option<i64>,
+        error: Option<&str>,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: i64,
+    ) -> Result<(), BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let updated = update(webhook_deliveries::table.find(delivery_id))
+                .set((
+                    webhook_deliveries::status.eq(status.to_string()),
+                    webhook_deliveries::attempts.eq(webhook_deliveries::attempts + 1),
+                    webhook_deliveries::next_attempt_at.eq(next_attempt_at),
+                ))
+                .execute(self.conn)?;
+
+            if updated == 0 {
+                return Err(BatchTrackingStoreError::NotFoundError(format!(
+                    "Could not find webhook delivery with ID {}",
+                    delivery_id
+                )));
+            }
+
+            if status == WebhookDeliveryStatus::Delivered {
+                update(webhook_deliveries::table.find(delivery_id))
+                    .set(webhook_deliveries::delivered_at.eq(Some(attempted_at)))
+                    .execute(self.conn)?;
+            }
+
+            insert_into(webhook_delivery_attempts::table)
+                .values(NewWebhookDeliveryAttemptModel {
+                    delivery_id,
+                    response_code,
+                    error: error.map(String::from),
+                    attempted_at,
+                })
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
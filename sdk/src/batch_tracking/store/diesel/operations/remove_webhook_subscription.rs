@@ -0,0 +1,134 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::BatchTrackingStoreOperations;
+
+use crate::batch_tracking::store::{
+    diesel::schema::{webhook_deliveries, webhook_delivery_attempts, webhook_subscriptions},
+    BatchTrackingStoreError,
+};
+use diesel::{dsl::delete, prelude::*};
+
+pub(in crate::batch_tracking::store::diesel) trait BatchTrackingStoreRemoveWebhookSubscriptionOperation
+{
+    fn remove_webhook_subscription(
+        &self,
+        id: i64,
+        signer_public_key: &str,
+    ) -> Result<(), BatchTrackingStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> BatchTrackingStoreRemoveWebhookSubscriptionOperation
+    for BatchTrackingStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn remove_webhook_subscription(
+        &self,
+        id: i64,
+        signer_public_key: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let subscription = webhook_subscriptions::table
+                .select(webhook_subscriptions::id)
+                .filter(
+                    webhook_subscriptions::id
+                        .eq(id)
+                        .and(webhook_subscriptions::signer_public_key.eq(signer_public_key)),
+                )
+                .first::<i64>(self.conn)
+                .optional()?
+                .ok_or_else(|| {
+                    BatchTrackingStoreError::NotFoundError(format!(
+                        "Could not find webhook subscription with ID {}",
+                        id
+                    ))
+                })?;
+
+            // The child rows are removed explicitly, as SQLite does not enforce the cascade unless
+            // foreign keys are enabled on the connection
+            let deliveries = webhook_deliveries::table
+                .select(webhook_deliveries::id)
+                .filter(webhook_deliveries::subscription_id.eq(subscription));
+
+            delete(
+                webhook_delivery_attempts::table
+                    .filter(webhook_delivery_attempts::delivery_id.eq_any(deliveries)),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                webhook_deliveries::table
+                    .filter(webhook_deliveries::subscription_id.eq(subscription)),
+            )
+            .execute(self.conn)?;
+
+            delete(webhook_subscriptions::table.filter(webhook_subscriptions::id.eq(subscription)))
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> BatchTrackingStoreRemoveWebhookSubscriptionOperation
+    for BatchTrackingStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn remove_webhook_subscription(
+        &self,
+        id: i64,
+        signer_public_key: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
+            let subscription = webhook_subscriptions::table
+                .select(webhook_subscriptions::id)
+                .filter(
+                    webhook_subscriptions::id
+                        .eq(id)
+                        .and(webhook_subscriptions::signer_public_key.eq(signer_public_key)),
+                )
+                .first::<i64>(self.conn)
+                .optional()?
+                .ok_or_else(|| {
+                    BatchTrackingStoreError::NotFoundError(format!(
+                        "Could not find webhook subscription with ID {}",
+                        id
+                    ))
+                })?;
+
+            // The child rows are removed explicitly, as SQLite does not enforce the cascade unless
+            // foreign keys are enabled on the connection
+            let deliveries = webhook_deliveries::table
+                .select(webhook_deliveries::id)
+                .filter(webhook_deliveries::subscription_id.eq(subscription));
+
+            delete(
+                webhook_delivery_attempts::table
+                    .filter(webhook_delivery_attempts::delivery_id.eq_any(deliveries)),
+            )
+            .execute(self.conn)?;
+
+            delete(
+                webhook_deliveries::table
+                    .filter(webhook_deliveries::subscription_id.eq(subscription)),
+            )
+            .execute(self.conn)?;
+
+            delete(webhook_subscriptions::table.filter(webhook_subscriptions::id.eq(subscription)))
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::enqueue_webhook_deliveries::BatchTrackingStoreEnqueueWebhookDeliveriesOperation as _;
+use super::get_batch_id_from_data_change_id::BatchTrackingStoreGetBatchIdFromDataChangeIdOperation as _;
 use super::BatchTrackingStoreOperations;
 
 use crate::batch_tracking::store::{
@@ -54,18 +56,12 @@ impl<'a> BatchTrackingStoreUpdateBatchStatusOperation
         submission_error: Option<SubmissionError>,
     ) -> Result<(), BatchTrackingStoreError> {
         self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
-            let mut batch_id = id.to_string();
-            let is_dcid = is_data_change_id(id)?;
-            if is_dcid {
-                batch_id = batches::table
-                    .select(batches::batch_id)
-                    .filter(
-                        batches::data_change_id
-                            .eq(&id)
-                            .and(batches::service_id.eq(&service_id)),
-                    )
-                    .first::<String>(self.conn)?;
-            }
+            let batch_id = if is_data_change_id(id)? {
+                self.get_batch_id_from_data_change_id(id, service_id)?
+                    .ok_or(diesel::result::Error::NotFound)?
+            } else {
+                id.to_string()
+            };
 
             if let Some(batch_status) = status {
                 let status_string = BatchStatusName::try_from_string(batch_status)?;
@@ -95,16 +91,17 @@ impl<'a> BatchTrackingStoreUpdateBatchStatusOperation
                     }
                 }
 
-                let status_exists: bool = select(exists(
-                    batch_statuses::table.filter(
+                let previous_status = batch_statuses::table
+                    .select(batch_statuses::dlt_status)
+                    .filter(
                         batch_statuses::batch_id
                             .eq(&batch_id)
                             .and(batch_statuses::service_id.eq(&service_id)),
-                    ),
-                ))
-                .get_result(self.conn)?;
+                    )
+                    .first::<String>(self.conn)
+                    .optional()?;
 
-                if status_exists {
+                if previous_status.is_some() {
                     update(batch_statuses::table)
                         .filter(
                             batch_statuses::batch_id
@@ -124,6 +121,13 @@ impl<'a> BatchTrackingStoreUpdateBatchStatusOperation
                         .values(model)
                         .execute(self.conn)?;
                 };
+
+                self.enqueue_webhook_deliveries(
+                    &batch_id,
+                    service_id,
+                    previous_status.as_deref(),
+                    batch_status,
+                )?;
             } else {
                 update(batches::table)
                     .filter(
@@ -213,18 +217,12 @@ impl<'a> BatchTrackingStoreUpdateBatchStatusOperation
         submission_error: Option<SubmissionError>,
     ) -> Result<(), BatchTrackingStoreError> {
         self.conn.transaction::<_, BatchTrackingStoreError, _>(|| {
-            let mut batch_id = id.to_string();
-            let is_dcid = is_data_change_id(id)?;
-            if is_dcid {
-                batch_id = batches::table
-                    .select(batches::batch_id)
-                    .filter(
-                        batches::data_change_id
-                            .eq(&id)
-                            .and(batches::service_id.eq(&service_id)),
-                    )
-                    .first::<String>(self.conn)?;
-            }
+            let batch_id = if is_data_change_id(id)? {
+                self.get_batch_id_from_data_change_id(id, service_id)?
+                    .ok_or(diesel::result::Error::NotFound)?
+            } else {
+                id.to_string()
+            };
 
             if let Some(batch_status) = status {
                 let status_string = BatchStatusName::try_from_string(batch_status)?;
@@ -254,16 +252,17 @@ impl<'a> BatchTrackingStoreUpdateBatchStatusOperation
                     }
                 }
 
-                let status_exists: bool = select(exists(
-                    batch_statuses::table.filter(
+                let previous_status = batch_statuses::table
+                    .select(batch_statuses::dlt_status)
+                    .filter(
                         batch_statuses::batch_id
                             .eq(&batch_id)
                             .and(batch_statuses::service_id.eq(&service_id)),
-                    ),
-                ))
-                .get_result(self.conn)?;
+                    )
+                    .first::<String>(self.conn)
+                    .optional()?;
 
-                if status_exists {
+                if previous_status.is_some() {
                     update(batch_statuses::table)
                         .filter(
                             batch_statuses::batch_id
@@ -283,6 +282,13 @@ impl<'a> BatchTrackingStoreUpdateBatchStatusOperation
                         .values(model)
                         .execute(self.conn)?;
                 };
+
+                self.enqueue_webhook_deliveries(
+                    &batch_id,
+                    service_id,
+                    previous_status.as_deref(),
+                    batch_status,
+                )?;
             } else {
                 update(batches::table)
                     .filter(
@@ -32,6 +32,28 @@ table! {
         serialized_batch -> Binary,
         submitted -> Bool,
         created_at -> Int8,
+        priority -> Text,
+    }
+}
+
+table! {
+    dead_letter_actions (id) {
+        id -> Int8,
+        service_id -> Text,
+        batch_id -> Text,
+        action -> Text,
+        actor -> Text,
+        replacement_batch_id -> Nullable<Text>,
+        created_at -> Int8,
+    }
+}
+
+table! {
+    dead_letter_batches (service_id, batch_id) {
+        service_id -> Text,
+        batch_id -> Text,
+        reason -> Text,
+        created_at -> Int8,
     }
 }
 
@@ -73,10 +95,56 @@ table! {
     }
 }
 
+table! {
+    webhook_deliveries (id) {
+        id -> Int8,
+        subscription_id -> Int8,
+        service_id -> Text,
+        batch_id -> Text,
+        data_change_id -> Nullable<Text>,
+        event_type -> Text,
+        previous_status -> Nullable<Text>,
+        status -> Text,
+        attempts -> Int8,
+        next_attempt_at -> Int8,
+        created_at -> Int8,
+        delivered_at -> Nullable<Int8>,
+    }
+}
+
+table! {
+    webhook_delivery_attempts (id) {
+        id -> Int8,
+        delivery_id -> Int8,
+        response_code -> Nullable<Int8>,
+        error -> Nullable<Text>,
+        attempted_at -> Int8,
+    }
+}
+
+table! {
+    webhook_subscriptions (id) {
+        id -> Int8,
+        signer_public_key -> Text,
+        url -> Text,
+        secret -> Text,
+        event_types -> Text,
+        created_at -> Int8,
+    }
+}
+
+joinable!(webhook_deliveries -> webhook_subscriptions (subscription_id));
+joinable!(webhook_delivery_attempts -> webhook_deliveries (delivery_id));
+
 allow_tables_to_appear_in_same_query!(
     batch_statuses,
     batches,
+    dead_letter_actions,
+    dead_letter_batches,
     submissions,
     transaction_receipts,
     transactions,
+    webhook_deliveries,
+    webhook_delivery_attempts,
+    webhook_subscriptions,
 );
@@ -14,12 +14,13 @@
 
 use std::convert::TryFrom;
 use std::fmt;
+use std::str::FromStr;
 
 use transact::protocol::{
     batch::Batch,
     transaction::{Transaction, TransactionHeader},
 };
-use transact::protos::FromBytes;
+use transact::protos::{FromBytes, IntoBytes};
 
 use crate::batch_tracking::store::diesel::models::is_data_change_id;
 use crate::error::{InternalError, InvalidArgumentError};
@@ -31,7 +32,7 @@ mod error;
 
 pub use error::{BatchBuilderError, BatchTrackingStoreError};
 
-const NON_SPLINTER_SERVICE_ID_DEFAULT: &str = "----";
+pub(crate) const NON_SPLINTER_SERVICE_ID_DEFAULT: &str = "----";
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum BatchStatus {
@@ -56,6 +57,7 @@ impl fmt::Display for BatchStatus {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BatchStatusName {
     Unknown,
     Pending,
@@ -81,6 +83,19 @@ impl BatchStatusName {
     }
 }
 
+impl FromStr for BatchStatusName {
+    type Err = InvalidArgumentError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        BatchStatusName::try_from_string(value).map_err(|_| {
+            InvalidArgumentError::new(
+                "status".to_string(),
+                format!("{} is not a valid batch status", value),
+            )
+        })
+    }
+}
+
 impl fmt::Display for BatchStatusName {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -94,6 +109,49 @@ impl fmt::Display for BatchStatusName {
     }
 }
 
+/// The order in which queued batches are submitted relative to each other
+///
+/// Batches of a higher priority are submitted before batches of a lower priority; the variants
+/// are ordered from the highest priority to the lowest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BatchPriority {
+    /// Changes a user is waiting on, such as edits made in a UI
+    Interactive,
+    #[default]
+    Normal,
+    /// Large volumes of changes that may be delayed, such as imports
+    Bulk,
+}
+
+impl FromStr for BatchPriority {
+    type Err = InvalidArgumentError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "interactive" => Ok(BatchPriority::Interactive),
+            "normal" => Ok(BatchPriority::Normal),
+            "bulk" => Ok(BatchPriority::Bulk),
+            _ => Err(InvalidArgumentError::new(
+                "priority".to_string(),
+                format!(
+                    "{} is not a valid batch priority; expected interactive, normal or bulk",
+                    value
+                ),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for BatchPriority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BatchPriority::Interactive => write!(f, "interactive"),
+            BatchPriority::Normal => write!(f, "normal"),
+            BatchPriority::Bulk => write!(f, "bulk"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct InvalidTransaction {
     transaction_id: String,
@@ -250,6 +308,8 @@ impl ValidTransactionBuilder {
 pub struct SubmissionError {
     error_type: String,
     error_message: String,
+    attempts: i64,
+    last_attempt_at: i64,
 }
 
 impl SubmissionError {
@@ -260,6 +320,44 @@ impl SubmissionError {
     pub fn error_message(&self) -> &str {
         &self.error_message
     }
+
+    /// Returns the number of failed submission attempts recorded for the batch
+    ///
+    /// This is only known for errors that have been read back from a store; it is 0 otherwise.
+    pub fn attempts(&self) -> i64 {
+        self.attempts
+    }
+
+    /// Returns the time of the last failed submission attempt, in seconds since the epoch
+    ///
+    /// This is only known for errors that have been read back from a store; it is 0 otherwise.
+    pub fn last_attempt_at(&self) -> i64 {
+        self.last_attempt_at
+    }
+
+    /// Returns `true` unless the error was recorded as a `SubmissionErrorType::Permanent` error
+    pub fn is_retryable(&self) -> bool {
+        self.error_type != SubmissionErrorType::Permanent.to_string()
+    }
+}
+
+/// The category of a `SubmissionError`, which is recorded as its error type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmissionErrorType {
+    /// The submission failed for a transient reason, such as the DLT being unavailable, and may
+    /// succeed if it is attempted again
+    Retryable,
+    /// The batch was rejected and will not be accepted if it is submitted again
+    Permanent,
+}
+
+impl fmt::Display for SubmissionErrorType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubmissionErrorType::Retryable => write!(f, "Retryable"),
+            SubmissionErrorType::Permanent => write!(f, "Permanent"),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -300,6 +398,8 @@ impl SubmissionErrorBuilder {
         Ok(SubmissionError {
             error_type,
             error_message,
+            attempts: 0,
+            last_attempt_at: 0,
         })
     }
 }
@@ -314,6 +414,7 @@ pub struct TrackingBatch {
     serialized_batch: Vec<u8>,
     submitted: bool,
     created_at: i64,
+    priority: BatchPriority,
     transactions: Vec<TrackingTransaction>,
     batch_status: Option<BatchStatus>,
     submission_error: Option<SubmissionError>,
@@ -340,6 +441,10 @@ impl TrackingBatch {
         self.trace
     }
 
+    /// Returns the batch serialized as a `BatchList` holding only this batch.
+    ///
+    /// Batches recorded by an earlier `TrackingBatchBuilder` hold only the serialized batch
+    /// header. They cannot be submitted and must be sent again to be recorded in this format.
     pub fn serialized_batch(&self) -> &[u8] {
         &self.serialized_batch
     }
@@ -352,6 +457,10 @@ impl TrackingBatch {
         self.created_at
     }
 
+    pub fn priority(&self) -> BatchPriority {
+        self.priority
+    }
+
     pub fn transactions(&self) -> &[TrackingTransaction] {
         &self.transactions
     }
@@ -373,6 +482,7 @@ pub struct TrackingBatchBuilder {
     signer_public_key: String,
     submitted: bool,
     created_at: i64,
+    priority: BatchPriority,
     batch_status: Option<BatchStatus>,
     submission_error: Option<SubmissionError>,
 }
@@ -408,6 +518,11 @@ impl TrackingBatchBuilder {
         self
     }
 
+    pub fn with_priority(mut self, priority: BatchPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
     pub fn with_batch_status(mut self, status: BatchStatus) -> Self {
         self.batch_status = Some(status);
         self
@@ -426,6 +541,7 @@ impl TrackingBatchBuilder {
             signer_public_key,
             submitted,
             created_at,
+            priority,
             batch_status,
             submission_error,
         } = self;
@@ -462,7 +578,12 @@ impl TrackingBatchBuilder {
         };
 
         let batch_header = transact_batch.header_signature().to_string();
-        let serialized_batch = transact_batch.header().to_vec();
+        // The batch is stored as a single-batch list so it can be posted as-is to a `/batches`
+        // endpoint when it is submitted. This replaces the serialized batch header stored by
+        // earlier versions; see `TrackingBatch::serialized_batch`.
+        let serialized_batch = vec![transact_batch.clone()]
+            .into_bytes()
+            .map_err(|err| BatchBuilderError::BuildError(Box::new(err)))?;
         let trace = transact_batch.trace();
 
         let transactions: Vec<TrackingTransaction> = transact_batch
@@ -515,6 +636,7 @@ impl TrackingBatchBuilder {
             serialized_batch,
             submitted,
             created_at,
+            priority,
             transactions,
             batch_status,
             submission_error,
@@ -955,6 +1077,316 @@ impl TryFrom<TrackingBatch> for GlobalTrackingBatch {
     }
 }
 
+/// A batch that was set aside because it could not be submitted, either because it was rejected
+/// or because it exhausted its submission retries
+///
+/// Dead-lettered batches are not submitted again until they are replayed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DeadLetterBatch {
+    batch: TrackingBatch,
+    reason: String,
+    dead_lettered_at: i64,
+}
+
+impl DeadLetterBatch {
+    pub fn batch(&self) -> &TrackingBatch {
+        &self.batch
+    }
+
+    /// Returns why the batch was dead-lettered
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// Returns the time the batch was dead-lettered, in seconds since the epoch
+    pub fn dead_lettered_at(&self) -> i64 {
+        self.dead_lettered_at
+    }
+}
+
+/// A manual action that resolves a dead-lettered batch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeadLetterAction {
+    /// The batch, or a re-signed replacement of it, was queued for submission again
+    Replay,
+    /// The batch was removed without being submitted
+    Discard,
+}
+
+impl DeadLetterAction {
+    fn try_from_string(value: &str) -> Result<DeadLetterAction, BatchTrackingStoreError> {
+        match value {
+            "Replay" => Ok(DeadLetterAction::Replay),
+            "Discard" => Ok(DeadLetterAction::Discard),
+            _ => Err(BatchTrackingStoreError::InternalError(
+                InternalError::with_message(format!("Dead-letter action {} is not valid", value)),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DeadLetterAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeadLetterAction::Replay => write!(f, "Replay"),
+            DeadLetterAction::Discard => write!(f, "Discard"),
+        }
+    }
+}
+
+/// An audit record of a manual action taken on a dead-lettered batch
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DeadLetterAuditRecord {
+    service_id: String,
+    batch_id: String,
+    action: DeadLetterAction,
+    actor: String,
+    replacement_batch_id: Option<String>,
+    created_at: i64,
+}
+
+impl DeadLetterAuditRecord {
+    pub fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    pub fn batch_id(&self) -> &str {
+        &self.batch_id
+    }
+
+    pub fn action(&self) -> DeadLetterAction {
+        self.action
+    }
+
+    /// Returns the public key of the user who took the action
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    /// Returns the ID of the re-signed batch that replaced the dead-lettered batch, if any
+    pub fn replacement_batch_id(&self) -> Option<&str> {
+        self.replacement_batch_id.as_deref()
+    }
+
+    pub fn created_at(&self) -> i64 {
+        self.created_at
+    }
+}
+
+/// A subscription to notifications about status changes of the batches signed by a submitter
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WebhookSubscription {
+    id: i64,
+    signer_public_key: String,
+    url: String,
+    secret: String,
+    event_types: Vec<BatchStatusName>,
+    created_at: i64,
+}
+
+impl WebhookSubscription {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// Returns the public key of the submitter whose batches the subscription covers
+    pub fn signer_public_key(&self) -> &str {
+        &self.signer_public_key
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the secret used to sign the notifications sent to the subscription
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    /// Returns the batch statuses that trigger a notification
+    pub fn event_types(&self) -> &[BatchStatusName] {
+        &self.event_types
+    }
+
+    pub fn created_at(&self) -> i64 {
+        self.created_at
+    }
+}
+
+/// The state of a webhook notification
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebhookDeliveryStatus {
+    /// The notification has not been delivered yet and will be attempted again
+    Pending,
+    /// The subscriber accepted the notification
+    Delivered,
+    /// Delivery was given up on
+    Failed,
+}
+
+impl WebhookDeliveryStatus {
+    fn try_from_string(value: &str) -> Result<WebhookDeliveryStatus, BatchTrackingStoreError> {
+        match value {
+            "Pending" => Ok(WebhookDeliveryStatus::Pending),
+            "Delivered" => Ok(WebhookDeliveryStatus::Delivered),
+            "Failed" => Ok(WebhookDeliveryStatus::Failed),
+            _ => Err(BatchTrackingStoreError::InternalError(
+                InternalError::with_message(format!(
+                    "Webhook delivery status {} is not valid",
+                    value
+                )),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for WebhookDeliveryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WebhookDeliveryStatus::Pending => write!(f, "Pending"),
+            WebhookDeliveryStatus::Delivered => write!(f, "Delivered"),
+            WebhookDeliveryStatus::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+/// A notification that a batch changed status, to be delivered to a webhook subscription
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WebhookDelivery {
+    id: i64,
+    subscription_id: i64,
+    url: String,
+    secret: String,
+    service_id: String,
+    batch_id: String,
+    data_change_id: Option<String>,
+    event_type: BatchStatusName,
+    previous_status: Option<BatchStatusName>,
+    status: WebhookDeliveryStatus,
+    attempts: i64,
+    next_attempt_at: i64,
+    created_at: i64,
+    delivered_at: Option<i64>,
+}
+
+impl WebhookDelivery {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn subscription_id(&self) -> i64 {
+        self.subscription_id
+    }
+
+    /// Returns the URL of the subscription the notification is delivered to
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the secret of the subscription, used to sign the notification
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    pub fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    pub fn batch_id(&self) -> &str {
+        &self.batch_id
+    }
+
+    pub fn data_change_id(&self) -> Option<&str> {
+        self.data_change_id.as_deref()
+    }
+
+    /// Returns the status the batch changed to
+    pub fn event_type(&self) -> BatchStatusName {
+        self.event_type
+    }
+
+    /// Returns the status the batch had before the change, if it had one
+    pub fn previous_status(&self) -> Option<BatchStatusName> {
+        self.previous_status
+    }
+
+    pub fn status(&self) -> WebhookDeliveryStatus {
+        self.status
+    }
+
+    /// Returns the number of delivery attempts made so far
+    pub fn attempts(&self) -> i64 {
+        self.attempts
+    }
+
+    /// Returns the earliest time the next delivery attempt is made, in seconds since the epoch
+    pub fn next_attempt_at(&self) -> i64 {
+        self.next_attempt_at
+    }
+
+    pub fn created_at(&self) -> i64 {
+        self.created_at
+    }
+
+    pub fn delivered_at(&self) -> Option<i64> {
+        self.delivered_at
+    }
+}
+
+/// The log entry of a single attempt to deliver a webhook notification
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WebhookDeliveryAttempt {
+    delivery_id: i64,
+    response_code: Option<i64>,
+    error: Option<String>,
+    attempted_at: i64,
+}
+
+impl WebhookDeliveryAttempt {
+    pub fn delivery_id(&self) -> i64 {
+        self.delivery_id
+    }
+
+    /// Returns the HTTP status of the subscriber's response, if a response was received
+    pub fn response_code(&self) -> Option<i64> {
+        self.response_code
+    }
+
+    /// Returns why the attempt failed, if it did
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn attempted_at(&self) -> i64 {
+        self.attempted_at
+    }
+}
+
+/// The batches that are waiting to be submitted
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BatchQueueSummary {
+    depth: i64,
+    oldest_created_at: Option<i64>,
+}
+
+impl BatchQueueSummary {
+    /// Returns the number of batches that are waiting to be submitted
+    pub fn depth(&self) -> i64 {
+        self.depth
+    }
+
+    /// Returns when the oldest of the waiting batches was added, in seconds since the Unix
+    /// epoch, or `None` if no batches are waiting
+    pub fn oldest_created_at(&self) -> Option<i64> {
+        self.oldest_created_at
+    }
+}
+
+/// Stores the batches submitted through Grid and tracks their status
+///
+/// A data change ID is only unique for the signer that submitted it. The methods that accept a
+/// data change ID in place of a batch ID return a `ConstraintViolationError` if more than one
+/// signer submitted a batch with it to the service, as it does not identify a single batch.
 pub trait BatchTrackingStore {
     /// Gets the status of a batch from the underlying storage
     ///
@@ -1027,6 +1459,23 @@ pub trait BatchTrackingStore {
         service_id: &str,
     ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError>;
 
+    /// Gets a batch by the data change ID its signer submitted it with
+    ///
+    /// Data change IDs are only unique for a given signer and service, so the
+    /// same data change ID submitted by another signer will not match.
+    ///
+    /// # Arguments
+    ///
+    ///  * `data_change_id` - The data change ID of the batch to fetch
+    ///  * `signer_public_key` - The public key of the signer of the batch
+    ///  * `service_id` - The service ID
+    fn get_batch_by_data_change_id(
+        &self,
+        data_change_id: &str,
+        signer_public_key: &str,
+        service_id: &str,
+    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError>;
+
     /// Lists batches with a given status from the underlying storage
     ///
     /// # Arguments
@@ -1044,12 +1493,231 @@ pub trait BatchTrackingStore {
     ///  * `submitted_by` - The timestamp for which to delete records submitted before
     fn clean_stale_records(&self, submitted_by: i64) -> Result<(), BatchTrackingStoreError>;
 
+    /// Removes batches with the given status that were created before a given time, along with
+    /// their transactions, receipts and submission records
+    ///
+    /// Returns the number of batches removed.
+    ///
+    /// # Arguments
+    ///
+    ///  * `status` - The status of the batches to remove
+    ///  * `created_before` - The timestamp for which to remove batches created before
+    ///  * `limit` - The maximum number of batches to remove
+    fn purge_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError>;
+
+    /// Drops the serialized batch and transaction payloads of batches with the given status that
+    /// were created before a given time, keeping the rest of their records
+    ///
+    /// A compacted batch reports an empty serialized batch. Returns the number of batches
+    /// compacted.
+    ///
+    /// # Arguments
+    ///
+    ///  * `status` - The status of the batches to compact
+    ///  * `created_before` - The timestamp for which to compact batches created before
+    ///  * `limit` - The maximum number of batches to compact
+    fn compact_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError>;
+
     /// Gets batches that have not yet been submitted from the underlying storage
     fn get_unsubmitted_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError>;
 
+    /// Gets a page of the batches returned by `get_unsubmitted_batches`, in the order they are to
+    /// be submitted: by priority, then by the time they were added
+    ///
+    /// # Arguments
+    ///
+    ///  * `offset` - The number of batches to skip
+    ///  * `limit` - The maximum number of batches to return
+    fn get_next_unsubmitted_batches(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<TrackingBatchList, BatchTrackingStoreError>;
+
+    /// Counts the batches that have not yet been submitted, as returned by
+    /// `get_unsubmitted_batches`, without loading them
+    fn get_queue_summary(&self) -> Result<BatchQueueSummary, BatchTrackingStoreError>;
+
     /// Gets batches that failed either due to validation or submission errors
     /// from the underlying storage
     fn get_failed_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError>;
+
+    /// Moves a batch to the dead letter queue, so that it is no longer submitted
+    ///
+    /// The batch is given an `Invalid` status. Dead-lettering a batch that is already
+    /// dead-lettered updates the reason.
+    ///
+    /// # Arguments
+    ///
+    ///  * `id` - The ID or data change ID of the batch
+    ///  * `service_id` - The service ID
+    ///  * `reason` - Why the batch is being dead-lettered
+    fn dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        reason: &str,
+    ) -> Result<(), BatchTrackingStoreError>;
+
+    /// Lists the dead-lettered batches from the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `service_id` - optional - The service ID to list dead-lettered batches for
+    fn list_dead_letter_batches(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, BatchTrackingStoreError>;
+
+    /// Removes a batch from the dead letter queue and queues it for submission again
+    ///
+    /// The status, receipts and submission errors of the batch are cleared. If a replacement is
+    /// given, such as a re-signed copy of the batch, the dead-lettered batch is removed and the
+    /// replacement is added in its place.
+    ///
+    /// # Arguments
+    ///
+    ///  * `id` - The ID or data change ID of the dead-lettered batch
+    ///  * `service_id` - The service ID
+    ///  * `actor` - The public key of the user replaying the batch, recorded for auditing
+    ///  * `replacement` - optional - The batch to submit in place of the dead-lettered batch
+    fn replay_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+        replacement: Option<TrackingBatch>,
+    ) -> Result<(), BatchTrackingStoreError>;
+
+    /// Removes a dead-lettered batch without submitting it
+    ///
+    /// # Arguments
+    ///
+    ///  * `id` - The ID or data change ID of the dead-lettered batch
+    ///  * `service_id` - The service ID
+    ///  * `actor` - The public key of the user discarding the batch, recorded for auditing
+    fn discard_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+    ) -> Result<(), BatchTrackingStoreError>;
+
+    /// Lists the audit records of replayed and discarded batches, oldest first
+    ///
+    /// # Arguments
+    ///
+    ///  * `service_id` - optional - The service ID to list audit records for
+    fn list_dead_letter_audit_records(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterAuditRecord>, BatchTrackingStoreError>;
+
+    /// Subscribes a webhook to status changes of the batches signed by a submitter
+    ///
+    /// Once subscribed, a notification is queued for delivery whenever one of the submitter's
+    /// batches changes to one of the given statuses.
+    ///
+    /// # Arguments
+    ///
+    ///  * `signer_public_key` - The public key of the submitter
+    ///  * `url` - The URL notifications are posted to
+    ///  * `secret` - The secret used to sign the notifications
+    ///  * `event_types` - The batch statuses that trigger a notification
+    fn add_webhook_subscription(
+        &self,
+        signer_public_key: &str,
+        url: &str,
+        secret: &str,
+        event_types: &[BatchStatusName],
+    ) -> Result<WebhookSubscription, BatchTrackingStoreError>;
+
+    /// Lists the webhook subscriptions of a submitter
+    ///
+    /// # Arguments
+    ///
+    ///  * `signer_public_key` - The public key of the submitter
+    fn list_webhook_subscriptions(
+        &self,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookSubscription>, BatchTrackingStoreError>;
+
+    /// Removes a webhook subscription along with its queued notifications and delivery logs
+    ///
+    /// # Arguments
+    ///
+    ///  * `id` - The ID of the subscription
+    ///  * `signer_public_key` - The public key of the submitter that owns the subscription
+    fn remove_webhook_subscription(
+        &self,
+        id: i64,
+        signer_public_key: &str,
+    ) -> Result<(), BatchTrackingStoreError>;
+
+    /// Gets the pending webhook notifications that are due to be delivered, oldest first
+    ///
+    /// # Arguments
+    ///
+    ///  * `now` - The current time, in seconds since the epoch
+    ///  * `limit` - The maximum number of notifications to return
+    fn get_pending_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError>;
+
+    /// Records an attempt to deliver a webhook notification in its delivery log
+    ///
+    /// # Arguments
+    ///
+    ///  * `delivery_id` - The ID of the notification
+    ///  * `attempted_at` - The time of the attempt, in seconds since the epoch
+    ///  * `response_code` - optional - The HTTP status of the subscriber's response
+    ///  * `error` - optional - Why the attempt failed
+    ///  * `status` - The state of the notification after the attempt
+    ///  * `next_attempt_at` - The earliest time of the next attempt, in seconds since the epoch,
+    ///    if the notification is still pending
+    fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: i64,
+        attempted_at: i64,
+        response_code: Option<i64>,
+        error: Option<&str>,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: i64,
+    ) -> Result<(), BatchTrackingStoreError>;
+
+    /// Lists the notifications queued for a webhook subscription, oldest first
+    ///
+    /// # Arguments
+    ///
+    ///  * `subscription_id` - The ID of the subscription
+    ///  * `signer_public_key` - The public key of the submitter that owns the subscription
+    fn list_webhook_deliveries(
+        &self,
+        subscription_id: i64,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError>;
+
+    /// Lists the delivery log of a webhook notification, oldest first
+    ///
+    /// # Arguments
+    ///
+    ///  * `delivery_id` - The ID of the notification
+    fn list_webhook_delivery_attempts(
+        &self,
+        delivery_id: i64,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, BatchTrackingStoreError>;
 }
 
 impl<BS> BatchTrackingStore for Box<BS>
@@ -1110,6 +1778,15 @@ where
         (**self).get_batch(id, service_id)
     }
 
+    fn get_batch_by_data_change_id(
+        &self,
+        data_change_id: &str,
+        signer_public_key: &str,
+        service_id: &str,
+    ) -> Result<Option<TrackingBatch>, BatchTrackingStoreError> {
+        (**self).get_batch_by_data_change_id(data_change_id, signer_public_key, service_id)
+    }
+
     fn list_batches_by_status(
         &self,
         status: BatchStatus,
@@ -1121,13 +1798,152 @@ where
         (**self).clean_stale_records(submitted_by)
     }
 
+    fn purge_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        (**self).purge_batches(status, created_before, limit)
+    }
+
+    fn compact_batches(
+        &self,
+        status: BatchStatusName,
+        created_before: i64,
+        limit: i64,
+    ) -> Result<usize, BatchTrackingStoreError> {
+        (**self).compact_batches(status, created_before, limit)
+    }
+
     fn get_unsubmitted_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
         (**self).get_unsubmitted_batches()
     }
 
+    fn get_next_unsubmitted_batches(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<TrackingBatchList, BatchTrackingStoreError> {
+        (**self).get_next_unsubmitted_batches(offset, limit)
+    }
+
+    fn get_queue_summary(&self) -> Result<BatchQueueSummary, BatchTrackingStoreError> {
+        (**self).get_queue_summary()
+    }
+
     fn get_failed_batches(&self) -> Result<TrackingBatchList, BatchTrackingStoreError> {
         (**self).get_failed_batches()
     }
+
+    fn dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        reason: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        (**self).dead_letter_batch(id, service_id, reason)
+    }
+
+    fn list_dead_letter_batches(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, BatchTrackingStoreError> {
+        (**self).list_dead_letter_batches(service_id)
+    }
+
+    fn replay_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+        replacement: Option<TrackingBatch>,
+    ) -> Result<(), BatchTrackingStoreError> {
+        (**self).replay_dead_letter_batch(id, service_id, actor, replacement)
+    }
+
+    fn discard_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: &str,
+        actor: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        (**self).discard_dead_letter_batch(id, service_id, actor)
+    }
+
+    fn list_dead_letter_audit_records(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterAuditRecord>, BatchTrackingStoreError> {
+        (**self).list_dead_letter_audit_records(service_id)
+    }
+
+    fn add_webhook_subscription(
+        &self,
+        signer_public_key: &str,
+        url: &str,
+        secret: &str,
+        event_types: &[BatchStatusName],
+    ) -> Result<WebhookSubscription, BatchTrackingStoreError> {
+        (**self).add_webhook_subscription(signer_public_key, url, secret, event_types)
+    }
+
+    fn list_webhook_subscriptions(
+        &self,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookSubscription>, BatchTrackingStoreError> {
+        (**self).list_webhook_subscriptions(signer_public_key)
+    }
+
+    fn remove_webhook_subscription(
+        &self,
+        id: i64,
+        signer_public_key: &str,
+    ) -> Result<(), BatchTrackingStoreError> {
+        (**self).remove_webhook_subscription(id, signer_public_key)
+    }
+
+    fn get_pending_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        (**self).get_pending_webhook_deliveries(now, limit)
+    }
+
+    fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: i64,
+        attempted_at: i64,
+        response_code: Option<i64>,
+        error: Option<&str>,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: i64,
+    ) -> Result<(), BatchTrackingStoreError> {
+        (**self).record_webhook_delivery_attempt(
+            delivery_id,
+            attempted_at,
+            response_code,
+            error,
+            status,
+            next_attempt_at,
+        )
+    }
+
+    fn list_webhook_deliveries(
+        &self,
+        subscription_id: i64,
+        signer_public_key: &str,
+    ) -> Result<Vec<WebhookDelivery>, BatchTrackingStoreError> {
+        (**self).list_webhook_deliveries(subscription_id, signer_public_key)
+    }
+
+    fn list_webhook_delivery_attempts(
+        &self,
+        delivery_id: i64,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, BatchTrackingStoreError> {
+        (**self).list_webhook_delivery_attempts(delivery_id)
+    }
 }
 
 #[cfg(test)]
@@ -1146,6 +1962,7 @@ mod tests {
             serialized_batch: Vec::new(),
             submitted: false,
             created_at: 000,
+            priority: BatchPriority::Normal,
             transactions: Vec::new(),
             batch_status: None,
             submission_error: None,
@@ -1160,6 +1977,7 @@ mod tests {
             serialized_batch: Vec::new(),
             submitted: false,
             created_at: 000,
+            priority: BatchPriority::Normal,
             transactions: Vec::new(),
             batch_status: None,
             submission_error: None,
@@ -1196,6 +2014,7 @@ mod tests {
             serialized_batch: Vec::new(),
             submitted: false,
             created_at: 000,
+            priority: BatchPriority::Normal,
             transactions: Vec::new(),
             batch_status: None,
             submission_error: None,
@@ -1210,6 +2029,7 @@ mod tests {
             serialized_batch: Vec::new(),
             submitted: false,
             created_at: 000,
+            priority: BatchPriority::Normal,
             transactions: Vec::new(),
             batch_status: None,
             submission_error: None,
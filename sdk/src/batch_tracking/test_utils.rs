@@ -0,0 +1,90 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for the tests of the batch tracking store's users, such as the batch submitter
+
+use cylinder::{secp256k1::Secp256k1Context, Context};
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::sqlite::SqliteConnection;
+use transact::protocol::{
+    batch::BatchBuilder,
+    transaction::{HashMethod, TransactionBuilder},
+};
+
+use crate::batch_tracking::store::{TrackingBatch, TrackingBatchBuilder};
+use crate::migrations::run_sqlite_migrations;
+use crate::store::{sqlite::SqliteStoreFactory, TransactionalStoreFactory};
+
+/// Returns a store factory for a migrated in-memory SQLite database
+pub fn create_store_factory() -> Box<dyn TransactionalStoreFactory> {
+    let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+    let pool = Pool::builder()
+        .max_size(1)
+        .build(connection_manager)
+        .expect("Failed to build connection pool");
+
+    run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+        .expect("Failed to run migrations");
+
+    Box::new(SqliteStoreFactory::new(pool))
+}
+
+/// Returns a builder holding a batch of one transaction, signed with a new key so that each
+/// batch is unique
+pub fn tracking_batch_builder() -> TrackingBatchBuilder {
+    let context = Secp256k1Context::new();
+    let signer = context.new_signer(context.new_random_private_key());
+    let public_key = signer.public_key().expect("Failed to get public key");
+
+    let transaction = TransactionBuilder::new()
+        .with_batcher_public_key(public_key.as_slice().to_vec())
+        .with_family_name("test_family".to_string())
+        .with_family_version("0.1".to_string())
+        .with_inputs(vec![])
+        .with_nonce(b"f9kdzz".to_vec())
+        .with_outputs(vec![])
+        .with_payload_hash_method(HashMethod::Sha512)
+        .with_payload(vec![0x05, 0x06, 0x07, 0x08])
+        .build(&*signer)
+        .expect("Failed to build transaction");
+
+    let batch = BatchBuilder::new()
+        .with_transactions(vec![transaction])
+        .build(&*signer)
+        .expect("Failed to build batch");
+
+    TrackingBatchBuilder::default()
+        .with_batch(batch)
+        .with_signer_public_key(public_key.as_hex())
+}
+
+/// Adds an unsubmitted batch to the store
+pub fn add_tracking_batch(store_factory: &dyn TransactionalStoreFactory) -> TrackingBatch {
+    add_built_batch(store_factory, tracking_batch_builder())
+}
+
+/// Adds the batch built by the given builder to the store
+pub fn add_built_batch(
+    store_factory: &dyn TransactionalStoreFactory,
+    builder: TrackingBatchBuilder,
+) -> TrackingBatch {
+    let tracking_batch = builder.build().expect("Failed to build tracking batch");
+
+    store_factory
+        .get_batch_tracking_store()
+        .add_batches(vec![tracking_batch.clone()])
+        .expect("Failed to add batch");
+
+    tracking_batch
+}
@@ -0,0 +1,661 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Delivery of webhook notifications about batch status changes
+//!
+//! Whenever a batch tracked in the `BatchTrackingStore` changes status, a notification is queued
+//! in the store for every webhook subscription of the batch's submitter that includes the new
+//! status. The `WebhookDeliverer` polls the store for queued notifications and posts them to the
+//! subscribers as JSON.
+//!
+//! Each notification is signed with the secret of its subscription. The request carries the time
+//! it was sent in the `X-Grid-Timestamp` header and an HMAC-SHA256 of `<timestamp>.<body>`,
+//! keyed with the secret, in the `X-Grid-Signature` header as `sha256=<hex digest>`. Subscribers
+//! should recompute the signature and reject notifications with an old timestamp.
+//!
+//! Subscribers are delivered to concurrently, and each subscriber receives its notifications in
+//! the order they were queued. Once a notification to a subscriber fails, the subscriber's
+//! remaining notifications wait for the next poll, so that a slow or unreachable subscriber holds
+//! up neither the other subscribers nor more than one request timeout of its own.
+//!
+//! A notification that is not accepted with a 2xx response is attempted again after an
+//! exponential backoff, until it has been attempted the maximum number of times. Every attempt is
+//! recorded in the notification's delivery log.
+
+use std::cmp::min;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crypto::hmac::Hmac;
+use crypto::mac::Mac;
+use crypto::sha2::Sha256;
+use reqwest::blocking::Client;
+
+use crate::batch_tracking::store::{
+    BatchTrackingStore, WebhookDelivery, WebhookDeliveryStatus, NON_SPLINTER_SERVICE_ID_DEFAULT,
+};
+use crate::error::InternalError;
+use crate::hex::to_hex;
+use crate::store::TransactionalStoreFactory;
+use crate::threading::lifecycle::ShutdownHandle;
+
+pub const SIGNATURE_HEADER: &str = "X-Grid-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Grid-Timestamp";
+pub const DELIVERY_HEADER: &str = "X-Grid-Delivery";
+pub const EVENT_HEADER: &str = "X-Grid-Event";
+
+const DEFAULT_POLLING_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_BATCH_SIZE: i64 = 100;
+const DEFAULT_MAX_ATTEMPTS: u32 = 8;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(3600);
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// The longest the deliverer thread sleeps before checking whether it should shut down
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The body of a webhook notification
+#[derive(Debug, Serialize)]
+struct Notification<'a> {
+    id: i64,
+    event_type: String,
+    batch_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    service_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data_change_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_status: Option<String>,
+    changed_at: i64,
+}
+
+impl<'a> From<&'a WebhookDelivery> for Notification<'a> {
+    fn from(delivery: &'a WebhookDelivery) -> Self {
+        Notification {
+            id: delivery.id(),
+            event_type: delivery.event_type().to_string(),
+            batch_id: delivery.batch_id(),
+            service_id: Some(delivery.service_id())
+                .filter(|service_id| *service_id != NON_SPLINTER_SERVICE_ID_DEFAULT),
+            data_change_id: delivery.data_change_id(),
+            previous_status: delivery.previous_status().map(|status| status.to_string()),
+            changed_at: delivery.created_at(),
+        }
+    }
+}
+
+/// Computes the signature of a notification body sent at the given time
+///
+/// # Arguments
+///
+/// * `secret` - The secret of the subscription the notification is sent to
+/// * `timestamp` - The time the notification is sent, in seconds since the epoch
+/// * `body` - The body of the notification
+pub fn sign_notification(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut hmac = Hmac::new(Sha256::new(), secret.as_bytes());
+    hmac.input(timestamp.to_string().as_bytes());
+    hmac.input(b".");
+    hmac.input(body.as_bytes());
+    format!("sha256={}", to_hex(hmac.result().code()))
+}
+
+#[derive(Clone)]
+struct DeliverySettings {
+    batch_size: i64,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl DeliverySettings {
+    // Returns the time to wait after the given number of failed attempts, starting at 1
+    fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 2u32
+            .checked_pow(attempts.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .map(|backoff| min(backoff, self.max_backoff))
+            .unwrap_or(self.max_backoff)
+    }
+}
+
+/// Builds and starts a `WebhookDeliverer`
+pub struct WebhookDelivererBuilder {
+    store_factory: Box<dyn TransactionalStoreFactory>,
+    polling_interval: Duration,
+    request_timeout: Duration,
+    settings: DeliverySettings,
+}
+
+impl WebhookDelivererBuilder {
+    pub fn new(store_factory: Box<dyn TransactionalStoreFactory>) -> Self {
+        Self {
+            store_factory,
+            polling_interval: DEFAULT_POLLING_INTERVAL,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            settings: DeliverySettings {
+                batch_size: DEFAULT_BATCH_SIZE,
+                max_attempts: DEFAULT_MAX_ATTEMPTS,
+                initial_backoff: DEFAULT_INITIAL_BACKOFF,
+                max_backoff: DEFAULT_MAX_BACKOFF,
+            },
+        }
+    }
+
+    /// Sets how often the store is checked for notifications that are due
+    pub fn with_polling_interval(mut self, polling_interval: Duration) -> Self {
+        self.polling_interval = polling_interval;
+        self
+    }
+
+    /// Sets the maximum number of notifications delivered per poll
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.settings.batch_size = batch_size;
+        self
+    }
+
+    /// Sets the number of attempts after which the delivery of a notification is given up on
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.settings.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the wait after the first failed attempt; the wait doubles after every further
+    /// failure, up to the maximum backoff
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.settings.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.settings.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the timeout of a single delivery request
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Starts the deliverer thread
+    pub fn start(self) -> Result<WebhookDeliverer, InternalError> {
+        if self.settings.max_attempts == 0 {
+            return Err(InternalError::with_message(
+                "Maximum number of webhook delivery attempts must be greater than 0".into(),
+            ));
+        }
+
+        // Redirects are not followed, so that a subscriber cannot send notifications on to a
+        // host a webhook URL could not name
+        let client = Client::builder()
+            .timeout(self.request_timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let store_factory = self.store_factory;
+        let polling_interval = self.polling_interval;
+        let settings = self.settings;
+
+        let join_handle = thread::Builder::new()
+            .name("Webhook Deliverer".into())
+            .spawn(move || {
+                while thread_running.load(Ordering::SeqCst) {
+                    deliver_pending(
+                        &*store_factory.get_batch_tracking_store(),
+                        &client,
+                        &settings,
+                        now(),
+                    );
+
+                    let start = Instant::now();
+                    while thread_running.load(Ordering::SeqCst)
+                        && start.elapsed() < polling_interval
+                    {
+                        thread::sleep(min(SHUTDOWN_CHECK_INTERVAL, polling_interval));
+                    }
+                }
+            })
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        Ok(WebhookDeliverer {
+            running,
+            join_handle,
+        })
+    }
+}
+
+/// Delivers the webhook notifications queued in the batch tracking store on a background thread
+pub struct WebhookDeliverer {
+    running: Arc<AtomicBool>,
+    join_handle: thread::JoinHandle<()>,
+}
+
+impl ShutdownHandle for WebhookDeliverer {
+    fn signal_shutdown(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    fn wait_for_shutdown(self) -> Result<(), InternalError> {
+        self.join_handle.join().map_err(|_| {
+            InternalError::with_message(
+                "Webhook deliverer thread did not shutdown correctly".into(),
+            )
+        })
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+// Attempts to deliver the notifications that are due and records the outcome of each attempt
+fn deliver_pending(
+    store: &dyn BatchTrackingStore,
+    client: &Client,
+    settings: &DeliverySettings,
+    now: i64,
+) {
+    let deliveries = match store.get_pending_webhook_deliveries(now, settings.batch_size) {
+        Ok(deliveries) => deliveries,
+        Err(err) => {
+            error!("Unable to fetch pending webhook deliveries: {}", err);
+            return;
+        }
+    };
+
+    // The deliveries are returned in the order they were queued, which is kept for each subscriber
+    let mut by_subscription: BTreeMap<i64, Vec<WebhookDelivery>> = BTreeMap::new();
+    for delivery in deliveries {
+        by_subscription
+            .entry(delivery.subscription_id())
+            .or_default()
+            .push(delivery);
+    }
+
+    let workers = by_subscription
+        .into_iter()
+        .map(|(subscription_id, deliveries)| {
+            let client = client.clone();
+            let worker = thread::Builder::new()
+                .name(format!("Webhook Delivery {}", subscription_id))
+                .spawn(move || deliver_to_subscriber(&client, deliveries));
+            (subscription_id, worker)
+        })
+        .collect::<Vec<_>>();
+
+    for (subscription_id, worker) in workers {
+        let outcomes = match worker {
+            Ok(join_handle) => match join_handle.join() {
+                Ok(outcomes) => outcomes,
+                Err(_) => {
+                    error!(
+                        "Webhook delivery thread for subscription {} panicked",
+                        subscription_id
+                    );
+                    continue;
+                }
+            },
+            Err(err) => {
+                error!(
+                    "Unable to start webhook delivery thread for subscription {}: {}",
+                    subscription_id, err
+                );
+                continue;
+            }
+        };
+
+        for (delivery, result) in outcomes {
+            record_attempt(store, settings, now, &delivery, result);
+        }
+    }
+}
+
+// Posts a subscriber's notifications in order, stopping at the first one that is not accepted.
+// Returns the outcome of each notification that was attempted.
+fn deliver_to_subscriber(
+    client: &Client,
+    deliveries: Vec<WebhookDelivery>,
+) -> Vec<(WebhookDelivery, Result<i64, String>)> {
+    let mut outcomes = Vec::with_capacity(deliveries.len());
+    for delivery in deliveries {
+        let result = deliver(client, &delivery);
+        let accepted = matches!(result, Ok(code) if (200..300).contains(&code));
+        outcomes.push((delivery, result));
+        if !accepted {
+            break;
+        }
+    }
+    outcomes
+}
+
+// Records the outcome of an attempt, scheduling the next attempt or giving up on the delivery
+fn record_attempt(
+    store: &dyn BatchTrackingStore,
+    settings: &DeliverySettings,
+    now: i64,
+    delivery: &WebhookDelivery,
+    result: Result<i64, String>,
+) {
+    let (response_code, error) = match result {
+        Ok(code) if (200..300).contains(&code) => (Some(code), None),
+        Ok(code) => (
+            Some(code),
+            Some(format!("Subscriber responded with HTTP {}", code)),
+        ),
+        Err(err) => (None, Some(err)),
+    };
+
+    let attempts = u32::try_from(delivery.attempts() + 1).unwrap_or(u32::MAX);
+    let (status, next_attempt_at) = match error {
+        None => (WebhookDeliveryStatus::Delivered, now),
+        Some(ref err) if attempts >= settings.max_attempts => {
+            warn!(
+                "Giving up on webhook delivery {} to {} after {} attempts: {}",
+                delivery.id(),
+                delivery.url(),
+                attempts,
+                err
+            );
+            (WebhookDeliveryStatus::Failed, now)
+        }
+        Some(ref err) => {
+            debug!(
+                "Webhook delivery {} to {} failed: {}",
+                delivery.id(),
+                delivery.url(),
+                err
+            );
+            (
+                WebhookDeliveryStatus::Pending,
+                now + settings.backoff(attempts).as_secs() as i64,
+            )
+        }
+    };
+
+    if let Err(err) = store.record_webhook_delivery_attempt(
+        delivery.id(),
+        now,
+        response_code,
+        error.as_deref(),
+        status,
+        next_attempt_at,
+    ) {
+        error!(
+            "Unable to record attempt of webhook delivery {}: {}",
+            delivery.id(),
+            err
+        );
+    }
+}
+
+// Posts a notification, signed at the time it is sent, returning the status of the response
+fn deliver(client: &Client, delivery: &WebhookDelivery) -> Result<i64, String> {
+    let body = serde_json::to_string(&Notification::from(delivery))
+        .map_err(|err| format!("Unable to serialize notification: {}", err))?;
+    let timestamp = now();
+    let signature = sign_notification(delivery.secret(), timestamp, &body);
+
+    client
+        .post(delivery.url())
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(DELIVERY_HEADER, delivery.id().to_string())
+        .header(EVENT_HEADER, delivery.event_type().to_string())
+        .body(body)
+        .send()
+        .map(|res| i64::from(res.status().as_u16()))
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use mockito::{mock, Matcher};
+
+    use crate::batch_tracking::store::{BatchStatus, BatchStatusName};
+    use crate::batch_tracking::test_utils::{add_tracking_batch, create_store_factory};
+
+    /// Verify that a notification is signed with an HMAC-SHA256 of the timestamp and body.
+    #[test]
+    fn test_sign_notification() {
+        assert_eq!(
+            sign_notification("secret", 1654000000, "{}"),
+            "sha256=0492b7afbf5932d72ceab7fcba9156774dda6415599c9f5723562044b5d3ff8e"
+        );
+    }
+
+    /// Verify that a notification queued by a status change is posted to the subscriber with a
+    /// valid signature, and that the delivery is recorded.
+    #[test]
+    fn test_deliver_pending_notification() {
+        let store_factory = create_store_factory();
+        let store = store_factory.get_batch_tracking_store();
+        let batch = add_tracking_batch(&*store_factory);
+        let batch_id = batch.batch_header().to_string();
+        let signer_public_key = batch.signer_public_key().to_string();
+
+        let subscription = store
+            .add_webhook_subscription(
+                &signer_public_key,
+                &format!("{}/delivered", mockito::server_url()),
+                "secret",
+                &[BatchStatusName::Committed],
+            )
+            .expect("Failed to add subscription");
+
+        store
+            .update_batch_status(
+                &batch_id,
+                NON_SPLINTER_SERVICE_ID_DEFAULT,
+                Some(BatchStatus::Committed(vec![])),
+                vec![],
+                None,
+            )
+            .expect("Failed to update batch status");
+
+        let now = now();
+        let mock = mock("POST", "/delivered")
+            .match_header(EVENT_HEADER, "Committed")
+            .match_header(TIMESTAMP_HEADER, Matcher::Regex("^[0-9]+$".into()))
+            .match_header(
+                SIGNATURE_HEADER,
+                Matcher::Regex("^sha256=[0-9a-f]{64}$".into()),
+            )
+            .match_body(Matcher::PartialJsonString(format!(
+                r#"{{"event_type": "Committed", "batch_id": "{}"}}"#,
+                batch_id
+            )))
+            .with_status(200)
+            .create();
+
+        deliver_pending(&*store, &Client::new(), &settings(3), now);
+        mock.assert();
+
+        let deliveries = store
+            .list_webhook_deliveries(subscription.id(), &signer_public_key)
+            .expect("Failed to list deliveries");
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].status(), WebhookDeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].delivered_at(), Some(now));
+
+        let attempts = store
+            .list_webhook_delivery_attempts(deliveries[0].id())
+            .expect("Failed to list delivery attempts");
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].response_code(), Some(200));
+        assert_eq!(attempts[0].error(), None);
+    }
+
+    /// Verify that a rejected notification is retried after a backoff and given up on once the
+    /// maximum number of attempts is reached.
+    #[test]
+    fn test_retry_rejected_notification() {
+        let store_factory = create_store_factory();
+        let store = store_factory.get_batch_tracking_store();
+        let batch = add_tracking_batch(&*store_factory);
+        let batch_id = batch.batch_header().to_string();
+        let signer_public_key = batch.signer_public_key().to_string();
+
+        let subscription = store
+            .add_webhook_subscription(
+                &signer_public_key,
+                &format!("{}/rejected", mockito::server_url()),
+                "secret",
+                &[BatchStatusName::Invalid],
+            )
+            .expect("Failed to add subscription");
+
+        store
+            .update_batch_status(
+                &batch_id,
+                NON_SPLINTER_SERVICE_ID_DEFAULT,
+                Some(BatchStatus::Invalid(vec![])),
+                vec![],
+                None,
+            )
+            .expect("Failed to update batch status");
+
+        let mock = mock("POST", "/rejected")
+            .with_status(500)
+            .expect(2)
+            .create();
+
+        let now = now();
+        deliver_pending(&*store, &Client::new(), &settings(2), now);
+
+        let deliveries = store
+            .list_webhook_deliveries(subscription.id(), &signer_public_key)
+            .expect("Failed to list deliveries");
+        assert_eq!(deliveries[0].status(), WebhookDeliveryStatus::Pending);
+        assert_eq!(deliveries[0].attempts(), 1);
+        assert_eq!(deliveries[0].next_attempt_at(), now + 5);
+
+        // The notification is not due until the backoff has passed
+        deliver_pending(&*store, &Client::new(), &settings(2), now + 4);
+        deliver_pending(&*store, &Client::new(), &settings(2), now + 5);
+        mock.assert();
+
+        let deliveries = store
+            .list_webhook_deliveries(subscription.id(), &signer_public_key)
+            .expect("Failed to list deliveries");
+        assert_eq!(deliveries[0].status(), WebhookDeliveryStatus::Failed);
+        assert_eq!(deliveries[0].attempts(), 2);
+
+        let attempts = store
+            .list_webhook_delivery_attempts(deliveries[0].id())
+            .expect("Failed to list delivery attempts");
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[1].response_code(), Some(500));
+        assert_eq!(
+            attempts[1].error(),
+            Some("Subscriber responded with HTTP 500")
+        );
+    }
+
+    /// Verify that a failing subscriber does not hold up another subscriber, and that its
+    /// remaining notifications wait for the next poll once one of them fails.
+    #[test]
+    fn test_failing_subscriber_does_not_block_others() {
+        let store_factory = create_store_factory();
+        let store = store_factory.get_batch_tracking_store();
+        let batch = add_tracking_batch(&*store_factory);
+        let batch_id = batch.batch_header().to_string();
+        let signer_public_key = batch.signer_public_key().to_string();
+
+        let event_types = [BatchStatusName::Pending, BatchStatusName::Committed];
+        let failing = store
+            .add_webhook_subscription(
+                &signer_public_key,
+                &format!("{}/unavailable", mockito::server_url()),
+                "secret",
+                &event_types,
+            )
+            .expect("Failed to add subscription");
+        let available = store
+            .add_webhook_subscription(
+                &signer_public_key,
+                &format!("{}/available", mockito::server_url()),
+                "secret",
+                &event_types,
+            )
+            .expect("Failed to add subscription");
+
+        for status in &[BatchStatus::Pending, BatchStatus::Committed(vec![])] {
+            store
+                .update_batch_status(
+                    &batch_id,
+                    NON_SPLINTER_SERVICE_ID_DEFAULT,
+                    Some(status.clone()),
+                    vec![],
+                    None,
+                )
+                .expect("Failed to update batch status");
+        }
+
+        let unavailable_mock = mock("POST", "/unavailable")
+            .with_status(503)
+            .expect(1)
+            .create();
+        let available_mock = mock("POST", "/available")
+            .with_status(200)
+            .expect(2)
+            .create();
+
+        deliver_pending(&*store, &Client::new(), &settings(3), now());
+        unavailable_mock.assert();
+        available_mock.assert();
+
+        let deliveries = store
+            .list_webhook_deliveries(available.id(), &signer_public_key)
+            .expect("Failed to list deliveries");
+        assert_eq!(deliveries.len(), 2);
+        assert!(deliveries
+            .iter()
+            .all(|delivery| delivery.status() == WebhookDeliveryStatus::Delivered));
+
+        let mut deliveries = store
+            .list_webhook_deliveries(failing.id(), &signer_public_key)
+            .expect("Failed to list deliveries");
+        deliveries.sort_by_key(|delivery| delivery.id());
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].status(), WebhookDeliveryStatus::Pending);
+        assert_eq!(deliveries[0].attempts(), 1);
+        assert_eq!(deliveries[1].status(), WebhookDeliveryStatus::Pending);
+        assert_eq!(deliveries[1].attempts(), 0);
+    }
+
+    fn settings(max_attempts: u32) -> DeliverySettings {
+        DeliverySettings {
+            batch_size: DEFAULT_BATCH_SIZE,
+            max_attempts,
+            initial_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::ClientError;
+use sawtooth_sdk::messages::batch::BatchList;
+
+/// The client representation of a batch that griddle could not submit
+#[derive(Debug, PartialEq, Eq)]
+pub struct DeadLetterBatch {
+    pub id: String,
+    pub data_change_id: Option<String>,
+    pub signer_public_key: String,
+    pub reason: String,
+    pub dead_lettered_at: i64,
+    pub submission_error: Option<String>,
+    /// The serialized `BatchList` containing the batch
+    pub serialized_batch: Vec<u8>,
+}
+
+/// The client representation of a replay or discard of a dead-lettered batch
+#[derive(Debug, PartialEq, Eq)]
+pub struct DeadLetterAction {
+    pub batch_id: String,
+    pub action: String,
+    pub actor: String,
+    pub replacement_batch_id: Option<String>,
+}
+
+pub trait BatchClient {
+    /// Fetches the list of dead-lettered batches
+    ///
+    /// # Arguments
+    ///
+    /// * `service_id` - optional - the service ID to fetch the batches from
+    fn list_dead_letter_batches(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, ClientError>;
+
+    /// Queues a dead-lettered batch for submission again
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the ID or data change ID of the batch
+    /// * `service_id` - optional - the service ID the batch was submitted to
+    /// * `replacement` - optional - a `BatchList` holding a re-signed copy of the batch to submit
+    ///   in its place
+    fn replay_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: Option<&str>,
+        replacement: Option<&BatchList>,
+    ) -> Result<DeadLetterAction, ClientError>;
+
+    /// Removes a dead-lettered batch without submitting it
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the ID or data change ID of the batch
+    /// * `service_id` - optional - the service ID the batch was submitted to
+    fn discard_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: Option<&str>,
+    ) -> Result<DeadLetterAction, ClientError>;
+}
@@ -14,6 +14,10 @@
 
 //! Traits and implementations useful for interacting with the REST API.
 
+#[cfg(feature = "client-batch")]
+pub mod batch;
+#[cfg(feature = "client-batch")]
+pub use batch::*;
 #[cfg(feature = "location")]
 pub mod location;
 #[cfg(feature = "location")]
@@ -36,10 +40,22 @@ pub mod reqwest;
 pub mod schema;
 #[cfg(feature = "schema")]
 pub use schema::*;
+#[cfg(feature = "track-and-trace")]
+pub mod track_and_trace;
+#[cfg(feature = "track-and-trace")]
+pub use track_and_trace::*;
 
 use crate::error::ClientError;
 use sawtooth_sdk::messages::batch::BatchList;
 
+/// The client representation of the status of a submitted batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchStatus {
+    pub id: String,
+    pub status: String,
+    pub invalid_transactions: Vec<String>,
+}
+
 pub trait Client {
     /// Submits a list of batches
     ///
@@ -57,6 +73,13 @@ pub trait Client {
 }
 
 pub trait ClientFactory {
+    /// Retrieves a client for managing dead-lettered batches
+    ///
+    /// The authorization is sent with every request; griddle requires a Cylinder JWT to identify
+    /// the user replaying or discarding a batch.
+    #[cfg(feature = "client-batch")]
+    fn get_batch_client(&self, url: String, authorization: String) -> Box<dyn batch::BatchClient>;
+
     /// Retrieves a client for listing and showing locations
     #[cfg(feature = "location")]
     fn get_location_client(&self, url: String) -> Box<dyn location::LocationClient>;
@@ -80,4 +103,11 @@ pub trait ClientFactory {
     /// Retrieves a client for listing and showing schemas
     #[cfg(feature = "schema")]
     fn get_schema_client(&self, url: String) -> Box<dyn schema::SchemaClient>;
+
+    /// Retrieves a client for listing and showing track and trace records
+    #[cfg(feature = "track-and-trace")]
+    fn get_track_and_trace_client(
+        &self,
+        url: String,
+    ) -> Box<dyn track_and_trace::TrackAndTraceClient>;
 }
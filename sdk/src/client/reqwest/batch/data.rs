@@ -0,0 +1,84 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the data types for the reqwest-backed client
+//! implementation. These must be able to be converted into their
+//! corresponding structs in the corresponding client module.
+
+use crate::client::batch::{
+    DeadLetterAction as ClientDeadLetterAction, DeadLetterBatch as ClientDeadLetterBatch,
+};
+use crate::error::ClientError;
+use crate::hex::parse_hex;
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterBatchList {
+    pub data: Vec<DeadLetterBatch>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterBatch {
+    pub id: String,
+    pub data_change_id: Option<String>,
+    pub signer_public_key: String,
+    pub reason: String,
+    pub dead_lettered_at: i64,
+    pub submission_error: Option<SubmissionError>,
+    pub serialized_batch: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmissionError {
+    pub error_type: String,
+    pub error_message: String,
+}
+
+impl std::convert::TryFrom<DeadLetterBatch> for ClientDeadLetterBatch {
+    type Error = ClientError;
+
+    fn try_from(d: DeadLetterBatch) -> Result<Self, Self::Error> {
+        Ok(Self {
+            serialized_batch: parse_hex(&d.serialized_batch).map_err(|err| {
+                ClientError::InternalError(format!("Invalid serialized batch: {}", err))
+            })?,
+            id: d.id,
+            data_change_id: d.data_change_id,
+            signer_public_key: d.signer_public_key,
+            reason: d.reason,
+            dead_lettered_at: d.dead_lettered_at,
+            submission_error: d
+                .submission_error
+                .map(|err| format!("{}: {}", err.error_type, err.error_message)),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterAction {
+    pub batch_id: String,
+    pub action: String,
+    pub actor: String,
+    pub replacement_batch_id: Option<String>,
+}
+
+impl From<DeadLetterAction> for ClientDeadLetterAction {
+    fn from(d: DeadLetterAction) -> Self {
+        Self {
+            batch_id: d.batch_id,
+            action: d.action,
+            actor: d.actor,
+            replacement_batch_id: d.replacement_batch_id,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub(crate) mod data;
+
+use std::convert::TryFrom;
+
+use protobuf::Message;
+use reqwest::blocking::{Client as BlockingClient, RequestBuilder, Response};
+use sawtooth_sdk::messages::batch::BatchList;
+
+use crate::client::batch::{BatchClient, DeadLetterAction, DeadLetterBatch};
+use crate::error::ClientError;
+
+const DEAD_LETTER_ROUTE: &str = "batches/dead-letter";
+
+/// The Reqwest implementation of the Batch client
+pub struct ReqwestBatchClient {
+    url: String,
+    authorization: String,
+}
+
+impl ReqwestBatchClient {
+    pub fn new(url: String, authorization: String) -> Self {
+        Self { url, authorization }
+    }
+
+    fn request(&self, request: RequestBuilder, service_id: Option<&str>) -> RequestBuilder {
+        let query_params: Vec<(&str, &str)> = service_id
+            .into_iter()
+            .map(|sid| ("service_id", sid))
+            .collect();
+
+        request
+            .header("Authorization", &self.authorization)
+            .query(&query_params)
+    }
+}
+
+impl BatchClient for ReqwestBatchClient {
+    /// Fetches the list of dead-lettered batches
+    ///
+    /// # Arguments
+    ///
+    /// * `service_id` - optional - the service ID to fetch the batches from
+    fn list_dead_letter_batches(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Vec<DeadLetterBatch>, ClientError> {
+        let response = self
+            .request(
+                BlockingClient::new().get(&format!("{}/{}", self.url, DEAD_LETTER_ROUTE)),
+                service_id,
+            )
+            .send()?;
+
+        check_response(response)?
+            .json::<data::DeadLetterBatchList>()?
+            .data
+            .into_iter()
+            .map(DeadLetterBatch::try_from)
+            .collect()
+    }
+
+    /// Queues a dead-lettered batch for submission again
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the ID or data change ID of the batch
+    /// * `service_id` - optional - the service ID the batch was submitted to
+    /// * `replacement` - optional - a `BatchList` holding a re-signed copy of the batch
+    fn replay_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: Option<&str>,
+        replacement: Option<&BatchList>,
+    ) -> Result<DeadLetterAction, ClientError> {
+        let mut request = self.request(
+            BlockingClient::new()
+                .post(&format!("{}/{}/{}/replay", self.url, DEAD_LETTER_ROUTE, id)),
+            service_id,
+        );
+
+        if let Some(replacement) = replacement {
+            let bytes = replacement.write_to_bytes().map_err(|_err| {
+                ClientError::InternalError("Failed to convert batch list to bytes".to_string())
+            })?;
+            request = request
+                .header("Content-Type", "application/octet-stream")
+                .body(bytes);
+        }
+
+        Ok(DeadLetterAction::from(
+            check_response(request.send()?)?.json::<data::DeadLetterAction>()?,
+        ))
+    }
+
+    /// Removes a dead-lettered batch without submitting it
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the ID or data change ID of the batch
+    /// * `service_id` - optional - the service ID the batch was submitted to
+    fn discard_dead_letter_batch(
+        &self,
+        id: &str,
+        service_id: Option<&str>,
+    ) -> Result<DeadLetterAction, ClientError> {
+        let request = self.request(
+            BlockingClient::new().post(&format!(
+                "{}/{}/{}/discard",
+                self.url, DEAD_LETTER_ROUTE, id
+            )),
+            service_id,
+        );
+
+        Ok(DeadLetterAction::from(
+            check_response(request.send()?)?.json::<data::DeadLetterAction>()?,
+        ))
+    }
+}
+
+fn check_response(response: Response) -> Result<Response, ClientError> {
+    if !response.status().is_success() {
+        return Err(ClientError::InternalError(response.text()?));
+    }
+
+    Ok(response)
+}
@@ -23,6 +23,10 @@ use serde::Deserialize;
 use std::collections::HashMap;
 use std::time::Instant;
 
+#[cfg(feature = "client-batch")]
+mod batch;
+#[cfg(feature = "client-batch")]
+pub use batch::*;
 #[cfg(feature = "location")]
 mod location;
 #[cfg(feature = "location")]
@@ -41,6 +45,10 @@ mod purchase_order;
 pub use purchase_order::*;
 #[cfg(feature = "schema")]
 mod schema;
+#[cfg(feature = "track-and-trace")]
+mod track_and_trace;
+#[cfg(feature = "client-batch")]
+use super::batch as client_batch;
 #[cfg(feature = "location")]
 use super::location as client_location;
 #[cfg(feature = "pike")]
@@ -51,9 +59,13 @@ use super::product as client_product;
 use super::purchase_order as client_purchase_order;
 #[cfg(feature = "schema")]
 use super::schema as client_schema;
-use super::ClientFactory;
+#[cfg(feature = "track-and-trace")]
+use super::track_and_trace as client_track_and_trace;
+use super::{BatchStatus as ClientBatchStatus, ClientFactory};
 #[cfg(feature = "schema")]
 pub use schema::*;
+#[cfg(feature = "track-and-trace")]
+pub use track_and_trace::*;
 
 /// This is the abstraction of the `ClientFactory` struct for the
 /// reqwest-backed implementation. This provides methods to return the reqwest
@@ -73,6 +85,16 @@ impl Default for ReqwestClientFactory {
 }
 
 impl ClientFactory for ReqwestClientFactory {
+    /// Retrieves a client for managing dead-lettered batches
+    #[cfg(feature = "client-batch")]
+    fn get_batch_client(
+        &self,
+        url: String,
+        authorization: String,
+    ) -> Box<dyn client_batch::BatchClient> {
+        Box::new(ReqwestBatchClient::new(url, authorization))
+    }
+
     /// Retrieves a client for listing and showing locations
     #[cfg(feature = "location")]
     fn get_location_client(&self, url: String) -> Box<dyn client_location::LocationClient> {
@@ -106,6 +128,15 @@ impl ClientFactory for ReqwestClientFactory {
     fn get_schema_client(&self, url: String) -> Box<dyn client_schema::SchemaClient> {
         Box::new(ReqwestSchemaClient::new(url))
     }
+
+    /// Retrieves a client for listing and showing track and trace records
+    #[cfg(feature = "track-and-trace")]
+    fn get_track_and_trace_client(
+        &self,
+        url: String,
+    ) -> Box<dyn client_track_and_trace::TrackAndTraceClient> {
+        Box::new(ReqwestTrackAndTraceClient::new(url))
+    }
 }
 
 /// Reqwest client representation of response paging
@@ -212,6 +243,8 @@ struct BatchStatusResponse {
 
 #[derive(Deserialize, Debug)]
 struct BatchStatus {
+    #[serde(default)]
+    pub id: String,
     pub invalid_transactions: Vec<HashMap<String, String>>,
     pub status: String,
 }
@@ -311,6 +344,40 @@ pub fn fetch_entity<T: DeserializeOwned>(
     Ok(agent)
 }
 
+/// Fetches and deserializes a single `T` entity from REST API, returning `None` if the entity
+/// does not exist
+///
+/// # Arguments
+///
+/// * `url` - the base url of the request
+/// * `route` - the identifying route where to find the entity
+/// * `service_id` - optional - the service ID to fetch the entity from
+pub fn fetch_entity_if_exists<T: DeserializeOwned>(
+    url: &str,
+    route: String,
+    service_id: Option<&str>,
+) -> Result<Option<T>, ClientError> {
+    let client = BlockingClient::new();
+    let final_url = format!("{}/{}", url, route);
+
+    let query_params: Vec<(&str, String)> = service_id
+        .into_iter()
+        .map(|sid| ("service_id", sid.to_string()))
+        .collect();
+
+    let response = client.get(&final_url).query(&query_params).send()?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    if !response.status().is_success() {
+        return Err(ClientError::InternalError(response.text()?));
+    }
+
+    Ok(Some(response.json::<T>()?))
+}
+
 /// Submits a list of batches
 ///
 /// # Arguments
@@ -326,6 +393,39 @@ pub fn post_batches(
     batch_list: &BatchList,
     service_id: Option<&str>,
 ) -> Result<(), ClientError> {
+    let batch_statuses = submit_batches(url, wait, batch_list, service_id)?;
+
+    for batch_status in &batch_statuses {
+        if batch_status.status == "Invalid" {
+            for message in &batch_status.invalid_transactions {
+                error!("Error: {}", message);
+            }
+        }
+    }
+
+    if batch_statuses.iter().all(|d| d.status == "Valid") {
+        info!("Batch and transaction structure was valid. Batch queued.");
+    }
+
+    Ok(())
+}
+
+/// Submits a list of batches, returning the status of each batch once they are no longer
+/// pending or the wait time has elapsed
+///
+/// # Arguments
+///
+/// * `url` - the base url of the request
+/// * `wait` - duration to wait for batch status response
+/// * `batch_list` - the list of batches to submit
+/// * `service_id` - optional - the service ID to submit batches to if running
+///   on splinter
+pub fn submit_batches(
+    url: &str,
+    wait: u64,
+    batch_list: &BatchList,
+    service_id: Option<&str>,
+) -> Result<Vec<ClientBatchStatus>, ClientError> {
     let bytes = batch_list.write_to_bytes().map_err(|_err| {
         ClientError::InternalError("Failed to convert batch list to bytes".to_string())
     })?;
@@ -359,14 +459,30 @@ pub fn post_batches(
         ClientError::InternalError("Unable to get batch status link from response".to_string())
     })?;
 
-    let params: Vec<&str> = batch_link.link.split('?').collect();
-
-    let id_param: Vec<&str> = params[1].split('=').collect();
-
-    let id = id_param[1];
+    let id = batch_link
+        .link
+        .split('?')
+        .nth(1)
+        .and_then(|params| {
+            params
+                .split('&')
+                .find_map(|param| param.strip_prefix("id="))
+        })
+        .ok_or_else(|| {
+            ClientError::InternalError("Unable to get batch IDs from status link".to_string())
+        })?;
 
     info!("Submitted batch: {}", id);
 
+    let mut batch_statuses = id
+        .split(',')
+        .map(|id| ClientBatchStatus {
+            id: id.to_string(),
+            status: "PENDING".to_string(),
+            invalid_transactions: vec![],
+        })
+        .collect::<Vec<_>>();
+
     while wait_time > 0 {
         let time = Instant::now();
 
@@ -398,28 +514,30 @@ pub fn post_batches(
             ClientError::InternalError("Unable to get batch status response".to_string())
         })?;
 
-        for t in &batch_status.data {
-            if t.status == "Invalid" {
-                for i in &t.invalid_transactions {
-                    error!(
-                        "Error: {}",
+        batch_statuses = batch_status
+            .data
+            .into_iter()
+            .map(|status| ClientBatchStatus {
+                id: status.id,
+                invalid_transactions: status
+                    .invalid_transactions
+                    .iter()
+                    .map(|i| {
                         i.get("message")
-                            .unwrap_or(&"Batch contained invalid transactions".to_string())
-                    );
-                }
-            }
-        }
-
-        if batch_status.data.iter().all(|d| d.status == "Valid") {
-            info!("Batch and transaction structure was valid. Batch queued.");
-        }
-
-        if batch_status.data.iter().all(|x| x.status != "PENDING") {
+                            .cloned()
+                            .unwrap_or_else(|| "Batch contained invalid transactions".to_string())
+                    })
+                    .collect(),
+                status: status.status,
+            })
+            .collect();
+
+        if batch_statuses.iter().all(|x| x.status != "PENDING") {
             break;
         }
 
-        wait_time -= time.elapsed().as_secs()
+        wait_time = wait_time.saturating_sub(time.elapsed().as_secs())
     }
 
-    Ok(())
+    Ok(batch_statuses)
 }
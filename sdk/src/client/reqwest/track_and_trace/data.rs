@@ -0,0 +1,55 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the data types for the reqwest-backed client
+//! implementation. These must be able to be converted into their
+//! corresponding structs in the corresponding client module.
+
+use crate::client::track_and_trace::Record as ClientRecord;
+
+#[derive(Debug, Deserialize)]
+pub struct Record {
+    pub record_id: String,
+    pub schema: String,
+    pub owner: String,
+    pub custodian: String,
+    pub r#final: bool,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location_id: Option<String>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_id: Option<String>,
+}
+
+impl From<&Record> for ClientRecord {
+    fn from(d: &Record) -> Self {
+        Self {
+            record_id: d.record_id.to_string(),
+            schema: d.schema.to_string(),
+            owner: d.owner.to_string(),
+            custodian: d.custodian.to_string(),
+            r#final: d.r#final,
+            product_id: d.product_id.as_ref().map(String::from),
+            location_id: d.location_id.as_ref().map(String::from),
+            service_id: d.service_id.as_ref().map(String::from),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod data;
+
+use crate::client::reqwest::{
+    fetch_entities_list, fetch_entity_if_exists, post_batches, submit_batches,
+};
+use crate::client::track_and_trace::{Record, TrackAndTraceClient};
+use crate::client::{BatchStatus, Client};
+use crate::error::ClientError;
+
+use sawtooth_sdk::messages::batch::BatchList;
+
+const RECORD_ROUTE: &str = "record";
+
+/// The Reqwest implementation of the Track and Trace client
+pub struct ReqwestTrackAndTraceClient {
+    url: String,
+}
+
+impl ReqwestTrackAndTraceClient {
+    pub fn new(url: String) -> Self {
+        Self { url }
+    }
+}
+
+impl Client for ReqwestTrackAndTraceClient {
+    /// Submits a list of batches
+    ///
+    /// # Arguments
+    ///
+    /// * `wait` - wait time in seconds
+    /// * `batch_list` - The `BatchList` to be submitted
+    /// * `service_id` - optional - the service ID to post batches to if running splinter
+    fn post_batches(
+        &self,
+        wait: u64,
+        batch_list: &BatchList,
+        service_id: Option<&str>,
+    ) -> Result<(), ClientError> {
+        post_batches(&self.url, wait, batch_list, service_id)
+    }
+}
+
+impl TrackAndTraceClient for ReqwestTrackAndTraceClient {
+    /// Fetches a record based on its identifier
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the record's identifier
+    /// * `service_id` - optional - the service ID to fetch the record from
+    fn get_record(
+        &self,
+        id: String,
+        service_id: Option<&str>,
+    ) -> Result<Option<Record>, ClientError> {
+        let dto = fetch_entity_if_exists::<data::Record>(
+            &self.url,
+            format!("{}/{}", RECORD_ROUTE, id),
+            service_id,
+        )?;
+        Ok(dto.as_ref().map(Record::from))
+    }
+
+    /// Fetches records
+    ///
+    /// # Arguments
+    ///
+    /// * `service_id` - optional - the service ID to fetch records from
+    fn list_records(&self, service_id: Option<&str>) -> Result<Vec<Record>, ClientError> {
+        let dto_vec = fetch_entities_list::<data::Record>(
+            &self.url,
+            RECORD_ROUTE.to_string(),
+            service_id,
+            None,
+        )?;
+        Ok(dto_vec.iter().map(Record::from).collect())
+    }
+
+    /// Submits a list of batches, returning the status of each submitted batch
+    ///
+    /// # Arguments
+    ///
+    /// * `wait` - wait time in seconds
+    /// * `batch_list` - The `BatchList` to be submitted
+    /// * `service_id` - optional - the service ID to post batches to if running splinter
+    fn submit_batches(
+        &self,
+        wait: u64,
+        batch_list: &BatchList,
+        service_id: Option<&str>,
+    ) -> Result<Vec<BatchStatus>, ClientError> {
+        submit_batches(&self.url, wait, batch_list, service_id)
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::ClientError;
+
+use sawtooth_sdk::messages::batch::BatchList;
+
+use super::{BatchStatus, Client};
+
+/// The client representation of a Grid Track and Trace record
+#[derive(Debug, PartialEq, Eq)]
+pub struct Record {
+    pub record_id: String,
+    pub schema: String,
+    pub owner: String,
+    pub custodian: String,
+    pub r#final: bool,
+    pub product_id: Option<String>,
+    pub location_id: Option<String>,
+    pub service_id: Option<String>,
+}
+
+pub trait TrackAndTraceClient: Client {
+    /// Fetches a record based on its identifier, returning `None` if the record does not exist
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the record's identifier
+    /// * `service_id` - optional - the service ID to fetch the record from
+    fn get_record(
+        &self,
+        id: String,
+        service_id: Option<&str>,
+    ) -> Result<Option<Record>, ClientError>;
+
+    /// Fetches records
+    ///
+    /// # Arguments
+    ///
+    /// * `service_id` - optional - the service ID to fetch records from
+    fn list_records(&self, service_id: Option<&str>) -> Result<Vec<Record>, ClientError>;
+
+    /// Submits a list of batches, returning the status of each submitted batch
+    ///
+    /// # Arguments
+    ///
+    /// * `wait` - wait time in seconds
+    /// * `batch_list` - the `BatchList` to be submitted
+    /// * `service_id` - optional - the service ID to post batches to if running splinter
+    fn submit_batches(
+        &self,
+        wait: u64,
+        batch_list: &BatchList,
+        service_id: Option<&str>,
+    ) -> Result<Vec<BatchStatus>, ClientError>;
+}
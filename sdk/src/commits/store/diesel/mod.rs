@@ -21,16 +21,27 @@ pub(crate) mod schema;
 use diesel::connection::AnsiTransactionManager;
 use diesel::r2d2::{ConnectionManager, Pool};
 
-use super::{Commit, CommitEvent, CommitStore, CommitStoreError};
+use super::{
+    Commit, CommitEvent, CommitStore, CommitStoreError, EventHandlerCheckpoint, IndexedNamespace,
+    ServiceLifecycle,
+};
 use crate::commits::store::diesel::models::{CommitModel, NewCommitModel};
 
 use operations::add_commit::CommitStoreAddCommitOperation as _;
 use operations::create_db_commit_from_commit_event::CommitStoreCreateDbCommitFromCommitEventOperation as _;
 use operations::get_commit_by_commit_num::CommitStoreGetCommitByCommitNumOperation as _;
+use operations::get_current_commit::CommitStoreGetCurrentCommitOperation as _;
 use operations::get_current_commit_id::CommitStoreGetCurrentCommitIdOperation as _;
 use operations::get_current_service_commits::CommitStoreGetCurrentServiceCommitsOperation as _;
+use operations::get_event_handler_checkpoints::CommitStoreGetEventHandlerCheckpointsOperation as _;
 use operations::get_next_commit_num::CommitStoreGetNextCommitNumOperation as _;
+use operations::list_service_lifecycles::CommitStoreListServiceLifecyclesOperation as _;
+use operations::purge_service::CommitStorePurgeServiceOperation as _;
+use operations::reindex::CommitStoreReindexOperation as _;
 use operations::resolve_fork::CommitStoreResolveForkOperation as _;
+use operations::take_pending_reindex::CommitStoreTakePendingReindexOperation as _;
+use operations::update_event_handler_checkpoint::CommitStoreUpdateEventHandlerCheckpointOperation as _;
+use operations::update_service_lifecycle::CommitStoreUpdateServiceLifecycleOperation as _;
 use operations::CommitStoreOperations;
 
 /// Manages creating commits in the database
@@ -56,16 +67,80 @@ impl CommitStore for DieselCommitStore<diesel::pg::PgConnection> {
         CommitStoreOperations::new(&*self.connection_pool.get()?).add_commit(commit.into())
     }
 
-    fn resolve_fork(&self, commit_num: i64) -> Result<(), CommitStoreError> {
-        CommitStoreOperations::new(&*self.connection_pool.get()?).resolve_fork(commit_num)
+    fn resolve_fork(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?)
+            .resolve_fork(commit_num, service_id)
+    }
+
+    fn reindex(
+        &self,
+        commit_num: i64,
+        namespaces: &[IndexedNamespace],
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?).reindex(commit_num, namespaces)
+    }
+
+    fn take_pending_reindex(
+        &self,
+        commit_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Vec<IndexedNamespace>>, CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?)
+            .take_pending_reindex(commit_id, service_id)
+    }
+
+    fn get_event_handler_checkpoints(
+        &self,
+        handler_id: &str,
+    ) -> Result<Vec<EventHandlerCheckpoint>, CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?)
+            .get_event_handler_checkpoints(handler_id)
+    }
+
+    fn update_event_handler_checkpoint(
+        &self,
+        handler_id: &str,
+        service_id: Option<&str>,
+        commit_id: &str,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?)
+            .update_event_handler_checkpoint(handler_id, service_id, commit_id)
+    }
+
+    fn list_service_lifecycles(&self) -> Result<Vec<ServiceLifecycle>, CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?).list_service_lifecycles()
+    }
+
+    fn update_service_lifecycle(
+        &self,
+        lifecycle: ServiceLifecycle,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?)
+            .update_service_lifecycle(lifecycle)
+    }
+
+    fn purge_service(&self, service_id: &str) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?).purge_service(service_id)
     }
 
     fn get_commit_by_commit_num(
         &self,
         commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<Option<Commit>, CommitStoreError> {
         CommitStoreOperations::new(&*self.connection_pool.get()?)
-            .get_commit_by_commit_num(commit_num)
+            .get_commit_by_commit_num(commit_num, service_id)
+    }
+
+    fn get_current_commit(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?).get_current_commit(service_id)
     }
 
     fn get_current_commit_id(&self) -> Result<Option<String>, CommitStoreError> {
@@ -95,16 +170,80 @@ impl CommitStore for DieselCommitStore<diesel::sqlite::SqliteConnection> {
         CommitStoreOperations::new(&*self.connection_pool.get()?).add_commit(commit.into())
     }
 
-    fn resolve_fork(&self, commit_num: i64) -> Result<(), CommitStoreError> {
-        CommitStoreOperations::new(&*self.connection_pool.get()?).resolve_fork(commit_num)
+    fn resolve_fork(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?)
+            .resolve_fork(commit_num, service_id)
+    }
+
+    fn reindex(
+        &self,
+        commit_num: i64,
+        namespaces: &[IndexedNamespace],
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?).reindex(commit_num, namespaces)
+    }
+
+    fn take_pending_reindex(
+        &self,
+        commit_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Vec<IndexedNamespace>>, CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?)
+            .take_pending_reindex(commit_id, service_id)
+    }
+
+    fn get_event_handler_checkpoints(
+        &self,
+        handler_id: &str,
+    ) -> Result<Vec<EventHandlerCheckpoint>, CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?)
+            .get_event_handler_checkpoints(handler_id)
+    }
+
+    fn update_event_handler_checkpoint(
+        &self,
+        handler_id: &str,
+        service_id: Option<&str>,
+        commit_id: &str,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?)
+            .update_event_handler_checkpoint(handler_id, service_id, commit_id)
+    }
+
+    fn list_service_lifecycles(&self) -> Result<Vec<ServiceLifecycle>, CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?).list_service_lifecycles()
+    }
+
+    fn update_service_lifecycle(
+        &self,
+        lifecycle: ServiceLifecycle,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?)
+            .update_service_lifecycle(lifecycle)
+    }
+
+    fn purge_service(&self, service_id: &str) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?).purge_service(service_id)
     }
 
     fn get_commit_by_commit_num(
         &self,
         commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<Option<Commit>, CommitStoreError> {
         CommitStoreOperations::new(&*self.connection_pool.get()?)
-            .get_commit_by_commit_num(commit_num)
+            .get_commit_by_commit_num(commit_num, service_id)
+    }
+
+    fn get_current_commit(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError> {
+        CommitStoreOperations::new(&*self.connection_pool.get()?).get_current_commit(service_id)
     }
 
     fn get_current_commit_id(&self) -> Result<Option<String>, CommitStoreError> {
@@ -152,15 +291,75 @@ impl<'a> CommitStore for DieselConnectionCommitStore<'a, diesel::pg::PgConnectio
         CommitStoreOperations::new(self.connection).add_commit(commit.into())
     }
 
-    fn resolve_fork(&self, commit_num: i64) -> Result<(), CommitStoreError> {
-        CommitStoreOperations::new(self.connection).resolve_fork(commit_num)
+    fn resolve_fork(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(self.connection).resolve_fork(commit_num, service_id)
+    }
+
+    fn reindex(
+        &self,
+        commit_num: i64,
+        namespaces: &[IndexedNamespace],
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(self.connection).reindex(commit_num, namespaces)
+    }
+
+    fn take_pending_reindex(
+        &self,
+        commit_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Vec<IndexedNamespace>>, CommitStoreError> {
+        CommitStoreOperations::new(self.connection).take_pending_reindex(commit_id, service_id)
+    }
+
+    fn get_event_handler_checkpoints(
+        &self,
+        handler_id: &str,
+    ) -> Result<Vec<EventHandlerCheckpoint>, CommitStoreError> {
+        CommitStoreOperations::new(self.connection).get_event_handler_checkpoints(handler_id)
+    }
+
+    fn update_event_handler_checkpoint(
+        &self,
+        handler_id: &str,
+        service_id: Option<&str>,
+        commit_id: &str,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(self.connection)
+            .update_event_handler_checkpoint(handler_id, service_id, commit_id)
+    }
+
+    fn list_service_lifecycles(&self) -> Result<Vec<ServiceLifecycle>, CommitStoreError> {
+        CommitStoreOperations::new(self.connection).list_service_lifecycles()
+    }
+
+    fn update_service_lifecycle(
+        &self,
+        lifecycle: ServiceLifecycle,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(self.connection).update_service_lifecycle(lifecycle)
+    }
+
+    fn purge_service(&self, service_id: &str) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(self.connection).purge_service(service_id)
     }
 
     fn get_commit_by_commit_num(
         &self,
         commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<Option<Commit>, CommitStoreError> {
-        CommitStoreOperations::new(self.connection).get_commit_by_commit_num(commit_num)
+        CommitStoreOperations::new(self.connection).get_commit_by_commit_num(commit_num, service_id)
+    }
+
+    fn get_current_commit(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError> {
+        CommitStoreOperations::new(self.connection).get_current_commit(service_id)
     }
 
     fn get_current_commit_id(&self) -> Result<Option<String>, CommitStoreError> {
@@ -189,15 +388,75 @@ impl<'a> CommitStore for DieselConnectionCommitStore<'a, diesel::sqlite::SqliteC
         CommitStoreOperations::new(self.connection).add_commit(commit.into())
     }
 
-    fn resolve_fork(&self, commit_num: i64) -> Result<(), CommitStoreError> {
-        CommitStoreOperations::new(self.connection).resolve_fork(commit_num)
+    fn resolve_fork(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(self.connection).resolve_fork(commit_num, service_id)
+    }
+
+    fn reindex(
+        &self,
+        commit_num: i64,
+        namespaces: &[IndexedNamespace],
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(self.connection).reindex(commit_num, namespaces)
+    }
+
+    fn take_pending_reindex(
+        &self,
+        commit_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Vec<IndexedNamespace>>, CommitStoreError> {
+        CommitStoreOperations::new(self.connection).take_pending_reindex(commit_id, service_id)
+    }
+
+    fn get_event_handler_checkpoints(
+        &self,
+        handler_id: &str,
+    ) -> Result<Vec<EventHandlerCheckpoint>, CommitStoreError> {
+        CommitStoreOperations::new(self.connection).get_event_handler_checkpoints(handler_id)
+    }
+
+    fn update_event_handler_checkpoint(
+        &self,
+        handler_id: &str,
+        service_id: Option<&str>,
+        commit_id: &str,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(self.connection)
+            .update_event_handler_checkpoint(handler_id, service_id, commit_id)
+    }
+
+    fn list_service_lifecycles(&self) -> Result<Vec<ServiceLifecycle>, CommitStoreError> {
+        CommitStoreOperations::new(self.connection).list_service_lifecycles()
+    }
+
+    fn update_service_lifecycle(
+        &self,
+        lifecycle: ServiceLifecycle,
+    ) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(self.connection).update_service_lifecycle(lifecycle)
+    }
+
+    fn purge_service(&self, service_id: &str) -> Result<(), CommitStoreError> {
+        CommitStoreOperations::new(self.connection).purge_service(service_id)
     }
 
     fn get_commit_by_commit_num(
         &self,
         commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError> {
+        CommitStoreOperations::new(self.connection).get_commit_by_commit_num(commit_num, service_id)
+    }
+
+    fn get_current_commit(
+        &self,
+        service_id: Option<&str>,
     ) -> Result<Option<Commit>, CommitStoreError> {
-        CommitStoreOperations::new(self.connection).get_commit_by_commit_num(commit_num)
+        CommitStoreOperations::new(self.connection).get_current_commit(service_id)
     }
 
     fn get_current_commit_id(&self) -> Result<Option<String>, CommitStoreError> {
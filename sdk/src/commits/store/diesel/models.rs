@@ -12,7 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::TryFrom;
+
 use crate::commits::store::diesel::schema::*;
+use crate::commits::store::{CommitStoreError, ServiceLifecycle};
+use crate::error::InternalError;
 
 #[derive(Insertable, PartialEq, Eq, Queryable)]
 #[table_name = "commits"]
@@ -30,3 +34,53 @@ pub struct CommitModel {
     pub commit_num: i64,
     pub service_id: Option<String>,
 }
+
+#[derive(Insertable, PartialEq, Eq, Debug)]
+#[table_name = "pending_reindex"]
+pub struct NewPendingReindexModel {
+    pub commit_id: String,
+    pub commit_num: i64,
+    pub service_id: Option<String>,
+    pub namespace: String,
+}
+
+#[derive(Insertable, PartialEq, Eq, Debug)]
+#[table_name = "event_handler_checkpoint"]
+pub struct NewEventHandlerCheckpointModel {
+    pub handler_id: String,
+    pub service_id: Option<String>,
+    pub commit_id: String,
+}
+
+#[derive(Insertable, PartialEq, Eq, Debug)]
+#[table_name = "service_lifecycle"]
+pub struct NewServiceLifecycleModel {
+    pub service_id: String,
+    pub status: String,
+    pub data_status: String,
+}
+
+#[derive(Queryable, PartialEq, Eq, Identifiable, Debug)]
+#[table_name = "service_lifecycle"]
+pub struct ServiceLifecycleModel {
+    pub id: i64,
+    pub service_id: String,
+    pub status: String,
+    pub data_status: String,
+}
+
+impl TryFrom<ServiceLifecycleModel> for ServiceLifecycle {
+    type Error = CommitStoreError;
+
+    fn try_from(model: ServiceLifecycleModel) -> Result<Self, Self::Error> {
+        Ok(ServiceLifecycle {
+            status: model.status.parse().map_err(|err| {
+                CommitStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })?,
+            data_status: model.data_status.parse().map_err(|err| {
+                CommitStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })?,
+            service_id: model.service_id,
+        })
+    }
+}
@@ -21,8 +21,11 @@ use crate::error::InternalError;
 use diesel::prelude::*;
 
 pub(in crate::commits) trait CommitStoreGetCommitByCommitNumOperation {
-    fn get_commit_by_commit_num(&self, commit_num: i64)
-        -> Result<Option<Commit>, CommitStoreError>;
+    fn get_commit_by_commit_num(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError>;
 }
 
 #[cfg(feature = "postgres")]
@@ -32,11 +35,21 @@ impl<'a> CommitStoreGetCommitByCommitNumOperation
     fn get_commit_by_commit_num(
         &self,
         commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<Option<Commit>, CommitStoreError> {
         self.conn.transaction::<_, CommitStoreError, _>(|| {
-            commits::table
+            let mut query = commits::table
+                .into_boxed()
                 .select(commits::all_columns)
-                .filter(commits::commit_num.eq(&commit_num))
+                .filter(commits::commit_num.eq(&commit_num));
+
+            if let Some(service_id) = service_id {
+                query = query.filter(commits::service_id.eq(service_id));
+            } else {
+                query = query.filter(commits::service_id.is_null());
+            }
+
+            query
                 .first::<CommitModel>(self.conn)
                 .map(Commit::from)
                 .optional()
@@ -54,11 +67,21 @@ impl<'a> CommitStoreGetCommitByCommitNumOperation
     fn get_commit_by_commit_num(
         &self,
         commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<Option<Commit>, CommitStoreError> {
         self.conn.transaction::<_, CommitStoreError, _>(|| {
-            commits::table
+            let mut query = commits::table
+                .into_boxed()
                 .select(commits::all_columns)
-                .filter(commits::commit_num.eq(&commit_num))
+                .filter(commits::commit_num.eq(&commit_num));
+
+            if let Some(service_id) = service_id {
+                query = query.filter(commits::service_id.eq(service_id));
+            } else {
+                query = query.filter(commits::service_id.is_null());
+            }
+
+            query
                 .first::<CommitModel>(self.conn)
                 .map(Commit::from)
                 .optional()
@@ -68,3 +91,55 @@ impl<'a> CommitStoreGetCommitByCommitNumOperation
         })
     }
 }
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use diesel::insert_into;
+
+    use crate::commits::store::diesel::models::NewCommitModel;
+    use crate::migrations::run_sqlite_migrations;
+
+    /// Verify that a commit is only returned for the service it belongs to, as commits from
+    /// different sources may share a commit number.
+    #[test]
+    fn test_get_commit_by_commit_num_for_service() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        insert_into(commits::table)
+            .values(vec![
+                NewCommitModel {
+                    commit_id: "sawtooth".into(),
+                    commit_num: 1,
+                    service_id: None,
+                },
+                NewCommitModel {
+                    commit_id: "splinter".into(),
+                    commit_num: 1,
+                    service_id: Some("circuit::svc".into()),
+                },
+            ])
+            .execute(&conn)?;
+
+        let ops = CommitStoreOperations::new(&conn);
+
+        assert_eq!(
+            ops.get_commit_by_commit_num(1, None)?
+                .map(|commit| commit.commit_id),
+            Some("sawtooth".to_string())
+        );
+        assert_eq!(
+            ops.get_commit_by_commit_num(1, Some("circuit::svc"))?
+                .map(|commit| commit.commit_id),
+            Some("splinter".to_string())
+        );
+        assert!(ops
+            .get_commit_by_commit_num(1, Some("other::svc"))?
+            .is_none());
+
+        Ok(())
+    }
+}
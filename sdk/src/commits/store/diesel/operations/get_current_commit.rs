@@ -0,0 +1,152 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::CommitStoreOperations;
+use crate::commits::store::diesel::{
+    models::CommitModel, schema::commits, Commit, CommitStoreError,
+};
+use crate::error::InternalError;
+
+use diesel::prelude::*;
+
+/// This database operation takes one argument: `service_id`, the service whose current commit is
+/// returned, or `None` for the current commit without a service (i.e. the Sawtooth commit).
+
+pub(in crate::commits) trait CommitStoreGetCurrentCommitOperation {
+    fn get_current_commit(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CommitStoreGetCurrentCommitOperation
+    for CommitStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn get_current_commit(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            let mut query = commits::table
+                .into_boxed()
+                .select(commits::all_columns)
+                .order_by(commits::commit_num.desc());
+
+            if let Some(service_id) = service_id {
+                query = query.filter(commits::service_id.eq(service_id));
+            } else {
+                query = query.filter(commits::service_id.is_null());
+            }
+
+            query
+                .first::<CommitModel>(self.conn)
+                .map(Commit::from)
+                .optional()
+                .map_err(|err| {
+                    CommitStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CommitStoreGetCurrentCommitOperation
+    for CommitStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_current_commit(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            let mut query = commits::table
+                .into_boxed()
+                .select(commits::all_columns)
+                .order_by(commits::commit_num.desc());
+
+            if let Some(service_id) = service_id {
+                query = query.filter(commits::service_id.eq(service_id));
+            } else {
+                query = query.filter(commits::service_id.is_null());
+            }
+
+            query
+                .first::<CommitModel>(self.conn)
+                .map(Commit::from)
+                .optional()
+                .map_err(|err| {
+                    CommitStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use diesel::insert_into;
+
+    use crate::commits::store::diesel::models::NewCommitModel;
+    use crate::migrations::run_sqlite_migrations;
+
+    /// Verify that the current commit of each source is returned, even when the commits of
+    /// another source have higher commit numbers.
+    #[test]
+    fn test_get_current_commit() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        let ops = CommitStoreOperations::new(&conn);
+        assert!(ops.get_current_commit(None)?.is_none());
+
+        insert_into(commits::table)
+            .values(vec![
+                NewCommitModel {
+                    commit_id: "sawtooth-1".into(),
+                    commit_num: 1,
+                    service_id: None,
+                },
+                NewCommitModel {
+                    commit_id: "sawtooth-2".into(),
+                    commit_num: 2,
+                    service_id: None,
+                },
+                NewCommitModel {
+                    commit_id: "splinter-3".into(),
+                    commit_num: 3,
+                    service_id: Some("circuit::svc".into()),
+                },
+            ])
+            .execute(&conn)?;
+
+        assert_eq!(
+            ops.get_current_commit(None)?,
+            Some(Commit {
+                commit_id: "sawtooth-2".into(),
+                commit_num: 2,
+                service_id: None,
+            })
+        );
+        assert_eq!(
+            ops.get_current_commit(Some("circuit::svc"))?
+                .map(|commit| commit.commit_id),
+            Some("splinter-3".to_string())
+        );
+        assert!(ops.get_current_commit(Some("other::svc"))?.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::CommitStoreOperations;
+use crate::commits::store::diesel::schema::{commits, event_handler_checkpoint};
+use crate::commits::store::{CommitStoreError, EventHandlerCheckpoint};
+
+use diesel::prelude::*;
+
+/// This database operation takes one argument: `handler_id`, which identifies an event handler.
+/// The handler's checkpoints are returned, one for each service it has processed commits from,
+/// along with the number of each checkpoint's commit if the commit is still in the commit store.
+
+pub(in crate::commits) trait CommitStoreGetEventHandlerCheckpointsOperation {
+    fn get_event_handler_checkpoints(
+        &self,
+        handler_id: &str,
+    ) -> Result<Vec<EventHandlerCheckpoint>, CommitStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CommitStoreGetEventHandlerCheckpointsOperation
+    for CommitStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn get_event_handler_checkpoints(
+        &self,
+        handler_id: &str,
+    ) -> Result<Vec<EventHandlerCheckpoint>, CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            let checkpoints = event_handler_checkpoint::table
+                .select((
+                    event_handler_checkpoint::service_id,
+                    event_handler_checkpoint::commit_id,
+                ))
+                .filter(event_handler_checkpoint::handler_id.eq(handler_id))
+                .order_by(event_handler_checkpoint::id)
+                .load::<(Option<String>, String)>(self.conn)?;
+
+            let mut handler_checkpoints = Vec::with_capacity(checkpoints.len());
+            for (service_id, commit_id) in checkpoints {
+                let query = commits::table
+                    .select(commits::commit_num)
+                    .filter(commits::commit_id.eq(&commit_id))
+                    .into_boxed();
+                let query = match service_id {
+                    Some(ref service_id) => query.filter(commits::service_id.eq(service_id)),
+                    None => query.filter(commits::service_id.is_null()),
+                };
+                let commit_num = query.first::<i64>(self.conn).optional()?;
+
+                handler_checkpoints.push(EventHandlerCheckpoint {
+                    handler_id: handler_id.to_string(),
+                    service_id,
+                    commit_id,
+                    commit_num,
+                });
+            }
+
+            Ok(handler_checkpoints)
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CommitStoreGetEventHandlerCheckpointsOperation
+    for CommitStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_event_handler_checkpoints(
+        &self,
+        handler_id: &str,
+    ) -> Result<Vec<EventHandlerCheckpoint>, CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            let checkpoints = event_handler_checkpoint::table
+                .select((
+                    event_handler_checkpoint::service_id,
+                    event_handler_checkpoint::commit_id,
+                ))
+                .filter(event_handler_checkpoint::handler_id.eq(handler_id))
+                .order_by(event_handler_checkpoint::id)
+                .load::<(Option<String>, String)>(self.conn)?;
+
+            let mut handler_checkpoints = Vec::with_capacity(checkpoints.len());
+            for (service_id, commit_id) in checkpoints {
+                let query = commits::table
+                    .select(commits::commit_num)
+                    .filter(commits::commit_id.eq(&commit_id))
+                    .into_boxed();
+                let query = match service_id {
+                    Some(ref service_id) => query.filter(commits::service_id.eq(service_id)),
+                    None => query.filter(commits::service_id.is_null()),
+                };
+                let commit_num = query.first::<i64>(self.conn).optional()?;
+
+                handler_checkpoints.push(EventHandlerCheckpoint {
+                    handler_id: handler_id.to_string(),
+                    service_id,
+                    commit_id,
+                    commit_num,
+                });
+            }
+
+            Ok(handler_checkpoints)
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::commits::store::diesel::models::NewCommitModel;
+    use crate::commits::store::diesel::operations::update_event_handler_checkpoint::CommitStoreUpdateEventHandlerCheckpointOperation as _;
+    use crate::migrations::run_sqlite_migrations;
+
+    /// Verify that a handler's checkpoints are returned for each service, with the number of
+    /// the checkpoint's commit, and that updating a checkpoint replaces the service's previous
+    /// checkpoint. A checkpoint whose commit is no longer known is returned without a number.
+    #[test]
+    fn test_event_handler_checkpoints() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        diesel::insert_into(commits::table)
+            .values(vec![
+                NewCommitModel {
+                    commit_id: "commit-1".into(),
+                    commit_num: 1,
+                    service_id: Some("circuit::a".into()),
+                },
+                NewCommitModel {
+                    commit_id: "commit-2".into(),
+                    commit_num: 2,
+                    service_id: Some("circuit::a".into()),
+                },
+                NewCommitModel {
+                    commit_id: "commit-3".into(),
+                    commit_num: 3,
+                    service_id: Some("circuit::b".into()),
+                },
+            ])
+            .execute(&conn)?;
+
+        let ops = CommitStoreOperations::new(&conn);
+
+        assert!(ops.get_event_handler_checkpoints("warehouse")?.is_empty());
+
+        ops.update_event_handler_checkpoint("warehouse", Some("circuit::a"), "commit-1")?;
+        ops.update_event_handler_checkpoint("warehouse", Some("circuit::a"), "commit-2")?;
+        ops.update_event_handler_checkpoint("warehouse", Some("circuit::b"), "forked")?;
+        ops.update_event_handler_checkpoint("archive", Some("circuit::b"), "commit-3")?;
+
+        assert_eq!(
+            ops.get_event_handler_checkpoints("warehouse")?,
+            vec![
+                EventHandlerCheckpoint {
+                    handler_id: "warehouse".into(),
+                    service_id: Some("circuit::a".into()),
+                    commit_id: "commit-2".into(),
+                    commit_num: Some(2),
+                },
+                EventHandlerCheckpoint {
+                    handler_id: "warehouse".into(),
+                    service_id: Some("circuit::b".into()),
+                    commit_id: "forked".into(),
+                    commit_num: None,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+
+use super::CommitStoreOperations;
+use crate::commits::store::diesel::{models::ServiceLifecycleModel, schema::service_lifecycle};
+use crate::commits::store::{CommitStoreError, ServiceLifecycle};
+
+use diesel::prelude::*;
+
+/// This database operation takes no arguments. The lifecycle states recorded for services are
+/// returned, ordered by service ID.
+
+pub(in crate::commits) trait CommitStoreListServiceLifecyclesOperation {
+    fn list_service_lifecycles(&self) -> Result<Vec<ServiceLifecycle>, CommitStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CommitStoreListServiceLifecyclesOperation
+    for CommitStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn list_service_lifecycles(&self) -> Result<Vec<ServiceLifecycle>, CommitStoreError> {
+        service_lifecycle::table
+            .order(service_lifecycle::service_id)
+            .load::<ServiceLifecycleModel>(self.conn)?
+            .into_iter()
+            .map(ServiceLifecycle::try_from)
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CommitStoreListServiceLifecyclesOperation
+    for CommitStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn list_service_lifecycles(&self) -> Result<Vec<ServiceLifecycle>, CommitStoreError> {
+        service_lifecycle::table
+            .order(service_lifecycle::service_id)
+            .load::<ServiceLifecycleModel>(self.conn)?
+            .into_iter()
+            .map(ServiceLifecycle::try_from)
+            .collect()
+    }
+}
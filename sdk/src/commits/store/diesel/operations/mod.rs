@@ -15,10 +15,18 @@
 pub(super) mod add_commit;
 pub(super) mod create_db_commit_from_commit_event;
 pub(super) mod get_commit_by_commit_num;
+pub(super) mod get_current_commit;
 pub(super) mod get_current_commit_id;
 pub(super) mod get_current_service_commits;
+pub(super) mod get_event_handler_checkpoints;
 pub(super) mod get_next_commit_num;
+pub(super) mod list_service_lifecycles;
+pub(super) mod purge_service;
+pub(super) mod reindex;
 pub(super) mod resolve_fork;
+pub(super) mod take_pending_reindex;
+pub(super) mod update_event_handler_checkpoint;
+pub(super) mod update_service_lifecycle;
 
 pub(super) struct CommitStoreOperations<'a, C> {
     conn: &'a C,
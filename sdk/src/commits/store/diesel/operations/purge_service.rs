@@ -0,0 +1,176 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::CommitStoreOperations;
+use crate::commits::store::diesel::schema::{commits, event_handler_checkpoint, pending_reindex};
+use crate::commits::store::{CommitStoreError, IndexedNamespace};
+
+use diesel::{dsl::delete, prelude::*, sql_query, sql_types::Text};
+
+/// This database operation takes one argument: `service_id`, the service whose data is purged.
+///
+/// The records of every indexed namespace that came from the service's commits are removed,
+/// whether current or not, along with the service's commits, any of them pending reindex and the
+/// event handler checkpoints for the service.
+
+pub(in crate::commits) trait CommitStorePurgeServiceOperation {
+    fn purge_service(&self, service_id: &str) -> Result<(), CommitStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CommitStorePurgeServiceOperation for CommitStoreOperations<'a, diesel::pg::PgConnection> {
+    fn purge_service(&self, service_id: &str) -> Result<(), CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            for table in IndexedNamespace::ALL
+                .iter()
+                .flat_map(IndexedNamespace::tables)
+            {
+                sql_query(format!("DELETE FROM {} WHERE service_id = $1", table))
+                    .bind::<Text, _>(service_id)
+                    .execute(self.conn)?;
+            }
+
+            delete(pending_reindex::table)
+                .filter(pending_reindex::service_id.eq(service_id))
+                .execute(self.conn)?;
+            delete(event_handler_checkpoint::table)
+                .filter(event_handler_checkpoint::service_id.eq(service_id))
+                .execute(self.conn)?;
+            delete(commits::table)
+                .filter(commits::service_id.eq(service_id))
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CommitStorePurgeServiceOperation
+    for CommitStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn purge_service(&self, service_id: &str) -> Result<(), CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            for table in IndexedNamespace::ALL
+                .iter()
+                .flat_map(IndexedNamespace::tables)
+            {
+                sql_query(format!("DELETE FROM {} WHERE service_id = ?", table))
+                    .bind::<Text, _>(service_id)
+                    .execute(self.conn)?;
+            }
+
+            delete(pending_reindex::table)
+                .filter(pending_reindex::service_id.eq(service_id))
+                .execute(self.conn)?;
+            delete(event_handler_checkpoint::table)
+                .filter(event_handler_checkpoint::service_id.eq(service_id))
+                .execute(self.conn)?;
+            delete(commits::table)
+                .filter(commits::service_id.eq(service_id))
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use diesel::dsl::insert_into;
+
+    use crate::commits::store::diesel::models::{NewCommitModel, NewEventHandlerCheckpointModel};
+    use crate::migrations::run_sqlite_migrations;
+
+    /// Verify that purging a service removes its records, commits and checkpoints, and leaves
+    /// those of other services in place.
+    #[test]
+    fn test_purge_service() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        insert_into(commits::table)
+            .values(vec![
+                NewCommitModel {
+                    commit_id: "commit-1".into(),
+                    commit_num: 1,
+                    service_id: Some("circuit-1::svc".into()),
+                },
+                NewCommitModel {
+                    commit_id: "commit-2".into(),
+                    commit_num: 2,
+                    service_id: Some("circuit-2::svc".into()),
+                },
+            ])
+            .execute(&conn)?;
+
+        insert_into(event_handler_checkpoint::table)
+            .values(vec![
+                NewEventHandlerCheckpointModel {
+                    handler_id: "database".into(),
+                    service_id: Some("circuit-1::svc".into()),
+                    commit_id: "commit-1".into(),
+                },
+                NewEventHandlerCheckpointModel {
+                    handler_id: "database".into(),
+                    service_id: Some("circuit-2::svc".into()),
+                    commit_id: "commit-2".into(),
+                },
+            ])
+            .execute(&conn)?;
+
+        sql_query(
+            "INSERT INTO product \
+            (product_id, product_address, product_namespace, owner, start_commit_num, \
+            end_commit_num, service_id) VALUES \
+            ('p1', 'addr1', 'GS1', 'org', 1, 9223372036854775807, 'circuit-1::svc'), \
+            ('p2', 'addr2', 'GS1', 'org', 2, 9223372036854775807, 'circuit-2::svc')",
+        )
+        .execute(&conn)?;
+
+        let ops = CommitStoreOperations::new(&conn);
+        ops.purge_service("circuit-1::svc")?;
+
+        assert_eq!(
+            commits::table
+                .select(commits::commit_id)
+                .load::<String>(&conn)?,
+            vec!["commit-2".to_string()]
+        );
+        assert_eq!(
+            event_handler_checkpoint::table
+                .select(event_handler_checkpoint::commit_id)
+                .load::<String>(&conn)?,
+            vec!["commit-2".to_string()]
+        );
+
+        #[derive(QueryableByName, Debug, PartialEq)]
+        struct ProductId {
+            #[sql_type = "Text"]
+            product_id: String,
+        }
+
+        assert_eq!(
+            sql_query("SELECT product_id FROM product").load::<ProductId>(&conn)?,
+            vec![ProductId {
+                product_id: "p2".into()
+            }]
+        );
+
+        Ok(())
+    }
+}
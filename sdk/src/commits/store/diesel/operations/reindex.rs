@@ -0,0 +1,295 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::CommitStoreOperations;
+use crate::commits::store::diesel::{
+    models::NewPendingReindexModel,
+    schema::{commits, pending_reindex},
+};
+use crate::commits::store::{CommitStoreError, IndexedNamespace};
+use crate::commits::MAX_COMMIT_NUM;
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+    sql_query,
+    sql_types::BigInt,
+};
+
+/// This database operation takes two arguments: `commit_num`, the height from which the
+/// namespaces are rebuilt, and `namespaces`, the namespaces to rebuild.
+///
+/// Each namespace's tables are rewound the same way `resolve_fork` rewinds them:
+/// records with a `start_commit_num` greater than or equal to `commit_num` are removed and
+/// records with an `end_commit_num` greater than or equal to it are made current again. Then
+/// the commits from `commit_num` on are recorded as pending reindex for the namespaces, along
+/// with any commits still pending from an earlier reindex at or after that height, and removed
+/// so that their events are requested again.
+
+pub(in crate::commits) trait CommitStoreReindexOperation {
+    fn reindex(
+        &self,
+        commit_num: i64,
+        namespaces: &[IndexedNamespace],
+    ) -> Result<(), CommitStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CommitStoreReindexOperation for CommitStoreOperations<'a, diesel::pg::PgConnection> {
+    fn reindex(
+        &self,
+        commit_num: i64,
+        namespaces: &[IndexedNamespace],
+    ) -> Result<(), CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            for table in namespaces.iter().flat_map(IndexedNamespace::tables) {
+                sql_query(format!(
+                    "DELETE FROM {} WHERE start_commit_num >= $1",
+                    table
+                ))
+                .bind::<BigInt, _>(commit_num)
+                .execute(self.conn)?;
+
+                sql_query(format!(
+                    "UPDATE {} SET end_commit_num = $1 WHERE end_commit_num >= $2",
+                    table
+                ))
+                .bind::<BigInt, _>(MAX_COMMIT_NUM)
+                .bind::<BigInt, _>(commit_num)
+                .execute(self.conn)?;
+            }
+
+            let mut reindexed = commits::table
+                .select((commits::commit_id, commits::commit_num, commits::service_id))
+                .filter(commits::commit_num.ge(commit_num))
+                .load::<ReindexedCommit>(self.conn)?;
+
+            // Commits still pending from an earlier reindex are rebuilt for these namespaces too
+            let still_pending = pending_reindex::table
+                .select((
+                    pending_reindex::commit_id,
+                    pending_reindex::commit_num,
+                    pending_reindex::service_id,
+                ))
+                .filter(pending_reindex::commit_num.ge(commit_num))
+                .distinct()
+                .load::<ReindexedCommit>(self.conn)?;
+            for commit in still_pending {
+                if !reindexed.contains(&commit) {
+                    reindexed.push(commit);
+                }
+            }
+
+            let pending = make_pending_reindex_models(reindexed, namespaces);
+            if !pending.is_empty() {
+                insert_into(pending_reindex::table)
+                    .values(pending)
+                    .execute(self.conn)?;
+            }
+
+            delete(commits::table)
+                .filter(commits::commit_num.ge(commit_num))
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CommitStoreReindexOperation
+    for CommitStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn reindex(
+        &self,
+        commit_num: i64,
+        namespaces: &[IndexedNamespace],
+    ) -> Result<(), CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            for table in namespaces.iter().flat_map(IndexedNamespace::tables) {
+                sql_query(format!("DELETE FROM {} WHERE start_commit_num >= ?", table))
+                    .bind::<BigInt, _>(commit_num)
+                    .execute(self.conn)?;
+
+                sql_query(format!(
+                    "UPDATE {} SET end_commit_num = ? WHERE end_commit_num >= ?",
+                    table
+                ))
+                .bind::<BigInt, _>(MAX_COMMIT_NUM)
+                .bind::<BigInt, _>(commit_num)
+                .execute(self.conn)?;
+            }
+
+            let mut reindexed = commits::table
+                .select((commits::commit_id, commits::commit_num, commits::service_id))
+                .filter(commits::commit_num.ge(commit_num))
+                .load::<ReindexedCommit>(self.conn)?;
+
+            // Commits still pending from an earlier reindex are rebuilt for these namespaces too
+            let still_pending = pending_reindex::table
+                .select((
+                    pending_reindex::commit_id,
+                    pending_reindex::commit_num,
+                    pending_reindex::service_id,
+                ))
+                .filter(pending_reindex::commit_num.ge(commit_num))
+                .distinct()
+                .load::<ReindexedCommit>(self.conn)?;
+            for commit in still_pending {
+                if !reindexed.contains(&commit) {
+                    reindexed.push(commit);
+                }
+            }
+
+            let pending = make_pending_reindex_models(reindexed, namespaces);
+            if !pending.is_empty() {
+                insert_into(pending_reindex::table)
+                    .values(pending)
+                    .execute(self.conn)?;
+            }
+
+            delete(commits::table)
+                .filter(commits::commit_num.ge(commit_num))
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+// A commit that is reindexed: its ID, number and service ID
+type ReindexedCommit = (String, i64, Option<String>);
+
+fn make_pending_reindex_models(
+    commits: Vec<ReindexedCommit>,
+    namespaces: &[IndexedNamespace],
+) -> Vec<NewPendingReindexModel> {
+    commits
+        .into_iter()
+        .flat_map(|(commit_id, commit_num, service_id)| {
+            namespaces
+                .iter()
+                .map(move |namespace| NewPendingReindexModel {
+                    commit_id: commit_id.clone(),
+                    commit_num,
+                    service_id: service_id.clone(),
+                    namespace: namespace.to_string(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::commits::store::diesel::models::NewCommitModel;
+    use crate::commits::store::diesel::operations::take_pending_reindex::CommitStoreTakePendingReindexOperation as _;
+    use crate::migrations::run_sqlite_migrations;
+
+    #[derive(QueryableByName, Debug, PartialEq)]
+    struct ChainRange {
+        #[sql_type = "BigInt"]
+        start_commit_num: i64,
+        #[sql_type = "BigInt"]
+        end_commit_num: i64,
+    }
+
+    /// Verify that reindexing a namespace rewinds only that namespace's records, removes the
+    /// commits from the given height and records them as pending reindex for the namespace.
+    #[test]
+    fn test_reindex_namespace() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        insert_into(commits::table)
+            .values(vec![
+                NewCommitModel {
+                    commit_id: "commit-1".into(),
+                    commit_num: 1,
+                    service_id: None,
+                },
+                NewCommitModel {
+                    commit_id: "commit-2".into(),
+                    commit_num: 2,
+                    service_id: None,
+                },
+                NewCommitModel {
+                    commit_id: "commit-3".into(),
+                    commit_num: 3,
+                    service_id: None,
+                },
+            ])
+            .execute(&conn)?;
+
+        sql_query(
+            "INSERT INTO product \
+            (product_id, product_address, product_namespace, owner, start_commit_num, \
+            end_commit_num) VALUES \
+            ('p1', 'addr1', 'GS1', 'org', 1, 2), \
+            ('p1', 'addr1', 'GS1', 'org', 2, 9223372036854775807), \
+            ('p2', 'addr2', 'GS1', 'org', 3, 9223372036854775807)",
+        )
+        .execute(&conn)?;
+        sql_query(
+            "INSERT INTO pike_allowed_orgs \
+            (role_name, org_id, allowed_org_id, start_commit_num, end_commit_num) VALUES \
+            ('admin', 'org', 'other', 3, 9223372036854775807)",
+        )
+        .execute(&conn)?;
+
+        let ops = CommitStoreOperations::new(&conn);
+        ops.reindex(2, &[IndexedNamespace::Product])?;
+
+        assert_eq!(
+            sql_query("SELECT start_commit_num, end_commit_num FROM product")
+                .load::<ChainRange>(&conn)?,
+            vec![ChainRange {
+                start_commit_num: 1,
+                end_commit_num: MAX_COMMIT_NUM,
+            }]
+        );
+        assert_eq!(
+            sql_query("SELECT start_commit_num, end_commit_num FROM pike_allowed_orgs")
+                .load::<ChainRange>(&conn)?,
+            vec![ChainRange {
+                start_commit_num: 3,
+                end_commit_num: MAX_COMMIT_NUM,
+            }]
+        );
+        assert_eq!(
+            commits::table
+                .select(commits::commit_num)
+                .load::<i64>(&conn)?,
+            vec![1]
+        );
+
+        assert_eq!(
+            ops.take_pending_reindex("commit-2", None)?,
+            Some(vec![IndexedNamespace::Product])
+        );
+        assert_eq!(ops.take_pending_reindex("commit-2", None)?, None);
+        assert_eq!(ops.take_pending_reindex("commit-3", Some("service"))?, None);
+
+        // A commit still pending from the first reindex is rebuilt for both namespaces
+        ops.reindex(3, &[IndexedNamespace::Pike])?;
+        assert_eq!(
+            ops.take_pending_reindex("commit-3", None)?,
+            Some(vec![IndexedNamespace::Product, IndexedNamespace::Pike])
+        );
+
+        Ok(())
+    }
+}
@@ -14,50 +14,120 @@
 
 use super::CommitStoreOperations;
 use crate::commits::store::diesel::{schema::chain_record, schema::commits};
-use crate::commits::store::CommitStoreError;
+use crate::commits::store::{CommitStoreError, IndexedNamespace};
 use crate::commits::MAX_COMMIT_NUM;
 
 use diesel::{
     dsl::{delete, update},
     prelude::*,
+    sql_query,
+    sql_types::{BigInt, Text},
 };
 
-/// This database operation takes one argument: `commit_num`. This argument is
-/// the height of the commit that will be replaced. The commit is only replaced
-/// if it is not a duplicate.
+/// This database operation takes two arguments: `commit_num`, the height of the commit that
+/// will be replaced, and `service_id`, the service the commit belongs to. The commit is only
+/// replaced if it is not a duplicate.
 ///
 /// If a fork is detected when attempting to add a commit, and the existing
 /// commit in the store is not a duplicate of the commit that is being added,
 /// the existing commit and anything with a `start_commit_num` greater than
 /// the height of the commit being added is removed. Then, the end_commit_num`
 /// for any record that is greater than the height of the commit being added is
-/// set to `MAX_COMMIT_NUM`, meaning it is the current record. Finally, the
+/// set to `MAX_COMMIT_NUM`, meaning it is the current record. The records of
+/// every indexed namespace are rewound along with the chain records. Finally, the
 /// commit being replaced is deleted. This allows for the database handler to
 /// call the operation to insert the record at the correct height.
+///
+/// Only the commits and records of the given service are rewound, or those without a service
+/// if `service_id` is `None`, so that a fork on one source leaves the others in place.
 
 pub(in crate::commits) trait CommitStoreResolveForkOperation {
-    fn resolve_fork(&self, commit_num: i64) -> Result<(), CommitStoreError>;
+    fn resolve_fork(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), CommitStoreError>;
 }
 
 #[cfg(feature = "postgres")]
 impl<'a> CommitStoreResolveForkOperation for CommitStoreOperations<'a, diesel::pg::PgConnection> {
-    fn resolve_fork(&self, commit_num: i64) -> Result<(), CommitStoreError> {
+    fn resolve_fork(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), CommitStoreError> {
         self.conn.transaction::<_, CommitStoreError, _>(|| {
-            delete(chain_record::table)
-                .filter(chain_record::start_commit_num.ge(commit_num))
-                .execute(self.conn)
-                .map(|_| ())?;
+            let mut query = delete(chain_record::table)
+                .into_boxed()
+                .filter(chain_record::start_commit_num.ge(commit_num));
+            if let Some(service_id) = service_id {
+                query = query.filter(chain_record::service_id.eq(service_id));
+            } else {
+                query = query.filter(chain_record::service_id.is_null());
+            }
+            query.execute(self.conn)?;
 
-            update(chain_record::table)
-                .filter(chain_record::end_commit_num.ge(commit_num))
+            let mut query = update(chain_record::table)
                 .set(chain_record::end_commit_num.eq(MAX_COMMIT_NUM))
-                .execute(self.conn)
-                .map(|_| ())?;
+                .into_boxed()
+                .filter(chain_record::end_commit_num.ge(commit_num));
+            if let Some(service_id) = service_id {
+                query = query.filter(chain_record::service_id.eq(service_id));
+            } else {
+                query = query.filter(chain_record::service_id.is_null());
+            }
+            query.execute(self.conn)?;
+
+            for table in IndexedNamespace::ALL
+                .iter()
+                .flat_map(IndexedNamespace::tables)
+            {
+                if let Some(service_id) = service_id {
+                    sql_query(format!(
+                        "DELETE FROM {} WHERE start_commit_num >= $1 AND service_id = $2",
+                        table
+                    ))
+                    .bind::<BigInt, _>(commit_num)
+                    .bind::<Text, _>(service_id)
+                    .execute(self.conn)?;
+
+                    sql_query(format!(
+                        "UPDATE {} SET end_commit_num = $1 \
+                        WHERE end_commit_num >= $2 AND service_id = $3",
+                        table
+                    ))
+                    .bind::<BigInt, _>(MAX_COMMIT_NUM)
+                    .bind::<BigInt, _>(commit_num)
+                    .bind::<Text, _>(service_id)
+                    .execute(self.conn)?;
+                } else {
+                    sql_query(format!(
+                        "DELETE FROM {} WHERE start_commit_num >= $1 AND service_id IS NULL",
+                        table
+                    ))
+                    .bind::<BigInt, _>(commit_num)
+                    .execute(self.conn)?;
 
-            delete(commits::table)
-                .filter(commits::commit_num.ge(commit_num))
-                .execute(self.conn)
-                .map(|_| ())?;
+                    sql_query(format!(
+                        "UPDATE {} SET end_commit_num = $1 \
+                        WHERE end_commit_num >= $2 AND service_id IS NULL",
+                        table
+                    ))
+                    .bind::<BigInt, _>(MAX_COMMIT_NUM)
+                    .bind::<BigInt, _>(commit_num)
+                    .execute(self.conn)?;
+                }
+            }
+
+            let mut query = delete(commits::table)
+                .into_boxed()
+                .filter(commits::commit_num.ge(commit_num));
+            if let Some(service_id) = service_id {
+                query = query.filter(commits::service_id.eq(service_id));
+            } else {
+                query = query.filter(commits::service_id.is_null());
+            }
+            query.execute(self.conn)?;
 
             Ok(())
         })
@@ -68,25 +138,177 @@ impl<'a> CommitStoreResolveForkOperation for CommitStoreOperations<'a, diesel::p
 impl<'a> CommitStoreResolveForkOperation
     for CommitStoreOperations<'a, diesel::sqlite::SqliteConnection>
 {
-    fn resolve_fork(&self, commit_num: i64) -> Result<(), CommitStoreError> {
+    fn resolve_fork(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), CommitStoreError> {
         self.conn.transaction::<_, CommitStoreError, _>(|| {
-            delete(chain_record::table)
-                .filter(chain_record::start_commit_num.ge(commit_num))
-                .execute(self.conn)
-                .map(|_| ())?;
+            let mut query = delete(chain_record::table)
+                .into_boxed()
+                .filter(chain_record::start_commit_num.ge(commit_num));
+            if let Some(service_id) = service_id {
+                query = query.filter(chain_record::service_id.eq(service_id));
+            } else {
+                query = query.filter(chain_record::service_id.is_null());
+            }
+            query.execute(self.conn)?;
 
-            update(chain_record::table)
-                .filter(chain_record::end_commit_num.ge(commit_num))
+            let mut query = update(chain_record::table)
                 .set(chain_record::end_commit_num.eq(MAX_COMMIT_NUM))
-                .execute(self.conn)
-                .map(|_| ())?;
+                .into_boxed()
+                .filter(chain_record::end_commit_num.ge(commit_num));
+            if let Some(service_id) = service_id {
+                query = query.filter(chain_record::service_id.eq(service_id));
+            } else {
+                query = query.filter(chain_record::service_id.is_null());
+            }
+            query.execute(self.conn)?;
+
+            for table in IndexedNamespace::ALL
+                .iter()
+                .flat_map(IndexedNamespace::tables)
+            {
+                if let Some(service_id) = service_id {
+                    sql_query(format!(
+                        "DELETE FROM {} WHERE start_commit_num >= ? AND service_id = ?",
+                        table
+                    ))
+                    .bind::<BigInt, _>(commit_num)
+                    .bind::<Text, _>(service_id)
+                    .execute(self.conn)?;
+
+                    sql_query(format!(
+                        "UPDATE {} SET end_commit_num = ? \
+                        WHERE end_commit_num >= ? AND service_id = ?",
+                        table
+                    ))
+                    .bind::<BigInt, _>(MAX_COMMIT_NUM)
+                    .bind::<BigInt, _>(commit_num)
+                    .bind::<Text, _>(service_id)
+                    .execute(self.conn)?;
+                } else {
+                    sql_query(format!(
+                        "DELETE FROM {} WHERE start_commit_num >= ? AND service_id IS NULL",
+                        table
+                    ))
+                    .bind::<BigInt, _>(commit_num)
+                    .execute(self.conn)?;
 
-            delete(commits::table)
-                .filter(commits::commit_num.ge(commit_num))
-                .execute(self.conn)
-                .map(|_| ())?;
+                    sql_query(format!(
+                        "UPDATE {} SET end_commit_num = ? \
+                        WHERE end_commit_num >= ? AND service_id IS NULL",
+                        table
+                    ))
+                    .bind::<BigInt, _>(MAX_COMMIT_NUM)
+                    .bind::<BigInt, _>(commit_num)
+                    .execute(self.conn)?;
+                }
+            }
+
+            let mut query = delete(commits::table)
+                .into_boxed()
+                .filter(commits::commit_num.ge(commit_num));
+            if let Some(service_id) = service_id {
+                query = query.filter(commits::service_id.eq(service_id));
+            } else {
+                query = query.filter(commits::service_id.is_null());
+            }
+            query.execute(self.conn)?;
 
             Ok(())
         })
     }
 }
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use diesel::dsl::insert_into;
+
+    use crate::commits::store::diesel::models::NewCommitModel;
+    use crate::migrations::run_sqlite_migrations;
+
+    /// Verify that resolving a fork on the commits without a service rewinds only their commits
+    /// and records, leaving those of a service with the same or later commit numbers in place.
+    #[test]
+    fn test_resolve_fork_for_service() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        insert_into(commits::table)
+            .values(vec![
+                NewCommitModel {
+                    commit_id: "sawtooth-1".into(),
+                    commit_num: 1,
+                    service_id: None,
+                },
+                NewCommitModel {
+                    commit_id: "sawtooth-2".into(),
+                    commit_num: 2,
+                    service_id: None,
+                },
+                NewCommitModel {
+                    commit_id: "splinter-2".into(),
+                    commit_num: 2,
+                    service_id: Some("circuit::svc".into()),
+                },
+            ])
+            .execute(&conn)?;
+
+        sql_query(
+            "INSERT INTO product \
+            (product_id, product_address, product_namespace, owner, start_commit_num, \
+            end_commit_num, service_id) VALUES \
+            ('p1', 'addr1', 'GS1', 'org', 1, 2, NULL), \
+            ('p1', 'addr1', 'GS1', 'org', 2, 9223372036854775807, NULL), \
+            ('p2', 'addr2', 'GS1', 'org', 2, 9223372036854775807, 'circuit::svc')",
+        )
+        .execute(&conn)?;
+
+        let ops = CommitStoreOperations::new(&conn);
+        ops.resolve_fork(2, None)?;
+
+        assert_eq!(
+            commits::table
+                .select(commits::commit_id)
+                .order(commits::id)
+                .load::<String>(&conn)?,
+            vec!["sawtooth-1".to_string(), "splinter-2".to_string()]
+        );
+
+        #[derive(QueryableByName, Debug, PartialEq)]
+        struct Product {
+            #[sql_type = "Text"]
+            product_id: String,
+            #[sql_type = "BigInt"]
+            start_commit_num: i64,
+            #[sql_type = "BigInt"]
+            end_commit_num: i64,
+        }
+
+        assert_eq!(
+            sql_query(
+                "SELECT product_id, start_commit_num, end_commit_num FROM product \
+                ORDER BY product_id"
+            )
+            .load::<Product>(&conn)?,
+            vec![
+                Product {
+                    product_id: "p1".into(),
+                    start_commit_num: 1,
+                    end_commit_num: MAX_COMMIT_NUM,
+                },
+                Product {
+                    product_id: "p2".into(),
+                    start_commit_num: 2,
+                    end_commit_num: MAX_COMMIT_NUM,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+
+use super::CommitStoreOperations;
+use crate::commits::store::diesel::schema::pending_reindex;
+use crate::commits::store::{CommitStoreError, IndexedNamespace};
+use crate::error::InternalError;
+
+use diesel::{dsl::delete, prelude::*};
+
+/// This database operation takes two arguments: `commit_id` and `service_id`, which identify a
+/// commit. If the commit was recorded as pending reindex, the records are removed and the
+/// namespaces to rebuild from the commit's events are returned. Otherwise `None` is returned.
+
+pub(in crate::commits) trait CommitStoreTakePendingReindexOperation {
+    fn take_pending_reindex(
+        &self,
+        commit_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Vec<IndexedNamespace>>, CommitStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CommitStoreTakePendingReindexOperation
+    for CommitStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn take_pending_reindex(
+        &self,
+        commit_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Vec<IndexedNamespace>>, CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            let namespaces = match service_id {
+                Some(service_id) => {
+                    let filter = pending_reindex::commit_id
+                        .eq(commit_id)
+                        .and(pending_reindex::service_id.eq(service_id));
+                    let namespaces = pending_reindex::table
+                        .select(pending_reindex::namespace)
+                        .filter(filter)
+                        .load::<String>(self.conn)?;
+                    delete(pending_reindex::table)
+                        .filter(filter)
+                        .execute(self.conn)?;
+                    namespaces
+                }
+                None => {
+                    let filter = pending_reindex::commit_id
+                        .eq(commit_id)
+                        .and(pending_reindex::service_id.is_null());
+                    let namespaces = pending_reindex::table
+                        .select(pending_reindex::namespace)
+                        .filter(filter)
+                        .load::<String>(self.conn)?;
+                    delete(pending_reindex::table)
+                        .filter(filter)
+                        .execute(self.conn)?;
+                    namespaces
+                }
+            };
+
+            parse_namespaces(namespaces)
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CommitStoreTakePendingReindexOperation
+    for CommitStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn take_pending_reindex(
+        &self,
+        commit_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Vec<IndexedNamespace>>, CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            let namespaces = match service_id {
+                Some(service_id) => {
+                    let filter = pending_reindex::commit_id
+                        .eq(commit_id)
+                        .and(pending_reindex::service_id.eq(service_id));
+                    let namespaces = pending_reindex::table
+                        .select(pending_reindex::namespace)
+                        .filter(filter)
+                        .load::<String>(self.conn)?;
+                    delete(pending_reindex::table)
+                        .filter(filter)
+                        .execute(self.conn)?;
+                    namespaces
+                }
+                None => {
+                    let filter = pending_reindex::commit_id
+                        .eq(commit_id)
+                        .and(pending_reindex::service_id.is_null());
+                    let namespaces = pending_reindex::table
+                        .select(pending_reindex::namespace)
+                        .filter(filter)
+                        .load::<String>(self.conn)?;
+                    delete(pending_reindex::table)
+                        .filter(filter)
+                        .execute(self.conn)?;
+                    namespaces
+                }
+            };
+
+            parse_namespaces(namespaces)
+        })
+    }
+}
+
+fn parse_namespaces(
+    namespaces: Vec<String>,
+) -> Result<Option<Vec<IndexedNamespace>>, CommitStoreError> {
+    if namespaces.is_empty() {
+        return Ok(None);
+    }
+
+    let mut parsed = Vec::with_capacity(namespaces.len());
+    for namespace in namespaces {
+        let namespace = IndexedNamespace::from_str(&namespace).map_err(|err| {
+            CommitStoreError::InternalError(InternalError::from_source(Box::new(err)))
+        })?;
+        if !parsed.contains(&namespace) {
+            parsed.push(namespace);
+        }
+    }
+
+    Ok(Some(parsed))
+}
@@ -0,0 +1,119 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::CommitStoreOperations;
+use crate::commits::store::diesel::{
+    models::NewEventHandlerCheckpointModel, schema::event_handler_checkpoint,
+};
+use crate::commits::store::CommitStoreError;
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+/// This database operation takes three arguments: `handler_id`, `service_id` and `commit_id`.
+/// The commit is recorded as the last commit from the service that the event handler has
+/// processed, replacing the handler's previous checkpoint for the service.
+
+pub(in crate::commits) trait CommitStoreUpdateEventHandlerCheckpointOperation {
+    fn update_event_handler_checkpoint(
+        &self,
+        handler_id: &str,
+        service_id: Option<&str>,
+        commit_id: &str,
+    ) -> Result<(), CommitStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CommitStoreUpdateEventHandlerCheckpointOperation
+    for CommitStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn update_event_handler_checkpoint(
+        &self,
+        handler_id: &str,
+        service_id: Option<&str>,
+        commit_id: &str,
+    ) -> Result<(), CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            match service_id {
+                Some(service_id) => delete(event_handler_checkpoint::table)
+                    .filter(
+                        event_handler_checkpoint::handler_id
+                            .eq(handler_id)
+                            .and(event_handler_checkpoint::service_id.eq(service_id)),
+                    )
+                    .execute(self.conn)?,
+                None => delete(event_handler_checkpoint::table)
+                    .filter(
+                        event_handler_checkpoint::handler_id
+                            .eq(handler_id)
+                            .and(event_handler_checkpoint::service_id.is_null()),
+                    )
+                    .execute(self.conn)?,
+            };
+
+            insert_into(event_handler_checkpoint::table)
+                .values(NewEventHandlerCheckpointModel {
+                    handler_id: handler_id.to_string(),
+                    service_id: service_id.map(ToString::to_string),
+                    commit_id: commit_id.to_string(),
+                })
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CommitStoreUpdateEventHandlerCheckpointOperation
+    for CommitStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn update_event_handler_checkpoint(
+        &self,
+        handler_id: &str,
+        service_id: Option<&str>,
+        commit_id: &str,
+    ) -> Result<(), CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            match service_id {
+                Some(service_id) => delete(event_handler_checkpoint::table)
+                    .filter(
+                        event_handler_checkpoint::handler_id
+                            .eq(handler_id)
+                            .and(event_handler_checkpoint::service_id.eq(service_id)),
+                    )
+                    .execute(self.conn)?,
+                None => delete(event_handler_checkpoint::table)
+                    .filter(
+                        event_handler_checkpoint::handler_id
+                            .eq(handler_id)
+                            .and(event_handler_checkpoint::service_id.is_null()),
+                    )
+                    .execute(self.conn)?,
+            };
+
+            insert_into(event_handler_checkpoint::table)
+                .values(NewEventHandlerCheckpointModel {
+                    handler_id: handler_id.to_string(),
+                    service_id: service_id.map(ToString::to_string),
+                    commit_id: commit_id.to_string(),
+                })
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::CommitStoreOperations;
+use crate::commits::store::diesel::{models::NewServiceLifecycleModel, schema::service_lifecycle};
+use crate::commits::store::{CommitStoreError, ServiceLifecycle};
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+/// This database operation takes one argument: `lifecycle`, the lifecycle state of a service,
+/// which replaces the state previously recorded for the service.
+
+pub(in crate::commits) trait CommitStoreUpdateServiceLifecycleOperation {
+    fn update_service_lifecycle(&self, lifecycle: ServiceLifecycle)
+        -> Result<(), CommitStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CommitStoreUpdateServiceLifecycleOperation
+    for CommitStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn update_service_lifecycle(
+        &self,
+        lifecycle: ServiceLifecycle,
+    ) -> Result<(), CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            delete(service_lifecycle::table)
+                .filter(service_lifecycle::service_id.eq(&lifecycle.service_id))
+                .execute(self.conn)?;
+
+            insert_into(service_lifecycle::table)
+                .values(NewServiceLifecycleModel {
+                    service_id: lifecycle.service_id,
+                    status: lifecycle.status.to_string(),
+                    data_status: lifecycle.data_status.to_string(),
+                })
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CommitStoreUpdateServiceLifecycleOperation
+    for CommitStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn update_service_lifecycle(
+        &self,
+        lifecycle: ServiceLifecycle,
+    ) -> Result<(), CommitStoreError> {
+        self.conn.transaction::<_, CommitStoreError, _>(|| {
+            delete(service_lifecycle::table)
+                .filter(service_lifecycle::service_id.eq(&lifecycle.service_id))
+                .execute(self.conn)?;
+
+            insert_into(service_lifecycle::table)
+                .values(NewServiceLifecycleModel {
+                    service_id: lifecycle.service_id,
+                    status: lifecycle.status.to_string(),
+                    data_status: lifecycle.data_status.to_string(),
+                })
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::commits::store::diesel::operations::list_service_lifecycles::CommitStoreListServiceLifecyclesOperation as _;
+    use crate::commits::store::{ServiceDataStatus, ServiceStatus};
+    use crate::migrations::run_sqlite_migrations;
+
+    /// Verify that updating a service's lifecycle state replaces its previous state.
+    #[test]
+    fn test_update_service_lifecycle() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        let ops = CommitStoreOperations::new(&conn);
+        ops.update_service_lifecycle(ServiceLifecycle {
+            service_id: "circuit-1::svc".into(),
+            status: ServiceStatus::Active,
+            data_status: ServiceDataStatus::Indexed,
+        })?;
+        ops.update_service_lifecycle(ServiceLifecycle {
+            service_id: "circuit-2::svc".into(),
+            status: ServiceStatus::Active,
+            data_status: ServiceDataStatus::Indexed,
+        })?;
+        ops.update_service_lifecycle(ServiceLifecycle {
+            service_id: "circuit-1::svc".into(),
+            status: ServiceStatus::Disbanded,
+            data_status: ServiceDataStatus::Archived,
+        })?;
+
+        assert_eq!(
+            ops.list_service_lifecycles()?,
+            vec![
+                ServiceLifecycle {
+                    service_id: "circuit-1::svc".into(),
+                    status: ServiceStatus::Disbanded,
+                    data_status: ServiceDataStatus::Archived,
+                },
+                ServiceLifecycle {
+                    service_id: "circuit-2::svc".into(),
+                    status: ServiceStatus::Active,
+                    data_status: ServiceDataStatus::Indexed,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -29,3 +29,31 @@ table! {
         service_id -> Nullable<Text>,
     }
 }
+
+table! {
+    pending_reindex (id) {
+        id -> Int8,
+        commit_id -> Text,
+        commit_num -> Int8,
+        service_id -> Nullable<Text>,
+        namespace -> Text,
+    }
+}
+
+table! {
+    event_handler_checkpoint (id) {
+        id -> Int8,
+        handler_id -> Text,
+        service_id -> Nullable<Text>,
+        commit_id -> Text,
+    }
+}
+
+table! {
+    service_lifecycle (id) {
+        id -> Int8,
+        service_id -> Text,
+        status -> Text,
+        data_status -> Text,
+    }
+}
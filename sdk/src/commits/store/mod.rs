@@ -20,6 +20,11 @@ mod error;
 pub use self::diesel::{DieselCommitStore, DieselConnectionCommitStore};
 pub use error::CommitStoreError;
 
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::InvalidArgumentError;
+
 /// Represents a Grid commit
 #[derive(Clone, Debug, Serialize, PartialEq, Eq)]
 pub struct Commit {
@@ -35,6 +40,193 @@ pub struct ChainRecord {
     pub service_id: Option<String>,
 }
 
+/// The last commit from a service that an event handler has processed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventHandlerCheckpoint {
+    pub handler_id: String,
+    pub service_id: Option<String>,
+    pub commit_id: String,
+    /// The number of the commit in the commit store, or `None` if the commit is no longer
+    /// known, as when it was removed by fork resolution or a reindex
+    pub commit_num: Option<i64>,
+}
+
+/// The state of a service's circuit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServiceStatus {
+    Active,
+    Disbanded,
+    Abandoned,
+}
+
+impl fmt::Display for ServiceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServiceStatus::Active => f.write_str("active"),
+            ServiceStatus::Disbanded => f.write_str("disbanded"),
+            ServiceStatus::Abandoned => f.write_str("abandoned"),
+        }
+    }
+}
+
+impl FromStr for ServiceStatus {
+    type Err = InvalidArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(ServiceStatus::Active),
+            "disbanded" => Ok(ServiceStatus::Disbanded),
+            "abandoned" => Ok(ServiceStatus::Abandoned),
+            _ => Err(InvalidArgumentError::new(
+                "status".to_string(),
+                format!("'{}' is not one of active, disbanded or abandoned", s),
+            )),
+        }
+    }
+}
+
+/// What has become of the records indexed from a service's commits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServiceDataStatus {
+    /// The records are current and updated from the service's commits
+    Indexed,
+    /// The records are kept, but are no longer updated
+    Archived,
+    /// The records have been removed
+    Purged,
+}
+
+impl fmt::Display for ServiceDataStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServiceDataStatus::Indexed => f.write_str("indexed"),
+            ServiceDataStatus::Archived => f.write_str("archived"),
+            ServiceDataStatus::Purged => f.write_str("purged"),
+        }
+    }
+}
+
+impl FromStr for ServiceDataStatus {
+    type Err = InvalidArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "indexed" => Ok(ServiceDataStatus::Indexed),
+            "archived" => Ok(ServiceDataStatus::Archived),
+            "purged" => Ok(ServiceDataStatus::Purged),
+            _ => Err(InvalidArgumentError::new(
+                "data_status".to_string(),
+                format!("'{}' is not one of indexed, archived or purged", s),
+            )),
+        }
+    }
+}
+
+/// The lifecycle state of a service that commits are indexed from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceLifecycle {
+    /// The service ID, in the form `{circuit_id}::{service_id}`
+    pub service_id: String,
+    pub status: ServiceStatus,
+    pub data_status: ServiceDataStatus,
+}
+
+/// A namespace whose state is indexed in the database from commit events
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IndexedNamespace {
+    Pike,
+    Schema,
+    Product,
+    Location,
+    PurchaseOrder,
+    TrackAndTrace,
+}
+
+impl IndexedNamespace {
+    /// All of the namespaces indexed in the database
+    pub const ALL: [IndexedNamespace; 6] = [
+        IndexedNamespace::Pike,
+        IndexedNamespace::Schema,
+        IndexedNamespace::Product,
+        IndexedNamespace::Location,
+        IndexedNamespace::PurchaseOrder,
+        IndexedNamespace::TrackAndTrace,
+    ];
+
+    /// Returns the tables that hold the namespace's records
+    pub fn tables(&self) -> &'static [&'static str] {
+        match self {
+            IndexedNamespace::Pike => &[
+                "pike_agent",
+                "pike_agent_role_assoc",
+                "pike_allowed_orgs",
+                "pike_inherit_from",
+                "pike_organization",
+                "pike_organization_alternate_id",
+                "pike_organization_location_assoc",
+                "pike_organization_metadata",
+                "pike_permissions",
+                "pike_role",
+                "pike_role_state_address_assoc",
+            ],
+            IndexedNamespace::Schema => &["grid_schema", "grid_property_definition"],
+            IndexedNamespace::Product => &["product", "product_property_value"],
+            IndexedNamespace::Location => &["location", "location_attribute"],
+            IndexedNamespace::PurchaseOrder => &[
+                "purchase_order",
+                "purchase_order_alternate_id",
+                "purchase_order_version",
+                "purchase_order_version_revision",
+            ],
+            IndexedNamespace::TrackAndTrace => &[
+                "alert",
+                "associated_agent",
+                "property",
+                "proposal",
+                "record",
+                "reported_value",
+                "reporter",
+            ],
+        }
+    }
+}
+
+impl fmt::Display for IndexedNamespace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndexedNamespace::Pike => f.write_str("pike"),
+            IndexedNamespace::Schema => f.write_str("schema"),
+            IndexedNamespace::Product => f.write_str("product"),
+            IndexedNamespace::Location => f.write_str("location"),
+            IndexedNamespace::PurchaseOrder => f.write_str("purchase_order"),
+            IndexedNamespace::TrackAndTrace => f.write_str("track_and_trace"),
+        }
+    }
+}
+
+impl FromStr for IndexedNamespace {
+    type Err = InvalidArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.replace('-', "_").as_str() {
+            "pike" => Ok(IndexedNamespace::Pike),
+            "schema" => Ok(IndexedNamespace::Schema),
+            "product" => Ok(IndexedNamespace::Product),
+            "location" => Ok(IndexedNamespace::Location),
+            "purchase_order" => Ok(IndexedNamespace::PurchaseOrder),
+            "track_and_trace" => Ok(IndexedNamespace::TrackAndTrace),
+            _ => Err(InvalidArgumentError::new(
+                "namespace".to_string(),
+                format!(
+                    "'{}' is not one of pike, schema, product, location, purchase_order or \
+                    track_and_trace",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
 /// A change that has been applied to state, represented in terms of a key/value pair
 #[derive(Clone, Eq, PartialEq)]
 pub enum StateChange {
@@ -69,8 +261,23 @@ pub trait CommitStore {
     /// # Arguments
     ///
     ///  * `commit_num` - The commit to be fetched
-    fn get_commit_by_commit_num(&self, commit_num: i64)
-        -> Result<Option<Commit>, CommitStoreError>;
+    ///  * `service_id` - The service the commit belongs to, if any
+    fn get_commit_by_commit_num(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError>;
+
+    /// Gets the current commit of a service from the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `service_id` - The service whose current commit is fetched, or `None` for the current
+    ///    commit without a service
+    fn get_current_commit(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError>;
 
     /// Gets the current commit ID from the underlying storage
     fn get_current_commit_id(&self) -> Result<Option<String>, CommitStoreError>;
@@ -86,10 +293,95 @@ pub trait CommitStore {
 
     /// Resolves a fork
     ///
+    /// Only the commits and records of the given service are rewound, so a fork on one source
+    /// leaves the others in place.
+    ///
     /// # Arguments
     ///
     ///  * `commit_num` - The commit to be fetched
-    fn resolve_fork(&self, commit_num: i64) -> Result<(), CommitStoreError>;
+    ///  * `service_id` - The service the fork occurred on, if any
+    fn resolve_fork(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), CommitStoreError>;
+
+    /// Rewinds the given namespaces to a commit height so that they are rebuilt from commit events
+    ///
+    /// As when resolving a fork, records of the namespaces that start at or after `commit_num`
+    /// are removed and records that ended at or after it are made current again. The commits
+    /// from `commit_num` on are removed so that their events are requested again, and are
+    /// recorded as pending reindex for the given namespaces; see `take_pending_reindex`.
+    ///
+    /// # Arguments
+    ///
+    ///  * `commit_num` - The height of the first commit to reindex
+    ///  * `namespaces` - The namespaces to rebuild
+    fn reindex(
+        &self,
+        commit_num: i64,
+        namespaces: &[IndexedNamespace],
+    ) -> Result<(), CommitStoreError>;
+
+    /// Removes a commit from the commits pending reindex
+    ///
+    /// Returns the namespaces to rebuild from the commit's events, or `None` if the commit is not
+    /// pending reindex and all of its state changes should be applied.
+    ///
+    /// # Arguments
+    ///
+    ///  * `commit_id` - The ID of the commit
+    ///  * `service_id` - The service the commit belongs to, if any
+    fn take_pending_reindex(
+        &self,
+        commit_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Vec<IndexedNamespace>>, CommitStoreError>;
+
+    /// Gets the checkpoints recorded for an event handler, one for each service that the handler
+    /// has processed commits from
+    ///
+    /// # Arguments
+    ///
+    ///  * `handler_id` - The ID of the event handler
+    fn get_event_handler_checkpoints(
+        &self,
+        handler_id: &str,
+    ) -> Result<Vec<EventHandlerCheckpoint>, CommitStoreError>;
+
+    /// Records the last commit from a service that an event handler has processed, replacing the
+    /// handler's previous checkpoint for the service
+    ///
+    /// # Arguments
+    ///
+    ///  * `handler_id` - The ID of the event handler
+    ///  * `service_id` - The service the commit belongs to, if any
+    ///  * `commit_id` - The ID of the commit
+    fn update_event_handler_checkpoint(
+        &self,
+        handler_id: &str,
+        service_id: Option<&str>,
+        commit_id: &str,
+    ) -> Result<(), CommitStoreError>;
+
+    /// Gets the lifecycle states recorded for services
+    fn list_service_lifecycles(&self) -> Result<Vec<ServiceLifecycle>, CommitStoreError>;
+
+    /// Records the lifecycle state of a service, replacing its previous state
+    ///
+    /// # Arguments
+    ///
+    ///  * `lifecycle` - The lifecycle state of the service
+    fn update_service_lifecycle(&self, lifecycle: ServiceLifecycle)
+        -> Result<(), CommitStoreError>;
+
+    /// Removes the records indexed from a service's commits, along with its commits, the event
+    /// handler checkpoints for it and any of its commits pending reindex
+    ///
+    /// # Arguments
+    ///
+    ///  * `service_id` - The service whose records are removed
+    fn purge_service(&self, service_id: &str) -> Result<(), CommitStoreError>;
 
     /// Creates a commit model from a commit event
     ///
@@ -113,8 +405,16 @@ where
     fn get_commit_by_commit_num(
         &self,
         commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<Option<Commit>, CommitStoreError> {
-        (**self).get_commit_by_commit_num(commit_num)
+        (**self).get_commit_by_commit_num(commit_num, service_id)
+    }
+
+    fn get_current_commit(
+        &self,
+        service_id: Option<&str>,
+    ) -> Result<Option<Commit>, CommitStoreError> {
+        (**self).get_current_commit(service_id)
     }
 
     fn get_current_commit_id(&self) -> Result<Option<String>, CommitStoreError> {
@@ -129,8 +429,59 @@ where
         (**self).get_next_commit_num()
     }
 
-    fn resolve_fork(&self, commit_num: i64) -> Result<(), CommitStoreError> {
-        (**self).resolve_fork(commit_num)
+    fn resolve_fork(
+        &self,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), CommitStoreError> {
+        (**self).resolve_fork(commit_num, service_id)
+    }
+
+    fn reindex(
+        &self,
+        commit_num: i64,
+        namespaces: &[IndexedNamespace],
+    ) -> Result<(), CommitStoreError> {
+        (**self).reindex(commit_num, namespaces)
+    }
+
+    fn take_pending_reindex(
+        &self,
+        commit_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Vec<IndexedNamespace>>, CommitStoreError> {
+        (**self).take_pending_reindex(commit_id, service_id)
+    }
+
+    fn get_event_handler_checkpoints(
+        &self,
+        handler_id: &str,
+    ) -> Result<Vec<EventHandlerCheckpoint>, CommitStoreError> {
+        (**self).get_event_handler_checkpoints(handler_id)
+    }
+
+    fn update_event_handler_checkpoint(
+        &self,
+        handler_id: &str,
+        service_id: Option<&str>,
+        commit_id: &str,
+    ) -> Result<(), CommitStoreError> {
+        (**self).update_event_handler_checkpoint(handler_id, service_id, commit_id)
+    }
+
+    fn list_service_lifecycles(&self) -> Result<Vec<ServiceLifecycle>, CommitStoreError> {
+        (**self).list_service_lifecycles()
+    }
+
+    fn update_service_lifecycle(
+        &self,
+        lifecycle: ServiceLifecycle,
+    ) -> Result<(), CommitStoreError> {
+        (**self).update_service_lifecycle(lifecycle)
+    }
+
+    fn purge_service(&self, service_id: &str) -> Result<(), CommitStoreError> {
+        (**self).purge_service(service_id)
     }
 
     fn create_db_commit_from_commit_event(
@@ -31,6 +31,7 @@ use crate::error::ResourceTemporarilyUnavailableError;
 use operations::add_location::LocationStoreAddLocationOperation as _;
 use operations::delete_location::LocationStoreDeleteLocationOperation as _;
 use operations::get_location::LocationStoreGetLocationOperation as _;
+use operations::get_location_id_from_address::LocationStoreGetLocationIdFromAddressOperation as _;
 use operations::list_locations::LocationStoreListLocationsOperation as _;
 use operations::LocationStoreOperations;
 
@@ -95,13 +96,27 @@ impl LocationStore for DieselLocationStore<diesel::pg::PgConnection> {
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), LocationStoreError> {
         LocationStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             LocationStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .delete_location(address, current_commit_num)
+        .delete_location(address, current_commit_num, service_id)
+    }
+
+    fn get_location_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, LocationStoreError> {
+        LocationStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            LocationStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_location_id_from_address(address, service_id)
     }
 }
 
@@ -149,13 +164,27 @@ impl LocationStore for DieselLocationStore<diesel::sqlite::SqliteConnection> {
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), LocationStoreError> {
         LocationStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             LocationStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .delete_location(address, current_commit_num)
+        .delete_location(address, current_commit_num, service_id)
+    }
+
+    fn get_location_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, LocationStoreError> {
+        LocationStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            LocationStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_location_id_from_address(address, service_id)
     }
 }
 
@@ -210,8 +239,22 @@ impl<'a> LocationStore for DieselConnectionLocationStore<'a, diesel::pg::PgConne
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), LocationStoreError> {
-        LocationStoreOperations::new(self.connection).delete_location(address, current_commit_num)
+        LocationStoreOperations::new(self.connection).delete_location(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn get_location_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, LocationStoreError> {
+        LocationStoreOperations::new(self.connection)
+            .get_location_id_from_address(address, service_id)
     }
 }
 
@@ -248,8 +291,22 @@ impl<'a> LocationStore for DieselConnectionLocationStore<'a, diesel::sqlite::Sql
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), LocationStoreError> {
-        LocationStoreOperations::new(self.connection).delete_location(address, current_commit_num)
+        LocationStoreOperations::new(self.connection).delete_location(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn get_location_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, LocationStoreError> {
+        LocationStoreOperations::new(self.connection)
+            .get_location_id_from_address(address, service_id)
     }
 }
 
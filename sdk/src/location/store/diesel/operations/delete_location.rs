@@ -26,6 +26,7 @@ pub(in crate::location::store::diesel) trait LocationStoreDeleteLocationOperatio
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), LocationStoreError>;
 }
 
@@ -37,10 +38,11 @@ impl<'a> LocationStoreDeleteLocationOperation
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), LocationStoreError> {
         self.conn.transaction::<_, LocationStoreError, _>(|| {
-            pg::delete_location(self.conn, address, current_commit_num)?;
-            pg::delete_location_attributes(self.conn, address, current_commit_num)?;
+            pg::delete_location(self.conn, address, current_commit_num, service_id)?;
+            pg::delete_location_attributes(self.conn, address, current_commit_num, service_id)?;
 
             Ok(())
         })
@@ -55,10 +57,11 @@ impl<'a> LocationStoreDeleteLocationOperation
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), LocationStoreError> {
         self.conn.transaction::<_, LocationStoreError, _>(|| {
-            sqlite::delete_location(self.conn, address, current_commit_num)?;
-            sqlite::delete_location_attributes(self.conn, address, current_commit_num)?;
+            sqlite::delete_location(self.conn, address, current_commit_num, service_id)?;
+            sqlite::delete_location_attributes(self.conn, address, current_commit_num, service_id)?;
 
             Ok(())
         })
@@ -73,32 +76,48 @@ mod pg {
         conn: &PgConnection,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> QueryResult<()> {
-        update(location::table)
+        let mut query = update(location::table)
             .filter(
                 location::location_address
                     .eq(address)
                     .and(location::end_commit_num.eq(MAX_COMMIT_NUM)),
             )
             .set(location::end_commit_num.eq(current_commit_num))
-            .execute(conn)
-            .map(|_| ())
+            .into_boxed();
+
+        if let Some(service_id) = service_id {
+            query = query.filter(location::service_id.eq(service_id));
+        } else {
+            query = query.filter(location::service_id.is_null());
+        }
+
+        query.execute(conn).map(|_| ())
     }
 
     pub fn delete_location_attributes(
         conn: &PgConnection,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> QueryResult<()> {
-        update(location_attribute::table)
+        let mut query = update(location_attribute::table)
             .filter(
                 location_attribute::location_address
                     .eq(address)
                     .and(location_attribute::end_commit_num.eq(MAX_COMMIT_NUM)),
             )
             .set(location_attribute::end_commit_num.eq(current_commit_num))
-            .execute(conn)
-            .map(|_| ())
+            .into_boxed();
+
+        if let Some(service_id) = service_id {
+            query = query.filter(location_attribute::service_id.eq(service_id));
+        } else {
+            query = query.filter(location_attribute::service_id.is_null());
+        }
+
+        query.execute(conn).map(|_| ())
     }
 }
 
@@ -110,31 +129,47 @@ mod sqlite {
         conn: &SqliteConnection,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> QueryResult<()> {
-        update(location::table)
+        let mut query = update(location::table)
             .filter(
                 location::location_address
                     .eq(address)
                     .and(location::end_commit_num.eq(MAX_COMMIT_NUM)),
             )
             .set(location::end_commit_num.eq(current_commit_num))
-            .execute(conn)
-            .map(|_| ())
+            .into_boxed();
+
+        if let Some(service_id) = service_id {
+            query = query.filter(location::service_id.eq(service_id));
+        } else {
+            query = query.filter(location::service_id.is_null());
+        }
+
+        query.execute(conn).map(|_| ())
     }
 
     pub fn delete_location_attributes(
         conn: &SqliteConnection,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> QueryResult<()> {
-        update(location_attribute::table)
+        let mut query = update(location_attribute::table)
             .filter(
                 location_attribute::location_address
                     .eq(address)
                     .and(location_attribute::end_commit_num.eq(MAX_COMMIT_NUM)),
             )
             .set(location_attribute::end_commit_num.eq(current_commit_num))
-            .execute(conn)
-            .map(|_| ())
+            .into_boxed();
+
+        if let Some(service_id) = service_id {
+            query = query.filter(location_attribute::service_id.eq(service_id));
+        } else {
+            query = query.filter(location_attribute::service_id.is_null());
+        }
+
+        query.execute(conn).map(|_| ())
     }
 }
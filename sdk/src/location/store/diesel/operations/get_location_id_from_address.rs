@@ -0,0 +1,76 @@
+// Copyright 2018-2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::LocationStoreOperations;
+use crate::location::store::diesel::{schema::location, LocationStoreError};
+
+use diesel::prelude::*;
+
+pub(in crate::location::store::diesel) trait LocationStoreGetLocationIdFromAddressOperation {
+    fn get_location_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, LocationStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> LocationStoreGetLocationIdFromAddressOperation
+    for LocationStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn get_location_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, LocationStoreError> {
+        // Deleted locations are included; an address is only ever derived from one ID
+        let mut query = location::table
+            .into_boxed()
+            .select(location::location_id)
+            .filter(location::location_address.eq(address));
+
+        if let Some(service_id) = service_id {
+            query = query.filter(location::service_id.eq(service_id));
+        } else {
+            query = query.filter(location::service_id.is_null());
+        }
+
+        Ok(query.first::<String>(self.conn).optional()?)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> LocationStoreGetLocationIdFromAddressOperation
+    for LocationStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_location_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, LocationStoreError> {
+        // Deleted locations are included; an address is only ever derived from one ID
+        let mut query = location::table
+            .into_boxed()
+            .select(location::location_id)
+            .filter(location::location_address.eq(address));
+
+        if let Some(service_id) = service_id {
+            query = query.filter(location::service_id.eq(service_id));
+        } else {
+            query = query.filter(location::service_id.is_null());
+        }
+
+        Ok(query.first::<String>(self.conn).optional()?)
+    }
+}
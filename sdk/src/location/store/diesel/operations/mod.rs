@@ -15,6 +15,7 @@
 pub(super) mod add_location;
 pub(super) mod delete_location;
 pub(super) mod get_location;
+pub(super) mod get_location_id_from_address;
 pub(super) mod list_locations;
 
 pub(super) struct LocationStoreOperations<'a, C> {
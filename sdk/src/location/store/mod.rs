@@ -115,11 +115,25 @@ pub trait LocationStore {
     ///
     ///  * `address` - The address of the record to be deleted
     ///  * `current_commit_num` - The current commit height
+    ///  * `service_id` - The service ID of the location to delete
     fn delete_location(
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), LocationStoreError>;
+
+    /// Fetches the ID of the location stored at a state address, even if it has been deleted
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the location
+    ///  * `service_id` - optional - The service ID the location was stored for
+    fn get_location_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, LocationStoreError>;
 }
 
 impl<LS> LocationStore for Box<LS>
@@ -151,7 +165,16 @@ where
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), LocationStoreError> {
-        (**self).delete_location(address, current_commit_num)
+        (**self).delete_location(address, current_commit_num, service_id)
+    }
+
+    fn get_location_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, LocationStoreError> {
+        (**self).get_location_id_from_address(address, service_id)
     }
 }
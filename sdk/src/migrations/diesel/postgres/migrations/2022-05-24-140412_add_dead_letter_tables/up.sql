@@ -0,0 +1,35 @@
+-- Copyright 2022 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+CREATE TABLE dead_letter_batches
+  (
+     service_id        VARCHAR(17) NOT NULL,
+     batch_id          VARCHAR(128) NOT NULL,
+     reason            TEXT NOT NULL,
+     created_at        BIGINT NOT NULL DEFAULT utc_timestamp(),
+     PRIMARY KEY (service_id, batch_id),
+     FOREIGN KEY (service_id, batch_id) REFERENCES batches(service_id, batch_id) ON DELETE CASCADE
+  );
+
+CREATE TABLE dead_letter_actions
+  (
+     id                    BIGSERIAL PRIMARY KEY,
+     service_id            VARCHAR(17) NOT NULL,
+     batch_id              VARCHAR(128) NOT NULL,
+     action                VARCHAR(16) NOT NULL,
+     actor                 VARCHAR(70) NOT NULL,
+     replacement_batch_id  VARCHAR(128),
+     created_at            BIGINT NOT NULL DEFAULT utc_timestamp()
+  );
@@ -0,0 +1,23 @@
+-- Copyright 2022 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+CREATE TABLE event_handler_checkpoint (
+    id BIGSERIAL PRIMARY KEY,
+    handler_id TEXT NOT NULL,
+    service_id TEXT,
+    commit_id VARCHAR(128) NOT NULL
+);
+
+CREATE INDEX event_handler_checkpoint_handler_id_idx ON event_handler_checkpoint (handler_id);
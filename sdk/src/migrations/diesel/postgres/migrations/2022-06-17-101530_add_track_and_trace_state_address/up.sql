@@ -0,0 +1,36 @@
+-- Copyright 2022 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+ALTER TABLE record ADD COLUMN state_address VARCHAR(70) NOT NULL DEFAULT '';
+ALTER TABLE property ADD COLUMN state_address VARCHAR(70) NOT NULL DEFAULT '';
+ALTER TABLE proposal ADD COLUMN state_address VARCHAR(70) NOT NULL DEFAULT '';
+
+-- Existing rows are given the address they are stored at, as computed by
+-- grid_sdk::track_and_trace::addressing
+UPDATE record SET state_address = 'a43b46ec'
+    || substr(encode(sha512(convert_to(record_id, 'UTF8')), 'hex'), 1, 62);
+
+UPDATE property SET state_address = 'a43b46ea'
+    || substr(encode(sha512(convert_to(record_id, 'UTF8')), 'hex'), 1, 36)
+    || substr(encode(sha512(convert_to(name, 'UTF8')), 'hex'), 1, 22)
+    || '0000';
+
+UPDATE proposal SET state_address = 'a43b46aa'
+    || substr(encode(sha512(convert_to(record_id, 'UTF8')), 'hex'), 1, 36)
+    || substr(encode(sha512(convert_to(receiving_agent, 'UTF8')), 'hex'), 1, 26);
+
+CREATE INDEX record_state_address_idx ON record (state_address);
+CREATE INDEX property_state_address_idx ON property (state_address);
+CREATE INDEX proposal_state_address_idx ON proposal (state_address);
@@ -16,12 +16,15 @@
 
 #[cfg(feature = "postgres")]
 use diesel::pg::PgConnection;
+use diesel_migrations::MigrationConnection;
 
 use crate::error::ResourceTemporarilyUnavailableError;
 use crate::migrations::error::MigrationsError;
 
 embed_migrations!("./src/migrations/diesel/postgres/migrations");
 
+include!(concat!(env!("OUT_DIR"), "/migrations/postgres_versions.rs"));
+
 /// Run database migrations to create Grid tables
 ///
 /// # Arguments
@@ -40,3 +43,32 @@ pub fn run_migrations(conn: &PgConnection) -> Result<(), MigrationsError> {
 
     Ok(())
 }
+
+/// Returns the version of the last migration applied to the database, or `None` if no
+/// migrations have been applied
+///
+/// # Arguments
+///
+/// * `conn` - Connection to database
+///
+#[cfg(all(feature = "postgres", feature = "diesel"))]
+pub fn get_migration_version(conn: &PgConnection) -> Result<Option<String>, MigrationsError> {
+    Ok(conn.latest_run_migration_version()?)
+}
+
+/// Returns the versions of the embedded migrations that have not been applied to the database
+///
+/// # Arguments
+///
+/// * `conn` - Connection to database
+///
+#[cfg(all(feature = "postgres", feature = "diesel"))]
+pub fn get_pending_migrations(conn: &PgConnection) -> Result<Vec<String>, MigrationsError> {
+    let applied = conn.previously_run_migration_versions()?;
+
+    Ok(MIGRATION_VERSIONS
+        .iter()
+        .filter(|version| !applied.contains(**version))
+        .map(ToString::to_string)
+        .collect())
+}
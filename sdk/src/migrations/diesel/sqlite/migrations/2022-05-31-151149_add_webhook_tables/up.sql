@@ -0,0 +1,55 @@
+-- Copyright 2022 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+CREATE TABLE webhook_subscriptions
+  (
+     id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+     signer_public_key  VARCHAR(70) NOT NULL,
+     url                TEXT NOT NULL,
+     secret             TEXT NOT NULL,
+     event_types        TEXT NOT NULL,
+     created_at         INTEGER NOT NULL DEFAULT (cast(strftime('%s') as int))
+  );
+
+CREATE INDEX webhook_subscriptions_signer_idx ON webhook_subscriptions (signer_public_key);
+
+CREATE TABLE webhook_deliveries
+  (
+     id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+     subscription_id    INTEGER NOT NULL,
+     service_id         VARCHAR(17) NOT NULL,
+     batch_id           VARCHAR(128) NOT NULL,
+     data_change_id     VARCHAR(256),
+     event_type         VARCHAR(16) NOT NULL,
+     previous_status    VARCHAR(16),
+     status             VARCHAR(16) NOT NULL DEFAULT 'Pending',
+     attempts           INTEGER NOT NULL DEFAULT 0,
+     next_attempt_at    INTEGER NOT NULL DEFAULT (cast(strftime('%s') as int)),
+     created_at         INTEGER NOT NULL DEFAULT (cast(strftime('%s') as int)),
+     delivered_at       INTEGER,
+     FOREIGN KEY (subscription_id) REFERENCES webhook_subscriptions(id) ON DELETE CASCADE
+  );
+
+CREATE INDEX webhook_deliveries_pending_idx ON webhook_deliveries (status, next_attempt_at);
+
+CREATE TABLE webhook_delivery_attempts
+  (
+     id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+     delivery_id        INTEGER NOT NULL,
+     response_code      INTEGER,
+     error              TEXT,
+     attempted_at       INTEGER NOT NULL DEFAULT (cast(strftime('%s') as int)),
+     FOREIGN KEY (delivery_id) REFERENCES webhook_deliveries(id) ON DELETE CASCADE
+  );
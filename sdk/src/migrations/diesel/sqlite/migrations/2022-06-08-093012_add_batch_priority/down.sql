@@ -0,0 +1,45 @@
+-- Copyright 2022 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+CREATE TABLE batches_temp
+  (
+     service_id        VARCHAR(17) NOT NULL,
+     batch_id          VARCHAR(128) NOT NULL,
+     data_change_id    VARCHAR(256),
+     signer_public_key VARCHAR(70) NOT NULL,
+     trace             BOOLEAN NOT NULL,
+     serialized_batch  BLOB NOT NULL,
+     submitted         BOOLEAN NOT NULL,
+     created_at        INTEGER NOT NULL DEFAULT (cast(strftime('%s') as int)),
+     PRIMARY KEY (service_id, batch_id)
+  );
+
+INSERT INTO batches_temp SELECT
+service_id,
+batch_id,
+data_change_id,
+signer_public_key,
+trace,
+serialized_batch,
+submitted,
+created_at
+FROM batches;
+
+DROP TABLE batches;
+
+ALTER TABLE batches_temp RENAME TO batches;
+
+CREATE UNIQUE INDEX batches_data_change_id_idx
+    ON batches (signer_public_key, service_id, data_change_id);
@@ -0,0 +1,24 @@
+-- Copyright 2022 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+CREATE TABLE pending_reindex (
+    id INTEGER PRIMARY KEY,
+    commit_id VARCHAR(128) NOT NULL,
+    commit_num BIGINT NOT NULL,
+    service_id TEXT,
+    namespace TEXT NOT NULL
+);
+
+CREATE INDEX pending_reindex_commit_id_idx ON pending_reindex (commit_id);
@@ -0,0 +1,25 @@
+-- Copyright 2022 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+CREATE TABLE service_lifecycle (
+    id INTEGER PRIMARY KEY,
+    service_id TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL,
+    data_status TEXT NOT NULL
+);
+
+-- The services that commits have already been indexed from are active
+INSERT INTO service_lifecycle (service_id, status, data_status)
+SELECT DISTINCT service_id, 'active', 'indexed' FROM commits WHERE service_id IS NOT NULL;
@@ -0,0 +1,22 @@
+-- Copyright 2022 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+DROP INDEX record_state_address_idx;
+DROP INDEX property_state_address_idx;
+DROP INDEX proposal_state_address_idx;
+
+ALTER TABLE record DROP COLUMN state_address;
+ALTER TABLE property DROP COLUMN state_address;
+ALTER TABLE proposal DROP COLUMN state_address;
@@ -12,14 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crypto::digest::Digest;
+use crypto::sha2::Sha512;
+use diesel::sql_types::Text;
 #[cfg(feature = "sqlite")]
 use diesel::sqlite::SqliteConnection;
+use diesel_migrations::MigrationConnection;
 
 use crate::error::ResourceTemporarilyUnavailableError;
 use crate::migrations::error::MigrationsError;
 
 embed_migrations!("./src/migrations/diesel/sqlite/migrations");
 
+include!(concat!(env!("OUT_DIR"), "/migrations/sqlite_versions.rs"));
+
+sql_function! {
+    /// Returns the hex encoded SHA-512 hash of a string. It is only called from migrations that
+    /// compute state addresses, as SQLite has no hash functions of its own.
+    #[allow(dead_code)]
+    fn sha512_hex(value: Text) -> Text;
+}
+
 /// Run database migrations to create Grid tables
 ///
 /// # Arguments
@@ -28,6 +41,12 @@ embed_migrations!("./src/migrations/diesel/sqlite/migrations");
 ///
 #[cfg(all(feature = "sqlite", feature = "diesel"))]
 pub fn run_migrations(conn: &SqliteConnection) -> Result<(), MigrationsError> {
+    sha512_hex::register_impl(conn, |value: String| {
+        let mut sha = Sha512::new();
+        sha.input_str(&value);
+        sha.result_str()
+    })?;
+
     embedded_migrations::run(conn).map_err(|err| {
         MigrationsError::ResourceTemporarilyUnavailableError(
             ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
@@ -38,3 +57,32 @@ pub fn run_migrations(conn: &SqliteConnection) -> Result<(), MigrationsError> {
 
     Ok(())
 }
+
+/// Returns the version of the last migration applied to the database, or `None` if no
+/// migrations have been applied
+///
+/// # Arguments
+///
+/// * `conn` - Connection to database
+///
+#[cfg(all(feature = "sqlite", feature = "diesel"))]
+pub fn get_migration_version(conn: &SqliteConnection) -> Result<Option<String>, MigrationsError> {
+    Ok(conn.latest_run_migration_version()?)
+}
+
+/// Returns the versions of the embedded migrations that have not been applied to the database
+///
+/// # Arguments
+///
+/// * `conn` - Connection to database
+///
+#[cfg(all(feature = "sqlite", feature = "diesel"))]
+pub fn get_pending_migrations(conn: &SqliteConnection) -> Result<Vec<String>, MigrationsError> {
+    let applied = conn.previously_run_migration_versions()?;
+
+    Ok(MIGRATION_VERSIONS
+        .iter()
+        .filter(|version| !applied.contains(**version))
+        .map(ToString::to_string)
+        .collect())
+}
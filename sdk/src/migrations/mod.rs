@@ -30,8 +30,16 @@
 mod diesel;
 pub mod error;
 
+#[cfg(feature = "postgres")]
+pub use self::diesel::postgres::get_migration_version as get_postgres_migration_version;
+#[cfg(feature = "postgres")]
+pub use self::diesel::postgres::get_pending_migrations as get_pending_postgres_migrations;
 #[cfg(feature = "postgres")]
 pub use self::diesel::postgres::run_migrations as run_postgres_migrations;
 
+#[cfg(feature = "sqlite")]
+pub use self::diesel::sqlite::get_migration_version as get_sqlite_migration_version;
+#[cfg(feature = "sqlite")]
+pub use self::diesel::sqlite::get_pending_migrations as get_pending_sqlite_migrations;
 #[cfg(feature = "sqlite")]
 pub use self::diesel::sqlite::run_migrations as run_sqlite_migrations;
@@ -41,6 +41,9 @@ use models::{
 use operations::add_agent::PikeStoreAddAgentOperation as _;
 use operations::add_organization::PikeStoreAddOrganizationOperation as _;
 use operations::add_role::PikeStoreAddRoleOperation as _;
+use operations::delete_agent::PikeStoreDeleteAgentOperation as _;
+use operations::delete_alternate_id_index_entry::PikeStoreDeleteAlternateIdIndexEntryOperation as _;
+use operations::delete_organization::PikeStoreDeleteOrganizationOperation as _;
 use operations::delete_role::PikeStoreDeleteRoleOperation as _;
 use operations::get_agent::PikeStoreGetAgentOperation as _;
 use operations::get_organization::PikeStoreGetOrganizationOperation as _;
@@ -199,13 +202,60 @@ impl PikeStore for DieselPikeStore<diesel::pg::PgConnection> {
         .get_organization(org_id, service_id)
     }
 
-    fn delete_role(&self, address: &str, current_commit_num: i64) -> Result<(), PikeStoreError> {
+    fn delete_role(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PikeStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_role(address, current_commit_num, service_id)
+    }
+
+    fn delete_agent(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PikeStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_agent(address, current_commit_num, service_id)
+    }
+
+    fn delete_organization(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
         PikeStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             PikeStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .delete_role(address, current_commit_num)
+        .delete_organization(address, current_commit_num, service_id)
+    }
+
+    fn delete_alternate_id_index_entry(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PikeStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_alternate_id_index_entry(address, current_commit_num, service_id)
     }
 }
 
@@ -340,13 +390,60 @@ impl PikeStore for DieselPikeStore<diesel::sqlite::SqliteConnection> {
         .get_organization(org_id, service_id)
     }
 
-    fn delete_role(&self, address: &str, current_commit_num: i64) -> Result<(), PikeStoreError> {
+    fn delete_role(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PikeStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_role(address, current_commit_num, service_id)
+    }
+
+    fn delete_agent(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PikeStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_agent(address, current_commit_num, service_id)
+    }
+
+    fn delete_organization(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PikeStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_organization(address, current_commit_num, service_id)
+    }
+
+    fn delete_alternate_id_index_entry(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
         PikeStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             PikeStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .delete_role(address, current_commit_num)
+        .delete_alternate_id_index_entry(address, current_commit_num, service_id)
     }
 }
 
@@ -452,8 +549,56 @@ impl<'a> PikeStore for DieselConnectionPikeStore<'a, diesel::pg::PgConnection> {
         PikeStoreOperations::new(self.connection).get_organization(org_id, service_id)
     }
 
-    fn delete_role(&self, address: &str, current_commit_num: i64) -> Result<(), PikeStoreError> {
-        PikeStoreOperations::new(self.connection).delete_role(address, current_commit_num)
+    fn delete_role(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(self.connection).delete_role(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn delete_agent(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(self.connection).delete_agent(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn delete_organization(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(self.connection).delete_organization(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn delete_alternate_id_index_entry(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(self.connection).delete_alternate_id_index_entry(
+            address,
+            current_commit_num,
+            service_id,
+        )
     }
 }
 
@@ -541,7 +686,55 @@ impl<'a> PikeStore for DieselConnectionPikeStore<'a, diesel::sqlite::SqliteConne
         PikeStoreOperations::new(self.connection).get_organization(org_id, service_id)
     }
 
-    fn delete_role(&self, address: &str, current_commit_num: i64) -> Result<(), PikeStoreError> {
-        PikeStoreOperations::new(self.connection).delete_role(address, current_commit_num)
+    fn delete_role(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(self.connection).delete_role(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn delete_agent(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(self.connection).delete_agent(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn delete_organization(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(self.connection).delete_organization(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn delete_alternate_id_index_entry(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        PikeStoreOperations::new(self.connection).delete_alternate_id_index_entry(
+            address,
+            current_commit_num,
+            service_id,
+        )
     }
 }
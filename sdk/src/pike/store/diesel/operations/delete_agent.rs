@@ -0,0 +1,242 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "delete agent" operation for the `DieselPikeStore`.
+
+use super::PikeStoreOperations;
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::InternalError;
+use crate::pike::store::diesel::{
+    schema::{pike_agent, pike_agent_role_assoc},
+    PikeStoreError,
+};
+
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::pike) trait PikeStoreDeleteAgentOperation {
+    fn delete_agent(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> PikeStoreDeleteAgentOperation for PikeStoreOperations<'a, diesel::pg::PgConnection> {
+    fn delete_agent(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        self.conn.transaction::<_, PikeStoreError, _>(|| {
+            let mut query = pike_agent::table
+                .into_boxed()
+                .select((pike_agent::id, pike_agent::public_key, pike_agent::org_id))
+                .filter(
+                    pike_agent::state_address
+                        .eq(address)
+                        .and(pike_agent::end_commit_num.eq(MAX_COMMIT_NUM)),
+                );
+
+            if let Some(service_id) = service_id {
+                query = query.filter(pike_agent::service_id.eq(service_id));
+            } else {
+                query = query.filter(pike_agent::service_id.is_null());
+            }
+
+            let agents = query
+                .load::<(i64, String, String)>(self.conn)
+                .map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+            for (id, public_key, org_id) in agents {
+                let mut query = pike_agent_role_assoc::table
+                    .into_boxed()
+                    .select(pike_agent_role_assoc::id)
+                    .filter(
+                        pike_agent_role_assoc::agent_public_key
+                            .eq(&public_key)
+                            .and(pike_agent_role_assoc::org_id.eq(&org_id))
+                            .and(pike_agent_role_assoc::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_agent_role_assoc::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_agent_role_assoc::service_id.is_null());
+                }
+
+                let role_assoc_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+                update(pike_agent_role_assoc::table)
+                    .filter(pike_agent_role_assoc::id.eq_any(role_assoc_ids))
+                    .set(pike_agent_role_assoc::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+
+                update(pike_agent::table)
+                    .filter(pike_agent::id.eq(id))
+                    .set(pike_agent::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> PikeStoreDeleteAgentOperation
+    for PikeStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn delete_agent(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        self.conn.transaction::<_, PikeStoreError, _>(|| {
+            let mut query = pike_agent::table
+                .into_boxed()
+                .select((pike_agent::id, pike_agent::public_key, pike_agent::org_id))
+                .filter(
+                    pike_agent::state_address
+                        .eq(address)
+                        .and(pike_agent::end_commit_num.eq(MAX_COMMIT_NUM)),
+                );
+
+            if let Some(service_id) = service_id {
+                query = query.filter(pike_agent::service_id.eq(service_id));
+            } else {
+                query = query.filter(pike_agent::service_id.is_null());
+            }
+
+            let agents = query
+                .load::<(i64, String, String)>(self.conn)
+                .map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+            for (id, public_key, org_id) in agents {
+                let mut query = pike_agent_role_assoc::table
+                    .into_boxed()
+                    .select(pike_agent_role_assoc::id)
+                    .filter(
+                        pike_agent_role_assoc::agent_public_key
+                            .eq(&public_key)
+                            .and(pike_agent_role_assoc::org_id.eq(&org_id))
+                            .and(pike_agent_role_assoc::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_agent_role_assoc::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_agent_role_assoc::service_id.is_null());
+                }
+
+                let role_assoc_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+                update(pike_agent_role_assoc::table)
+                    .filter(pike_agent_role_assoc::id.eq_any(role_assoc_ids))
+                    .set(pike_agent_role_assoc::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+
+                update(pike_agent::table)
+                    .filter(pike_agent::id.eq(id))
+                    .set(pike_agent::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+    use crate::pike::store::diesel::models::{NewAgentModel, NewRoleAssociationModel};
+
+    use diesel::{dsl::insert_into, sqlite::SqliteConnection};
+
+    /// Verify that deleting an agent ends it and its role associations at the given commit,
+    /// leaving the agent stored at the same address for another service untouched.
+    #[test]
+    fn test_delete_agent() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        let service_ids = vec![None, Some("service".to_string())];
+        for service_id in &service_ids {
+            insert_into(pike_agent::table)
+                .values(NewAgentModel {
+                    state_address: "agent-address".into(),
+                    public_key: "agent-key".into(),
+                    org_id: "org".into(),
+                    active: true,
+                    metadata: vec![],
+                    start_commit_num: 1,
+                    end_commit_num: MAX_COMMIT_NUM,
+                    service_id: service_id.clone(),
+                })
+                .execute(&conn)?;
+            insert_into(pike_agent_role_assoc::table)
+                .values(NewRoleAssociationModel {
+                    agent_public_key: "agent-key".into(),
+                    org_id: "org".into(),
+                    role_name: "admin".into(),
+                    start_commit_num: 1,
+                    end_commit_num: MAX_COMMIT_NUM,
+                    service_id: service_id.clone(),
+                })
+                .execute(&conn)?;
+        }
+
+        PikeStoreOperations::new(&conn).delete_agent("agent-address", 5, None)?;
+
+        let expected = vec![(None, 5), (Some("service".to_string()), MAX_COMMIT_NUM)];
+        assert_eq!(
+            pike_agent::table
+                .select((pike_agent::service_id, pike_agent::end_commit_num))
+                .order(pike_agent::id)
+                .load::<(Option<String>, i64)>(&conn)?,
+            expected
+        );
+        assert_eq!(
+            pike_agent_role_assoc::table
+                .select((
+                    pike_agent_role_assoc::service_id,
+                    pike_agent_role_assoc::end_commit_num
+                ))
+                .order(pike_agent_role_assoc::id)
+                .load::<(Option<String>, i64)>(&conn)?,
+            expected
+        );
+
+        Ok(())
+    }
+}
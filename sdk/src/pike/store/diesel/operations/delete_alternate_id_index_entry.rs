@@ -0,0 +1,198 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "delete alternate ID index entry" operation for the `DieselPikeStore`.
+
+use super::PikeStoreOperations;
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::InternalError;
+use crate::pike::{
+    addressing::compute_alternate_id_index_entry_address,
+    store::diesel::{schema::pike_organization_alternate_id, PikeStoreError},
+};
+
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::pike) trait PikeStoreDeleteAlternateIdIndexEntryOperation {
+    fn delete_alternate_id_index_entry(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> PikeStoreDeleteAlternateIdIndexEntryOperation
+    for PikeStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn delete_alternate_id_index_entry(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        self.conn.transaction::<_, PikeStoreError, _>(|| {
+            // Index entries are not stored with their address, so it is recomputed from the
+            // alternate IDs that are still current
+            let mut query = pike_organization_alternate_id::table
+                .into_boxed()
+                .select((
+                    pike_organization_alternate_id::id,
+                    pike_organization_alternate_id::alternate_id_type,
+                    pike_organization_alternate_id::alternate_id,
+                ))
+                .filter(pike_organization_alternate_id::end_commit_num.eq(MAX_COMMIT_NUM));
+
+            if let Some(service_id) = service_id {
+                query = query.filter(pike_organization_alternate_id::service_id.eq(service_id));
+            } else {
+                query = query.filter(pike_organization_alternate_id::service_id.is_null());
+            }
+
+            let alternate_id_ids = query
+                .load::<(i64, String, String)>(self.conn)
+                .map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?
+                .into_iter()
+                .filter(|(_, id_type, id)| {
+                    compute_alternate_id_index_entry_address(id_type, id) == address
+                })
+                .map(|(id, _, _)| id)
+                .collect::<Vec<i64>>();
+
+            update(pike_organization_alternate_id::table)
+                .filter(pike_organization_alternate_id::id.eq_any(alternate_id_ids))
+                .set(pike_organization_alternate_id::end_commit_num.eq(current_commit_num))
+                .execute(self.conn)
+                .map_err(PikeStoreError::from)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> PikeStoreDeleteAlternateIdIndexEntryOperation
+    for PikeStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn delete_alternate_id_index_entry(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        self.conn.transaction::<_, PikeStoreError, _>(|| {
+            // Index entries are not stored with their address, so it is recomputed from the
+            // alternate IDs that are still current
+            let mut query = pike_organization_alternate_id::table
+                .into_boxed()
+                .select((
+                    pike_organization_alternate_id::id,
+                    pike_organization_alternate_id::alternate_id_type,
+                    pike_organization_alternate_id::alternate_id,
+                ))
+                .filter(pike_organization_alternate_id::end_commit_num.eq(MAX_COMMIT_NUM));
+
+            if let Some(service_id) = service_id {
+                query = query.filter(pike_organization_alternate_id::service_id.eq(service_id));
+            } else {
+                query = query.filter(pike_organization_alternate_id::service_id.is_null());
+            }
+
+            let alternate_id_ids = query
+                .load::<(i64, String, String)>(self.conn)
+                .map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?
+                .into_iter()
+                .filter(|(_, id_type, id)| {
+                    compute_alternate_id_index_entry_address(id_type, id) == address
+                })
+                .map(|(id, _, _)| id)
+                .collect::<Vec<i64>>();
+
+            update(pike_organization_alternate_id::table)
+                .filter(pike_organization_alternate_id::id.eq_any(alternate_id_ids))
+                .set(pike_organization_alternate_id::end_commit_num.eq(current_commit_num))
+                .execute(self.conn)
+                .map_err(PikeStoreError::from)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+    use crate::pike::store::diesel::models::NewAlternateIdModel;
+
+    use diesel::{dsl::insert_into, sqlite::SqliteConnection};
+
+    /// Verify that deleting an alternate ID index entry ends only the alternate ID whose index
+    /// entry is stored at the given address.
+    #[test]
+    fn test_delete_alternate_id_index_entry() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        insert_into(pike_organization_alternate_id::table)
+            .values(vec![
+                NewAlternateIdModel {
+                    org_id: "org".into(),
+                    alternate_id_type: "gs1_company_prefix".into(),
+                    alternate_id: "0123".into(),
+                    start_commit_num: 1,
+                    end_commit_num: MAX_COMMIT_NUM,
+                    service_id: None,
+                },
+                NewAlternateIdModel {
+                    org_id: "org".into(),
+                    alternate_id_type: "gs1_company_prefix".into(),
+                    alternate_id: "4567".into(),
+                    start_commit_num: 1,
+                    end_commit_num: MAX_COMMIT_NUM,
+                    service_id: None,
+                },
+            ])
+            .execute(&conn)?;
+
+        PikeStoreOperations::new(&conn).delete_alternate_id_index_entry(
+            &compute_alternate_id_index_entry_address("gs1_company_prefix", "0123"),
+            5,
+            None,
+        )?;
+
+        assert_eq!(
+            pike_organization_alternate_id::table
+                .select((
+                    pike_organization_alternate_id::alternate_id,
+                    pike_organization_alternate_id::end_commit_num
+                ))
+                .order(pike_organization_alternate_id::id)
+                .load::<(String, i64)>(&conn)?,
+            vec![
+                ("0123".to_string(), 5),
+                ("4567".to_string(), MAX_COMMIT_NUM)
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "delete organization" operation for the `DieselPikeStore`.
+
+use super::PikeStoreOperations;
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::InternalError;
+use crate::pike::store::diesel::{
+    schema::{
+        pike_organization, pike_organization_alternate_id, pike_organization_location_assoc,
+        pike_organization_metadata,
+    },
+    PikeStoreError,
+};
+
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::pike) trait PikeStoreDeleteOrganizationOperation {
+    fn delete_organization(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> PikeStoreDeleteOrganizationOperation
+    for PikeStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn delete_organization(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        self.conn.transaction::<_, PikeStoreError, _>(|| {
+            let mut query = pike_organization::table
+                .into_boxed()
+                .select((pike_organization::id, pike_organization::org_id))
+                .filter(
+                    pike_organization::state_address
+                        .eq(address)
+                        .and(pike_organization::end_commit_num.eq(MAX_COMMIT_NUM)),
+                );
+
+            if let Some(service_id) = service_id {
+                query = query.filter(pike_organization::service_id.eq(service_id));
+            } else {
+                query = query.filter(pike_organization::service_id.is_null());
+            }
+
+            let orgs = query.load::<(i64, String)>(self.conn).map_err(|err| {
+                PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })?;
+
+            for (id, org_id) in orgs {
+                let mut query = pike_organization_metadata::table
+                    .into_boxed()
+                    .select(pike_organization_metadata::id)
+                    .filter(
+                        pike_organization_metadata::org_id
+                            .eq(&org_id)
+                            .and(pike_organization_metadata::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_organization_metadata::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_organization_metadata::service_id.is_null());
+                }
+
+                let metadata_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+                update(pike_organization_metadata::table)
+                    .filter(pike_organization_metadata::id.eq_any(metadata_ids))
+                    .set(pike_organization_metadata::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+
+                let mut query =
+                    pike_organization_alternate_id::table
+                        .into_boxed()
+                        .select(pike_organization_alternate_id::id)
+                        .filter(pike_organization_alternate_id::org_id.eq(&org_id).and(
+                            pike_organization_alternate_id::end_commit_num.eq(MAX_COMMIT_NUM),
+                        ));
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_organization_alternate_id::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_organization_alternate_id::service_id.is_null());
+                }
+
+                let alternate_id_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+                update(pike_organization_alternate_id::table)
+                    .filter(pike_organization_alternate_id::id.eq_any(alternate_id_ids))
+                    .set(pike_organization_alternate_id::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+
+                let mut query =
+                    pike_organization_location_assoc::table
+                        .into_boxed()
+                        .select(pike_organization_location_assoc::id)
+                        .filter(pike_organization_location_assoc::org_id.eq(&org_id).and(
+                            pike_organization_location_assoc::end_commit_num.eq(MAX_COMMIT_NUM),
+                        ));
+
+                if let Some(service_id) = service_id {
+                    query =
+                        query.filter(pike_organization_location_assoc::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_organization_location_assoc::service_id.is_null());
+                }
+
+                let location_assoc_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+                update(pike_organization_location_assoc::table)
+                    .filter(pike_organization_location_assoc::id.eq_any(location_assoc_ids))
+                    .set(pike_organization_location_assoc::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+
+                update(pike_organization::table)
+                    .filter(pike_organization::id.eq(id))
+                    .set(pike_organization::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> PikeStoreDeleteOrganizationOperation
+    for PikeStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn delete_organization(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        self.conn.transaction::<_, PikeStoreError, _>(|| {
+            let mut query = pike_organization::table
+                .into_boxed()
+                .select((pike_organization::id, pike_organization::org_id))
+                .filter(
+                    pike_organization::state_address
+                        .eq(address)
+                        .and(pike_organization::end_commit_num.eq(MAX_COMMIT_NUM)),
+                );
+
+            if let Some(service_id) = service_id {
+                query = query.filter(pike_organization::service_id.eq(service_id));
+            } else {
+                query = query.filter(pike_organization::service_id.is_null());
+            }
+
+            let orgs = query.load::<(i64, String)>(self.conn).map_err(|err| {
+                PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })?;
+
+            for (id, org_id) in orgs {
+                let mut query = pike_organization_metadata::table
+                    .into_boxed()
+                    .select(pike_organization_metadata::id)
+                    .filter(
+                        pike_organization_metadata::org_id
+                            .eq(&org_id)
+                            .and(pike_organization_metadata::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_organization_metadata::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_organization_metadata::service_id.is_null());
+                }
+
+                let metadata_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+                update(pike_organization_metadata::table)
+                    .filter(pike_organization_metadata::id.eq_any(metadata_ids))
+                    .set(pike_organization_metadata::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+
+                let mut query =
+                    pike_organization_alternate_id::table
+                        .into_boxed()
+                        .select(pike_organization_alternate_id::id)
+                        .filter(pike_organization_alternate_id::org_id.eq(&org_id).and(
+                            pike_organization_alternate_id::end_commit_num.eq(MAX_COMMIT_NUM),
+                        ));
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_organization_alternate_id::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_organization_alternate_id::service_id.is_null());
+                }
+
+                let alternate_id_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+                update(pike_organization_alternate_id::table)
+                    .filter(pike_organization_alternate_id::id.eq_any(alternate_id_ids))
+                    .set(pike_organization_alternate_id::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+
+                let mut query =
+                    pike_organization_location_assoc::table
+                        .into_boxed()
+                        .select(pike_organization_location_assoc::id)
+                        .filter(pike_organization_location_assoc::org_id.eq(&org_id).and(
+                            pike_organization_location_assoc::end_commit_num.eq(MAX_COMMIT_NUM),
+                        ));
+
+                if let Some(service_id) = service_id {
+                    query =
+                        query.filter(pike_organization_location_assoc::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_organization_location_assoc::service_id.is_null());
+                }
+
+                let location_assoc_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PikeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+                update(pike_organization_location_assoc::table)
+                    .filter(pike_organization_location_assoc::id.eq_any(location_assoc_ids))
+                    .set(pike_organization_location_assoc::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+
+                update(pike_organization::table)
+                    .filter(pike_organization::id.eq(id))
+                    .set(pike_organization::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PikeStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+    use crate::pike::store::diesel::models::{NewAlternateIdModel, NewOrganizationModel};
+
+    use diesel::{dsl::insert_into, sqlite::SqliteConnection};
+
+    /// Verify that deleting an organization ends it and its alternate IDs at the given commit,
+    /// leaving the organization stored at the same address for another service untouched.
+    #[test]
+    fn test_delete_organization() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        insert_into(pike_organization::table)
+            .values(vec![
+                NewOrganizationModel {
+                    state_address: "org-address".into(),
+                    org_id: "org".into(),
+                    name: "Org".into(),
+                    start_commit_num: 1,
+                    end_commit_num: MAX_COMMIT_NUM,
+                    service_id: None,
+                },
+                NewOrganizationModel {
+                    state_address: "org-address".into(),
+                    org_id: "org".into(),
+                    name: "Org".into(),
+                    start_commit_num: 1,
+                    end_commit_num: MAX_COMMIT_NUM,
+                    service_id: Some("service".into()),
+                },
+            ])
+            .execute(&conn)?;
+        insert_into(pike_organization_alternate_id::table)
+            .values(NewAlternateIdModel {
+                org_id: "org".into(),
+                alternate_id_type: "gs1_company_prefix".into(),
+                alternate_id: "0123".into(),
+                start_commit_num: 1,
+                end_commit_num: MAX_COMMIT_NUM,
+                service_id: None,
+            })
+            .execute(&conn)?;
+
+        PikeStoreOperations::new(&conn).delete_organization("org-address", 5, None)?;
+
+        assert_eq!(
+            pike_organization::table
+                .select((
+                    pike_organization::service_id,
+                    pike_organization::end_commit_num
+                ))
+                .order(pike_organization::id)
+                .load::<(Option<String>, i64)>(&conn)?,
+            vec![(None, 5), (Some("service".to_string()), MAX_COMMIT_NUM)]
+        );
+        assert_eq!(
+            pike_organization_alternate_id::table
+                .select(pike_organization_alternate_id::end_commit_num)
+                .load::<i64>(&conn)?,
+            vec![5]
+        );
+
+        Ok(())
+    }
+}
@@ -29,19 +29,35 @@ use crate::commits::MAX_COMMIT_NUM;
 use diesel::{dsl::update, prelude::*, result::Error as dsl_error};
 
 pub(in crate::pike) trait PikeStoreDeleteRoleOperation {
-    fn delete_role(&self, address: &str, current_commit_num: i64) -> Result<(), PikeStoreError>;
+    fn delete_role(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError>;
 }
 
 #[cfg(feature = "postgres")]
 impl<'a> PikeStoreDeleteRoleOperation for PikeStoreOperations<'a, diesel::pg::PgConnection> {
-    fn delete_role(&self, address: &str, current_commit_num: i64) -> Result<(), PikeStoreError> {
+    fn delete_role(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
         self.conn.transaction::<_, PikeStoreError, _>(|| {
-            let query = pike_role_state_address_assoc::table.into_boxed().filter(
+            let mut query = pike_role_state_address_assoc::table.into_boxed().filter(
                 pike_role_state_address_assoc::state_address
                     .eq(address)
                     .and(pike_role_state_address_assoc::end_commit_num.eq(MAX_COMMIT_NUM)),
             );
 
+            if let Some(service_id) = service_id {
+                query = query.filter(pike_role_state_address_assoc::service_id.eq(service_id));
+            } else {
+                query = query.filter(pike_role_state_address_assoc::service_id.is_null());
+            }
+
             let duplicate_role = query
                 .first::<RoleStateAddressAssociationModel>(self.conn)
                 .map(Some)
@@ -57,18 +73,27 @@ impl<'a> PikeStoreDeleteRoleOperation for PikeStoreOperations<'a, diesel::pg::Pg
                 })?;
 
             if let Some(existing_role) = duplicate_role {
-                update(pike_role::table)
+                let mut query = update(pike_role::table)
                     .filter(
                         pike_role::state_address
                             .eq(&existing_role.state_address)
                             .and(pike_role::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_role::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_role::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_role::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
 
-                update(pike_agent_role_assoc::table)
+                let mut query = update(pike_agent_role_assoc::table)
                     .filter(
                         pike_agent_role_assoc::role_name
                             .eq(&existing_role.name)
@@ -76,11 +101,20 @@ impl<'a> PikeStoreDeleteRoleOperation for PikeStoreOperations<'a, diesel::pg::Pg
                             .and(pike_agent_role_assoc::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_agent_role_assoc::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_agent_role_assoc::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_agent_role_assoc::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
 
-                update(pike_permissions::table)
+                let mut query = update(pike_permissions::table)
                     .filter(
                         pike_permissions::role_name
                             .eq(&existing_role.name)
@@ -88,11 +122,20 @@ impl<'a> PikeStoreDeleteRoleOperation for PikeStoreOperations<'a, diesel::pg::Pg
                             .and(pike_permissions::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_permissions::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_permissions::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_permissions::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
 
-                update(pike_inherit_from::table)
+                let mut query = update(pike_inherit_from::table)
                     .filter(
                         pike_inherit_from::role_name
                             .eq(&existing_role.name)
@@ -100,11 +143,20 @@ impl<'a> PikeStoreDeleteRoleOperation for PikeStoreOperations<'a, diesel::pg::Pg
                             .and(pike_inherit_from::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_inherit_from::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_inherit_from::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_inherit_from::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
 
-                update(pike_inherit_from::table)
+                let mut query = update(pike_inherit_from::table)
                     .filter(
                         pike_inherit_from::role_name
                             .eq(&existing_role.name)
@@ -112,11 +164,20 @@ impl<'a> PikeStoreDeleteRoleOperation for PikeStoreOperations<'a, diesel::pg::Pg
                             .and(pike_inherit_from::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_inherit_from::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_inherit_from::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_inherit_from::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
 
-                update(pike_allowed_orgs::table)
+                let mut query = update(pike_allowed_orgs::table)
                     .filter(
                         pike_allowed_orgs::role_name
                             .eq(&existing_role.name)
@@ -124,6 +185,15 @@ impl<'a> PikeStoreDeleteRoleOperation for PikeStoreOperations<'a, diesel::pg::Pg
                             .and(pike_allowed_orgs::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_allowed_orgs::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_allowed_orgs::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_allowed_orgs::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
@@ -138,14 +208,25 @@ impl<'a> PikeStoreDeleteRoleOperation for PikeStoreOperations<'a, diesel::pg::Pg
 impl<'a> PikeStoreDeleteRoleOperation
     for PikeStoreOperations<'a, diesel::sqlite::SqliteConnection>
 {
-    fn delete_role(&self, address: &str, current_commit_num: i64) -> Result<(), PikeStoreError> {
+    fn delete_role(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
         self.conn.transaction::<_, PikeStoreError, _>(|| {
-            let query = pike_role_state_address_assoc::table.into_boxed().filter(
+            let mut query = pike_role_state_address_assoc::table.into_boxed().filter(
                 pike_role_state_address_assoc::state_address
                     .eq(address)
                     .and(pike_role_state_address_assoc::end_commit_num.eq(MAX_COMMIT_NUM)),
             );
 
+            if let Some(service_id) = service_id {
+                query = query.filter(pike_role_state_address_assoc::service_id.eq(service_id));
+            } else {
+                query = query.filter(pike_role_state_address_assoc::service_id.is_null());
+            }
+
             let duplicate_role = query
                 .first::<RoleStateAddressAssociationModel>(self.conn)
                 .map(Some)
@@ -161,18 +242,27 @@ impl<'a> PikeStoreDeleteRoleOperation
                 })?;
 
             if let Some(existing_role) = duplicate_role {
-                update(pike_role::table)
+                let mut query = update(pike_role::table)
                     .filter(
                         pike_role::state_address
                             .eq(&existing_role.state_address)
                             .and(pike_role::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_role::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_role::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_role::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
 
-                update(pike_agent_role_assoc::table)
+                let mut query = update(pike_agent_role_assoc::table)
                     .filter(
                         pike_agent_role_assoc::role_name
                             .eq(&existing_role.name)
@@ -180,11 +270,20 @@ impl<'a> PikeStoreDeleteRoleOperation
                             .and(pike_agent_role_assoc::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_agent_role_assoc::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_agent_role_assoc::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_agent_role_assoc::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
 
-                update(pike_permissions::table)
+                let mut query = update(pike_permissions::table)
                     .filter(
                         pike_permissions::role_name
                             .eq(&existing_role.name)
@@ -192,11 +291,20 @@ impl<'a> PikeStoreDeleteRoleOperation
                             .and(pike_permissions::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_permissions::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_permissions::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_permissions::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
 
-                update(pike_inherit_from::table)
+                let mut query = update(pike_inherit_from::table)
                     .filter(
                         pike_inherit_from::role_name
                             .eq(&existing_role.name)
@@ -204,11 +312,20 @@ impl<'a> PikeStoreDeleteRoleOperation
                             .and(pike_inherit_from::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_inherit_from::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_inherit_from::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_inherit_from::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
 
-                update(pike_inherit_from::table)
+                let mut query = update(pike_inherit_from::table)
                     .filter(
                         pike_inherit_from::role_name
                             .eq(&existing_role.name)
@@ -216,11 +333,20 @@ impl<'a> PikeStoreDeleteRoleOperation
                             .and(pike_inherit_from::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_inherit_from::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_inherit_from::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_inherit_from::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
 
-                update(pike_allowed_orgs::table)
+                let mut query = update(pike_allowed_orgs::table)
                     .filter(
                         pike_allowed_orgs::role_name
                             .eq(&existing_role.name)
@@ -228,6 +354,15 @@ impl<'a> PikeStoreDeleteRoleOperation
                             .and(pike_allowed_orgs::end_commit_num.eq(MAX_COMMIT_NUM)),
                     )
                     .set(pike_allowed_orgs::end_commit_num.eq(current_commit_num))
+                    .into_boxed();
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(pike_allowed_orgs::service_id.eq(service_id));
+                } else {
+                    query = query.filter(pike_allowed_orgs::service_id.is_null());
+                }
+
+                query
                     .execute(self.conn)
                     .map(|_| ())
                     .map_err(PikeStoreError::from)?;
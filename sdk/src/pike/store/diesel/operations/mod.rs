@@ -17,6 +17,9 @@
 pub(super) mod add_agent;
 pub(super) mod add_organization;
 pub(super) mod add_role;
+pub(super) mod delete_agent;
+pub(super) mod delete_alternate_id_index_entry;
+pub(super) mod delete_organization;
 pub(super) mod delete_role;
 pub(super) mod get_agent;
 pub(super) mod get_organization;
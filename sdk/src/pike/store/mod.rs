@@ -435,7 +435,13 @@ pub trait PikeStore {
     ///
     ///  * `address` - The state address of the role to delete
     ///  * `current_commit_num` - The current commit number to update the chain record
-    fn delete_role(&self, address: &str, current_commit_num: i64) -> Result<(), PikeStoreError>;
+    ///  * `service_id` - The service ID of the role to delete
+    fn delete_role(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError>;
 
     /// Adds an organization to the underlying storage
     ///
@@ -469,6 +475,50 @@ pub trait PikeStore {
         org_id: &str,
         service_id: Option<&str>,
     ) -> Result<Option<Organization>, PikeStoreError>;
+
+    /// Deletes an agent from the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the agent to delete
+    ///  * `current_commit_num` - The current commit number to update the chain record
+    ///  * `service_id` - The service ID of the agent to delete
+    fn delete_agent(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError>;
+
+    /// Deletes an organization, along with its metadata, alternate IDs and locations,
+    /// from the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the organization to delete
+    ///  * `current_commit_num` - The current commit number to update the chain record
+    ///  * `service_id` - The service ID of the organization to delete
+    fn delete_organization(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError>;
+
+    /// Deletes the organization alternate ID an index entry refers to from the
+    /// underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the alternate ID index entry to delete
+    ///  * `current_commit_num` - The current commit number to update the chain record
+    ///  * `service_id` - The service ID of the alternate ID index entry to delete
+    fn delete_alternate_id_index_entry(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError>;
 }
 
 impl<PS> PikeStore for Box<PS>
@@ -523,8 +573,13 @@ where
         (**self).update_agent(agent)
     }
 
-    fn delete_role(&self, address: &str, current_commit_num: i64) -> Result<(), PikeStoreError> {
-        (**self).delete_role(address, current_commit_num)
+    fn delete_role(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        (**self).delete_role(address, current_commit_num, service_id)
     }
 
     fn add_organization(&self, org: Organization) -> Result<(), PikeStoreError> {
@@ -547,4 +602,31 @@ where
     ) -> Result<Option<Organization>, PikeStoreError> {
         (**self).get_organization(org_id, service_id)
     }
+
+    fn delete_agent(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        (**self).delete_agent(address, current_commit_num, service_id)
+    }
+
+    fn delete_organization(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        (**self).delete_organization(address, current_commit_num, service_id)
+    }
+
+    fn delete_alternate_id_index_entry(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PikeStoreError> {
+        (**self).delete_alternate_id_index_entry(address, current_commit_num, service_id)
+    }
 }
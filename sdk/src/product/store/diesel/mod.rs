@@ -20,8 +20,10 @@ use crate::error::ResourceTemporarilyUnavailableError;
 
 use operations::{
     add_product::AddProductOperation, delete_product::DeleteProductOperation,
-    get_product::GetProductOperation, list_products::ListProductsOperation,
-    update_product::UpdateProductOperation, ProductStoreOperations,
+    get_product::GetProductOperation,
+    get_product_id_from_address::GetProductIdFromAddressOperation,
+    list_products::ListProductsOperation, update_product::UpdateProductOperation,
+    ProductStoreOperations,
 };
 
 use diesel::connection::AnsiTransactionManager;
@@ -96,13 +98,27 @@ impl ProductStore for DieselProductStore<diesel::pg::PgConnection> {
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), ProductStoreError> {
         ProductStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             ProductStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .delete_product(address, current_commit_num)
+        .delete_product(address, current_commit_num, service_id)
+    }
+
+    fn get_product_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, ProductStoreError> {
+        ProductStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            ProductStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_product_id_from_address(address, service_id)
     }
 }
 
@@ -162,13 +178,27 @@ impl ProductStore for DieselProductStore<diesel::sqlite::SqliteConnection> {
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), ProductStoreError> {
         ProductStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
             ProductStoreError::ResourceTemporarilyUnavailableError(
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .delete_product(address, current_commit_num)
+        .delete_product(address, current_commit_num, service_id)
+    }
+
+    fn get_product_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, ProductStoreError> {
+        ProductStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            ProductStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_product_id_from_address(address, service_id)
     }
 }
 
@@ -230,8 +260,22 @@ impl<'a> ProductStore for DieselConnectionProductStore<'a, diesel::pg::PgConnect
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), ProductStoreError> {
-        ProductStoreOperations::new(self.connection).delete_product(address, current_commit_num)
+        ProductStoreOperations::new(self.connection).delete_product(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn get_product_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, ProductStoreError> {
+        ProductStoreOperations::new(self.connection)
+            .get_product_id_from_address(address, service_id)
     }
 }
 
@@ -275,7 +319,21 @@ impl<'a> ProductStore for DieselConnectionProductStore<'a, diesel::sqlite::Sqlit
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), ProductStoreError> {
-        ProductStoreOperations::new(self.connection).delete_product(address, current_commit_num)
+        ProductStoreOperations::new(self.connection).delete_product(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn get_product_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, ProductStoreError> {
+        ProductStoreOperations::new(self.connection)
+            .get_product_id_from_address(address, service_id)
     }
 }
@@ -28,6 +28,7 @@ pub(in crate::product) trait DeleteProductOperation {
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), ProductStoreError>;
 }
 
@@ -37,10 +38,11 @@ impl<'a> DeleteProductOperation for ProductStoreOperations<'a, diesel::pg::PgCon
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), ProductStoreError> {
         self.conn.transaction::<_, ProductStoreError, _>(|| {
-            pg::delete_product(self.conn, address, current_commit_num)?;
-            pg::delete_product_property_values(self.conn, address, current_commit_num)?;
+            pg::delete_product(self.conn, address, current_commit_num, service_id)?;
+            pg::delete_product_property_values(self.conn, address, current_commit_num, service_id)?;
 
             Ok(())
         })
@@ -53,10 +55,16 @@ impl<'a> DeleteProductOperation for ProductStoreOperations<'a, diesel::sqlite::S
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), ProductStoreError> {
         self.conn.transaction::<_, ProductStoreError, _>(|| {
-            sqlite::delete_product(self.conn, address, current_commit_num)?;
-            sqlite::delete_product_property_values(self.conn, address, current_commit_num)?;
+            sqlite::delete_product(self.conn, address, current_commit_num, service_id)?;
+            sqlite::delete_product_property_values(
+                self.conn,
+                address,
+                current_commit_num,
+                service_id,
+            )?;
 
             Ok(())
         })
@@ -71,32 +79,48 @@ mod pg {
         conn: &PgConnection,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> QueryResult<()> {
-        update(product::table)
+        let mut query = update(product::table)
             .filter(
                 product::product_address
                     .eq(address)
                     .and(product::end_commit_num.eq(MAX_COMMIT_NUM)),
             )
             .set(product::end_commit_num.eq(current_commit_num))
-            .execute(conn)
-            .map(|_| ())
+            .into_boxed();
+
+        if let Some(service_id) = service_id {
+            query = query.filter(product::service_id.eq(service_id));
+        } else {
+            query = query.filter(product::service_id.is_null());
+        }
+
+        query.execute(conn).map(|_| ())
     }
 
     pub fn delete_product_property_values(
         conn: &PgConnection,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> QueryResult<()> {
-        update(product_property_value::table)
+        let mut query = update(product_property_value::table)
             .filter(
                 product_property_value::product_address
                     .eq(address)
                     .and(product_property_value::end_commit_num.eq(MAX_COMMIT_NUM)),
             )
             .set(product_property_value::end_commit_num.eq(current_commit_num))
-            .execute(conn)
-            .map(|_| ())
+            .into_boxed();
+
+        if let Some(service_id) = service_id {
+            query = query.filter(product_property_value::service_id.eq(service_id));
+        } else {
+            query = query.filter(product_property_value::service_id.is_null());
+        }
+
+        query.execute(conn).map(|_| ())
     }
 }
 
@@ -108,31 +132,47 @@ mod sqlite {
         conn: &SqliteConnection,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> QueryResult<()> {
-        update(product::table)
+        let mut query = update(product::table)
             .filter(
                 product::product_address
                     .eq(address)
                     .and(product::end_commit_num.eq(MAX_COMMIT_NUM)),
             )
             .set(product::end_commit_num.eq(current_commit_num))
-            .execute(conn)
-            .map(|_| ())
+            .into_boxed();
+
+        if let Some(service_id) = service_id {
+            query = query.filter(product::service_id.eq(service_id));
+        } else {
+            query = query.filter(product::service_id.is_null());
+        }
+
+        query.execute(conn).map(|_| ())
     }
 
     pub fn delete_product_property_values(
         conn: &SqliteConnection,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> QueryResult<()> {
-        update(product_property_value::table)
+        let mut query = update(product_property_value::table)
             .filter(
                 product_property_value::product_address
                     .eq(address)
                     .and(product_property_value::end_commit_num.eq(MAX_COMMIT_NUM)),
             )
             .set(product_property_value::end_commit_num.eq(current_commit_num))
-            .execute(conn)
-            .map(|_| ())
+            .into_boxed();
+
+        if let Some(service_id) = service_id {
+            query = query.filter(product_property_value::service_id.eq(service_id));
+        } else {
+            query = query.filter(product_property_value::service_id.is_null());
+        }
+
+        query.execute(conn).map(|_| ())
     }
 }
@@ -0,0 +1,74 @@
+// Copyright 2018-2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::ProductStoreOperations;
+
+use crate::product::store::{diesel::schema::product, error::ProductStoreError};
+use diesel::prelude::*;
+
+pub(in crate::product) trait GetProductIdFromAddressOperation {
+    fn get_product_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, ProductStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> GetProductIdFromAddressOperation for ProductStoreOperations<'a, diesel::pg::PgConnection> {
+    fn get_product_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, ProductStoreError> {
+        // Deleted products are included; an address is only ever derived from one ID
+        let mut query = product::table
+            .into_boxed()
+            .select(product::product_id)
+            .filter(product::product_address.eq(address));
+
+        if let Some(service_id) = service_id {
+            query = query.filter(product::service_id.eq(service_id));
+        } else {
+            query = query.filter(product::service_id.is_null());
+        }
+
+        Ok(query.first::<String>(self.conn).optional()?)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> GetProductIdFromAddressOperation
+    for ProductStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_product_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, ProductStoreError> {
+        // Deleted products are included; an address is only ever derived from one ID
+        let mut query = product::table
+            .into_boxed()
+            .select(product::product_id)
+            .filter(product::product_address.eq(address));
+
+        if let Some(service_id) = service_id {
+            query = query.filter(product::service_id.eq(service_id));
+        } else {
+            query = query.filter(product::service_id.is_null());
+        }
+
+        Ok(query.first::<String>(self.conn).optional()?)
+    }
+}
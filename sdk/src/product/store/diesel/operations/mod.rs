@@ -15,6 +15,7 @@
 pub(super) mod add_product;
 pub(super) mod delete_product;
 pub(super) mod get_product;
+pub(super) mod get_product_id_from_address;
 pub(super) mod list_products;
 pub(super) mod update_product;
 
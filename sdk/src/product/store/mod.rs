@@ -563,11 +563,25 @@ pub trait ProductStore {
     ///
     ///  * `address` - The address of the record to be deleted
     ///  * `current_commit_num` - The current commit height
+    ///  * `service_id` - The service ID of the product to delete
     fn delete_product(
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), ProductStoreError>;
+
+    /// Gets the ID of the product stored at a state address, even if it has been deleted
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the product
+    ///  * `service_id` - The service ID the product was stored for
+    fn get_product_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, ProductStoreError>;
 }
 
 impl<PS> ProductStore for Box<PS>
@@ -608,7 +622,16 @@ where
         &self,
         address: &str,
         current_commit_num: i64,
+        service_id: Option<&str>,
     ) -> Result<(), ProductStoreError> {
-        (**self).delete_product(address, current_commit_num)
+        (**self).delete_product(address, current_commit_num, service_id)
+    }
+
+    fn get_product_id_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, ProductStoreError> {
+        (**self).get_product_id_from_address(address, service_id)
     }
 }
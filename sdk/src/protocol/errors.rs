@@ -17,12 +17,14 @@
 #[derive(Debug)]
 pub enum BuilderError {
     MissingField(String),
+    InvalidField(String),
 }
 
 impl std::fmt::Display for BuilderError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
             BuilderError::MissingField(ref s) => write!(f, "MissingField: {}", s),
+            BuilderError::InvalidField(ref s) => write!(f, "InvalidField: {}", s),
         }
     }
 }
@@ -31,6 +33,7 @@ impl std::error::Error for BuilderError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             BuilderError::MissingField(_) => None,
+            BuilderError::InvalidField(_) => None,
         }
     }
 }
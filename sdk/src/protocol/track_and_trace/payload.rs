@@ -17,6 +17,7 @@
 use protobuf::Message;
 use protobuf::RepeatedField;
 
+use std::collections::HashSet;
 use std::default::Default;
 
 use super::errors::BuilderError;
@@ -35,6 +36,8 @@ pub struct CreateRecordAction {
     record_id: String,
     schema: String,
     properties: Vec<PropertyValue>,
+    product_id: String,
+    location_id: String,
 }
 
 impl CreateRecordAction {
@@ -47,6 +50,14 @@ impl CreateRecordAction {
     pub fn properties(&self) -> &[PropertyValue] {
         &self.properties
     }
+    /// Returns the GTIN of the product to link, or an empty string if there is none
+    pub fn product_id(&self) -> &str {
+        &self.product_id
+    }
+    /// Returns the GLN of the location to link, or an empty string if there is none
+    pub fn location_id(&self) -> &str {
+        &self.location_id
+    }
 }
 
 /// Builder used to create a "create record" action
@@ -55,6 +66,8 @@ pub struct CreateRecordActionBuilder {
     record_id: Option<String>,
     schema: Option<String>,
     properties: Option<Vec<PropertyValue>>,
+    product_id: Option<String>,
+    location_id: Option<String>,
 }
 
 impl CreateRecordActionBuilder {
@@ -73,6 +86,14 @@ impl CreateRecordActionBuilder {
         self.properties = Some(value);
         self
     }
+    pub fn with_product_id(mut self, value: String) -> Self {
+        self.product_id = Some(value);
+        self
+    }
+    pub fn with_location_id(mut self, value: String) -> Self {
+        self.location_id = Some(value);
+        self
+    }
     pub fn build(self) -> Result<CreateRecordAction, BuilderError> {
         let record_id = self
             .record_id
@@ -83,10 +104,14 @@ impl CreateRecordActionBuilder {
         let properties = self
             .properties
             .ok_or_else(|| BuilderError::MissingField("properties".into()))?;
+        let product_id = self.product_id.unwrap_or_default();
+        let location_id = self.location_id.unwrap_or_default();
         Ok(CreateRecordAction {
             record_id,
             schema,
             properties,
+            product_id,
+            location_id,
         })
     }
 }
@@ -104,6 +129,8 @@ impl FromProto<track_and_trace_payload::CreateRecordAction> for CreateRecordActi
                 .cloned()
                 .map(PropertyValue::from_proto)
                 .collect::<Result<Vec<PropertyValue>, ProtoConversionError>>()?,
+            product_id: proto.get_product_id().to_string(),
+            location_id: proto.get_location_id().to_string(),
         })
     }
 }
@@ -122,6 +149,8 @@ impl FromNative<CreateRecordAction> for track_and_trace_payload::CreateRecordAct
                 .collect::<Result<Vec<protos::schema_state::PropertyValue>, ProtoConversionError>>(
                 )?,
         ));
+        proto.set_product_id(create_record_action.product_id().to_string());
+        proto.set_location_id(create_record_action.location_id().to_string());
 
         Ok(proto)
     }
@@ -229,6 +258,88 @@ impl IntoBytes for FinalizeRecordAction {
 impl IntoProto<track_and_trace_payload::FinalizeRecordAction> for FinalizeRecordAction {}
 impl IntoNative<FinalizeRecordAction> for track_and_trace_payload::FinalizeRecordAction {}
 
+/// Builder used to create "create record" actions for many records of the same schema
+#[derive(Default, Debug)]
+pub struct CreateRecordActionsBuilder {
+    schema: Option<String>,
+    records: Vec<CreateRecordActionBuilder>,
+}
+
+impl CreateRecordActionsBuilder {
+    pub fn new() -> Self {
+        CreateRecordActionsBuilder::default()
+    }
+    pub fn with_schema(mut self, value: String) -> Self {
+        self.schema = Some(value);
+        self
+    }
+    /// Adds a record to be created. The schema of the record is set by the bulk builder.
+    pub fn with_record(mut self, value: CreateRecordActionBuilder) -> Self {
+        self.records.push(value);
+        self
+    }
+    pub fn build(self) -> Result<Vec<CreateRecordAction>, BuilderError> {
+        let schema = self
+            .schema
+            .ok_or_else(|| BuilderError::MissingField("schema".into()))?;
+        if self.records.is_empty() {
+            return Err(BuilderError::MissingField("records".into()));
+        }
+
+        let mut record_ids = HashSet::new();
+        self.records
+            .into_iter()
+            .map(|record| {
+                let action = record.with_schema(schema.clone()).build()?;
+                if !record_ids.insert(action.record_id.clone()) {
+                    return Err(BuilderError::InvalidField(format!(
+                        "Duplicate record_id: {}",
+                        action.record_id
+                    )));
+                }
+                Ok(action)
+            })
+            .collect()
+    }
+}
+
+/// Builder used to create "finalize record" actions for many records
+#[derive(Default, Debug)]
+pub struct FinalizeRecordActionsBuilder {
+    record_ids: Vec<String>,
+}
+
+impl FinalizeRecordActionsBuilder {
+    pub fn new() -> Self {
+        FinalizeRecordActionsBuilder::default()
+    }
+    pub fn with_record_id(mut self, value: String) -> Self {
+        self.record_ids.push(value);
+        self
+    }
+    pub fn build(self) -> Result<Vec<FinalizeRecordAction>, BuilderError> {
+        if self.record_ids.is_empty() {
+            return Err(BuilderError::MissingField("record_ids".into()));
+        }
+
+        let mut record_ids = HashSet::new();
+        self.record_ids
+            .into_iter()
+            .map(|record_id| {
+                if !record_ids.insert(record_id.clone()) {
+                    return Err(BuilderError::InvalidField(format!(
+                        "Duplicate record_id: {}",
+                        record_id
+                    )));
+                }
+                FinalizeRecordActionBuilder::new()
+                    .with_record_id(record_id)
+                    .build()
+            })
+            .collect()
+    }
+}
+
 /// Native representation of an "update properties" action
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UpdatePropertiesAction {
@@ -344,6 +455,7 @@ pub struct CreateProposalAction {
     role: Role,
     properties: Vec<String>,
     terms: String,
+    expires_at: u64,
 }
 
 impl CreateProposalAction {
@@ -362,6 +474,9 @@ impl CreateProposalAction {
     pub fn terms(&self) -> &str {
         &self.terms
     }
+    pub fn expires_at(&self) -> &u64 {
+        &self.expires_at
+    }
 }
 
 /// Builder used to create a "create proposal" action
@@ -372,6 +487,7 @@ pub struct CreateProposalActionBuilder {
     role: Option<Role>,
     properties: Option<Vec<String>>,
     terms: Option<String>,
+    expires_at: Option<u64>,
 }
 
 impl CreateProposalActionBuilder {
@@ -398,6 +514,12 @@ impl CreateProposalActionBuilder {
         self.terms = Some(value);
         self
     }
+    /// Sets the time at which the proposal expires, as a Unix UTC timestamp. A value of 0, the
+    /// default, means the proposal does not expire.
+    pub fn with_expires_at(mut self, value: u64) -> Self {
+        self.expires_at = Some(value);
+        self
+    }
     pub fn build(self) -> Result<CreateProposalAction, BuilderError> {
         let record_id = self
             .record_id
@@ -414,12 +536,14 @@ impl CreateProposalActionBuilder {
         let terms = self
             .terms
             .ok_or_else(|| BuilderError::MissingField("terms".into()))?;
+        let expires_at = self.expires_at.unwrap_or(0);
         Ok(CreateProposalAction {
             record_id,
             receiving_agent,
             role,
             properties,
             terms,
+            expires_at,
         })
     }
 }
@@ -439,6 +563,7 @@ impl FromProto<track_and_trace_payload::CreateProposalAction> for CreateProposal
                 .map(String::from)
                 .collect(),
             terms: proto.get_terms().to_string(),
+            expires_at: proto.get_expires_at(),
         })
     }
 }
@@ -451,6 +576,7 @@ impl FromNative<CreateProposalAction> for track_and_trace_payload::CreateProposa
         proto.set_role(native.role().clone().into_proto()?);
         proto.set_properties(RepeatedField::from_vec(native.properties().to_vec()));
         proto.set_terms(native.terms().to_string());
+        proto.set_expires_at(*native.expires_at());
         Ok(proto)
     }
 }
@@ -762,6 +888,204 @@ impl IntoBytes for RevokeReporterAction {
 impl IntoProto<track_and_trace_payload::RevokeReporterAction> for RevokeReporterAction {}
 impl IntoNative<RevokeReporterAction> for track_and_trace_payload::RevokeReporterAction {}
 
+/// Native representation of a "cancel stale proposals" action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CancelStaleProposalsAction {
+    record_id: String,
+    receiving_agents: Vec<String>,
+}
+
+impl CancelStaleProposalsAction {
+    pub fn record_id(&self) -> &str {
+        &self.record_id
+    }
+    pub fn receiving_agents(&self) -> &[String] {
+        &self.receiving_agents
+    }
+}
+
+/// Builder used to create a "cancel stale proposals" action
+#[derive(Default, Debug)]
+pub struct CancelStaleProposalsActionBuilder {
+    record_id: Option<String>,
+    receiving_agents: Option<Vec<String>>,
+}
+
+impl CancelStaleProposalsActionBuilder {
+    pub fn new() -> Self {
+        CancelStaleProposalsActionBuilder::default()
+    }
+    pub fn with_record_id(mut self, value: String) -> Self {
+        self.record_id = Some(value);
+        self
+    }
+    pub fn with_receiving_agents(mut self, value: Vec<String>) -> Self {
+        self.receiving_agents = Some(value);
+        self
+    }
+    pub fn build(self) -> Result<CancelStaleProposalsAction, BuilderError> {
+        let record_id = self
+            .record_id
+            .ok_or_else(|| BuilderError::MissingField("record_id".into()))?;
+        let receiving_agents = self
+            .receiving_agents
+            .ok_or_else(|| BuilderError::MissingField("receiving_agents".into()))?;
+        Ok(CancelStaleProposalsAction {
+            record_id,
+            receiving_agents,
+        })
+    }
+}
+
+impl FromProto<track_and_trace_payload::CancelStaleProposalsAction> for CancelStaleProposalsAction {
+    fn from_proto(
+        proto: track_and_trace_payload::CancelStaleProposalsAction,
+    ) -> Result<Self, ProtoConversionError> {
+        Ok(CancelStaleProposalsAction {
+            record_id: proto.get_record_id().to_string(),
+            receiving_agents: proto.get_receiving_agents().to_vec(),
+        })
+    }
+}
+
+impl FromNative<CancelStaleProposalsAction>
+    for track_and_trace_payload::CancelStaleProposalsAction
+{
+    fn from_native(native: CancelStaleProposalsAction) -> Result<Self, ProtoConversionError> {
+        let mut proto = track_and_trace_payload::CancelStaleProposalsAction::new();
+        proto.set_record_id(native.record_id().to_string());
+        proto.set_receiving_agents(RepeatedField::from_vec(native.receiving_agents().to_vec()));
+        Ok(proto)
+    }
+}
+
+impl FromBytes<CancelStaleProposalsAction> for CancelStaleProposalsAction {
+    fn from_bytes(bytes: &[u8]) -> Result<CancelStaleProposalsAction, ProtoConversionError> {
+        let proto: track_and_trace_payload::CancelStaleProposalsAction =
+            Message::parse_from_bytes(bytes).map_err(|_| {
+                ProtoConversionError::SerializationError(
+                    "Unable to get CancelStaleProposalsAction from bytes".into(),
+                )
+            })?;
+        proto.into_native()
+    }
+}
+impl IntoBytes for CancelStaleProposalsAction {
+    fn into_bytes(self) -> Result<Vec<u8>, ProtoConversionError> {
+        let proto = self.into_proto()?;
+        let bytes = proto.write_to_bytes().map_err(|_| {
+            ProtoConversionError::SerializationError(
+                "Unable to get CancelStaleProposalsAction from bytes".into(),
+            )
+        })?;
+        Ok(bytes)
+    }
+}
+impl IntoProto<track_and_trace_payload::CancelStaleProposalsAction> for CancelStaleProposalsAction {}
+impl IntoNative<CancelStaleProposalsAction>
+    for track_and_trace_payload::CancelStaleProposalsAction
+{
+}
+
+/// Native representation of an "update record location" action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateRecordLocationAction {
+    record_id: String,
+    location_id: String,
+}
+
+impl UpdateRecordLocationAction {
+    pub fn record_id(&self) -> &str {
+        &self.record_id
+    }
+    /// Returns the GLN of the location the record is now at, or an empty string if the record is
+    /// no longer at a location
+    pub fn location_id(&self) -> &str {
+        &self.location_id
+    }
+}
+
+/// Builder used to create an "update record location" action
+#[derive(Default, Debug)]
+pub struct UpdateRecordLocationActionBuilder {
+    record_id: Option<String>,
+    location_id: Option<String>,
+}
+
+impl UpdateRecordLocationActionBuilder {
+    pub fn new() -> Self {
+        UpdateRecordLocationActionBuilder::default()
+    }
+    pub fn with_record_id(mut self, value: String) -> Self {
+        self.record_id = Some(value);
+        self
+    }
+    pub fn with_location_id(mut self, value: String) -> Self {
+        self.location_id = Some(value);
+        self
+    }
+    pub fn build(self) -> Result<UpdateRecordLocationAction, BuilderError> {
+        let record_id = self
+            .record_id
+            .ok_or_else(|| BuilderError::MissingField("record_id".into()))?;
+        let location_id = self.location_id.unwrap_or_default();
+        Ok(UpdateRecordLocationAction {
+            record_id,
+            location_id,
+        })
+    }
+}
+
+impl FromProto<track_and_trace_payload::UpdateRecordLocationAction> for UpdateRecordLocationAction {
+    fn from_proto(
+        proto: track_and_trace_payload::UpdateRecordLocationAction,
+    ) -> Result<Self, ProtoConversionError> {
+        Ok(UpdateRecordLocationAction {
+            record_id: proto.get_record_id().to_string(),
+            location_id: proto.get_location_id().to_string(),
+        })
+    }
+}
+
+impl FromNative<UpdateRecordLocationAction>
+    for track_and_trace_payload::UpdateRecordLocationAction
+{
+    fn from_native(native: UpdateRecordLocationAction) -> Result<Self, ProtoConversionError> {
+        let mut proto = track_and_trace_payload::UpdateRecordLocationAction::new();
+        proto.set_record_id(native.record_id().to_string());
+        proto.set_location_id(native.location_id().to_string());
+        Ok(proto)
+    }
+}
+
+impl FromBytes<UpdateRecordLocationAction> for UpdateRecordLocationAction {
+    fn from_bytes(bytes: &[u8]) -> Result<UpdateRecordLocationAction, ProtoConversionError> {
+        let proto: track_and_trace_payload::UpdateRecordLocationAction =
+            Message::parse_from_bytes(bytes).map_err(|_| {
+                ProtoConversionError::SerializationError(
+                    "Unable to get UpdateRecordLocationAction from bytes".into(),
+                )
+            })?;
+        proto.into_native()
+    }
+}
+impl IntoBytes for UpdateRecordLocationAction {
+    fn into_bytes(self) -> Result<Vec<u8>, ProtoConversionError> {
+        let proto = self.into_proto()?;
+        let bytes = proto.write_to_bytes().map_err(|_| {
+            ProtoConversionError::SerializationError(
+                "Unable to get UpdateRecordLocationAction from bytes".into(),
+            )
+        })?;
+        Ok(bytes)
+    }
+}
+impl IntoProto<track_and_trace_payload::UpdateRecordLocationAction> for UpdateRecordLocationAction {}
+impl IntoNative<UpdateRecordLocationAction>
+    for track_and_trace_payload::UpdateRecordLocationAction
+{
+}
+
 /// The Track and Trace payload action envelope
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
@@ -771,6 +1095,8 @@ pub enum Action {
     CreateProposal(CreateProposalAction),
     AnswerProposal(AnswerProposalAction),
     RevokeReporter(RevokeReporterAction),
+    CancelStaleProposals(CancelStaleProposalsAction),
+    UpdateRecordLocation(UpdateRecordLocationAction),
 }
 
 /// Native representation of a Track and Trace payload
@@ -842,6 +1168,12 @@ impl FromProto<track_and_trace_payload::TrackAndTracePayload> for TrackAndTraceP
             TrackAndTracePayload_Action::REVOKE_REPORTER => Action::RevokeReporter(
                 RevokeReporterAction::from_proto(proto.get_revoke_reporter().clone())?,
             ),
+            TrackAndTracePayload_Action::CANCEL_STALE_PROPOSALS => Action::CancelStaleProposals(
+                CancelStaleProposalsAction::from_proto(proto.get_cancel_stale_proposals().clone())?,
+            ),
+            TrackAndTracePayload_Action::UPDATE_RECORD_LOCATION => Action::UpdateRecordLocation(
+                UpdateRecordLocationAction::from_proto(proto.get_update_record_location().clone())?,
+            ),
             TrackAndTracePayload_Action::UNSET_ACTION => {
                 return Err(ProtoConversionError::InvalidTypeError(
                     "Cannot convert TrackAndTracePayload_Action with type unset.".to_string(),
@@ -887,6 +1219,14 @@ impl FromNative<TrackAndTracePayload> for track_and_trace_payload::TrackAndTrace
                 proto.set_action(TrackAndTracePayload_Action::REVOKE_REPORTER);
                 proto.set_revoke_reporter(payload.clone().into_proto()?);
             }
+            Action::CancelStaleProposals(payload) => {
+                proto.set_action(TrackAndTracePayload_Action::CANCEL_STALE_PROPOSALS);
+                proto.set_cancel_stale_proposals(payload.clone().into_proto()?);
+            }
+            Action::UpdateRecordLocation(payload) => {
+                proto.set_action(TrackAndTracePayload_Action::UPDATE_RECORD_LOCATION);
+                proto.set_update_record_location(payload.clone().into_proto()?);
+            }
         }
 
         Ok(proto)
@@ -949,12 +1289,16 @@ mod tests {
             .with_record_id("32".into())
             .with_schema("schema".into())
             .with_properties(vec![property_value.clone()])
+            .with_product_id("00012345600012".into())
+            .with_location_id("0123456789012".into())
             .build()
             .unwrap();
 
         assert_eq!(action.record_id(), "32");
         assert_eq!(action.schema(), "schema");
         assert!(action.properties().iter().any(|x| *x == property_value));
+        assert_eq!(action.product_id(), "00012345600012");
+        assert_eq!(action.location_id(), "0123456789012");
     }
 
     #[test]
@@ -972,6 +1316,7 @@ mod tests {
             .with_record_id("32".into())
             .with_schema("schema".into())
             .with_properties(vec![property_value.clone()])
+            .with_product_id("00012345600012".into())
             .build()
             .unwrap();
 
@@ -1001,6 +1346,76 @@ mod tests {
         test_from_bytes(action, FinalizeRecordAction::from_bytes);
     }
 
+    #[test]
+    /// Validate "create record" actions for many records are built correctly, sharing the
+    /// schema set on the bulk builder
+    fn test_create_record_actions_builder() {
+        let actions = CreateRecordActionsBuilder::new()
+            .with_schema("schema".into())
+            .with_record(
+                CreateRecordActionBuilder::new()
+                    .with_record_id("32".into())
+                    .with_properties(vec![]),
+            )
+            .with_record(
+                CreateRecordActionBuilder::new()
+                    .with_record_id("33".into())
+                    .with_properties(vec![])
+                    .with_product_id("00012345600012".into()),
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].record_id(), "32");
+        assert_eq!(actions[0].schema(), "schema");
+        assert_eq!(actions[1].record_id(), "33");
+        assert_eq!(actions[1].schema(), "schema");
+        assert_eq!(actions[1].product_id(), "00012345600012");
+    }
+
+    #[test]
+    /// Validate building "create record" actions fails if a record ID is repeated
+    fn test_create_record_actions_builder_duplicate() {
+        let result = CreateRecordActionsBuilder::new()
+            .with_schema("schema".into())
+            .with_record(
+                CreateRecordActionBuilder::new()
+                    .with_record_id("32".into())
+                    .with_properties(vec![]),
+            )
+            .with_record(
+                CreateRecordActionBuilder::new()
+                    .with_record_id("32".into())
+                    .with_properties(vec![]),
+            )
+            .build();
+
+        assert!(matches!(result, Err(BuilderError::InvalidField(_))));
+    }
+
+    #[test]
+    /// Validate "finalize record" actions for many records are built correctly, and that
+    /// building them fails if a record ID is repeated
+    fn test_finalize_record_actions_builder() {
+        let actions = FinalizeRecordActionsBuilder::new()
+            .with_record_id("32".into())
+            .with_record_id("33".into())
+            .build()
+            .unwrap();
+
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].record_id(), "32");
+        assert_eq!(actions[1].record_id(), "33");
+
+        let result = FinalizeRecordActionsBuilder::new()
+            .with_record_id("32".into())
+            .with_record_id("32".into())
+            .build();
+
+        assert!(matches!(result, Err(BuilderError::InvalidField(_))));
+    }
+
     #[test]
     /// Validate an "update properties" action is built correctly
     fn test_update_properties_action() {
@@ -1050,12 +1465,14 @@ mod tests {
             .with_role(Role::Custodian)
             .with_properties(vec!["egg".into()])
             .with_terms("term".to_string())
+            .with_expires_at(100)
             .build()
             .unwrap();
 
         assert_eq!(action.record_id(), "32");
         assert_eq!(action.receiving_agent(), "jim");
         assert_eq!(action.terms(), "term");
+        assert_eq!(*action.expires_at(), 100);
         assert_eq!(*action.role(), Role::Custodian);
         assert!(action.properties().iter().any(|x| x == "egg"));
     }
@@ -1070,6 +1487,7 @@ mod tests {
             .with_role(Role::Custodian)
             .with_properties(vec!["egg".into()])
             .with_terms("term".to_string())
+            .with_expires_at(100)
             .build()
             .unwrap();
 
@@ -1137,6 +1555,66 @@ mod tests {
         test_from_bytes(action, RevokeReporterAction::from_bytes);
     }
 
+    #[test]
+    /// Validate a "cancel stale proposals" action is built correctly
+    fn test_cancel_stale_proposals_action_builder() {
+        let action = CancelStaleProposalsActionBuilder::new()
+            .with_record_id("32".into())
+            .with_receiving_agents(vec!["jim".into()])
+            .build()
+            .unwrap();
+
+        assert_eq!(action.record_id(), "32");
+        assert!(action.receiving_agents().iter().any(|x| x == "jim"));
+    }
+
+    #[test]
+    /// Validate a "cancel stale proposals" action may be converted into bytes and back to its
+    /// native representation successfully
+    fn test_cancel_stale_proposals_action_bytes() {
+        let action = CancelStaleProposalsActionBuilder::new()
+            .with_record_id("32".into())
+            .with_receiving_agents(vec!["jim".into()])
+            .build()
+            .unwrap();
+
+        test_from_bytes(action, CancelStaleProposalsAction::from_bytes);
+    }
+
+    #[test]
+    /// Validate an "update record location" action is built correctly, and that the location
+    /// defaults to none
+    fn test_update_record_location_action_builder() {
+        let action = UpdateRecordLocationActionBuilder::new()
+            .with_record_id("32".into())
+            .with_location_id("0123456789012".into())
+            .build()
+            .unwrap();
+
+        assert_eq!(action.record_id(), "32");
+        assert_eq!(action.location_id(), "0123456789012");
+
+        let action = UpdateRecordLocationActionBuilder::new()
+            .with_record_id("32".into())
+            .build()
+            .unwrap();
+
+        assert_eq!(action.location_id(), "");
+    }
+
+    #[test]
+    /// Validate an "update record location" action may be converted into bytes and back to its
+    /// native representation successfully
+    fn test_update_record_location_action_bytes() {
+        let action = UpdateRecordLocationActionBuilder::new()
+            .with_record_id("32".into())
+            .with_location_id("0123456789012".into())
+            .build()
+            .unwrap();
+
+        test_from_bytes(action, UpdateRecordLocationAction::from_bytes);
+    }
+
     #[test]
     /// Validate that a Track and Trace payload is built correctly
     fn test_payload_builder() {
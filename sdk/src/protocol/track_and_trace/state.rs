@@ -848,6 +848,7 @@ pub struct Proposal {
     properties: Vec<String>,
     status: Status,
     terms: String,
+    expires_at: u64,
 }
 
 impl Proposal {
@@ -875,6 +876,14 @@ impl Proposal {
     pub fn terms(&self) -> &str {
         &self.terms
     }
+    pub fn expires_at(&self) -> &u64 {
+        &self.expires_at
+    }
+    /// Returns true if the proposal has an expiry time, and that time has been reached at the
+    /// given timestamp
+    pub fn is_expired(&self, timestamp: u64) -> bool {
+        self.expires_at != 0 && timestamp >= self.expires_at
+    }
     pub fn into_builder(self) -> ProposalBuilder {
         ProposalBuilder::new()
             .with_record_id(self.record_id)
@@ -885,6 +894,7 @@ impl Proposal {
             .with_properties(self.properties)
             .with_status(self.status)
             .with_terms(self.terms)
+            .with_expires_at(self.expires_at)
     }
 }
 
@@ -899,6 +909,7 @@ pub struct ProposalBuilder {
     properties: Option<Vec<String>>,
     status: Option<Status>,
     terms: Option<String>,
+    expires_at: Option<u64>,
 }
 
 impl ProposalBuilder {
@@ -937,6 +948,12 @@ impl ProposalBuilder {
         self.terms = Some(value);
         self
     }
+    /// Sets the time at which the proposal expires, as a Unix UTC timestamp. A value of 0, the
+    /// default, means the proposal does not expire.
+    pub fn with_expires_at(mut self, value: u64) -> Self {
+        self.expires_at = Some(value);
+        self
+    }
     pub fn build(self) -> Result<Proposal, BuilderError> {
         let record_id = self
             .record_id
@@ -962,6 +979,7 @@ impl ProposalBuilder {
         let terms = self
             .terms
             .ok_or_else(|| BuilderError::MissingField("terms".into()))?;
+        let expires_at = self.expires_at.unwrap_or(0);
         Ok(Proposal {
             record_id,
             timestamp,
@@ -971,6 +989,7 @@ impl ProposalBuilder {
             properties,
             status,
             terms,
+            expires_at,
         })
     }
 }
@@ -991,6 +1010,7 @@ impl FromProto<track_and_trace_state::Proposal> for Proposal {
                 .collect(),
             status: Status::from_proto(proto.get_status())?,
             terms: proto.get_terms().to_string(),
+            expires_at: proto.get_expires_at(),
         })
     }
 }
@@ -1007,6 +1027,7 @@ impl FromNative<Proposal> for track_and_trace_state::Proposal {
         proto.set_properties(RepeatedField::from_vec(native.properties().to_vec()));
         proto.set_status(native.status().clone().into_proto()?);
         proto.set_terms(native.terms().to_string());
+        proto.set_expires_at(*native.expires_at());
 
         Ok(proto)
     }
@@ -1243,6 +1264,8 @@ pub struct Record {
     owners: Vec<AssociatedAgent>,
     custodians: Vec<AssociatedAgent>,
     field_final: bool,
+    product_id: String,
+    location_id: String,
 }
 
 impl Record {
@@ -1261,6 +1284,14 @@ impl Record {
     pub fn field_final(&self) -> &bool {
         &self.field_final
     }
+    /// Returns the GTIN of the linked product, or an empty string if there is none
+    pub fn product_id(&self) -> &str {
+        &self.product_id
+    }
+    /// Returns the GLN of the linked location, or an empty string if there is none
+    pub fn location_id(&self) -> &str {
+        &self.location_id
+    }
     pub fn into_builder(self) -> RecordBuilder {
         RecordBuilder::new()
             .with_record_id(self.record_id)
@@ -1268,6 +1299,8 @@ impl Record {
             .with_owners(self.owners)
             .with_custodians(self.custodians)
             .with_field_final(self.field_final)
+            .with_product_id(self.product_id)
+            .with_location_id(self.location_id)
     }
 }
 
@@ -1279,6 +1312,8 @@ pub struct RecordBuilder {
     owners: Option<Vec<AssociatedAgent>>,
     custodians: Option<Vec<AssociatedAgent>>,
     field_final: Option<bool>,
+    product_id: Option<String>,
+    location_id: Option<String>,
 }
 
 impl RecordBuilder {
@@ -1305,6 +1340,14 @@ impl RecordBuilder {
         self.field_final = Some(value);
         self
     }
+    pub fn with_product_id(mut self, value: String) -> Self {
+        self.product_id = Some(value);
+        self
+    }
+    pub fn with_location_id(mut self, value: String) -> Self {
+        self.location_id = Some(value);
+        self
+    }
     pub fn build(self) -> Result<Record, BuilderError> {
         let record_id = self
             .record_id
@@ -1321,12 +1364,16 @@ impl RecordBuilder {
         let field_final = self
             .field_final
             .ok_or_else(|| BuilderError::MissingField("field_final".into()))?;
+        let product_id = self.product_id.unwrap_or_default();
+        let location_id = self.location_id.unwrap_or_default();
         Ok(Record {
             record_id,
             schema,
             owners,
             custodians,
             field_final,
+            product_id,
+            location_id,
         })
     }
 }
@@ -1349,6 +1396,8 @@ impl FromProto<track_and_trace_state::Record> for Record {
                 .map(AssociatedAgent::from_proto)
                 .collect::<Result<Vec<AssociatedAgent>, ProtoConversionError>>()?,
             field_final: proto.get_field_final(),
+            product_id: proto.get_product_id().to_string(),
+            location_id: proto.get_location_id().to_string(),
         })
     }
 }
@@ -1385,6 +1434,8 @@ impl FromNative<Record> for track_and_trace_state::Record {
             ),
         );
         proto.set_field_final(*native.field_final());
+        proto.set_product_id(native.product_id().to_string());
+        proto.set_location_id(native.location_id().to_string());
 
         Ok(proto)
     }
@@ -1989,6 +2040,7 @@ mod tests {
             .with_properties(vec!["wet".into()])
             .with_status(Status::Open)
             .with_terms("a term".into())
+            .with_expires_at(300)
             .build()
             .unwrap();
 
@@ -2000,6 +2052,9 @@ mod tests {
         assert!(proposal.properties().iter().any(|x| x == "wet"));
         assert_eq!(*proposal.status(), Status::Open);
         assert_eq!(proposal.terms(), "a term");
+        assert_eq!(*proposal.expires_at(), 300);
+        assert!(!proposal.is_expired(299));
+        assert!(proposal.is_expired(300));
     }
 
     #[test]
@@ -2135,6 +2190,7 @@ mod tests {
             .with_owners(vec![associated_agent.clone()])
             .with_custodians(vec![associated_agent.clone()])
             .with_field_final(false)
+            .with_product_id("00012345600012".into())
             .build()
             .unwrap();
 
@@ -2143,6 +2199,8 @@ mod tests {
         assert!(record.owners().iter().any(|x| *x == associated_agent));
         assert!(record.custodians().iter().any(|x| *x == associated_agent));
         assert_eq!(*record.field_final(), false);
+        assert_eq!(record.product_id(), "00012345600012");
+        assert_eq!(record.location_id(), "");
     }
 
     #[test]
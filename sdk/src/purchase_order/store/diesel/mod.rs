@@ -34,10 +34,13 @@ use models::{
 use crate::error::ResourceTemporarilyUnavailableError;
 
 use operations::add_purchase_order::PurchaseOrderStoreAddPurchaseOrderOperation as _;
+use operations::delete_purchase_order::PurchaseOrderStoreDeletePurchaseOrderOperation as _;
+use operations::delete_purchase_order_alternate_id::PurchaseOrderStoreDeletePurchaseOrderAlternateIdOperation as _;
 use operations::get_latest_revision_id::PurchaseOrderStoreGetLatestRevisionIdOperation as _;
 use operations::get_purchase_order::PurchaseOrderStoreGetPurchaseOrderOperation as _;
 use operations::get_purchase_order_version::PurchaseOrderStoreGetPurchaseOrderVersionOperation as _;
 use operations::get_purchase_order_version_revision::PurchaseOrderStoreGetPurchaseOrderRevisionOperation as _;
+use operations::get_uid_from_address::PurchaseOrderStoreGetUidFromAddressOperation as _;
 use operations::list_alternate_ids_for_purchase_order::PurchaseOrderStoreListAlternateIdsForPurchaseOrderOperation as _;
 use operations::list_purchase_order_version_revisions::PurchaseOrderStoreListPurchaseOrderRevisionsOperation as _;
 use operations::list_purchase_order_versions::PurchaseOrderStoreListPurchaseOrderVersionsOperation as _;
@@ -205,6 +208,47 @@ impl PurchaseOrderStore for DieselPurchaseOrderStore<diesel::pg::PgConnection> {
         })?)
         .get_latest_revision_id(purchase_order_uid, version_id, service_id)
     }
+
+    fn delete_purchase_order(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PurchaseOrderStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_purchase_order(address, current_commit_num, service_id)
+    }
+
+    fn get_uid_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PurchaseOrderStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_uid_from_address(address, service_id)
+    }
+
+    fn delete_purchase_order_alternate_id(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PurchaseOrderStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_purchase_order_alternate_id(address, current_commit_num, service_id)
+    }
 }
 
 #[cfg(feature = "sqlite")]
@@ -351,6 +395,47 @@ impl PurchaseOrderStore for DieselPurchaseOrderStore<diesel::sqlite::SqliteConne
         })?)
         .get_latest_revision_id(purchase_order_uid, version_id, service_id)
     }
+
+    fn delete_purchase_order(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PurchaseOrderStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_purchase_order(address, current_commit_num, service_id)
+    }
+
+    fn get_uid_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PurchaseOrderStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_uid_from_address(address, service_id)
+    }
+
+    fn delete_purchase_order_alternate_id(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            PurchaseOrderStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_purchase_order_alternate_id(address, current_commit_num, service_id)
+    }
 }
 
 pub struct DieselConnectionPurchaseOrderStore<'a, C>
@@ -488,6 +573,40 @@ impl<'a> PurchaseOrderStore for DieselConnectionPurchaseOrderStore<'a, diesel::p
             service_id,
         )
     }
+
+    fn delete_purchase_order(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(self.connection).delete_purchase_order(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn get_uid_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(self.connection).get_uid_from_address(address, service_id)
+    }
+
+    fn delete_purchase_order_alternate_id(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(self.connection).delete_purchase_order_alternate_id(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
 }
 
 #[cfg(feature = "sqlite")]
@@ -609,4 +728,38 @@ impl<'a> PurchaseOrderStore
             service_id,
         )
     }
+
+    fn delete_purchase_order(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(self.connection).delete_purchase_order(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn get_uid_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(self.connection).get_uid_from_address(address, service_id)
+    }
+
+    fn delete_purchase_order_alternate_id(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        PurchaseOrderStoreOperations::new(self.connection).delete_purchase_order_alternate_id(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
 }
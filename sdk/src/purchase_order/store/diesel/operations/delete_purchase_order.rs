@@ -0,0 +1,401 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "delete purchase order" operation for the `DieselPurchaseOrderStore`.
+
+use super::PurchaseOrderStoreOperations;
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::InternalError;
+use crate::purchase_order::{
+    addressing::compute_purchase_order_address,
+    store::diesel::{
+        schema::{
+            purchase_order, purchase_order_alternate_id, purchase_order_version,
+            purchase_order_version_revision,
+        },
+        PurchaseOrderStoreError,
+    },
+};
+
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::purchase_order::store::diesel) trait PurchaseOrderStoreDeletePurchaseOrderOperation {
+    fn delete_purchase_order(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> PurchaseOrderStoreDeletePurchaseOrderOperation
+    for PurchaseOrderStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn delete_purchase_order(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        self.conn.transaction::<_, PurchaseOrderStoreError, _>(|| {
+            let mut query = purchase_order::table
+                .into_boxed()
+                .select((purchase_order::id, purchase_order::purchase_order_uid))
+                .filter(purchase_order::end_commit_num.eq(MAX_COMMIT_NUM));
+
+            if let Some(service_id) = service_id {
+                query = query.filter(purchase_order::service_id.eq(service_id));
+            } else {
+                query = query.filter(purchase_order::service_id.is_null());
+            }
+
+            let orders = query
+                .load::<(i64, String)>(self.conn)
+                .map_err(|err| {
+                    PurchaseOrderStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?
+                .into_iter()
+                .filter(|(_, uid)| compute_purchase_order_address(uid) == address);
+
+            for (id, uid) in orders {
+                let mut query = purchase_order_version::table
+                    .into_boxed()
+                    .select(purchase_order_version::id)
+                    .filter(
+                        purchase_order_version::purchase_order_uid
+                            .eq(&uid)
+                            .and(purchase_order_version::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(purchase_order_version::service_id.eq(service_id));
+                } else {
+                    query = query.filter(purchase_order_version::service_id.is_null());
+                }
+
+                let version_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PurchaseOrderStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(purchase_order_version::table)
+                    .filter(purchase_order_version::id.eq_any(version_ids))
+                    .set(purchase_order_version::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PurchaseOrderStoreError::from)?;
+
+                let mut query = purchase_order_version_revision::table
+                    .into_boxed()
+                    .select(purchase_order_version_revision::id)
+                    .filter(
+                        purchase_order_version_revision::purchase_order_uid
+                            .eq(&uid)
+                            .and(
+                                purchase_order_version_revision::end_commit_num.eq(MAX_COMMIT_NUM),
+                            ),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query =
+                        query.filter(purchase_order_version_revision::service_id.eq(service_id));
+                } else {
+                    query = query.filter(purchase_order_version_revision::service_id.is_null());
+                }
+
+                let revision_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PurchaseOrderStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(purchase_order_version_revision::table)
+                    .filter(purchase_order_version_revision::id.eq_any(revision_ids))
+                    .set(purchase_order_version_revision::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PurchaseOrderStoreError::from)?;
+
+                let mut query = purchase_order_alternate_id::table
+                    .into_boxed()
+                    .select(purchase_order_alternate_id::id)
+                    .filter(
+                        purchase_order_alternate_id::purchase_order_uid
+                            .eq(&uid)
+                            .and(purchase_order_alternate_id::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(purchase_order_alternate_id::service_id.eq(service_id));
+                } else {
+                    query = query.filter(purchase_order_alternate_id::service_id.is_null());
+                }
+
+                let alternate_id_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PurchaseOrderStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(purchase_order_alternate_id::table)
+                    .filter(purchase_order_alternate_id::id.eq_any(alternate_id_ids))
+                    .set(purchase_order_alternate_id::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PurchaseOrderStoreError::from)?;
+
+                update(purchase_order::table)
+                    .filter(purchase_order::id.eq(id))
+                    .set(purchase_order::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PurchaseOrderStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> PurchaseOrderStoreDeletePurchaseOrderOperation
+    for PurchaseOrderStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn delete_purchase_order(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        self.conn.transaction::<_, PurchaseOrderStoreError, _>(|| {
+            let mut query = purchase_order::table
+                .into_boxed()
+                .select((purchase_order::id, purchase_order::purchase_order_uid))
+                .filter(purchase_order::end_commit_num.eq(MAX_COMMIT_NUM));
+
+            if let Some(service_id) = service_id {
+                query = query.filter(purchase_order::service_id.eq(service_id));
+            } else {
+                query = query.filter(purchase_order::service_id.is_null());
+            }
+
+            let orders = query
+                .load::<(i64, String)>(self.conn)
+                .map_err(|err| {
+                    PurchaseOrderStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?
+                .into_iter()
+                .filter(|(_, uid)| compute_purchase_order_address(uid) == address);
+
+            for (id, uid) in orders {
+                let mut query = purchase_order_version::table
+                    .into_boxed()
+                    .select(purchase_order_version::id)
+                    .filter(
+                        purchase_order_version::purchase_order_uid
+                            .eq(&uid)
+                            .and(purchase_order_version::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(purchase_order_version::service_id.eq(service_id));
+                } else {
+                    query = query.filter(purchase_order_version::service_id.is_null());
+                }
+
+                let version_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PurchaseOrderStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(purchase_order_version::table)
+                    .filter(purchase_order_version::id.eq_any(version_ids))
+                    .set(purchase_order_version::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PurchaseOrderStoreError::from)?;
+
+                let mut query = purchase_order_version_revision::table
+                    .into_boxed()
+                    .select(purchase_order_version_revision::id)
+                    .filter(
+                        purchase_order_version_revision::purchase_order_uid
+                            .eq(&uid)
+                            .and(
+                                purchase_order_version_revision::end_commit_num.eq(MAX_COMMIT_NUM),
+                            ),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query =
+                        query.filter(purchase_order_version_revision::service_id.eq(service_id));
+                } else {
+                    query = query.filter(purchase_order_version_revision::service_id.is_null());
+                }
+
+                let revision_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PurchaseOrderStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(purchase_order_version_revision::table)
+                    .filter(purchase_order_version_revision::id.eq_any(revision_ids))
+                    .set(purchase_order_version_revision::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PurchaseOrderStoreError::from)?;
+
+                let mut query = purchase_order_alternate_id::table
+                    .into_boxed()
+                    .select(purchase_order_alternate_id::id)
+                    .filter(
+                        purchase_order_alternate_id::purchase_order_uid
+                            .eq(&uid)
+                            .and(purchase_order_alternate_id::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(purchase_order_alternate_id::service_id.eq(service_id));
+                } else {
+                    query = query.filter(purchase_order_alternate_id::service_id.is_null());
+                }
+
+                let alternate_id_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    PurchaseOrderStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(purchase_order_alternate_id::table)
+                    .filter(purchase_order_alternate_id::id.eq_any(alternate_id_ids))
+                    .set(purchase_order_alternate_id::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PurchaseOrderStoreError::from)?;
+
+                update(purchase_order::table)
+                    .filter(purchase_order::id.eq(id))
+                    .set(purchase_order::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(PurchaseOrderStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+    use crate::purchase_order::addressing::compute_alternate_id_address;
+    use crate::purchase_order::store::diesel::operations::delete_purchase_order_alternate_id::PurchaseOrderStoreDeletePurchaseOrderAlternateIdOperation as _;
+
+    use diesel::{sql_query, sqlite::SqliteConnection};
+
+    /// Verify that purchase orders, along with their versions, revisions and alternate IDs, are
+    /// deleted by the address computed from their UID, ending only the matching records at the
+    /// given commit.
+    #[test]
+    fn test_delete_purchase_order() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        sql_query(
+            "INSERT INTO purchase_order \
+            (purchase_order_uid, workflow_state, buyer_org_id, seller_org_id, is_closed, \
+            created_at, workflow_id, start_commit_num, end_commit_num) VALUES \
+            ('PO-1', 'issued', 'buyer', 'seller', 0, 1, 'built-in::collaborative::v1', 1, \
+            9223372036854775807), \
+            ('PO-2', 'issued', 'buyer', 'seller', 0, 1, 'built-in::collaborative::v1', 1, \
+            9223372036854775807)",
+        )
+        .execute(&conn)?;
+        sql_query(
+            "INSERT INTO purchase_order_alternate_id \
+            (purchase_order_uid, alternate_id_type, alternate_id, start_commit_num, \
+            end_commit_num) VALUES \
+            ('PO-1', 'po_number', '1', 1, 9223372036854775807), \
+            ('PO-2', 'po_number', '2', 1, 9223372036854775807), \
+            ('PO-2', 'po_number', '3', 1, 9223372036854775807)",
+        )
+        .execute(&conn)?;
+        sql_query(
+            "INSERT INTO purchase_order_version \
+            (purchase_order_uid, version_id, is_draft, current_revision_id, workflow_state, \
+            start_commit_num, end_commit_num) VALUES \
+            ('PO-1', '1', 0, 1, 'proposed', 1, 9223372036854775807), \
+            ('PO-2', '1', 0, 1, 'proposed', 1, 9223372036854775807)",
+        )
+        .execute(&conn)?;
+        sql_query(
+            "INSERT INTO purchase_order_version_revision \
+            (purchase_order_uid, version_id, revision_id, order_xml_v3_4, submitter, \
+            created_at, start_commit_num, end_commit_num) VALUES \
+            ('PO-1', '1', 1, '<Order />', 'buyer', 1, 1, 9223372036854775807), \
+            ('PO-1', '1', 2, '<Order />', 'buyer', 2, 1, 9223372036854775807), \
+            ('PO-2', '1', 1, '<Order />', 'buyer', 1, 1, 9223372036854775807)",
+        )
+        .execute(&conn)?;
+
+        let ops = PurchaseOrderStoreOperations::new(&conn);
+        ops.delete_purchase_order(&compute_purchase_order_address("PO-1"), 5, None)?;
+        ops.delete_purchase_order_alternate_id(
+            &compute_alternate_id_address("PO-2", "po_number", "3"),
+            6,
+            None,
+        )?;
+
+        assert_eq!(
+            purchase_order::table
+                .select((
+                    purchase_order::purchase_order_uid,
+                    purchase_order::end_commit_num,
+                ))
+                .order(purchase_order::id)
+                .load::<(String, i64)>(&conn)?,
+            vec![
+                ("PO-1".to_string(), 5),
+                ("PO-2".to_string(), MAX_COMMIT_NUM)
+            ]
+        );
+        assert_eq!(
+            purchase_order_alternate_id::table
+                .select(purchase_order_alternate_id::end_commit_num)
+                .order(purchase_order_alternate_id::id)
+                .load::<i64>(&conn)?,
+            vec![5, MAX_COMMIT_NUM, 6]
+        );
+        assert_eq!(
+            purchase_order_version::table
+                .select(purchase_order_version::end_commit_num)
+                .order(purchase_order_version::id)
+                .load::<i64>(&conn)?,
+            vec![5, MAX_COMMIT_NUM]
+        );
+        assert_eq!(
+            purchase_order_version_revision::table
+                .select(purchase_order_version_revision::end_commit_num)
+                .order(purchase_order_version_revision::id)
+                .load::<i64>(&conn)?,
+            vec![5, 5, MAX_COMMIT_NUM]
+        );
+
+        Ok(())
+    }
+}
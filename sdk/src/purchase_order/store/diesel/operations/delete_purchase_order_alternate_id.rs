@@ -0,0 +1,186 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "delete purchase order alternate ID" operation for the
+//! `DieselPurchaseOrderStore`.
+
+use super::PurchaseOrderStoreOperations;
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::InternalError;
+use crate::purchase_order::{
+    addressing::compute_alternate_id_address,
+    store::diesel::{schema::purchase_order_alternate_id, PurchaseOrderStoreError},
+};
+
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::purchase_order::store::diesel) trait PurchaseOrderStoreDeletePurchaseOrderAlternateIdOperation
+{
+    fn delete_purchase_order_alternate_id(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> PurchaseOrderStoreDeletePurchaseOrderAlternateIdOperation
+    for PurchaseOrderStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn delete_purchase_order_alternate_id(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        self.conn.transaction::<_, PurchaseOrderStoreError, _>(|| {
+            let mut query = purchase_order_alternate_id::table
+                .into_boxed()
+                .select((
+                    purchase_order_alternate_id::id,
+                    purchase_order_alternate_id::purchase_order_uid,
+                    purchase_order_alternate_id::alternate_id_type,
+                    purchase_order_alternate_id::alternate_id,
+                ))
+                .filter(purchase_order_alternate_id::end_commit_num.eq(MAX_COMMIT_NUM));
+
+            if let Some(service_id) = service_id {
+                query = query.filter(purchase_order_alternate_id::service_id.eq(service_id));
+            } else {
+                query = query.filter(purchase_order_alternate_id::service_id.is_null());
+            }
+
+            let alternate_id_ids = query
+                .load::<(i64, String, String, String)>(self.conn)
+                .map_err(|err| {
+                    PurchaseOrderStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?
+                .into_iter()
+                .filter(|(_, uid, id_type, id)| {
+                    compute_alternate_id_address(uid, id_type, id) == address
+                })
+                .map(|(id, _, _, _)| id)
+                .collect::<Vec<i64>>();
+
+            update(purchase_order_alternate_id::table)
+                .filter(purchase_order_alternate_id::id.eq_any(alternate_id_ids))
+                .set(purchase_order_alternate_id::end_commit_num.eq(current_commit_num))
+                .execute(self.conn)
+                .map_err(PurchaseOrderStoreError::from)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> PurchaseOrderStoreDeletePurchaseOrderAlternateIdOperation
+    for PurchaseOrderStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn delete_purchase_order_alternate_id(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        self.conn.transaction::<_, PurchaseOrderStoreError, _>(|| {
+            let mut query = purchase_order_alternate_id::table
+                .into_boxed()
+                .select((
+                    purchase_order_alternate_id::id,
+                    purchase_order_alternate_id::purchase_order_uid,
+                    purchase_order_alternate_id::alternate_id_type,
+                    purchase_order_alternate_id::alternate_id,
+                ))
+                .filter(purchase_order_alternate_id::end_commit_num.eq(MAX_COMMIT_NUM));
+
+            if let Some(service_id) = service_id {
+                query = query.filter(purchase_order_alternate_id::service_id.eq(service_id));
+            } else {
+                query = query.filter(purchase_order_alternate_id::service_id.is_null());
+            }
+
+            let alternate_id_ids = query
+                .load::<(i64, String, String, String)>(self.conn)
+                .map_err(|err| {
+                    PurchaseOrderStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?
+                .into_iter()
+                .filter(|(_, uid, id_type, id)| {
+                    compute_alternate_id_address(uid, id_type, id) == address
+                })
+                .map(|(id, _, _, _)| id)
+                .collect::<Vec<i64>>();
+
+            update(purchase_order_alternate_id::table)
+                .filter(purchase_order_alternate_id::id.eq_any(alternate_id_ids))
+                .set(purchase_order_alternate_id::end_commit_num.eq(current_commit_num))
+                .execute(self.conn)
+                .map_err(PurchaseOrderStoreError::from)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+
+    use diesel::{sql_query, sqlite::SqliteConnection};
+
+    /// Verify that deleting a purchase order alternate ID ends only the alternate ID stored at
+    /// the given address, leaving the purchase order's other alternate IDs untouched.
+    #[test]
+    fn test_delete_purchase_order_alternate_id() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        sql_query(
+            "INSERT INTO purchase_order_alternate_id \
+            (purchase_order_uid, alternate_id_type, alternate_id, start_commit_num, \
+            end_commit_num) VALUES \
+            ('PO-1', 'po_number', '1', 1, 9223372036854775807), \
+            ('PO-1', 'po_number', '2', 1, 9223372036854775807)",
+        )
+        .execute(&conn)?;
+
+        PurchaseOrderStoreOperations::new(&conn).delete_purchase_order_alternate_id(
+            &compute_alternate_id_address("PO-1", "po_number", "2"),
+            5,
+            None,
+        )?;
+
+        assert_eq!(
+            purchase_order_alternate_id::table
+                .select((
+                    purchase_order_alternate_id::alternate_id,
+                    purchase_order_alternate_id::end_commit_num
+                ))
+                .order(purchase_order_alternate_id::id)
+                .load::<(String, i64)>(&conn)?,
+            vec![("1".to_string(), MAX_COMMIT_NUM), ("2".to_string(), 5)]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright 2018-2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "get UID from address" operation for the `DieselPurchaseOrderStore`.
+
+use super::PurchaseOrderStoreOperations;
+use crate::purchase_order::{
+    addressing::compute_purchase_order_address,
+    store::diesel::{schema::purchase_order, PurchaseOrderStoreError},
+};
+
+use diesel::prelude::*;
+
+pub(in crate::purchase_order::store::diesel) trait PurchaseOrderStoreGetUidFromAddressOperation {
+    fn get_uid_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, PurchaseOrderStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> PurchaseOrderStoreGetUidFromAddressOperation
+    for PurchaseOrderStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn get_uid_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, PurchaseOrderStoreError> {
+        // The address is not stored, so it is computed from each UID. Deleted purchase orders
+        // are included; an address is only ever derived from one UID.
+        let mut query = purchase_order::table
+            .into_boxed()
+            .select(purchase_order::purchase_order_uid)
+            .distinct();
+
+        if let Some(service_id) = service_id {
+            query = query.filter(purchase_order::service_id.eq(service_id));
+        } else {
+            query = query.filter(purchase_order::service_id.is_null());
+        }
+
+        Ok(query
+            .load::<String>(self.conn)?
+            .into_iter()
+            .find(|uid| compute_purchase_order_address(uid) == address))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> PurchaseOrderStoreGetUidFromAddressOperation
+    for PurchaseOrderStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_uid_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, PurchaseOrderStoreError> {
+        // The address is not stored, so it is computed from each UID. Deleted purchase orders
+        // are included; an address is only ever derived from one UID.
+        let mut query = purchase_order::table
+            .into_boxed()
+            .select(purchase_order::purchase_order_uid)
+            .distinct();
+
+        if let Some(service_id) = service_id {
+            query = query.filter(purchase_order::service_id.eq(service_id));
+        } else {
+            query = query.filter(purchase_order::service_id.is_null());
+        }
+
+        Ok(query
+            .load::<String>(self.conn)?
+            .into_iter()
+            .find(|uid| compute_purchase_order_address(uid) == address))
+    }
+}
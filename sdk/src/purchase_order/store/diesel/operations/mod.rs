@@ -16,10 +16,13 @@ pub(super) mod add_alternate_id;
 pub(super) mod add_purchase_order;
 mod add_purchase_order_version;
 mod add_purchase_order_version_revision;
+pub(super) mod delete_purchase_order;
+pub(super) mod delete_purchase_order_alternate_id;
 pub(super) mod get_latest_revision_id;
 pub(super) mod get_purchase_order;
 pub(super) mod get_purchase_order_version;
 pub(super) mod get_purchase_order_version_revision;
+pub(super) mod get_uid_from_address;
 mod get_uid_from_alternate_id;
 pub(super) mod list_alternate_ids_for_purchase_order;
 pub(super) mod list_purchase_order_version_revisions;
@@ -961,6 +961,48 @@ pub trait PurchaseOrderStore {
         version_id: &str,
         service_id: Option<&str>,
     ) -> Result<Option<i64>, PurchaseOrderStoreError>;
+
+    /// Deletes a purchase order, along with its versions, revisions and alternate IDs,
+    /// from the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the purchase order to delete
+    ///  * `current_commit_num` - The current commit number to update the chain record
+    ///  * `service_id` - The service ID of the purchase order to delete
+    fn delete_purchase_order(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError>;
+
+    /// Fetches the UID of the purchase order stored at a state address, even if it has been
+    /// deleted
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the purchase order
+    ///  * `service_id` - The service ID the purchase order was stored for
+    fn get_uid_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, PurchaseOrderStoreError>;
+
+    /// Deletes a purchase order alternate ID from the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the alternate ID to delete
+    ///  * `current_commit_num` - The current commit number to update the chain record
+    ///  * `service_id` - The service ID of the alternate ID to delete
+    fn delete_purchase_order_alternate_id(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError>;
 }
 
 impl<PS> PurchaseOrderStore for Box<PS>
@@ -1055,4 +1097,30 @@ where
     ) -> Result<Option<i64>, PurchaseOrderStoreError> {
         (**self).get_latest_revision_id(purchase_order_uid, version_id, service_id)
     }
+
+    fn delete_purchase_order(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        (**self).delete_purchase_order(address, current_commit_num, service_id)
+    }
+
+    fn get_uid_from_address(
+        &self,
+        address: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<String>, PurchaseOrderStoreError> {
+        (**self).get_uid_from_address(address, service_id)
+    }
+
+    fn delete_purchase_order_alternate_id(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), PurchaseOrderStoreError> {
+        (**self).delete_purchase_order_alternate_id(address, current_commit_num, service_id)
+    }
 }
@@ -0,0 +1,134 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use actix_web_4::{http::header, web, HttpRequest, HttpResponse};
+use futures::{stream, StreamExt};
+
+use crate::rest_api::resources::{
+    error::ErrorResponse,
+    event_stream::v1::{EventBroadcaster, EventFilter, StreamEvent, StreamEventType},
+};
+
+/// How often a comment is sent on an idle stream, so that proxies do not close the connection
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+const RESYNC_MESSAGE: &[u8] = b"event: resync\ndata: {}\n\n";
+const KEEP_ALIVE_MESSAGE: &[u8] = b": keep-alive\n\n";
+
+#[derive(Deserialize, Debug)]
+pub struct EventStreamQueryParams {
+    /// A comma-separated list of the event types to stream
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    pub org_id: Option<String>,
+    pub service_id: Option<String>,
+    /// The last commit number seen, which takes precedence over the `Last-Event-ID` header
+    pub last_commit_num: Option<i64>,
+}
+
+/// Streams batch status changes and committed changes to Grid entities as server-sent events
+///
+/// Committed changes carry their commit number as the event ID, so a client that reconnects,
+/// either with the `Last-Event-ID` header or the `last_commit_num` query parameter, is sent the
+/// changes it missed. If those changes are no longer available, a `resync` event is sent first and
+/// the client should list the resources it follows again.
+pub async fn stream_events(
+    req: HttpRequest,
+    broadcaster: web::Data<EventBroadcaster>,
+    query: web::Query<EventStreamQueryParams>,
+) -> HttpResponse {
+    let query = query.into_inner();
+
+    let mut filter = EventFilter::default();
+    if let Some(event_types) = query.event_type {
+        match event_types
+            .split(',')
+            .map(|event_type| event_type.trim().parse::<StreamEventType>())
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(event_types) => filter = filter.with_event_types(event_types),
+            Err(err) => {
+                return HttpResponse::BadRequest().json(ErrorResponse::new(400, &err.to_string()))
+            }
+        }
+    }
+    if let Some(org_id) = query.org_id {
+        filter = filter.with_org_id(org_id);
+    }
+    if let Some(service_id) = query.service_id {
+        filter = filter.with_service_id(service_id);
+    }
+
+    let last_commit_num = match query.last_commit_num {
+        Some(last_commit_num) => Some(last_commit_num),
+        None => match req.headers().get("Last-Event-ID") {
+            Some(value) => match value.to_str().ok().and_then(|id| id.parse::<i64>().ok()) {
+                Some(last_commit_num) => Some(last_commit_num),
+                None => {
+                    return HttpResponse::BadRequest().json(ErrorResponse::new(
+                        400,
+                        "Last-Event-ID must be a commit number",
+                    ))
+                }
+            },
+            None => None,
+        },
+    };
+
+    let subscription = match broadcaster.subscribe(filter, last_commit_num) {
+        Ok(subscription) => subscription,
+        Err(err) => {
+            error!("Unable to subscribe to events: {}", err);
+            return HttpResponse::InternalServerError()
+                .json(ErrorResponse::internal_error(Box::new(err)));
+        }
+    };
+
+    let resync = if subscription.resync_required() {
+        Some(Ok(web::Bytes::from_static(RESYNC_MESSAGE)))
+    } else {
+        None
+    };
+
+    let events = stream::unfold(subscription, |mut subscription| async move {
+        match tokio::time::timeout(KEEP_ALIVE_INTERVAL, subscription.recv()).await {
+            Ok(Some(event)) => Some((format_event(&event), subscription)),
+            Ok(None) => None,
+            Err(_) => Some((
+                Ok(web::Bytes::from_static(KEEP_ALIVE_MESSAGE)),
+                subscription,
+            )),
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(stream::iter(resync).chain(events))
+}
+
+/// Formats an event as a server-sent event, with its commit number, if any, as the event ID
+fn format_event(event: &StreamEvent) -> Result<web::Bytes, serde_json::Error> {
+    let data = serde_json::to_string(event)?;
+
+    let mut message = String::new();
+    if let Some(commit_num) = event.commit_num {
+        message.push_str(&format!("id: {}\n", commit_num));
+    }
+    message.push_str(&format!("event: {}\ndata: {}\n\n", event.event_type, data));
+
+    Ok(web::Bytes::from(message))
+}
@@ -16,6 +16,8 @@
 pub(crate) mod agents;
 #[cfg(feature = "rest-api-endpoint-batches")]
 pub(crate) mod batches;
+#[cfg(feature = "rest-api-endpoint-event-stream")]
+pub(crate) mod event_stream;
 #[cfg(feature = "rest-api-endpoint-location")]
 pub(crate) mod locations;
 #[cfg(feature = "rest-api-endpoint-organization")]
@@ -32,6 +34,8 @@ pub(crate) mod records;
 pub(crate) mod roles;
 #[cfg(feature = "rest-api-endpoint-schema")]
 pub(crate) mod schemas;
+#[cfg(feature = "rest-api-endpoint-service")]
+pub(crate) mod services;
 #[cfg(feature = "rest-api-endpoint-submit")]
 pub(crate) mod submit;
 
@@ -39,6 +43,8 @@ pub(crate) mod submit;
 pub use agents::*;
 #[cfg(feature = "rest-api-endpoint-batches")]
 pub use batches::*;
+#[cfg(feature = "rest-api-endpoint-event-stream")]
+pub use event_stream::*;
 #[cfg(feature = "rest-api-endpoint-location")]
 pub use locations::*;
 #[cfg(feature = "rest-api-endpoint-organization")]
@@ -55,6 +61,8 @@ pub use records::*;
 pub use roles::*;
 #[cfg(feature = "rest-api-endpoint-schema")]
 pub use schemas::*;
+#[cfg(feature = "rest-api-endpoint-service")]
+pub use services::*;
 #[cfg(feature = "rest-api-endpoint-submit")]
 pub use submit::*;
 
@@ -68,6 +76,7 @@ pub use submit::*;
     feature = "rest-api-endpoint-record",
     feature = "rest-api-endpoint-role",
     feature = "rest-api-endpoint-schema",
+    feature = "rest-api-endpoint-service",
     feature = "rest-api-endpoint-submit",
 ))]
 pub(in crate::rest_api) const DEFAULT_GRID_PROTOCOL_VERSION: &str = "1";
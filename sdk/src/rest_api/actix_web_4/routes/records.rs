@@ -20,6 +20,7 @@ use crate::rest_api::{
     actix_web_4::{request, AcceptServiceIdParam, QueryPaging, QueryServiceId, StoreState},
     resources::track_and_trace::v1,
 };
+use crate::track_and_trace::store::{ListProposalFilters, ListRecordFilters};
 
 use super::DEFAULT_GRID_PROTOCOL_VERSION;
 
@@ -52,6 +53,7 @@ pub async fn get_record(
 pub async fn list_records(
     req: HttpRequest,
     store_state: web::Data<StoreState>,
+    query_filters: web::Query<ListRecordFilters>,
     query_service_id: web::Query<QueryServiceId>,
     query_paging: web::Query<QueryPaging>,
     version: ProtocolVersion,
@@ -60,12 +62,14 @@ pub async fn list_records(
     let store = store_state.store_factory.get_grid_track_and_trace_store();
     match version {
         ProtocolVersion::V1 => {
+            let filters = query_filters.into_inner();
             let paging = query_paging.into_inner();
             let service_id = query_service_id.into_inner().service_id;
             match request::get_base_url(&req).and_then(|url| {
                 v1::list_records(
                     url,
                     store,
+                    filters,
                     service_id.as_deref(),
                     paging.offset(),
                     paging.limit(),
@@ -82,10 +86,20 @@ pub async fn list_records(
     }
 }
 
+/// Represents the time bucketing of a property's reported values passed to the endpoint in the
+/// query string
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryPropertyBuckets {
+    pub bucket: Option<u64>,
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+}
+
 pub async fn get_record_property_name(
     store_state: web::Data<StoreState>,
     path_variables: web::Path<(String, String)>,
     query: web::Query<QueryServiceId>,
+    query_buckets: web::Query<QueryPropertyBuckets>,
     version: ProtocolVersion,
     _: AcceptServiceIdParam,
 ) -> HttpResponse {
@@ -93,10 +107,49 @@ pub async fn get_record_property_name(
     match version {
         ProtocolVersion::V1 => {
             let (record_id, property_name) = path_variables.into_inner();
-            match v1::get_record_property(
+            let service_id = query.into_inner().service_id;
+            let buckets = query_buckets.into_inner();
+            let res = match buckets.bucket {
+                Some(bucket_size) => v1::get_record_property_aggregate(
+                    store,
+                    record_id,
+                    property_name,
+                    bucket_size,
+                    buckets.start,
+                    buckets.end,
+                    service_id.as_deref(),
+                )
+                .map(|res| HttpResponse::Ok().json(res)),
+                None => {
+                    v1::get_record_property(store, record_id, property_name, service_id.as_deref())
+                        .map(|res| HttpResponse::Ok().json(res))
+                }
+            };
+            match res {
+                Ok(res) => res,
+                Err(err) => HttpResponse::build(
+                    StatusCode::from_u16(err.status_code())
+                        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                )
+                .json(err),
+            }
+        }
+    }
+}
+
+pub async fn list_record_alerts(
+    store_state: web::Data<StoreState>,
+    record_id: web::Path<String>,
+    query: web::Query<QueryServiceId>,
+    version: ProtocolVersion,
+    _: AcceptServiceIdParam,
+) -> HttpResponse {
+    let store = store_state.store_factory.get_grid_track_and_trace_store();
+    match version {
+        ProtocolVersion::V1 => {
+            match v1::list_record_alerts(
                 store,
-                record_id,
-                property_name,
+                record_id.into_inner(),
                 query.into_inner().service_id.as_deref(),
             ) {
                 Ok(res) => HttpResponse::Ok().json(res),
@@ -110,6 +163,34 @@ pub async fn get_record_property_name(
     }
 }
 
+pub async fn list_record_proposals(
+    store_state: web::Data<StoreState>,
+    record_id: web::Path<String>,
+    query_filters: web::Query<ListProposalFilters>,
+    query_service_id: web::Query<QueryServiceId>,
+    version: ProtocolVersion,
+    _: AcceptServiceIdParam,
+) -> HttpResponse {
+    let store = store_state.store_factory.get_grid_track_and_trace_store();
+    match version {
+        ProtocolVersion::V1 => {
+            match v1::list_record_proposals(
+                store,
+                record_id.into_inner(),
+                query_filters.into_inner(),
+                query_service_id.into_inner().service_id.as_deref(),
+            ) {
+                Ok(res) => HttpResponse::Ok().json(res),
+                Err(err) => HttpResponse::build(
+                    StatusCode::from_u16(err.status_code())
+                        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                )
+                .json(err),
+            }
+        }
+    }
+}
+
 pub enum ProtocolVersion {
     V1,
 }
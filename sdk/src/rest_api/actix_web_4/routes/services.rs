@@ -0,0 +1,87 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use actix_web_4::{dev, http::StatusCode, web, Error, FromRequest, HttpRequest, HttpResponse};
+use futures::future;
+use futures_util::future::{FutureExt, LocalBoxFuture};
+
+use crate::rest_api::{actix_web_4::StoreState, resources::services::v1};
+
+use super::DEFAULT_GRID_PROTOCOL_VERSION;
+
+/// Represents a `circuit_id` passed to the endpoint in the query string
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryCircuitId {
+    pub circuit_id: Option<String>,
+}
+
+/// Lists the services that commits are indexed from, with the lifecycle state of each service's
+/// circuit, optionally only those of the circuit given by `circuit_id`
+pub async fn list_services(
+    store_state: web::Data<StoreState>,
+    query: web::Query<QueryCircuitId>,
+    version: ProtocolVersion,
+) -> HttpResponse {
+    let store = store_state.store_factory.get_grid_commit_store();
+    match version {
+        ProtocolVersion::V1 => {
+            match v1::list_services(store, query.into_inner().circuit_id.as_deref()) {
+                Ok(res) => HttpResponse::Ok().json(res),
+                Err(err) => HttpResponse::build(
+                    StatusCode::from_u16(err.status_code())
+                        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                )
+                .json(err),
+            }
+        }
+    }
+}
+
+pub enum ProtocolVersion {
+    V1,
+}
+
+impl FromRequest for ProtocolVersion {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut dev::Payload) -> Self::Future {
+        let protocol_version = match req
+            .headers()
+            .get("GridProtocolVersion")
+            .map(|ver| ver.to_str().map(String::from))
+        {
+            Some(Ok(ver)) => ver,
+            Some(Err(err)) => {
+                error!(
+                    "Failed to parse version using default version {}: {}",
+                    DEFAULT_GRID_PROTOCOL_VERSION, err
+                );
+                DEFAULT_GRID_PROTOCOL_VERSION.to_string()
+            }
+            None => {
+                warn!(
+                    "No Protocol version specified, defaulting to version {}",
+                    DEFAULT_GRID_PROTOCOL_VERSION
+                );
+                DEFAULT_GRID_PROTOCOL_VERSION.to_string()
+            }
+        };
+
+        match protocol_version.as_str() {
+            "1" => future::ok(ProtocolVersion::V1).boxed_local(),
+            _ => future::ok(ProtocolVersion::V1).boxed_local(),
+        }
+    }
+}
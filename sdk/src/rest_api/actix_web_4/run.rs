@@ -154,6 +154,14 @@ pub async fn run(
             app = app
                 .route("/record", web::get().to(records::list_records))
                 .route("/record/{record_id}", web::get().to(records::get_record))
+                .route(
+                    "/record/{record_id}/alerts",
+                    web::get().to(records::list_record_alerts),
+                )
+                .route(
+                    "/record/{record_id}/proposals",
+                    web::get().to(records::list_record_proposals),
+                )
                 .route(
                     "record/{record_id}/property/{property_name}",
                     web::get().to(records::get_record_property_name),
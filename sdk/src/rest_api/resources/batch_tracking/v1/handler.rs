@@ -0,0 +1,847 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+#[cfg(feature = "batch-tracking-wait")]
+use std::time::Duration;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "batch-tracking-wait")]
+use tokio::time::Instant;
+use transact::protocol::{
+    batch::{Batch, BatchHeader},
+    transaction::TransactionHeader,
+};
+use transact::protos::FromBytes;
+use url::{Host, Url};
+
+#[cfg(feature = "batch-tracking-wait")]
+use crate::batch_tracking::notifier::BatchStatusNotifier;
+use crate::batch_tracking::store::{
+    BatchPriority, BatchStatusName, BatchTrackingStore, BatchTrackingStoreError, DeadLetterAction,
+    TrackingBatch, TrackingBatchBuilder, NON_SPLINTER_SERVICE_ID_DEFAULT,
+};
+use crate::hex;
+use crate::rest_api::resources::error::ErrorResponse;
+
+use super::payloads::{
+    BatchStatusLink, BatchStatusListSlice, BatchStatusSlice, DeadLetterActionSlice,
+    DeadLetterBatchListSlice, DeadLetterBatchSlice, NewWebhookSubscriptionPayload,
+    WebhookDeliveryListSlice, WebhookDeliverySlice, WebhookSubscriptionListSlice,
+    WebhookSubscriptionSlice,
+};
+
+/// The status reported for a batch that has not yet been committed or found invalid
+#[cfg(feature = "batch-tracking-wait")]
+const PENDING_STATUS: &str = "PENDING";
+/// The longest time a waiting request goes without reading the statuses again
+#[cfg(feature = "batch-tracking-wait")]
+const WAIT_POLLING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Persists the batches in a serialized `BatchList` so that they can be submitted
+///
+/// A batch may be given a data change ID, which is unique for the signer and service the batch
+/// is submitted by. A batch whose data change ID has already been submitted is not added again;
+/// the batch that was submitted first is reported in the returned link instead, so that a client
+/// can safely resend a list after it failed to receive a response.
+///
+/// # Arguments
+///
+/// * `response_url` - The URL of the batch status resource, used to build the returned link
+/// * `store` - The store the batches are recorded in
+/// * `bytes` - A serialized `BatchList`
+/// * `service_id` - The service the batches are submitted to, if any
+/// * `data_change_ids` - A comma-separated list of data change IDs, one for each batch in the
+///   list, if any. An empty entry leaves the corresponding batch without a data change ID.
+/// * `priority` - The priority the batches are submitted with, one of `interactive`, `normal` or
+///   `bulk`; `normal` if not given
+pub fn submit_batches<'a>(
+    mut response_url: Url,
+    store: Box<dyn BatchTrackingStore + 'a>,
+    bytes: &[u8],
+    service_id: Option<&str>,
+    data_change_ids: Option<&str>,
+    priority: Option<&str>,
+) -> Result<BatchStatusLink, ErrorResponse> {
+    let batches = Vec::<Batch>::from_bytes(bytes).map_err(|err| {
+        ErrorResponse::new(
+            400,
+            &format!("Protobuf message was badly formatted. {}", err),
+        )
+    })?;
+
+    if batches.is_empty() {
+        return Err(ErrorResponse::new(400, "No batches provided"));
+    }
+
+    let data_change_ids = parse_data_change_ids(data_change_ids, batches.len())?;
+
+    let priority = priority
+        .map(BatchPriority::from_str)
+        .transpose()
+        .map_err(|err| ErrorResponse::new(400, &err.to_string()))?
+        .unwrap_or_default();
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .map_err(|err| ErrorResponse::internal_error(Box::new(err)))?;
+
+    let tracking_batches = batches
+        .into_iter()
+        .zip(data_change_ids.into_iter())
+        .map(|(batch, data_change_id)| {
+            let header = BatchHeader::from_bytes(batch.header()).map_err(|err| {
+                ErrorResponse::new(400, &format!("Batch header was badly formatted. {}", err))
+            })?;
+
+            let mut builder = TrackingBatchBuilder::default()
+                .with_signer_public_key(hex::to_hex(header.signer_public_key()))
+                .with_created_at(created_at)
+                .with_priority(priority)
+                .with_batch(batch);
+
+            if let Some(service_id) = service_id {
+                builder = builder.with_service_id(service_id.to_string());
+            }
+
+            if let Some(data_change_id) = data_change_id {
+                builder = builder.with_data_change_id(data_change_id.to_string());
+            }
+
+            builder
+                .build()
+                .map_err(|err| ErrorResponse::new(400, &format!("Invalid batch: {}", err)))
+        })
+        .collect::<Result<Vec<TrackingBatch>, ErrorResponse>>()?;
+
+    let mut batches = tracking_batches
+        .into_iter()
+        .map(|batch| find_submitted_batch(&*store, batch))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match add_new_batches(&*store, &batches) {
+        Ok(()) => (),
+        // A concurrent resend may have added batches with the same data change IDs first
+        Err(BatchTrackingStoreError::ConstraintViolationError(err)) => {
+            let new_count = batches.iter().filter(|batch| batch.is_new()).count();
+            batches = batches
+                .into_iter()
+                .map(|batch| match batch {
+                    SubmittedBatch::New(batch) => find_submitted_batch(&*store, batch),
+                    existing => Ok(existing),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if batches.iter().filter(|batch| batch.is_new()).count() == new_count {
+                return Err(add_batches_error_response(
+                    BatchTrackingStoreError::ConstraintViolationError(err),
+                ));
+            }
+
+            add_new_batches(&*store, &batches).map_err(add_batches_error_response)?;
+        }
+        Err(err) => return Err(add_batches_error_response(err)),
+    }
+
+    let mut ids = Vec::with_capacity(batches.len());
+    let mut existing = Vec::new();
+    for batch in batches {
+        match batch {
+            SubmittedBatch::New(batch) => ids.push(batch.batch_header().to_string()),
+            SubmittedBatch::Existing(batch) => {
+                ids.push(batch.batch_header().to_string());
+                existing.push(BatchStatusSlice::from(batch));
+            }
+        }
+    }
+
+    {
+        let mut query = response_url.query_pairs_mut();
+        query.append_pair("id", &ids.join(","));
+        if let Some(service_id) = service_id {
+            query.append_pair("service_id", service_id);
+        }
+    }
+
+    Ok(BatchStatusLink {
+        link: response_url.to_string(),
+        existing,
+    })
+}
+
+/// A batch in a submission, which is either new or was submitted before with the same data change
+/// ID by the same signer
+enum SubmittedBatch {
+    New(TrackingBatch),
+    Existing(TrackingBatch),
+}
+
+impl SubmittedBatch {
+    fn is_new(&self) -> bool {
+        matches!(self, SubmittedBatch::New(_))
+    }
+}
+
+/// Looks up the batch that was already submitted with the data change ID of the given batch, if
+/// any
+fn find_submitted_batch(
+    store: &dyn BatchTrackingStore,
+    batch: TrackingBatch,
+) -> Result<SubmittedBatch, ErrorResponse> {
+    let existing_batch = match batch.data_change_id() {
+        Some(data_change_id) => store
+            .get_batch_by_data_change_id(
+                data_change_id,
+                batch.signer_public_key(),
+                batch
+                    .service_id()
+                    .unwrap_or(NON_SPLINTER_SERVICE_ID_DEFAULT),
+            )
+            .map_err(store_error_response)?,
+        None => None,
+    };
+
+    Ok(match existing_batch {
+        Some(existing_batch) => SubmittedBatch::Existing(existing_batch),
+        None => SubmittedBatch::New(batch),
+    })
+}
+
+/// Adds the new batches of a submission to the store
+fn add_new_batches(
+    store: &dyn BatchTrackingStore,
+    batches: &[SubmittedBatch],
+) -> Result<(), BatchTrackingStoreError> {
+    let new_batches = batches
+        .iter()
+        .filter_map(|batch| match batch {
+            SubmittedBatch::New(batch) => Some(batch.clone()),
+            SubmittedBatch::Existing(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    if new_batches.is_empty() {
+        return Ok(());
+    }
+
+    store.add_batches(new_batches)
+}
+
+fn add_batches_error_response(err: BatchTrackingStoreError) -> ErrorResponse {
+    match err {
+        BatchTrackingStoreError::ConstraintViolationError(err) => {
+            ErrorResponse::new(400, &format!("Batch has already been submitted: {}", err))
+        }
+        BatchTrackingStoreError::ResourceTemporarilyUnavailableError(_) => {
+            ErrorResponse::new(503, "Service Unavailable")
+        }
+        err => ErrorResponse::internal_error(Box::new(err)),
+    }
+}
+
+/// Splits the `data_change_ids` of a submission into one optional data change ID per batch
+fn parse_data_change_ids(
+    data_change_ids: Option<&str>,
+    batch_count: usize,
+) -> Result<Vec<Option<&str>>, ErrorResponse> {
+    let data_change_ids = match data_change_ids {
+        Some(data_change_ids) => data_change_ids
+            .split(',')
+            .map(|id| if id.is_empty() { None } else { Some(id) })
+            .collect::<Vec<_>>(),
+        None => return Ok(vec![None; batch_count]),
+    };
+
+    if data_change_ids.len() != batch_count {
+        return Err(ErrorResponse::new(
+            400,
+            &format!(
+                "Expected one data change ID for each of the {} batches, found {}",
+                batch_count,
+                data_change_ids.len()
+            ),
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    if let Some(duplicate) = data_change_ids
+        .iter()
+        .flatten()
+        .find(|id| !seen.insert(**id))
+    {
+        return Err(ErrorResponse::new(
+            400,
+            &format!(
+                "Data change ID {} is used by more than one batch",
+                duplicate
+            ),
+        ));
+    }
+
+    Ok(data_change_ids)
+}
+
+/// Fetches the tracked status of a list of batches
+///
+/// # Arguments
+///
+/// * `link` - The URL of the request
+/// * `store` - The store the batches are recorded in
+/// * `ids` - A comma-separated list of batch IDs or data change IDs
+/// * `service_id` - The service the batches were submitted to, if any
+pub fn get_batch_statuses<'a>(
+    link: String,
+    store: Box<dyn BatchTrackingStore + 'a>,
+    ids: &str,
+    service_id: Option<&str>,
+) -> Result<BatchStatusListSlice, ErrorResponse> {
+    let ids = parse_batch_ids(ids)?;
+    let data = batch_status_slices(&*store, &ids, service_id)?;
+
+    Ok(BatchStatusListSlice { data, link })
+}
+
+/// Gets the statuses of tracked batches once none of them is pending, waiting up to `wait` for
+/// them to be committed or found invalid
+///
+/// The statuses are read again whenever the notifier reports a status change, and at least once a
+/// second to pick up changes recorded by other processes sharing the store.
+///
+/// # Arguments
+///
+/// * `link` - The link to the batch status resource returned with the statuses
+/// * `store` - The store the batches are tracked in
+/// * `ids` - A comma-separated list of batch IDs
+/// * `service_id` - The service the batches were submitted to, if any
+/// * `wait` - The longest time to wait for the batches to leave the pending status
+/// * `notifier` - Notified whenever a batch status is recorded in this process
+#[cfg(feature = "batch-tracking-wait")]
+pub async fn wait_for_batch_statuses<'a>(
+    link: String,
+    store: Box<dyn BatchTrackingStore + 'a>,
+    ids: &str,
+    service_id: Option<&str>,
+    wait: Duration,
+    notifier: &BatchStatusNotifier,
+) -> Result<BatchStatusListSlice, ErrorResponse> {
+    let ids = parse_batch_ids(ids)?;
+    let deadline = Instant::now() + wait;
+    // Subscribe before the first read, so that no change made after it is missed
+    let mut changes = notifier.subscribe();
+
+    loop {
+        let data = batch_status_slices(&*store, &ids, service_id)?;
+
+        let now = Instant::now();
+        if now >= deadline || data.iter().all(|status| status.status != PENDING_STATUS) {
+            return Ok(BatchStatusListSlice { data, link });
+        }
+
+        let _ = tokio::time::timeout(
+            std::cmp::min(deadline - now, WAIT_POLLING_INTERVAL),
+            changes.changed(),
+        )
+        .await;
+    }
+}
+
+fn parse_batch_ids(ids: &str) -> Result<Vec<&str>, ErrorResponse> {
+    let ids = ids
+        .split(',')
+        .filter(|id| !id.is_empty())
+        .collect::<Vec<_>>();
+
+    if ids.is_empty() {
+        return Err(ErrorResponse::new(400, "At least one batch ID is required"));
+    }
+
+    Ok(ids)
+}
+
+fn batch_status_slices(
+    store: &dyn BatchTrackingStore,
+    ids: &[&str],
+    service_id: Option<&str>,
+) -> Result<Vec<BatchStatusSlice>, ErrorResponse> {
+    let service_id = service_id.unwrap_or(NON_SPLINTER_SERVICE_ID_DEFAULT);
+
+    ids.iter()
+        .map(|id| {
+            store
+                .get_batch(id, service_id)
+                .map(|batch| {
+                    batch
+                        .map(BatchStatusSlice::from)
+                        .unwrap_or_else(|| BatchStatusSlice::unknown(id))
+                })
+                .map_err(|err| match err {
+                    BatchTrackingStoreError::ResourceTemporarilyUnavailableError(_) => {
+                        ErrorResponse::new(503, "Service Unavailable")
+                    }
+                    err => ErrorResponse::internal_error(Box::new(err)),
+                })
+        })
+        .collect()
+}
+
+/// Lists the batches that were dead-lettered because they could not be submitted
+///
+/// # Arguments
+///
+/// * `store` - The store the batches are recorded in
+/// * `service_id` - The service the batches were submitted to, if any
+pub fn list_dead_letter_batches<'a>(
+    store: Box<dyn BatchTrackingStore + 'a>,
+    service_id: Option<&str>,
+) -> Result<DeadLetterBatchListSlice, ErrorResponse> {
+    let service_id = service_id.unwrap_or(NON_SPLINTER_SERVICE_ID_DEFAULT);
+
+    let data = store
+        .list_dead_letter_batches(Some(service_id))
+        .map_err(store_error_response)?
+        .iter()
+        .map(DeadLetterBatchSlice::from)
+        .collect();
+
+    Ok(DeadLetterBatchListSlice { data })
+}
+
+/// Queues a dead-lettered batch for submission again
+///
+/// If a replacement is given, it must be a single batch that contains the transactions of the
+/// dead-lettered batch, in the same order and with the same payloads, re-signed with fresh
+/// nonces by the actor.
+///
+/// # Arguments
+///
+/// * `store` - The store the batches are recorded in
+/// * `batch_id` - The ID or data change ID of the dead-lettered batch
+/// * `service_id` - The service the batch was submitted to, if any
+/// * `actor` - The public key of the user replaying the batch
+/// * `admin_keys` - The public keys allowed to replay batches signed by other keys
+/// * `replacement` - optional - A serialized `BatchList` containing the re-signed batch
+pub fn replay_dead_letter_batch<'a>(
+    store: Box<dyn BatchTrackingStore + 'a>,
+    batch_id: &str,
+    service_id: Option<&str>,
+    actor: &str,
+    admin_keys: &[String],
+    replacement: Option<&[u8]>,
+) -> Result<DeadLetterActionSlice, ErrorResponse> {
+    let service_id_or_default = service_id.unwrap_or(NON_SPLINTER_SERVICE_ID_DEFAULT);
+
+    let original = store
+        .get_batch(batch_id, service_id_or_default)
+        .map_err(store_error_response)?
+        .ok_or_else(|| ErrorResponse::new(404, &format!("Batch {} not found", batch_id)))?;
+
+    check_dead_letter_actor(&original, actor, admin_keys)?;
+
+    let replacement = replacement
+        .map(|bytes| make_replacement_batch(&original, bytes, service_id, actor))
+        .transpose()?;
+    let replacement_batch_id = replacement
+        .as_ref()
+        .map(|batch| batch.batch_header().to_string());
+
+    store
+        .replay_dead_letter_batch(batch_id, service_id_or_default, actor, replacement)
+        .map_err(store_error_response)?;
+
+    Ok(DeadLetterActionSlice {
+        batch_id: original.batch_header().to_string(),
+        action: DeadLetterAction::Replay.to_string(),
+        actor: actor.to_string(),
+        replacement_batch_id,
+    })
+}
+
+/// Removes a dead-lettered batch without submitting it
+///
+/// # Arguments
+///
+/// * `store` - The store the batches are recorded in
+/// * `batch_id` - The ID or data change ID of the dead-lettered batch
+/// * `service_id` - The service the batch was submitted to, if any
+/// * `actor` - The public key of the user discarding the batch
+/// * `admin_keys` - The public keys allowed to discard batches signed by other keys
+pub fn discard_dead_letter_batch<'a>(
+    store: Box<dyn BatchTrackingStore + 'a>,
+    batch_id: &str,
+    service_id: Option<&str>,
+    actor: &str,
+    admin_keys: &[String],
+) -> Result<DeadLetterActionSlice, ErrorResponse> {
+    let service_id = service_id.unwrap_or(NON_SPLINTER_SERVICE_ID_DEFAULT);
+
+    let original = store
+        .get_batch(batch_id, service_id)
+        .map_err(store_error_response)?
+        .ok_or_else(|| ErrorResponse::new(404, &format!("Batch {} not found", batch_id)))?;
+
+    check_dead_letter_actor(&original, actor, admin_keys)?;
+
+    store
+        .discard_dead_letter_batch(batch_id, service_id, actor)
+        .map_err(store_error_response)?;
+
+    Ok(DeadLetterActionSlice {
+        batch_id: original.batch_header().to_string(),
+        action: DeadLetterAction::Discard.to_string(),
+        actor: actor.to_string(),
+        replacement_batch_id: None,
+    })
+}
+
+/// Subscribes a webhook to status changes of the batches signed by a submitter
+///
+/// # Arguments
+///
+/// * `store` - The store the subscription is recorded in
+/// * `signer_public_key` - The public key of the submitter making the request
+/// * `payload` - The URL, secret and event types of the subscription
+pub fn add_webhook_subscription<'a>(
+    store: Box<dyn BatchTrackingStore + 'a>,
+    signer_public_key: &str,
+    payload: NewWebhookSubscriptionPayload,
+) -> Result<WebhookSubscriptionSlice, ErrorResponse> {
+    let url = Url::parse(&payload.url)
+        .map_err(|err| ErrorResponse::new(400, &format!("Invalid webhook URL: {}", err)))?;
+    validate_webhook_url(&url)?;
+
+    if payload.secret.is_empty() {
+        return Err(ErrorResponse::new(400, "Webhook secret must not be empty"));
+    }
+
+    if payload.event_types.is_empty() {
+        return Err(ErrorResponse::new(
+            400,
+            "At least one event type must be provided",
+        ));
+    }
+
+    let event_types = payload
+        .event_types
+        .iter()
+        .map(|event_type| BatchStatusName::from_str(event_type))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| ErrorResponse::new(400, &err.to_string()))?;
+
+    store
+        .add_webhook_subscription(
+            signer_public_key,
+            url.as_str(),
+            &payload.secret,
+            &event_types,
+        )
+        .map(|subscription| WebhookSubscriptionSlice::from(&subscription))
+        .map_err(store_error_response)
+}
+
+/// Checks that a webhook URL is an http or https URL of a host outside the node's own network.
+///
+/// Notifications are posted by the node, so a URL naming the node itself, a private network or a
+/// link-local address (such as a cloud metadata service) would let a submitter reach services
+/// that are not otherwise exposed to them. Only literal addresses and `localhost` can be checked
+/// here; a host name that resolves to such an address is not caught.
+fn validate_webhook_url(url: &Url) -> Result<(), ErrorResponse> {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(ErrorResponse::new(
+            400,
+            "Webhook URL must use the http or https scheme",
+        ));
+    }
+
+    let internal = match url.host() {
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        Some(Host::Ipv4(address)) => is_internal_ipv4(&address),
+        Some(Host::Ipv6(address)) => is_internal_ipv6(&address),
+        None => return Err(ErrorResponse::new(400, "Webhook URL must have a host")),
+    };
+
+    if internal {
+        return Err(ErrorResponse::new(
+            400,
+            "Webhook URL must not be a loopback, private or link-local address",
+        ));
+    }
+
+    Ok(())
+}
+
+fn is_internal_ipv4(address: &Ipv4Addr) -> bool {
+    let octets = address.octets();
+    address.is_loopback()
+        || address.is_private()
+        || address.is_link_local()
+        || address.is_unspecified()
+        || address.is_broadcast()
+        // Shared address space used for carrier-grade NAT, 100.64.0.0/10
+        || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+}
+
+fn is_internal_ipv6(address: &Ipv6Addr) -> bool {
+    let segments = address.segments();
+    // An IPv4 address mapped to IPv6, ::ffff:0:0/96
+    if segments[..5].iter().all(|segment| *segment == 0) && segments[5] == 0xffff {
+        let [a, b] = segments[6].to_be_bytes();
+        let [c, d] = segments[7].to_be_bytes();
+        return is_internal_ipv4(&Ipv4Addr::new(a, b, c, d));
+    }
+
+    address.is_loopback()
+        || address.is_unspecified()
+        // Unique local addresses, fc00::/7
+        || (segments[0] & 0xfe00) == 0xfc00
+        // Link-local addresses, fe80::/10
+        || (segments[0] & 0xffc0) == 0xfe80
+}
+
+/// Lists the webhook subscriptions of a submitter
+pub fn list_webhook_subscriptions<'a>(
+    store: Box<dyn BatchTrackingStore + 'a>,
+    signer_public_key: &str,
+) -> Result<WebhookSubscriptionListSlice, ErrorResponse> {
+    let data = store
+        .list_webhook_subscriptions(signer_public_key)
+        .map_err(store_error_response)?
+        .iter()
+        .map(WebhookSubscriptionSlice::from)
+        .collect();
+
+    Ok(WebhookSubscriptionListSlice { data })
+}
+
+/// Removes one of a submitter's webhook subscriptions
+pub fn remove_webhook_subscription<'a>(
+    store: Box<dyn BatchTrackingStore + 'a>,
+    id: i64,
+    signer_public_key: &str,
+) -> Result<(), ErrorResponse> {
+    store
+        .remove_webhook_subscription(id, signer_public_key)
+        .map_err(store_error_response)
+}
+
+/// Lists the notifications queued for one of a submitter's webhook subscriptions, along with the
+/// log of each attempt to deliver them
+pub fn list_webhook_deliveries<'a>(
+    store: Box<dyn BatchTrackingStore + 'a>,
+    subscription_id: i64,
+    signer_public_key: &str,
+) -> Result<WebhookDeliveryListSlice, ErrorResponse> {
+    let deliveries = store
+        .list_webhook_deliveries(subscription_id, signer_public_key)
+        .map_err(store_error_response)?;
+
+    let data = deliveries
+        .iter()
+        .map(|delivery| {
+            store
+                .list_webhook_delivery_attempts(delivery.id())
+                .map(|attempts| WebhookDeliverySlice::new(delivery, &attempts))
+        })
+        .collect::<Result<_, _>>()
+        .map_err(store_error_response)?;
+
+    Ok(WebhookDeliveryListSlice { data })
+}
+
+/// Checks that a dead-lettered batch is replayed or discarded by its signer or by an admin
+fn check_dead_letter_actor(
+    batch: &TrackingBatch,
+    actor: &str,
+    admin_keys: &[String],
+) -> Result<(), ErrorResponse> {
+    if batch.signer_public_key() == actor || admin_keys.iter().any(|key| key == actor) {
+        Ok(())
+    } else {
+        Err(ErrorResponse::new(
+            403,
+            &format!(
+                "Batch {} may only be replayed or discarded by its signer or an admin",
+                batch.batch_header()
+            ),
+        ))
+    }
+}
+
+/// Checks that a serialized `BatchList` holds a re-signed copy of the original batch and builds
+/// the `TrackingBatch` that replaces it
+fn make_replacement_batch(
+    original: &TrackingBatch,
+    bytes: &[u8],
+    service_id: Option<&str>,
+    actor: &str,
+) -> Result<TrackingBatch, ErrorResponse> {
+    let mut batches = Vec::<Batch>::from_bytes(bytes).map_err(|err| {
+        ErrorResponse::new(
+            400,
+            &format!("Protobuf message was badly formatted. {}", err),
+        )
+    })?;
+
+    if batches.len() != 1 {
+        return Err(ErrorResponse::new(
+            400,
+            "Exactly one replacement batch must be provided",
+        ));
+    }
+    let replacement = batches.remove(0);
+
+    let header = BatchHeader::from_bytes(replacement.header()).map_err(|err| {
+        ErrorResponse::new(400, &format!("Batch header was badly formatted. {}", err))
+    })?;
+    let signer_public_key = hex::to_hex(header.signer_public_key());
+    if signer_public_key != actor {
+        return Err(ErrorResponse::new(
+            400,
+            "Replacement batch must be signed by the user replaying it",
+        ));
+    }
+
+    let original_batch = Vec::<Batch>::from_bytes(original.serialized_batch())
+        .map_err(|err| ErrorResponse::internal_error(Box::new(err)))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| ErrorResponse::new(500, "Dead-lettered batch is empty"))?;
+
+    if original_batch.transactions().len() != replacement.transactions().len() {
+        return Err(ErrorResponse::new(
+            400,
+            "Replacement batch must contain the transactions of the dead-lettered batch",
+        ));
+    }
+
+    for (original_txn, replacement_txn) in original_batch
+        .transactions()
+        .iter()
+        .zip(replacement.transactions())
+    {
+        let original_header = TransactionHeader::from_bytes(original_txn.header())
+            .map_err(|err| ErrorResponse::internal_error(Box::new(err)))?;
+        let replacement_header =
+            TransactionHeader::from_bytes(replacement_txn.header()).map_err(|err| {
+                ErrorResponse::new(
+                    400,
+                    &format!("Transaction header was badly formatted. {}", err),
+                )
+            })?;
+
+        if original_txn.payload() != replacement_txn.payload()
+            || original_header.family_name() != replacement_header.family_name()
+            || original_header.family_version() != replacement_header.family_version()
+        {
+            return Err(ErrorResponse::new(
+                400,
+                "Replacement batch must contain the transactions of the dead-lettered batch",
+            ));
+        }
+
+        if original_header.nonce() == replacement_header.nonce() {
+            return Err(ErrorResponse::new(
+                400,
+                "Replacement transactions must be signed with a fresh nonce",
+            ));
+        }
+    }
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .map_err(|err| ErrorResponse::internal_error(Box::new(err)))?;
+
+    let mut builder = TrackingBatchBuilder::default()
+        .with_signer_public_key(signer_public_key)
+        .with_created_at(created_at)
+        .with_batch(replacement);
+
+    if let Some(service_id) = service_id {
+        builder = builder.with_service_id(service_id.to_string());
+    }
+
+    if let Some(data_change_id) = original.data_change_id() {
+        builder = builder.with_data_change_id(data_change_id.to_string());
+    }
+
+    builder
+        .build()
+        .map_err(|err| ErrorResponse::new(400, &format!("Invalid batch: {}", err)))
+}
+
+fn store_error_response(err: BatchTrackingStoreError) -> ErrorResponse {
+    match err {
+        BatchTrackingStoreError::NotFoundError(msg) => ErrorResponse::new(404, &msg),
+        BatchTrackingStoreError::ConstraintViolationError(err) => {
+            ErrorResponse::new(400, &err.to_string())
+        }
+        BatchTrackingStoreError::ResourceTemporarilyUnavailableError(_) => {
+            ErrorResponse::new(503, "Service Unavailable")
+        }
+        err => ErrorResponse::internal_error(Box::new(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that webhook URLs of public hosts are accepted, and that other schemes and URLs of
+    /// loopback, private and link-local hosts are rejected.
+    #[test]
+    fn test_validate_webhook_url() {
+        for accepted in &[
+            "https://example.com/hooks",
+            "http://203.0.113.7:8080/hooks",
+            "https://[2001:db8::1]/hooks",
+        ] {
+            let url = Url::parse(accepted).expect("Failed to parse URL");
+            assert!(
+                validate_webhook_url(&url).is_ok(),
+                "{} was rejected",
+                accepted
+            );
+        }
+
+        for rejected in &[
+            "ftp://example.com/hooks",
+            "file:///etc/passwd",
+            "http://localhost:8080/hooks",
+            "http://api.localhost/hooks",
+            "http://127.0.0.1/hooks",
+            "http://10.1.2.3/hooks",
+            "http://172.16.0.1/hooks",
+            "http://192.168.1.1/hooks",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/hooks",
+            "http://0.0.0.0/hooks",
+            "http://[::1]/hooks",
+            "http://[fd00::1]/hooks",
+            "http://[fe80::1]/hooks",
+            "http://[::ffff:127.0.0.1]/hooks",
+        ] {
+            let url = Url::parse(rejected).expect("Failed to parse URL");
+            match validate_webhook_url(&url) {
+                Ok(()) => panic!("{} was accepted", rejected),
+                Err(err) => assert_eq!(err.status_code(), 400),
+            }
+        }
+    }
+}
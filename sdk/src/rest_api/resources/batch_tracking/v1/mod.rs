@@ -0,0 +1,31 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod handler;
+pub mod payloads;
+
+#[cfg(feature = "batch-tracking-wait")]
+pub use handler::wait_for_batch_statuses;
+pub use handler::{
+    add_webhook_subscription, discard_dead_letter_batch, get_batch_statuses,
+    list_dead_letter_batches, list_webhook_deliveries, list_webhook_subscriptions,
+    remove_webhook_subscription, replay_dead_letter_batch, submit_batches,
+};
+pub use payloads::{
+    BatchStatusLink, BatchStatusListSlice, BatchStatusSlice, DeadLetterActionSlice,
+    DeadLetterBatchListSlice, DeadLetterBatchSlice, InvalidTransactionSlice,
+    NewWebhookSubscriptionPayload, SubmissionErrorSlice, WebhookDeliveryAttemptSlice,
+    WebhookDeliveryListSlice, WebhookDeliverySlice, WebhookSubscriptionListSlice,
+    WebhookSubscriptionSlice,
+};
@@ -0,0 +1,278 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::batch_tracking::store::{
+    BatchStatus, DeadLetterAuditRecord, DeadLetterBatch, InvalidTransaction, SubmissionError,
+    TrackingBatch, WebhookDelivery, WebhookDeliveryAttempt, WebhookSubscription,
+};
+use crate::hex;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchStatusSlice {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_change_id: Option<String>,
+    pub status: String,
+    pub submitted: bool,
+    pub invalid_transactions: Vec<InvalidTransactionSlice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submission_error: Option<SubmissionErrorSlice>,
+}
+
+impl BatchStatusSlice {
+    /// Status reported for an ID that does not match a tracked batch
+    pub fn unknown(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            data_change_id: None,
+            status: "UNKNOWN".to_string(),
+            submitted: false,
+            invalid_transactions: Vec::new(),
+            submission_error: None,
+        }
+    }
+}
+
+impl From<TrackingBatch> for BatchStatusSlice {
+    fn from(batch: TrackingBatch) -> Self {
+        // Status names follow the Sawtooth batch status API so that existing clients can poll
+        // for the result of a submission
+        let (status, invalid_transactions) = match batch.batch_status() {
+            Some(BatchStatus::Committed(_)) => ("COMMITTED", Vec::new()),
+            Some(BatchStatus::Invalid(txns)) => (
+                "INVALID",
+                txns.iter().map(InvalidTransactionSlice::from).collect(),
+            ),
+            Some(BatchStatus::Unknown) => ("UNKNOWN", Vec::new()),
+            Some(BatchStatus::Pending)
+            | Some(BatchStatus::Delayed)
+            | Some(BatchStatus::Valid(_))
+            | None => ("PENDING", Vec::new()),
+        };
+
+        Self {
+            id: batch.batch_header().to_string(),
+            data_change_id: batch.data_change_id().map(String::from),
+            status: status.to_string(),
+            submitted: batch.submitted(),
+            invalid_transactions,
+            submission_error: batch.submission_error().map(SubmissionErrorSlice::from),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvalidTransactionSlice {
+    pub id: String,
+    pub message: String,
+    pub extended_data: String,
+}
+
+impl From<&InvalidTransaction> for InvalidTransactionSlice {
+    fn from(txn: &InvalidTransaction) -> Self {
+        Self {
+            id: txn.transaction_id().to_string(),
+            message: txn
+                .error_message()
+                .or_else(|| txn.external_error_message())
+                .unwrap_or_default()
+                .to_string(),
+            extended_data: txn.error_data().map(hex::to_hex).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmissionErrorSlice {
+    pub error_type: String,
+    pub error_message: String,
+}
+
+impl From<&SubmissionError> for SubmissionErrorSlice {
+    fn from(err: &SubmissionError) -> Self {
+        Self {
+            error_type: err.error_type().to_string(),
+            error_message: err.error_message().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchStatusListSlice {
+    pub data: Vec<BatchStatusSlice>,
+    pub link: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchStatusLink {
+    pub link: String,
+    /// Batches in the submission whose data change ID had already been submitted, which were
+    /// not added again
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub existing: Vec<BatchStatusSlice>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadLetterBatchSlice {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_change_id: Option<String>,
+    pub signer_public_key: String,
+    pub reason: String,
+    pub dead_lettered_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submission_error: Option<SubmissionErrorSlice>,
+    /// The hex-encoded `BatchList` containing the batch, which is needed to re-sign it
+    pub serialized_batch: String,
+}
+
+impl From<&DeadLetterBatch> for DeadLetterBatchSlice {
+    fn from(dead_letter: &DeadLetterBatch) -> Self {
+        let batch = dead_letter.batch();
+        Self {
+            id: batch.batch_header().to_string(),
+            data_change_id: batch.data_change_id().map(String::from),
+            signer_public_key: batch.signer_public_key().to_string(),
+            reason: dead_letter.reason().to_string(),
+            dead_lettered_at: dead_letter.dead_lettered_at(),
+            submission_error: batch.submission_error().map(SubmissionErrorSlice::from),
+            serialized_batch: hex::to_hex(batch.serialized_batch()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadLetterBatchListSlice {
+    pub data: Vec<DeadLetterBatchSlice>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadLetterActionSlice {
+    pub batch_id: String,
+    pub action: String,
+    pub actor: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement_batch_id: Option<String>,
+}
+
+impl From<&DeadLetterAuditRecord> for DeadLetterActionSlice {
+    fn from(record: &DeadLetterAuditRecord) -> Self {
+        Self {
+            batch_id: record.batch_id().to_string(),
+            action: record.action().to_string(),
+            actor: record.actor().to_string(),
+            replacement_batch_id: record.replacement_batch_id().map(String::from),
+        }
+    }
+}
+
+/// The body of a request to subscribe a webhook to batch status changes
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewWebhookSubscriptionPayload {
+    pub url: String,
+    /// The shared secret notifications are signed with
+    pub secret: String,
+    /// The batch statuses that trigger a notification
+    pub event_types: Vec<String>,
+}
+
+/// A webhook subscription; the secret is never returned once the subscription is created
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookSubscriptionSlice {
+    pub id: i64,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub created_at: i64,
+}
+
+impl From<&WebhookSubscription> for WebhookSubscriptionSlice {
+    fn from(subscription: &WebhookSubscription) -> Self {
+        Self {
+            id: subscription.id(),
+            url: subscription.url().to_string(),
+            event_types: subscription
+                .event_types()
+                .iter()
+                .map(|event_type| event_type.to_string())
+                .collect(),
+            created_at: subscription.created_at(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookSubscriptionListSlice {
+    pub data: Vec<WebhookSubscriptionSlice>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDeliverySlice {
+    pub id: i64,
+    pub batch_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_change_id: Option<String>,
+    pub event_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<String>,
+    pub status: String,
+    pub next_attempt_at: i64,
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivered_at: Option<i64>,
+    pub attempts: Vec<WebhookDeliveryAttemptSlice>,
+}
+
+impl WebhookDeliverySlice {
+    pub fn new(delivery: &WebhookDelivery, attempts: &[WebhookDeliveryAttempt]) -> Self {
+        Self {
+            id: delivery.id(),
+            batch_id: delivery.batch_id().to_string(),
+            data_change_id: delivery.data_change_id().map(String::from),
+            event_type: delivery.event_type().to_string(),
+            previous_status: delivery.previous_status().map(|status| status.to_string()),
+            status: delivery.status().to_string(),
+            next_attempt_at: delivery.next_attempt_at(),
+            created_at: delivery.created_at(),
+            delivered_at: delivery.delivered_at(),
+            attempts: attempts
+                .iter()
+                .map(WebhookDeliveryAttemptSlice::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDeliveryAttemptSlice {
+    pub attempted_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_code: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<&WebhookDeliveryAttempt> for WebhookDeliveryAttemptSlice {
+    fn from(attempt: &WebhookDeliveryAttempt) -> Self {
+        Self {
+            attempted_at: attempt.attempted_at(),
+            response_code: attempt.response_code(),
+            error: attempt.error().map(String::from),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDeliveryListSlice {
+    pub data: Vec<WebhookDeliverySlice>,
+}
@@ -0,0 +1,405 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fans out changes to the clients of the event stream
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::error::InternalError;
+
+use super::payloads::{StreamEvent, StreamEventType};
+
+const DEFAULT_HISTORY_SIZE: usize = 1000;
+const DEFAULT_BUFFER_SIZE: usize = 1000;
+
+/// Selects the events a client of the event stream receives
+///
+/// An empty filter matches every event. An organization filter only matches events for entities
+/// that belong to the organization, so it excludes batch status changes.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    event_types: Vec<StreamEventType>,
+    org_id: Option<String>,
+    service_id: Option<String>,
+}
+
+impl EventFilter {
+    pub fn with_event_types(mut self, event_types: Vec<StreamEventType>) -> Self {
+        self.event_types = event_types;
+        self
+    }
+
+    pub fn with_org_id(mut self, org_id: String) -> Self {
+        self.org_id = Some(org_id);
+        self
+    }
+
+    pub fn with_service_id(mut self, service_id: String) -> Self {
+        self.service_id = Some(service_id);
+        self
+    }
+
+    pub fn matches(&self, event: &StreamEvent) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && self
+                .org_id
+                .as_ref()
+                .map(|org_id| event.org_ids.contains(org_id))
+                .unwrap_or(true)
+            && self
+                .service_id
+                .as_ref()
+                .map(|service_id| event.service_id.as_ref() == Some(service_id))
+                .unwrap_or(true)
+    }
+}
+
+/// The events streamed to a single client
+pub struct EventSubscription {
+    resync_required: bool,
+    receiver: Receiver<StreamEvent>,
+}
+
+impl EventSubscription {
+    /// Returns whether changes the client asked to resume from are no longer available, in which
+    /// case it should list the resources it follows again
+    pub fn resync_required(&self) -> bool {
+        self.resync_required
+    }
+
+    /// Waits for the next event, returning `None` once the broadcaster has been dropped or the
+    /// client has fallen too far behind
+    pub async fn recv(&mut self) -> Option<StreamEvent> {
+        self.receiver.recv().await
+    }
+}
+
+/// Publishes changes to every client of the event stream whose filter matches them
+///
+/// The most recent committed changes are kept in a history, so that a client that reconnects with
+/// the last commit number it saw is sent the changes it missed. Every change in that commit is
+/// sent again, so events are delivered at least once. If the commit is older than the history, the
+/// client is told to resynchronize instead.
+///
+/// Each client is sent events through a bounded buffer. A client that falls so far behind that its
+/// buffer fills is dropped, ending its stream; it resumes from the last commit it received when it
+/// reconnects.
+#[derive(Clone)]
+pub struct EventBroadcaster {
+    inner: Arc<Mutex<Inner>>,
+}
+
+struct Inner {
+    history: VecDeque<StreamEvent>,
+    history_size: usize,
+    /// The highest commit number whose changes may be missing from the history
+    missing_through: Option<i64>,
+    buffer_size: usize,
+    subscribers: Vec<(EventFilter, Sender<StreamEvent>)>,
+}
+
+impl EventBroadcaster {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                history: VecDeque::new(),
+                history_size: DEFAULT_HISTORY_SIZE,
+                missing_through: None,
+                buffer_size: DEFAULT_BUFFER_SIZE,
+                subscribers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Sets the number of committed changes kept for clients that resume the stream
+    pub fn with_history_size(self, history_size: usize) -> Self {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.history_size = history_size;
+        }
+        self
+    }
+
+    /// Sets the number of events buffered for each client before it is dropped
+    pub fn with_buffer_size(self, buffer_size: usize) -> Self {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.buffer_size = buffer_size.max(1);
+        }
+        self
+    }
+
+    /// Sets the last commit made before the broadcaster was created, whose changes are not in
+    /// the history
+    pub fn with_committed_through(self, commit_num: i64) -> Self {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.missing_through = Some(commit_num);
+        }
+        self
+    }
+
+    /// Sends an event to the matching clients, and records it in the history if it was committed
+    pub fn publish(&self, event: StreamEvent) -> Result<(), InternalError> {
+        let mut inner = self.lock()?;
+
+        inner.subscribers.retain(|(filter, sender)| {
+            !sender.is_closed()
+                && (!filter.matches(&event) || sender.try_send(event.clone()).is_ok())
+        });
+
+        if let Some(commit_num) = event.commit_num {
+            inner.history.push_back(event);
+            while inner.history.len() > inner.history_size {
+                if let Some(evicted) = inner.history.pop_front() {
+                    let evicted_commit_num = evicted.commit_num.unwrap_or(commit_num);
+                    inner.missing_through = Some(
+                        inner
+                            .missing_through
+                            .map(|missing| missing.max(evicted_commit_num))
+                            .unwrap_or(evicted_commit_num),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Subscribes a client to the events that match its filter
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Selects the events sent to the client
+    /// * `last_commit_num` - The last commit the client saw, if it is resuming the stream
+    pub fn subscribe(
+        &self,
+        filter: EventFilter,
+        last_commit_num: Option<i64>,
+    ) -> Result<EventSubscription, InternalError> {
+        let mut inner = self.lock()?;
+        let (sender, receiver) = channel(inner.buffer_size);
+
+        let mut resync_required = match (last_commit_num, inner.missing_through) {
+            (Some(last_commit_num), Some(missing_through)) => last_commit_num <= missing_through,
+            _ => false,
+        };
+
+        if let (Some(last_commit_num), false) = (last_commit_num, resync_required) {
+            let missed = inner
+                .history
+                .iter()
+                .filter(|event| {
+                    event
+                        .commit_num
+                        .map(|commit_num| commit_num >= last_commit_num)
+                        .unwrap_or(false)
+                        && filter.matches(event)
+                })
+                .collect::<Vec<_>>();
+
+            // Changes that do not fit in the client's buffer cannot be sent
+            if missed.len() > inner.buffer_size {
+                resync_required = true;
+            } else {
+                missed
+                    .into_iter()
+                    .try_for_each(|event| sender.try_send(event.clone()))
+                    .map_err(|err| InternalError::from_source(Box::new(err)))?;
+            }
+        }
+
+        inner.subscribers.push((filter, sender));
+
+        Ok(EventSubscription {
+            resync_required,
+            receiver,
+        })
+    }
+
+    fn lock(&self) -> Result<MutexGuard<Inner>, InternalError> {
+        self.inner
+            .lock()
+            .map_err(|_| InternalError::with_message("Event broadcaster lock poisoned".into()))
+    }
+}
+
+impl Default for EventBroadcaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product_event(id: &str, org_id: &str, commit_num: i64) -> StreamEvent {
+        StreamEvent::entity_updated(
+            StreamEventType::Product,
+            id,
+            vec![org_id.to_string()],
+            commit_num,
+            None,
+        )
+    }
+
+    fn received(subscription: &mut EventSubscription) -> Vec<String> {
+        let mut ids = Vec::new();
+        while let Ok(event) = subscription.receiver.try_recv() {
+            ids.push(event.id);
+        }
+        ids
+    }
+
+    /// Verifies that published events are only sent to the subscribers whose filters match them
+    #[test]
+    fn test_publish_to_matching_subscribers() {
+        let broadcaster = EventBroadcaster::new();
+
+        let mut all = broadcaster
+            .subscribe(EventFilter::default(), None)
+            .expect("Unable to subscribe");
+        let mut org = broadcaster
+            .subscribe(EventFilter::default().with_org_id("org1".into()), None)
+            .expect("Unable to subscribe");
+        let mut batches = broadcaster
+            .subscribe(
+                EventFilter::default().with_event_types(vec![StreamEventType::BatchStatus]),
+                None,
+            )
+            .expect("Unable to subscribe");
+
+        broadcaster
+            .publish(product_event("product1", "org1", 1))
+            .expect("Unable to publish");
+        broadcaster
+            .publish(product_event("product2", "org2", 2))
+            .expect("Unable to publish");
+        broadcaster
+            .publish(StreamEvent::batch_status(
+                "batch1",
+                None,
+                Some("Pending"),
+                "Committed",
+            ))
+            .expect("Unable to publish");
+
+        assert_eq!(received(&mut all), vec!["product1", "product2", "batch1"]);
+        assert_eq!(received(&mut org), vec!["product1"]);
+        assert_eq!(received(&mut batches), vec!["batch1"]);
+    }
+
+    /// Verifies that a subscriber resuming from a commit is sent the committed changes from that
+    /// commit onwards, and that batch status changes are not replayed
+    #[test]
+    fn test_resume_from_commit_num() {
+        let broadcaster = EventBroadcaster::new();
+
+        broadcaster
+            .publish(product_event("product1", "org1", 1))
+            .expect("Unable to publish");
+        broadcaster
+            .publish(StreamEvent::batch_status("batch1", None, None, "Pending"))
+            .expect("Unable to publish");
+        broadcaster
+            .publish(product_event("product2", "org1", 2))
+            .expect("Unable to publish");
+        broadcaster
+            .publish(product_event("product3", "org1", 3))
+            .expect("Unable to publish");
+
+        let mut subscription = broadcaster
+            .subscribe(EventFilter::default(), Some(2))
+            .expect("Unable to subscribe");
+
+        assert!(!subscription.resync_required());
+        assert_eq!(received(&mut subscription), vec!["product2", "product3"]);
+    }
+
+    /// Verifies that a subscriber resuming from a commit whose changes are no longer in the
+    /// history is told to resynchronize
+    #[test]
+    fn test_resync_when_history_exceeded() {
+        let broadcaster = EventBroadcaster::new().with_history_size(2);
+
+        for commit_num in 1..=4 {
+            broadcaster
+                .publish(product_event(
+                    &format!("product{}", commit_num),
+                    "org1",
+                    commit_num,
+                ))
+                .expect("Unable to publish");
+        }
+
+        let mut stale = broadcaster
+            .subscribe(EventFilter::default(), Some(2))
+            .expect("Unable to subscribe");
+        assert!(stale.resync_required());
+        assert!(received(&mut stale).is_empty());
+
+        let mut recent = broadcaster
+            .subscribe(EventFilter::default(), Some(3))
+            .expect("Unable to subscribe");
+        assert!(!recent.resync_required());
+        assert_eq!(received(&mut recent), vec!["product3", "product4"]);
+
+        let restarted = EventBroadcaster::new().with_committed_through(10);
+        assert!(restarted
+            .subscribe(EventFilter::default(), Some(9))
+            .expect("Unable to subscribe")
+            .resync_required());
+    }
+
+    /// Verifies that a subscriber whose buffer fills is dropped after receiving the buffered
+    /// events, and that a subscriber resuming from more changes than fit in its buffer is told to
+    /// resynchronize
+    #[test]
+    fn test_drop_lagging_subscriber() {
+        let broadcaster = EventBroadcaster::new().with_buffer_size(2);
+
+        let mut lagging = broadcaster
+            .subscribe(EventFilter::default(), None)
+            .expect("Unable to subscribe");
+
+        for commit_num in 1..=3 {
+            broadcaster
+                .publish(product_event(
+                    &format!("product{}", commit_num),
+                    "org1",
+                    commit_num,
+                ))
+                .expect("Unable to publish");
+        }
+
+        assert_eq!(received(&mut lagging), vec!["product1", "product2"]);
+        assert_eq!(
+            lagging.receiver.try_recv(),
+            Err(tokio::sync::mpsc::error::TryRecvError::Disconnected)
+        );
+
+        let mut resumed = broadcaster
+            .subscribe(EventFilter::default(), Some(1))
+            .expect("Unable to subscribe");
+        assert!(resumed.resync_required());
+        assert!(received(&mut resumed).is_empty());
+
+        let mut recent = broadcaster
+            .subscribe(EventFilter::default(), Some(2))
+            .expect("Unable to subscribe");
+        assert!(!recent.resync_required());
+        assert_eq!(received(&mut recent), vec!["product2", "product3"]);
+    }
+}
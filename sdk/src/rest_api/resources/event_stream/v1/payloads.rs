@@ -0,0 +1,144 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::InvalidArgumentError;
+
+/// The kind of change reported by a `StreamEvent`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamEventType {
+    BatchStatus,
+    Location,
+    Product,
+    PurchaseOrder,
+}
+
+impl StreamEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StreamEventType::BatchStatus => "batch_status",
+            StreamEventType::Location => "location",
+            StreamEventType::Product => "product",
+            StreamEventType::PurchaseOrder => "purchase_order",
+        }
+    }
+}
+
+impl fmt::Display for StreamEventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for StreamEventType {
+    type Err = InvalidArgumentError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "batch_status" => Ok(StreamEventType::BatchStatus),
+            "location" => Ok(StreamEventType::Location),
+            "product" => Ok(StreamEventType::Product),
+            "purchase_order" => Ok(StreamEventType::PurchaseOrder),
+            _ => Err(InvalidArgumentError::new(
+                "type".to_string(),
+                format!("{} is not a valid event type", value),
+            )),
+        }
+    }
+}
+
+/// A change that is streamed to clients of the event stream
+///
+/// An event only identifies what changed; clients fetch the changed resource from its endpoint.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StreamEvent {
+    pub event_type: StreamEventType,
+    /// The ID of the changed entity or batch
+    pub id: String,
+    /// `updated` or `deleted` for an entity, or the new status of a batch
+    pub change: String,
+    /// The commit the change was made in. Batch status changes are not committed, so they are
+    /// not numbered and cannot be resumed from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_num: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_id: Option<String>,
+    /// The organizations the changed entity belongs to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub org_ids: Vec<String>,
+    /// The status of a batch before it changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<String>,
+}
+
+impl StreamEvent {
+    /// Creates an event for an entity that was added or updated in a commit
+    pub fn entity_updated(
+        event_type: StreamEventType,
+        id: &str,
+        org_ids: Vec<String>,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Self {
+        Self {
+            event_type,
+            id: id.to_string(),
+            change: "updated".to_string(),
+            commit_num: Some(commit_num),
+            service_id: service_id.map(String::from),
+            org_ids,
+            previous_status: None,
+        }
+    }
+
+    /// Creates an event for an entity that was deleted from state in a commit
+    pub fn entity_deleted(
+        event_type: StreamEventType,
+        id: &str,
+        org_ids: Vec<String>,
+        commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Self {
+        Self {
+            event_type,
+            id: id.to_string(),
+            change: "deleted".to_string(),
+            commit_num: Some(commit_num),
+            service_id: service_id.map(String::from),
+            org_ids,
+            previous_status: None,
+        }
+    }
+
+    /// Creates an event for a batch that changed status
+    pub fn batch_status(
+        batch_id: &str,
+        service_id: Option<&str>,
+        previous_status: Option<&str>,
+        status: &str,
+    ) -> Self {
+        Self {
+            event_type: StreamEventType::BatchStatus,
+            id: batch_id.to_string(),
+            change: status.to_string(),
+            commit_num: None,
+            service_id: service_id.map(String::from),
+            org_ids: Vec::new(),
+            previous_status: previous_status.map(String::from),
+        }
+    }
+}
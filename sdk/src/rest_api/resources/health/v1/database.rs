@@ -0,0 +1,144 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use diesel::r2d2::{ConnectionManager, Pool};
+
+#[cfg(feature = "postgres")]
+use crate::migrations::{get_pending_postgres_migrations, get_postgres_migration_version};
+#[cfg(feature = "sqlite")]
+use crate::migrations::{get_pending_sqlite_migrations, get_sqlite_migration_version};
+
+use super::HealthCheck;
+
+/// How long a check waits for a connection, so that a readiness probe is answered before it
+/// times out
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Checks that the database can be connected to and that every migration has been applied to it,
+/// and reports the version of the last migration that was applied
+#[derive(Clone)]
+pub enum DatabaseHealthCheck {
+    #[cfg(feature = "postgres")]
+    Postgres(Pool<ConnectionManager<diesel::pg::PgConnection>>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(Pool<ConnectionManager<diesel::sqlite::SqliteConnection>>),
+}
+
+impl DatabaseHealthCheck {
+    #[cfg(feature = "postgres")]
+    pub fn with_pg_pool(
+        connection_pool: Pool<ConnectionManager<diesel::pg::PgConnection>>,
+    ) -> Self {
+        DatabaseHealthCheck::Postgres(connection_pool)
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn with_sqlite_pool(
+        connection_pool: Pool<ConnectionManager<diesel::sqlite::SqliteConnection>>,
+    ) -> Self {
+        DatabaseHealthCheck::Sqlite(connection_pool)
+    }
+
+    /// Returns the `database` check, which fails if the database cannot be connected to or any
+    /// of the migrations embedded in this binary have not been applied to it
+    pub fn check(&self) -> HealthCheck {
+        let migrations = match self {
+            #[cfg(feature = "postgres")]
+            DatabaseHealthCheck::Postgres(pool) => pool
+                .get_timeout(CONNECTION_TIMEOUT)
+                .map_err(|err| format!("Unable to connect to database: {}", err))
+                .and_then(|conn| {
+                    get_postgres_migration_version(&conn)
+                        .and_then(|version| Ok((version, get_pending_postgres_migrations(&conn)?)))
+                        .map_err(|err| format!("Unable to get database migrations: {}", err))
+                }),
+            #[cfg(feature = "sqlite")]
+            DatabaseHealthCheck::Sqlite(pool) => pool
+                .get_timeout(CONNECTION_TIMEOUT)
+                .map_err(|err| format!("Unable to connect to database: {}", err))
+                .and_then(|conn| {
+                    get_sqlite_migration_version(&conn)
+                        .and_then(|version| Ok((version, get_pending_sqlite_migrations(&conn)?)))
+                        .map_err(|err| format!("Unable to get database migrations: {}", err))
+                }),
+        };
+
+        match migrations {
+            Ok((Some(version), pending)) if pending.is_empty() => {
+                HealthCheck::pass("database").with_detail("migration_version", version)
+            }
+            Ok((version, pending)) => HealthCheck::fail(
+                "database",
+                &format!("{} migrations have not been applied", pending.len()),
+            )
+            .with_detail("migration_version", version)
+            .with_detail("pending_migrations", pending),
+            Err(err) => HealthCheck::fail("database", &err),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use diesel::{sql_query, RunQueryDsl};
+
+    use crate::migrations::run_sqlite_migrations;
+
+    /// Verify that the check fails until the migrations have been applied, then passes with the
+    /// version of the last of them, and that it fails with the versions of any migration that has
+    /// not been applied.
+    #[test]
+    fn test_sqlite_database_check() {
+        let connection_manager =
+            ConnectionManager::<diesel::sqlite::SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        let database_check = DatabaseHealthCheck::with_sqlite_pool(pool.clone());
+
+        assert!(!database_check.check().passed());
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        let check = database_check.check();
+        assert!(check.passed());
+        assert!(check.details.contains_key("migration_version"));
+
+        let conn = pool.get().expect("Failed to get connection");
+        let last_version = get_sqlite_migration_version(&conn)
+            .expect("Failed to get migration version")
+            .expect("No migrations have been applied");
+        sql_query(format!(
+            "DELETE FROM __diesel_schema_migrations WHERE version = '{}'",
+            last_version
+        ))
+        .execute(&*conn)
+        .expect("Failed to remove migration");
+        drop(conn);
+
+        let check = database_check.check();
+        assert!(!check.passed());
+        assert_eq!(
+            check.details.get("pending_migrations"),
+            Some(&serde_json::json!([last_version]))
+        );
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+/// Whether a check, or a service as a whole, is healthy
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Pass,
+    Fail,
+}
+
+/// The result of checking one of the things that a service depends on, such as its database
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    pub name: String,
+    pub status: HealthStatus,
+    /// Why the check failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// What was measured by the check, such as a migration version or a number of batches
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub details: BTreeMap<String, Value>,
+}
+
+impl HealthCheck {
+    pub fn pass(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: HealthStatus::Pass,
+            error: None,
+            details: BTreeMap::new(),
+        }
+    }
+
+    pub fn fail(name: &str, error: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: HealthStatus::Fail,
+            error: Some(error.to_string()),
+            details: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a measurement to the check. A value that cannot be represented as JSON is left out.
+    pub fn with_detail<V: serde::Serialize>(mut self, key: &str, value: V) -> Self {
+        match serde_json::to_value(value) {
+            Ok(value) => {
+                self.details.insert(key.to_string(), value);
+            }
+            Err(err) => warn!("Unable to report {} of {} check: {}", key, self.name, err),
+        }
+        self
+    }
+
+    pub fn passed(&self) -> bool {
+        self.status == HealthStatus::Pass
+    }
+}
+
+/// The response of the liveness and readiness endpoints
+///
+/// A service is ready when every one of its checks passes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HealthResponse {
+    pub status: HealthStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthResponse {
+    /// The response of a service that is running, which is not checked any further
+    pub fn live() -> Self {
+        Self {
+            status: HealthStatus::Pass,
+            checks: Vec::new(),
+        }
+    }
+
+    pub fn from_checks(checks: Vec<HealthCheck>) -> Self {
+        let status = if checks.iter().all(HealthCheck::passed) {
+            HealthStatus::Pass
+        } else {
+            HealthStatus::Fail
+        };
+
+        Self { status, checks }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.status == HealthStatus::Pass
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a response only passes if all of its checks pass, and that the details and
+    /// error of a check are only serialized when present.
+    #[test]
+    fn test_health_response() {
+        let response = HealthResponse::from_checks(vec![
+            HealthCheck::pass("database").with_detail("migration_version", "2022-09-01-000000"),
+            HealthCheck::pass("splinter"),
+        ]);
+        assert!(response.passed());
+        assert_eq!(
+            serde_json::to_value(&response).expect("Unable to serialize response"),
+            serde_json::json!({
+                "status": "pass",
+                "checks": [
+                    {
+                        "name": "database",
+                        "status": "pass",
+                        "details": { "migration_version": "2022-09-01-000000" },
+                    },
+                    { "name": "splinter", "status": "pass" },
+                ],
+            })
+        );
+
+        let response = HealthResponse::from_checks(vec![
+            HealthCheck::pass("database"),
+            HealthCheck::fail("splinter", "Connection refused"),
+        ]);
+        assert!(!response.passed());
+        assert_eq!(
+            serde_json::to_value(&response.checks[1]).expect("Unable to serialize check"),
+            serde_json::json!({
+                "name": "splinter",
+                "status": "fail",
+                "error": "Connection refused",
+            })
+        );
+
+        assert_eq!(
+            serde_json::to_value(&HealthResponse::live()).expect("Unable to serialize response"),
+            serde_json::json!({ "status": "pass" })
+        );
+    }
+}
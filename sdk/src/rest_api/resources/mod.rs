@@ -14,9 +14,15 @@
 
 #[cfg(feature = "rest-api-resources-agent")]
 pub mod agents;
+#[cfg(feature = "rest-api-resources-batch-tracking")]
+pub mod batch_tracking;
 #[cfg(feature = "rest-api-resources-batches")]
 pub mod batches;
 pub mod error;
+#[cfg(feature = "rest-api-resources-event-stream")]
+pub mod event_stream;
+#[cfg(feature = "rest-api-resources-health")]
+pub mod health;
 #[cfg(feature = "rest-api-resources-location")]
 pub mod locations;
 #[cfg(feature = "rest-api-resources-organization")]
@@ -30,6 +36,8 @@ pub mod purchase_order;
 pub mod roles;
 #[cfg(feature = "rest-api-resources-schema")]
 pub mod schemas;
+#[cfg(feature = "rest-api-resources-service")]
+pub mod services;
 #[cfg(any(
     feature = "rest-api-resources-submit",
     feature = "rest-api-resources-batch-tracking"
@@ -0,0 +1,47 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    commits::store::{CommitStore, CommitStoreError},
+    rest_api::resources::error::ErrorResponse,
+};
+
+use super::payloads::{ServiceListSlice, ServiceSlice};
+
+pub fn list_services<'a>(
+    store: Box<dyn CommitStore + 'a>,
+    circuit_id: Option<&str>,
+) -> Result<ServiceListSlice, ErrorResponse> {
+    let lifecycles = store.list_service_lifecycles().map_err(|err| match err {
+        CommitStoreError::InternalError(err) => ErrorResponse::internal_error(Box::new(err)),
+        CommitStoreError::ConstraintViolationError(err) => {
+            ErrorResponse::new(400, &format!("{}", err))
+        }
+        CommitStoreError::ResourceTemporarilyUnavailableError(_) => {
+            ErrorResponse::new(503, "Service Unavailable")
+        }
+        CommitStoreError::NotFoundError(_) => ErrorResponse::new(404, "Resource not found"),
+    })?;
+
+    let data = lifecycles
+        .into_iter()
+        .map(ServiceSlice::from)
+        .filter(|service| match circuit_id {
+            Some(circuit_id) => service.circuit_id == circuit_id,
+            None => true,
+        })
+        .collect();
+
+    Ok(ServiceListSlice { data })
+}
@@ -0,0 +1,44 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::commits::store::ServiceLifecycle;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceSlice {
+    pub service_id: String,
+    pub circuit_id: String,
+    pub status: String,
+    pub data_status: String,
+}
+
+impl From<ServiceLifecycle> for ServiceSlice {
+    fn from(lifecycle: ServiceLifecycle) -> Self {
+        Self {
+            circuit_id: lifecycle
+                .service_id
+                .split("::")
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            service_id: lifecycle.service_id,
+            status: lifecycle.status.to_string(),
+            data_status: lifecycle.data_status.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceListSlice {
+    pub data: Vec<ServiceSlice>,
+}
@@ -18,18 +18,20 @@ use url::Url;
 use crate::{
     rest_api::resources::{error::ErrorResponse, paging::v1::Paging},
     track_and_trace::store::{
-        AssociatedAgent, Property, Proposal, ReportedValueReporterToAgentMetadata,
-        TrackAndTraceStore, TrackAndTraceStoreError,
+        AssociatedAgent, ListProposalFilters, ListRecordFilters, Property, Proposal,
+        ReportedValueReporterToAgentMetadata, TrackAndTraceStore, TrackAndTraceStoreError,
     },
 };
 
 use super::payloads::{
-    PropertySlice, PropertyValueSlice, RecordListSlice, RecordSlice, StructPropertyValue,
+    AlertSlice, PropertyAggregateSlice, PropertySlice, PropertyValueSlice, ProposalSlice,
+    RecordListSlice, RecordSlice, StructPropertyValue,
 };
 
 pub fn list_records<'a>(
     url: Url,
     store: Box<dyn TrackAndTraceStore + 'a>,
+    filters: ListRecordFilters,
     service_id: Option<&str>,
     offset: u64,
     limit: u16,
@@ -39,7 +41,7 @@ pub fn list_records<'a>(
     let limit = i64::try_from(limit).unwrap_or(10);
 
     let record_list = store
-        .list_records(service_id, offset, limit)
+        .list_records(filters, service_id, offset, limit)
         .map_err(|err| match err {
             TrackAndTraceStoreError::InternalError(err) => {
                 ErrorResponse::internal_error(Box::new(err))
@@ -47,6 +49,9 @@ pub fn list_records<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -62,7 +67,7 @@ pub fn list_records<'a>(
         .collect();
 
     let proposals = store
-        .list_proposals(&record_ids, service_id)
+        .list_proposals(&record_ids, ListProposalFilters::default(), service_id)
         .map_err(|err| match err {
             TrackAndTraceStoreError::InternalError(err) => {
                 ErrorResponse::internal_error(Box::new(err))
@@ -70,6 +75,9 @@ pub fn list_records<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -87,6 +95,9 @@ pub fn list_records<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -104,6 +115,9 @@ pub fn list_records<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -160,6 +174,9 @@ pub fn get_record<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -170,7 +187,11 @@ pub fn get_record<'a>(
         .ok_or_else(|| ErrorResponse::new(404, &format!("Resource {} not found", record_id)))?;
 
     let proposals = store
-        .list_proposals(&[record_id.clone()], service_id)
+        .list_proposals(
+            &[record_id.clone()],
+            ListProposalFilters::default(),
+            service_id,
+        )
         .map_err(|err| match err {
             TrackAndTraceStoreError::InternalError(err) => {
                 ErrorResponse::internal_error(Box::new(err))
@@ -178,6 +199,9 @@ pub fn get_record<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -195,6 +219,9 @@ pub fn get_record<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -215,6 +242,9 @@ pub fn get_record<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -231,6 +261,105 @@ pub fn get_record<'a>(
     ))
 }
 
+pub fn list_record_alerts<'a>(
+    store: Box<dyn TrackAndTraceStore + 'a>,
+    record_id: String,
+    service_id: Option<&str>,
+) -> Result<Vec<AlertSlice>, ErrorResponse> {
+    store
+        .get_record(&record_id, service_id)
+        .map_err(|err| match err {
+            TrackAndTraceStoreError::InternalError(err) => {
+                ErrorResponse::internal_error(Box::new(err))
+            }
+            TrackAndTraceStoreError::ConstraintViolationError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
+                ErrorResponse::new(503, "Service Unavailable")
+            }
+            TrackAndTraceStoreError::NotFoundError(_) => {
+                ErrorResponse::new(404, &format!("Record {} not found", record_id))
+            }
+        })?
+        .ok_or_else(|| ErrorResponse::new(404, &format!("Record {} not found", record_id)))?;
+
+    let alerts = store
+        .list_alerts(&record_id, service_id)
+        .map_err(|err| match err {
+            TrackAndTraceStoreError::InternalError(err) => {
+                ErrorResponse::internal_error(Box::new(err))
+            }
+            TrackAndTraceStoreError::ConstraintViolationError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
+                ErrorResponse::new(503, "Service Unavailable")
+            }
+            TrackAndTraceStoreError::NotFoundError(_) => {
+                ErrorResponse::new(404, "Resource not found")
+            }
+        })?;
+
+    Ok(alerts.into_iter().map(AlertSlice::from).collect())
+}
+
+pub fn list_record_proposals<'a>(
+    store: Box<dyn TrackAndTraceStore + 'a>,
+    record_id: String,
+    filters: ListProposalFilters,
+    service_id: Option<&str>,
+) -> Result<Vec<ProposalSlice>, ErrorResponse> {
+    store
+        .get_record(&record_id, service_id)
+        .map_err(|err| match err {
+            TrackAndTraceStoreError::InternalError(err) => {
+                ErrorResponse::internal_error(Box::new(err))
+            }
+            TrackAndTraceStoreError::ConstraintViolationError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
+                ErrorResponse::new(503, "Service Unavailable")
+            }
+            TrackAndTraceStoreError::NotFoundError(_) => {
+                ErrorResponse::new(404, &format!("Record {} not found", record_id))
+            }
+        })?
+        .ok_or_else(|| ErrorResponse::new(404, &format!("Record {} not found", record_id)))?;
+
+    let proposals = store
+        .list_proposals(&[record_id], filters, service_id)
+        .map_err(|err| match err {
+            TrackAndTraceStoreError::InternalError(err) => {
+                ErrorResponse::internal_error(Box::new(err))
+            }
+            TrackAndTraceStoreError::ConstraintViolationError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
+                ErrorResponse::new(503, "Service Unavailable")
+            }
+            TrackAndTraceStoreError::NotFoundError(_) => {
+                ErrorResponse::new(404, "Resource not found")
+            }
+        })?;
+
+    Ok(proposals.into_iter().map(ProposalSlice::from).collect())
+}
+
 pub fn get_record_property<'a>(
     store: Box<dyn TrackAndTraceStore + 'a>,
     record_id: String,
@@ -246,6 +375,9 @@ pub fn get_record_property<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -258,6 +390,65 @@ pub fn get_record_property<'a>(
     parse_property_slice(&store, &property, &data_type, service_id)
 }
 
+pub fn get_record_property_aggregate<'a>(
+    store: Box<dyn TrackAndTraceStore + 'a>,
+    record_id: String,
+    property_name: String,
+    bucket_size: u64,
+    start: Option<u64>,
+    end: Option<u64>,
+    service_id: Option<&str>,
+) -> Result<PropertyAggregateSlice, ErrorResponse> {
+    if bucket_size == 0 {
+        return Err(ErrorResponse::new(
+            400,
+            "Bucket size must be greater than 0",
+        ));
+    }
+
+    let aggregation = store
+        .aggregate_reported_values(
+            &record_id,
+            &property_name,
+            i64::try_from(bucket_size).unwrap_or(i64::MAX),
+            start.map(|start| i64::try_from(start).unwrap_or(i64::MAX)),
+            end.map(|end| i64::try_from(end).unwrap_or(i64::MAX)),
+            service_id,
+        )
+        .map_err(|err| match err {
+            TrackAndTraceStoreError::InternalError(err) => {
+                ErrorResponse::internal_error(Box::new(err))
+            }
+            TrackAndTraceStoreError::ConstraintViolationError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
+                ErrorResponse::new(503, "Service Unavailable")
+            }
+            TrackAndTraceStoreError::NotFoundError(_) => {
+                ErrorResponse::new(404, &format!("Property {} not found", property_name))
+            }
+        })?
+        .ok_or_else(|| ErrorResponse::new(404, &format!("Property {} not found", property_name)))?;
+
+    match aggregation.data_type.as_deref() {
+        Some("Number") | Some("LatLong") => {
+            Ok(PropertyAggregateSlice::from_model(aggregation, service_id))
+        }
+        data_type => Err(ErrorResponse::new(
+            400,
+            &format!(
+                "Property {} of data type {} cannot be aggregated",
+                property_name,
+                data_type.unwrap_or("Unknown")
+            ),
+        )),
+    }
+}
+
 #[allow(clippy::borrowed_box)]
 fn parse_property_slice<'a>(
     store: &Box<dyn TrackAndTraceStore + 'a>,
@@ -274,6 +465,9 @@ fn parse_property_slice<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -296,6 +490,9 @@ fn parse_property_slice<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -333,6 +530,9 @@ fn parse_property_slice<'a>(
             TrackAndTraceStoreError::ConstraintViolationError(err) => {
                 ErrorResponse::new(400, &format!("{}", err))
             }
+            TrackAndTraceStoreError::InvalidArgumentError(err) => {
+                ErrorResponse::new(400, &format!("{}", err))
+            }
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(_) => {
                 ErrorResponse::new(503, "Service Unavailable")
             }
@@ -15,8 +15,12 @@
 pub mod handler;
 pub mod payloads;
 
-pub use handler::{get_record, get_record_property, list_records};
+pub use handler::{
+    get_record, get_record_property, get_record_property_aggregate, list_record_alerts,
+    list_record_proposals, list_records,
+};
 pub use payloads::{
-    AssociatedAgentSlice, LatLong, PropertySlice, PropertyValueSlice, ProposalSlice,
-    RecordListSlice, RecordSlice, ReporterSlice, StructPropertyValue, Value,
+    AlertSlice, AssociatedAgentSlice, BucketValue, LatLong, PropertyAggregateSlice,
+    PropertyBucketSlice, PropertySlice, PropertyValueSlice, ProposalSlice, RecordListSlice,
+    RecordSlice, ReporterSlice, StructPropertyValue, Value,
 };
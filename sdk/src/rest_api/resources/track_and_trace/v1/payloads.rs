@@ -15,11 +15,42 @@
 use crate::{
     rest_api::resources::{error::ErrorResponse, paging::v1::Paging},
     track_and_trace::store::{
-        AssociatedAgent, LatLongValue, Property, Proposal, Record,
-        ReportedValueReporterToAgentMetadata,
+        Alert, AssociatedAgent, LatLongValue, Property, Proposal, Record, ReportedValueAggregate,
+        ReportedValueAggregation, ReportedValueBucket, ReportedValueReporterToAgentMetadata,
     },
 };
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AlertSlice {
+    pub rule_name: String,
+    pub record_id: String,
+    pub property_name: String,
+    pub status: String,
+    pub started_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub triggered_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<u64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_id: Option<String>,
+}
+
+impl From<Alert> for AlertSlice {
+    fn from(alert: Alert) -> Self {
+        Self {
+            rule_name: alert.rule_name,
+            record_id: alert.record_id,
+            property_name: alert.property_name,
+            status: alert.status,
+            started_at: alert.started_at as u64,
+            triggered_at: alert.triggered_at.map(|t| t as u64),
+            resolved_at: alert.resolved_at.map(|t| t as u64),
+            service_id: alert.service_id,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AssociatedAgentSlice {
     pub agent_id: String,
@@ -50,6 +81,9 @@ pub struct ProposalSlice {
     pub timestamp: u64,
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub service_id: Option<String>,
 }
 
@@ -63,6 +97,7 @@ impl From<Proposal> for ProposalSlice {
             status: proposal.status.clone(),
             terms: proposal.terms.clone(),
             timestamp: proposal.timestamp as u64,
+            expires_at: proposal.expires_at.map(|expires_at| expires_at as u64),
             service_id: proposal.service_id,
         }
     }
@@ -81,6 +116,12 @@ pub struct RecordSlice {
     pub custodian_updates: Vec<AssociatedAgentSlice>,
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location_id: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub service_id: Option<String>,
 }
 
@@ -122,6 +163,8 @@ impl RecordSlice {
             proposals: proposals.into_iter().map(ProposalSlice::from).collect(),
             owner_updates,
             custodian_updates,
+            product_id: record.product_id,
+            location_id: record.location_id,
             service_id: record.service_id,
         }
     }
@@ -165,6 +208,95 @@ impl PropertySlice {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PropertyAggregateSlice {
+    pub name: String,
+    pub record_id: String,
+    pub data_type: String,
+    pub number_exponent: i64,
+    pub bucket_size: u64,
+    pub buckets: Vec<PropertyBucketSlice>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_id: Option<String>,
+}
+
+impl PropertyAggregateSlice {
+    pub fn from_model(
+        aggregation: ReportedValueAggregation,
+        service_id: Option<&str>,
+    ) -> PropertyAggregateSlice {
+        PropertyAggregateSlice {
+            name: aggregation.property_name,
+            record_id: aggregation.record_id,
+            data_type: aggregation
+                .data_type
+                .unwrap_or_else(|| "Unknown".to_string()),
+            number_exponent: aggregation.number_exponent,
+            bucket_size: aggregation.bucket_size as u64,
+            buckets: aggregation
+                .buckets
+                .into_iter()
+                .map(PropertyBucketSlice::from)
+                .collect(),
+            service_id: service_id.map(String::from),
+        }
+    }
+}
+
+/// The aggregate of a bucket of reported values. Number aggregates are the real values, already
+/// multiplied by 10^`number_exponent` of the aggregation.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PropertyBucketSlice {
+    pub start: u64,
+    pub count: u64,
+    pub last_timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg: Option<f64>,
+    pub last: BucketValue,
+}
+
+/// The last value of a bucket
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum BucketValue {
+    Number(f64),
+    LatLong(LatLong),
+}
+
+impl From<ReportedValueBucket> for PropertyBucketSlice {
+    fn from(bucket: ReportedValueBucket) -> Self {
+        let (min, max, avg, last) = match bucket.aggregate {
+            ReportedValueAggregate::Number {
+                min,
+                max,
+                avg,
+                last,
+            } => (Some(min), Some(max), Some(avg), BucketValue::Number(last)),
+            ReportedValueAggregate::LatLong { last } => (
+                None,
+                None,
+                None,
+                BucketValue::LatLong(LatLong::from_model(last)),
+            ),
+        };
+
+        Self {
+            start: bucket.bucket_start as u64,
+            count: bucket.value_count as u64,
+            last_timestamp: bucket.last_timestamp as u64,
+            min,
+            max,
+            avg,
+            last,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PropertyValueSlice {
     pub timestamp: u64,
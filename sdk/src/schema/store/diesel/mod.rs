@@ -20,7 +20,7 @@ use crate::error::ResourceTemporarilyUnavailableError;
 
 use models::{GridPropertyDefinition, GridSchema, NewGridPropertyDefinition, NewGridSchema};
 use operations::{
-    add_schema::AddSchemaOperation,
+    add_schema::AddSchemaOperation, delete_schema::DeleteSchemaOperation,
     get_property_definition_by_name::GetPropertyDefinitionByNameOperation,
     get_schema::GetSchemaOperation, list_property_definitions::ListPropertyDefinitionsOperation,
     list_property_definitions_with_schema_name::ListPropertyDefinitionsWithSchemaNameOperation,
@@ -120,6 +120,20 @@ impl SchemaStore for DieselSchemaStore<diesel::pg::PgConnection> {
         })?)
         .get_property_definition_by_name(schema_name, definition_name, service_id)
     }
+
+    fn delete_schema(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), SchemaStoreError> {
+        SchemaStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            SchemaStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_schema(address, current_commit_num, service_id)
+    }
 }
 
 #[cfg(feature = "sqlite")]
@@ -198,6 +212,20 @@ impl SchemaStore for DieselSchemaStore<diesel::sqlite::SqliteConnection> {
         })?)
         .get_property_definition_by_name(schema_name, definition_name, service_id)
     }
+
+    fn delete_schema(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), SchemaStoreError> {
+        SchemaStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            SchemaStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_schema(address, current_commit_num, service_id)
+    }
 }
 
 pub struct DieselConnectionSchemaStore<'a, C>
@@ -269,6 +297,19 @@ impl<'a> SchemaStore for DieselConnectionSchemaStore<'a, diesel::pg::PgConnectio
             service_id,
         )
     }
+
+    fn delete_schema(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), SchemaStoreError> {
+        SchemaStoreOperations::new(self.connection).delete_schema(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
 }
 
 #[cfg(feature = "sqlite")]
@@ -322,6 +363,19 @@ impl<'a> SchemaStore for DieselConnectionSchemaStore<'a, diesel::sqlite::SqliteC
             service_id,
         )
     }
+
+    fn delete_schema(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), SchemaStoreError> {
+        SchemaStoreOperations::new(self.connection).delete_schema(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
 }
 
 impl From<Schema> for (NewGridSchema, Vec<NewGridPropertyDefinition>) {
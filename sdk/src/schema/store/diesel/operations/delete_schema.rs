@@ -0,0 +1,227 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "delete schema" operation for the `DieselSchemaStore`.
+
+use super::SchemaStoreOperations;
+
+use crate::error::InternalError;
+use crate::schema::{
+    addressing::compute_schema_address,
+    store::{
+        diesel::schema::{grid_property_definition, grid_schema},
+        error::SchemaStoreError,
+    },
+    MAX_COMMIT_NUM,
+};
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::schema) trait DeleteSchemaOperation {
+    fn delete_schema(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), SchemaStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> DeleteSchemaOperation for SchemaStoreOperations<'a, diesel::pg::PgConnection> {
+    fn delete_schema(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), SchemaStoreError> {
+        self.conn.transaction::<_, SchemaStoreError, _>(|| {
+            let mut query = grid_schema::table
+                .into_boxed()
+                .select((grid_schema::id, grid_schema::name))
+                .filter(grid_schema::end_commit_num.eq(MAX_COMMIT_NUM));
+
+            if let Some(service_id) = service_id {
+                query = query.filter(grid_schema::service_id.eq(service_id));
+            } else {
+                query = query.filter(grid_schema::service_id.is_null());
+            }
+
+            let schemas = query
+                .load::<(i64, String)>(self.conn)
+                .map_err(|err| {
+                    SchemaStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?
+                .into_iter()
+                .filter(|(_, name)| compute_schema_address(name) == address);
+
+            for (id, name) in schemas {
+                let mut query = grid_property_definition::table
+                    .into_boxed()
+                    .select(grid_property_definition::id)
+                    .filter(
+                        grid_property_definition::schema_name
+                            .eq(&name)
+                            .and(grid_property_definition::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(grid_property_definition::service_id.eq(service_id));
+                } else {
+                    query = query.filter(grid_property_definition::service_id.is_null());
+                }
+
+                let definition_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    SchemaStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+                update(grid_property_definition::table)
+                    .filter(grid_property_definition::id.eq_any(definition_ids))
+                    .set(grid_property_definition::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(SchemaStoreError::from)?;
+
+                update(grid_schema::table)
+                    .filter(grid_schema::id.eq(id))
+                    .set(grid_schema::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(SchemaStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> DeleteSchemaOperation for SchemaStoreOperations<'a, diesel::sqlite::SqliteConnection> {
+    fn delete_schema(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), SchemaStoreError> {
+        self.conn.transaction::<_, SchemaStoreError, _>(|| {
+            let mut query = grid_schema::table
+                .into_boxed()
+                .select((grid_schema::id, grid_schema::name))
+                .filter(grid_schema::end_commit_num.eq(MAX_COMMIT_NUM));
+
+            if let Some(service_id) = service_id {
+                query = query.filter(grid_schema::service_id.eq(service_id));
+            } else {
+                query = query.filter(grid_schema::service_id.is_null());
+            }
+
+            let schemas = query
+                .load::<(i64, String)>(self.conn)
+                .map_err(|err| {
+                    SchemaStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?
+                .into_iter()
+                .filter(|(_, name)| compute_schema_address(name) == address);
+
+            for (id, name) in schemas {
+                let mut query = grid_property_definition::table
+                    .into_boxed()
+                    .select(grid_property_definition::id)
+                    .filter(
+                        grid_property_definition::schema_name
+                            .eq(&name)
+                            .and(grid_property_definition::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(grid_property_definition::service_id.eq(service_id));
+                } else {
+                    query = query.filter(grid_property_definition::service_id.is_null());
+                }
+
+                let definition_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    SchemaStoreError::InternalError(InternalError::from_source(Box::new(err)))
+                })?;
+
+                update(grid_property_definition::table)
+                    .filter(grid_property_definition::id.eq_any(definition_ids))
+                    .set(grid_property_definition::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(SchemaStoreError::from)?;
+
+                update(grid_schema::table)
+                    .filter(grid_schema::id.eq(id))
+                    .set(grid_schema::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(SchemaStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+
+    use diesel::{sql_query, sqlite::SqliteConnection};
+
+    /// Verify that deleting a schema ends it and its property definitions at the given commit,
+    /// leaving the schema stored at the same address for another service untouched.
+    #[test]
+    fn test_delete_schema() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        sql_query(
+            "INSERT INTO grid_schema \
+            (start_commit_num, end_commit_num, name, description, owner, service_id) VALUES \
+            (1, 9223372036854775807, 'schema', '', 'org', NULL), \
+            (1, 9223372036854775807, 'schema', '', 'org', 'service')",
+        )
+        .execute(&conn)?;
+        sql_query(
+            "INSERT INTO grid_property_definition \
+            (start_commit_num, end_commit_num, name, schema_name, data_type, required, \
+            description, number_exponent, enum_options, service_id) VALUES \
+            (1, 9223372036854775807, 'weight', 'schema', 'Number', 1, '', 0, '', NULL), \
+            (1, 9223372036854775807, 'color', 'schema', 'String', 0, '', 0, '', NULL), \
+            (1, 9223372036854775807, 'weight', 'schema', 'Number', 1, '', 0, '', 'service')",
+        )
+        .execute(&conn)?;
+
+        SchemaStoreOperations::new(&conn).delete_schema(
+            &compute_schema_address("schema"),
+            5,
+            None,
+        )?;
+
+        assert_eq!(
+            grid_schema::table
+                .select((grid_schema::service_id, grid_schema::end_commit_num))
+                .order(grid_schema::id)
+                .load::<(Option<String>, i64)>(&conn)?,
+            vec![(None, 5), (Some("service".to_string()), MAX_COMMIT_NUM)]
+        );
+        assert_eq!(
+            grid_property_definition::table
+                .select(grid_property_definition::end_commit_num)
+                .order(grid_property_definition::id)
+                .load::<i64>(&conn)?,
+            vec![5, 5, MAX_COMMIT_NUM]
+        );
+
+        Ok(())
+    }
+}
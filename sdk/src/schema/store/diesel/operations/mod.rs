@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub(super) mod add_schema;
+pub(super) mod delete_schema;
 pub(super) mod get_property_definition_by_name;
 pub(super) mod get_schema;
 pub(super) mod list_property_definitions;
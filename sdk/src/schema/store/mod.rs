@@ -135,6 +135,20 @@ pub trait SchemaStore {
         definition_name: &str,
         service_id: Option<&str>,
     ) -> Result<Option<PropertyDefinition>, SchemaStoreError>;
+
+    /// Deletes a schema and its property definitions from the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the schema to delete
+    ///  * `current_commit_num` - The current commit number to update the chain record
+    ///  * `service_id` - The service ID of the schema to delete
+    fn delete_schema(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), SchemaStoreError>;
 }
 
 impl<SS> SchemaStore for Box<SS>
@@ -185,4 +199,13 @@ where
     ) -> Result<Option<PropertyDefinition>, SchemaStoreError> {
         (**self).get_property_definition_by_name(schema_name, definition_name, service_id)
     }
+
+    fn delete_schema(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), SchemaStoreError> {
+        (**self).delete_schema(address, current_commit_num, service_id)
+    }
 }
@@ -14,3 +14,5 @@
 
 //! This module will contain components that will be used to support different threading models
 pub mod lifecycle;
+#[cfg(feature = "pacemaker")]
+pub mod pacemaker;
@@ -121,6 +121,16 @@ where
     }
 }
 
+impl<M, F> Default for PacemakerBuilder<M, F>
+where
+    M: Send + 'static,
+    F: Fn() -> M + Send + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Pacemaker is responsible for periodically sending a message to
 /// another component over a channel. The message is meant to be used as
 /// a notification that some action should take place.
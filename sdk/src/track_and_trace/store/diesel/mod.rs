@@ -20,27 +20,39 @@ use diesel::connection::AnsiTransactionManager;
 use diesel::r2d2::{ConnectionManager, Pool};
 
 use super::diesel::models::{
-    AssociatedAgentModel, NewAssociatedAgentModel, NewPropertyModel, NewProposalModel,
-    NewRecordModel, NewReportedValueModel, NewReporterModel, PropertyModel, ProposalModel,
-    RecordModel, ReportedValueReporterToAgentMetadataModel, ReporterModel,
+    AlertModel, AssociatedAgentModel, NewAlertModel, NewAssociatedAgentModel, NewPropertyModel,
+    NewProposalModel, NewRecordModel, NewReportedValueModel, NewReporterModel, PropertyModel,
+    ProposalModel, RecordModel, ReportedValueReporterToAgentMetadataModel, ReporterModel,
 };
 use super::{
-    AssociatedAgent, LatLongValue, Property, Proposal, Record, RecordList, ReportedValue,
+    Alert, AssociatedAgent, LatLongValue, ListProposalFilters, ListRecordFilters, Property,
+    Proposal, Record, RecordList, ReportedValue, ReportedValueAggregation,
     ReportedValueReporterToAgentMetadata, Reporter, TrackAndTraceStore, TrackAndTraceStoreError,
 };
 use crate::error::{
     ConstraintViolationError, ConstraintViolationType, InternalError,
     ResourceTemporarilyUnavailableError,
 };
+use crate::track_and_trace::addressing::{
+    make_property_address, make_proposal_address, make_record_address,
+};
+use operations::add_alerts::TrackAndTraceStoreAddAlertsOperation as _;
 use operations::add_associated_agents::TrackAndTraceStoreAddAssociatedAgentsOperation as _;
 use operations::add_properties::TrackAndTraceStoreAddPropertiesOperation as _;
 use operations::add_proposals::TrackAndTraceStoreAddProposalsOperation as _;
 use operations::add_records::TrackAndTraceStoreAddRecordsOperation as _;
 use operations::add_reported_values::TrackAndTraceStoreAddReportedValuesOperation as _;
 use operations::add_reporters::TrackAndTraceStoreAddReportersOperation as _;
+use operations::aggregate_reported_values::TrackAndTraceStoreAggregateReportedValuesOperation as _;
+use operations::delete_property::TrackAndTraceStoreDeletePropertyOperation as _;
+use operations::delete_proposals::TrackAndTraceStoreDeleteProposalsOperation as _;
+use operations::delete_record::TrackAndTraceStoreDeleteRecordOperation as _;
+use operations::get_open_alert::TrackAndTraceStoreGetOpenAlertOperation as _;
 use operations::get_property_with_data_type::TrackAndTraceStoreGetPropertyWithDataTypeOperation as _;
 use operations::get_record::TrackAndTraceStoreGetRecordOperation as _;
 use operations::get_reported_value_reporter_to_agent_metadata::TrackAndTraceStoreGetReportedValueReporterToAgentMetadataOperation as _;
+use operations::list_alerts::TrackAndTraceStoreListAlertsOperation as _;
+use operations::list_alerts_by_status::TrackAndTraceStoreListAlertsByStatusOperation as _;
 use operations::list_associated_agents::TrackAndTraceStoreListAssociatedAgentsOperation as _;
 use operations::list_properties_with_data_type::TrackAndTraceStoreListPropertiesWithDataTypeOperation as _;
 use operations::list_proposals::TrackAndTraceStoreListProposalsOperation as _;
@@ -69,6 +81,15 @@ impl<C: diesel::Connection> DieselTrackAndTraceStore<C> {
 
 #[cfg(feature = "postgres")]
 impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::pg::PgConnection> {
+    fn add_alerts(&self, alerts: Vec<Alert>) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .add_alerts(alerts.into_iter().map(|a| a.into()).collect())
+    }
+
     fn add_associated_agents(
         &self,
         agents: Vec<AssociatedAgent>,
@@ -129,6 +150,45 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::pg::PgConnection> {
         .add_reporters(reporters.into_iter().map(|r| r.into()).collect())
     }
 
+    fn aggregate_reported_values(
+        &self,
+        record_id: &str,
+        property_name: &str,
+        bucket_size: i64,
+        start: Option<i64>,
+        end: Option<i64>,
+        service_id: Option<&str>,
+    ) -> Result<Option<ReportedValueAggregation>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .aggregate_reported_values(
+            record_id,
+            property_name,
+            bucket_size,
+            start,
+            end,
+            service_id,
+        )
+    }
+
+    fn get_open_alert(
+        &self,
+        rule_name: &str,
+        record_id: &str,
+        property_name: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_open_alert(rule_name, record_id, property_name, service_id)
+    }
+
     fn get_property_with_data_type(
         &self,
         record_id: &str,
@@ -176,6 +236,32 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::pg::PgConnection> {
         )
     }
 
+    fn list_alerts(
+        &self,
+        record_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_alerts(record_id, service_id)
+    }
+
+    fn list_alerts_by_status(
+        &self,
+        status: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_alerts_by_status(status, service_id)
+    }
+
     fn list_associated_agents(
         &self,
         record_ids: &[String],
@@ -205,6 +291,7 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::pg::PgConnection> {
     fn list_proposals(
         &self,
         record_ids: &[String],
+        filters: ListProposalFilters,
         service_id: Option<&str>,
     ) -> Result<Vec<Proposal>, TrackAndTraceStoreError> {
         TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
@@ -212,11 +299,12 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::pg::PgConnection> {
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .list_proposals(record_ids, service_id)
+        .list_proposals(record_ids, filters, service_id)
     }
 
     fn list_records(
         &self,
+        filters: ListRecordFilters,
         service_id: Option<&str>,
         offset: i64,
         limit: i64,
@@ -226,7 +314,7 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::pg::PgConnection> {
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .list_records(service_id, offset, limit)
+        .list_records(filters, service_id, offset, limit)
     }
 
     fn list_reported_value_reporter_to_agent_metadata(
@@ -260,10 +348,61 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::pg::PgConnection> {
         })?)
         .list_reporters(record_id, property_name, service_id)
     }
+
+    fn delete_record(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_record(address, current_commit_num, service_id)
+    }
+
+    fn delete_property(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_property(address, current_commit_num, service_id)
+    }
+
+    fn delete_proposals(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_proposals(address, current_commit_num, service_id)
+    }
 }
 
 #[cfg(feature = "sqlite")]
 impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::sqlite::SqliteConnection> {
+    fn add_alerts(&self, alerts: Vec<Alert>) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .add_alerts(alerts.into_iter().map(|a| a.into()).collect())
+    }
+
     fn add_associated_agents(
         &self,
         agents: Vec<AssociatedAgent>,
@@ -324,6 +463,45 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::sqlite::SqliteConne
         .add_reporters(reporters.into_iter().map(|r| r.into()).collect())
     }
 
+    fn aggregate_reported_values(
+        &self,
+        record_id: &str,
+        property_name: &str,
+        bucket_size: i64,
+        start: Option<i64>,
+        end: Option<i64>,
+        service_id: Option<&str>,
+    ) -> Result<Option<ReportedValueAggregation>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .aggregate_reported_values(
+            record_id,
+            property_name,
+            bucket_size,
+            start,
+            end,
+            service_id,
+        )
+    }
+
+    fn get_open_alert(
+        &self,
+        rule_name: &str,
+        record_id: &str,
+        property_name: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .get_open_alert(rule_name, record_id, property_name, service_id)
+    }
+
     fn get_property_with_data_type(
         &self,
         record_id: &str,
@@ -371,6 +549,32 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::sqlite::SqliteConne
         )
     }
 
+    fn list_alerts(
+        &self,
+        record_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_alerts(record_id, service_id)
+    }
+
+    fn list_alerts_by_status(
+        &self,
+        status: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .list_alerts_by_status(status, service_id)
+    }
+
     fn list_associated_agents(
         &self,
         record_ids: &[String],
@@ -400,6 +604,7 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::sqlite::SqliteConne
     fn list_proposals(
         &self,
         record_ids: &[String],
+        filters: ListProposalFilters,
         service_id: Option<&str>,
     ) -> Result<Vec<Proposal>, TrackAndTraceStoreError> {
         TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
@@ -407,11 +612,12 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::sqlite::SqliteConne
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .list_proposals(record_ids, service_id)
+        .list_proposals(record_ids, filters, service_id)
     }
 
     fn list_records(
         &self,
+        filters: ListRecordFilters,
         service_id: Option<&str>,
         offset: i64,
         limit: i64,
@@ -421,7 +627,7 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::sqlite::SqliteConne
                 ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
             )
         })?)
-        .list_records(service_id, offset, limit)
+        .list_records(filters, service_id, offset, limit)
     }
 
     fn list_reported_value_reporter_to_agent_metadata(
@@ -455,6 +661,48 @@ impl TrackAndTraceStore for DieselTrackAndTraceStore<diesel::sqlite::SqliteConne
         })?)
         .list_reporters(record_id, property_name, service_id)
     }
+
+    fn delete_record(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_record(address, current_commit_num, service_id)
+    }
+
+    fn delete_property(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_property(address, current_commit_num, service_id)
+    }
+
+    fn delete_proposals(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(&*self.connection_pool.get().map_err(|err| {
+            TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(
+                ResourceTemporarilyUnavailableError::from_source(Box::new(err)),
+            )
+        })?)
+        .delete_proposals(address, current_commit_num, service_id)
+    }
 }
 
 pub struct DieselConnectionTrackAndTraceStore<'a, C>
@@ -477,6 +725,11 @@ where
 
 #[cfg(feature = "postgres")]
 impl<'a> TrackAndTraceStore for DieselConnectionTrackAndTraceStore<'a, diesel::pg::PgConnection> {
+    fn add_alerts(&self, alerts: Vec<Alert>) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection)
+            .add_alerts(alerts.into_iter().map(|a| a.into()).collect())
+    }
+
     fn add_associated_agents(
         &self,
         agents: Vec<AssociatedAgent>,
@@ -513,6 +766,40 @@ impl<'a> TrackAndTraceStore for DieselConnectionTrackAndTraceStore<'a, diesel::p
             .add_reporters(reporters.into_iter().map(|r| r.into()).collect())
     }
 
+    fn aggregate_reported_values(
+        &self,
+        record_id: &str,
+        property_name: &str,
+        bucket_size: i64,
+        start: Option<i64>,
+        end: Option<i64>,
+        service_id: Option<&str>,
+    ) -> Result<Option<ReportedValueAggregation>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).aggregate_reported_values(
+            record_id,
+            property_name,
+            bucket_size,
+            start,
+            end,
+            service_id,
+        )
+    }
+
+    fn get_open_alert(
+        &self,
+        rule_name: &str,
+        record_id: &str,
+        property_name: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).get_open_alert(
+            rule_name,
+            record_id,
+            property_name,
+            service_id,
+        )
+    }
+
     fn get_property_with_data_type(
         &self,
         record_id: &str,
@@ -550,6 +837,22 @@ impl<'a> TrackAndTraceStore for DieselConnectionTrackAndTraceStore<'a, diesel::p
             )
     }
 
+    fn list_alerts(
+        &self,
+        record_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).list_alerts(record_id, service_id)
+    }
+
+    fn list_alerts_by_status(
+        &self,
+        status: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).list_alerts_by_status(status, service_id)
+    }
+
     fn list_associated_agents(
         &self,
         record_ids: &[String],
@@ -571,18 +874,22 @@ impl<'a> TrackAndTraceStore for DieselConnectionTrackAndTraceStore<'a, diesel::p
     fn list_proposals(
         &self,
         record_ids: &[String],
+        filters: ListProposalFilters,
         service_id: Option<&str>,
     ) -> Result<Vec<Proposal>, TrackAndTraceStoreError> {
-        TrackAndTraceStoreOperations::new(self.connection).list_proposals(record_ids, service_id)
+        TrackAndTraceStoreOperations::new(self.connection)
+            .list_proposals(record_ids, filters, service_id)
     }
 
     fn list_records(
         &self,
+        filters: ListRecordFilters,
         service_id: Option<&str>,
         offset: i64,
         limit: i64,
     ) -> Result<RecordList, TrackAndTraceStoreError> {
-        TrackAndTraceStoreOperations::new(self.connection).list_records(service_id, offset, limit)
+        TrackAndTraceStoreOperations::new(self.connection)
+            .list_records(filters, service_id, offset, limit)
     }
 
     fn list_reported_value_reporter_to_agent_metadata(
@@ -607,12 +914,56 @@ impl<'a> TrackAndTraceStore for DieselConnectionTrackAndTraceStore<'a, diesel::p
             service_id,
         )
     }
+
+    fn delete_record(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).delete_record(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn delete_property(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).delete_property(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn delete_proposals(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).delete_proposals(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
 }
 
 #[cfg(feature = "sqlite")]
 impl<'a> TrackAndTraceStore
     for DieselConnectionTrackAndTraceStore<'a, diesel::sqlite::SqliteConnection>
 {
+    fn add_alerts(&self, alerts: Vec<Alert>) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection)
+            .add_alerts(alerts.into_iter().map(|a| a.into()).collect())
+    }
+
     fn add_associated_agents(
         &self,
         agents: Vec<AssociatedAgent>,
@@ -649,6 +1000,40 @@ impl<'a> TrackAndTraceStore
             .add_reporters(reporters.into_iter().map(|r| r.into()).collect())
     }
 
+    fn aggregate_reported_values(
+        &self,
+        record_id: &str,
+        property_name: &str,
+        bucket_size: i64,
+        start: Option<i64>,
+        end: Option<i64>,
+        service_id: Option<&str>,
+    ) -> Result<Option<ReportedValueAggregation>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).aggregate_reported_values(
+            record_id,
+            property_name,
+            bucket_size,
+            start,
+            end,
+            service_id,
+        )
+    }
+
+    fn get_open_alert(
+        &self,
+        rule_name: &str,
+        record_id: &str,
+        property_name: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).get_open_alert(
+            rule_name,
+            record_id,
+            property_name,
+            service_id,
+        )
+    }
+
     fn get_property_with_data_type(
         &self,
         record_id: &str,
@@ -686,6 +1071,22 @@ impl<'a> TrackAndTraceStore
             )
     }
 
+    fn list_alerts(
+        &self,
+        record_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).list_alerts(record_id, service_id)
+    }
+
+    fn list_alerts_by_status(
+        &self,
+        status: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).list_alerts_by_status(status, service_id)
+    }
+
     fn list_associated_agents(
         &self,
         record_ids: &[String],
@@ -707,18 +1108,22 @@ impl<'a> TrackAndTraceStore
     fn list_proposals(
         &self,
         record_ids: &[String],
+        filters: ListProposalFilters,
         service_id: Option<&str>,
     ) -> Result<Vec<Proposal>, TrackAndTraceStoreError> {
-        TrackAndTraceStoreOperations::new(self.connection).list_proposals(record_ids, service_id)
+        TrackAndTraceStoreOperations::new(self.connection)
+            .list_proposals(record_ids, filters, service_id)
     }
 
     fn list_records(
         &self,
+        filters: ListRecordFilters,
         service_id: Option<&str>,
         offset: i64,
         limit: i64,
     ) -> Result<RecordList, TrackAndTraceStoreError> {
-        TrackAndTraceStoreOperations::new(self.connection).list_records(service_id, offset, limit)
+        TrackAndTraceStoreOperations::new(self.connection)
+            .list_records(filters, service_id, offset, limit)
     }
 
     fn list_reported_value_reporter_to_agent_metadata(
@@ -743,6 +1148,45 @@ impl<'a> TrackAndTraceStore
             service_id,
         )
     }
+
+    fn delete_record(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).delete_record(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn delete_property(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).delete_property(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
+
+    fn delete_proposals(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        TrackAndTraceStoreOperations::new(self.connection).delete_proposals(
+            address,
+            current_commit_num,
+            service_id,
+        )
+    }
 }
 
 impl From<(i64, i64)> for LatLongValue {
@@ -751,6 +1195,23 @@ impl From<(i64, i64)> for LatLongValue {
     }
 }
 
+impl From<Alert> for NewAlertModel {
+    fn from(alert: Alert) -> Self {
+        Self {
+            rule_name: alert.rule_name,
+            record_id: alert.record_id,
+            property_name: alert.property_name,
+            status: alert.status,
+            started_at: alert.started_at,
+            triggered_at: alert.triggered_at,
+            resolved_at: alert.resolved_at,
+            start_commit_num: alert.start_commit_num,
+            end_commit_num: alert.end_commit_num,
+            service_id: alert.service_id,
+        }
+    }
+}
+
 impl From<AssociatedAgent> for NewAssociatedAgentModel {
     fn from(agent: AssociatedAgent) -> Self {
         Self {
@@ -768,6 +1229,7 @@ impl From<AssociatedAgent> for NewAssociatedAgentModel {
 impl From<Property> for NewPropertyModel {
     fn from(property: Property) -> Self {
         Self {
+            state_address: make_property_address(&property.record_id, &property.name, 0),
             name: property.name,
             record_id: property.record_id,
             property_definition: property.property_definition,
@@ -783,6 +1245,7 @@ impl From<Property> for NewPropertyModel {
 impl From<Proposal> for NewProposalModel {
     fn from(proposal: Proposal) -> Self {
         Self {
+            state_address: make_proposal_address(&proposal.record_id, &proposal.receiving_agent),
             record_id: proposal.record_id,
             timestamp: proposal.timestamp,
             issuing_agent: proposal.issuing_agent,
@@ -794,6 +1257,7 @@ impl From<Proposal> for NewProposalModel {
             start_commit_num: proposal.start_commit_num,
             end_commit_num: proposal.end_commit_num,
             service_id: proposal.service_id,
+            expires_at: proposal.expires_at,
         }
     }
 }
@@ -801,6 +1265,7 @@ impl From<Proposal> for NewProposalModel {
 impl From<Record> for NewRecordModel {
     fn from(record: Record) -> Self {
         Self {
+            state_address: make_record_address(&record.record_id),
             record_id: record.record_id,
             schema: record.schema,
             final_: record.final_,
@@ -809,6 +1274,8 @@ impl From<Record> for NewRecordModel {
             start_commit_num: record.start_commit_num,
             end_commit_num: record.end_commit_num,
             service_id: record.service_id,
+            product_id: record.product_id,
+            location_id: record.location_id,
         }
     }
 }
@@ -868,6 +1335,24 @@ fn make_reported_value_models(
     vals
 }
 
+impl From<AlertModel> for Alert {
+    fn from(model: AlertModel) -> Self {
+        Self {
+            id: Some(model.id),
+            rule_name: model.rule_name,
+            record_id: model.record_id,
+            property_name: model.property_name,
+            status: model.status,
+            started_at: model.started_at,
+            triggered_at: model.triggered_at,
+            resolved_at: model.resolved_at,
+            start_commit_num: model.start_commit_num,
+            end_commit_num: model.end_commit_num,
+            service_id: model.service_id,
+        }
+    }
+}
+
 impl From<AssociatedAgentModel> for AssociatedAgent {
     fn from(model: AssociatedAgentModel) -> Self {
         Self {
@@ -914,6 +1399,7 @@ impl From<ProposalModel> for Proposal {
             start_commit_num: model.start_commit_num,
             end_commit_num: model.end_commit_num,
             service_id: model.service_id,
+            expires_at: model.expires_at,
         }
     }
 }
@@ -930,6 +1416,8 @@ impl From<RecordModel> for Record {
             start_commit_num: model.start_commit_num,
             end_commit_num: model.end_commit_num,
             service_id: model.service_id,
+            product_id: model.product_id,
+            location_id: model.location_id,
         }
     }
 }
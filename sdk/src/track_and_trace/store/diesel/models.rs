@@ -14,6 +14,37 @@
 
 use crate::track_and_trace::store::diesel::schema::*;
 
+#[derive(Insertable, PartialEq, Eq, Queryable, Debug)]
+#[table_name = "alert"]
+pub struct NewAlertModel {
+    pub rule_name: String,
+    pub record_id: String,
+    pub property_name: String,
+    pub status: String,
+    pub started_at: i64,
+    pub triggered_at: Option<i64>,
+    pub resolved_at: Option<i64>,
+    pub start_commit_num: i64,
+    pub end_commit_num: i64,
+    pub service_id: Option<String>,
+}
+
+#[derive(Insertable, PartialEq, Eq, Queryable, Debug)]
+#[table_name = "alert"]
+pub struct AlertModel {
+    pub id: i64,
+    pub rule_name: String,
+    pub record_id: String,
+    pub property_name: String,
+    pub status: String,
+    pub started_at: i64,
+    pub triggered_at: Option<i64>,
+    pub resolved_at: Option<i64>,
+    pub start_commit_num: i64,
+    pub end_commit_num: i64,
+    pub service_id: Option<String>,
+}
+
 #[derive(Insertable, PartialEq, Eq, Queryable, Debug)]
 #[table_name = "associated_agent"]
 pub struct NewAssociatedAgentModel {
@@ -85,6 +116,7 @@ pub struct NewPropertyModel {
     pub start_commit_num: i64,
     pub end_commit_num: i64,
     pub service_id: Option<String>,
+    pub state_address: String,
 }
 
 #[derive(Insertable, PartialEq, Eq, Queryable, Debug)]
@@ -99,6 +131,7 @@ pub struct PropertyModel {
     pub start_commit_num: i64,
     pub end_commit_num: i64,
     pub service_id: Option<String>,
+    pub state_address: String,
 }
 
 #[derive(Insertable, PartialEq, Eq, Queryable, Debug)]
@@ -117,6 +150,8 @@ pub struct NewProposalModel {
     pub start_commit_num: i64,
     pub end_commit_num: i64,
     pub service_id: Option<String>,
+    pub expires_at: Option<i64>,
+    pub state_address: String,
 }
 
 #[derive(Insertable, PartialEq, Eq, Queryable, Debug)]
@@ -135,6 +170,8 @@ pub struct ProposalModel {
     pub start_commit_num: i64,
     pub end_commit_num: i64,
     pub service_id: Option<String>,
+    pub expires_at: Option<i64>,
+    pub state_address: String,
 }
 
 #[derive(Insertable, PartialEq, Eq, Queryable, Debug)]
@@ -150,6 +187,9 @@ pub struct NewRecordModel {
     pub start_commit_num: i64,
     pub end_commit_num: i64,
     pub service_id: Option<String>,
+    pub product_id: Option<String>,
+    pub location_id: Option<String>,
+    pub state_address: String,
 }
 
 #[derive(Insertable, PartialEq, Eq, Queryable, Debug)]
@@ -166,6 +206,9 @@ pub struct RecordModel {
     pub start_commit_num: i64,
     pub end_commit_num: i64,
     pub service_id: Option<String>,
+    pub product_id: Option<String>,
+    pub location_id: Option<String>,
+    pub state_address: String,
 }
 
 #[derive(Insertable, PartialEq, Eq, Queryable, Debug)]
@@ -0,0 +1,182 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::TrackAndTraceStoreOperations;
+use crate::track_and_trace::store::diesel::{schema::alert, TrackAndTraceStoreError};
+
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::{ConstraintViolationError, ConstraintViolationType, InternalError};
+use crate::track_and_trace::store::diesel::models::{AlertModel, NewAlertModel};
+
+use diesel::{
+    dsl::{insert_into, update},
+    prelude::*,
+    result::{DatabaseErrorKind, Error as dsl_error},
+};
+
+pub(in crate::track_and_trace::store::diesel) trait TrackAndTraceStoreAddAlertsOperation {
+    fn add_alerts(&self, alerts: Vec<NewAlertModel>) -> Result<(), TrackAndTraceStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> TrackAndTraceStoreAddAlertsOperation
+    for TrackAndTraceStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn add_alerts(&self, alerts: Vec<NewAlertModel>) -> Result<(), TrackAndTraceStoreError> {
+        self.conn
+            .build_transaction()
+            .read_write()
+            .run::<_, TrackAndTraceStoreError, _>(|| {
+                for new_alert in alerts {
+                    let mut query = alert::table.into_boxed().select(alert::all_columns).filter(
+                        alert::rule_name
+                            .eq(&new_alert.rule_name)
+                            .and(alert::record_id.eq(&new_alert.record_id))
+                            .and(alert::property_name.eq(&new_alert.property_name))
+                            .and(alert::started_at.eq(new_alert.started_at))
+                            .and(alert::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                    if let Some(service_id) = &new_alert.service_id {
+                        query = query.filter(alert::service_id.eq(service_id));
+                    } else {
+                        query = query.filter(alert::service_id.is_null());
+                    }
+
+                    let duplicate = query
+                        .first::<AlertModel>(self.conn)
+                        .map(Some)
+                        .or_else(|err| {
+                            if err == dsl_error::NotFound {
+                                Ok(None)
+                            } else {
+                                Err(err)
+                            }
+                        })
+                        .map_err(|err| {
+                            TrackAndTraceStoreError::InternalError(InternalError::from_source(
+                                Box::new(err),
+                            ))
+                        })?;
+
+                    if let Some(duplicate) = duplicate {
+                        update(alert::table.find(duplicate.id))
+                            .set(alert::end_commit_num.eq(new_alert.start_commit_num))
+                            .execute(self.conn)
+                            .map(|_| ())
+                            .map_err(|err| {
+                                TrackAndTraceStoreError::InternalError(InternalError::from_source(
+                                    Box::new(err),
+                                ))
+                            })?;
+                    }
+
+                    insert_into(alert::table)
+                        .values(&new_alert)
+                        .execute(self.conn)
+                        .map(|_| ())
+                        .map_err(|err| match err {
+                            dsl_error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                                TrackAndTraceStoreError::ConstraintViolationError(
+                                    ConstraintViolationError::from_source_with_violation_type(
+                                        ConstraintViolationType::Unique,
+                                        Box::new(err),
+                                    ),
+                                )
+                            }
+                            _ => TrackAndTraceStoreError::InternalError(
+                                InternalError::from_source(Box::new(err)),
+                            ),
+                        })?;
+                }
+
+                Ok(())
+            })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> TrackAndTraceStoreAddAlertsOperation
+    for TrackAndTraceStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_alerts(&self, alerts: Vec<NewAlertModel>) -> Result<(), TrackAndTraceStoreError> {
+        self.conn
+            .immediate_transaction::<_, TrackAndTraceStoreError, _>(|| {
+                for new_alert in alerts {
+                    let mut query = alert::table.into_boxed().select(alert::all_columns).filter(
+                        alert::rule_name
+                            .eq(&new_alert.rule_name)
+                            .and(alert::record_id.eq(&new_alert.record_id))
+                            .and(alert::property_name.eq(&new_alert.property_name))
+                            .and(alert::started_at.eq(new_alert.started_at))
+                            .and(alert::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                    if let Some(service_id) = &new_alert.service_id {
+                        query = query.filter(alert::service_id.eq(service_id));
+                    } else {
+                        query = query.filter(alert::service_id.is_null());
+                    }
+
+                    let duplicate = query
+                        .first::<AlertModel>(self.conn)
+                        .map(Some)
+                        .or_else(|err| {
+                            if err == dsl_error::NotFound {
+                                Ok(None)
+                            } else {
+                                Err(err)
+                            }
+                        })
+                        .map_err(|err| {
+                            TrackAndTraceStoreError::InternalError(InternalError::from_source(
+                                Box::new(err),
+                            ))
+                        })?;
+
+                    if let Some(duplicate) = duplicate {
+                        update(alert::table.find(duplicate.id))
+                            .set(alert::end_commit_num.eq(new_alert.start_commit_num))
+                            .execute(self.conn)
+                            .map(|_| ())
+                            .map_err(|err| {
+                                TrackAndTraceStoreError::InternalError(InternalError::from_source(
+                                    Box::new(err),
+                                ))
+                            })?;
+                    }
+
+                    insert_into(alert::table)
+                        .values(&new_alert)
+                        .execute(self.conn)
+                        .map(|_| ())
+                        .map_err(|err| match err {
+                            dsl_error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                                TrackAndTraceStoreError::ConstraintViolationError(
+                                    ConstraintViolationError::from_source_with_violation_type(
+                                        ConstraintViolationType::Unique,
+                                        Box::new(err),
+                                    ),
+                                )
+                            }
+                            _ => TrackAndTraceStoreError::InternalError(
+                                InternalError::from_source(Box::new(err)),
+                            ),
+                        })?;
+                }
+
+                Ok(())
+            })
+    }
+}
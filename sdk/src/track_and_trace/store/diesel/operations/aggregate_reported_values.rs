@@ -0,0 +1,445 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::TrackAndTraceStoreOperations;
+use crate::track_and_trace::store::diesel::{
+    schema::{grid_property_definition, property, record},
+    TrackAndTraceStoreError,
+};
+
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::{InternalError, InvalidArgumentError};
+use crate::track_and_trace::store::{
+    LatLongValue, ReportedValueAggregate, ReportedValueAggregation, ReportedValueBucket,
+};
+
+use diesel::{
+    prelude::*,
+    result::Error::NotFound,
+    sql_query,
+    sql_types::{BigInt, Double, Nullable, Text},
+};
+
+// The queries below are written with SQLite's numbered parameters; Postgres uses `$` in place
+// of `?`. Bucket boundaries are computed with integer division on the reported timestamps.
+//
+// A property page is committed again with every value reported to it, and each commit inserts a
+// row for every value on the page, so the rows of superseded commits are included and the copies
+// of each value are then reduced to the latest row per reporter and timestamp.
+//
+// Parameters:
+//  1 - bucket size
+//  2 - record ID
+//  3 - property name
+//  4 - maximum end commit number, so that superseded values are included
+//  5 - service ID
+//  6 - start of the time range, inclusive
+//  7 - end of the time range, exclusive
+const NUMBER_BUCKETS_QUERY: &str = r#"
+    WITH reported AS
+    (
+        SELECT id,
+               timestamp,
+               number_value,
+               ROW_NUMBER() OVER (
+                   PARTITION BY reporter_index, timestamp
+                   ORDER BY id DESC
+               ) AS copy
+        FROM reported_value
+        WHERE record_id = ?2
+          AND property_name = ?3
+          AND end_commit_num <= ?4
+          AND ((?5 IS NULL AND service_id IS NULL) OR service_id = ?5)
+          AND timestamp >= ?6
+          AND timestamp < ?7
+          AND parent_name IS NULL
+          AND number_value IS NOT NULL
+    ),
+    ranked AS
+    (
+        SELECT (timestamp / ?1) * ?1 AS bucket_start,
+               timestamp,
+               number_value,
+               ROW_NUMBER() OVER (
+                   PARTITION BY timestamp / ?1
+                   ORDER BY timestamp DESC, id DESC
+               ) AS position
+        FROM reported
+        WHERE copy = 1
+    )
+    SELECT bucket_start,
+           COUNT(*) AS value_count,
+           MAX(timestamp) AS last_timestamp,
+           MIN(number_value) AS min_value,
+           MAX(number_value) AS max_value,
+           CAST(AVG(number_value) AS DOUBLE PRECISION) AS avg_value,
+           MAX(CASE WHEN position = 1 THEN number_value END) AS last_value
+    FROM ranked
+    GROUP BY bucket_start
+    ORDER BY bucket_start
+"#;
+
+const LAT_LONG_BUCKETS_QUERY: &str = r#"
+    WITH reported AS
+    (
+        SELECT id,
+               timestamp,
+               latitude_value,
+               longitude_value,
+               ROW_NUMBER() OVER (
+                   PARTITION BY reporter_index, timestamp
+                   ORDER BY id DESC
+               ) AS copy
+        FROM reported_value
+        WHERE record_id = ?2
+          AND property_name = ?3
+          AND end_commit_num <= ?4
+          AND ((?5 IS NULL AND service_id IS NULL) OR service_id = ?5)
+          AND timestamp >= ?6
+          AND timestamp < ?7
+          AND parent_name IS NULL
+          AND latitude_value IS NOT NULL
+          AND longitude_value IS NOT NULL
+    ),
+    ranked AS
+    (
+        SELECT (timestamp / ?1) * ?1 AS bucket_start,
+               timestamp,
+               latitude_value,
+               longitude_value,
+               ROW_NUMBER() OVER (
+                   PARTITION BY timestamp / ?1
+                   ORDER BY timestamp DESC, id DESC
+               ) AS position,
+               COUNT(*) OVER (PARTITION BY timestamp / ?1) AS value_count
+        FROM reported
+        WHERE copy = 1
+    )
+    SELECT bucket_start,
+           value_count,
+           timestamp AS last_timestamp,
+           latitude_value,
+           longitude_value
+    FROM ranked
+    WHERE position = 1
+    ORDER BY bucket_start
+"#;
+
+#[derive(QueryableByName)]
+struct NumberBucket {
+    #[sql_type = "BigInt"]
+    bucket_start: i64,
+    #[sql_type = "BigInt"]
+    value_count: i64,
+    #[sql_type = "BigInt"]
+    last_timestamp: i64,
+    #[sql_type = "BigInt"]
+    min_value: i64,
+    #[sql_type = "BigInt"]
+    max_value: i64,
+    #[sql_type = "Double"]
+    avg_value: f64,
+    #[sql_type = "BigInt"]
+    last_value: i64,
+}
+
+impl NumberBucket {
+    /// Converts the bucket, scaling its values by 10^number_exponent
+    fn into_bucket(self, number_exponent: i64) -> ReportedValueBucket {
+        ReportedValueBucket {
+            bucket_start: self.bucket_start,
+            value_count: self.value_count,
+            last_timestamp: self.last_timestamp,
+            aggregate: ReportedValueAggregate::Number {
+                min: scale(self.min_value as f64, number_exponent),
+                max: scale(self.max_value as f64, number_exponent),
+                avg: scale(self.avg_value, number_exponent),
+                last: scale(self.last_value as f64, number_exponent),
+            },
+        }
+    }
+}
+
+/// Multiplies the value by 10^number_exponent. A negative exponent divides by the positive power
+/// of ten instead, so that 3 with an exponent of -1 is 0.3 rather than 0.30000000000000004.
+fn scale(value: f64, number_exponent: i64) -> f64 {
+    let power = 10f64.powi(number_exponent.abs() as i32);
+    if number_exponent < 0 {
+        value / power
+    } else {
+        value * power
+    }
+}
+
+#[derive(QueryableByName)]
+struct LatLongBucket {
+    #[sql_type = "BigInt"]
+    bucket_start: i64,
+    #[sql_type = "BigInt"]
+    value_count: i64,
+    #[sql_type = "BigInt"]
+    last_timestamp: i64,
+    #[sql_type = "BigInt"]
+    latitude_value: i64,
+    #[sql_type = "BigInt"]
+    longitude_value: i64,
+}
+
+impl From<LatLongBucket> for ReportedValueBucket {
+    fn from(bucket: LatLongBucket) -> Self {
+        Self {
+            bucket_start: bucket.bucket_start,
+            value_count: bucket.value_count,
+            last_timestamp: bucket.last_timestamp,
+            aggregate: ReportedValueAggregate::LatLong {
+                last: LatLongValue(bucket.latitude_value, bucket.longitude_value),
+            },
+        }
+    }
+}
+
+pub(in crate::track_and_trace::store::diesel) trait TrackAndTraceStoreAggregateReportedValuesOperation
+{
+    fn aggregate_reported_values(
+        &self,
+        record_id: &str,
+        property_name: &str,
+        bucket_size: i64,
+        start: Option<i64>,
+        end: Option<i64>,
+        service_id: Option<&str>,
+    ) -> Result<Option<ReportedValueAggregation>, TrackAndTraceStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> TrackAndTraceStoreAggregateReportedValuesOperation
+    for TrackAndTraceStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn aggregate_reported_values(
+        &self,
+        record_id: &str,
+        property_name: &str,
+        bucket_size: i64,
+        start: Option<i64>,
+        end: Option<i64>,
+        service_id: Option<&str>,
+    ) -> Result<Option<ReportedValueAggregation>, TrackAndTraceStoreError> {
+        check_bucket_size(bucket_size)?;
+
+        let mut query = property::table
+            .into_boxed()
+            .left_join(
+                record::table.on(property::record_id
+                    .eq(record::record_id)
+                    .and(property::end_commit_num.eq(record::end_commit_num))),
+            )
+            .left_join(
+                grid_property_definition::table.on(record::schema
+                    .eq(grid_property_definition::schema_name)
+                    .and(property::name.eq(grid_property_definition::name))
+                    .and(property::end_commit_num.eq(record::end_commit_num))),
+            )
+            .filter(
+                property::name
+                    .eq(property_name)
+                    .and(property::record_id.eq(record_id))
+                    .and(property::end_commit_num.eq(MAX_COMMIT_NUM)),
+            );
+
+        if let Some(service_id) = service_id {
+            query = query.filter(property::service_id.eq(service_id));
+        } else {
+            query = query.filter(property::service_id.is_null());
+        }
+
+        let (data_type, number_exponent) = match query
+            .select((
+                grid_property_definition::data_type.nullable(),
+                grid_property_definition::number_exponent.nullable(),
+            ))
+            .first::<(Option<String>, Option<i64>)>(self.conn)
+            .map(Some)
+            .or_else(|err| if err == NotFound { Ok(None) } else { Err(err) })
+            .map_err(|err| {
+                TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })? {
+            Some(definition) => definition,
+            None => return Ok(None),
+        };
+
+        let sql = match data_type.as_deref() {
+            Some("Number") => NUMBER_BUCKETS_QUERY.replace('?', "$"),
+            Some("LatLong") => LAT_LONG_BUCKETS_QUERY.replace('?', "$"),
+            _ => String::new(),
+        };
+
+        let buckets = if sql.is_empty() {
+            vec![]
+        } else {
+            let query = sql_query(sql)
+                .bind::<BigInt, _>(bucket_size)
+                .bind::<Text, _>(record_id)
+                .bind::<Text, _>(property_name)
+                .bind::<BigInt, _>(MAX_COMMIT_NUM)
+                .bind::<Nullable<Text>, _>(service_id)
+                .bind::<BigInt, _>(start.unwrap_or(0))
+                .bind::<BigInt, _>(end.unwrap_or(i64::MAX));
+
+            if data_type.as_deref() == Some("Number") {
+                query.load::<NumberBucket>(self.conn).map(|buckets| {
+                    buckets
+                        .into_iter()
+                        .map(|bucket| bucket.into_bucket(number_exponent.unwrap_or(0)))
+                        .collect::<Vec<_>>()
+                })
+            } else {
+                query.load::<LatLongBucket>(self.conn).map(|buckets| {
+                    buckets
+                        .into_iter()
+                        .map(ReportedValueBucket::from)
+                        .collect::<Vec<_>>()
+                })
+            }
+            .map_err(|err| {
+                TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })?
+        };
+
+        Ok(Some(ReportedValueAggregation {
+            record_id: record_id.to_string(),
+            property_name: property_name.to_string(),
+            data_type,
+            number_exponent: number_exponent.unwrap_or(0),
+            bucket_size,
+            buckets,
+        }))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> TrackAndTraceStoreAggregateReportedValuesOperation
+    for TrackAndTraceStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn aggregate_reported_values(
+        &self,
+        record_id: &str,
+        property_name: &str,
+        bucket_size: i64,
+        start: Option<i64>,
+        end: Option<i64>,
+        service_id: Option<&str>,
+    ) -> Result<Option<ReportedValueAggregation>, TrackAndTraceStoreError> {
+        check_bucket_size(bucket_size)?;
+
+        let mut query = property::table
+            .into_boxed()
+            .left_join(
+                record::table.on(property::record_id
+                    .eq(record::record_id)
+                    .and(property::end_commit_num.eq(record::end_commit_num))),
+            )
+            .left_join(
+                grid_property_definition::table.on(record::schema
+                    .eq(grid_property_definition::schema_name)
+                    .and(property::name.eq(grid_property_definition::name))
+                    .and(property::end_commit_num.eq(record::end_commit_num))),
+            )
+            .filter(
+                property::name
+                    .eq(property_name)
+                    .and(property::record_id.eq(record_id))
+                    .and(property::end_commit_num.eq(MAX_COMMIT_NUM)),
+            );
+
+        if let Some(service_id) = service_id {
+            query = query.filter(property::service_id.eq(service_id));
+        } else {
+            query = query.filter(property::service_id.is_null());
+        }
+
+        let (data_type, number_exponent) = match query
+            .select((
+                grid_property_definition::data_type.nullable(),
+                grid_property_definition::number_exponent.nullable(),
+            ))
+            .first::<(Option<String>, Option<i64>)>(self.conn)
+            .map(Some)
+            .or_else(|err| if err == NotFound { Ok(None) } else { Err(err) })
+            .map_err(|err| {
+                TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })? {
+            Some(definition) => definition,
+            None => return Ok(None),
+        };
+
+        let sql = match data_type.as_deref() {
+            Some("Number") => NUMBER_BUCKETS_QUERY,
+            Some("LatLong") => LAT_LONG_BUCKETS_QUERY,
+            _ => "",
+        };
+
+        let buckets = if sql.is_empty() {
+            vec![]
+        } else {
+            let query = sql_query(sql)
+                .bind::<BigInt, _>(bucket_size)
+                .bind::<Text, _>(record_id)
+                .bind::<Text, _>(property_name)
+                .bind::<BigInt, _>(MAX_COMMIT_NUM)
+                .bind::<Nullable<Text>, _>(service_id)
+                .bind::<BigInt, _>(start.unwrap_or(0))
+                .bind::<BigInt, _>(end.unwrap_or(i64::MAX));
+
+            if data_type.as_deref() == Some("Number") {
+                query.load::<NumberBucket>(self.conn).map(|buckets| {
+                    buckets
+                        .into_iter()
+                        .map(|bucket| bucket.into_bucket(number_exponent.unwrap_or(0)))
+                        .collect::<Vec<_>>()
+                })
+            } else {
+                query.load::<LatLongBucket>(self.conn).map(|buckets| {
+                    buckets
+                        .into_iter()
+                        .map(ReportedValueBucket::from)
+                        .collect::<Vec<_>>()
+                })
+            }
+            .map_err(|err| {
+                TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })?
+        };
+
+        Ok(Some(ReportedValueAggregation {
+            record_id: record_id.to_string(),
+            property_name: property_name.to_string(),
+            data_type,
+            number_exponent: number_exponent.unwrap_or(0),
+            bucket_size,
+            buckets,
+        }))
+    }
+}
+
+fn check_bucket_size(bucket_size: i64) -> Result<(), TrackAndTraceStoreError> {
+    if bucket_size <= 0 {
+        return Err(TrackAndTraceStoreError::InvalidArgumentError(
+            InvalidArgumentError::new(
+                "bucket_size".to_string(),
+                format!("must be greater than 0, got {}", bucket_size),
+            ),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,313 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "delete property" operation for the `DieselTrackAndTraceStore`.
+
+use super::TrackAndTraceStoreOperations;
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::InternalError;
+use crate::track_and_trace::store::diesel::{
+    schema::{property, reported_value, reporter},
+    TrackAndTraceStoreError,
+};
+
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::track_and_trace::store::diesel) trait TrackAndTraceStoreDeletePropertyOperation {
+    fn delete_property(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> TrackAndTraceStoreDeletePropertyOperation
+    for TrackAndTraceStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn delete_property(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        self.conn.transaction::<_, TrackAndTraceStoreError, _>(|| {
+            let mut query = property::table
+                .into_boxed()
+                .select((property::id, property::name, property::record_id))
+                .filter(
+                    property::state_address
+                        .eq(address)
+                        .and(property::end_commit_num.eq(MAX_COMMIT_NUM)),
+                );
+
+            if let Some(service_id) = service_id {
+                query = query.filter(property::service_id.eq(service_id));
+            } else {
+                query = query.filter(property::service_id.is_null());
+            }
+
+            let properties = query
+                .load::<(i64, String, String)>(self.conn)
+                .map_err(|err| {
+                    TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+            for (id, name, record_id) in properties {
+                let mut query = reporter::table.into_boxed().select(reporter::id).filter(
+                    reporter::record_id
+                        .eq(&record_id)
+                        .and(reporter::property_name.eq(&name))
+                        .and(reporter::end_commit_num.eq(MAX_COMMIT_NUM)),
+                );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(reporter::service_id.eq(service_id));
+                } else {
+                    query = query.filter(reporter::service_id.is_null());
+                }
+
+                let reporter_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(reporter::table)
+                    .filter(reporter::id.eq_any(reporter_ids))
+                    .set(reporter::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(TrackAndTraceStoreError::from)?;
+
+                // The values reported on the property's pages end along with the property
+                let mut query = reported_value::table
+                    .into_boxed()
+                    .select(reported_value::id)
+                    .filter(
+                        reported_value::record_id
+                            .eq(&record_id)
+                            .and(reported_value::property_name.eq(&name))
+                            .and(reported_value::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(reported_value::service_id.eq(service_id));
+                } else {
+                    query = query.filter(reported_value::service_id.is_null());
+                }
+
+                let reported_value_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(reported_value::table)
+                    .filter(reported_value::id.eq_any(reported_value_ids))
+                    .set(reported_value::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(TrackAndTraceStoreError::from)?;
+
+                update(property::table)
+                    .filter(property::id.eq(id))
+                    .set(property::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(TrackAndTraceStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> TrackAndTraceStoreDeletePropertyOperation
+    for TrackAndTraceStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn delete_property(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        self.conn.transaction::<_, TrackAndTraceStoreError, _>(|| {
+            let mut query = property::table
+                .into_boxed()
+                .select((property::id, property::name, property::record_id))
+                .filter(
+                    property::state_address
+                        .eq(address)
+                        .and(property::end_commit_num.eq(MAX_COMMIT_NUM)),
+                );
+
+            if let Some(service_id) = service_id {
+                query = query.filter(property::service_id.eq(service_id));
+            } else {
+                query = query.filter(property::service_id.is_null());
+            }
+
+            let properties = query
+                .load::<(i64, String, String)>(self.conn)
+                .map_err(|err| {
+                    TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+            for (id, name, record_id) in properties {
+                let mut query = reporter::table.into_boxed().select(reporter::id).filter(
+                    reporter::record_id
+                        .eq(&record_id)
+                        .and(reporter::property_name.eq(&name))
+                        .and(reporter::end_commit_num.eq(MAX_COMMIT_NUM)),
+                );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(reporter::service_id.eq(service_id));
+                } else {
+                    query = query.filter(reporter::service_id.is_null());
+                }
+
+                let reporter_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(reporter::table)
+                    .filter(reporter::id.eq_any(reporter_ids))
+                    .set(reporter::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(TrackAndTraceStoreError::from)?;
+
+                // The values reported on the property's pages end along with the property
+                let mut query = reported_value::table
+                    .into_boxed()
+                    .select(reported_value::id)
+                    .filter(
+                        reported_value::record_id
+                            .eq(&record_id)
+                            .and(reported_value::property_name.eq(&name))
+                            .and(reported_value::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(reported_value::service_id.eq(service_id));
+                } else {
+                    query = query.filter(reported_value::service_id.is_null());
+                }
+
+                let reported_value_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(reported_value::table)
+                    .filter(reported_value::id.eq_any(reported_value_ids))
+                    .set(reported_value::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(TrackAndTraceStoreError::from)?;
+
+                update(property::table)
+                    .filter(property::id.eq(id))
+                    .set(property::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(TrackAndTraceStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+    use crate::track_and_trace::addressing::make_property_address;
+
+    use diesel::{sql_query, sqlite::SqliteConnection};
+
+    /// Verify that deleting a property ends it, its reporters and the values reported on its
+    /// pages at the given commit, leaving the record's other properties untouched.
+    #[test]
+    fn test_delete_property() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        let address = make_property_address("record", "temperature", 0);
+        sql_query(format!(
+            "INSERT INTO property \
+            (name, record_id, property_definition, current_page, wrapped, start_commit_num, \
+            end_commit_num, state_address) VALUES \
+            ('temperature', 'record', 'temperature', 2, 0, 1, 9223372036854775807, '{}'), \
+            ('weight', 'record', 'weight', 1, 0, 1, 9223372036854775807, '{}')",
+            address,
+            make_property_address("record", "weight", 0)
+        ))
+        .execute(&conn)?;
+        sql_query(
+            "INSERT INTO reporter \
+            (property_name, record_id, public_key, authorized, reporter_index, \
+            start_commit_num, end_commit_num) VALUES \
+            ('temperature', 'record', 'agent', 1, 0, 1, 9223372036854775807), \
+            ('weight', 'record', 'agent', 1, 0, 1, 9223372036854775807)",
+        )
+        .execute(&conn)?;
+        sql_query(
+            "INSERT INTO reported_value \
+            (property_name, record_id, reporter_index, timestamp, data_type, number_value, \
+            start_commit_num, end_commit_num) VALUES \
+            ('temperature', 'record', 0, 1, 'Number', 20, 1, 9223372036854775807), \
+            ('temperature', 'record', 0, 2, 'Number', 21, 2, 9223372036854775807), \
+            ('weight', 'record', 0, 1, 'Number', 5, 1, 9223372036854775807)",
+        )
+        .execute(&conn)?;
+
+        TrackAndTraceStoreOperations::new(&conn).delete_property(&address, 5, None)?;
+
+        assert_eq!(
+            property::table
+                .select((property::name, property::end_commit_num))
+                .order(property::id)
+                .load::<(String, i64)>(&conn)?,
+            vec![
+                ("temperature".to_string(), 5),
+                ("weight".to_string(), MAX_COMMIT_NUM)
+            ]
+        );
+        assert_eq!(
+            reporter::table
+                .select(reporter::end_commit_num)
+                .order(reporter::id)
+                .load::<i64>(&conn)?,
+            vec![5, MAX_COMMIT_NUM]
+        );
+        assert_eq!(
+            reported_value::table
+                .select(reported_value::end_commit_num)
+                .order(reported_value::id)
+                .load::<i64>(&conn)?,
+            vec![5, 5, MAX_COMMIT_NUM]
+        );
+
+        Ok(())
+    }
+}
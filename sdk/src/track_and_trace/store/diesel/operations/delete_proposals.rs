@@ -0,0 +1,152 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "delete proposals" operation for the `DieselTrackAndTraceStore`.
+
+use super::TrackAndTraceStoreOperations;
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::InternalError;
+use crate::track_and_trace::store::diesel::{schema::proposal, TrackAndTraceStoreError};
+
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::track_and_trace::store::diesel) trait TrackAndTraceStoreDeleteProposalsOperation {
+    fn delete_proposals(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> TrackAndTraceStoreDeleteProposalsOperation
+    for TrackAndTraceStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn delete_proposals(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        // A proposal address holds every proposal for a record and receiving agent
+        let mut query = proposal::table.into_boxed().select(proposal::id).filter(
+            proposal::state_address
+                .eq(address)
+                .and(proposal::end_commit_num.eq(MAX_COMMIT_NUM)),
+        );
+
+        if let Some(service_id) = service_id {
+            query = query.filter(proposal::service_id.eq(service_id));
+        } else {
+            query = query.filter(proposal::service_id.is_null());
+        }
+
+        let proposal_ids = query.load::<i64>(self.conn).map_err(|err| {
+            TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(err)))
+        })?;
+
+        update(proposal::table)
+            .filter(proposal::id.eq_any(proposal_ids))
+            .set(proposal::end_commit_num.eq(current_commit_num))
+            .execute(self.conn)
+            .map_err(TrackAndTraceStoreError::from)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> TrackAndTraceStoreDeleteProposalsOperation
+    for TrackAndTraceStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn delete_proposals(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        // A proposal address holds every proposal for a record and receiving agent
+        let mut query = proposal::table.into_boxed().select(proposal::id).filter(
+            proposal::state_address
+                .eq(address)
+                .and(proposal::end_commit_num.eq(MAX_COMMIT_NUM)),
+        );
+
+        if let Some(service_id) = service_id {
+            query = query.filter(proposal::service_id.eq(service_id));
+        } else {
+            query = query.filter(proposal::service_id.is_null());
+        }
+
+        let proposal_ids = query.load::<i64>(self.conn).map_err(|err| {
+            TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(err)))
+        })?;
+
+        update(proposal::table)
+            .filter(proposal::id.eq_any(proposal_ids))
+            .set(proposal::end_commit_num.eq(current_commit_num))
+            .execute(self.conn)
+            .map_err(TrackAndTraceStoreError::from)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+    use crate::track_and_trace::addressing::make_proposal_address;
+
+    use diesel::{sql_query, sqlite::SqliteConnection};
+
+    /// Verify that deleting the proposals at an address ends every proposal for that record and
+    /// receiving agent at the given commit, leaving proposals to other agents untouched.
+    #[test]
+    fn test_delete_proposals() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        let address = make_proposal_address("record", "agent");
+        sql_query(format!(
+            "INSERT INTO proposal \
+            (record_id, timestamp, issuing_agent, receiving_agent, role, properties, status, \
+            terms, start_commit_num, end_commit_num, state_address) VALUES \
+            ('record', 1, 'owner', 'agent', 'OWNER', '', 'OPEN', '', 1, 9223372036854775807, \
+            '{0}'), \
+            ('record', 2, 'owner', 'agent', 'CUSTODIAN', '', 'OPEN', '', 1, \
+            9223372036854775807, '{0}'), \
+            ('record', 1, 'owner', 'other', 'OWNER', '', 'OPEN', '', 1, 9223372036854775807, \
+            '{1}')",
+            address,
+            make_proposal_address("record", "other")
+        ))
+        .execute(&conn)?;
+
+        TrackAndTraceStoreOperations::new(&conn).delete_proposals(&address, 5, None)?;
+
+        assert_eq!(
+            proposal::table
+                .select(proposal::end_commit_num)
+                .order(proposal::id)
+                .load::<i64>(&conn)?,
+            vec![5, 5, MAX_COMMIT_NUM]
+        );
+
+        Ok(())
+    }
+}
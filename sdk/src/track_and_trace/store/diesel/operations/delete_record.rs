@@ -0,0 +1,232 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "delete record" operation for the `DieselTrackAndTraceStore`.
+
+use super::TrackAndTraceStoreOperations;
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::InternalError;
+use crate::track_and_trace::store::diesel::{
+    schema::{associated_agent, record},
+    TrackAndTraceStoreError,
+};
+
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::track_and_trace::store::diesel) trait TrackAndTraceStoreDeleteRecordOperation {
+    fn delete_record(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> TrackAndTraceStoreDeleteRecordOperation
+    for TrackAndTraceStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn delete_record(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        self.conn.transaction::<_, TrackAndTraceStoreError, _>(|| {
+            let mut query = record::table
+                .into_boxed()
+                .select((record::id, record::record_id))
+                .filter(
+                    record::state_address
+                        .eq(address)
+                        .and(record::end_commit_num.eq(MAX_COMMIT_NUM)),
+                );
+
+            if let Some(service_id) = service_id {
+                query = query.filter(record::service_id.eq(service_id));
+            } else {
+                query = query.filter(record::service_id.is_null());
+            }
+
+            let records = query.load::<(i64, String)>(self.conn).map_err(|err| {
+                TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })?;
+
+            for (id, record_id) in records {
+                let mut query = associated_agent::table
+                    .into_boxed()
+                    .select(associated_agent::id)
+                    .filter(
+                        associated_agent::record_id
+                            .eq(&record_id)
+                            .and(associated_agent::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(associated_agent::service_id.eq(service_id));
+                } else {
+                    query = query.filter(associated_agent::service_id.is_null());
+                }
+
+                let associated_agent_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(associated_agent::table)
+                    .filter(associated_agent::id.eq_any(associated_agent_ids))
+                    .set(associated_agent::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(TrackAndTraceStoreError::from)?;
+
+                update(record::table)
+                    .filter(record::id.eq(id))
+                    .set(record::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(TrackAndTraceStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> TrackAndTraceStoreDeleteRecordOperation
+    for TrackAndTraceStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn delete_record(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        self.conn.transaction::<_, TrackAndTraceStoreError, _>(|| {
+            let mut query = record::table
+                .into_boxed()
+                .select((record::id, record::record_id))
+                .filter(
+                    record::state_address
+                        .eq(address)
+                        .and(record::end_commit_num.eq(MAX_COMMIT_NUM)),
+                );
+
+            if let Some(service_id) = service_id {
+                query = query.filter(record::service_id.eq(service_id));
+            } else {
+                query = query.filter(record::service_id.is_null());
+            }
+
+            let records = query.load::<(i64, String)>(self.conn).map_err(|err| {
+                TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })?;
+
+            for (id, record_id) in records {
+                let mut query = associated_agent::table
+                    .into_boxed()
+                    .select(associated_agent::id)
+                    .filter(
+                        associated_agent::record_id
+                            .eq(&record_id)
+                            .and(associated_agent::end_commit_num.eq(MAX_COMMIT_NUM)),
+                    );
+
+                if let Some(service_id) = service_id {
+                    query = query.filter(associated_agent::service_id.eq(service_id));
+                } else {
+                    query = query.filter(associated_agent::service_id.is_null());
+                }
+
+                let associated_agent_ids = query.load::<i64>(self.conn).map_err(|err| {
+                    TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(
+                        err,
+                    )))
+                })?;
+
+                update(associated_agent::table)
+                    .filter(associated_agent::id.eq_any(associated_agent_ids))
+                    .set(associated_agent::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(TrackAndTraceStoreError::from)?;
+
+                update(record::table)
+                    .filter(record::id.eq(id))
+                    .set(record::end_commit_num.eq(current_commit_num))
+                    .execute(self.conn)
+                    .map_err(TrackAndTraceStoreError::from)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+    use crate::track_and_trace::addressing::make_record_address;
+
+    use diesel::{sql_query, sqlite::SqliteConnection};
+
+    /// Verify that deleting a record ends it and its associated agents at the given commit,
+    /// leaving the record stored at the same address for another service untouched.
+    #[test]
+    fn test_delete_record() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = SqliteConnection::establish(":memory:")?;
+
+        run_sqlite_migrations(&conn)?;
+
+        let address = make_record_address("record");
+        sql_query(format!(
+            "INSERT INTO record \
+            (record_id, schema, final, owners, custodians, start_commit_num, end_commit_num, \
+            service_id, state_address) VALUES \
+            ('record', 'schema', 0, 'agent', 'agent', 1, 9223372036854775807, NULL, '{0}'), \
+            ('record', 'schema', 0, 'agent', 'agent', 1, 9223372036854775807, 'service', '{0}')",
+            address
+        ))
+        .execute(&conn)?;
+        sql_query(
+            "INSERT INTO associated_agent \
+            (record_id, role, agent_id, timestamp, start_commit_num, end_commit_num, \
+            service_id) VALUES \
+            ('record', 'OWNER', 'agent', 1, 1, 9223372036854775807, NULL), \
+            ('record', 'CUSTODIAN', 'agent', 1, 1, 9223372036854775807, NULL), \
+            ('record', 'OWNER', 'agent', 1, 1, 9223372036854775807, 'service')",
+        )
+        .execute(&conn)?;
+
+        TrackAndTraceStoreOperations::new(&conn).delete_record(&address, 5, None)?;
+
+        assert_eq!(
+            record::table
+                .select((record::service_id, record::end_commit_num))
+                .order(record::id)
+                .load::<(Option<String>, i64)>(&conn)?,
+            vec![(None, 5), (Some("service".to_string()), MAX_COMMIT_NUM)]
+        );
+        assert_eq!(
+            associated_agent::table
+                .select(associated_agent::end_commit_num)
+                .order(associated_agent::id)
+                .load::<i64>(&conn)?,
+            vec![5, 5, MAX_COMMIT_NUM]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright 2022 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::TrackAndTraceStoreOperations;
+use crate::track_and_trace::store::diesel::{schema::alert, TrackAndTraceStoreError};
+
+use crate::commits::MAX_COMMIT_NUM;
+use crate::error::InternalError;
+use crate::track_and_trace::store::diesel::models::AlertModel;
+use crate::track_and_trace::store::Alert;
+
+use diesel::prelude::*;
+
+pub(in crate::track_and_trace::store::diesel) trait TrackAndTraceStoreListAlertsOperation {
+    fn list_alerts(
+        &self,
+        record_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> TrackAndTraceStoreListAlertsOperation
+    for TrackAndTraceStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn list_alerts(
+        &self,
+        record_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        let mut query = alert::table.into_boxed().select(alert::all_columns).filter(
+            alert::record_id
+                .eq(record_id)
+                .and(alert::end_commit_num.eq(MAX_COMMIT_NUM)),
+        );
+
+        if let Some(service_id) = service_id {
+            query = query.filter(alert::service_id.eq(service_id));
+        } else {
+            query = query.filter(alert::service_id.is_null());
+        }
+
+        let models = query
+            .order(alert::started_at.desc())
+            .load::<AlertModel>(self.conn)
+            .map_err(|err| {
+                TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })?;
+
+        Ok(models.into_iter().map(Alert::from).collect())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> TrackAndTraceStoreListAlertsOperation
+    for TrackAndTraceStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn list_alerts(
+        &self,
+        record_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        let mut query = alert::table.into_boxed().select(alert::all_columns).filter(
+            alert::record_id
+                .eq(record_id)
+                .and(alert::end_commit_num.eq(MAX_COMMIT_NUM)),
+        );
+
+        if let Some(service_id) = service_id {
+            query = query.filter(alert::service_id.eq(service_id));
+        } else {
+            query = query.filter(alert::service_id.is_null());
+        }
+
+        let models = query
+            .order(alert::started_at.desc())
+            .load::<AlertModel>(self.conn)
+            .map_err(|err| {
+                TrackAndTraceStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            })?;
+
+        Ok(models.into_iter().map(Alert::from).collect())
+    }
+}
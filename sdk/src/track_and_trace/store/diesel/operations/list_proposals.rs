@@ -18,7 +18,7 @@ use crate::track_and_trace::store::diesel::{schema::proposal, TrackAndTraceStore
 use crate::commits::MAX_COMMIT_NUM;
 use crate::error::InternalError;
 use crate::track_and_trace::store::diesel::models::ProposalModel;
-use crate::track_and_trace::store::Proposal;
+use crate::track_and_trace::store::{ListProposalFilters, Proposal};
 
 use diesel::prelude::*;
 
@@ -26,6 +26,7 @@ pub(in crate::track_and_trace::store::diesel) trait TrackAndTraceStoreListPropos
     fn list_proposals(
         &self,
         record_ids: &[String],
+        filters: ListProposalFilters,
         service_id: Option<&str>,
     ) -> Result<Vec<Proposal>, TrackAndTraceStoreError>;
 }
@@ -37,6 +38,7 @@ impl<'a> TrackAndTraceStoreListProposalsOperation
     fn list_proposals(
         &self,
         record_ids: &[String],
+        filters: ListProposalFilters,
         service_id: Option<&str>,
     ) -> Result<Vec<Proposal>, TrackAndTraceStoreError> {
         let mut query = proposal::table
@@ -48,6 +50,29 @@ impl<'a> TrackAndTraceStoreListProposalsOperation
                     .and(proposal::record_id.eq_any(record_ids)),
             );
 
+        let ListProposalFilters {
+            status,
+            expired_as_of,
+            active_as_of,
+        } = filters;
+
+        if let Some(status) = status {
+            query = query.filter(proposal::status.eq(status));
+        }
+
+        if let Some(expired_as_of) = expired_as_of {
+            query = query.filter(proposal::expires_at.le(expired_as_of));
+        }
+
+        if let Some(active_as_of) = active_as_of {
+            query = query.filter(
+                proposal::expires_at
+                    .is_null()
+                    .nullable()
+                    .or(proposal::expires_at.gt(active_as_of)),
+            );
+        }
+
         if let Some(service_id) = service_id {
             query = query.filter(proposal::service_id.eq(service_id));
         } else {
@@ -77,6 +102,7 @@ impl<'a> TrackAndTraceStoreListProposalsOperation
     fn list_proposals(
         &self,
         record_ids: &[String],
+        filters: ListProposalFilters,
         service_id: Option<&str>,
     ) -> Result<Vec<Proposal>, TrackAndTraceStoreError> {
         let mut query = proposal::table
@@ -88,6 +114,29 @@ impl<'a> TrackAndTraceStoreListProposalsOperation
                     .and(proposal::record_id.eq_any(record_ids)),
             );
 
+        let ListProposalFilters {
+            status,
+            expired_as_of,
+            active_as_of,
+        } = filters;
+
+        if let Some(status) = status {
+            query = query.filter(proposal::status.eq(status));
+        }
+
+        if let Some(expired_as_of) = expired_as_of {
+            query = query.filter(proposal::expires_at.le(expired_as_of));
+        }
+
+        if let Some(active_as_of) = active_as_of {
+            query = query.filter(
+                proposal::expires_at
+                    .is_null()
+                    .nullable()
+                    .or(proposal::expires_at.gt(active_as_of)),
+            );
+        }
+
         if let Some(service_id) = service_id {
             query = query.filter(proposal::service_id.eq(service_id));
         } else {
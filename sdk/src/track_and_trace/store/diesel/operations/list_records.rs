@@ -19,7 +19,7 @@ use crate::commits::MAX_COMMIT_NUM;
 use crate::error::InternalError;
 use crate::paging::Paging;
 use crate::track_and_trace::store::diesel::models::RecordModel;
-use crate::track_and_trace::store::{Record, RecordList};
+use crate::track_and_trace::store::{ListRecordFilters, Record, RecordList};
 
 use diesel::prelude::*;
 use std::convert::TryInto;
@@ -27,6 +27,7 @@ use std::convert::TryInto;
 pub(in crate::track_and_trace::store::diesel) trait TrackAndTraceStoreListRecordsOperation {
     fn list_records(
         &self,
+        filters: ListRecordFilters,
         service_id: Option<&str>,
         offset: i64,
         limit: i64,
@@ -39,6 +40,7 @@ impl<'a> TrackAndTraceStoreListRecordsOperation
 {
     fn list_records(
         &self,
+        filters: ListRecordFilters,
         service_id: Option<&str>,
         offset: i64,
         limit: i64,
@@ -50,6 +52,19 @@ impl<'a> TrackAndTraceStoreListRecordsOperation
             .limit(limit)
             .filter(record::end_commit_num.eq(MAX_COMMIT_NUM));
 
+        let ListRecordFilters {
+            product_id,
+            location_id,
+        } = filters;
+
+        if let Some(product_id) = product_id {
+            query = query.filter(record::product_id.eq(product_id));
+        }
+
+        if let Some(location_id) = location_id {
+            query = query.filter(record::location_id.eq(location_id));
+        }
+
         if let Some(service_id) = service_id {
             query = query.filter(record::service_id.eq(service_id));
         } else {
@@ -87,6 +102,7 @@ impl<'a> TrackAndTraceStoreListRecordsOperation
 {
     fn list_records(
         &self,
+        filters: ListRecordFilters,
         service_id: Option<&str>,
         offset: i64,
         limit: i64,
@@ -98,6 +114,19 @@ impl<'a> TrackAndTraceStoreListRecordsOperation
             .limit(limit)
             .filter(record::end_commit_num.eq(MAX_COMMIT_NUM));
 
+        let ListRecordFilters {
+            product_id,
+            location_id,
+        } = filters;
+
+        if let Some(product_id) = product_id {
+            query = query.filter(record::product_id.eq(product_id));
+        }
+
+        if let Some(location_id) = location_id {
+            query = query.filter(record::location_id.eq(location_id));
+        }
+
         if let Some(service_id) = service_id {
             query = query.filter(record::service_id.eq(service_id));
         } else {
@@ -12,15 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub(super) mod add_alerts;
 pub(super) mod add_associated_agents;
 pub(super) mod add_properties;
 pub(super) mod add_proposals;
 pub(super) mod add_records;
 pub(super) mod add_reported_values;
 pub(super) mod add_reporters;
+pub(super) mod aggregate_reported_values;
+pub(super) mod delete_property;
+pub(super) mod delete_proposals;
+pub(super) mod delete_record;
+pub(super) mod get_open_alert;
 pub(super) mod get_property_with_data_type;
 pub(super) mod get_record;
 pub(super) mod get_reported_value_reporter_to_agent_metadata;
+pub(super) mod list_alerts;
+pub(super) mod list_alerts_by_status;
 pub(super) mod list_associated_agents;
 pub(super) mod list_properties_with_data_type;
 pub(super) mod list_proposals;
@@ -12,6 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+table! {
+    alert (id) {
+        id -> Int8,
+        rule_name -> Text,
+        record_id -> Text,
+        property_name -> Text,
+        status -> Text,
+        started_at -> Int8,
+        triggered_at -> Nullable<Int8>,
+        resolved_at -> Nullable<Int8>,
+        start_commit_num -> Int8,
+        end_commit_num -> Int8,
+        service_id -> Nullable<Text>,
+    }
+}
+
 table! {
     associated_agent (id) {
         id -> Int8,
@@ -53,6 +69,7 @@ table! {
         start_commit_num -> Int8,
         end_commit_num -> Int8,
         service_id -> Nullable<Text>,
+        state_address -> Text,
     }
 }
 
@@ -70,6 +87,8 @@ table! {
         start_commit_num -> Int8,
         end_commit_num -> Int8,
         service_id -> Nullable<Text>,
+        expires_at -> Nullable<Int8>,
+        state_address -> Text,
     }
 }
 
@@ -85,6 +104,9 @@ table! {
         start_commit_num -> Int8,
         end_commit_num -> Int8,
         service_id -> Nullable<Text>,
+        product_id -> Nullable<Text>,
+        location_id -> Nullable<Text>,
+        state_address -> Text,
     }
 }
 
@@ -151,6 +173,7 @@ table! {
 }
 
 allow_tables_to_appear_in_same_query!(
+    alert,
     associated_agent,
     grid_property_definition,
     property,
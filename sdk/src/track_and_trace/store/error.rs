@@ -15,13 +15,17 @@
 use std::error::Error;
 use std::fmt;
 
-use crate::error::{ConstraintViolationError, InternalError, ResourceTemporarilyUnavailableError};
+use crate::error::{
+    ConstraintViolationError, InternalError, InvalidArgumentError,
+    ResourceTemporarilyUnavailableError,
+};
 
 /// Represents TrackAndTraceStore errors
 #[derive(Debug)]
 pub enum TrackAndTraceStoreError {
     InternalError(InternalError),
     ConstraintViolationError(ConstraintViolationError),
+    InvalidArgumentError(InvalidArgumentError),
     ResourceTemporarilyUnavailableError(ResourceTemporarilyUnavailableError),
     NotFoundError(String),
 }
@@ -31,6 +35,7 @@ impl Error for TrackAndTraceStoreError {
         match self {
             TrackAndTraceStoreError::InternalError(err) => Some(err),
             TrackAndTraceStoreError::ConstraintViolationError(err) => Some(err),
+            TrackAndTraceStoreError::InvalidArgumentError(err) => Some(err),
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(err) => Some(err),
             TrackAndTraceStoreError::NotFoundError(_) => None,
         }
@@ -42,6 +47,7 @@ impl fmt::Display for TrackAndTraceStoreError {
         match self {
             TrackAndTraceStoreError::InternalError(err) => err.fmt(f),
             TrackAndTraceStoreError::ConstraintViolationError(err) => err.fmt(f),
+            TrackAndTraceStoreError::InvalidArgumentError(err) => err.fmt(f),
             TrackAndTraceStoreError::ResourceTemporarilyUnavailableError(err) => err.fmt(f),
             TrackAndTraceStoreError::NotFoundError(ref s) => write!(f, "Element not found: {}", s),
         }
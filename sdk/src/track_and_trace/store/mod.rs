@@ -22,6 +22,21 @@ use crate::paging::Paging;
 pub use self::diesel::{DieselConnectionTrackAndTraceStore, DieselTrackAndTraceStore};
 pub use error::TrackAndTraceStoreError;
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: Option<i64>,
+    pub rule_name: String,
+    pub record_id: String,
+    pub property_name: String,
+    pub status: String,
+    pub started_at: i64,
+    pub triggered_at: Option<i64>,
+    pub resolved_at: Option<i64>,
+    pub start_commit_num: i64,
+    pub end_commit_num: i64,
+    pub service_id: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssociatedAgent {
     pub id: Option<i64>,
@@ -61,6 +76,17 @@ pub struct Proposal {
     pub start_commit_num: i64,
     pub end_commit_num: i64,
     pub service_id: Option<String>,
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ListProposalFilters {
+    /// Only include proposals with this status
+    pub status: Option<String>,
+    /// Only include proposals whose expiry time is at or before this timestamp
+    pub expired_as_of: Option<i64>,
+    /// Only include proposals that have no expiry time, or expire after this timestamp
+    pub active_as_of: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +100,16 @@ pub struct Record {
     pub start_commit_num: i64,
     pub end_commit_num: i64,
     pub service_id: Option<String>,
+    pub product_id: Option<String>,
+    pub location_id: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ListRecordFilters {
+    // GTIN of the product the records are linked to
+    pub product_id: Option<String>,
+    // GLN of the location the records are linked to
+    pub location_id: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -150,7 +186,51 @@ pub struct LatLong;
 #[derive(Default, Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct LatLongValue(pub i64, pub i64);
 
+/// The aggregated reported values of a property, split into fixed size time buckets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportedValueAggregation {
+    pub record_id: String,
+    pub property_name: String,
+    pub data_type: Option<String>,
+    /// Exponent of the property's Number values, which the Number aggregates have already been
+    /// scaled by
+    pub number_exponent: i64,
+    pub bucket_size: i64,
+    pub buckets: Vec<ReportedValueBucket>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportedValueBucket {
+    /// Start of the bucket, inclusive. The bucket ends at bucket_start + bucket_size, exclusive.
+    pub bucket_start: i64,
+    pub value_count: i64,
+    pub last_timestamp: i64,
+    pub aggregate: ReportedValueAggregate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ReportedValueAggregate {
+    /// Number values multiplied by 10^number_exponent of the property, giving the real values
+    Number {
+        min: f64,
+        max: f64,
+        avg: f64,
+        last: f64,
+    },
+    LatLong {
+        last: LatLongValue,
+    },
+}
+
 pub trait TrackAndTraceStore {
+    /// Adds alerts to the underlying storage, replacing the current version of any alert with
+    /// the same rule, record, property and start time
+    ///
+    /// # Arguments
+    ///
+    ///  * `alerts` - The alerts to be added
+    fn add_alerts(&self, alerts: Vec<Alert>) -> Result<(), TrackAndTraceStoreError>;
+
     /// Adds an associated agent to the underlying storage
     ///
     /// # Arguments
@@ -199,6 +279,46 @@ pub trait TrackAndTraceStore {
     ///  * `reporters` - The reporters to be added
     fn add_reporters(&self, reporters: Vec<Reporter>) -> Result<(), TrackAndTraceStoreError>;
 
+    /// Fetches the reported values of a Number or LatLong property from the underlying storage,
+    /// aggregated into time buckets. Number values are summarized by their min, max, average
+    /// and last value per bucket, LatLong values by their last position per bucket. Returns
+    /// `None` if the property does not exist.
+    ///
+    /// # Arguments
+    ///
+    ///  * `record_id` - The record ID to fetch for
+    ///  * `property_name` - The property name to fetch for
+    ///  * `bucket_size` - The size of each bucket, in the units of the reported timestamps
+    ///  * `start` - Only include values reported at or after this timestamp
+    ///  * `end` - Only include values reported before this timestamp
+    ///  * `service_id` - The service ID to fetch for
+    fn aggregate_reported_values(
+        &self,
+        record_id: &str,
+        property_name: &str,
+        bucket_size: i64,
+        start: Option<i64>,
+        end: Option<i64>,
+        service_id: Option<&str>,
+    ) -> Result<Option<ReportedValueAggregation>, TrackAndTraceStoreError>;
+
+    /// Fetches the alert for a rule, record and property that has not been resolved from the
+    /// underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `rule_name` - The name of the rule that raised the alert
+    ///  * `record_id` - The record ID to fetch for
+    ///  * `property_name` - The property name to fetch for
+    ///  * `service_id` - The service ID to fetch for
+    fn get_open_alert(
+        &self,
+        rule_name: &str,
+        record_id: &str,
+        property_name: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Alert>, TrackAndTraceStoreError>;
+
     /// Fetches a property and its data type from the underlying storage
     ///
     /// # Arguments
@@ -241,6 +361,31 @@ pub trait TrackAndTraceStore {
         service_id: Option<&str>,
     ) -> Result<Option<ReportedValueReporterToAgentMetadata>, TrackAndTraceStoreError>;
 
+    /// Fetches a list of alerts from the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `record_id` - The record ID to fetch for
+    ///  * `service_id` - The service ID to fetch for
+    fn list_alerts(
+        &self,
+        record_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError>;
+
+    /// Fetches the current alerts with the given status from the underlying storage, oldest
+    /// first
+    ///
+    /// # Arguments
+    ///
+    ///  * `status` - The alert status to fetch for
+    ///  * `service_id` - The service ID to fetch for
+    fn list_alerts_by_status(
+        &self,
+        status: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError>;
+
     /// Fetches a list of associated agents from the underlying storage
     ///
     /// # Arguments
@@ -270,10 +415,12 @@ pub trait TrackAndTraceStore {
     /// # Arguments
     ///
     ///  * `record_ids` - The list of record IDs to fetch for
+    ///  * `filters` - Filters on proposal status and expiry
     ///  * `service_id` - The service ID to fetch for
     fn list_proposals(
         &self,
         record_ids: &[String],
+        filters: ListProposalFilters,
         service_id: Option<&str>,
     ) -> Result<Vec<Proposal>, TrackAndTraceStoreError>;
 
@@ -281,11 +428,13 @@ pub trait TrackAndTraceStore {
     ///
     /// # Arguments
     ///
+    ///  * `filters` - Filters on the product and location records are linked to
     ///  * `service_id` - The service ID to fetch for
     ///  * `offset` - The index of the first in storage to retrieve
     ///  * `limit` - The number of items to retrieve from the offset
     fn list_records(
         &self,
+        filters: ListRecordFilters,
         service_id: Option<&str>,
         offset: i64,
         limit: i64,
@@ -319,12 +468,59 @@ pub trait TrackAndTraceStore {
         property_name: &str,
         service_id: Option<&str>,
     ) -> Result<Vec<Reporter>, TrackAndTraceStoreError>;
+
+    /// Deletes a record and its associated agents from the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the record to delete
+    ///  * `current_commit_num` - The current commit number to update the chain record
+    ///  * `service_id` - The service ID of the record to delete
+    fn delete_record(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError>;
+
+    /// Deletes a property, its reporters and the values reported on its pages from the
+    /// underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the property to delete
+    ///  * `current_commit_num` - The current commit number to update the chain record
+    ///  * `service_id` - The service ID of the property to delete
+    fn delete_property(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError>;
+
+    /// Deletes the proposals stored at an address from the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `address` - The state address of the proposals to delete
+    ///  * `current_commit_num` - The current commit number to update the chain record
+    ///  * `service_id` - The service ID of the proposals to delete
+    fn delete_proposals(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError>;
 }
 
 impl<TS> TrackAndTraceStore for Box<TS>
 where
     TS: TrackAndTraceStore + ?Sized,
 {
+    fn add_alerts(&self, alerts: Vec<Alert>) -> Result<(), TrackAndTraceStoreError> {
+        (**self).add_alerts(alerts)
+    }
+
     fn add_associated_agents(
         &self,
         agents: Vec<AssociatedAgent>,
@@ -355,6 +551,35 @@ where
         (**self).add_reporters(reporters)
     }
 
+    fn aggregate_reported_values(
+        &self,
+        record_id: &str,
+        property_name: &str,
+        bucket_size: i64,
+        start: Option<i64>,
+        end: Option<i64>,
+        service_id: Option<&str>,
+    ) -> Result<Option<ReportedValueAggregation>, TrackAndTraceStoreError> {
+        (**self).aggregate_reported_values(
+            record_id,
+            property_name,
+            bucket_size,
+            start,
+            end,
+            service_id,
+        )
+    }
+
+    fn get_open_alert(
+        &self,
+        rule_name: &str,
+        record_id: &str,
+        property_name: &str,
+        service_id: Option<&str>,
+    ) -> Result<Option<Alert>, TrackAndTraceStoreError> {
+        (**self).get_open_alert(rule_name, record_id, property_name, service_id)
+    }
+
     fn get_property_with_data_type(
         &self,
         record_id: &str,
@@ -387,6 +612,22 @@ where
         )
     }
 
+    fn list_alerts(
+        &self,
+        record_id: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        (**self).list_alerts(record_id, service_id)
+    }
+
+    fn list_alerts_by_status(
+        &self,
+        status: &str,
+        service_id: Option<&str>,
+    ) -> Result<Vec<Alert>, TrackAndTraceStoreError> {
+        (**self).list_alerts_by_status(status, service_id)
+    }
+
     fn list_associated_agents(
         &self,
         record_ids: &[String],
@@ -406,18 +647,20 @@ where
     fn list_proposals(
         &self,
         record_ids: &[String],
+        filters: ListProposalFilters,
         service_id: Option<&str>,
     ) -> Result<Vec<Proposal>, TrackAndTraceStoreError> {
-        (**self).list_proposals(record_ids, service_id)
+        (**self).list_proposals(record_ids, filters, service_id)
     }
 
     fn list_records(
         &self,
+        filters: ListRecordFilters,
         service_id: Option<&str>,
         offset: i64,
         limit: i64,
     ) -> Result<RecordList, TrackAndTraceStoreError> {
-        (**self).list_records(service_id, offset, limit)
+        (**self).list_records(filters, service_id, offset, limit)
     }
 
     fn list_reported_value_reporter_to_agent_metadata(
@@ -441,4 +684,31 @@ where
     ) -> Result<Vec<Reporter>, TrackAndTraceStoreError> {
         (**self).list_reporters(record_id, property_name, service_id)
     }
+
+    fn delete_record(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        (**self).delete_record(address, current_commit_num, service_id)
+    }
+
+    fn delete_property(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        (**self).delete_property(address, current_commit_num, service_id)
+    }
+
+    fn delete_proposals(
+        &self,
+        address: &str,
+        current_commit_num: i64,
+        service_id: Option<&str>,
+    ) -> Result<(), TrackAndTraceStoreError> {
+        (**self).delete_proposals(address, current_commit_num, service_id)
+    }
 }